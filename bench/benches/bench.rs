@@ -8,8 +8,8 @@ use criterion::{
     criterion_group, criterion_main,
 };
 use filerune_fusion::{
-    check::Check,
-    merge::Merge,
+    check::{Check, CheckReport},
+    merge::{Merge, MergeResult},
     split::{Split, SplitResult},
 };
 use tokio::runtime::Runtime;
@@ -44,7 +44,7 @@ fn pre_split<R: Into<String>>(runtime: R) -> SplitResult {
 
     Split::new()
         .in_file(&configs.in_file)
-        .out_dir(&configs.cache_dir.join("-1"))
+        .out_dir(configs.cache_dir.join("-1"))
         .run()
         .unwrap()
 }
@@ -192,8 +192,8 @@ fn bench_check(c: &mut Criterion) {
         let configs: Configs = get_configs(RUNTIME_STD);
 
         b.iter(|| {
-            let result: () = Check::new()
-                .in_dir(&configs.cache_dir.join("0"))
+            let result: CheckReport = Check::new()
+                .in_dir(configs.cache_dir.join("0"))
                 .file_size(split.file_size)
                 .total_chunks(split.total_chunks)
                 .run()
@@ -210,8 +210,8 @@ fn bench_check(c: &mut Criterion) {
         let configs: Configs = get_configs(RUNTIME_ASYNC_STD);
 
         b.to_async(AsyncStdExecutor).iter(async || {
-            let result: () = Check::new()
-                .in_dir(&configs.cache_dir.join("0"))
+            let result: CheckReport = Check::new()
+                .in_dir(configs.cache_dir.join("0"))
                 .file_size(split.file_size)
                 .total_chunks(split.total_chunks)
                 .run_async()
@@ -229,8 +229,8 @@ fn bench_check(c: &mut Criterion) {
         let configs: Configs = get_configs(RUNTIME_SMOL);
 
         b.to_async(SmolExecutor).iter(async || {
-            let result: () = Check::new()
-                .in_dir(&configs.cache_dir.join("0"))
+            let result: CheckReport = Check::new()
+                .in_dir(configs.cache_dir.join("0"))
                 .file_size(split.file_size)
                 .total_chunks(split.total_chunks)
                 .run_async()
@@ -253,8 +253,8 @@ fn bench_check(c: &mut Criterion) {
             .unwrap();
 
         b.to_async(runtime).iter(async || {
-            let result: () = Check::new()
-                .in_dir(&configs.cache_dir.join("0"))
+            let result: CheckReport = Check::new()
+                .in_dir(configs.cache_dir.join("0"))
                 .file_size(split.file_size)
                 .total_chunks(split.total_chunks)
                 .run_async()
@@ -286,8 +286,8 @@ fn bench_merge(c: &mut Criterion) {
         b.iter(|| {
             let out_file: PathBuf = configs.out_dir.join(format!("{}.jpg", i));
 
-            let result: () = Merge::new()
-                .in_dir(&configs.cache_dir.join("0"))
+            let result: MergeResult = Merge::new()
+                .in_dir(configs.cache_dir.join("0"))
                 .out_file(out_file)
                 .run()
                 .unwrap();
@@ -320,8 +320,8 @@ fn bench_merge(c: &mut Criterion) {
             let out_file: PathBuf =
                 configs.out_dir.join(format!("{}.jpg", idx));
 
-            let result: () = Merge::new()
-                .in_dir(&configs.cache_dir.join("0"))
+            let result: MergeResult = Merge::new()
+                .in_dir(configs.cache_dir.join("0"))
                 .out_file(out_file)
                 .run_async()
                 .await
@@ -353,8 +353,8 @@ fn bench_merge(c: &mut Criterion) {
             let out_file: PathBuf =
                 configs.out_dir.join(format!("{}.jpg", idx));
 
-            let result: () = Merge::new()
-                .in_dir(&configs.cache_dir.join("0"))
+            let result: MergeResult = Merge::new()
+                .in_dir(configs.cache_dir.join("0"))
                 .out_file(out_file)
                 .run_async()
                 .await
@@ -391,8 +391,8 @@ fn bench_merge(c: &mut Criterion) {
             let out_file: PathBuf =
                 configs.out_dir.join(format!("{}.jpg", idx));
 
-            let result: () = Merge::new()
-                .in_dir(&configs.cache_dir.join("0"))
+            let result: MergeResult = Merge::new()
+                .in_dir(configs.cache_dir.join("0"))
                 .out_file(out_file)
                 .run_async()
                 .await
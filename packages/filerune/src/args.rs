@@ -0,0 +1,203 @@
+use std::{array, ffi::OsString, path::PathBuf, str::FromStr, vec};
+
+use crate::error::CliError;
+
+/// Argument of the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    /// An option as written, such as `--chunk-size` or `-s`.
+    Option(String),
+    /// A positional argument.
+    Value(OsString),
+}
+
+/// Parser of the arguments of the command line.
+///
+/// The options are written as `--name value`, `--name=value` or
+/// `-n value`, and the arguments after `--` are positional.
+///
+/// ## Example
+///
+/// ```
+/// use filerune::args::{Arg, Parser};
+///
+/// let mut parser: Parser = Parser::new(["--chunk-size=4M", "file"]);
+///
+/// assert_eq!(
+///     parser.next_arg().unwrap(),
+///     Some(Arg::Option("--chunk-size".to_string()))
+/// );
+/// assert_eq!(parser.size::<u64>("--chunk-size").unwrap(), 4 * 1024 * 1024);
+/// assert_eq!(parser.next_arg().unwrap(), Some(Arg::Value("file".into())));
+/// assert_eq!(parser.next_arg().unwrap(), None);
+/// ```
+#[derive(Debug)]
+pub struct Parser {
+    args: vec::IntoIter<OsString>,
+    // the option written with `=` and its value
+    attached: Option<(String, OsString)>,
+    // whether the rest of the arguments are positional
+    positional: bool,
+}
+
+impl Parser {
+    /// Create a new parser of the arguments, without the program name.
+    pub fn new<I: IntoIterator<Item = A>, A: Into<OsString>>(args: I) -> Self {
+        Self {
+            args: args
+                .into_iter()
+                .map(Into::into)
+                .collect::<Vec<OsString>>()
+                .into_iter(),
+            attached: None,
+            positional: false,
+        }
+    }
+
+    /// Get the next argument, `None` at the end.
+    ///
+    /// Fails if the value of the previous option written with `=`
+    /// was not taken.
+    pub fn next_arg(&mut self) -> Result<Option<Arg>, CliError> {
+        if let Some((option, _)) = self.attached.take() {
+            return Err(CliError::OptionValueUnexpected(option));
+        }
+
+        let arg: OsString = match self.args.next() {
+            | Some(arg) => arg,
+            | None => return Ok(None),
+        };
+
+        let text: &str = match arg.to_str() {
+            | Some(text) if !self.positional => text,
+            | _ => return Ok(Some(Arg::Value(arg))),
+        };
+
+        if text == "--" {
+            self.positional = true;
+            return self.next_arg();
+        }
+
+        if !text.starts_with('-') || text == "-" {
+            return Ok(Some(Arg::Value(arg)));
+        }
+
+        match text.split_once('=').filter(|_| text.starts_with("--")) {
+            | Some((option, value)) => {
+                self.attached =
+                    Some((option.to_string(), OsString::from(value)));
+
+                Ok(Some(Arg::Option(option.to_string())))
+            },
+            | None => Ok(Some(Arg::Option(text.to_string()))),
+        }
+    }
+
+    /// Get the value of an option.
+    pub fn value(
+        &mut self,
+        option: &str,
+    ) -> Result<OsString, CliError> {
+        if let Some((_, value)) = self.attached.take() {
+            return Ok(value);
+        }
+
+        self.args
+            .next()
+            .ok_or_else(|| CliError::OptionValueNotSet(option.to_string()))
+    }
+
+    /// Get the value of an option as a string.
+    pub fn text(
+        &mut self,
+        option: &str,
+    ) -> Result<String, CliError> {
+        self.value(option)?
+            .into_string()
+            .map_err(|_| CliError::OptionValueInvalid(option.to_string()))
+    }
+
+    /// Get the value of an option as a path.
+    pub fn path(
+        &mut self,
+        option: &str,
+    ) -> Result<PathBuf, CliError> {
+        self.value(option).map(PathBuf::from)
+    }
+
+    /// Get the value of an option as a number.
+    pub fn number<T: FromStr>(
+        &mut self,
+        option: &str,
+    ) -> Result<T, CliError> {
+        self.text(option)?
+            .parse()
+            .map_err(|_| CliError::OptionValueInvalid(option.to_string()))
+    }
+
+    /// Get the value of an option as a size in bytes,
+    /// as parsed by [`parse_size`].
+    pub fn size<T: TryFrom<u64>>(
+        &mut self,
+        option: &str,
+    ) -> Result<T, CliError> {
+        parse_size(&self.text(option)?)
+            .and_then(|size| T::try_from(size).ok())
+            .ok_or_else(|| CliError::OptionValueInvalid(option.to_string()))
+    }
+}
+
+/// Get the positional arguments by their names, in order.
+///
+/// Fails if an argument is missing or unexpected.
+pub fn positionals<const N: usize>(
+    values: Vec<OsString>,
+    names: [&str; N],
+) -> Result<[PathBuf; N], CliError> {
+    if let Some(value) = values.get(N) {
+        return Err(CliError::ArgumentUnexpected(
+            value.to_string_lossy().into_owned(),
+        ));
+    }
+
+    if let Some(name) = names.get(values.len()) {
+        return Err(CliError::ArgumentNotSet(name.to_string()));
+    }
+
+    let mut values = values.into_iter();
+
+    Ok(array::from_fn(|_| values.next().map(PathBuf::from).unwrap_or_default()))
+}
+
+/// Parse a size in bytes, such as `1048576`, `1024K` or `1MiB`.
+///
+/// The units are binary and case-insensitive, written as `K`, `KB` or
+/// `KiB`, up to `T`. Returns `None` if the size is invalid or overflows.
+///
+/// ## Example
+///
+/// ```
+/// use filerune::args::parse_size;
+///
+/// assert_eq!(parse_size("512"), Some(512));
+/// assert_eq!(parse_size("4M"), Some(4 * 1024 * 1024));
+/// assert_eq!(parse_size("2GiB"), Some(2 * 1024 * 1024 * 1024));
+/// assert_eq!(parse_size("2X"), None);
+/// ```
+pub fn parse_size(text: &str) -> Option<u64> {
+    let digits: usize =
+        text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+
+    let (number, unit) = text.split_at(digits);
+
+    let shift: u32 = match unit.to_ascii_lowercase().as_str() {
+        | "" | "b" => 0,
+        | "k" | "kb" | "kib" => 10,
+        | "m" | "mb" | "mib" => 20,
+        | "g" | "gb" | "gib" => 30,
+        | "t" | "tb" | "tib" => 40,
+        | _ => return None,
+    };
+
+    number.parse::<u64>().ok()?.checked_mul(1 << shift)
+}
@@ -0,0 +1,142 @@
+use std::{
+    ffi::OsString,
+    io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use filerune_fusion::{
+    check::{Check, CheckError, CheckReport},
+    hash::HashAlgorithm,
+    report::Report,
+};
+
+use crate::{
+    args::{self, Arg, Parser},
+    error::CliError,
+    manifest, write_out,
+};
+
+/// Usage of the `check` command.
+pub const USAGE: &str = "\
+Check the chunks in a directory.
+
+Usage: filerune check [OPTIONS] --file-size <SIZE> --total-chunks <N> <IN_DIR>
+
+Options:
+      --file-size <SIZE>     Size of the original file, such as `10M`
+  -n, --total-chunks <N>     Total number of chunks
+      --prefix <PREFIX>      Prefix of the chunk file names
+      --extension <EXT>      Extension of the chunk file names
+      --pad-width <WIDTH>    Minimum width of the chunk index, padded with zeros
+      --start-index <INDEX>  Index of the first chunk in the file names
+      --hash <ALGORITHM>     Hash the chunks, with `sha256` or `blake3`
+  -m, --manifest <FILE>      Write the digests of the chunks found to a manifest
+      --json                 Print the report as JSON
+  -h, --help                 Print help
+";
+
+/// Run the `check` command.
+pub fn run<W: io::Write>(
+    mut parser: Parser,
+    out: &mut W,
+) -> Result<(), CliError> {
+    let mut check: Check = Check::new();
+    let mut algorithm: Option<HashAlgorithm> = None;
+    let mut manifest: Option<PathBuf> = None;
+    let mut json: bool = false;
+    let mut values: Vec<OsString> = Vec::new();
+
+    while let Some(arg) = parser.next_arg()? {
+        let option: String = match arg {
+            | Arg::Value(value) => {
+                values.push(value);
+                continue;
+            },
+            | Arg::Option(option) => option,
+        };
+
+        match option.as_str() {
+            | "-h" | "--help" => return write_out(out, USAGE),
+            | "--file-size" => check = check.file_size(parser.size(&option)?),
+            | "-n" | "--total-chunks" => {
+                check = check.total_chunks(parser.number(&option)?)
+            },
+            | "--prefix" => check = check.chunk_prefix(parser.text(&option)?),
+            | "--extension" => {
+                check = check.chunk_extension(parser.text(&option)?)
+            },
+            | "--pad-width" => check = check.pad_width(parser.number(&option)?),
+            | "--start-index" => {
+                check = check.start_index(parser.number(&option)?)
+            },
+            | "--hash" => {
+                algorithm = Some(crate::algorithm(&mut parser, &option)?)
+            },
+            | "-m" | "--manifest" => manifest = Some(parser.path(&option)?),
+            | "--json" => json = true,
+            | _ => return Err(CliError::OptionUnknown(option)),
+        }
+    }
+
+    let [in_dir] = args::positionals(values, ["<IN_DIR>"])?;
+
+    // the manifest is written from the digests of the check
+    if manifest.is_some() && algorithm.is_none() {
+        algorithm = Some(HashAlgorithm::default());
+    }
+
+    if let Some(algorithm) = algorithm {
+        check = check.hash_algorithm(algorithm);
+    }
+
+    let check: Check = check.in_dir(in_dir);
+
+    let started: Instant = Instant::now();
+
+    let result: Result<CheckReport, CheckError> = check.run();
+
+    let duration: Duration = started.elapsed();
+
+    if json {
+        write_out(
+            out,
+            &format!(
+                "{}\n",
+                Report::check(&check, &result, duration).to_json()
+            ),
+        )?;
+    }
+
+    let report: CheckReport = result.map_err(CliError::Check)?;
+
+    if let Some(manifest) = manifest {
+        manifest::write(
+            manifest,
+            &report
+                .chunks
+                .iter()
+                .filter_map(|c| c.hash.clone().map(|h| (c.path.clone(), h)))
+                .collect::<Vec<_>>(),
+        )?;
+    }
+
+    if json {
+        return Ok(());
+    }
+
+    let found: usize = report.chunks.iter().filter(|c| c.found).count();
+
+    let mut summary: String =
+        format!("found {found} chunks of {} bytes\n", report.actual_size);
+
+    if let Some(hash) = report.aggregate_hash {
+        summary.push_str(&format!(
+            "{} {}\n",
+            hash.algorithm.as_str(),
+            hash.to_hex()
+        ));
+    }
+
+    write_out(out, &summary)
+}
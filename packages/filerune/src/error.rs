@@ -0,0 +1,230 @@
+use filerune_fusion::{
+    check::CheckError, merge::MergeError, split::SplitError,
+};
+
+/// The command succeeded.
+pub const EXIT_SUCCESS: u8 = 0;
+
+/// The command failed for another reason, such as when cancelled.
+pub const EXIT_FAILURE: u8 = 1;
+
+/// The command line is invalid.
+pub const EXIT_USAGE: u8 = 2;
+
+/// The input is not found, not set or could not be read.
+pub const EXIT_INPUT: u8 = 3;
+
+/// The output could not be created or written.
+pub const EXIT_OUTPUT: u8 = 4;
+
+/// The chunks, the size or the hash do not match.
+pub const EXIT_MISMATCH: u8 = 5;
+
+/// The directory is locked by another process.
+pub const EXIT_LOCKED: u8 = 6;
+
+/// Command line error enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    Split(SplitError),
+    Check(CheckError),
+    Merge(MergeError),
+    CommandNotSet,
+    CommandUnknown(String),
+    OptionUnknown(String),
+    OptionValueNotSet(String),
+    OptionValueInvalid(String),
+    OptionValueUnexpected(String),
+    ArgumentNotSet(String),
+    ArgumentUnexpected(String),
+    ManifestNotWritten,
+    OutputNotWritten,
+}
+
+impl CliError {
+    /// Get the code of the error as `&str`.
+    ///
+    /// The errors of the commands keep the code of the command.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::Split(error) => error.as_code(),
+            | Self::Check(error) => error.as_code(),
+            | Self::Merge(error) => error.as_code(),
+            | Self::CommandNotSet => "command_not_set",
+            | Self::CommandUnknown(_) => "command_unknown",
+            | Self::OptionUnknown(_) => "option_unknown",
+            | Self::OptionValueNotSet(_) => "option_value_not_set",
+            | Self::OptionValueInvalid(_) => "option_value_invalid",
+            | Self::OptionValueUnexpected(_) => "option_value_unexpected",
+            | Self::ArgumentNotSet(_) => "argument_not_set",
+            | Self::ArgumentUnexpected(_) => "argument_unexpected",
+            | Self::ManifestNotWritten => "manifest_not_written",
+            | Self::OutputNotWritten => "output_not_written",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    ///
+    /// The errors of the commands keep the message of the command.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::Split(error) => error.as_message(),
+            | Self::Check(error) => error.as_message(),
+            | Self::Merge(error) => error.as_message(),
+            | Self::CommandNotSet => "The command is not set.",
+            | Self::CommandUnknown(_) => "The command is unknown.",
+            | Self::OptionUnknown(_) => "The option is unknown.",
+            | Self::OptionValueNotSet(_) => {
+                "The value of the option is not set."
+            },
+            | Self::OptionValueInvalid(_) => {
+                "The value of the option is invalid."
+            },
+            | Self::OptionValueUnexpected(_) => {
+                "The option does not take a value."
+            },
+            | Self::ArgumentNotSet(_) => "The argument is not set.",
+            | Self::ArgumentUnexpected(_) => "The argument is unexpected.",
+            | Self::ManifestNotWritten => "The manifest could not be written.",
+            | Self::OutputNotWritten => "The output could not be written.",
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+
+    /// Get the command, the option or the argument the error is about.
+    pub fn argument(&self) -> Option<&str> {
+        match self {
+            | Self::CommandUnknown(argument)
+            | Self::OptionUnknown(argument)
+            | Self::OptionValueNotSet(argument)
+            | Self::OptionValueInvalid(argument)
+            | Self::OptionValueUnexpected(argument)
+            | Self::ArgumentNotSet(argument)
+            | Self::ArgumentUnexpected(argument) => Some(argument),
+            | _ => None,
+        }
+    }
+
+    /// Get the exit code of the process for the error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            | Self::Split(error) => split_exit_code(error),
+            | Self::Check(error) => check_exit_code(error),
+            | Self::Merge(error) => merge_exit_code(error),
+            | Self::CommandNotSet
+            | Self::CommandUnknown(_)
+            | Self::OptionUnknown(_)
+            | Self::OptionValueNotSet(_)
+            | Self::OptionValueInvalid(_)
+            | Self::OptionValueUnexpected(_)
+            | Self::ArgumentNotSet(_)
+            | Self::ArgumentUnexpected(_) => EXIT_USAGE,
+            | Self::ManifestNotWritten | Self::OutputNotWritten => EXIT_OUTPUT,
+        }
+    }
+}
+
+/// Get the exit code of a split error.
+fn split_exit_code(error: &SplitError) -> u8 {
+    match error {
+        | SplitError::InFileNotFound
+        | SplitError::InFileNotFile
+        | SplitError::InFileNotSet
+        | SplitError::InFileNotOpened
+        | SplitError::InFileNotRead
+        | SplitError::InUrlNotRead
+        | SplitError::InUrlExpired
+        | SplitError::InReaderConsumed
+        | SplitError::InFileSizeUnknown => EXIT_INPUT,
+        | SplitError::OutDirNotCreated
+        | SplitError::OutDirNotDir
+        | SplitError::OutDirNotSet
+        | SplitError::OutDirNotEmpty
+        | SplitError::OutDirNotCleaned
+        | SplitError::OutFileNotOpened
+        | SplitError::OutFileNotWritten
+        | SplitError::OutFileNotCloned
+        | SplitError::InFileNotRemoved
+        | SplitError::InsufficientSpace => EXIT_OUTPUT,
+        | SplitError::SourceChangedDuringSplit
+        | SplitError::ChunksNotVerified => EXIT_MISMATCH,
+        | SplitError::TotalChunksInvalid
+        | SplitError::ChunkStrategyInvalid
+        | SplitError::ParityLimitExceeded
+        | SplitError::FountainLimitExceeded => EXIT_USAGE,
+        | SplitError::Locked | SplitError::LockNotAcquired => EXIT_LOCKED,
+        | SplitError::WarningRaised
+        | SplitError::Cancelled
+        | SplitError::DeadlineExceeded => EXIT_FAILURE,
+    }
+}
+
+/// Get the exit code of a check error.
+fn check_exit_code(error: &CheckError) -> u8 {
+    match error {
+        | CheckError::InDirNotFound
+        | CheckError::InDirNotDir
+        | CheckError::InDirNotSet
+        | CheckError::InFileNotOpened
+        | CheckError::InFileNotRead => EXIT_INPUT,
+        | CheckError::FileSizeNotSet | CheckError::TotalChunksNotSet => {
+            EXIT_USAGE
+        },
+        | CheckError::MissingChunks(_)
+        | CheckError::ChunkSizeMismatch(_)
+        | CheckError::SizeMismatch(_) => EXIT_MISMATCH,
+        | CheckError::ChunksNotRepaired => EXIT_OUTPUT,
+        | CheckError::WarningRaised
+        | CheckError::Cancelled
+        | CheckError::DeadlineExceeded => EXIT_FAILURE,
+    }
+}
+
+/// Get the exit code of a merge error.
+fn merge_exit_code(error: &MergeError) -> u8 {
+    match error {
+        | MergeError::InDirNotFound
+        | MergeError::InDirNotDir
+        | MergeError::InDirNotSet
+        | MergeError::InDirNotRead
+        | MergeError::InDirNoFile
+        | MergeError::InDirNoChunk
+        | MergeError::InUrlNotRead
+        | MergeError::InUrlExpired
+        | MergeError::InFileNotOpened
+        | MergeError::InFileNotRead
+        | MergeError::OutboardNotFound => EXIT_INPUT,
+        | MergeError::OutDirNotCreated
+        | MergeError::OutFileNotSet
+        | MergeError::OutFileExists
+        | MergeError::OutFileNotFile
+        | MergeError::OutFileNotRemoved
+        | MergeError::OutFileNotRenamed
+        | MergeError::OutFileNotOpened
+        | MergeError::OutFileNotWritten
+        | MergeError::OutFileNotCloned
+        | MergeError::OutFileNotAllocated
+        | MergeError::InsufficientSpace
+        | MergeError::ChunksNotRepaired => EXIT_OUTPUT,
+        | MergeError::ChunkMismatch
+        | MergeError::DuplicateChunk
+        | MergeError::ChunkHeaderMismatch
+        | MergeError::ChunksIncomplete
+        | MergeError::FileSizeMismatch
+        | MergeError::HashMismatch
+        | MergeError::SymbolsInsufficient => EXIT_MISMATCH,
+        | MergeError::Locked | MergeError::LockNotAcquired => EXIT_LOCKED,
+        | MergeError::WarningRaised
+        | MergeError::Cancelled
+        | MergeError::DeadlineExceeded => EXIT_FAILURE,
+    }
+}
@@ -1 +1,119 @@
+//! # FileRune
+//!
+//! A file handling solution.
+//!
+//! ## Command Line
+//!
+//! Split a file into chunks, check the chunks and merge them back
+//! with the `filerune` binary.
+//!
+//! ```sh
+//! filerune split --chunk-size 4M --manifest chunks.sha256 file dir
+//! filerune check --file-size 10M dir
+//! filerune merge dir file
+//! ```
+//!
+//! The exit code tells the kind of error, as defined in the
+//! [`error`] module, such as [`EXIT_MISMATCH`](error::EXIT_MISMATCH)
+//! for missing or mismatched chunks.
+//!
+//! The commands are also available as a library with [`run`].
+//!
+//! ```no_run
+//! filerune::run(["check", "dir"], &mut std::io::stdout()).unwrap();
+//! ```
 
+use std::{ffi::OsString, io};
+
+use filerune_fusion::hash::HashAlgorithm;
+
+use crate::{
+    args::{Arg, Parser},
+    error::CliError,
+};
+
+/// Args module.
+pub mod args;
+
+/// Error module.
+pub mod error;
+
+/// Manifest module.
+pub mod manifest;
+
+/// Split module.
+pub mod split;
+
+/// Check module.
+pub mod check;
+
+/// Merge module.
+pub mod merge;
+
+/// Usage of the `filerune` command.
+pub const USAGE: &str = "\
+A file handling solution.
+
+Usage: filerune <COMMAND> [OPTIONS]
+
+Commands:
+  split  Split a file into chunks in a directory
+  check  Check the chunks in a directory
+  merge  Merge the chunks in a directory into a file
+
+Options:
+  -h, --help     Print help
+  -V, --version  Print version
+
+Run `filerune <COMMAND> --help` for the options of a command.
+";
+
+/// Run the command line, without the program name, writing the output.
+pub fn run<I: IntoIterator<Item = A>, A: Into<OsString>, W: io::Write>(
+    args: I,
+    out: &mut W,
+) -> Result<(), CliError> {
+    let mut parser: Parser = Parser::new(args);
+
+    let command: OsString = match parser.next_arg()? {
+        | Some(Arg::Value(command)) => command,
+        | Some(Arg::Option(option)) => {
+            return match option.as_str() {
+                | "-h" | "--help" => write_out(out, USAGE),
+                | "-V" | "--version" => write_out(
+                    out,
+                    &format!("filerune {}\n", env!("CARGO_PKG_VERSION")),
+                ),
+                | _ => Err(CliError::OptionUnknown(option)),
+            };
+        },
+        | None => return Err(CliError::CommandNotSet),
+    };
+
+    match command.to_str() {
+        | Some("split") => split::run(parser, out),
+        | Some("check") => check::run(parser, out),
+        | Some("merge") => merge::run(parser, out),
+        | Some("help") => write_out(out, USAGE),
+        | _ => Err(CliError::CommandUnknown(
+            command.to_string_lossy().into_owned(),
+        )),
+    }
+}
+
+/// Write the output of a command.
+pub(crate) fn write_out<W: io::Write>(
+    out: &mut W,
+    text: &str,
+) -> Result<(), CliError> {
+    out.write_all(text.as_bytes()).map_err(|_| CliError::OutputNotWritten)
+}
+
+/// Get the value of an option as a hash algorithm.
+pub(crate) fn algorithm(
+    parser: &mut Parser,
+    option: &str,
+) -> Result<HashAlgorithm, CliError> {
+    HashAlgorithm::parse(&parser.text(option)?)
+        .ok_or_else(|| CliError::OptionValueInvalid(option.to_string()))
+}
@@ -0,0 +1,26 @@
+use std::{env, io, process::ExitCode};
+
+use filerune::error::{CliError, EXIT_USAGE};
+
+fn main() -> ExitCode {
+    let result: Result<(), CliError> =
+        filerune::run(env::args_os().skip(1), &mut io::stdout().lock());
+
+    let error: CliError = match result {
+        | Ok(()) => return ExitCode::SUCCESS,
+        | Err(error) => error,
+    };
+
+    match error.argument() {
+        | Some(argument) => {
+            eprintln!("error: {} `{argument}`", error.as_message())
+        },
+        | None => eprintln!("error: {}", error.as_message()),
+    }
+
+    if error.exit_code() == EXIT_USAGE {
+        eprintln!("\nFor more information, try `filerune --help`.");
+    }
+
+    ExitCode::from(error.exit_code())
+}
@@ -0,0 +1,50 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use filerune_fusion::hash::{Digest, HashAlgorithm};
+
+use crate::error::CliError;
+
+/// Hash the chunk files, in order.
+pub fn hash_chunks<'a, I: IntoIterator<Item = &'a PathBuf>>(
+    algorithm: HashAlgorithm,
+    paths: I,
+) -> Result<Vec<(PathBuf, Digest)>, CliError> {
+    paths
+        .into_iter()
+        .map(|path| {
+            fs::read(path)
+                .map(|data| (path.clone(), algorithm.digest(&data)))
+                .map_err(|_| CliError::ManifestNotWritten)
+        })
+        .collect()
+}
+
+/// Encode the digests of the chunks as a manifest,
+/// one `<hex>  <file name>` line per chunk.
+///
+/// The manifest is read by `sha256sum --check` or `b3sum --check`
+/// in the chunk directory.
+pub fn encode(chunks: &[(PathBuf, Digest)]) -> String {
+    chunks
+        .iter()
+        .map(|(path, digest)| {
+            let name: &str = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+
+            format!("{}  {name}\n", digest.to_hex())
+        })
+        .collect()
+}
+
+/// Write the digests of the chunks as a manifest to a path.
+pub fn write<P: AsRef<Path>>(
+    path: P,
+    chunks: &[(PathBuf, Digest)],
+) -> Result<(), CliError> {
+    fs::write(path, encode(chunks)).map_err(|_| CliError::ManifestNotWritten)
+}
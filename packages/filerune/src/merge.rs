@@ -0,0 +1,133 @@
+use std::{
+    ffi::OsString,
+    io,
+    time::{Duration, Instant},
+};
+
+use filerune_fusion::{
+    hash::{Digest, HashAlgorithm},
+    merge::{Merge, MergeError, MergeResult, OutFilePolicy},
+    report::Report,
+};
+
+use crate::{
+    args::{self, Arg, Parser},
+    error::CliError,
+    write_out,
+};
+
+/// Usage of the `merge` command.
+pub const USAGE: &str = "\
+Merge the chunks in a directory into a file.
+
+Usage: filerune merge [OPTIONS] <IN_DIR> <OUT_FILE>
+
+The output file is written to the standard output if it is `-`,
+without the summary nor the report.
+
+Options:
+      --prefix <PREFIX>      Prefix of the chunk file names
+      --extension <EXT>      Extension of the chunk file names
+      --pad-width <WIDTH>    Minimum width of the chunk index, padded with zeros
+      --start-index <INDEX>  Index of the first chunk in the file names
+      --hash <ALGORITHM>     Hash of the expected hash, `sha256` or `blake3` [default: sha256]
+      --expected-hash <HEX>  Verify the output against a hash, in hexadecimal
+      --overwrite            Replace an existing output file
+      --json                 Print the report as JSON
+  -h, --help                 Print help
+";
+
+/// Run the `merge` command.
+pub fn run<W: io::Write>(
+    mut parser: Parser,
+    out: &mut W,
+) -> Result<(), CliError> {
+    let mut merge: Merge = Merge::new();
+    let mut algorithm: HashAlgorithm = HashAlgorithm::default();
+    let mut expected_hash: Option<String> = None;
+    let mut json: bool = false;
+    let mut values: Vec<OsString> = Vec::new();
+
+    while let Some(arg) = parser.next_arg()? {
+        let option: String = match arg {
+            | Arg::Value(value) => {
+                values.push(value);
+                continue;
+            },
+            | Arg::Option(option) => option,
+        };
+
+        match option.as_str() {
+            | "-h" | "--help" => return write_out(out, USAGE),
+            | "--prefix" => merge = merge.chunk_prefix(parser.text(&option)?),
+            | "--extension" => {
+                merge = merge.chunk_extension(parser.text(&option)?)
+            },
+            | "--pad-width" => merge = merge.pad_width(parser.number(&option)?),
+            | "--start-index" => {
+                merge = merge.start_index(parser.number(&option)?)
+            },
+            | "--hash" => algorithm = crate::algorithm(&mut parser, &option)?,
+            | "--expected-hash" => expected_hash = Some(parser.text(&option)?),
+            | "--overwrite" => {
+                merge = merge.overwrite(OutFilePolicy::Overwrite)
+            },
+            | "--json" => json = true,
+            | _ => return Err(CliError::OptionUnknown(option)),
+        }
+    }
+
+    let [in_dir, out_file] =
+        args::positionals(values, ["<IN_DIR>", "<OUT_FILE>"])?;
+
+    // the algorithm may be set after the hash
+    if let Some(hex) = expected_hash {
+        let digest: Digest =
+            Digest::from_hex(algorithm, &hex).ok_or_else(|| {
+                CliError::OptionValueInvalid("--expected-hash".to_string())
+            })?;
+
+        merge = merge.expected_hash(algorithm, digest.bytes);
+    }
+
+    // the merged output is the only output
+    if out_file.as_os_str() == "-" {
+        return merge
+            .in_dir(in_dir)
+            .run_to_writer(out)
+            .map(|_| ())
+            .map_err(CliError::Merge);
+    }
+
+    let merge: Merge = merge.in_dir(in_dir).out_file(out_file);
+
+    let started: Instant = Instant::now();
+
+    let result: Result<MergeResult, MergeError> = merge.run();
+
+    let duration: Duration = started.elapsed();
+
+    if json {
+        write_out(
+            out,
+            &format!(
+                "{}\n",
+                Report::merge(&merge, &result, duration).to_json()
+            ),
+        )?;
+    }
+
+    let result: MergeResult = result.map_err(CliError::Merge)?;
+
+    if json {
+        return Ok(());
+    }
+
+    write_out(
+        out,
+        &format!(
+            "merged {} chunks into {} bytes\n",
+            result.chunks_merged, result.bytes_written
+        ),
+    )
+}
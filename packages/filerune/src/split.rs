@@ -0,0 +1,134 @@
+use std::{
+    ffi::OsString,
+    io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use filerune_fusion::{
+    hash::HashAlgorithm,
+    report::Report,
+    split::{OverwritePolicy, Split, SplitError, SplitResult},
+};
+
+use crate::{
+    args::{self, Arg, Parser},
+    error::CliError,
+    manifest, write_out,
+};
+
+/// Usage of the `split` command.
+pub const USAGE: &str = "\
+Split a file into chunks in a directory.
+
+Usage: filerune split [OPTIONS] <IN_FILE> <OUT_DIR>
+
+The input file is read from the standard input if it is `-`.
+
+Options:
+  -s, --chunk-size <SIZE>    Size of each chunk, such as `4M` [default: 2M]
+  -n, --total-chunks <N>     Total number of chunks, instead of the size
+      --prefix <PREFIX>      Prefix of the chunk file names
+      --extension <EXT>      Extension of the chunk file names
+      --pad-width <WIDTH>    Minimum width of the chunk index, padded with zeros
+      --start-index <INDEX>  Index of the first chunk in the file names
+      --hash <ALGORITHM>     Hash of the manifest, `sha256` or `blake3` [default: sha256]
+  -m, --manifest <FILE>      Write the digests of the chunks to a manifest
+      --overwrite            Remove the entries of the output directory first
+      --json                 Print the report as JSON
+  -h, --help                 Print help
+";
+
+/// Run the `split` command.
+pub fn run<W: io::Write>(
+    mut parser: Parser,
+    out: &mut W,
+) -> Result<(), CliError> {
+    let mut split: Split = Split::new();
+    let mut algorithm: HashAlgorithm = HashAlgorithm::default();
+    let mut manifest: Option<PathBuf> = None;
+    let mut json: bool = false;
+    let mut values: Vec<OsString> = Vec::new();
+
+    while let Some(arg) = parser.next_arg()? {
+        let option: String = match arg {
+            | Arg::Value(value) => {
+                values.push(value);
+                continue;
+            },
+            | Arg::Option(option) => option,
+        };
+
+        match option.as_str() {
+            | "-h" | "--help" => return write_out(out, USAGE),
+            | "-s" | "--chunk-size" => {
+                split = split.chunk_size(parser.size(&option)?)
+            },
+            | "-n" | "--total-chunks" => {
+                split = split.total_chunks(parser.number(&option)?)
+            },
+            | "--prefix" => split = split.chunk_prefix(parser.text(&option)?),
+            | "--extension" => {
+                split = split.chunk_extension(parser.text(&option)?)
+            },
+            | "--pad-width" => split = split.pad_width(parser.number(&option)?),
+            | "--start-index" => {
+                split = split.start_index(parser.number(&option)?)
+            },
+            | "--hash" => algorithm = crate::algorithm(&mut parser, &option)?,
+            | "-m" | "--manifest" => manifest = Some(parser.path(&option)?),
+            | "--overwrite" => split = split.overwrite(OverwritePolicy::Clean),
+            | "--json" => json = true,
+            | _ => return Err(CliError::OptionUnknown(option)),
+        }
+    }
+
+    let [in_file, out_dir] =
+        args::positionals(values, ["<IN_FILE>", "<OUT_DIR>"])?;
+
+    let split: Split = match in_file.as_os_str() == "-" {
+        | true => split.in_reader(io::stdin(), None),
+        | false => split.in_file(in_file),
+    }
+    .out_dir(out_dir);
+
+    let started: Instant = Instant::now();
+
+    let result: Result<SplitResult, SplitError> = split.run();
+
+    let duration: Duration = started.elapsed();
+
+    if json {
+        write_out(
+            out,
+            &format!(
+                "{}\n",
+                Report::split(&split, &result, duration).to_json()
+            ),
+        )?;
+    }
+
+    let result: SplitResult = result.map_err(CliError::Split)?;
+
+    if let Some(manifest) = manifest {
+        manifest::write(
+            manifest,
+            &manifest::hash_chunks(
+                algorithm,
+                result.chunks.iter().map(|c| &c.path),
+            )?,
+        )?;
+    }
+
+    if json {
+        return Ok(());
+    }
+
+    write_out(
+        out,
+        &format!(
+            "split {} bytes into {} chunks\n",
+            result.file_size, result.total_chunks
+        ),
+    )
+}
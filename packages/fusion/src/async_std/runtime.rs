@@ -0,0 +1,168 @@
+use std::{
+    fs::Metadata,
+    io,
+    path::{Path, PathBuf},
+};
+
+use async_std::{
+    fs,
+    io::{ReadExt, SeekExt as _, WriteExt},
+    stream::StreamExt as _,
+};
+
+use crate::{
+    runtime::{Reader, Runtime, Writer},
+    sync::SyncPolicy,
+    sys::{allocate, read_options},
+};
+
+/// Runtime of `async_std`.
+pub(crate) struct AsyncStd;
+
+impl Reader for fs::File {
+    async fn read(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> io::Result<usize> {
+        ReadExt::read(self, buffer).await
+    }
+}
+
+impl Writer for fs::File {
+    async fn write_all(
+        &mut self,
+        data: &[u8],
+    ) -> io::Result<()> {
+        WriteExt::write_all(self, data).await
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        WriteExt::flush(self).await
+    }
+}
+
+impl Runtime for AsyncStd {
+    type File = fs::File;
+
+    async fn open(path: &Path) -> io::Result<fs::File> {
+        let path: PathBuf = path.to_path_buf();
+
+        async_std::task::spawn_blocking(move || read_options().open(path))
+            .await
+            .map(fs::File::from)
+    }
+
+    async fn create(
+        path: &Path,
+        truncate: bool,
+    ) -> io::Result<fs::File> {
+        fs::OpenOptions::new()
+            .create(true)
+            .truncate(truncate)
+            .write(true)
+            .open(path)
+            .await
+    }
+
+    async fn file_metadata(file: &fs::File) -> io::Result<Metadata> {
+        file.metadata().await
+    }
+
+    async fn set_len(
+        file: &fs::File,
+        len: u64,
+    ) -> io::Result<()> {
+        file.set_len(len).await
+    }
+
+    async fn seek(
+        file: &mut fs::File,
+        position: u64,
+    ) -> io::Result<()> {
+        file.seek(io::SeekFrom::Start(position)).await.map(|_| ())
+    }
+
+    async fn skip(
+        file: &mut fs::File,
+        len: u64,
+    ) -> io::Result<u64> {
+        file.seek(io::SeekFrom::Current(len as i64)).await
+    }
+
+    fn allocate(
+        file: &fs::File,
+        len: u64,
+    ) -> io::Result<()> {
+        allocate(file, len)
+    }
+
+    async fn sync_file(
+        policy: SyncPolicy,
+        file: &fs::File,
+    ) -> io::Result<()> {
+        match policy {
+            | SyncPolicy::None => Ok(()),
+            | SyncPolicy::Data => file.sync_data().await,
+            | SyncPolicy::All => file.sync_all().await,
+        }
+    }
+
+    async fn sync_dir(
+        policy: SyncPolicy,
+        dir: &Path,
+    ) -> io::Result<()> {
+        if policy == SyncPolicy::None || cfg!(not(unix)) {
+            return Ok(());
+        }
+
+        fs::File::open(dir).await?.sync_all().await
+    }
+
+    async fn metadata(path: &Path) -> io::Result<Metadata> {
+        fs::metadata(path).await
+    }
+
+    async fn read_dir(path: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut read_dir: fs::ReadDir = fs::read_dir(path).await?;
+
+        let mut entries: Vec<PathBuf> = Vec::new();
+
+        while let Some(entry) = read_dir.next().await.transpose()? {
+            entries.push(entry.path().into());
+        }
+
+        Ok(entries)
+    }
+
+    async fn create_dir_all(path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path).await
+    }
+
+    async fn write(
+        path: &Path,
+        data: &[u8],
+    ) -> io::Result<()> {
+        fs::write(path, data).await
+    }
+
+    async fn rename(
+        from: &Path,
+        to: &Path,
+    ) -> io::Result<()> {
+        fs::rename(from, to).await
+    }
+
+    async fn remove_file(path: &Path) -> io::Result<()> {
+        fs::remove_file(path).await
+    }
+
+    async fn remove_dir_all(path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path).await
+    }
+
+    async fn spawn_blocking<T: Send + 'static>(
+        f: impl FnOnce() -> T + Send + 'static
+    ) -> T {
+        async_std::task::spawn_blocking(f).await
+    }
+}
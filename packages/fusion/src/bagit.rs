@@ -0,0 +1,520 @@
+use std::{
+    fs,
+    io::{self, Read as _},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    BUFFER_CAPACITY_DEFAULT,
+    hash::{Digest, HashAlgorithm, Hasher},
+};
+
+/// The version of BagIt written to `bagit.txt`.
+pub const BAGIT_VERSION: &str = "1.0";
+
+/// The name of the payload directory of a bag.
+pub const PAYLOAD_DIR_NAME: &str = "data";
+
+/// The name of the bag declaration.
+pub(crate) const DECLARATION_NAME: &str = "bagit.txt";
+
+/// The name of the bag metadata.
+const BAG_INFO_NAME: &str = "bag-info.txt";
+
+/// The software agent written to the bag metadata.
+const SOFTWARE_AGENT: &str =
+    concat!("filerune_fusion/", env!("CARGO_PKG_VERSION"));
+
+/// Entry of a manifest, a file of the bag and its digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BagEntry {
+    /// The path of the file relative to the bag directory,
+    /// such as `data/0` for a chunk.
+    pub path: PathBuf,
+    /// The digest of the file.
+    pub hash: Digest,
+}
+
+/// Manifest of the payload of a bag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BagManifest {
+    /// The algorithm of the digests.
+    pub algorithm: HashAlgorithm,
+    /// The files of the payload, sorted by path.
+    pub entries: Vec<BagEntry>,
+    /// The number of bytes of the payload.
+    pub payload_size: u64,
+}
+
+/// Bag process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BagError {
+    BagDirNotFound,
+    BagDirNotDir,
+    BagDirNotSet,
+    PayloadNotFound,
+    PayloadNotRead,
+    PayloadNotMatched,
+    TagFileNotRead,
+    TagFileNotWritten,
+    TagFileInvalid,
+    TagFileNotMatched,
+}
+
+impl BagError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::BagDirNotFound => "bag_dir_not_found",
+            | Self::BagDirNotDir => "bag_dir_not_dir",
+            | Self::BagDirNotSet => "bag_dir_not_set",
+            | Self::PayloadNotFound => "payload_not_found",
+            | Self::PayloadNotRead => "payload_not_read",
+            | Self::PayloadNotMatched => "payload_not_matched",
+            | Self::TagFileNotRead => "tag_file_not_read",
+            | Self::TagFileNotWritten => "tag_file_not_written",
+            | Self::TagFileInvalid => "tag_file_invalid",
+            | Self::TagFileNotMatched => "tag_file_not_matched",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::BagDirNotFound => "The bag directory not found.",
+            | Self::BagDirNotDir => "The bag directory is not a directory.",
+            | Self::BagDirNotSet => "The bag directory is not set.",
+            | Self::PayloadNotFound => "The payload directory not found.",
+            | Self::PayloadNotRead => "The payload could not be read.",
+            | Self::PayloadNotMatched => {
+                "The payload does not match the manifest."
+            },
+            | Self::TagFileNotRead => "A tag file could not be read.",
+            | Self::TagFileNotWritten => "A tag file could not be written.",
+            | Self::TagFileInvalid => "A tag file is invalid.",
+            | Self::TagFileNotMatched => {
+                "The tag files do not match the tag manifest."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Process to write and verify the BagIt manifests of a chunk set,
+/// following RFC 8493.
+///
+/// The bag directory holds the chunks in its `data` payload directory,
+/// next to the tag files: `bagit.txt`, `bag-info.txt`, the payload manifest
+/// such as `manifest-sha256.txt`, and the tag manifest such as
+/// `tagmanifest-sha256.txt`. Split into the payload directory,
+/// then write the bag, so archival tools accept the chunk set.
+/// Verify the bag before merging from the payload directory.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     bagit::{Bag, BagManifest},
+///     merge::Merge,
+///     split::Split,
+/// };
+///
+/// let bag_dir: PathBuf = PathBuf::from("path").join("to").join("bag");
+///
+/// Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(bag_dir.join("data"))
+///     .run()
+///     .unwrap();
+///
+/// let bag: Bag = Bag::new()
+///     .bag_dir(&bag_dir)
+///     .info("Source-Organization", "Library");
+///
+/// bag.write().unwrap();
+///
+/// // later, or elsewhere
+/// let manifest: BagManifest = bag.verify().unwrap();
+///
+/// Merge::new()
+///     .in_dir(bag_dir.join("data"))
+///     .out_file(PathBuf::from("path").join("to").join("output"))
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bag {
+    pub bag_dir: Option<Arc<Path>>,
+    pub algorithm: HashAlgorithm,
+    pub info: Vec<(String, String)>,
+}
+
+impl Bag {
+    /// Create a new bag process.
+    pub fn new() -> Self {
+        Self {
+            bag_dir: None,
+            algorithm: HashAlgorithm::Sha256,
+            info: Vec::new(),
+        }
+    }
+
+    /// Create a new bag process from an existing one.
+    pub fn from<P: Into<Bag>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the bag directory, holding the chunks in its `data` directory.
+    pub fn bag_dir<BagDir: AsRef<Path>>(
+        mut self,
+        path: BagDir,
+    ) -> Self {
+        self.bag_dir = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the bag directory with a shared path.
+    ///
+    /// Unlike [`Bag::bag_dir`], an [`Arc<Path>`] is stored without copying.
+    pub fn bag_dir_shared<BagDir: Into<Arc<Path>>>(
+        mut self,
+        path: BagDir,
+    ) -> Self {
+        self.bag_dir = Some(path.into());
+        self
+    }
+
+    /// Set the algorithm of the manifests.
+    ///
+    /// By default, it is [`HashAlgorithm::Sha256`].
+    pub fn algorithm(
+        mut self,
+        algorithm: HashAlgorithm,
+    ) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Add a metadata element to `bag-info.txt`, such as
+    /// `Source-Organization` or `External-Identifier`.
+    ///
+    /// The `Payload-Oxum` and `Bag-Software-Agent` elements are always written.
+    pub fn info<Label: Into<String>, Value: Into<String>>(
+        mut self,
+        label: Label,
+        value: Value,
+    ) -> Self {
+        self.info.push((label.into(), value.into()));
+        self
+    }
+
+    /// Write the tag files of the bag, hashing the payload.
+    pub fn write(&self) -> Result<BagManifest, BagError> {
+        let bag_dir: &Path = self.prepare_bag_dir()?;
+
+        let mut entries: Vec<BagEntry> = Vec::new();
+
+        let mut payload_size: u64 = 0;
+
+        for path in payload_files(bag_dir)? {
+            let (len, hash) = hash_file(self.algorithm, &bag_dir.join(&path))
+                .map_err(|_| BagError::PayloadNotRead)?;
+
+            payload_size += len;
+
+            entries.push(BagEntry { path, hash });
+        }
+
+        let mut bag_info: String = format!(
+            "Bag-Software-Agent: {SOFTWARE_AGENT}\n\
+             Payload-Oxum: {payload_size}.{}\n",
+            entries.len()
+        );
+
+        for (label, value) in self.info.iter() {
+            bag_info.push_str(&format!("{label}: {value}\n"));
+        }
+
+        let manifest: BagManifest =
+            BagManifest { algorithm: self.algorithm, entries, payload_size };
+
+        let tag_files: [(String, String); 3] = [
+            (
+                DECLARATION_NAME.to_string(),
+                format!(
+                    "BagIt-Version: {BAGIT_VERSION}\n\
+                     Tag-File-Character-Encoding: UTF-8\n"
+                ),
+            ),
+            (BAG_INFO_NAME.to_string(), bag_info),
+            (
+                manifest_name(self.algorithm, false),
+                encode_manifest(&manifest.entries),
+            ),
+        ];
+
+        let mut tag_entries: Vec<BagEntry> =
+            Vec::with_capacity(tag_files.len());
+
+        for (name, content) in tag_files {
+            fs::write(bag_dir.join(&name), &content)
+                .map_err(|_| BagError::TagFileNotWritten)?;
+
+            tag_entries.push(BagEntry {
+                path: PathBuf::from(name),
+                hash: self.algorithm.digest(content.as_bytes()),
+            });
+        }
+
+        fs::write(
+            bag_dir.join(manifest_name(self.algorithm, true)),
+            encode_manifest(&tag_entries),
+        )
+        .map_err(|_| BagError::TagFileNotWritten)?;
+
+        Ok(manifest)
+    }
+
+    /// Verify the payload of the bag by its manifest,
+    /// and the tag files by the tag manifest if any.
+    ///
+    /// Every file of the payload must be listed with a matching digest,
+    /// otherwise it fails with [`BagError::PayloadNotMatched`].
+    pub fn verify(&self) -> Result<BagManifest, BagError> {
+        let bag_dir: &Path = self.prepare_bag_dir()?;
+
+        let declaration: String =
+            fs::read_to_string(bag_dir.join(DECLARATION_NAME))
+                .map_err(|_| BagError::TagFileNotRead)?;
+
+        if !declaration.starts_with("BagIt-Version:") {
+            return Err(BagError::TagFileInvalid);
+        }
+
+        // the tag files are verified first, the manifest included
+        match fs::read_to_string(
+            bag_dir.join(manifest_name(self.algorithm, true)),
+        ) {
+            | Ok(content) => {
+                for entry in decode_manifest(self.algorithm, &content)? {
+                    let (_, hash) =
+                        hash_file(self.algorithm, &bag_dir.join(&entry.path))
+                            .map_err(|_| BagError::TagFileNotMatched)?;
+
+                    if hash != entry.hash {
+                        return Err(BagError::TagFileNotMatched);
+                    }
+                }
+            },
+            | Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+            | Err(_) => return Err(BagError::TagFileNotRead),
+        }
+
+        let content: String = fs::read_to_string(
+            bag_dir.join(manifest_name(self.algorithm, false)),
+        )
+        .map_err(|_| BagError::TagFileNotRead)?;
+
+        let mut entries: Vec<BagEntry> =
+            decode_manifest(self.algorithm, &content)?;
+
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let files: Vec<PathBuf> = payload_files(bag_dir)?;
+
+        // every file of the payload is listed, once
+        if entries.len() != files.len()
+            || entries.iter().zip(files.iter()).any(|(e, f)| e.path != *f)
+        {
+            return Err(BagError::PayloadNotMatched);
+        }
+
+        let mut payload_size: u64 = 0;
+
+        for entry in entries.iter() {
+            let (len, hash) =
+                hash_file(self.algorithm, &bag_dir.join(&entry.path))
+                    .map_err(|_| BagError::PayloadNotRead)?;
+
+            if hash != entry.hash {
+                return Err(BagError::PayloadNotMatched);
+            }
+
+            payload_size += len;
+        }
+
+        Ok(BagManifest { algorithm: self.algorithm, entries, payload_size })
+    }
+
+    /// Validate the bag directory and its payload directory.
+    fn prepare_bag_dir(&self) -> Result<&Path, BagError> {
+        let bag_dir: &Path = match self.bag_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if bag_dir not exists
+                if !p.exists() {
+                    return Err(BagError::BagDirNotFound);
+                }
+
+                // if bag_dir not a directory
+                if !p.is_dir() {
+                    return Err(BagError::BagDirNotDir);
+                }
+
+                p
+            },
+            | None => return Err(BagError::BagDirNotSet),
+        };
+
+        if !bag_dir.join(PAYLOAD_DIR_NAME).is_dir() {
+            return Err(BagError::PayloadNotFound);
+        }
+
+        Ok(bag_dir)
+    }
+}
+
+impl Default for Bag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the name of the payload or the tag manifest of an algorithm.
+pub(crate) fn manifest_name(
+    algorithm: HashAlgorithm,
+    tag: bool,
+) -> String {
+    match tag {
+        | true => format!("tagmanifest-{}.txt", algorithm.as_str()),
+        | false => format!("manifest-{}.txt", algorithm.as_str()),
+    }
+}
+
+/// List the files of the payload relative to the bag directory, sorted.
+fn payload_files(bag_dir: &Path) -> Result<Vec<PathBuf>, BagError> {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    let mut dirs: Vec<PathBuf> = vec![PathBuf::from(PAYLOAD_DIR_NAME)];
+
+    while let Some(dir) = dirs.pop() {
+        let read_dir: fs::ReadDir = fs::read_dir(bag_dir.join(&dir))
+            .map_err(|_| BagError::PayloadNotRead)?;
+
+        for entry in read_dir {
+            let entry: fs::DirEntry =
+                entry.map_err(|_| BagError::PayloadNotRead)?;
+
+            let path: PathBuf = dir.join(entry.file_name());
+
+            match entry.file_type().map_err(|_| BagError::PayloadNotRead)? {
+                | t if t.is_dir() => dirs.push(path),
+                | _ => files.push(path),
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// Hash a file, returning its size and its digest.
+fn hash_file(
+    algorithm: HashAlgorithm,
+    path: &Path,
+) -> io::Result<(u64, Digest)> {
+    let mut file: fs::File = fs::File::open(path)?;
+
+    let mut hasher: Hasher = algorithm.hasher();
+
+    let mut buffer: Vec<u8> = vec![0; BUFFER_CAPACITY_DEFAULT];
+
+    let mut len: u64 = 0;
+
+    loop {
+        match file.read(&mut buffer)? {
+            | 0 => break,
+            | n => {
+                hasher.update(&buffer[..n]);
+
+                len += n as u64;
+            },
+        }
+    }
+
+    Ok((len, hasher.finalize()))
+}
+
+/// Encode the entries of a manifest, one `<digest>  <path>` line each.
+///
+/// The paths are separated by slashes, with `%`, CR and LF percent-encoded.
+fn encode_manifest(entries: &[BagEntry]) -> String {
+    let mut content: String = String::new();
+
+    for entry in entries {
+        let path: Vec<String> = entry
+            .path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+
+        let path: String = path
+            .join("/")
+            .replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A");
+
+        content.push_str(&format!("{}  {path}\n", entry.hash.to_hex()));
+    }
+
+    content
+}
+
+/// Decode the entries of a manifest.
+///
+/// The paths escaping the bag directory are invalid.
+pub(crate) fn decode_manifest(
+    algorithm: HashAlgorithm,
+    content: &str,
+) -> Result<Vec<BagEntry>, BagError> {
+    let mut entries: Vec<BagEntry> = Vec::new();
+
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let (hex, path) =
+            line.split_once([' ', '\t']).ok_or(BagError::TagFileInvalid)?;
+
+        let path: PathBuf = PathBuf::from(
+            path.trim_start()
+                .replace("%0A", "\n")
+                .replace("%0a", "\n")
+                .replace("%0D", "\r")
+                .replace("%0d", "\r")
+                .replace("%25", "%"),
+        );
+
+        if path.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(BagError::TagFileInvalid);
+        }
+
+        let hash: Digest =
+            Digest::from_hex(algorithm, hex).ok_or(BagError::TagFileInvalid)?;
+
+        entries.push(BagEntry { path, hash });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,569 @@
+use std::{
+    fs,
+    io::{self, Read as _},
+    path::{Path, PathBuf},
+};
+
+use blake3::hazmat::{
+    ChainingValue, HasherExt as _, Mode, merge_subtrees_non_root,
+    merge_subtrees_root,
+};
+
+use crate::{
+    chunk::temp_path,
+    hash::{Digest, HashAlgorithm},
+    sync::SyncPolicy,
+};
+
+/// The size of the BLAKE3 chunks, the unit verified by an outboard tree.
+pub const BAO_CHUNK_LEN: usize = 1024;
+
+/// The extension of the outboard tree written alongside the chunks.
+pub const OUTBOARD_EXTENSION: &str = "obao";
+
+/// The length of the header of an outboard tree, the content size.
+const HEADER_LEN: usize = 8;
+
+/// The length of a parent node, the chaining values of its children.
+const PARENT_LEN: usize = 64;
+
+/// Bao process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaoError {
+    OutboardNotRead,
+    OutboardInvalid,
+    RangeExceeded,
+    HashMismatch,
+}
+
+impl BaoError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::OutboardNotRead => "outboard_not_read",
+            | Self::OutboardInvalid => "outboard_invalid",
+            | Self::RangeExceeded => "range_exceeded",
+            | Self::HashMismatch => "hash_mismatch",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::OutboardNotRead => "The outboard tree could not be read.",
+            | Self::OutboardInvalid => "The outboard tree is invalid.",
+            | Self::RangeExceeded => "The data exceeds the size of the tree.",
+            | Self::HashMismatch => {
+                "The data does not match the hash of the tree."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Get the chaining value of a BLAKE3 chunk, of up to
+/// [`BAO_CHUNK_LEN`] bytes, or the hash of the content if root.
+fn chunk_cv(
+    data: &[u8],
+    counter: u64,
+    root: bool,
+) -> [u8; 32] {
+    if root {
+        return blake3::hash(data).into();
+    }
+
+    let mut hasher: blake3::Hasher = blake3::Hasher::new();
+
+    hasher.set_input_offset(counter * BAO_CHUNK_LEN as u64);
+    hasher.update(data);
+
+    hasher.finalize_non_root()
+}
+
+/// Get the chaining value of a parent node,
+/// or the hash of the content if root.
+fn parent_cv(
+    node: &[u8; 64],
+    root: bool,
+) -> [u8; 32] {
+    let mut left: ChainingValue = [0; 32];
+    let mut right: ChainingValue = [0; 32];
+
+    left.copy_from_slice(&node[..32]);
+    right.copy_from_slice(&node[32..]);
+
+    match root {
+        | true => merge_subtrees_root(&left, &right, Mode::Hash).into(),
+        | false => merge_subtrees_non_root(&left, &right, Mode::Hash),
+    }
+}
+
+/// Get the parent node of two chaining values.
+fn parent_node(
+    left: &[u8; 32],
+    right: &[u8; 32],
+) -> [u8; 64] {
+    let mut node: [u8; 64] = [0; 64];
+
+    node[..32].copy_from_slice(left);
+    node[32..].copy_from_slice(right);
+
+    node
+}
+
+/// Get the number of BLAKE3 chunks of a content size, at least one.
+fn chunk_count(len: u64) -> u64 {
+    len.div_ceil(BAO_CHUNK_LEN as u64).max(1)
+}
+
+/// Get the number of chunks of the left subtree of a tree,
+/// the largest power of two less than the number of chunks.
+fn left_chunks(chunks: u64) -> u64 {
+    1 << (chunks - 1).ilog2()
+}
+
+/// Encode the outboard tree of a subtree read from the reader, with
+/// the parent nodes written to their position in pre-order.
+///
+/// Returns the chaining value of the subtree.
+fn encode_subtree<R: io::Read, W: io::Write + io::Seek>(
+    reader: &mut R,
+    writer: &mut W,
+    len: u64,
+    counter: u64,
+    next_node: &mut u64,
+    root: bool,
+) -> io::Result<[u8; 32]> {
+    let chunks: u64 = chunk_count(len);
+
+    if chunks == 1 {
+        let mut data: Vec<u8> = vec![0; len as usize];
+
+        reader.read_exact(&mut data)?;
+
+        return Ok(chunk_cv(&data, counter, root));
+    }
+
+    let node_index: u64 = *next_node;
+
+    *next_node += 1;
+
+    let left_len: u64 = left_chunks(chunks) * BAO_CHUNK_LEN as u64;
+
+    let left: [u8; 32] =
+        encode_subtree(reader, writer, left_len, counter, next_node, false)?;
+
+    let right: [u8; 32] = encode_subtree(
+        reader,
+        writer,
+        len - left_len,
+        counter + left_len / BAO_CHUNK_LEN as u64,
+        next_node,
+        false,
+    )?;
+
+    let node: [u8; 64] = parent_node(&left, &right);
+
+    writer.seek(io::SeekFrom::Start(
+        HEADER_LEN as u64 + node_index * PARENT_LEN as u64,
+    ))?;
+
+    writer.write_all(&node)?;
+
+    Ok(parent_cv(&node, root))
+}
+
+/// Encode the outboard tree of the content read from the reader,
+/// of `len` bytes, to the writer.
+///
+/// Returns the BLAKE3 hash of the content, the root of the tree.
+fn encode<R: io::Read, W: io::Write + io::Seek>(
+    mut reader: R,
+    len: u64,
+    writer: &mut W,
+) -> io::Result<Digest> {
+    writer.write_all(&len.to_le_bytes())?;
+
+    let root: [u8; 32] =
+        encode_subtree(&mut reader, writer, len, 0, &mut 0, true)?;
+
+    writer.seek(io::SeekFrom::End(0))?;
+
+    Ok(Digest { algorithm: HashAlgorithm::Blake3, bytes: root.to_vec() })
+}
+
+/// Encode the outboard tree of a content, in the format of `bao`.
+///
+/// The tree holds the size of the content and the chaining values of its
+/// BLAKE3 chunks of [`BAO_CHUNK_LEN`] bytes, so any of them can be verified
+/// against the root hash with [`BaoVerifier`], without the rest.
+/// Returns the BLAKE3 hash of the content and the tree.
+///
+/// ## Example
+///
+/// ```
+/// use filerune_fusion::{
+///     bao::{self, BaoVerifier},
+///     hash::{Digest, HashAlgorithm},
+/// };
+///
+/// let data: Vec<u8> = vec![7; 4096];
+///
+/// let (root, outboard): (Digest, Vec<u8>) =
+///     bao::outboard(data.as_slice(), data.len() as u64).unwrap();
+///
+/// assert_eq!(root, HashAlgorithm::Blake3.digest(&data));
+///
+/// // verify the third BLAKE3 chunk only
+/// let mut verifier: BaoVerifier<&[u8]> =
+///     BaoVerifier::new(outboard.as_slice(), &root, 2048).unwrap();
+///
+/// verifier.update(&data[2048..3072]).unwrap();
+/// ```
+pub fn outboard<R: io::Read>(
+    reader: R,
+    len: u64,
+) -> Result<(Digest, Vec<u8>), BaoError> {
+    let mut writer: io::Cursor<Vec<u8>> = io::Cursor::new(Vec::new());
+
+    let root: Digest = encode(reader, len, &mut writer)
+        .map_err(|_| BaoError::OutboardNotRead)?;
+
+    Ok((root, writer.into_inner()))
+}
+
+/// Write the outboard tree of a content to a file,
+/// through a temporary file synced by the policy.
+pub(crate) fn write_outboard<R: io::Read>(
+    path: &Path,
+    reader: R,
+    len: u64,
+    sync: SyncPolicy,
+) -> io::Result<Digest> {
+    let write_path: PathBuf = temp_path(path);
+
+    let mut writer: io::BufWriter<fs::File> =
+        io::BufWriter::new(fs::File::create(&write_path)?);
+
+    let root: Digest = encode(reader, len, &mut writer)?;
+
+    let file: fs::File = writer.into_inner().map_err(|e| e.into_error())?;
+
+    sync.sync_file(&file)?;
+
+    drop(file);
+
+    fs::rename(&write_path, path)?;
+
+    Ok(root)
+}
+
+/// Reader of the contents of the chunks, in order,
+/// with one chunk in memory at a time.
+pub(crate) struct ChunkContents<I: Iterator<Item = io::Result<Vec<u8>>>> {
+    chunks: I,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl<I: Iterator<Item = io::Result<Vec<u8>>>> ChunkContents<I> {
+    /// Create a new reader of the contents of the chunks.
+    pub(crate) fn new(chunks: I) -> Self {
+        Self { chunks, data: Vec::new(), offset: 0 }
+    }
+}
+
+impl<I: Iterator<Item = io::Result<Vec<u8>>>> io::Read for ChunkContents<I> {
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        while self.offset == self.data.len() {
+            match self.chunks.next() {
+                | Some(data) => {
+                    self.data = data?;
+                    self.offset = 0;
+                },
+                | None => return Ok(0),
+            }
+        }
+
+        let len: usize = (self.data.len() - self.offset).min(buf.len());
+
+        buf[..len].copy_from_slice(&self.data[self.offset..self.offset + len]);
+
+        self.offset += len;
+
+        Ok(len)
+    }
+}
+
+/// Check if a path is an outboard tree.
+pub(crate) fn is_outboard(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == OUTBOARD_EXTENSION)
+}
+
+/// Find the outboard tree of a directory, the first one by name.
+pub(crate) fn find_outboard(dir: &Path) -> Option<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| is_outboard(path) && path.is_file())
+        .collect();
+
+    paths.sort();
+
+    paths.into_iter().next()
+}
+
+/// Subtree expected by a verifier.
+#[derive(Debug, Clone, Copy)]
+struct Subtree {
+    cv: [u8; 32],
+    counter: u64,
+    chunks: u64,
+    root: bool,
+}
+
+/// BLAKE3 chunk being verified.
+#[derive(Debug, Clone, Copy)]
+struct Leaf {
+    cv: [u8; 32],
+    counter: u64,
+    root: bool,
+    end: u64,
+    verified: bool,
+}
+
+/// Verifier of a content streamed against an outboard tree and its root.
+///
+/// The content is fed in order from a position, such as the start of
+/// a byte range, and each BLAKE3 chunk of [`BAO_CHUNK_LEN`] bytes is
+/// verified once complete, so a corruption is detected as soon as its
+/// chunk is received. The outboard tree is read in order along with it.
+///
+/// The BLAKE3 chunks fed partially, before a position not aligned to
+/// [`BAO_CHUNK_LEN`] or at the end of a range before the end of the
+/// content, are not verified.
+///
+/// See [`outboard`] for an example.
+#[derive(Debug)]
+pub struct BaoVerifier<O: io::Read> {
+    outboard: O,
+    len: u64,
+    position: u64,
+    stack: Vec<Subtree>,
+    leaf: Option<Leaf>,
+    buffer: Vec<u8>,
+}
+
+impl<O: io::Read> BaoVerifier<O> {
+    /// Create a new verifier of the content fed from the position,
+    /// with the outboard tree read from the start and its root hash.
+    pub fn new(
+        mut outboard: O,
+        root: &Digest,
+        position: u64,
+    ) -> Result<Self, BaoError> {
+        let root: [u8; 32] = match root.algorithm {
+            | HashAlgorithm::Blake3 => root
+                .bytes
+                .as_slice()
+                .try_into()
+                .map_err(|_| BaoError::HashMismatch)?,
+            | _ => return Err(BaoError::HashMismatch),
+        };
+
+        let mut header: [u8; HEADER_LEN] = [0; HEADER_LEN];
+
+        outboard
+            .read_exact(&mut header)
+            .map_err(|_| BaoError::OutboardNotRead)?;
+
+        let len: u64 = u64::from_le_bytes(header);
+
+        if position > len {
+            return Err(BaoError::RangeExceeded);
+        }
+
+        Ok(Self {
+            outboard,
+            len,
+            position,
+            stack: vec![Subtree {
+                cv: root,
+                counter: 0,
+                chunks: chunk_count(len),
+                root: true,
+            }],
+            leaf: None,
+            buffer: Vec::with_capacity(BAO_CHUNK_LEN),
+        })
+    }
+
+    /// Get the size of the content of the tree.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Check if the content of the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the position of the next byte to feed.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Descend the tree to the BLAKE3 chunk at the position,
+    /// verifying the parent nodes on the way.
+    fn next_leaf(&mut self) -> Result<Leaf, BaoError> {
+        while let Some(subtree) = self.stack.pop() {
+            let start: u64 = subtree.counter * BAO_CHUNK_LEN as u64;
+
+            let end: u64 =
+                (start + subtree.chunks * BAO_CHUNK_LEN as u64).min(self.len);
+
+            // the subtree before the position is skipped
+            if end <= self.position && end > start {
+                let skip: u64 = (subtree.chunks - 1) * PARENT_LEN as u64;
+
+                let skipped: u64 = io::copy(
+                    &mut (&mut self.outboard).take(skip),
+                    &mut io::sink(),
+                )
+                .map_err(|_| BaoError::OutboardNotRead)?;
+
+                if skipped != skip {
+                    return Err(BaoError::OutboardInvalid);
+                }
+
+                continue;
+            }
+
+            if subtree.chunks == 1 {
+                return Ok(Leaf {
+                    cv: subtree.cv,
+                    counter: subtree.counter,
+                    root: subtree.root,
+                    end,
+                    verified: start == self.position,
+                });
+            }
+
+            let mut node: [u8; PARENT_LEN] = [0; PARENT_LEN];
+
+            self.outboard
+                .read_exact(&mut node)
+                .map_err(|_| BaoError::OutboardInvalid)?;
+
+            if parent_cv(&node, subtree.root) != subtree.cv {
+                return Err(BaoError::HashMismatch);
+            }
+
+            let left: u64 = left_chunks(subtree.chunks);
+
+            let mut cv: [u8; 32] = [0; 32];
+
+            cv.copy_from_slice(&node[32..]);
+
+            self.stack.push(Subtree {
+                cv,
+                counter: subtree.counter + left,
+                chunks: subtree.chunks - left,
+                root: false,
+            });
+
+            cv.copy_from_slice(&node[..32]);
+
+            self.stack.push(Subtree {
+                cv,
+                counter: subtree.counter,
+                chunks: left,
+                root: false,
+            });
+        }
+
+        Err(BaoError::RangeExceeded)
+    }
+
+    /// Verify the BLAKE3 chunk once complete.
+    fn close_leaf(
+        &mut self,
+        leaf: Leaf,
+    ) -> Result<(), BaoError> {
+        self.leaf = None;
+
+        if leaf.verified
+            && chunk_cv(&self.buffer, leaf.counter, leaf.root) != leaf.cv
+        {
+            return Err(BaoError::HashMismatch);
+        }
+
+        self.buffer.clear();
+
+        Ok(())
+    }
+
+    /// Feed the next part of the content, verifying the BLAKE3 chunks
+    /// completed by it.
+    pub fn update(
+        &mut self,
+        mut data: &[u8],
+    ) -> Result<(), BaoError> {
+        if data.len() as u64 > self.len - self.position {
+            return Err(BaoError::RangeExceeded);
+        }
+
+        while !data.is_empty() {
+            let leaf: Leaf = match self.leaf {
+                | Some(leaf) => leaf,
+                | None => self.next_leaf()?,
+            };
+
+            self.leaf = Some(leaf);
+
+            let take: usize =
+                (leaf.end - self.position).min(data.len() as u64) as usize;
+
+            if leaf.verified {
+                self.buffer.extend_from_slice(&data[..take]);
+            }
+
+            self.position += take as u64;
+
+            data = &data[take..];
+
+            if self.position == leaf.end {
+                self.close_leaf(leaf)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finish the verification.
+    ///
+    /// An empty content is verified against the root hash,
+    /// as it has no byte to feed.
+    pub fn finish(mut self) -> Result<(), BaoError> {
+        if self.len == 0 {
+            let leaf: Leaf = self.next_leaf()?;
+
+            return self.close_leaf(leaf);
+        }
+
+        Ok(())
+    }
+}
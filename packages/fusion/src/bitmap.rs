@@ -0,0 +1,206 @@
+/// Compact set of chunk indices, one bit per chunk.
+///
+/// The bit `i % 8` of the byte `i / 8` is set if the chunk `i` is in the set,
+/// such as the chunks present or verified. Bitmaps of peers can be combined
+/// to find the chunks to exchange.
+///
+/// With the `serde` feature, the bitmap is serialized as its length and bytes.
+///
+/// ## Example
+///
+/// ```
+/// use filerune_fusion::bitmap::ChunkBitmap;
+///
+/// let local: ChunkBitmap = ChunkBitmap::from_indices(4, [0, 1, 2]);
+/// let remote: ChunkBitmap = ChunkBitmap::from_indices(4, [2, 3]);
+///
+/// // chunks held by the remote peer only
+/// let wanted: ChunkBitmap = remote.difference(&local);
+///
+/// assert_eq!(wanted.iter().collect::<Vec<usize>>(), vec![3]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "ChunkBitmapData"))]
+pub struct ChunkBitmap {
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+/// Serialized form of the bitmap, normalized when deserialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ChunkBitmapData {
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl From<ChunkBitmapData> for ChunkBitmap {
+    fn from(data: ChunkBitmapData) -> Self {
+        Self::from_bytes(data.len, data.bytes)
+    }
+}
+
+impl ChunkBitmap {
+    /// Create a new bitmap of `len` chunks, with no chunk set.
+    pub fn new(len: usize) -> Self {
+        Self { len, bytes: vec![0; len.div_ceil(8)] }
+    }
+
+    /// Create a new bitmap of `len` chunks, with all chunks set.
+    pub fn full(len: usize) -> Self {
+        Self::from_bytes(len, vec![u8::MAX; len.div_ceil(8)])
+    }
+
+    /// Create a new bitmap of `len` chunks from its bytes.
+    ///
+    /// Missing bytes are treated as unset, bits beyond `len` are ignored.
+    pub fn from_bytes(
+        len: usize,
+        mut bytes: Vec<u8>,
+    ) -> Self {
+        bytes.resize(len.div_ceil(8), 0);
+
+        // clear the bits beyond the length
+        if len % 8 != 0 {
+            if let Some(last) = bytes.last_mut() {
+                *last &= (1 << (len % 8)) - 1;
+            }
+        }
+
+        Self { len, bytes }
+    }
+
+    /// Create a new bitmap of `len` chunks with the given indices set.
+    ///
+    /// Indices beyond `len` are ignored.
+    pub fn from_indices<I: IntoIterator<Item = usize>>(
+        len: usize,
+        indices: I,
+    ) -> Self {
+        let mut bitmap: Self = Self::new(len);
+
+        for index in indices {
+            bitmap.insert(index);
+        }
+
+        bitmap
+    }
+
+    /// Get the number of chunks of the bitmap.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the bitmap has no chunk.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the bytes of the bitmap.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Check if the chunk is set.
+    pub fn contains(
+        &self,
+        index: usize,
+    ) -> bool {
+        index < self.len && self.bytes[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    /// Set the chunk, returns `false` if the index is beyond the length.
+    pub fn insert(
+        &mut self,
+        index: usize,
+    ) -> bool {
+        if index >= self.len {
+            return false;
+        }
+
+        self.bytes[index / 8] |= 1 << (index % 8);
+
+        true
+    }
+
+    /// Unset the chunk.
+    pub fn remove(
+        &mut self,
+        index: usize,
+    ) {
+        if index < self.len {
+            self.bytes[index / 8] &= !(1 << (index % 8));
+        }
+    }
+
+    /// Get the number of chunks set.
+    pub fn count(&self) -> usize {
+        self.bytes.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Check if all chunks are set.
+    pub fn is_full(&self) -> bool {
+        self.count() == self.len
+    }
+
+    /// Iterate over the indices of the chunks set, in order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|i| self.contains(*i))
+    }
+
+    /// Iterate over the indices of the chunks not set, in order.
+    pub fn missing(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|i| !self.contains(*i))
+    }
+
+    /// Get the chunks set in either bitmap.
+    ///
+    /// The result has the length of the longer bitmap.
+    pub fn union(
+        &self,
+        other: &Self,
+    ) -> Self {
+        self.combine(other, self.len.max(other.len), |a, b| a | b)
+    }
+
+    /// Get the chunks set in both bitmaps.
+    ///
+    /// The result has the length of the shorter bitmap.
+    pub fn intersection(
+        &self,
+        other: &Self,
+    ) -> Self {
+        self.combine(other, self.len.min(other.len), |a, b| a & b)
+    }
+
+    /// Get the chunks set in this bitmap but not in the other.
+    ///
+    /// The result has the length of this bitmap.
+    pub fn difference(
+        &self,
+        other: &Self,
+    ) -> Self {
+        self.combine(other, self.len, |a, b| a & !b)
+    }
+
+    /// Combine the bytes of two bitmaps into a bitmap of `len` chunks.
+    fn combine(
+        &self,
+        other: &Self,
+        len: usize,
+        op: impl Fn(u8, u8) -> u8,
+    ) -> Self {
+        let bytes: Vec<u8> = (0..len.div_ceil(8))
+            .map(|i| {
+                op(
+                    self.bytes.get(i).copied().unwrap_or(0),
+                    other.bytes.get(i).copied().unwrap_or(0),
+                )
+            })
+            .collect();
+
+        Self::from_bytes(len, bytes)
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// Token to cancel a running process cooperatively.
+///
+/// The process checks the token between chunks,
+/// and cleans up the partially written output once it is cancelled.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{cancel::CancellationToken, split::Split};
+///
+/// let token: CancellationToken = CancellationToken::new();
+///
+/// let process: Split = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .cancellation_token(token.clone());
+///
+/// // from another thread...
+/// token.cancel();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a new cancellation token.
+    pub fn new() -> Self {
+        Self { cancelled: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Cancel the processes holding this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if the token is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
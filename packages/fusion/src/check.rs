@@ -1,6 +1,33 @@
 use std::{
     fs,
+    io::{self, Read as _},
     path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+#[cfg(feature = "camino")]
+use camino::Utf8Path;
+
+#[cfg(feature = "compression")]
+use crate::codec::Codec;
+
+use crate::{
+    BUFFER_CAPACITY_DEFAULT,
+    bitmap::ChunkBitmap,
+    cancel::CancellationToken,
+    chunk::ChunkNaming,
+    hash::{Digest, HashAlgorithm, Hasher},
+    header::{self, ChunkFormat, ChunkHeader},
+    par2,
+    parity::{self, ParityError},
+    progress::{Progress, ProgressGuard},
+    report::Report,
+    split::{Split, SplitResult},
+    store::{ChunkStore, StoreError},
+    sync::SyncPolicy,
+    sys::{long_path, long_path_shared, read_options},
+    warning::{Warning, WarningHandler},
 };
 
 /// Run asynchronously with `async_std` feature.
@@ -49,11 +76,131 @@ pub struct MissingChunks {
     pub missing: Vec<usize>,
 }
 
+/// Check process mismatched chunks error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MismatchedChunks {
+    pub mismatched: Vec<usize>,
+}
+
 /// Check process size mismatch error.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SizeMismatch {
-    pub expected: usize,
-    pub actual: usize,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+/// Status of a chunk in the check process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkStatus {
+    /// The index of the chunk.
+    pub index: usize,
+    /// The expected path of the chunk.
+    pub path: PathBuf,
+    /// The size of the chunk in bytes, `0` if not found.
+    pub size: usize,
+    /// Whether the chunk was found.
+    pub found: bool,
+    /// The digest of the chunk, if found and hashed.
+    pub hash: Option<Digest>,
+    /// The expected stored size of the chunk in bytes, if given.
+    pub expected_size: Option<usize>,
+}
+
+#[cfg(feature = "camino")]
+impl ChunkStatus {
+    /// Get the expected path of the chunk as a UTF-8 path,
+    /// `None` if the path is not valid UTF-8.
+    pub fn utf8_path(&self) -> Option<&Utf8Path> {
+        Utf8Path::from_path(&self.path)
+    }
+}
+
+/// Report of the check process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckReport {
+    /// The expected size of the original file in bytes.
+    pub file_size: u64,
+    /// The total size of the chunks found in bytes.
+    pub actual_size: u64,
+    /// The status of each chunk, in order.
+    pub chunks: Vec<ChunkStatus>,
+    /// The digest over the chunks found, if hashed.
+    ///
+    /// It is computed from the index and the digest of each chunk found,
+    /// in order, so peers holding the same chunks get the same digest.
+    pub aggregate_hash: Option<Digest>,
+}
+
+impl CheckReport {
+    /// Get the indices of the missing chunks.
+    pub fn missing(&self) -> Vec<usize> {
+        self.chunks.iter().filter(|c| !c.found).map(|c| c.index).collect()
+    }
+
+    /// Get the indices of the chunks found with an unexpected stored size.
+    pub fn mismatched(&self) -> Vec<usize> {
+        self.chunks
+            .iter()
+            .filter(|c| c.found && c.expected_size.is_some_and(|s| s != c.size))
+            .map(|c| c.index)
+            .collect()
+    }
+
+    /// Get the bitmap of the chunks found.
+    pub fn bitmap(&self) -> ChunkBitmap {
+        ChunkBitmap::from_indices(
+            self.chunks.len(),
+            self.chunks.iter().filter(|c| c.found).map(|c| c.index),
+        )
+    }
+
+    /// Check if all chunks are found and match the file size.
+    pub fn is_complete(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Compute the digest over the chunks found.
+    pub(crate) fn aggregate(
+        algorithm: HashAlgorithm,
+        chunks: &[ChunkStatus],
+    ) -> Digest {
+        let mut hasher: Hasher = algorithm.hasher();
+
+        for chunk in chunks {
+            if let Some(ref hash) = chunk.hash {
+                hasher.update(&(chunk.index as u64).to_le_bytes());
+                hasher.update(&hash.bytes);
+            }
+        }
+
+        hasher.finalize()
+    }
+
+    /// Validate the report as the result of the check process.
+    pub(crate) fn validate(&self) -> Result<(), CheckError> {
+        let missing: Vec<usize> = self.missing();
+
+        if !missing.is_empty() {
+            return Err(CheckError::MissingChunks(MissingChunks { missing }));
+        }
+
+        let mismatched: Vec<usize> = self.mismatched();
+
+        if !mismatched.is_empty() {
+            return Err(CheckError::ChunkSizeMismatch(MismatchedChunks {
+                mismatched,
+            }));
+        }
+
+        if self.file_size != self.actual_size {
+            return Err(CheckError::SizeMismatch(SizeMismatch {
+                expected: self.file_size,
+                actual: self.actual_size,
+            }));
+        }
+
+        Ok(())
+    }
 }
 
 /// Check process error enum.
@@ -67,7 +214,12 @@ pub enum CheckError {
     FileSizeNotSet,
     TotalChunksNotSet,
     MissingChunks(MissingChunks),
+    ChunkSizeMismatch(MismatchedChunks),
     SizeMismatch(SizeMismatch),
+    ChunksNotRepaired,
+    WarningRaised,
+    Cancelled,
+    DeadlineExceeded,
 }
 
 impl CheckError {
@@ -82,7 +234,12 @@ impl CheckError {
             | Self::FileSizeNotSet => "file_size_not_set",
             | Self::TotalChunksNotSet => "total_chunks_not_set",
             | Self::MissingChunks(_) => "missing_chunks",
+            | Self::ChunkSizeMismatch(_) => "chunk_size_mismatch",
             | Self::SizeMismatch(_) => "size_mismatch",
+            | Self::ChunksNotRepaired => "chunks_not_repaired",
+            | Self::WarningRaised => "warning_raised",
+            | Self::Cancelled => "cancelled",
+            | Self::DeadlineExceeded => "deadline_exceeded",
         }
     }
 
@@ -104,9 +261,20 @@ impl CheckError {
             | Self::MissingChunks(_) => {
                 "Some of the chunks are missing to merge the file."
             },
+            | Self::ChunkSizeMismatch(_) => {
+                "Some of the chunks are not of the expected stored size."
+            },
             | Self::SizeMismatch(_) => {
                 "The actual file size is not equal the input file size."
             },
+            | Self::ChunksNotRepaired => {
+                "The missing or corrupted chunks could not be repaired."
+            },
+            | Self::WarningRaised => "A warning was raised in strict mode.",
+            | Self::Cancelled => "The check process was cancelled.",
+            | Self::DeadlineExceeded => {
+                "The deadline of the check process was exceeded."
+            },
         }
     }
 
@@ -146,35 +314,114 @@ impl CheckError {
 /// ```
 #[derive(Debug, Clone)]
 pub struct Check {
-    pub in_dir: Option<PathBuf>,
-    pub file_size: Option<usize>,
+    pub in_dir: Option<Arc<Path>>,
+    pub file_size: Option<u64>,
     pub total_chunks: Option<usize>,
+    pub naming: ChunkNaming,
+    pub format: ChunkFormat,
+    pub cancellation_token: Option<CancellationToken>,
+    pub deadline: Option<Instant>,
+    pub progress: Option<Progress>,
+    pub on_warning: Option<WarningHandler>,
+    pub concurrency: usize,
+    pub hash_algorithm: Option<HashAlgorithm>,
+    pub chunk_sizes: Option<Vec<usize>>,
+    #[cfg(feature = "compression")]
+    pub compression: Option<Codec>,
+    pub repair: bool,
+    pub strict: bool,
 }
 
 impl Check {
     /// Create a new check process.
     pub fn new() -> Self {
-        Self { in_dir: None, file_size: None, total_chunks: None }
+        Self {
+            in_dir: None,
+            file_size: None,
+            total_chunks: None,
+            naming: ChunkNaming::new(),
+            format: ChunkFormat::Raw,
+            cancellation_token: None,
+            deadline: None,
+            progress: None,
+            on_warning: None,
+            concurrency: 1,
+            hash_algorithm: None,
+            chunk_sizes: None,
+            #[cfg(feature = "compression")]
+            compression: None,
+            repair: false,
+            strict: false,
+        }
     }
 
-    /// Create a new check process from an existing one.
+    /// Create a new check process from an existing one,
+    /// or from a split process as converted from [`&Split`](Split).
     pub fn from<P: Into<Check>>(process: P) -> Self {
         process.into()
     }
 
+    /// Create a new check process of the chunks written by a split run.
+    ///
+    /// The input directory, the naming scheme, the format and the codec
+    /// are the ones of the split process, and the size of the file and
+    /// the total number of chunks the ones of its result.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{
+    ///     check::{Check, CheckReport},
+    ///     split::{Split, SplitResult},
+    /// };
+    ///
+    /// let split: Split = Split::new()
+    ///     .in_file(PathBuf::from("path").join("to").join("file"))
+    ///     .out_dir(PathBuf::from("path").join("to").join("dir"));
+    ///
+    /// let result: SplitResult = split.run().unwrap();
+    ///
+    /// let report: CheckReport =
+    ///     Check::from_split(&split, &result).run().unwrap();
+    /// ```
+    pub fn from_split(
+        process: &Split,
+        result: &SplitResult,
+    ) -> Self {
+        Self {
+            file_size: Some(result.file_size),
+            total_chunks: Some(result.total_chunks),
+            ..Self::from(process)
+        }
+    }
+
     /// Set the input directory.
     pub fn in_dir<InDir: AsRef<Path>>(
         mut self,
         path: InDir,
     ) -> Self {
-        self.in_dir = Some(path.as_ref().to_path_buf());
+        self.in_dir = Some(long_path(path.as_ref()));
+        self
+    }
+
+    /// Set the input directory with a shared path.
+    ///
+    /// Unlike [`Check::in_dir`], an [`Arc<Path>`] is stored without copying,
+    /// unless extended on Windows.
+    pub fn in_dir_shared<InDir: Into<Arc<Path>>>(
+        mut self,
+        path: InDir,
+    ) -> Self {
+        self.in_dir = Some(long_path_shared(path.into()));
         self
     }
 
     /// Set the size of the original file in bytes.
     pub fn file_size(
         mut self,
-        size: usize,
+        size: u64,
     ) -> Self {
         self.file_size = Some(size);
         self
@@ -189,8 +436,583 @@ impl Check {
         self
     }
 
+    /// Set the maximum number of chunks to check concurrently.
+    ///
+    /// The report lists the chunks by index, the same whatever the
+    /// concurrency. It only applies to the asynchronous runs.
+    ///
+    /// By default, it is `1`.
+    pub fn concurrency(
+        mut self,
+        concurrency: usize,
+    ) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Set the algorithm to hash the chunks found.
+    ///
+    /// The chunks are read entirely to compute the digests,
+    /// and the report gets an aggregate digest of the chunks found.
+    ///
+    /// By default, the chunks are not hashed.
+    pub fn hash_algorithm(
+        mut self,
+        algorithm: HashAlgorithm,
+    ) -> Self {
+        self.hash_algorithm = Some(algorithm);
+        self
+    }
+
+    /// Set the expected stored size of each chunk in bytes, in order.
+    ///
+    /// Chunks found with another size fail the check with
+    /// [`CheckError::ChunkSizeMismatch`], so a truncated chunk is caught
+    /// even if the total size matches. The sizes can be taken from the
+    /// chunks of a split result.
+    ///
+    /// By default, only the total size is checked.
+    pub fn chunk_sizes<I: IntoIterator<Item = usize>>(
+        mut self,
+        sizes: I,
+    ) -> Self {
+        self.chunk_sizes = Some(sizes.into_iter().collect());
+        self
+    }
+
+    /// Set the codec to decompress the chunks compressed by
+    /// [`Split::compression`](crate::split::Split::compression).
+    ///
+    /// The sizes and the digests of the chunks are of the decompressed
+    /// content, so they match the ones of the chunks not compressed.
+    ///
+    /// By default, the chunks are not compressed.
+    #[cfg(feature = "compression")]
+    pub fn compression(
+        mut self,
+        codec: Codec,
+    ) -> Self {
+        self.compression = Some(codec);
+        self
+    }
+
+    /// Set whether to repair the chunks from the parity chunks
+    /// written by [`Split::parity_chunks`](crate::split::Split::parity_chunks)
+    /// and the PAR2 files written by
+    /// [`Split::par2_recovery`](crate::split::Split::par2_recovery)
+    /// before checking.
+    ///
+    /// The missing or corrupted chunks are reconstructed in the input
+    /// directory and reported as [`Warning::ChunkRepaired`]. If more chunks
+    /// are lost than parity chunks are left, the chunks are checked as is.
+    ///
+    /// By default, it is `false`.
+    pub fn repair(
+        mut self,
+        repair: bool,
+    ) -> Self {
+        self.repair = repair;
+        self
+    }
+
+    /// Set whether to fail on the first warning of the process.
+    ///
+    /// The warning is emitted to the handler set by
+    /// [`Check::on_warning`] before the process fails with
+    /// [`CheckError::WarningRaised`], so conditions otherwise tolerated,
+    /// such as stray entries or a reflink falling back to a copy,
+    /// stop the process.
+    ///
+    /// By default, it is `false`.
+    pub fn strict(
+        mut self,
+        strict: bool,
+    ) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set the naming scheme of the chunk files.
+    ///
+    /// By default, chunks are named by their index only.
+    pub fn naming(
+        mut self,
+        naming: ChunkNaming,
+    ) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Set the prefix of the chunk file names.
+    pub fn chunk_prefix<Prefix: Into<String>>(
+        mut self,
+        prefix: Prefix,
+    ) -> Self {
+        self.naming = self.naming.prefix(prefix);
+        self
+    }
+
+    /// Set the extension of the chunk file names.
+    pub fn chunk_extension<Extension: AsRef<str>>(
+        mut self,
+        extension: Extension,
+    ) -> Self {
+        self.naming = self.naming.extension(extension);
+        self
+    }
+
+    /// Set the minimum width of the chunk index, padded with zeros.
+    pub fn pad_width(
+        mut self,
+        width: usize,
+    ) -> Self {
+        self.naming = self.naming.pad_width(width);
+        self
+    }
+
+    /// Set the index of the first chunk in the file names.
+    pub fn start_index(
+        mut self,
+        index: usize,
+    ) -> Self {
+        self.naming = self.naming.start_index(index);
+        self
+    }
+
+    /// Set the format of the chunk files, as split by
+    /// [`Split::format`](crate::split::Split::format).
+    ///
+    /// With [`ChunkFormat::Header`], the chunks are found by the index in
+    /// their headers instead of their names, and the file size and the total
+    /// number of chunks default to the ones of the headers. A chunk not
+    /// matching the digest of its header is reported as missing, with
+    /// [`Warning::CorruptedChunk`].
+    ///
+    /// By default, it is [`ChunkFormat::Raw`].
+    pub fn format(
+        mut self,
+        format: ChunkFormat,
+    ) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the handle to poll the progress of the process.
+    pub fn progress(
+        mut self,
+        progress: Progress,
+    ) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Set the token to cancel the process.
+    pub fn cancellation_token(
+        mut self,
+        token: CancellationToken,
+    ) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Set the deadline of the process, checked between chunks.
+    ///
+    /// Once passed, the process fails with [`CheckError::DeadlineExceeded`].
+    pub fn deadline(
+        mut self,
+        deadline: Instant,
+    ) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the handler to receive the warnings of the process.
+    pub fn on_warning<F: Fn(&Warning) + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_warning = Some(WarningHandler::new(handler));
+        self
+    }
+
+    /// Emit a warning to the handler of the process.
+    ///
+    /// In strict mode, the process fails once the warning is emitted.
+    pub(crate) fn warn(
+        &self,
+        warning: Warning,
+    ) -> Result<(), CheckError> {
+        if let Some(ref handler) = self.on_warning {
+            handler.emit(&warning);
+        }
+
+        match self.strict {
+            | true => Err(CheckError::WarningRaised),
+            | false => Ok(()),
+        }
+    }
+
+    /// Get the expected stored size of a chunk, if given.
+    pub(crate) fn expected_size(
+        &self,
+        index: usize,
+    ) -> Option<usize> {
+        self.chunk_sizes.as_ref()?.get(index).copied()
+    }
+
+    /// Check if the chunks are encoded, compressed or with a header.
+    pub(crate) fn is_encoded(&self) -> bool {
+        #[cfg(feature = "compression")]
+        if self.compression.is_some() {
+            return true;
+        }
+
+        self.format != ChunkFormat::Raw
+    }
+
+    /// Locate the chunks of the input directory by their headers,
+    /// sorted by index, if the chunks have headers.
+    ///
+    /// The chunks of another chunk set than the first one are ignored.
+    pub(crate) fn locate_chunks(
+        &self,
+        in_dir: &Path,
+    ) -> Result<Vec<(ChunkHeader, PathBuf)>, CheckError> {
+        if self.format != ChunkFormat::Header {
+            return Ok(Vec::new());
+        }
+
+        let files: Vec<PathBuf> = fs::read_dir(in_dir)
+            .map_err(|_| CheckError::InFileNotRead)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+
+        let (mut headers, _) = header::read_headers(files)
+            .map_err(|_| CheckError::InFileNotRead)?;
+
+        if let Some((set, _)) = headers.first().cloned() {
+            let (chunks, others): (Vec<_>, Vec<_>) =
+                headers.into_iter().partition(|(h, _)| h.is_same_set(&set));
+
+            for (_, path) in others {
+                self.warn(Warning::IgnoredEntry(path))?;
+            }
+
+            headers = chunks;
+        }
+
+        Ok(headers)
+    }
+
+    /// Get the path of a chunk, located by its header if any,
+    /// else by its name.
+    pub(crate) fn chunk_path(
+        &self,
+        in_dir: &Path,
+        located: &[(ChunkHeader, PathBuf)],
+        index: usize,
+    ) -> PathBuf {
+        match located.binary_search_by_key(&index, |(h, _)| h.index) {
+            | Ok(i) => located[i].1.clone(),
+            | Err(_) => in_dir.join(self.naming.file_name(index)),
+        }
+    }
+
+    /// Strip the header of a chunk as stored, if the chunks have headers.
+    ///
+    /// Returns `None` if the chunk does not match the digest of its header,
+    /// reported as [`Warning::CorruptedChunk`].
+    pub(crate) fn strip_header(
+        &self,
+        path: &Path,
+        data: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, CheckError> {
+        if self.format != ChunkFormat::Header {
+            return Ok(Some(data));
+        }
+
+        match header::decode(data) {
+            | Ok(data) => Ok(Some(data)),
+            | Err(_) => {
+                self.warn(Warning::CorruptedChunk(path.to_path_buf()))?;
+
+                Ok(None)
+            },
+        }
+    }
+
+    /// Decode the content of a chunk as stored, decompressed if enabled.
+    pub(crate) fn decode(
+        &self,
+        data: Vec<u8>,
+    ) -> io::Result<Vec<u8>> {
+        #[cfg(feature = "compression")]
+        if let Some(codec) = self.compression {
+            return codec.decompress(&data);
+        }
+
+        Ok(data)
+    }
+
+    /// Read the content of a chunk file of the stored size `len`.
+    ///
+    /// Returns the size of the content and its digest, if hashed,
+    /// `None` if the chunk does not match the digest of its header.
+    fn read_chunk(
+        &self,
+        path: &Path,
+        mut file: fs::File,
+        len: u64,
+    ) -> Result<Option<(usize, Option<Digest>)>, CheckError> {
+        // a chunk larger than the address space could not be merged
+        let len: usize =
+            usize::try_from(len).map_err(|_| CheckError::InFileNotRead)?;
+
+        // the digest is of the decoded content
+        if self.is_encoded() {
+            let mut data: Vec<u8> = Vec::with_capacity(len);
+
+            file.read_to_end(&mut data)
+                .map_err(|_| CheckError::InFileNotRead)?;
+
+            let data: Vec<u8> = match self.strip_header(path, data)? {
+                | Some(data) => {
+                    self.decode(data).map_err(|_| CheckError::InFileNotRead)?
+                },
+                | None => return Ok(None),
+            };
+
+            let hash: Option<Digest> =
+                self.hash_algorithm.map(|a| a.digest(&data));
+
+            return Ok(Some((data.len(), hash)));
+        }
+
+        let hash: Option<Digest> = match self.hash_algorithm {
+            | Some(algorithm) => {
+                let mut hasher: Hasher = algorithm.hasher();
+
+                let mut buffer: Vec<u8> = vec![0; BUFFER_CAPACITY_DEFAULT];
+
+                loop {
+                    let read: usize = file
+                        .read(&mut buffer)
+                        .map_err(|_| CheckError::InFileNotRead)?;
+
+                    if read == 0 {
+                        break;
+                    }
+
+                    hasher.update(&buffer[..read]);
+                }
+
+                Some(hasher.finalize())
+            },
+            | None => None,
+        };
+
+        Ok(Some((len, hash)))
+    }
+
+    /// Repair the chunks of the input directory from the parity chunks,
+    /// if enabled.
+    pub(crate) fn repair_chunks(
+        &self,
+        in_dir: &Path,
+    ) -> Result<(), CheckError> {
+        if !self.repair {
+            return Ok(());
+        }
+
+        let mut repaired: Vec<usize> = Vec::new();
+
+        for result in [
+            parity::repair(in_dir, in_dir, &self.naming, SyncPolicy::None),
+            par2::repair(in_dir, in_dir, &self.naming, SyncPolicy::None),
+        ] {
+            match result {
+                | Ok(indices) => repaired.extend(indices),
+                // the lost chunks are reported by the check
+                | Err(ParityError::Unrecoverable) => {},
+                | Err(ParityError::ChunkNotRead) => {
+                    return Err(CheckError::InFileNotRead);
+                },
+                | Err(ParityError::ChunkNotWritten) => {
+                    return Err(CheckError::ChunksNotRepaired);
+                },
+            }
+        }
+
+        for index in repaired {
+            self.warn(Warning::ChunkRepaired(
+                in_dir.join(self.naming.file_name(index)),
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Check if the process is cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation_token.as_ref().is_some_and(|t| t.is_cancelled())
+    }
+
+    /// Check if the deadline of the process is exceeded.
+    pub(crate) fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
     /// Run the check process.
-    pub fn run(&self) -> Result<(), CheckError> {
+    pub fn run(&self) -> Result<CheckReport, CheckError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let report: CheckReport = self.check_chunks(&progress)?;
+
+        report.validate()?;
+
+        progress.finish();
+
+        Ok(report)
+    }
+
+    /// Run the check process with the chunks read from a store,
+    /// such as a bucket, instead of the input directory.
+    ///
+    /// The file size and the total number of chunks must be set.
+    /// The chunks are read whole by index, and the path of the status of
+    /// each chunk is its file name by the naming scheme. The input directory
+    /// and the repair option are ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use filerune_fusion::{
+    ///     check::{Check, CheckReport},
+    ///     store::MemoryStore,
+    /// };
+    ///
+    /// let store: MemoryStore = MemoryStore::new(); // chunks from split...
+    ///
+    /// let report: CheckReport = Check::new()
+    ///     .file_size(0) // result from split function...
+    ///     .total_chunks(0) // result from split function...
+    ///     .run_from_store(&store)
+    ///     .unwrap();
+    /// ```
+    pub fn run_from_store<S: ChunkStore>(
+        &self,
+        store: &S,
+    ) -> Result<CheckReport, CheckError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let file_size: u64 =
+            self.file_size.ok_or(CheckError::FileSizeNotSet)?;
+
+        let total_chunks: usize =
+            self.total_chunks.ok_or(CheckError::TotalChunksNotSet)?;
+
+        let mut actual_size: u64 = 0;
+        let mut chunks: Vec<ChunkStatus> = Vec::with_capacity(total_chunks);
+
+        for i in 0..total_chunks {
+            if self.is_cancelled() {
+                return Err(CheckError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(CheckError::DeadlineExceeded);
+            }
+
+            let path: PathBuf = PathBuf::from(self.naming.file_name(i));
+
+            let data: Option<Vec<u8>> = match store.read(i) {
+                | Ok(data) => match self.strip_header(&path, data)? {
+                    | Some(data) => Some(
+                        self.decode(data)
+                            .map_err(|_| CheckError::InFileNotRead)?,
+                    ),
+                    | None => None,
+                },
+                | Err(StoreError::ChunkNotFound) => None,
+                | Err(_) => return Err(CheckError::InFileNotRead),
+            };
+
+            let data: Vec<u8> = match data {
+                | Some(data) => data,
+                | None => {
+                    chunks.push(ChunkStatus {
+                        index: i,
+                        path,
+                        size: 0,
+                        found: false,
+                        hash: None,
+                        expected_size: self.expected_size(i),
+                    });
+                    continue;
+                },
+            };
+
+            progress.advance(i, data.len());
+
+            actual_size += data.len() as u64;
+
+            chunks.push(ChunkStatus {
+                index: i,
+                path,
+                size: data.len(),
+                found: true,
+                hash: self.hash_algorithm.map(|a| a.digest(&data)),
+                expected_size: self.expected_size(i),
+            });
+        }
+
+        let aggregate_hash: Option<Digest> = self
+            .hash_algorithm
+            .map(|algorithm| CheckReport::aggregate(algorithm, &chunks));
+
+        let report: CheckReport =
+            CheckReport { file_size, actual_size, chunks, aggregate_hash };
+
+        report.validate()?;
+
+        progress.finish();
+
+        Ok(report)
+    }
+
+    /// Run the check process and get the report as a JSON object.
+    ///
+    /// See [`Report`] for the schema of the report.
+    pub fn report_json(&self) -> String {
+        let started: Instant = Instant::now();
+
+        let result: Result<CheckReport, CheckError> = self.run();
+
+        Report::check(self, &result, started.elapsed()).to_json()
+    }
+
+    /// Run the check process without failing on missing chunks
+    /// or mismatched size, to get the status of each chunk.
+    pub fn report(&self) -> Result<CheckReport, CheckError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let report: CheckReport = self.check_chunks(&progress)?;
+
+        progress.finish();
+
+        Ok(report)
+    }
+
+    /// Collect the status of each chunk.
+    fn check_chunks(
+        &self,
+        progress: &ProgressGuard,
+    ) -> Result<CheckReport, CheckError> {
         let in_dir: &Path = match self.in_dir {
             | Some(ref p) => {
                 let p: &Path = p.as_ref();
@@ -210,50 +1032,103 @@ impl Check {
             | None => return Err(CheckError::InDirNotSet),
         };
 
-        let file_size: usize =
-            self.file_size.ok_or(CheckError::FileSizeNotSet)?;
+        let located: Vec<(ChunkHeader, PathBuf)> =
+            self.locate_chunks(in_dir)?;
 
-        let total_chunks: usize =
-            self.total_chunks.ok_or(CheckError::TotalChunksNotSet)?;
+        let set: Option<&ChunkHeader> = located.first().map(|(h, _)| h);
+
+        let file_size: u64 = self
+            .file_size
+            .or(set.and_then(|h| h.file_size))
+            .ok_or(CheckError::FileSizeNotSet)?;
 
-        let mut actual_size: usize = 0;
-        let mut missing: Vec<usize> = Vec::with_capacity(total_chunks);
+        let total_chunks: usize = self
+            .total_chunks
+            .or(set.and_then(|h| h.total_chunks))
+            .ok_or(CheckError::TotalChunksNotSet)?;
+
+        self.repair_chunks(in_dir)?;
+
+        let mut actual_size: u64 = 0;
+        let mut chunks: Vec<ChunkStatus> = Vec::with_capacity(total_chunks);
 
         for i in 0..total_chunks {
-            let target_file: PathBuf = in_dir.join(i.to_string());
+            if self.is_cancelled() {
+                return Err(CheckError::Cancelled);
+            }
 
-            let file: fs::File =
-                match fs::OpenOptions::new().read(true).open(&target_file) {
-                    | Ok(f) => f,
-                    | Err(_) => {
-                        missing.push(i);
-                        continue;
-                    },
-                };
+            if self.is_expired() {
+                return Err(CheckError::DeadlineExceeded);
+            }
+
+            let target_file: PathBuf = self.chunk_path(in_dir, &located, i);
+
+            let file: fs::File = match read_options().open(&target_file) {
+                | Ok(f) => f,
+                | Err(_) => {
+                    chunks.push(ChunkStatus {
+                        index: i,
+                        path: target_file,
+                        size: 0,
+                        found: false,
+                        hash: None,
+                        expected_size: self.expected_size(i),
+                    });
+                    continue;
+                },
+            };
 
             let metadata: fs::Metadata =
                 file.metadata().map_err(|_| CheckError::InFileNotRead)?;
 
             if !metadata.is_file() {
-                missing.push(i);
+                self.warn(Warning::IgnoredEntry(target_file.clone()))?;
+                chunks.push(ChunkStatus {
+                    index: i,
+                    path: target_file,
+                    size: 0,
+                    found: false,
+                    hash: None,
+                    expected_size: self.expected_size(i),
+                });
                 continue;
             }
 
-            actual_size += metadata.len() as usize;
-        }
+            let (size, hash) =
+                match self.read_chunk(&target_file, file, metadata.len())? {
+                    | Some(stat) => stat,
+                    | None => {
+                        chunks.push(ChunkStatus {
+                            index: i,
+                            path: target_file,
+                            size: 0,
+                            found: false,
+                            hash: None,
+                            expected_size: self.expected_size(i),
+                        });
+                        continue;
+                    },
+                };
 
-        if !missing.is_empty() {
-            return Err(CheckError::MissingChunks(MissingChunks { missing }));
-        }
+            progress.advance(i, size);
 
-        if file_size != actual_size {
-            return Err(CheckError::SizeMismatch(SizeMismatch {
-                expected: file_size,
-                actual: actual_size,
-            }));
+            actual_size += size as u64;
+
+            chunks.push(ChunkStatus {
+                index: i,
+                path: target_file,
+                size,
+                found: true,
+                hash,
+                expected_size: self.expected_size(i),
+            });
         }
 
-        Ok(())
+        let aggregate_hash: Option<Digest> = self
+            .hash_algorithm
+            .map(|algorithm| CheckReport::aggregate(algorithm, &chunks));
+
+        Ok(CheckReport { file_size, actual_size, chunks, aggregate_hash })
     }
 }
 
@@ -262,3 +1137,22 @@ impl Default for Check {
         Self::new()
     }
 }
+
+impl From<&Split> for Check {
+    /// Create a check process of the chunks written by a split process,
+    /// with its output directory, naming scheme, format and codec.
+    ///
+    /// The size of the file is not known before the split runs,
+    /// use [`Check::from_split`] with the result instead.
+    fn from(process: &Split) -> Self {
+        Self {
+            in_dir: process.out_dir.clone(),
+            total_chunks: process.total_chunks,
+            naming: process.naming.clone(),
+            format: process.format,
+            #[cfg(feature = "compression")]
+            compression: process.compression,
+            ..Self::new()
+        }
+    }
+}
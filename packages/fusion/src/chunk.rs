@@ -0,0 +1,430 @@
+use std::{
+    ffi::OsString,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[cfg(feature = "camino")]
+use camino::{Utf8Path, Utf8PathBuf};
+
+/// Information of a chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// The index of the chunk.
+    pub index: usize,
+    /// The path of the chunk.
+    pub path: PathBuf,
+    /// The size of the chunk in bytes.
+    pub size: usize,
+}
+
+#[cfg(feature = "camino")]
+impl ChunkInfo {
+    /// Get the path of the chunk as a UTF-8 path,
+    /// `None` if the path is not valid UTF-8.
+    pub fn utf8_path(&self) -> Option<&Utf8Path> {
+        Utf8Path::from_path(&self.path)
+    }
+}
+
+/// Get the temporary path to write a file to, before renaming it to the path.
+pub(crate) fn temp_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path: &Path = path.as_ref();
+
+    let mut file_name: OsString =
+        path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+
+    file_name.push(".tmp");
+
+    path.with_file_name(file_name)
+}
+
+/// The files written by the operating systems in any directory.
+const SYSTEM_FILES: [&str; 3] = [".DS_Store", "Thumbs.db", "desktop.ini"];
+
+/// Check if the file is written by the operating system, not a chunk,
+/// such as `.DS_Store` or the `._` resource forks of macOS.
+pub(crate) fn is_system_file<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().file_name().and_then(|n| n.to_str()).is_some_and(|name| {
+        SYSTEM_FILES.iter().any(|f| name.eq_ignore_ascii_case(f))
+            || name.starts_with("._")
+    })
+}
+
+/// Handler to receive a chunk once it is written.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::split::Split;
+///
+/// Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .on_first_chunk(|chunk| println!("{}", chunk.path.display()))
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct ChunkHandler {
+    handler: Arc<dyn Fn(&ChunkInfo) + Send + Sync>,
+}
+
+impl ChunkHandler {
+    /// Create a new chunk handler.
+    pub fn new<F: Fn(&ChunkInfo) + Send + Sync + 'static>(handler: F) -> Self {
+        Self { handler: Arc::new(handler) }
+    }
+
+    /// Emit a chunk to the handler.
+    pub fn emit(
+        &self,
+        chunk: &ChunkInfo,
+    ) {
+        (self.handler)(chunk)
+    }
+}
+
+impl fmt::Debug for ChunkHandler {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("ChunkHandler").finish_non_exhaustive()
+    }
+}
+
+/// Naming scheme of the chunk files.
+///
+/// By default, chunks are named by their index only, such as `0`, `1`, `2`.
+///
+/// ## Example
+///
+/// ```
+/// use filerune_fusion::chunk::ChunkNaming;
+///
+/// let naming: ChunkNaming = ChunkNaming::new()
+///     .prefix("part-")
+///     .extension("bin")
+///     .pad_width(3)
+///     .start_index(1);
+///
+/// assert_eq!(naming.file_name(0), "part-001.bin");
+/// assert_eq!(naming.parse("part-001.bin"), Some(0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkNaming {
+    /// The prefix of the chunk file names.
+    pub prefix: String,
+    /// The extension of the chunk file names, without the leading dot.
+    pub extension: String,
+    /// The minimum width of the index, padded with zeros.
+    pub pad_width: usize,
+    /// The index of the first chunk in the file names.
+    pub start_index: usize,
+}
+
+impl ChunkNaming {
+    /// Create a new naming scheme.
+    pub fn new() -> Self {
+        Self {
+            prefix: String::new(),
+            extension: String::new(),
+            pad_width: 0,
+            start_index: 0,
+        }
+    }
+
+    /// Set the prefix of the chunk file names.
+    pub fn prefix<Prefix: Into<String>>(
+        mut self,
+        prefix: Prefix,
+    ) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set the extension of the chunk file names.
+    pub fn extension<Extension: AsRef<str>>(
+        mut self,
+        extension: Extension,
+    ) -> Self {
+        self.extension = extension.as_ref().trim_start_matches('.').to_string();
+        self
+    }
+
+    /// Set the minimum width of the index, padded with zeros.
+    pub fn pad_width(
+        mut self,
+        width: usize,
+    ) -> Self {
+        self.pad_width = width;
+        self
+    }
+
+    /// Set the index of the first chunk in the file names.
+    pub fn start_index(
+        mut self,
+        index: usize,
+    ) -> Self {
+        self.start_index = index;
+        self
+    }
+
+    /// Get the file name of a chunk by its index.
+    pub fn file_name(
+        &self,
+        index: usize,
+    ) -> String {
+        let mut name: String = format!(
+            "{}{:0width$}",
+            self.prefix,
+            index + self.start_index,
+            width = self.pad_width
+        );
+
+        if !self.extension.is_empty() {
+            name.push('.');
+            name.push_str(&self.extension);
+        }
+
+        name
+    }
+
+    /// Parse the index of a chunk from its file name.
+    ///
+    /// Returns `None` if the file name does not follow the naming scheme.
+    pub fn parse(
+        &self,
+        file_name: &str,
+    ) -> Option<usize> {
+        let mut digits: &str = file_name.strip_prefix(&self.prefix)?;
+
+        if !self.extension.is_empty() {
+            digits = digits.strip_suffix(&self.extension)?.strip_suffix('.')?;
+        }
+
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        digits.parse::<usize>().ok()?.checked_sub(self.start_index)
+    }
+
+    /// Parse the index of a chunk from its path.
+    pub(crate) fn index_of<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Option<usize> {
+        self.parse(path.as_ref().file_name()?.to_str()?)
+    }
+
+    /// Get the UTF-8 path of a chunk in the directory by its index.
+    #[cfg(feature = "camino")]
+    pub fn utf8_path<Dir: AsRef<Utf8Path>>(
+        &self,
+        dir: Dir,
+        index: usize,
+    ) -> Utf8PathBuf {
+        dir.as_ref().join(self.file_name(index))
+    }
+
+    /// Parse the index of a chunk from its UTF-8 path,
+    /// without converting the file name.
+    #[cfg(feature = "camino")]
+    pub fn utf8_index_of<P: AsRef<Utf8Path>>(
+        &self,
+        path: P,
+    ) -> Option<usize> {
+        self.parse(path.as_ref().file_name()?)
+    }
+}
+
+/// Naming scheme of the chunk files produced by other tools.
+///
+/// ## Example
+///
+/// ```
+/// use filerune_fusion::chunk::ForeignNaming;
+///
+/// let naming: ForeignNaming =
+///     ForeignNaming::detect(["video.mp4.001", "video.mp4.002"]).unwrap();
+///
+/// assert_eq!(naming, ForeignNaming::Numbered { stem: "video.mp4".into() });
+/// assert_eq!(naming.parse("video.mp4.002"), Some(1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForeignNaming {
+    /// GNU `split` style, such as `xaa`, `xab`, `xac`,
+    /// and the widened suffixes after `xyz`, such as `xzaaa`.
+    GnuSplit {
+        /// The prefix of the chunk file names.
+        prefix: String,
+        /// The number of letters in the suffix, before it is widened.
+        suffix_width: usize,
+    },
+    /// HJSplit and 7-Zip style, such as `file.001`, `file.002`.
+    Numbered {
+        /// The name of the original file.
+        stem: String,
+    },
+    /// Part style, such as `file.part1`, `file.part2`.
+    Part {
+        /// The name of the original file.
+        stem: String,
+    },
+}
+
+impl ForeignNaming {
+    /// Detect the naming scheme from the chunk file names.
+    ///
+    /// The scheme is anchored on its first chunk,
+    /// such as `xaa`, `file.001` or `file.part1`,
+    /// and the one matching the most file names is chosen.
+    ///
+    /// Returns `None` if no scheme is found.
+    pub fn detect<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        file_names: I
+    ) -> Option<Self> {
+        let file_names: Vec<S> = file_names.into_iter().collect();
+
+        let mut candidates: Vec<Self> = Vec::new();
+
+        for file_name in file_names.iter() {
+            let file_name: &str = file_name.as_ref();
+
+            if let Some((stem, digits)) = file_name.rsplit_once('.') {
+                // `file.001`
+                if digits.len() >= 3
+                    && digits.bytes().all(|b| b.is_ascii_digit())
+                    && digits.parse::<usize>() == Ok(1)
+                    && !stem.is_empty()
+                {
+                    candidates.push(Self::Numbered { stem: stem.to_string() });
+                }
+
+                // `file.part1`
+                if let Some(digits) = digits.strip_prefix("part") {
+                    if !digits.is_empty()
+                        && digits.bytes().all(|b| b.is_ascii_digit())
+                        && digits.parse::<usize>() == Ok(1)
+                        && !stem.is_empty()
+                    {
+                        candidates.push(Self::Part { stem: stem.to_string() });
+                    }
+                }
+            }
+
+            // `xaa`
+            let suffix_width: usize =
+                file_name.bytes().rev().take_while(|b| *b == b'a').count();
+
+            if suffix_width >= 2 && suffix_width < file_name.len() {
+                candidates.push(Self::GnuSplit {
+                    prefix: file_name[..file_name.len() - suffix_width]
+                        .to_string(),
+                    suffix_width,
+                });
+            }
+        }
+
+        // the first candidate wins on ties
+        let mut best: Option<(usize, Self)> = None;
+
+        for candidate in candidates {
+            let count: usize = file_names
+                .iter()
+                .filter(|n| candidate.parse(n.as_ref()).is_some())
+                .count();
+
+            if best.as_ref().is_none_or(|(c, _)| count > *c) {
+                best = Some((count, candidate));
+            }
+        }
+
+        best.map(|(_, naming)| naming)
+    }
+
+    /// Parse the index of a chunk from its file name.
+    ///
+    /// Returns `None` if the file name does not follow the naming scheme.
+    pub fn parse(
+        &self,
+        file_name: &str,
+    ) -> Option<usize> {
+        match self {
+            | Self::GnuSplit { prefix, suffix_width } => {
+                let letters: &str = file_name.strip_prefix(prefix.as_str())?;
+
+                if !letters.bytes().all(|b| b.is_ascii_lowercase()) {
+                    return None;
+                }
+
+                // once the names run out, `split` widens the suffix with one
+                // more `z` and one more letter each time, `zaaa` after `yz`
+                let widened: usize = match letters.len() == *suffix_width {
+                    | true => 0,
+                    | false => {
+                        letters.bytes().take_while(|b| *b == b'z').count()
+                    },
+                };
+
+                if letters.len() != suffix_width + 2 * widened {
+                    return None;
+                }
+
+                // the names of the narrower suffixes come first
+                let offset: usize = 26usize
+                    .checked_pow(
+                        u32::try_from(suffix_width.checked_sub(1)?).ok()?,
+                    )?
+                    .checked_mul(
+                        26usize.checked_pow(u32::try_from(widened).ok()?)? - 1,
+                    )?;
+
+                letters[widened..]
+                    .bytes()
+                    .try_fold(0usize, |index, b| {
+                        index.checked_mul(26)?.checked_add((b - b'a') as usize)
+                    })?
+                    .checked_add(offset)
+            },
+            | Self::Numbered { stem } => {
+                let digits: &str =
+                    file_name.strip_prefix(stem.as_str())?.strip_prefix('.')?;
+
+                if digits.len() < 3
+                    || !digits.bytes().all(|b| b.is_ascii_digit())
+                {
+                    return None;
+                }
+
+                digits.parse::<usize>().ok()?.checked_sub(1)
+            },
+            | Self::Part { stem } => {
+                let digits: &str = file_name
+                    .strip_prefix(stem.as_str())?
+                    .strip_prefix(".part")?;
+
+                if digits.is_empty()
+                    || !digits.bytes().all(|b| b.is_ascii_digit())
+                {
+                    return None;
+                }
+
+                digits.parse::<usize>().ok()?.checked_sub(1)
+            },
+        }
+    }
+
+    /// Parse the index of a chunk from its path.
+    pub(crate) fn index_of<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Option<usize> {
+        self.parse(path.as_ref().file_name()?.to_str()?)
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::{self, Read, Write as _};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+
+/// Codec to compress each chunk as it is written.
+///
+/// Each chunk is compressed as a standalone frame of the codec,
+/// so the chunks can be decompressed independently.
+///
+/// ## Example
+///
+/// ```
+/// use filerune_fusion::codec::Codec;
+///
+/// let codec: Codec = Codec::Zstd { level: 3 };
+///
+/// let compressed: Vec<u8> = codec.compress(&[0; 1024]).unwrap();
+///
+/// assert!(compressed.len() < 1024);
+/// assert_eq!(codec.decompress(&compressed).unwrap(), [0; 1024]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Zstandard, with the level from `1` to `22`, or `0` for the default.
+    Zstd { level: i32 },
+    /// LZ4, in the frame format.
+    Lz4,
+    /// Gzip, with the level from `0` to `9`.
+    Gzip { level: u32 },
+}
+
+impl Codec {
+    /// Get the name of the codec as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Zstd { .. } => "zstd",
+            | Self::Lz4 => "lz4",
+            | Self::Gzip { .. } => "gzip",
+        }
+    }
+
+    /// Compress the data as one frame.
+    pub fn compress(
+        &self,
+        data: &[u8],
+    ) -> io::Result<Vec<u8>> {
+        match self {
+            | Self::Zstd { level } => zstd::bulk::compress(data, *level),
+            | Self::Lz4 => {
+                let mut encoder: FrameEncoder<Vec<u8>> =
+                    FrameEncoder::new(Vec::new());
+
+                encoder.write_all(data)?;
+
+                encoder.finish().map_err(io::Error::other)
+            },
+            | Self::Gzip { level } => {
+                let mut encoder: GzEncoder<Vec<u8>> =
+                    GzEncoder::new(Vec::new(), Compression::new(*level));
+
+                encoder.write_all(data)?;
+
+                encoder.finish()
+            },
+        }
+    }
+
+    /// Decompress the data of one frame.
+    pub fn decompress(
+        &self,
+        data: &[u8],
+    ) -> io::Result<Vec<u8>> {
+        let mut decompressed: Vec<u8> = Vec::new();
+
+        self.decoder(data)?.read_to_end(&mut decompressed)?;
+
+        Ok(decompressed)
+    }
+
+    /// Wrap the reader of the compressed data to read the decompressed data.
+    pub(crate) fn decoder<'a, R: Read + 'a>(
+        &self,
+        reader: R,
+    ) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            | Self::Zstd { .. } => Box::new(zstd::Decoder::new(reader)?),
+            | Self::Lz4 => Box::new(FrameDecoder::new(reader)),
+            | Self::Gzip { .. } => Box::new(GzDecoder::new(reader)),
+        })
+    }
+}
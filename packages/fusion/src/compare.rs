@@ -0,0 +1,456 @@
+use std::{
+    fs,
+    io::{self, Read as _},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use crate::{
+    CHUNK_SIZE_DEFAULT,
+    cancel::CancellationToken,
+    chunk::ChunkNaming,
+    progress::{Progress, ProgressGuard},
+    report::Report,
+    sys::read_options,
+};
+
+/// Run asynchronously with `async_std` feature.
+///
+/// To use it, add the following code to the `Cargo.toml` file:
+///
+/// ```toml
+/// [dependencies]
+/// filerune_fusion = { version = "*", features = ["async_std"] }
+/// ```
+#[cfg(feature = "async_std")]
+pub mod async_std {
+    pub use crate::async_std::compare::CompareAsyncExt;
+}
+
+/// Run asynchronously with `smol` feature.
+///
+/// To use it, add the following code to the `Cargo.toml` file:
+///
+/// ```toml
+/// [dependencies]
+/// filerune_fusion = { version = "*", features = ["smol"] }
+/// ```
+#[cfg(feature = "smol")]
+pub mod smol {
+    pub use crate::smol::compare::CompareAsyncExt;
+}
+
+/// Run asynchronously with `tokio` feature.
+///
+/// To use it, add the following code to the `Cargo.toml` file:
+///
+/// ```toml
+/// [dependencies]
+/// filerune_fusion = { version = "*", features = ["tokio"] }
+/// ```
+#[cfg(feature = "tokio")]
+pub mod tokio {
+    pub use crate::tokio::compare::CompareAsyncExt;
+}
+
+/// Result of the compare process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompareResult {
+    /// The size of the input file in bytes.
+    pub file_size: u64,
+    /// The number of chunks expected from the input file.
+    pub total_chunks: usize,
+    /// The indices of the chunks with different content.
+    pub mismatched: Vec<usize>,
+    /// The indices of the chunks not found in the input directory.
+    pub missing: Vec<usize>,
+    /// The indices of the chunks beyond the end of the input file.
+    pub extra: Vec<usize>,
+}
+
+impl CompareResult {
+    /// Check if the chunks correspond to the input file.
+    pub fn is_match(&self) -> bool {
+        self.mismatched.is_empty()
+            && self.missing.is_empty()
+            && self.extra.is_empty()
+    }
+}
+
+/// Compare process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareError {
+    InFileNotFound,
+    InFileNotFile,
+    InFileNotSet,
+    InFileNotOpened,
+    InFileNotRead,
+    InDirNotFound,
+    InDirNotDir,
+    InDirNotSet,
+    InDirNotRead,
+    ChunkNotRead,
+    Cancelled,
+    DeadlineExceeded,
+}
+
+impl CompareError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::InFileNotFound => "in_file_not_found",
+            | Self::InFileNotFile => "in_file_not_file",
+            | Self::InFileNotSet => "in_file_not_set",
+            | Self::InFileNotOpened => "in_file_not_opened",
+            | Self::InFileNotRead => "in_file_not_read",
+            | Self::InDirNotFound => "in_dir_not_found",
+            | Self::InDirNotDir => "in_dir_not_dir",
+            | Self::InDirNotSet => "in_dir_not_set",
+            | Self::InDirNotRead => "in_dir_not_read",
+            | Self::ChunkNotRead => "chunk_not_read",
+            | Self::Cancelled => "cancelled",
+            | Self::DeadlineExceeded => "deadline_exceeded",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::InFileNotFound => "The input file not found.",
+            | Self::InFileNotFile => "The input file is not a file.",
+            | Self::InFileNotSet => "The input file is not set.",
+            | Self::InFileNotOpened => "The input file could not be opened.",
+            | Self::InFileNotRead => "The input file could not be read.",
+            | Self::InDirNotFound => "The input directory not found.",
+            | Self::InDirNotDir => "The input directory is not a directory.",
+            | Self::InDirNotSet => "The input directory is not set.",
+            | Self::InDirNotRead => "The input directory could not be read.",
+            | Self::ChunkNotRead => "A chunk could not be read.",
+            | Self::Cancelled => "The compare process was cancelled.",
+            | Self::DeadlineExceeded => {
+                "The deadline of the compare process was exceeded."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Process to compare a file with the chunks in a directory.
+///
+/// The file is read chunk by chunk in place and compared with the chunks,
+/// nothing is written.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::compare::{Compare, CompareResult};
+///
+/// let result: CompareResult = Compare::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+///     .run()
+///     .unwrap();
+///
+/// if !result.is_match() {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Compare {
+    pub in_file: Option<Arc<Path>>,
+    pub in_dir: Option<Arc<Path>>,
+    pub chunk_size: usize,
+    pub naming: ChunkNaming,
+    pub cancellation_token: Option<CancellationToken>,
+    pub deadline: Option<Instant>,
+    pub progress: Option<Progress>,
+}
+
+impl Compare {
+    /// Create a new compare process.
+    pub fn new() -> Self {
+        Self {
+            in_file: None,
+            in_dir: None,
+            chunk_size: CHUNK_SIZE_DEFAULT,
+            naming: ChunkNaming::new(),
+            cancellation_token: None,
+            deadline: None,
+            progress: None,
+        }
+    }
+
+    /// Create a new compare process from an existing one.
+    pub fn from<P: Into<Compare>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the input file.
+    pub fn in_file<InFile: AsRef<Path>>(
+        mut self,
+        path: InFile,
+    ) -> Self {
+        self.in_file = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the input file with a shared path.
+    ///
+    /// Unlike [`Compare::in_file`], an [`Arc<Path>`] is stored without copying.
+    pub fn in_file_shared<InFile: Into<Arc<Path>>>(
+        mut self,
+        path: InFile,
+    ) -> Self {
+        self.in_file = Some(path.into());
+        self
+    }
+
+    /// Set the input directory of the chunks.
+    pub fn in_dir<InDir: AsRef<Path>>(
+        mut self,
+        path: InDir,
+    ) -> Self {
+        self.in_dir = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the input directory of the chunks with a shared path.
+    ///
+    /// Unlike [`Compare::in_dir`], an [`Arc<Path>`] is stored without copying.
+    pub fn in_dir_shared<InDir: Into<Arc<Path>>>(
+        mut self,
+        path: InDir,
+    ) -> Self {
+        self.in_dir = Some(path.into());
+        self
+    }
+
+    /// Set the size of each chunk used to split the file.
+    ///
+    /// By default, the chunk size follows the [`CHUNK_SIZE_DEFAULT`].
+    pub fn chunk_size(
+        mut self,
+        size: usize,
+    ) -> Self {
+        self.chunk_size = size;
+        self
+    }
+
+    /// Set the naming scheme of the chunk files.
+    ///
+    /// By default, chunks are named by their index only.
+    pub fn naming(
+        mut self,
+        naming: ChunkNaming,
+    ) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Set the handle to poll the progress of the process.
+    pub fn progress(
+        mut self,
+        progress: Progress,
+    ) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Set the token to cancel the process.
+    pub fn cancellation_token(
+        mut self,
+        token: CancellationToken,
+    ) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Set the deadline of the process, checked between chunks.
+    ///
+    /// Once passed, the process fails with
+    /// [`CompareError::DeadlineExceeded`].
+    pub fn deadline(
+        mut self,
+        deadline: Instant,
+    ) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Check if the process is cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation_token.as_ref().is_some_and(|t| t.is_cancelled())
+    }
+
+    /// Check if the deadline of the process is exceeded.
+    pub(crate) fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Run the compare process.
+    pub fn run(&self) -> Result<CompareResult, CompareError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let in_file: &Path = match self.in_file {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_file not exists
+                if !p.exists() {
+                    return Err(CompareError::InFileNotFound);
+                }
+
+                // if in_file not a file
+                if !p.is_file() {
+                    return Err(CompareError::InFileNotFile);
+                }
+
+                p
+            },
+            | None => return Err(CompareError::InFileNotSet),
+        };
+
+        let in_dir: &Path = match self.in_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_dir not exists
+                if !p.exists() {
+                    return Err(CompareError::InDirNotFound);
+                }
+
+                // if in_dir not a directory
+                if !p.is_dir() {
+                    return Err(CompareError::InDirNotDir);
+                }
+
+                p
+            },
+            | None => return Err(CompareError::InDirNotSet),
+        };
+
+        let chunk_size: usize = self.chunk_size;
+
+        let mut input: fs::File = read_options()
+            .open(in_file)
+            .map_err(|_| CompareError::InFileNotOpened)?;
+
+        let mut buffer: Vec<u8> = vec![0; chunk_size];
+
+        // one more byte to detect a larger chunk
+        let mut chunk_buffer: Vec<u8> = vec![0; chunk_size + 1];
+
+        let mut result: CompareResult = CompareResult::default();
+
+        loop {
+            if self.is_cancelled() {
+                return Err(CompareError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(CompareError::DeadlineExceeded);
+            }
+
+            let size: usize = read_full(&mut input, &mut buffer)
+                .map_err(|_| CompareError::InFileNotRead)?;
+
+            if size == 0 {
+                break;
+            }
+
+            let index: usize = result.total_chunks;
+
+            let chunk_path: PathBuf = in_dir.join(self.naming.file_name(index));
+
+            if chunk_path.is_file() {
+                let mut chunk: fs::File = read_options()
+                    .open(&chunk_path)
+                    .map_err(|_| CompareError::ChunkNotRead)?;
+
+                let chunk_len: usize = read_full(&mut chunk, &mut chunk_buffer)
+                    .map_err(|_| CompareError::ChunkNotRead)?;
+
+                if buffer[..size] != chunk_buffer[..chunk_len] {
+                    result.mismatched.push(index);
+                }
+            } else {
+                result.missing.push(index);
+            }
+
+            progress.advance(index, size);
+
+            result.file_size += size as u64;
+
+            result.total_chunks += 1;
+        }
+
+        // chunks beyond the end of the input file
+        let read_dir: fs::ReadDir =
+            fs::read_dir(in_dir).map_err(|_| CompareError::InDirNotRead)?;
+
+        for entry in read_dir.filter_map(Result::ok) {
+            let path: PathBuf = entry.path();
+
+            if let Some(index) = self.naming.index_of(&path) {
+                if index >= result.total_chunks && path.is_file() {
+                    result.extra.push(index);
+                }
+            }
+        }
+
+        result.extra.sort_unstable();
+
+        progress.finish();
+
+        Ok(result)
+    }
+
+    /// Run the compare process and get the report as a JSON object.
+    ///
+    /// See [`Report`] for the schema of the report.
+    pub fn report_json(&self) -> String {
+        let started: Instant = Instant::now();
+
+        let result: Result<CompareResult, CompareError> = self.run();
+
+        Report::compare(self, &result, started.elapsed()).to_json()
+    }
+}
+
+impl Default for Compare {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read from the reader until the buffer is full or the end is reached.
+fn read_full(
+    reader: &mut fs::File,
+    buffer: &mut [u8],
+) -> io::Result<usize> {
+    let mut offset: usize = 0;
+
+    while offset < buffer.len() {
+        match reader.read(&mut buffer[offset..]) {
+            | Ok(0) => break,
+            | Ok(n) => offset += n,
+            | Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+            | Err(e) => return Err(e),
+        }
+    }
+
+    Ok(offset)
+}
@@ -0,0 +1,269 @@
+use std::{path::PathBuf, time::Duration};
+
+use crate::{
+    bagit::BagEntry,
+    chunk::ChunkInfo,
+    hash::{Digest, HashAlgorithm},
+    split::SplitResult,
+};
+
+/// Record error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordError {
+    ValueOutOfRange,
+    PathNotUtf8,
+    HashInvalid,
+}
+
+impl RecordError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::ValueOutOfRange => "value_out_of_range",
+            | Self::PathNotUtf8 => "path_not_utf8",
+            | Self::HashInvalid => "hash_invalid",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::ValueOutOfRange => {
+                "A value does not fit in the column of the record."
+            },
+            | Self::PathNotUtf8 => "A path is not valid UTF-8.",
+            | Self::HashInvalid => "A hash of the record is invalid.",
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Row of a split file, converted from a [`SplitResult`].
+///
+/// The sizes are stored as `BIGINT`, the duration in milliseconds and
+/// the hash as lowercase hex, so the record maps to the column types of
+/// most SQL databases. The chunks are stored apart as [`ChunkRecord`],
+/// with the key of the file chosen by the application.
+///
+/// With the `sqlx` feature, it derives `sqlx::FromRow` by column name.
+/// With the `diesel` feature, it derives `diesel::Queryable`
+/// in the order of the fields.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     db::{ChunkRecord, SplitRecord},
+///     split::{Split, SplitResult},
+/// };
+///
+/// let result: SplitResult = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .run()
+///     .unwrap();
+///
+/// let split: SplitRecord = SplitRecord::try_from(&result).unwrap();
+///
+/// let chunks: Vec<ChunkRecord> = ChunkRecord::from_result(&result).unwrap();
+///
+/// // insert the records, then read them back
+///
+/// let result: SplitResult = split.into_result(chunks).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "diesel", derive(diesel::Queryable))]
+pub struct SplitRecord {
+    /// Size of the original file in bytes.
+    pub file_size: i64,
+    /// The total number of chunks splitted from the original file.
+    pub total_chunks: i64,
+    /// The size of each chunk used in bytes.
+    pub chunk_size: i64,
+    /// The number of chunks kept from a previous run when resumed.
+    pub chunks_resumed: i64,
+    /// The time taken by the split process in milliseconds.
+    pub duration_ms: i64,
+    /// The BLAKE3 hash of the file as hex, if written.
+    pub bao_hash: Option<String>,
+}
+
+impl SplitRecord {
+    /// Convert the record back to a result, with the records of its chunks.
+    pub fn into_result(
+        self,
+        chunks: Vec<ChunkRecord>,
+    ) -> Result<SplitResult, RecordError> {
+        let mut chunks: Vec<ChunkInfo> = chunks
+            .into_iter()
+            .map(ChunkInfo::try_from)
+            .collect::<Result<Vec<ChunkInfo>, RecordError>>()?;
+
+        chunks.sort_by_key(|c| c.index);
+
+        let bao_hash: Option<Digest> = match self.bao_hash {
+            | Some(ref hex) => Some(
+                Digest::from_hex(HashAlgorithm::Blake3, hex)
+                    .ok_or(RecordError::HashInvalid)?,
+            ),
+            | None => None,
+        };
+
+        Ok(SplitResult {
+            file_size: to_u64(self.file_size)?,
+            total_chunks: to_usize(self.total_chunks)?,
+            chunk_size: to_usize(self.chunk_size)?,
+            chunks,
+            duration: Duration::from_millis(
+                u64::try_from(self.duration_ms)
+                    .map_err(|_| RecordError::ValueOutOfRange)?,
+            ),
+            chunks_resumed: to_usize(self.chunks_resumed)?,
+            bao_hash,
+        })
+    }
+}
+
+impl TryFrom<&SplitResult> for SplitRecord {
+    type Error = RecordError;
+
+    fn try_from(result: &SplitResult) -> Result<Self, Self::Error> {
+        Ok(Self {
+            file_size: to_i64(result.file_size)?,
+            total_chunks: to_i64(result.total_chunks)?,
+            chunk_size: to_i64(result.chunk_size)?,
+            chunks_resumed: to_i64(result.chunks_resumed)?,
+            duration_ms: i64::try_from(result.duration.as_millis())
+                .map_err(|_| RecordError::ValueOutOfRange)?,
+            bao_hash: result.bao_hash.as_ref().map(|d| d.to_hex()),
+        })
+    }
+}
+
+/// Row of a chunk, converted from a [`ChunkInfo`].
+///
+/// With the `sqlx` feature, it derives `sqlx::FromRow` by column name.
+/// With the `diesel` feature, it derives `diesel::Queryable`
+/// in the order of the fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "diesel", derive(diesel::Queryable))]
+pub struct ChunkRecord {
+    /// The index of the chunk.
+    pub chunk_index: i64,
+    /// The path of the chunk.
+    pub path: String,
+    /// The size of the chunk in bytes.
+    pub size: i64,
+}
+
+impl ChunkRecord {
+    /// Convert the chunks of a split result, in order.
+    pub fn from_result(result: &SplitResult) -> Result<Vec<Self>, RecordError> {
+        result.chunks.iter().map(Self::try_from).collect()
+    }
+}
+
+impl TryFrom<&ChunkInfo> for ChunkRecord {
+    type Error = RecordError;
+
+    fn try_from(chunk: &ChunkInfo) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chunk_index: to_i64(chunk.index)?,
+            path: chunk
+                .path
+                .to_str()
+                .ok_or(RecordError::PathNotUtf8)?
+                .to_string(),
+            size: to_i64(chunk.size)?,
+        })
+    }
+}
+
+impl TryFrom<ChunkRecord> for ChunkInfo {
+    type Error = RecordError;
+
+    fn try_from(record: ChunkRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            index: to_usize(record.chunk_index)?,
+            path: PathBuf::from(record.path),
+            size: to_usize(record.size)?,
+        })
+    }
+}
+
+/// Row of a manifest entry, converted from a [`BagEntry`].
+///
+/// With the `sqlx` feature, it derives `sqlx::FromRow` by column name.
+/// With the `diesel` feature, it derives `diesel::Queryable`
+/// in the order of the fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "diesel", derive(diesel::Queryable))]
+pub struct ManifestRecord {
+    /// The path of the file relative to the bag directory.
+    pub path: String,
+    /// The name of the algorithm of the hash, such as `sha256`.
+    pub hash_algorithm: String,
+    /// The hash of the file as lowercase hex.
+    pub hash: String,
+}
+
+impl TryFrom<&BagEntry> for ManifestRecord {
+    type Error = RecordError;
+
+    fn try_from(entry: &BagEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            path: entry
+                .path
+                .to_str()
+                .ok_or(RecordError::PathNotUtf8)?
+                .to_string(),
+            hash_algorithm: entry.hash.algorithm.as_str().to_string(),
+            hash: entry.hash.to_hex(),
+        })
+    }
+}
+
+impl TryFrom<ManifestRecord> for BagEntry {
+    type Error = RecordError;
+
+    fn try_from(record: ManifestRecord) -> Result<Self, Self::Error> {
+        let algorithm: HashAlgorithm =
+            HashAlgorithm::parse(&record.hash_algorithm)
+                .ok_or(RecordError::HashInvalid)?;
+
+        Ok(Self {
+            path: PathBuf::from(record.path),
+            hash: Digest::from_hex(algorithm, &record.hash)
+                .ok_or(RecordError::HashInvalid)?,
+        })
+    }
+}
+
+/// Convert a value to the integer type of a column.
+fn to_i64<T: TryInto<i64>>(value: T) -> Result<i64, RecordError> {
+    value.try_into().map_err(|_| RecordError::ValueOutOfRange)
+}
+
+/// Convert the integer of a column to a value.
+fn to_usize(value: i64) -> Result<usize, RecordError> {
+    usize::try_from(value).map_err(|_| RecordError::ValueOutOfRange)
+}
+
+/// Convert the integer of a column to a size in bytes.
+fn to_u64(value: i64) -> Result<u64, RecordError> {
+    u64::try_from(value).map_err(|_| RecordError::ValueOutOfRange)
+}
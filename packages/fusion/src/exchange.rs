@@ -0,0 +1,410 @@
+use std::io::{self, Read, Write};
+
+use crate::{
+    CHUNK_SIZE_DEFAULT,
+    bitmap::ChunkBitmap,
+    store::{ChunkStore, StoreError},
+};
+
+const TAG_REQUEST_BITMAP: u8 = 0;
+const TAG_BITMAP: u8 = 1;
+const TAG_REQUEST_CHUNK: u8 = 2;
+const TAG_CHUNK: u8 = 3;
+const TAG_NOT_FOUND: u8 = 4;
+
+/// The length of the header of a chunk payload, its index.
+const CHUNK_HEADER_LEN: u64 = 8;
+
+/// The default maximum length of the payload of a message, in bytes.
+///
+/// It fits a chunk of the [`CHUNK_SIZE_DEFAULT`] with its index.
+pub const MESSAGE_LEN_DEFAULT: u64 =
+    CHUNK_SIZE_DEFAULT as u64 + CHUNK_HEADER_LEN;
+
+/// Message of the chunk exchange protocol.
+///
+/// Each message is framed as a tag byte and the length of the payload
+/// as a little-endian `u64`, followed by the payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// Request the bitmap of the chunks held by the peer.
+    RequestBitmap,
+    /// The bitmap of the chunks held.
+    Bitmap(ChunkBitmap),
+    /// Request the content of a chunk.
+    RequestChunk(usize),
+    /// The content of a chunk.
+    Chunk {
+        /// The index of the chunk.
+        index: usize,
+        /// The content of the chunk.
+        data: Vec<u8>,
+    },
+    /// The requested chunk is not held.
+    NotFound(usize),
+}
+
+impl Message {
+    /// Encode the message as a frame.
+    pub fn encode(&self) -> Vec<u8> {
+        let (tag, payload): (u8, Vec<u8>) = match self {
+            | Self::RequestBitmap => (TAG_REQUEST_BITMAP, Vec::new()),
+            | Self::Bitmap(bitmap) => {
+                let mut payload: Vec<u8> =
+                    Vec::with_capacity(8 + bitmap.as_bytes().len());
+
+                payload.extend_from_slice(&(bitmap.len() as u64).to_le_bytes());
+                payload.extend_from_slice(bitmap.as_bytes());
+
+                (TAG_BITMAP, payload)
+            },
+            | Self::RequestChunk(index) => {
+                (TAG_REQUEST_CHUNK, (*index as u64).to_le_bytes().to_vec())
+            },
+            | Self::Chunk { index, data } => {
+                let mut payload: Vec<u8> = Vec::with_capacity(8 + data.len());
+
+                payload.extend_from_slice(&(*index as u64).to_le_bytes());
+                payload.extend_from_slice(data);
+
+                (TAG_CHUNK, payload)
+            },
+            | Self::NotFound(index) => {
+                (TAG_NOT_FOUND, (*index as u64).to_le_bytes().to_vec())
+            },
+        };
+
+        let mut frame: Vec<u8> = Vec::with_capacity(9 + payload.len());
+
+        frame.push(tag);
+        frame.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        frame.extend_from_slice(&payload);
+
+        frame
+    }
+
+    /// Decode a message from the tag and the payload of a frame.
+    pub fn decode(
+        tag: u8,
+        payload: &[u8],
+    ) -> Result<Self, ExchangeError> {
+        // the leading `u64` of the payload
+        let number: Option<usize> = payload
+            .get(..8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()) as usize);
+
+        match (tag, number) {
+            | (TAG_REQUEST_BITMAP, _) if payload.is_empty() => {
+                Ok(Self::RequestBitmap)
+            },
+            | (TAG_BITMAP, Some(len)) if payload.len() - 8 == len.div_ceil(8) => {
+                Ok(Self::Bitmap(ChunkBitmap::from_bytes(
+                    len,
+                    payload[8..].to_vec(),
+                )))
+            },
+            | (TAG_REQUEST_CHUNK, Some(index)) if payload.len() == 8 => {
+                Ok(Self::RequestChunk(index))
+            },
+            | (TAG_CHUNK, Some(index)) => {
+                Ok(Self::Chunk { index, data: payload[8..].to_vec() })
+            },
+            | (TAG_NOT_FOUND, Some(index)) if payload.len() == 8 => {
+                Ok(Self::NotFound(index))
+            },
+            | _ => Err(ExchangeError::MessageInvalid),
+        }
+    }
+
+    /// Write the message to a stream.
+    pub fn write_to<W: Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), ExchangeError> {
+        writer
+            .write_all(&self.encode())
+            .and_then(|_| writer.flush())
+            .map_err(|_| ExchangeError::StreamNotWritten)
+    }
+
+    /// Read a message from a stream, `None` if the stream is closed.
+    ///
+    /// Fails with [`ExchangeError::MessageInvalid`] before reading the
+    /// payload if it is longer than `max_len` bytes.
+    pub fn read_from<R: Read>(
+        reader: &mut R,
+        max_len: u64,
+    ) -> Result<Option<Self>, ExchangeError> {
+        let mut header: [u8; 9] = [0; 9];
+
+        match reader.read_exact(&mut header) {
+            | Ok(()) => {},
+            | Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                return Ok(None);
+            },
+            | Err(_) => return Err(ExchangeError::StreamNotRead),
+        }
+
+        let len: u64 = u64::from_le_bytes(header[1..].try_into().unwrap());
+
+        if len > max_len {
+            return Err(ExchangeError::MessageInvalid);
+        }
+
+        // read without trusting the length for the allocation
+        let mut payload: Vec<u8> = Vec::new();
+
+        let read: usize = reader
+            .take(len)
+            .read_to_end(&mut payload)
+            .map_err(|_| ExchangeError::StreamNotRead)?;
+
+        if read as u64 != len {
+            return Err(ExchangeError::StreamNotRead);
+        }
+
+        Self::decode(header[0], &payload).map(Some)
+    }
+}
+
+/// Exchange error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExchangeError {
+    StreamNotRead,
+    StreamNotWritten,
+    MessageInvalid,
+    MessageUnexpected,
+    ChunkNotRead,
+    ChunkNotWritten,
+}
+
+impl ExchangeError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::StreamNotRead => "stream_not_read",
+            | Self::StreamNotWritten => "stream_not_written",
+            | Self::MessageInvalid => "message_invalid",
+            | Self::MessageUnexpected => "message_unexpected",
+            | Self::ChunkNotRead => "chunk_not_read",
+            | Self::ChunkNotWritten => "chunk_not_written",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::StreamNotRead => "The stream could not be read.",
+            | Self::StreamNotWritten => "The stream could not be written.",
+            | Self::MessageInvalid => "The message is invalid.",
+            | Self::MessageUnexpected => "The message is unexpected.",
+            | Self::ChunkNotRead => "A chunk could not be read.",
+            | Self::ChunkNotWritten => "A chunk could not be written.",
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Endpoint to exchange chunks with a peer over any byte stream.
+///
+/// One endpoint serves the chunks of its store with [`Exchange::serve`],
+/// while the other fetches the chunks it misses with [`Exchange::fetch`].
+/// Requests received while fetching are answered as well, so both peers
+/// can fetch from each other over the same stream.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{net::TcpStream, path::PathBuf};
+///
+/// use filerune_fusion::{
+///     bitmap::ChunkBitmap,
+///     chunk::ChunkNaming,
+///     exchange::Exchange,
+///     store::DirStore,
+/// };
+///
+/// let stream: TcpStream = TcpStream::connect("127.0.0.1:7878").unwrap();
+///
+/// let store: DirStore = DirStore::new(
+///     PathBuf::from("path").join("to").join("dir"),
+///     ChunkNaming::new(),
+/// );
+///
+/// let mut exchange: Exchange<DirStore> =
+///     Exchange::new(store, 8).max_message_len(4 * 1024 * 1024 + 8);
+///
+/// let fetched: ChunkBitmap =
+///     exchange.fetch(&mut &stream, &mut &stream).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Exchange<S: ChunkStore> {
+    pub store: S,
+    pub total_chunks: usize,
+    pub max_message_len: u64,
+}
+
+impl<S: ChunkStore> Exchange<S> {
+    /// Create a new endpoint for a store of `total_chunks` chunks.
+    pub fn new(
+        store: S,
+        total_chunks: usize,
+    ) -> Self {
+        Self { store, total_chunks, max_message_len: MESSAGE_LEN_DEFAULT }
+    }
+
+    /// Set the maximum length of the payload of a message received,
+    /// in bytes.
+    ///
+    /// A peer sending a longer message fails the exchange with
+    /// [`ExchangeError::MessageInvalid`] before it is read, so the memory
+    /// held for a message is bounded. It must fit the largest chunk
+    /// and its 8-byte index. The bitmap of the chunks is always accepted.
+    ///
+    /// By default, it is [`MESSAGE_LEN_DEFAULT`].
+    pub fn max_message_len(
+        mut self,
+        len: u64,
+    ) -> Self {
+        self.max_message_len = len;
+        self
+    }
+
+    /// Get the maximum length of the payload of a message received,
+    /// at least the length of a bitmap of the chunks.
+    fn message_len_max(&self) -> u64 {
+        let bitmap_len: u64 =
+            CHUNK_HEADER_LEN + self.total_chunks.div_ceil(8) as u64;
+
+        self.max_message_len.max(bitmap_len)
+    }
+
+    /// Get the bitmap of the chunks held by the endpoint.
+    pub fn bitmap(&self) -> Result<ChunkBitmap, ExchangeError> {
+        self.store
+            .bitmap(self.total_chunks)
+            .map_err(|_| ExchangeError::ChunkNotRead)
+    }
+
+    /// Get the response to a request, `None` if the message is no request.
+    pub fn respond(
+        &self,
+        message: &Message,
+    ) -> Result<Option<Message>, ExchangeError> {
+        match message {
+            | Message::RequestBitmap => {
+                Ok(Some(Message::Bitmap(self.bitmap()?)))
+            },
+            | Message::RequestChunk(index) => {
+                if *index >= self.total_chunks {
+                    return Ok(Some(Message::NotFound(*index)));
+                }
+
+                match self.store.read(*index) {
+                    | Ok(data) => {
+                        Ok(Some(Message::Chunk { index: *index, data }))
+                    },
+                    | Err(StoreError::ChunkNotFound) => {
+                        Ok(Some(Message::NotFound(*index)))
+                    },
+                    | Err(_) => Err(ExchangeError::ChunkNotRead),
+                }
+            },
+            | _ => Ok(None),
+        }
+    }
+
+    /// Answer the requests of the peer until the stream is closed.
+    ///
+    /// Returns the number of chunks sent.
+    pub fn serve<R: Read, W: Write>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<usize, ExchangeError> {
+        let mut sent: usize = 0;
+
+        while let Some(message) =
+            Message::read_from(reader, self.message_len_max())?
+        {
+            match self.respond(&message)? {
+                | Some(response) => {
+                    if let Message::Chunk { .. } = response {
+                        sent += 1;
+                    }
+
+                    response.write_to(writer)?;
+                },
+                | None => return Err(ExchangeError::MessageUnexpected),
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// Fetch the chunks held by the peer but missing from the store.
+    ///
+    /// Returns the bitmap of the chunks fetched.
+    pub fn fetch<R: Read, W: Write>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<ChunkBitmap, ExchangeError> {
+        Message::RequestBitmap.write_to(writer)?;
+
+        let remote: ChunkBitmap = match self.receive(reader, writer)? {
+            | Message::Bitmap(bitmap) => bitmap,
+            | _ => return Err(ExchangeError::MessageUnexpected),
+        };
+
+        let wanted: ChunkBitmap = remote.difference(&self.bitmap()?);
+
+        let mut fetched: ChunkBitmap = ChunkBitmap::new(self.total_chunks);
+
+        for index in wanted.iter() {
+            Message::RequestChunk(index).write_to(writer)?;
+
+            match self.receive(reader, writer)? {
+                | Message::Chunk { index: i, data } if i == index => {
+                    self.store
+                        .write(index, &data)
+                        .map_err(|_| ExchangeError::ChunkNotWritten)?;
+
+                    fetched.insert(index);
+                },
+                // removed from the peer in the meantime
+                | Message::NotFound(i) if i == index => {},
+                | _ => return Err(ExchangeError::MessageUnexpected),
+            }
+        }
+
+        Ok(fetched)
+    }
+
+    /// Receive the next response, answering the requests of the peer.
+    fn receive<R: Read, W: Write>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<Message, ExchangeError> {
+        loop {
+            let message: Message =
+                Message::read_from(reader, self.message_len_max())?
+                    .ok_or(ExchangeError::StreamNotRead)?;
+
+            match self.respond(&message)? {
+                | Some(response) => response.write_to(writer)?,
+                | None => return Ok(message),
+            }
+        }
+    }
+}
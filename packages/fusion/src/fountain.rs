@@ -0,0 +1,517 @@
+use std::{
+    collections::VecDeque,
+    io::{Read, Seek, SeekFrom},
+};
+
+pub use raptorq::{EncodingPacket, ObjectTransmissionInformation};
+use raptorq::{
+    SourceBlockDecoder, SourceBlockEncoder, extended_source_block_symbols,
+    partition,
+};
+
+use crate::{CHUNK_SIZE_DEFAULT, split::SplitError};
+
+/// The default size of each symbol in bytes,
+/// fitting a UDP datagram on any link.
+pub const SYMBOL_SIZE_DEFAULT: u16 = 1024;
+
+/// The maximum number of source blocks of a file.
+pub const SOURCE_BLOCKS_MAX: usize = 255;
+
+/// The maximum number of symbols of a source block.
+pub const SOURCE_BLOCK_SYMBOLS_MAX: u32 = 56403;
+
+/// The maximum size of a file in bytes, whatever the symbol size.
+const TRANSFER_LENGTH_MAX: u64 = 942_574_504_275;
+
+/// The alignment of the symbols in bytes.
+const SYMBOL_ALIGNMENT: u8 = 8;
+
+/// The number of encoding symbol ids of a source block.
+const ENCODING_SYMBOL_IDS: u32 = 1 << 24;
+
+/// Decode a packet serialized by [`EncodingPacket::serialize`].
+///
+/// Returns `None` if the bytes are too short to be a packet.
+pub fn packet_from_bytes(bytes: &[u8]) -> Option<EncodingPacket> {
+    if bytes.len() <= 4 {
+        return None;
+    }
+
+    Some(EncodingPacket::deserialize(bytes))
+}
+
+/// Get the byte ranges of the source blocks of a file, by RFC 6330.
+///
+/// The range of the last block may end past the file,
+/// the bytes past the file are zeros.
+fn block_ranges(config: &ObjectTransmissionInformation) -> Vec<(u64, u64)> {
+    let symbol_size: u64 = u64::from(config.symbol_size());
+
+    if config.transfer_length() == 0 || symbol_size == 0 {
+        return Vec::new();
+    }
+
+    let symbols: u64 = config.transfer_length().div_ceil(symbol_size);
+
+    let (large, small, large_blocks, small_blocks) =
+        partition(symbols as u32, config.source_blocks());
+
+    let mut ranges: Vec<(u64, u64)> =
+        Vec::with_capacity((large_blocks + small_blocks) as usize);
+
+    let mut offset: u64 = 0;
+
+    for block in 0..large_blocks + small_blocks {
+        let len: u64 = match block < large_blocks {
+            | true => u64::from(large) * symbol_size,
+            | false => u64::from(small) * symbol_size,
+        };
+
+        ranges.push((offset, offset + len));
+
+        offset += len;
+    }
+
+    ranges
+}
+
+/// Encoder of an open-ended stream of RaptorQ (RFC 6330) packets.
+///
+/// The packets are sent over lossy one-way links, such as UDP multicast,
+/// and the file is reconstructed by [`FountainDecoder`] from any subset
+/// of the packets, slightly more than the source symbols of each block.
+/// The packets are serialized with [`EncodingPacket::serialize`], so any
+/// RaptorQ decoder reads them with the transmission information of
+/// [`FountainEncoder::config`], sent once out of band.
+///
+/// The file is read one source block at a time, so only one block
+/// is held in memory. The source symbols of each block are sent first,
+/// then the repair symbols of each block in rounds, endlessly.
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use filerune_fusion::fountain::{
+///     FountainDecoder, FountainEncoder, ObjectTransmissionInformation,
+///     packet_from_bytes,
+/// };
+///
+/// let data: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+///
+/// let encoder: FountainEncoder<Cursor<&[u8]>> =
+///     FountainEncoder::new(Cursor::new(data.as_slice()))
+///         .unwrap()
+///         .symbol_size(1024)
+///         .unwrap();
+///
+/// let config: ObjectTransmissionInformation = encoder.config();
+///
+/// let mut decoder: FountainDecoder = FountainDecoder::new(config);
+///
+/// // every other packet is lost
+/// for packet in encoder.step_by(2) {
+///     let bytes: Vec<u8> = packet.unwrap().serialize();
+///
+///     if decoder.push(packet_from_bytes(&bytes).unwrap()) {
+///         break;
+///     }
+/// }
+///
+/// assert_eq!(decoder.into_data().unwrap(), data);
+/// ```
+#[derive(Debug)]
+pub struct FountainEncoder<R> {
+    reader: R,
+    start: u64,
+    file_size: u64,
+    block_size: usize,
+    overhead: u32,
+    config: ObjectTransmissionInformation,
+    ranges: Vec<(u64, u64)>,
+    block: usize,
+    round: u32,
+    cached: Option<(usize, SourceBlockEncoder)>,
+    packets: VecDeque<EncodingPacket>,
+    finished: bool,
+}
+
+impl<R: Read + Seek> FountainEncoder<R> {
+    /// Create a new encoder of the content of a reader,
+    /// from its current position to its end.
+    pub fn new(mut reader: R) -> Result<Self, SplitError> {
+        let start: u64 =
+            reader.stream_position().map_err(|_| SplitError::InFileNotRead)?;
+
+        let end: u64 = reader
+            .seek(SeekFrom::End(0))
+            .map_err(|_| SplitError::InFileNotRead)?;
+
+        let mut encoder: Self = Self {
+            reader,
+            start,
+            file_size: end.saturating_sub(start),
+            block_size: CHUNK_SIZE_DEFAULT,
+            overhead: 5,
+            config: ObjectTransmissionInformation::new(
+                0,
+                SYMBOL_SIZE_DEFAULT,
+                1,
+                1,
+                SYMBOL_ALIGNMENT,
+            ),
+            ranges: Vec::new(),
+            block: 0,
+            round: 0,
+            cached: None,
+            packets: VecDeque::new(),
+            finished: false,
+        };
+
+        encoder.configure(SYMBOL_SIZE_DEFAULT)?;
+
+        Ok(encoder)
+    }
+
+    /// Set the size of each symbol in bytes,
+    /// rounded down to a multiple of `8`.
+    ///
+    /// A packet is the symbol and its 4-byte id, so the symbol should fit
+    /// a datagram of the link. The file is limited to
+    /// [`SOURCE_BLOCKS_MAX`] blocks of [`SOURCE_BLOCK_SYMBOLS_MAX`] symbols,
+    /// otherwise it fails with [`SplitError::FountainLimitExceeded`].
+    ///
+    /// By default, it is [`SYMBOL_SIZE_DEFAULT`].
+    pub fn symbol_size(
+        mut self,
+        size: u16,
+    ) -> Result<Self, SplitError> {
+        self.configure(size)?;
+        Ok(self)
+    }
+
+    /// Set the preferred size of each source block in bytes,
+    /// the memory held to encode and to decode a block.
+    ///
+    /// The blocks are made larger if the file does not fit the maximum
+    /// number of blocks otherwise.
+    ///
+    /// By default, the block size follows the [`CHUNK_SIZE_DEFAULT`].
+    pub fn block_size(
+        mut self,
+        size: usize,
+    ) -> Result<Self, SplitError> {
+        self.block_size = size;
+        self.configure(self.config.symbol_size())?;
+        Ok(self)
+    }
+
+    /// Set the number of repair symbols sent for each block in each round,
+    /// as a percentage of its source symbols, at least one symbol.
+    ///
+    /// By default, it is `5`.
+    pub fn overhead(
+        mut self,
+        percent: u32,
+    ) -> Self {
+        self.overhead = percent;
+        self
+    }
+
+    /// Get the transmission information of the encoded file,
+    /// required to decode the packets.
+    ///
+    /// It is serialized with [`ObjectTransmissionInformation::serialize`].
+    pub fn config(&self) -> ObjectTransmissionInformation {
+        self.config
+    }
+
+    /// Get the number of source blocks of the file.
+    pub fn blocks(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Compute the transmission information for the symbol size.
+    fn configure(
+        &mut self,
+        symbol_size: u16,
+    ) -> Result<(), SplitError> {
+        let symbol_size: u16 = (symbol_size / u16::from(SYMBOL_ALIGNMENT)
+            * u16::from(SYMBOL_ALIGNMENT))
+        .max(u16::from(SYMBOL_ALIGNMENT));
+
+        if self.file_size > TRANSFER_LENGTH_MAX {
+            return Err(SplitError::FountainLimitExceeded);
+        }
+
+        let symbols: u64 = self.file_size.div_ceil(u64::from(symbol_size));
+
+        // the preferred block size, unless too many blocks
+        let block_symbols: u64 = (self.block_size as u64
+            / u64::from(symbol_size))
+        .clamp(1, u64::from(SOURCE_BLOCK_SYMBOLS_MAX))
+        .max(symbols.div_ceil(SOURCE_BLOCKS_MAX as u64));
+
+        if block_symbols > u64::from(SOURCE_BLOCK_SYMBOLS_MAX) {
+            return Err(SplitError::FountainLimitExceeded);
+        }
+
+        let blocks: u8 = symbols.div_ceil(block_symbols).max(1) as u8;
+
+        self.config = ObjectTransmissionInformation::new(
+            self.file_size,
+            symbol_size,
+            blocks,
+            1,
+            SYMBOL_ALIGNMENT,
+        );
+
+        self.ranges = block_ranges(&self.config);
+
+        Ok(())
+    }
+
+    /// Read a source block, padded with zeros to its range.
+    fn read_block(
+        &mut self,
+        block: usize,
+    ) -> Result<Vec<u8>, SplitError> {
+        let (start, end) = self.ranges[block];
+
+        let mut data: Vec<u8> = vec![0; (end - start) as usize];
+
+        let len: usize = (end.min(self.file_size) - start) as usize;
+
+        self.reader
+            .seek(SeekFrom::Start(self.start + start))
+            .and_then(|_| self.reader.read_exact(&mut data[..len]))
+            .map_err(|_| SplitError::InFileNotRead)?;
+
+        Ok(data)
+    }
+
+    /// Encode the packets of the next block of the round.
+    fn encode_next(&mut self) -> Result<(), SplitError> {
+        let block: usize = self.block;
+
+        let encoder: SourceBlockEncoder = match self.cached.take() {
+            | Some((index, encoder)) if index == block => encoder,
+            | _ => {
+                let data: Vec<u8> = self.read_block(block)?;
+
+                SourceBlockEncoder::new2(block as u8, &self.config, &data)
+            },
+        };
+
+        let symbols: u32 = ((self.ranges[block].1 - self.ranges[block].0)
+            / u64::from(self.config.symbol_size()))
+            as u32;
+
+        let repair: u32 = (symbols * self.overhead).div_ceil(100).max(1);
+
+        if self.round == 0 {
+            self.packets.extend(encoder.source_packets());
+        }
+
+        let start: u32 = self.round.saturating_mul(repair);
+
+        // the ids of the repair symbols are exhausted
+        if u64::from(start)
+            + u64::from(repair)
+            + u64::from(extended_source_block_symbols(symbols))
+            > u64::from(ENCODING_SYMBOL_IDS)
+        {
+            self.finished = true;
+
+            return Ok(());
+        }
+
+        self.packets.extend(encoder.repair_packets(start, repair));
+
+        self.cached = Some((block, encoder));
+
+        self.block += 1;
+
+        if self.block == self.ranges.len() {
+            self.block = 0;
+            self.round += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Iterator for FountainEncoder<R> {
+    type Item = Result<EncodingPacket, SplitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(packet) = self.packets.pop_front() {
+                return Some(Ok(packet));
+            }
+
+            if self.finished || self.ranges.is_empty() {
+                return None;
+            }
+
+            if let Err(error) = self.encode_next() {
+                self.finished = true;
+
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+/// State of a source block being decoded.
+#[derive(Debug, Clone)]
+enum BlockState {
+    /// No packet of the block is received.
+    Empty,
+    /// The packets of the block are being received.
+    Decoding(Box<SourceBlockDecoder>),
+    /// The block is decoded.
+    Decoded(Vec<u8>),
+    /// The block is decoded and taken.
+    Taken,
+}
+
+/// Decoder of a file from RaptorQ packets, in any order.
+///
+/// Each source block is decoded as soon as enough of its packets are
+/// received, and can be taken out with [`FountainDecoder::take_block`]
+/// to be written, so the decoded file is not held in memory.
+/// Packets of another block, already decoded or not of the symbol size
+/// are ignored. See [`FountainEncoder`] for an example.
+#[derive(Debug, Clone)]
+pub struct FountainDecoder {
+    config: ObjectTransmissionInformation,
+    ranges: Vec<(u64, u64)>,
+    blocks: Vec<BlockState>,
+    decoded: usize,
+}
+
+impl FountainDecoder {
+    /// Create a new decoder for the transmission information of a file.
+    pub fn new(config: ObjectTransmissionInformation) -> Self {
+        let ranges: Vec<(u64, u64)> = block_ranges(&config);
+
+        Self {
+            config,
+            blocks: vec![BlockState::Empty; ranges.len()],
+            ranges,
+            decoded: 0,
+        }
+    }
+
+    /// Get the transmission information of the file.
+    pub fn config(&self) -> ObjectTransmissionInformation {
+        self.config
+    }
+
+    /// Get the number of source blocks of the file.
+    pub fn blocks(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Check if all the blocks are decoded.
+    pub fn is_complete(&self) -> bool {
+        self.decoded == self.ranges.len()
+    }
+
+    /// Check if a block is decoded, taken or not.
+    pub fn is_decoded(
+        &self,
+        block: usize,
+    ) -> bool {
+        matches!(
+            self.blocks.get(block),
+            Some(BlockState::Decoded(_) | BlockState::Taken)
+        )
+    }
+
+    /// Get the number of blocks decoded and of blocks in total.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.decoded, self.ranges.len())
+    }
+
+    /// Receive a packet.
+    ///
+    /// Returns whether all the blocks are decoded.
+    pub fn push(
+        &mut self,
+        packet: EncodingPacket,
+    ) -> bool {
+        let block: usize = packet.payload_id().source_block_number() as usize;
+
+        if block >= self.ranges.len()
+            || packet.data().len() != usize::from(self.config.symbol_size())
+        {
+            return self.is_complete();
+        }
+
+        let (start, end) = self.ranges[block];
+
+        // the decoder of a block is created by its first packet
+        if let BlockState::Empty = self.blocks[block] {
+            self.blocks[block] =
+                BlockState::Decoding(Box::new(SourceBlockDecoder::new2(
+                    block as u8,
+                    &self.config,
+                    end - start,
+                )));
+        }
+
+        let decoder: &mut SourceBlockDecoder = match self.blocks[block] {
+            | BlockState::Decoding(ref mut decoder) => decoder,
+            | _ => return self.is_complete(),
+        };
+
+        if let Some(mut data) = decoder.decode([packet]) {
+            data.truncate(
+                (end.min(self.config.transfer_length()) - start) as usize,
+            );
+
+            self.blocks[block] = BlockState::Decoded(data);
+            self.decoded += 1;
+        }
+
+        self.is_complete()
+    }
+
+    /// Take the content of a decoded block,
+    /// `None` if not decoded or already taken.
+    pub fn take_block(
+        &mut self,
+        block: usize,
+    ) -> Option<Vec<u8>> {
+        match self.blocks.get_mut(block) {
+            | Some(state @ BlockState::Decoded(_)) => {
+                match std::mem::replace(state, BlockState::Taken) {
+                    | BlockState::Decoded(data) => Some(data),
+                    | _ => None,
+                }
+            },
+            | _ => None,
+        }
+    }
+
+    /// Reconstruct the file, `None` if not complete
+    /// or if a block is taken.
+    pub fn into_data(mut self) -> Option<Vec<u8>> {
+        if !self.is_complete() {
+            return None;
+        }
+
+        let mut data: Vec<u8> =
+            Vec::with_capacity(self.config.transfer_length() as usize);
+
+        for block in 0..self.ranges.len() {
+            data.extend_from_slice(&self.take_block(block)?);
+        }
+
+        Some(data)
+    }
+}
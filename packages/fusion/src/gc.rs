@@ -0,0 +1,312 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::upload::JOURNAL_FILE_NAME;
+
+/// The default time to live of a chunk directory, a day.
+pub const GC_TTL_DEFAULT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Garbage collection process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcError {
+    RootNotSet,
+    RootNotFound,
+    RootNotDir,
+    RootNotRead,
+    DirNotRemoved,
+}
+
+impl GcError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::RootNotSet => "root_not_set",
+            | Self::RootNotFound => "root_not_found",
+            | Self::RootNotDir => "root_not_dir",
+            | Self::RootNotRead => "root_not_read",
+            | Self::DirNotRemoved => "dir_not_removed",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::RootNotSet => "The cache root is not set.",
+            | Self::RootNotFound => "The cache root not found.",
+            | Self::RootNotDir => "The cache root is not a directory.",
+            | Self::RootNotRead => "The cache root could not be read.",
+            | Self::DirNotRemoved => {
+                "A stale chunk directory could not be removed."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Reason of a chunk directory to be collected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcReason {
+    /// The directory is not modified within the time to live.
+    Expired,
+    /// The directory holds an upload session not modified within
+    /// the time to live of the sessions.
+    SessionAbandoned,
+}
+
+impl GcReason {
+    /// Get the name of the reason as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Expired => "expired",
+            | Self::SessionAbandoned => "session_abandoned",
+        }
+    }
+}
+
+/// Chunk directory collected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcEntry {
+    /// The path of the directory.
+    pub path: PathBuf,
+    /// The total size of the files of the directory in bytes.
+    pub size: u64,
+    /// The last time the directory or one of its files was modified.
+    pub modified: SystemTime,
+    /// The reason of the directory to be collected.
+    pub reason: GcReason,
+}
+
+/// Report of the garbage collection process.
+#[derive(Debug, Clone)]
+pub struct GcReport {
+    /// The chunk directories collected, removed unless in dry run.
+    pub collected: Vec<GcEntry>,
+    /// The number of directories scanned under the cache root.
+    pub dirs_scanned: usize,
+    /// The total size of the directories collected in bytes.
+    pub bytes_reclaimed: u64,
+    /// Whether the directories were kept, in dry run.
+    pub dry_run: bool,
+    /// The time taken by the process.
+    pub duration: Duration,
+}
+
+/// Process to remove the stale chunk directories under a cache root,
+/// such as the orphaned chunk sets of an upload server.
+///
+/// Each directory directly under the cache root is a chunk directory,
+/// collected once neither it nor any of its files is modified within the
+/// time to live. A directory holding an upload session, with the journal
+/// of an [`UploadSession`](crate::upload::UploadSession), is collected with
+/// the time to live of the sessions instead, as its journal is modified
+/// with every chunk received. The files directly under the cache root
+/// are kept.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{path::PathBuf, time::Duration};
+///
+/// use filerune_fusion::gc::{Gc, GcReport};
+///
+/// let report: GcReport = Gc::new()
+///     .root(PathBuf::from("path").join("to").join("cache"))
+///     .ttl(Duration::from_secs(7 * 24 * 60 * 60))
+///     .session_ttl(Duration::from_secs(60 * 60))
+///     .dry_run(true)
+///     .run()
+///     .unwrap();
+///
+/// println!("{} bytes to reclaim", report.bytes_reclaimed);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Gc {
+    pub root: Option<Arc<Path>>,
+    pub ttl: Duration,
+    pub session_ttl: Option<Duration>,
+    pub dry_run: bool,
+}
+
+impl Gc {
+    /// Create a new garbage collection process.
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            ttl: GC_TTL_DEFAULT,
+            session_ttl: None,
+            dry_run: false,
+        }
+    }
+
+    /// Create a new garbage collection process from an existing one.
+    pub fn from<P: Into<Gc>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the cache root holding the chunk directories.
+    pub fn root<Root: AsRef<Path>>(
+        mut self,
+        path: Root,
+    ) -> Self {
+        self.root = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the time to live of the chunk directories.
+    ///
+    /// By default, it is [`GC_TTL_DEFAULT`].
+    pub fn ttl(
+        mut self,
+        ttl: Duration,
+    ) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the time to live of the directories holding an upload session.
+    ///
+    /// By default, it is the time to live of the chunk directories.
+    pub fn session_ttl(
+        mut self,
+        ttl: Duration,
+    ) -> Self {
+        self.session_ttl = Some(ttl);
+        self
+    }
+
+    /// Set whether to only report the directories to collect,
+    /// without removing them.
+    ///
+    /// By default, it is `false`.
+    pub fn dry_run(
+        mut self,
+        dry_run: bool,
+    ) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Run the garbage collection process.
+    pub fn run(&self) -> Result<GcReport, GcError> {
+        let started: Instant = Instant::now();
+
+        let root: &Path = match self.root {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if root not exists
+                if !p.exists() {
+                    return Err(GcError::RootNotFound);
+                }
+
+                // if root not a directory
+                if !p.is_dir() {
+                    return Err(GcError::RootNotDir);
+                }
+
+                p
+            },
+            | None => return Err(GcError::RootNotSet),
+        };
+
+        let mut dirs: Vec<PathBuf> = fs::read_dir(root)
+            .map_err(|_| GcError::RootNotRead)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+
+        dirs.sort();
+
+        let now: SystemTime = SystemTime::now();
+
+        let mut collected: Vec<GcEntry> = Vec::new();
+
+        for path in dirs.iter() {
+            let (size, modified) = match scan_dir(path) {
+                | Some(scan) => scan,
+                // removed or not readable meanwhile
+                | None => continue,
+            };
+
+            let (ttl, reason) = match path.join(JOURNAL_FILE_NAME).is_file() {
+                | true => (
+                    self.session_ttl.unwrap_or(self.ttl),
+                    GcReason::SessionAbandoned,
+                ),
+                | false => (self.ttl, GcReason::Expired),
+            };
+
+            // a time in the future is not expired
+            let age: Duration =
+                now.duration_since(modified).unwrap_or_default();
+
+            if age <= ttl {
+                continue;
+            }
+
+            if !self.dry_run {
+                fs::remove_dir_all(path).map_err(|_| GcError::DirNotRemoved)?;
+            }
+
+            collected.push(GcEntry {
+                path: path.clone(),
+                size,
+                modified,
+                reason,
+            });
+        }
+
+        Ok(GcReport {
+            bytes_reclaimed: collected.iter().map(|e| e.size).sum(),
+            collected,
+            dirs_scanned: dirs.len(),
+            dry_run: self.dry_run,
+            duration: started.elapsed(),
+        })
+    }
+}
+
+impl Default for Gc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the total size of the files of a directory and the last time
+/// it or one of its files was modified, recursively.
+fn scan_dir(dir: &Path) -> Option<(u64, SystemTime)> {
+    let mut modified: SystemTime = fs::metadata(dir).ok()?.modified().ok()?;
+
+    let mut size: u64 = 0;
+
+    for entry in fs::read_dir(dir).ok()?.filter_map(Result::ok) {
+        let metadata: fs::Metadata = match entry.metadata() {
+            | Ok(metadata) => metadata,
+            | Err(_) => continue,
+        };
+
+        let (entry_size, entry_modified) = match metadata.is_dir() {
+            | true => scan_dir(&entry.path())?,
+            | false => (metadata.len(), metadata.modified().ok()?),
+        };
+
+        size += entry_size;
+        modified = modified.max(entry_modified);
+    }
+
+    Some((size, modified))
+}
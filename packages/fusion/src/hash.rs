@@ -0,0 +1,163 @@
+use sha2::{Digest as _, Sha256};
+
+/// Algorithm to hash the chunks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    /// SHA-256.
+    #[default]
+    Sha256,
+    /// BLAKE3, with an output of 32 bytes.
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Get the name of the algorithm as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Sha256 => "sha256",
+            | Self::Blake3 => "blake3",
+        }
+    }
+
+    /// Parse an algorithm from its name, as given by
+    /// [`HashAlgorithm::as_str`].
+    pub fn parse(name: &str) -> Option<Self> {
+        [Self::Sha256, Self::Blake3].into_iter().find(|a| a.as_str() == name)
+    }
+
+    /// Get the length of a digest of the algorithm in bytes.
+    pub fn output_len(&self) -> usize {
+        match self {
+            | Self::Sha256 | Self::Blake3 => 32,
+        }
+    }
+
+    /// Create a new hasher of the algorithm.
+    pub fn hasher(&self) -> Hasher {
+        match self {
+            | Self::Sha256 => {
+                Hasher { inner: HasherInner::Sha256(Sha256::new()) }
+            },
+            | Self::Blake3 => {
+                Hasher { inner: HasherInner::Blake3(Box::default()) }
+            },
+        }
+    }
+
+    /// Hash the data at once.
+    pub fn digest(
+        &self,
+        data: &[u8],
+    ) -> Digest {
+        let mut hasher: Hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+/// Incremental hasher.
+///
+/// ## Example
+///
+/// ```
+/// use filerune_fusion::hash::{Digest, HashAlgorithm, Hasher};
+///
+/// let mut hasher: Hasher = HashAlgorithm::Sha256.hasher();
+///
+/// hasher.update(b"hello ");
+/// hasher.update(b"world");
+///
+/// let digest: Digest = hasher.finalize();
+///
+/// assert_eq!(digest, HashAlgorithm::Sha256.digest(b"hello world"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Hasher {
+    inner: HasherInner,
+}
+
+#[derive(Debug, Clone)]
+enum HasherInner {
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    /// Get the algorithm of the hasher.
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self.inner {
+            | HasherInner::Sha256(_) => HashAlgorithm::Sha256,
+            | HasherInner::Blake3(_) => HashAlgorithm::Blake3,
+        }
+    }
+
+    /// Feed data into the hasher.
+    pub fn update(
+        &mut self,
+        data: &[u8],
+    ) {
+        match self.inner {
+            | HasherInner::Sha256(ref mut h) => h.update(data),
+            | HasherInner::Blake3(ref mut h) => {
+                h.update(data);
+            },
+        }
+    }
+
+    /// Finish hashing and get the digest.
+    pub fn finalize(self) -> Digest {
+        let algorithm: HashAlgorithm = self.algorithm();
+
+        let bytes: Vec<u8> = match self.inner {
+            | HasherInner::Sha256(h) => h.finalize().to_vec(),
+            | HasherInner::Blake3(h) => h.finalize().as_bytes().to_vec(),
+        };
+
+        Digest { algorithm, bytes }
+    }
+}
+
+/// Digest produced by a hasher.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Digest {
+    /// The algorithm of the digest.
+    pub algorithm: HashAlgorithm,
+    /// The raw bytes of the digest.
+    pub bytes: Vec<u8>,
+}
+
+impl Digest {
+    /// Get the digest as a lowercase hex string.
+    pub fn to_hex(&self) -> String {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        let mut hex: String = String::with_capacity(self.bytes.len() * 2);
+
+        for b in self.bytes.iter() {
+            hex.push(HEX[(b >> 4) as usize] as char);
+            hex.push(HEX[(b & 0x0f) as usize] as char);
+        }
+
+        hex
+    }
+
+    /// Create a digest of the algorithm from a hex string, in either case.
+    ///
+    /// Returns `None` if the string is not valid hex
+    /// or not the length of a digest of the algorithm.
+    pub fn from_hex(
+        algorithm: HashAlgorithm,
+        hex: &str,
+    ) -> Option<Self> {
+        if hex.len() != algorithm.output_len() * 2 {
+            return None;
+        }
+
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        Some(Self { algorithm, bytes })
+    }
+}
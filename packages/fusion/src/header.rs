@@ -0,0 +1,246 @@
+use std::{
+    fs,
+    io::{self, Read as _},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    hash::{Digest, HashAlgorithm},
+    sys::read_options,
+};
+
+/// The magic bytes at the start of a chunk with a header.
+const MAGIC: &[u8; 8] = b"FRCHUNK\0";
+
+/// The version of the chunk header format.
+const VERSION: u8 = 1;
+
+/// The length of the digest of the payload in the header of a chunk.
+const DIGEST_LEN: usize = 32;
+
+/// The value of a field of the header unknown when the chunk is written.
+const UNKNOWN: u64 = u64::MAX;
+
+/// Chunk file with its header.
+pub(crate) type HeaderEntry = (ChunkHeader, PathBuf);
+
+/// The length of the header at the start of a chunk.
+pub const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 8 + DIGEST_LEN;
+
+/// Format of the chunk files.
+///
+/// With [`ChunkFormat::Header`], each chunk begins with a [`ChunkHeader`]
+/// describing its place in the chunk set, so the chunks are merged and
+/// checked in order even when renamed, and their payload verified.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{header::ChunkFormat, split::Split};
+///
+/// let process: Split = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .format(ChunkFormat::Header);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChunkFormat {
+    /// The chunks hold their content only.
+    #[default]
+    Raw,
+    /// The chunks begin with a header before their content.
+    Header,
+}
+
+impl ChunkFormat {
+    /// Get the name of the format as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Raw => "raw",
+            | Self::Header => "header",
+        }
+    }
+}
+
+/// Header at the start of a chunk written with [`ChunkFormat::Header`].
+///
+/// The header is [`HEADER_LEN`] bytes long: the magic bytes `FRCHUNK\0`,
+/// the version of the format, the index of the chunk, the total number of
+/// chunks and the size of the original file as little-endian `u64`, then the
+/// SHA-256 digest of the payload. The payload is the rest of the chunk,
+/// the content compressed if enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkHeader {
+    /// The index of the chunk.
+    pub index: usize,
+    /// The total number of chunks, if known when the chunk is written.
+    ///
+    /// It is unknown when the chunks are cut by the content or
+    /// the size of the input is unknown, such as splitting a stream.
+    pub total_chunks: Option<usize>,
+    /// The size of the original file in bytes,
+    /// if known when the chunk is written.
+    pub file_size: Option<u64>,
+    /// The SHA-256 digest of the payload.
+    pub digest: Digest,
+}
+
+impl ChunkHeader {
+    /// Create the header of a payload.
+    pub fn new(
+        index: usize,
+        total_chunks: Option<usize>,
+        file_size: Option<u64>,
+        payload: &[u8],
+    ) -> Self {
+        Self {
+            index,
+            total_chunks,
+            file_size,
+            digest: HashAlgorithm::Sha256.digest(payload),
+        }
+    }
+
+    /// Encode the header.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut header: Vec<u8> = Vec::with_capacity(HEADER_LEN);
+
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+
+        for value in [
+            Some(self.index as u64),
+            self.total_chunks.map(|total| total as u64),
+            self.file_size,
+        ] {
+            header.extend_from_slice(&value.unwrap_or(UNKNOWN).to_le_bytes());
+        }
+
+        header.extend_from_slice(&self.digest.bytes);
+
+        header
+    }
+
+    /// Decode the header at the start of a chunk, not verifying the payload.
+    ///
+    /// Returns `None` if the data does not start with a header.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let mut cursor: &[u8] = data.strip_prefix(MAGIC)?;
+
+        let (version, rest) = cursor.split_first()?;
+
+        if *version != VERSION {
+            return None;
+        }
+
+        cursor = rest;
+
+        let index: usize = read_field(&mut cursor)??;
+        let total_chunks: Option<usize> = read_field(&mut cursor)?;
+        let file_size: Option<u64> = read_field(&mut cursor)?;
+
+        if total_chunks.is_some_and(|total| index >= total) {
+            return None;
+        }
+
+        let bytes: Vec<u8> = cursor.get(..DIGEST_LEN)?.to_vec();
+
+        Some(Self {
+            index,
+            total_chunks,
+            file_size,
+            digest: Digest { algorithm: HashAlgorithm::Sha256, bytes },
+        })
+    }
+
+    /// Read the header at the start of a chunk file.
+    ///
+    /// Returns `None` if the file does not start with a header.
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Option<Self>> {
+        let mut file: fs::File = read_options().open(path.as_ref())?;
+
+        let mut data: Vec<u8> = Vec::with_capacity(HEADER_LEN);
+
+        (&mut file).take(HEADER_LEN as u64).read_to_end(&mut data)?;
+
+        Ok(Self::decode(&data))
+    }
+
+    /// Check if the header describes the same chunk set as another.
+    pub(crate) fn is_same_set(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.total_chunks == other.total_chunks
+            && self.file_size == other.file_size
+    }
+}
+
+/// Prepend the header to the payload of a chunk.
+pub(crate) fn encode(
+    index: usize,
+    total_chunks: Option<usize>,
+    file_size: Option<u64>,
+    payload: &[u8],
+) -> Vec<u8> {
+    let mut data: Vec<u8> = Vec::with_capacity(HEADER_LEN + payload.len());
+
+    data.extend_from_slice(
+        &ChunkHeader::new(index, total_chunks, file_size, payload).encode(),
+    );
+    data.extend_from_slice(payload);
+
+    data
+}
+
+/// Strip the header of a chunk, verified by the digest of its payload.
+pub(crate) fn decode(mut data: Vec<u8>) -> io::Result<Vec<u8>> {
+    let header: ChunkHeader = ChunkHeader::decode(&data)
+        .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?;
+
+    let payload: &[u8] = &data[HEADER_LEN..];
+
+    if HashAlgorithm::Sha256.digest(payload) != header.digest {
+        return Err(io::Error::from(io::ErrorKind::InvalidData));
+    }
+
+    data.drain(..HEADER_LEN);
+
+    Ok(data)
+}
+
+/// Read the headers of the chunk files, sorted by index.
+///
+/// The files not starting with a header are returned apart.
+pub(crate) fn read_headers(
+    files: Vec<PathBuf>
+) -> io::Result<(Vec<HeaderEntry>, Vec<PathBuf>)> {
+    let mut chunks: Vec<HeaderEntry> = Vec::with_capacity(files.len());
+
+    let mut others: Vec<PathBuf> = Vec::new();
+
+    for path in files {
+        match ChunkHeader::read(&path)? {
+            | Some(header) => chunks.push((header, path)),
+            | None => others.push(path),
+        }
+    }
+
+    chunks.sort_by(|a, b| (a.0.index, &a.1).cmp(&(b.0.index, &b.1)));
+
+    Ok((chunks, others))
+}
+
+/// Take the next `u64` of the cursor as an optional integer.
+fn read_field<T: TryFrom<u64>>(cursor: &mut &[u8]) -> Option<Option<T>> {
+    let (value, rest) = cursor.split_at_checked(8)?;
+
+    *cursor = rest;
+
+    match u64::from_le_bytes(value.try_into().ok()?) {
+        | UNKNOWN => Some(None),
+        | value => T::try_from(value).ok().map(Some),
+    }
+}
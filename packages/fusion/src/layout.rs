@@ -0,0 +1,60 @@
+/// Get the number of chunks of a file split by a fixed chunk size,
+/// as written by [`Split::chunk_size`](crate::split::Split::chunk_size).
+///
+/// Every chunk has the chunk size except the last one, which holds the
+/// remaining bytes. An empty file has no chunk, nor a chunk size of `0`.
+/// The sizes and the offsets in the file are `u64`, so the files larger
+/// than the address space of 32-bit targets are counted correctly.
+///
+/// ## Example
+///
+/// ```
+/// use filerune_fusion::layout::{chunk_count, chunk_range, index_for_offset};
+///
+/// // a file of 10 bytes split into chunks of 4 bytes
+/// assert_eq!(chunk_count(10, 4), 3);
+/// assert_eq!(chunk_range(2, 10, 4), Some((8, 2)));
+/// assert_eq!(index_for_offset(9, 10, 4), Some(2));
+/// ```
+pub fn chunk_count(
+    file_size: u64,
+    chunk_size: usize,
+) -> usize {
+    if chunk_size == 0 {
+        return 0;
+    }
+
+    file_size.div_ceil(chunk_size as u64) as usize
+}
+
+/// Get the offset and the size of the chunk at an index.
+///
+/// Returns `None` if the file has no chunk at the index.
+pub fn chunk_range(
+    index: usize,
+    file_size: u64,
+    chunk_size: usize,
+) -> Option<(u64, usize)> {
+    if index >= chunk_count(file_size, chunk_size) {
+        return None;
+    }
+
+    let offset: u64 = index as u64 * chunk_size as u64;
+
+    Some((offset, (chunk_size as u64).min(file_size - offset) as usize))
+}
+
+/// Get the index of the chunk holding the byte at an offset.
+///
+/// Returns `None` if the offset is not within the file.
+pub fn index_for_offset(
+    offset: u64,
+    file_size: u64,
+    chunk_size: usize,
+) -> Option<usize> {
+    if offset >= file_size || chunk_size == 0 {
+        return None;
+    }
+
+    Some((offset / chunk_size as u64) as usize)
+}
@@ -61,6 +61,26 @@
 //!     .unwrap();
 //! # }
 //! ```
+//!
+//! UTF-8 paths of `camino` are accepted as inputs, and read back from the
+//! results with the `camino` feature:
+//!
+//! ```no_run
+//! # #[cfg(feature = "camino")]
+//! # {
+//! use camino::{Utf8Path, Utf8PathBuf};
+//!
+//! use filerune_fusion::split::{Split, SplitResult};
+//!
+//! let result: SplitResult = Split::new()
+//!     .in_file(Utf8PathBuf::from("path/to/file"))
+//!     .out_dir(Utf8PathBuf::from("path/to/dir"))
+//!     .run()
+//!     .unwrap();
+//!
+//! let path: &Utf8Path = result.chunks[0].utf8_path().unwrap();
+//! # }
+//! ```
 
 /// Split module.
 pub mod split;
@@ -71,6 +91,139 @@ pub mod check;
 /// Merge module.
 pub mod merge;
 
+/// Compare module.
+pub mod compare;
+
+/// Cancel module.
+pub mod cancel;
+
+/// Bitmap module.
+pub mod bitmap;
+
+/// Chunk module.
+pub mod chunk;
+
+/// Layout module.
+pub mod layout;
+
+/// Strategy module.
+pub mod strategy;
+
+/// Warning module.
+pub mod warning;
+
+/// Progress module.
+pub mod progress;
+
+/// Hash module.
+pub mod hash;
+
+/// Multipart module.
+pub mod multipart;
+
+/// Sink module.
+pub mod sink;
+
+/// Nextcloud module.
+pub mod nextcloud;
+
+/// S3 module.
+pub mod s3;
+
+/// Preset module.
+pub mod preset;
+
+/// Script module.
+pub mod script;
+
+/// Store module.
+pub mod store;
+
+/// Exchange module.
+pub mod exchange;
+
+/// Source module.
+pub mod source;
+
+/// Recover module.
+pub mod recover;
+
+/// Parity module.
+pub mod parity;
+
+/// PAR2 module.
+pub mod par2;
+
+/// BagIt module.
+pub mod bagit;
+
+/// Stats module.
+pub mod stats;
+
+/// Bao module.
+pub mod bao;
+
+/// Header module.
+pub mod header;
+
+/// Pipeline module.
+pub mod pipeline;
+
+/// Upload module.
+pub mod upload;
+
+/// GC module.
+pub mod gc;
+
+/// Report module.
+pub mod report;
+
+/// Sync module.
+pub mod sync;
+
+/// Reflink module.
+pub mod reflink;
+
+/// Lock module.
+pub mod lock;
+
+/// Metadata module.
+pub mod metadata;
+
+/// Reader module.
+pub mod reader;
+
+/// Codec module.
+#[cfg(feature = "compression")]
+pub mod codec;
+
+/// Fountain module.
+#[cfg(feature = "fountain")]
+pub mod fountain;
+
+/// Database module.
+#[cfg(any(feature = "sqlx", feature = "diesel"))]
+pub mod db;
+
+/// Seal module.
+#[cfg(feature = "seal")]
+pub mod seal;
+
+/// tus module.
+#[cfg(feature = "tus")]
+pub mod tus;
+
+/// Watch module.
+#[cfg(feature = "watch")]
+pub mod watch;
+
+/// Functions implemented with the platform APIs.
+pub(crate) mod sys;
+
+/// Functions implemented over the file system of any asynchronous runtime.
+#[cfg(any(feature = "async_std", feature = "smol", feature = "tokio"))]
+pub(crate) mod runtime;
+
 /// Functions implemented with `async_std`.
 #[cfg(feature = "async_std")]
 pub(crate) mod async_std;
@@ -83,6 +236,10 @@ pub(crate) mod smol;
 #[cfg(feature = "tokio")]
 pub(crate) mod tokio;
 
+/// Functions implemented with `tokio_uring`.
+#[cfg(all(feature = "tokio_uring", target_os = "linux"))]
+pub(crate) mod tokio_uring;
+
 /// The default chunk size in bytes.
 pub const CHUNK_SIZE_DEFAULT: usize = 2 * 1024 * 1024;
 
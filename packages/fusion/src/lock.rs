@@ -0,0 +1,183 @@
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::sys::{is_same_file, try_lock};
+
+/// The extension of the lock file, appended to the path locked.
+pub const LOCK_EXTENSION: &str = "lock";
+
+/// The number of attempts to lock a lock file removed meanwhile.
+const LOCK_ATTEMPTS: usize = 8;
+
+/// Lock error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockError {
+    Locked,
+    LockNotAcquired,
+}
+
+impl LockError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::Locked => "locked",
+            | Self::LockNotAcquired => "lock_not_acquired",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::Locked => "The path is locked by another process.",
+            | Self::LockNotAcquired => "The lock file could not be acquired.",
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Mode of a lock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LockMode {
+    /// Held by any number of readers at once.
+    Shared,
+    /// Held by a single writer, with no reader.
+    #[default]
+    Exclusive,
+}
+
+impl LockMode {
+    /// Get the name of the mode as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Shared => "shared",
+            | Self::Exclusive => "exclusive",
+        }
+    }
+}
+
+/// Advisory lock on a chunk directory or an output file, held until dropped.
+///
+/// The lock is taken with `flock` on a lock file next to the path locked,
+/// `<path>.lock`, so nothing is written inside a chunk directory and the
+/// lock file is never taken for a chunk. The lock is never waited for,
+/// a conflicting lock held by another process fails with
+/// [`LockError::Locked`]. Once dropped, the lock file is removed unless
+/// still held by another process.
+///
+/// The lock is advisory, only the processes taking it are excluded. On
+/// Windows, it is taken with `LockFileEx` instead, and a shared lock file
+/// is left once dropped. On platforms without file locks, the lock fails
+/// with [`LockError::LockNotAcquired`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::lock::{FileLock, LockMode};
+///
+/// let lock: FileLock = FileLock::acquire(
+///     PathBuf::from("path").join("to").join("dir"),
+///     LockMode::Exclusive,
+/// )
+/// .unwrap();
+///
+/// // write the chunks...
+///
+/// drop(lock);
+/// ```
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+    mode: LockMode,
+    file: fs::File,
+}
+
+impl FileLock {
+    /// Take a lock on a path, without waiting.
+    pub fn acquire<Target: AsRef<Path>>(
+        target: Target,
+        mode: LockMode,
+    ) -> Result<Self, LockError> {
+        let path: PathBuf = Self::lock_path(target);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|_| LockError::LockNotAcquired)?;
+        }
+
+        for _ in 0..LOCK_ATTEMPTS {
+            let file: fs::File = fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .read(true)
+                .write(true)
+                .open(&path)
+                .map_err(|_| LockError::LockNotAcquired)?;
+
+            let locked: bool = try_lock(&file, mode == LockMode::Exclusive)
+                .map_err(|_| LockError::LockNotAcquired)?;
+
+            if !locked {
+                return Err(LockError::Locked);
+            }
+
+            // removed by the previous holder meanwhile
+            if is_same_file(&file, &path) {
+                return Ok(Self { path, mode, file });
+            }
+        }
+
+        Err(LockError::LockNotAcquired)
+    }
+
+    /// Get the path of the lock file of a path.
+    pub fn lock_path<Target: AsRef<Path>>(target: Target) -> PathBuf {
+        let target: &Path = target.as_ref();
+
+        let mut name: OsString = target
+            .file_name()
+            .map_or_else(|| OsString::from(""), |n| n.to_os_string());
+
+        name.push(".");
+        name.push(LOCK_EXTENSION);
+
+        target.with_file_name(name)
+    }
+
+    /// Get the path of the lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the mode of the lock.
+    pub fn mode(&self) -> LockMode {
+        self.mode
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // only the last holder removes the lock file
+        let last: bool = match self.mode {
+            | LockMode::Exclusive => true,
+            | LockMode::Shared => try_lock(&self.file, true).unwrap_or(false),
+        };
+
+        if last {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
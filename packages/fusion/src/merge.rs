@@ -1,10 +1,57 @@
 use std::{
-    fs,
-    io::{self, Read as _, Write as _},
+    fmt, fs,
+    io::{self, Read as _, Seek as _, Write as _},
+    ops::Range,
     path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
-use crate::BUFFER_CAPACITY_DEFAULT;
+#[cfg(feature = "camino")]
+use camino::Utf8Path;
+
+#[cfg(feature = "compression")]
+use crate::codec::Codec;
+
+#[cfg(feature = "fountain")]
+use crate::fountain::{
+    EncodingPacket, FountainDecoder, ObjectTransmissionInformation,
+};
+
+#[cfg(feature = "reqwest")]
+use std::{collections::VecDeque, thread};
+
+#[cfg(feature = "reqwest")]
+use crate::store::{ChunkUrl, RefreshHandler};
+
+use crate::{
+    BUFFER_CAPACITY_DEFAULT,
+    bao::{self, BaoError, BaoVerifier},
+    cancel::CancellationToken,
+    check::{Check, CheckReport},
+    chunk::{ChunkInfo, ChunkNaming, ForeignNaming, is_system_file, temp_path},
+    hash::{Digest, HashAlgorithm, Hasher},
+    header::{self, ChunkFormat, ChunkHeader},
+    lock::{FileLock, LockError, LockMode},
+    metadata::{self, FileMetadata},
+    par2,
+    parity::{self, ParityError},
+    progress::{Progress, ProgressGuard, WatermarkHandler},
+    reflink::ReflinkPolicy,
+    report::Report,
+    split::{Split, SplitResult},
+    store::{ChunkStore, MergeSink, StoreError},
+    sync::SyncPolicy,
+    sys::{
+        DiskSpace, allocate, clone_range, disk_space, long_path,
+        long_path_shared, read_options,
+    },
+    warning::{Warning, WarningHandler},
+};
 
 /// Run asynchronously with `async_std` feature.
 ///
@@ -45,6 +92,144 @@ pub mod tokio {
     pub use crate::tokio::merge::MergeAsyncExt;
 }
 
+/// Run asynchronously with `tokio_uring` feature, on Linux.
+///
+/// To use it, add the following code to the `Cargo.toml` file:
+///
+/// ```toml
+/// [dependencies]
+/// filerune_fusion = { version = "*", features = ["tokio_uring"] }
+/// ```
+#[cfg(all(feature = "tokio_uring", target_os = "linux"))]
+pub mod tokio_uring {
+    pub use crate::tokio_uring::merge::MergeAsyncExt;
+}
+
+/// The size of the blocks of zeros left as holes
+/// with [`Merge::sparse`], in bytes.
+pub const SPARSE_BLOCK_SIZE: usize = 4096;
+
+/// Result of the merge process.
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    /// The number of bytes written to the output.
+    pub bytes_written: u64,
+    /// The number of chunks merged.
+    pub chunks_merged: usize,
+    /// The output file, `None` if merged into an opened file.
+    pub out_file: Option<PathBuf>,
+    /// The time taken by the merge process.
+    pub duration: Duration,
+    /// The chunks merged into the output file, in order.
+    pub chunks: Vec<ChunkInfo>,
+    /// The number of bytes kept from an existing output when resumed.
+    pub bytes_resumed: u64,
+}
+
+#[cfg(feature = "camino")]
+impl MergeResult {
+    /// Get the output file as a UTF-8 path,
+    /// `None` if merged into an opened file or the path is not valid UTF-8.
+    pub fn utf8_out_file(&self) -> Option<&Utf8Path> {
+        Utf8Path::from_path(self.out_file.as_deref()?)
+    }
+}
+
+/// Part of a chunk to merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ChunkSlice {
+    /// The index of the chunk.
+    pub(crate) index: usize,
+    /// The path of the chunk file.
+    pub(crate) path: PathBuf,
+    /// The offset of the part in the chunk.
+    pub(crate) offset: usize,
+    /// The length of the part.
+    pub(crate) len: usize,
+    /// The position of the part in the original file.
+    pub(crate) position: u64,
+}
+
+/// Policy to resolve chunks with the same index.
+///
+/// Duplicates happen when the chunks are named with different paddings,
+/// such as `7` and `007`, or after renaming.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Fail with [`MergeError::DuplicateChunk`].
+    #[default]
+    Fail,
+    /// Keep the largest chunk.
+    PreferLarger,
+    /// Keep the most recently modified chunk.
+    PreferNewer,
+}
+
+/// Policy for an existing output file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutFilePolicy {
+    /// Fail with [`MergeError::OutFileExists`].
+    #[default]
+    Fail,
+    /// Replace the existing file. A directory is never removed.
+    Overwrite,
+    /// Rename the existing file or directory with a numbered suffix,
+    /// such as `file.1`, before merging.
+    RenameExisting,
+}
+
+/// Get the first free path to rename an existing output to.
+pub(crate) fn backup_path(out_file: &Path) -> PathBuf {
+    let file_name: String = out_file
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    (1..)
+        .map(|n| out_file.with_file_name(format!("{file_name}.{n}")))
+        .find(|p| !p.exists())
+        .unwrap()
+}
+
+/// Get the error of an input directory without chunk,
+/// whether it has other files or not.
+pub(crate) fn no_chunk(found: bool) -> MergeError {
+    match found {
+        | true => MergeError::InDirNoChunk,
+        | false => MergeError::InDirNoFile,
+    }
+}
+
+/// Get the directory of the output file, `None` if relative without one.
+pub(crate) fn out_dir(out_file: &Path) -> Option<&Path> {
+    out_file.parent().filter(|p| !p.as_os_str().is_empty())
+}
+
+/// Find where to resume merging the slices into an output of `len` bytes.
+///
+/// Returns the number of slices fully written and the length they cover.
+pub(crate) fn resume_point(
+    slices: &[ChunkSlice],
+    len: u64,
+) -> (usize, u64) {
+    let mut skipped: usize = 0;
+
+    let mut position: u64 = 0;
+
+    for slice in slices {
+        // the first incomplete chunk
+        if position + slice.len as u64 > len {
+            break;
+        }
+
+        skipped += 1;
+
+        position += slice.len as u64;
+    }
+
+    (skipped, position)
+}
+
 /// Merge process error enum.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MergeError {
@@ -53,13 +238,36 @@ pub enum MergeError {
     InDirNotSet,
     InDirNotRead,
     InDirNoFile,
+    InDirNoChunk,
+    InUrlNotRead,
+    InUrlExpired,
+    ChunkMismatch,
+    DuplicateChunk,
+    ChunkHeaderMismatch,
+    ChunksIncomplete,
+    FileSizeMismatch,
     InFileNotOpened,
     InFileNotRead,
     OutDirNotCreated,
     OutFileNotSet,
+    OutFileExists,
+    OutFileNotFile,
     OutFileNotRemoved,
+    OutFileNotRenamed,
     OutFileNotOpened,
     OutFileNotWritten,
+    OutFileNotCloned,
+    OutFileNotAllocated,
+    InsufficientSpace,
+    Locked,
+    LockNotAcquired,
+    HashMismatch,
+    OutboardNotFound,
+    ChunksNotRepaired,
+    SymbolsInsufficient,
+    WarningRaised,
+    Cancelled,
+    DeadlineExceeded,
 }
 
 impl MergeError {
@@ -71,13 +279,36 @@ impl MergeError {
             | Self::InDirNotSet => "in_dir_not_set",
             | Self::InDirNotRead => "in_dir_not_read",
             | Self::InDirNoFile => "in_dir_no_file",
+            | Self::InDirNoChunk => "in_dir_no_chunk",
+            | Self::InUrlNotRead => "in_url_not_read",
+            | Self::InUrlExpired => "in_url_expired",
+            | Self::ChunkMismatch => "chunk_mismatch",
+            | Self::DuplicateChunk => "duplicate_chunk",
+            | Self::ChunkHeaderMismatch => "chunk_header_mismatch",
+            | Self::ChunksIncomplete => "chunks_incomplete",
+            | Self::FileSizeMismatch => "file_size_mismatch",
             | Self::InFileNotOpened => "in_file_not_opened",
             | Self::InFileNotRead => "in_file_not_read",
             | Self::OutDirNotCreated => "out_dir_not_created",
             | Self::OutFileNotSet => "out_file_not_set",
+            | Self::OutFileExists => "out_file_exists",
+            | Self::OutFileNotFile => "out_file_not_file",
             | Self::OutFileNotRemoved => "out_file_not_removed",
+            | Self::OutFileNotRenamed => "out_file_not_renamed",
             | Self::OutFileNotOpened => "out_file_not_opened",
             | Self::OutFileNotWritten => "out_file_not_written",
+            | Self::OutFileNotCloned => "out_file_not_cloned",
+            | Self::OutFileNotAllocated => "out_file_not_allocated",
+            | Self::InsufficientSpace => "insufficient_space",
+            | Self::Locked => "locked",
+            | Self::LockNotAcquired => "lock_not_acquired",
+            | Self::HashMismatch => "hash_mismatch",
+            | Self::OutboardNotFound => "outboard_not_found",
+            | Self::ChunksNotRepaired => "chunks_not_repaired",
+            | Self::SymbolsInsufficient => "symbols_insufficient",
+            | Self::WarningRaised => "warning_raised",
+            | Self::Cancelled => "cancelled",
+            | Self::DeadlineExceeded => "deadline_exceeded",
         }
     }
 
@@ -94,19 +325,76 @@ impl MergeError {
             | Self::InDirNotSet => "The input directory is not set.",
             | Self::InDirNotRead => "The input directory could not be read.",
             | Self::InDirNoFile => "The input directory has no file.",
+            | Self::InDirNoChunk => {
+                "The input directory has files but no chunk."
+            },
+            | Self::InUrlNotRead => "An input URL could not be read.",
+            | Self::InUrlExpired => "An input URL is expired.",
+            | Self::ChunkMismatch => {
+                "A downloaded chunk does not match its expected size or hash."
+            },
+            | Self::DuplicateChunk => {
+                "The input directory has chunks with the same index."
+            },
+            | Self::ChunkHeaderMismatch => {
+                "The chunks do not match the chunk set of their headers."
+            },
+            | Self::ChunksIncomplete => {
+                "The chunks are not contiguous from 0 to the expected total."
+            },
+            | Self::FileSizeMismatch => {
+                "The size of the chunks does not match the expected file size."
+            },
             | Self::InFileNotOpened => "The input file could not be opened.",
             | Self::InFileNotRead => "The input file could not be read.",
             | Self::OutDirNotCreated => {
                 "The output directory could not be created."
             },
             | Self::OutFileNotSet => "The output file is not set.",
+            | Self::OutFileExists => "The output file already exists.",
+            | Self::OutFileNotFile => "The output file is not a file.",
             | Self::OutFileNotRemoved => {
                 "The output file could not be removed."
             },
+            | Self::OutFileNotRenamed => {
+                "The existing output file could not be renamed."
+            },
             | Self::OutFileNotOpened => "The output file could not be opened.",
             | Self::OutFileNotWritten => {
                 "The output file could not be written."
             },
+            | Self::OutFileNotCloned => {
+                "The output file could not be cloned from the chunks."
+            },
+            | Self::OutFileNotAllocated => {
+                "The disk space of the output file could not be allocated."
+            },
+            | Self::InsufficientSpace => {
+                "The output file has not enough free space to be merged."
+            },
+            | Self::Locked => {
+                "The input directory or the output file is locked by another process."
+            },
+            | Self::LockNotAcquired => {
+                "The lock of the input directory or the output file could not be acquired."
+            },
+            | Self::HashMismatch => {
+                "The hash of the output does not match the expected hash."
+            },
+            | Self::OutboardNotFound => {
+                "The outboard tree of the chunks not found."
+            },
+            | Self::ChunksNotRepaired => {
+                "The missing or corrupted chunks could not be repaired."
+            },
+            | Self::SymbolsInsufficient => {
+                "The symbols are not enough to reconstruct the file."
+            },
+            | Self::WarningRaised => "A warning was raised in strict mode.",
+            | Self::Cancelled => "The merge process was cancelled.",
+            | Self::DeadlineExceeded => {
+                "The deadline of the merge process was exceeded."
+            },
         }
     }
 
@@ -118,6 +406,10 @@ impl MergeError {
 
 /// Process to merge chunks from a directory to a path.
 ///
+/// On Windows, the paths are made absolute with the `\\?\` prefix,
+/// so the chunks can be read past the 260 characters of `MAX_PATH`
+/// and from UNC shares.
+///
 /// ## Example
 ///
 /// ```no_run
@@ -133,9 +425,50 @@ impl MergeError {
 /// ```
 #[derive(Debug, Clone)]
 pub struct Merge {
-    pub in_dir: Option<PathBuf>,
-    pub out_file: Option<PathBuf>,
+    pub in_dir: Option<Arc<Path>>,
+    #[cfg(feature = "reqwest")]
+    pub in_urls: Option<Arc<[ChunkUrl]>>,
+    #[cfg(feature = "reqwest")]
+    pub on_url_expired: Option<RefreshHandler>,
+    #[cfg(feature = "reqwest")]
+    pub concurrency: usize,
+    #[cfg(feature = "reqwest")]
+    pub retries: usize,
+    pub out_file: Option<Arc<Path>>,
+    pub out_writer: Option<OutWriter>,
     pub buffer_capacity: usize,
+    pub naming: ChunkNaming,
+    pub format: ChunkFormat,
+    pub detect_naming: bool,
+    pub duplicate_policy: DuplicatePolicy,
+    pub chunk_range: Option<Range<usize>>,
+    pub byte_range: Option<Range<u64>>,
+    pub resume: bool,
+    pub overwrite: OutFilePolicy,
+    pub atomic: bool,
+    pub sync: SyncPolicy,
+    pub reflink: ReflinkPolicy,
+    pub preallocate: bool,
+    pub check_space: bool,
+    pub space_reserve: u64,
+    pub lock: bool,
+    pub sparse: bool,
+    pub preserve_metadata: bool,
+    pub progressive: bool,
+    pub on_watermark: Option<WatermarkHandler>,
+    #[cfg(feature = "compression")]
+    pub compression: Option<Codec>,
+    pub repair: bool,
+    pub read_only_source: bool,
+    pub expected_hash: Option<Digest>,
+    pub bao: Option<Digest>,
+    pub expected_chunks: Option<usize>,
+    pub expected_size: Option<u64>,
+    pub strict: bool,
+    pub cancellation_token: Option<CancellationToken>,
+    pub deadline: Option<Instant>,
+    pub progress: Option<Progress>,
+    pub on_warning: Option<WarningHandler>,
 }
 
 impl Merge {
@@ -143,22 +476,238 @@ impl Merge {
     pub fn new() -> Self {
         Self {
             in_dir: None,
+            #[cfg(feature = "reqwest")]
+            in_urls: None,
+            #[cfg(feature = "reqwest")]
+            on_url_expired: None,
+            #[cfg(feature = "reqwest")]
+            concurrency: 1,
+            #[cfg(feature = "reqwest")]
+            retries: 0,
             out_file: None,
+            out_writer: None,
             buffer_capacity: BUFFER_CAPACITY_DEFAULT,
+            naming: ChunkNaming::new(),
+            format: ChunkFormat::Raw,
+            detect_naming: false,
+            duplicate_policy: DuplicatePolicy::Fail,
+            chunk_range: None,
+            byte_range: None,
+            resume: false,
+            overwrite: OutFilePolicy::Fail,
+            atomic: false,
+            sync: SyncPolicy::None,
+            reflink: ReflinkPolicy::Never,
+            preallocate: false,
+            check_space: false,
+            space_reserve: 0,
+            lock: false,
+            sparse: false,
+            preserve_metadata: false,
+            progressive: false,
+            on_watermark: None,
+            #[cfg(feature = "compression")]
+            compression: None,
+            repair: false,
+            read_only_source: false,
+            expected_hash: None,
+            bao: None,
+            expected_chunks: None,
+            expected_size: None,
+            strict: false,
+            cancellation_token: None,
+            deadline: None,
+            progress: None,
+            on_warning: None,
         }
     }
 
-    /// Create a new merge process from an existing one.
+    /// Create a new merge process from an existing one,
+    /// or from a split or a check process as converted from
+    /// [`&Split`](Split) or [`&Check`](Check).
     pub fn from<P: Into<Merge>>(process: P) -> Self {
         process.into()
     }
 
+    /// Create a new merge process of the chunks written by a split run.
+    ///
+    /// The input directory, the naming scheme, the format and the codec
+    /// are the ones of the split process, and the chunks are expected as
+    /// in its result, see [`Merge::expected_chunks`]. The output is
+    /// verified against the root of the `bao` outboard tree,
+    /// if written by [`Split::bao`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{
+    ///     merge::Merge,
+    ///     split::{Split, SplitResult},
+    /// };
+    ///
+    /// let split: Split = Split::new()
+    ///     .in_file(PathBuf::from("path").join("to").join("file"))
+    ///     .out_dir(PathBuf::from("path").join("to").join("dir"));
+    ///
+    /// let result: SplitResult = split.run().unwrap();
+    ///
+    /// Merge::from_split(&split, &result)
+    ///     .out_file(PathBuf::from("path").join("to").join("merged"))
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn from_split(
+        process: &Split,
+        result: &SplitResult,
+    ) -> Self {
+        Self {
+            bao: result.bao_hash.clone(),
+            expected_chunks: Some(result.total_chunks),
+            expected_size: Some(result.file_size),
+            ..Self::from(process)
+        }
+    }
+
+    /// Create a new merge process of the chunks checked by a check run.
+    ///
+    /// The input directory, the naming scheme, the format and the codec
+    /// are the ones of the check process, and the chunks are expected as
+    /// in its report, see [`Merge::expected_chunks`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{
+    ///     check::{Check, CheckReport},
+    ///     merge::Merge,
+    /// };
+    ///
+    /// let check: Check = Check::new()
+    ///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .file_size(1024 * 1024)
+    ///     .total_chunks(1);
+    ///
+    /// let report: CheckReport = check.run().unwrap();
+    ///
+    /// Merge::from_check(&check, &report)
+    ///     .out_file(PathBuf::from("path").join("to").join("merged"))
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn from_check(
+        process: &Check,
+        report: &CheckReport,
+    ) -> Self {
+        Self {
+            expected_chunks: Some(report.chunks.len()),
+            expected_size: Some(report.file_size),
+            ..Self::from(process)
+        }
+    }
+
     /// Set the input directory.
     pub fn in_dir<InDir: AsRef<Path>>(
         mut self,
         path: InDir,
     ) -> Self {
-        self.in_dir = Some(path.as_ref().to_path_buf());
+        self.in_dir = Some(long_path(path.as_ref()));
+        self
+    }
+
+    /// Set the input directory with a shared path.
+    ///
+    /// Unlike [`Merge::in_dir`], an [`Arc<Path>`] is stored without copying,
+    /// unless extended on Windows.
+    pub fn in_dir_shared<InDir: Into<Arc<Path>>>(
+        mut self,
+        path: InDir,
+    ) -> Self {
+        self.in_dir = Some(long_path_shared(path.into()));
+        self
+    }
+
+    /// Set the URLs of the chunks of a hosted chunk set, in order,
+    /// instead of the input directory.
+    ///
+    /// The chunks are downloaded up to [`Merge::concurrency`] at a time,
+    /// verified against their expected size and hash, and written to the
+    /// output file in order, so only the chunks downloaded ahead are kept
+    /// in memory. A chunk not matching is downloaded again up to
+    /// [`Merge::retries`], then the process fails with
+    /// [`MergeError::ChunkMismatch`]. The chunk and byte ranges, the resume
+    /// and repair options are ignored. It only applies to [`Merge::run`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{merge::Merge, store::ChunkUrl};
+    ///
+    /// let urls: Vec<ChunkUrl> = ChunkUrl::parse_manifest(
+    ///     "https://example.com/file.0 1048576\nhttps://example.com/file.1",
+    /// )
+    /// .unwrap();
+    ///
+    /// Merge::new()
+    ///     .in_urls(urls)
+    ///     .out_file(PathBuf::from("path").join("to").join("file"))
+    ///     .concurrency(4)
+    ///     .retries(3)
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "reqwest")]
+    pub fn in_urls<InUrls: IntoIterator<Item = ChunkUrl>>(
+        mut self,
+        urls: InUrls,
+    ) -> Self {
+        self.in_urls = Some(urls.into_iter().collect());
+        self
+    }
+
+    /// Set the handler to refresh the URL of a chunk once expired.
+    ///
+    /// When a request to a chunk URL is rejected with `401 Unauthorized`
+    /// or `403 Forbidden`, the URL returned by the handler is requested
+    /// instead. The process fails with [`MergeError::InUrlExpired`] if the
+    /// handler returns `None`. It only applies to [`Merge::in_urls`].
+    #[cfg(feature = "reqwest")]
+    pub fn on_url_expired<
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    >(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_url_expired = Some(RefreshHandler::new(handler));
+        self
+    }
+
+    /// Set the maximum number of chunks to download concurrently.
+    ///
+    /// By default, it is `1`. It only applies to [`Merge::in_urls`].
+    #[cfg(feature = "reqwest")]
+    pub fn concurrency(
+        mut self,
+        concurrency: usize,
+    ) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Set the number of times a failed download of a chunk is retried.
+    ///
+    /// By default, it is `0`. It only applies to [`Merge::in_urls`].
+    #[cfg(feature = "reqwest")]
+    pub fn retries(
+        mut self,
+        retries: usize,
+    ) -> Self {
+        self.retries = retries;
         self
     }
 
@@ -167,7 +716,48 @@ impl Merge {
         mut self,
         path: OutFile,
     ) -> Self {
-        self.out_file = Some(path.as_ref().to_path_buf());
+        self.out_file = Some(long_path(path.as_ref()));
+        self
+    }
+
+    /// Set the output file with a shared path.
+    ///
+    /// Unlike [`Merge::out_file`], an [`Arc<Path>`] is stored without copying,
+    /// unless extended on Windows.
+    pub fn out_file_shared<OutFile: Into<Arc<Path>>>(
+        mut self,
+        path: OutFile,
+    ) -> Self {
+        self.out_file = Some(long_path_shared(path.into()));
+        self
+    }
+
+    /// Set a writer as the output, instead of the output file,
+    /// such as the standard output.
+    ///
+    /// The chunks are written in order as by [`Merge::run_to_writer`],
+    /// so the options of the output file, such as the resume and the
+    /// atomic options, are ignored. A clone of the process shares the
+    /// writer. It only applies to [`Merge::run`] with the input directory.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::{io, path::PathBuf};
+    ///
+    /// use filerune_fusion::merge::{Merge, MergeResult};
+    ///
+    /// let result: MergeResult = Merge::new()
+    ///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .out_writer(io::stdout())
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn out_writer<W: io::Write + Send + 'static>(
+        mut self,
+        writer: W,
+    ) -> Self {
+        self.out_writer = Some(OutWriter::new(writer));
         self
     }
 
@@ -182,122 +772,2194 @@ impl Merge {
         self
     }
 
-    /// Run the merge process.
-    pub fn run(&self) -> Result<(), MergeError> {
-        let in_dir: &Path = match self.in_dir {
-            | Some(ref p) => {
-                let p: &Path = p.as_ref();
+    /// Set the naming scheme of the chunk files.
+    ///
+    /// By default, chunks are named by their index only.
+    pub fn naming(
+        mut self,
+        naming: ChunkNaming,
+    ) -> Self {
+        self.naming = naming;
+        self
+    }
 
-                // if in_dir not exists
-                if !p.exists() {
-                    return Err(MergeError::InDirNotFound);
-                }
+    /// Set the prefix of the chunk file names.
+    pub fn chunk_prefix<Prefix: Into<String>>(
+        mut self,
+        prefix: Prefix,
+    ) -> Self {
+        self.naming = self.naming.prefix(prefix);
+        self
+    }
 
-                // if in_dir not a directory
-                if !p.is_dir() {
-                    return Err(MergeError::InDirNotDir);
-                }
+    /// Set the extension of the chunk file names.
+    pub fn chunk_extension<Extension: AsRef<str>>(
+        mut self,
+        extension: Extension,
+    ) -> Self {
+        self.naming = self.naming.extension(extension);
+        self
+    }
 
-                p
-            },
-            | None => return Err(MergeError::InDirNotSet),
-        };
+    /// Set the minimum width of the chunk index, padded with zeros.
+    pub fn pad_width(
+        mut self,
+        width: usize,
+    ) -> Self {
+        self.naming = self.naming.pad_width(width);
+        self
+    }
 
-        let out_file: &Path = match self.out_file {
-            | Some(ref p) => {
-                let p: &Path = p.as_ref();
+    /// Set the index of the first chunk in the file names.
+    pub fn start_index(
+        mut self,
+        index: usize,
+    ) -> Self {
+        self.naming = self.naming.start_index(index);
+        self
+    }
 
-                // delete out_path target if exists
-                if p.exists() {
-                    if p.is_dir() {
-                        fs::remove_dir_all(p)
-                            .map_err(|_| MergeError::OutFileNotRemoved)?;
-                    } else {
-                        fs::remove_file(p)
-                            .map_err(|_| MergeError::OutFileNotRemoved)?;
-                    }
-                }
+    /// Set the format of the chunk files, as split by
+    /// [`Split::format`](crate::split::Split::format).
+    ///
+    /// With [`ChunkFormat::Header`], the chunks are ordered by the index in
+    /// their headers instead of their names, so renamed chunks are still
+    /// merged in order, and the files without header are ignored.
+    /// The payload of each chunk is verified by its header. The chunks must
+    /// describe the same chunk set, complete unless a range is merged,
+    /// else the process fails with [`MergeError::ChunkHeaderMismatch`].
+    ///
+    /// By default, it is [`ChunkFormat::Raw`].
+    pub fn format(
+        mut self,
+        format: ChunkFormat,
+    ) -> Self {
+        self.format = format;
+        self
+    }
 
-                // create outpath
-                if let Some(parent) = p.parent() {
-                    fs::create_dir_all(parent)
-                        .map_err(|_| MergeError::OutDirNotCreated)?;
-                }
+    /// Set whether to detect the naming scheme of chunks split by other tools.
+    ///
+    /// When no chunk follows the naming scheme of the process,
+    /// the chunks are merged by the detected [`ForeignNaming`],
+    /// such as GNU `split`, HJSplit, 7-Zip or `.part1`.
+    ///
+    /// By default, it is `false`.
+    pub fn detect_naming(
+        mut self,
+        detect: bool,
+    ) -> Self {
+        self.detect_naming = detect;
+        self
+    }
 
-                p
-            },
-            | None => return Err(MergeError::OutFileNotSet),
-        };
+    /// Set the policy to resolve chunks with the same index.
+    ///
+    /// The discarded chunks are reported as [`Warning::DuplicateChunk`].
+    ///
+    /// By default, it is [`DuplicatePolicy::Fail`].
+    pub fn duplicate_policy(
+        mut self,
+        policy: DuplicatePolicy,
+    ) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
 
-        let buffer_capacity: usize = self.buffer_capacity;
+    /// Set the range of the chunk indices to merge.
+    ///
+    /// Only the chunks in the range are merged, to extract a part of the
+    /// original file without merging the whole file.
+    ///
+    /// By default, all chunks are merged.
+    pub fn chunks(
+        mut self,
+        range: Range<usize>,
+    ) -> Self {
+        self.chunk_range = Some(range);
+        self
+    }
 
-        let output: fs::File = fs::OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(out_file)
-            .map_err(|_| MergeError::OutFileNotOpened)?;
+    /// Set the range of the bytes to merge.
+    ///
+    /// The offsets are positions in the original file, counted over the
+    /// chunks found. Only the bytes in the range are merged, such as the
+    /// header of the file or the range of an HTTP `Range` request.
+    ///
+    /// By default, all bytes are merged.
+    pub fn byte_range(
+        mut self,
+        range: Range<u64>,
+    ) -> Self {
+        self.byte_range = Some(range);
+        self
+    }
 
-        // writer
-        let mut writer: io::BufWriter<fs::File> =
-            io::BufWriter::with_capacity(buffer_capacity, output);
+    /// Set the policy for an existing output file.
+    ///
+    /// It does not apply to an output file resumed by [`Merge::resume`].
+    ///
+    /// By default, it is [`OutFilePolicy::Fail`].
+    pub fn overwrite(
+        mut self,
+        policy: OutFilePolicy,
+    ) -> Self {
+        self.overwrite = policy;
+        self
+    }
 
-        // get inputs
-        let mut entries: Vec<PathBuf> = {
-            let read_dir: fs::ReadDir =
-                fs::read_dir(in_dir).map_err(|_| MergeError::InDirNotRead)?;
-
-            read_dir
-                .filter_map(Result::ok)
-                .filter(|entry| entry.path().is_file())
-                .map(|entry| entry.path())
-                .collect()
-        };
+    /// Set whether to resume merging into an existing output file.
+    ///
+    /// The length of the existing output file is compared to the offsets of
+    /// the chunks, and the merge continues from the first incomplete chunk
+    /// instead of starting over. The content already written is not verified.
+    /// Once cancelled, the output file is kept to be resumed later.
+    ///
+    /// Only applies when merging into the output file of the process.
+    ///
+    /// By default, it is `false`.
+    pub fn resume(
+        mut self,
+        resume: bool,
+    ) -> Self {
+        self.resume = resume;
+        self
+    }
 
-        if entries.is_empty() {
-            return Err(MergeError::InDirNoFile);
-        }
+    /// Set whether to write the output file atomically.
+    ///
+    /// The output file is written to a `.tmp` sibling first, such as `file.tmp`,
+    /// and renamed once complete, so other processes polling the output file
+    /// never observe a partially written file.
+    ///
+    /// With [`Merge::resume`], the merge resumes from the `.tmp` file.
+    ///
+    /// By default, it is `false`.
+    pub fn atomic(
+        mut self,
+        atomic: bool,
+    ) -> Self {
+        self.atomic = atomic;
+        self
+    }
 
-        entries.sort_by_key(|entry| {
-            entry
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .parse::<usize>()
-                .unwrap()
-        });
+    /// Set the policy to sync written output file to the disk.
+    ///
+    /// By default, it is [`SyncPolicy::None`], the output file is only flushed.
+    pub fn sync(
+        mut self,
+        policy: SyncPolicy,
+    ) -> Self {
+        self.sync = policy;
+        self
+    }
+
+    /// Set the policy to clone the output file from the chunks with reflinks.
+    ///
+    /// With [`ReflinkPolicy::Always`], the process fails with
+    /// [`MergeError::OutFileNotCloned`] if the file system does not support it,
+    /// and on platforms other than Linux, see [`ReflinkPolicy`].
+    /// With [`ReflinkPolicy::Auto`], the fallback to a copy is reported once
+    /// as [`Warning::ReflinkFallback`] with the chunk.
+    /// It only applies to [`Merge::run`].
+    ///
+    /// By default, it is [`ReflinkPolicy::Never`], the data is copied.
+    pub fn reflink(
+        mut self,
+        policy: ReflinkPolicy,
+    ) -> Self {
+        self.reflink = policy;
+        self
+    }
+
+    /// Set whether to allocate the disk space of the output file up front.
+    ///
+    /// The space of the merged size is allocated before writing, which avoids
+    /// the fragmentation of the output file and fails fast with
+    /// [`MergeError::OutFileNotAllocated`] if the disk space is insufficient.
+    /// The space is allocated with `fallocate` on Linux and `F_PREALLOCATE`
+    /// on macOS. It is ignored on other platforms, such as Windows and the
+    /// BSDs, and on file systems without the support.
+    ///
+    /// By default, it is `false`.
+    pub fn preallocate(
+        mut self,
+        preallocate: bool,
+    ) -> Self {
+        self.preallocate = preallocate;
+        self
+    }
+
+    /// Set whether to verify the free space of the output file system
+    /// before writing.
+    ///
+    /// The space needed is the size of the file to merge, less the bytes
+    /// already written when resumed. If the space available is smaller,
+    /// the process fails early with [`MergeError::InsufficientSpace`]
+    /// instead of failing halfway with [`MergeError::OutFileNotWritten`].
+    /// It only applies to an output file set by path, and the space is
+    /// verified with `statvfs` on Unix only, the check always passes on
+    /// other platforms, such as Windows.
+    ///
+    /// By default, it is `false`.
+    pub fn check_space(
+        mut self,
+        check_space: bool,
+    ) -> Self {
+        self.check_space = check_space;
+        self
+    }
+
+    /// Set the bytes to keep free on the file system of the output file,
+    /// once the chunks are merged.
+    ///
+    /// It only applies with [`Merge::check_space`].
+    ///
+    /// By default, it is `0`.
+    pub fn space_reserve(
+        mut self,
+        bytes: u64,
+    ) -> Self {
+        self.space_reserve = bytes;
+        self
+    }
+
+    /// Set whether to take advisory locks on the input directory and the
+    /// output file while merging.
+    ///
+    /// The locks are taken on `<path>.lock` next to them, see [`FileLock`].
+    /// The input directory is locked shared, so other merges can read the
+    /// same chunks but no split can write them, or exclusive if the chunks
+    /// are repaired in place. With [`Merge::read_only_source`], nothing is
+    /// written next to the input directory, so it is not locked. The output
+    /// file is locked exclusive. If another process holds a conflicting
+    /// lock, the process fails early with [`MergeError::Locked`] instead of
+    /// clobbering the output.
+    ///
+    /// By default, it is `false`.
+    pub fn lock(
+        mut self,
+        lock: bool,
+    ) -> Self {
+        self.lock = lock;
+        self
+    }
+
+    /// Set whether to leave holes in the output file for the runs of zeros,
+    /// producing a sparse file.
+    ///
+    /// The runs of [`SPARSE_BLOCK_SIZE`] bytes or more of zeros, such as the
+    /// empty areas of a disk image, are skipped by seeking forward instead
+    /// of being written, so the file system allocates no space for them.
+    /// The content of the output is the same, and the output is hashed as
+    /// written. The output must be empty past the position written, as the
+    /// files created by the process are; with [`Merge::run_to_file`], the
+    /// opened file must be empty from its current position. The holes are
+    /// allocated anyway with [`Merge::preallocate`], and the chunks cloned
+    /// by [`Merge::reflink`] are not scanned. On Windows, the output is not
+    /// marked as sparse, so NTFS allocates the holes as zeros.
+    ///
+    /// By default, it is `false`.
+    pub fn sparse(
+        mut self,
+        sparse: bool,
+    ) -> Self {
+        self.sparse = sparse;
+        self
+    }
+
+    /// Set whether to apply the metadata recorded alongside the chunks
+    /// to the output file.
+    ///
+    /// The permissions, the timestamps and, where permitted, the ownership
+    /// recorded by
+    /// [`Split::preserve_metadata`](crate::split::Split::preserve_metadata)
+    /// are applied once the output file is written, see [`FileMetadata`].
+    /// If no metadata is recorded or it could not be applied, it is
+    /// reported as [`Warning::MetadataNotApplied`]. It only applies with
+    /// [`Merge::out_file`].
+    ///
+    /// By default, it is `false`.
+    pub fn preserve_metadata(
+        mut self,
+        preserve_metadata: bool,
+    ) -> Self {
+        self.preserve_metadata = preserve_metadata;
+        self
+    }
+
+    /// Set whether to merge progressively, so a concurrent reader can consume
+    /// the output file while it grows.
+    ///
+    /// The chunks are written in order, and the output is flushed after each
+    /// chunk, then synced by the [`Merge::sync`] policy. The length written so
+    /// far is reported as a watermark to the handler set with
+    /// [`Merge::on_watermark`], up to which the output is safe to read.
+    /// The output file is written in place, even if [`Merge::atomic`] is set.
+    ///
+    /// By default, it is `false`.
+    pub fn progressive(
+        mut self,
+        progressive: bool,
+    ) -> Self {
+        self.progressive = progressive;
+        self
+    }
+
+    /// Set the handler to receive the watermarks of a progressive merge.
+    ///
+    /// The watermark includes the length resumed with [`Merge::resume`].
+    pub fn on_watermark<F: Fn(u64) + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_watermark = Some(WatermarkHandler::new(handler));
+        self
+    }
+
+    /// Set the codec to decompress the chunks compressed by
+    /// [`Split::compression`](crate::split::Split::compression).
+    ///
+    /// The chunk and byte ranges apply to the decompressed content.
+    /// Compressed chunks are not cloned with reflinks.
+    ///
+    /// By default, the chunks are not compressed.
+    #[cfg(feature = "compression")]
+    pub fn compression(
+        mut self,
+        codec: Codec,
+    ) -> Self {
+        self.compression = Some(codec);
+        self
+    }
+
+    /// Set whether to repair the chunks from the parity chunks
+    /// written by [`Split::parity_chunks`](crate::split::Split::parity_chunks)
+    /// and the PAR2 files written by
+    /// [`Split::par2_recovery`](crate::split::Split::par2_recovery)
+    /// before merging.
+    ///
+    /// The missing or corrupted chunks are reconstructed in the input
+    /// directory and reported as [`Warning::ChunkRepaired`]. If more chunks
+    /// are lost than parity chunks are left, the process fails with
+    /// [`MergeError::ChunksNotRepaired`].
+    ///
+    /// By default, it is `false`.
+    pub fn repair(
+        mut self,
+        repair: bool,
+    ) -> Self {
+        self.repair = repair;
+        self
+    }
+
+    /// Set whether the input directory is read-only,
+    /// such as a mounted disc image or a write-protected drive.
+    ///
+    /// Nothing is written to the input directory: the chunks repaired
+    /// with [`Merge::repair`] are reconstructed in a temporary directory
+    /// of the system instead, read from there, and removed once merged.
+    /// The output file must be outside of the input directory.
+    ///
+    /// By default, it is `false`.
+    pub fn read_only_source(
+        mut self,
+        read_only_source: bool,
+    ) -> Self {
+        self.read_only_source = read_only_source;
+        self
+    }
+
+    /// Set the expected hash of the output, to verify it as it is written.
+    ///
+    /// The output is hashed with the algorithm while merged, including the
+    /// part resumed and the chunks cloned by reflink, so no separate read
+    /// of the merged file is needed. If the final digest differs from the
+    /// expected one, the process fails with [`MergeError::HashMismatch`].
+    /// With the chunk or byte ranges, only the part merged is hashed.
+    ///
+    /// On mismatch, the output file is removed, unless resumed with
+    /// [`Merge::resume`]. With [`Merge::atomic`], the temporary file is
+    /// removed instead of renamed to its path.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{hash::HashAlgorithm, merge::Merge};
+    ///
+    /// let digest: [u8; 32] = [0; 32];
+    ///
+    /// Merge::new()
+    ///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .out_file(PathBuf::from("path").join("to").join("file"))
+    ///     .expected_hash(HashAlgorithm::Sha256, digest)
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn expected_hash<D: Into<Vec<u8>>>(
+        mut self,
+        algorithm: HashAlgorithm,
+        digest: D,
+    ) -> Self {
+        self.expected_hash = Some(Digest { algorithm, bytes: digest.into() });
+        self
+    }
+
+    /// Set the root of the `bao` outboard tree, the BLAKE3 hash of the file,
+    /// to verify the output as it is written.
+    ///
+    /// The outboard tree written by [`Split::bao`](crate::split::Split::bao)
+    /// is read from the input directory, otherwise the process fails with
+    /// [`MergeError::OutboardNotFound`]. Each BLAKE3 chunk of
+    /// [`BAO_CHUNK_LEN`](crate::bao::BAO_CHUNK_LEN) bytes of the output is
+    /// verified once written, so a corruption fails the process with
+    /// [`MergeError::HashMismatch`] as soon as it is merged, rather than
+    /// after the whole file. With the chunk or byte ranges, the BLAKE3
+    /// chunks covered partially at the edges of the range are not verified.
+    /// The chunks merged from a store or from symbols are not verified.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{
+    ///     merge::Merge,
+    ///     split::{Split, SplitResult},
+    /// };
+    ///
+    /// let result: SplitResult = Split::new()
+    ///     .in_file(PathBuf::from("path").join("to").join("file"))
+    ///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .bao(true)
+    ///     .run()
+    ///     .unwrap();
+    ///
+    /// Merge::new()
+    ///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .out_file(PathBuf::from("path").join("to").join("merged"))
+    ///     .byte_range(4096..8192)
+    ///     .bao(result.bao_hash.unwrap().bytes)
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn bao<D: Into<Vec<u8>>>(
+        mut self,
+        root: D,
+    ) -> Self {
+        self.bao = Some(Digest {
+            algorithm: HashAlgorithm::Blake3,
+            bytes: root.into(),
+        });
+        self
+    }
+
+    /// Set the chunk set expected in the input directory,
+    /// its total number of chunks and the size of the original file.
+    ///
+    /// Before any byte is written, the indices of the chunks must be
+    /// contiguous from `0` up to the total, otherwise the process fails
+    /// with [`MergeError::ChunksIncomplete`], and the chunks must add up to
+    /// the file size, otherwise it fails with
+    /// [`MergeError::FileSizeMismatch`]. The whole chunk set is validated,
+    /// even when merging the chunk or byte ranges. The sizes are the ones
+    /// of the content, once decompressed and stripped of their header.
+    /// It does not apply to the chunks merged from a store or from symbols.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{
+    ///     merge::Merge,
+    ///     split::{Split, SplitResult},
+    /// };
+    ///
+    /// let result: SplitResult = Split::new()
+    ///     .in_file(PathBuf::from("path").join("to").join("file"))
+    ///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .run()
+    ///     .unwrap();
+    ///
+    /// Merge::new()
+    ///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .out_file(PathBuf::from("path").join("to").join("merged"))
+    ///     .expected_chunks(result.total_chunks, result.file_size)
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn expected_chunks(
+        mut self,
+        total_chunks: usize,
+        file_size: u64,
+    ) -> Self {
+        self.expected_chunks = Some(total_chunks);
+        self.expected_size = Some(file_size);
+        self
+    }
+
+    /// Set whether to fail on the first warning of the process.
+    ///
+    /// The warning is emitted to the handler set by
+    /// [`Merge::on_warning`] before the process fails with
+    /// [`MergeError::WarningRaised`], so conditions otherwise tolerated,
+    /// such as stray entries or a reflink falling back to a copy,
+    /// stop the process.
+    ///
+    /// By default, it is `false`.
+    pub fn strict(
+        mut self,
+        strict: bool,
+    ) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set the handle to poll the progress of the process.
+    pub fn progress(
+        mut self,
+        progress: Progress,
+    ) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Set the token to cancel the process.
+    ///
+    /// Once cancelled, the output file is removed.
+    pub fn cancellation_token(
+        mut self,
+        token: CancellationToken,
+    ) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Set the deadline of the process, checked between chunks.
+    ///
+    /// Once passed, the process fails with [`MergeError::DeadlineExceeded`]
+    /// and the output written is kept to be resumed.
+    pub fn deadline(
+        mut self,
+        deadline: Instant,
+    ) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the handler to receive the warnings of the process.
+    pub fn on_warning<F: Fn(&Warning) + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_warning = Some(WarningHandler::new(handler));
+        self
+    }
+
+    /// Emit a warning to the handler of the process.
+    ///
+    /// In strict mode, the process fails once the warning is emitted.
+    pub(crate) fn warn(
+        &self,
+        warning: Warning,
+    ) -> Result<(), MergeError> {
+        if let Some(ref handler) = self.on_warning {
+            handler.emit(&warning);
+        }
+
+        match self.strict {
+            | true => Err(MergeError::WarningRaised),
+            | false => Ok(()),
+        }
+    }
+
+    /// Emit a watermark to the handler of the process.
+    pub(crate) fn watermark(
+        &self,
+        watermark: u64,
+    ) {
+        if let Some(ref handler) = self.on_watermark {
+            handler.emit(watermark);
+        }
+    }
+
+    /// Check if the output file is written to a temporary file to rename.
+    pub(crate) fn is_atomic(&self) -> bool {
+        self.atomic && !self.progressive
+    }
+
+    /// Apply the metadata recorded in the input directory
+    /// to the output file, if enabled.
+    pub(crate) fn apply_metadata(
+        &self,
+        in_dir: &Path,
+        out_file: &Path,
+    ) -> Result<(), MergeError> {
+        if !self.preserve_metadata {
+            return Ok(());
+        }
+
+        let applied: bool = metadata::find_metadata(in_dir)
+            .and_then(|path| FileMetadata::read(path).ok().flatten())
+            .is_some_and(|metadata| metadata.apply(out_file).is_ok());
+
+        match applied {
+            | true => Ok(()),
+            | false => {
+                self.warn(Warning::MetadataNotApplied(out_file.to_path_buf()))
+            },
+        }
+    }
+
+    /// Take the locks on the input directory and the output file,
+    /// if enabled.
+    pub(crate) fn lock_paths(
+        &self,
+        in_dir: &Path,
+        out_file: Option<&Path>,
+    ) -> Result<Vec<FileLock>, MergeError> {
+        if !self.lock {
+            return Ok(Vec::new());
+        }
+
+        let map_err = |e: LockError| match e {
+            | LockError::Locked => MergeError::Locked,
+            | LockError::LockNotAcquired => MergeError::LockNotAcquired,
+        };
+
+        let mut locks: Vec<FileLock> = Vec::with_capacity(2);
+
+        // nothing is written next to a read-only source
+        if !self.read_only_source {
+            let mode: LockMode = match self.repair {
+                | true => LockMode::Exclusive,
+                | false => LockMode::Shared,
+            };
+
+            locks.push(FileLock::acquire(in_dir, mode).map_err(map_err)?);
+        }
+
+        if let Some(out_file) = out_file {
+            locks.push(
+                FileLock::acquire(out_file, LockMode::Exclusive)
+                    .map_err(map_err)?,
+            );
+        }
+
+        Ok(locks)
+    }
+
+    /// Verify the free space of the output file system for `len` bytes
+    /// more, if enabled.
+    pub(crate) fn ensure_space(
+        &self,
+        len: u64,
+    ) -> Result<(), MergeError> {
+        if !self.check_space {
+            return Ok(());
+        }
+
+        let out_dir: &Path = match self.out_file.as_deref() {
+            | Some(p) => match p.parent() {
+                | Some(dir) if !dir.as_os_str().is_empty() => dir,
+                | _ => Path::new("."),
+            },
+            // the output file is opened by the caller
+            | None => return Ok(()),
+        };
+
+        let space: DiskSpace = match disk_space(out_dir) {
+            | Ok(Some(space)) => space,
+            // the space is unknown
+            | _ => return Ok(()),
+        };
+
+        let required: u64 =
+            space.allocated(len).saturating_add(self.space_reserve);
+
+        match required > space.available {
+            | true => Err(MergeError::InsufficientSpace),
+            | false => Ok(()),
+        }
+    }
+
+    /// Create a verifier of the output starting with the chunk slices,
+    /// by the expected hash and the outboard tree of the input directory.
+    pub(crate) fn output_verifier(
+        &self,
+        in_dir: Option<&Path>,
+        slices: &[ChunkSlice],
+    ) -> Result<OutputVerifier, MergeError> {
+        let bao: Option<BaoVerifier<io::BufReader<fs::File>>> =
+            match (self.bao.as_ref(), in_dir) {
+                | (Some(root), Some(in_dir)) => {
+                    let outboard: fs::File = bao::find_outboard(in_dir)
+                        .and_then(|path| fs::File::open(path).ok())
+                        .ok_or(MergeError::OutboardNotFound)?;
+
+                    let position: u64 =
+                        slices.first().map_or(0, |slice| slice.position);
+
+                    Some(
+                        BaoVerifier::new(
+                            io::BufReader::new(outboard),
+                            root,
+                            position,
+                        )
+                        .map_err(|_| MergeError::HashMismatch)?,
+                    )
+                },
+                | _ => None,
+            };
+
+        Ok(OutputVerifier {
+            hasher: self.expected_hash.as_ref().map(|d| d.algorithm.hasher()),
+            expected_hash: self.expected_hash.clone(),
+            bao,
+        })
+    }
+
+    /// Feed the content of the chunk slices into the verifier of the output,
+    /// for the parts of the output not written through it.
+    pub(crate) fn verify_slices(
+        &self,
+        slices: &[ChunkSlice],
+        verifier: &mut OutputVerifier,
+    ) -> Result<(), MergeError> {
+        for slice in slices {
+            self.copy_slice(slice, |data| verifier.update(data))?;
+        }
+
+        Ok(())
+    }
+
+    /// Check if the chunks are encoded, compressed or with a header.
+    pub(crate) fn is_encoded(&self) -> bool {
+        #[cfg(feature = "compression")]
+        if self.compression.is_some() {
+            return true;
+        }
+
+        self.format != ChunkFormat::Raw
+    }
+
+    /// Decode the content of a chunk as stored,
+    /// verified by its header and decompressed if enabled.
+    pub(crate) fn decode(
+        &self,
+        data: Vec<u8>,
+    ) -> io::Result<Vec<u8>> {
+        let data: Vec<u8> = match self.format {
+            | ChunkFormat::Raw => data,
+            | ChunkFormat::Header => header::decode(data)?,
+        };
+
+        #[cfg(feature = "compression")]
+        if let Some(codec) = self.compression {
+            return codec.decompress(&data);
+        }
+
+        Ok(data)
+    }
+
+    /// Get the size of the content of a chunk file, once decoded.
+    pub(crate) fn chunk_len(
+        &self,
+        path: &Path,
+    ) -> Result<usize, MergeError> {
+        if self.is_encoded() {
+            return fs::read(path)
+                .and_then(|data| self.decode(data))
+                .map(|data| data.len())
+                .map_err(|_| MergeError::InFileNotRead);
+        }
+
+        fs::metadata(path)
+            .ok()
+            .and_then(|m| usize::try_from(m.len()).ok())
+            .ok_or(MergeError::InFileNotRead)
+    }
+
+    /// Check if the process is cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation_token.as_ref().is_some_and(|t| t.is_cancelled())
+    }
+
+    /// Check if the deadline of the process is exceeded.
+    pub(crate) fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Repair the chunks of the input directory from the parity chunks,
+    /// if enabled.
+    ///
+    /// With a read-only source, the chunks are repaired to a temporary
+    /// directory returned to be merged from.
+    pub(crate) fn repair_chunks(
+        &self,
+        in_dir: &Path,
+    ) -> Result<Option<RepairDir>, MergeError> {
+        if !self.repair {
+            return Ok(None);
+        }
+
+        let mut repair_dir: Option<RepairDir> = match self.read_only_source {
+            | true => Some(RepairDir::create()?),
+            | false => None,
+        };
+
+        let out_dir: PathBuf = match repair_dir {
+            | Some(ref d) => d.path.clone(),
+            | None => in_dir.to_path_buf(),
+        };
+
+        let map_err = |e: ParityError| match e {
+            | ParityError::ChunkNotRead => MergeError::InDirNotRead,
+            | _ => MergeError::ChunksNotRepaired,
+        };
+
+        let mut repaired: Vec<usize> =
+            parity::repair(in_dir, &out_dir, &self.naming, self.sync)
+                .map_err(map_err)?;
+
+        repaired.extend(
+            par2::repair(in_dir, &out_dir, &self.naming, self.sync)
+                .map_err(map_err)?,
+        );
+
+        repaired.sort();
+        repaired.dedup();
+
+        for index in repaired {
+            if let Some(ref mut d) = repair_dir {
+                d.chunks
+                    .push((index, out_dir.join(self.naming.file_name(index))));
+            }
+
+            self.warn(Warning::ChunkRepaired(
+                in_dir.join(self.naming.file_name(index)),
+            ))?;
+        }
+
+        Ok(repair_dir)
+    }
+
+    /// Index the chunk files found in the input directory, sorted by index.
+    ///
+    /// Files not following the naming scheme are reported as ignored,
+    /// chunks with the same index are resolved by the duplicate policy.
+    pub(crate) fn index_chunks(
+        &self,
+        mut files: Vec<PathBuf>,
+    ) -> Result<Vec<(usize, PathBuf)>, MergeError> {
+        // the parity chunks, the PAR2 files, the outboard tree
+        // and the files of the operating system are not merged
+        files.retain(|p| {
+            parity::index_of(&self.naming, p).is_none()
+                && !par2::is_par2(p)
+                && !bao::is_outboard(p)
+                && !metadata::is_metadata(p)
+                && !is_system_file(p)
+        });
+
+        if self.format == ChunkFormat::Header {
+            return self.index_headers(files);
+        }
+
+        let mut foreign: Option<ForeignNaming> = None;
+
+        // if no chunk follows the naming scheme
+        if self.detect_naming
+            && !files.iter().any(|p| self.naming.index_of(p).is_some())
+        {
+            foreign = ForeignNaming::detect(
+                files
+                    .iter()
+                    .filter_map(|p| p.file_name()?.to_str())
+                    .collect::<Vec<&str>>(),
+            );
+        }
+
+        let mut entries: Vec<(usize, PathBuf)> =
+            Vec::with_capacity(files.len());
+
+        for path in files {
+            let index: Option<usize> = match foreign {
+                | Some(ref naming) => naming.index_of(&path),
+                | None => self.naming.index_of(&path),
+            };
+
+            match index {
+                | Some(index) => entries.push((index, path)),
+                | None => self.warn(Warning::IgnoredEntry(path))?,
+            }
+        }
+
+        entries.sort();
+
+        self.resolve_duplicates(entries)
+    }
+
+    /// Index the chunk files by their headers, sorted by index.
+    ///
+    /// Files without header are reported as ignored.
+    fn index_headers(
+        &self,
+        files: Vec<PathBuf>,
+    ) -> Result<Vec<(usize, PathBuf)>, MergeError> {
+        let (headers, others) = header::read_headers(files)
+            .map_err(|_| MergeError::InDirNotRead)?;
+
+        for path in others {
+            self.warn(Warning::IgnoredEntry(path))?;
+        }
+
+        let set: Option<ChunkHeader> = headers.first().map(|(h, _)| h.clone());
+
+        if let Some(ref set) = set {
+            if headers.iter().any(|(h, _)| !h.is_same_set(set)) {
+                return Err(MergeError::ChunkHeaderMismatch);
+            }
+        }
+
+        let chunks: Vec<(usize, PathBuf)> = self.resolve_duplicates(
+            headers.into_iter().map(|(h, path)| (h.index, path)).collect(),
+        )?;
+
+        // the indices are below the total, so all are found if as many
+        let total_chunks: Option<usize> = set.and_then(|s| s.total_chunks);
+
+        if self.chunk_range.is_none()
+            && self.byte_range.is_none()
+            && total_chunks.is_some_and(|total| total != chunks.len())
+        {
+            return Err(MergeError::ChunkHeaderMismatch);
+        }
+
+        Ok(chunks)
+    }
+
+    /// Resolve the chunks with the same index by the duplicate policy.
+    ///
+    /// The entries are given sorted by index.
+    fn resolve_duplicates(
+        &self,
+        entries: Vec<(usize, PathBuf)>,
+    ) -> Result<Vec<(usize, PathBuf)>, MergeError> {
+        let mut chunks: Vec<(usize, PathBuf)> =
+            Vec::with_capacity(entries.len());
+
+        for (index, path) in entries {
+            let previous: &mut PathBuf = match chunks.last_mut() {
+                | Some((i, previous)) if *i == index => previous,
+                | _ => {
+                    chunks.push((index, path));
+                    continue;
+                },
+            };
+
+            let keep: bool = match self.duplicate_policy {
+                | DuplicatePolicy::Fail => {
+                    return Err(MergeError::DuplicateChunk);
+                },
+                | DuplicatePolicy::PreferLarger => {
+                    let len = |p: &Path| fs::metadata(p).map(|m| m.len());
+
+                    len(&path).ok() > len(previous).ok()
+                },
+                | DuplicatePolicy::PreferNewer => {
+                    let modified =
+                        |p: &Path| fs::metadata(p).and_then(|m| m.modified());
+
+                    modified(&path).ok() > modified(previous).ok()
+                },
+            };
+
+            let discarded: PathBuf = match keep {
+                | true => std::mem::replace(previous, path),
+                | false => path,
+            };
+
+            self.warn(Warning::DuplicateChunk(discarded))?;
+        }
+
+        Ok(chunks)
+    }
+
+    /// Validate the chunks against the expected chunk set, if set.
+    ///
+    /// The chunks are given with their sizes, sorted by index.
+    pub(crate) fn validate_chunks(
+        &self,
+        chunks: &[(usize, PathBuf, usize)],
+    ) -> Result<(), MergeError> {
+        if let Some(total_chunks) = self.expected_chunks {
+            // the indices are sorted and unique, so contiguous if in place
+            if chunks.len() != total_chunks
+                || chunks.iter().enumerate().any(|(i, (index, ..))| i != *index)
+            {
+                return Err(MergeError::ChunksIncomplete);
+            }
+        }
+
+        if let Some(file_size) = self.expected_size {
+            if chunks.iter().map(|(.., size)| *size as u64).sum::<u64>()
+                != file_size
+            {
+                return Err(MergeError::FileSizeMismatch);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Select the parts of the chunks to merge by the chunk and byte ranges.
+    ///
+    /// The chunks are given with their sizes, sorted by index.
+    pub(crate) fn select_chunks(
+        &self,
+        chunks: Vec<(usize, PathBuf, usize)>,
+    ) -> Vec<ChunkSlice> {
+        let mut slices: Vec<ChunkSlice> = Vec::with_capacity(chunks.len());
+
+        // position of the chunk in the original file
+        let mut position: u64 = 0;
+
+        for (index, path, size) in chunks {
+            let start: u64 = position;
+
+            let end: u64 = position + size as u64;
+
+            position = end;
+
+            if let Some(ref range) = self.chunk_range {
+                if !range.contains(&index) {
+                    continue;
+                }
+            }
+
+            let (offset, len) = match self.byte_range {
+                | Some(ref range) => {
+                    let from: u64 = range.start.clamp(start, end);
+
+                    let to: u64 = range.end.clamp(from, end);
+
+                    // if the chunk is out of the byte range
+                    if from == to {
+                        continue;
+                    }
+
+                    ((from - start) as usize, (to - from) as usize)
+                },
+                | None => (0, size),
+            };
+
+            slices.push(ChunkSlice {
+                index,
+                path,
+                offset,
+                len,
+                position: start + offset as u64,
+            });
+        }
+
+        slices
+    }
+
+    /// Run the merge process.
+    pub fn run(&self) -> Result<MergeResult, MergeError> {
+        let _progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        #[cfg(feature = "reqwest")]
+        if let Some(ref urls) = self.in_urls {
+            return self.run_from_urls(urls);
+        }
+
+        if let Some(ref writer) = self.out_writer {
+            return self.run_to_writer(&mut *writer.lock()?);
+        }
+
+        let in_dir: &Path = match self.in_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_dir not exists
+                if !p.exists() {
+                    return Err(MergeError::InDirNotFound);
+                }
+
+                // if in_dir not a directory
+                if !p.is_dir() {
+                    return Err(MergeError::InDirNotDir);
+                }
+
+                p
+            },
+            | None => return Err(MergeError::InDirNotSet),
+        };
+
+        let _locks: Vec<FileLock> =
+            self.lock_paths(in_dir, self.out_file.as_deref())?;
+
+        let out_file: &Path = self.prepare_out_file()?;
+
+        // write to a temporary file to rename, if atomic
+        let write_path: PathBuf = match self.is_atomic() {
+            | true => temp_path(out_file),
+            | false => out_file.to_path_buf(),
+        };
+
+        let output: fs::File = fs::OpenOptions::new()
+            .create(true)
+            .truncate(!self.resume)
+            .write(true)
+            .open(&write_path)
+            .map_err(|_| MergeError::OutFileNotOpened)?;
+
+        let resume_from: u64 = match self.resume {
+            | true => output
+                .metadata()
+                .map_err(|_| MergeError::OutFileNotOpened)?
+                .len(),
+            | false => 0,
+        };
+
+        let result: Result<MergeResult, MergeError> =
+            self.merge_chunks(in_dir, &output, resume_from);
+
+        drop(output);
+
+        match result {
+            | Ok(result) => {
+                if self.is_atomic() {
+                    fs::rename(&write_path, out_file)
+                        .map_err(|_| MergeError::OutFileNotWritten)?;
+                }
+
+                self.apply_metadata(in_dir, out_file)?;
+
+                if let Some(parent) = out_dir(out_file) {
+                    self.sync
+                        .sync_dir(parent)
+                        .map_err(|_| MergeError::OutFileNotWritten)?;
+                }
+
+                Ok(MergeResult {
+                    out_file: Some(out_file.to_path_buf()),
+                    ..result
+                })
+            },
+            | Err(error) => {
+                // keep the output to be resumed, but not a corrupt one
+                if !self.resume
+                    && error != MergeError::DeadlineExceeded
+                    && (self.is_atomic()
+                        || error == MergeError::Cancelled
+                        || error == MergeError::HashMismatch)
+                {
+                    let _ = fs::remove_file(&write_path);
+                }
+
+                Err(error)
+            },
+        }
+    }
+
+    /// Run the merge process and get the report as a JSON object.
+    ///
+    /// See [`Report`] for the schema of the report.
+    pub fn report_json(&self) -> String {
+        let started: Instant = Instant::now();
+
+        let result: Result<MergeResult, MergeError> = self.run();
+
+        Report::merge(self, &result, started.elapsed()).to_json()
+    }
+
+    /// Run the merge process with an opened file as the output.
+    ///
+    /// The output file of the process is ignored,
+    /// the file must be opened with write access.
+    pub fn run_to_file(
+        &self,
+        file: fs::File,
+    ) -> Result<MergeResult, MergeError> {
+        let _progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let in_dir: &Path = match self.in_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_dir not exists
+                if !p.exists() {
+                    return Err(MergeError::InDirNotFound);
+                }
+
+                // if in_dir not a directory
+                if !p.is_dir() {
+                    return Err(MergeError::InDirNotDir);
+                }
+
+                p
+            },
+            | None => return Err(MergeError::InDirNotSet),
+        };
+
+        let _locks: Vec<FileLock> = self.lock_paths(in_dir, None)?;
+
+        match self.merge_chunks(in_dir, &file, 0) {
+            | Err(MergeError::Cancelled) => {
+                let _ = file.set_len(0);
+
+                Err(MergeError::Cancelled)
+            },
+            | result => result,
+        }
+    }
+
+    /// Run the merge process with a writer as the output,
+    /// such as an in-memory buffer or a socket.
+    ///
+    /// The output file of the process is ignored, and the writer is
+    /// flushed once all chunks are written, as by [`Merge::run_to_sink`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::merge::{Merge, MergeResult};
+    ///
+    /// let mut buffer: Vec<u8> = Vec::new();
+    ///
+    /// let result: MergeResult = Merge::new()
+    ///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .run_to_writer(&mut buffer)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(buffer.len() as u64, result.bytes_written);
+    /// ```
+    pub fn run_to_writer<W: io::Write>(
+        &self,
+        writer: W,
+    ) -> Result<MergeResult, MergeError> {
+        self.run_to_sink(&mut WriterSink(io::BufWriter::with_capacity(
+            self.buffer_capacity,
+            writer,
+        )))
+    }
+
+    /// Run the merge process with a sink as the output,
+    /// such as a remote destination.
+    ///
+    /// The output file of the process is ignored, and the sink is finished
+    /// once all chunks are written. Once cancelled, the sink is not finished.
+    pub fn run_to_sink<S: MergeSink>(
+        &self,
+        sink: &mut S,
+    ) -> Result<MergeResult, MergeError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let in_dir: &Path = match self.in_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_dir not exists
+                if !p.exists() {
+                    return Err(MergeError::InDirNotFound);
+                }
+
+                // if in_dir not a directory
+                if !p.is_dir() {
+                    return Err(MergeError::InDirNotDir);
+                }
+
+                p
+            },
+            | None => return Err(MergeError::InDirNotSet),
+        };
+
+        let started: Instant = Instant::now();
+
+        let repair_dir: Option<RepairDir> = self.repair_chunks(in_dir)?;
+
+        let slices: Vec<ChunkSlice> =
+            self.scan_chunks(in_dir, repair_dir.as_ref())?;
+
+        let mut chunks: Vec<ChunkInfo> = Vec::with_capacity(slices.len());
+
+        let mut bytes_written: u64 = 0;
+
+        let mut verifier: OutputVerifier =
+            self.output_verifier(Some(in_dir), &slices)?;
+
+        for slice in slices {
+            if self.is_cancelled() {
+                return Err(MergeError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(MergeError::DeadlineExceeded);
+            }
+
+            let size: usize = self.copy_slice(&slice, |data| {
+                verifier.update(data)?;
+
+                sink.write(data).map_err(|_| MergeError::OutFileNotWritten)
+            })?;
+
+            progress.advance(slice.index, size);
+
+            bytes_written += size as u64;
+
+            chunks.push(ChunkInfo {
+                index: slice.index,
+                path: slice.path,
+                size,
+            });
+        }
+
+        // the sink is not finished with a mismatched output
+        verifier.finish()?;
+
+        sink.finish().map_err(|_| MergeError::OutFileNotWritten)?;
+
+        progress.finish();
+
+        Ok(MergeResult {
+            bytes_written,
+            chunks_merged: chunks.len(),
+            out_file: None,
+            duration: started.elapsed(),
+            chunks,
+            bytes_resumed: 0,
+        })
+    }
+
+    /// Run the merge process with the chunks read from a store,
+    /// such as a [`SourcePool`](crate::source::SourcePool) of mirrors.
+    ///
+    /// The chunks from `0` to `total_chunks` are merged whole, in order,
+    /// and the chunks of the result have no path. The input directory,
+    /// the chunk and byte ranges and the resume option are ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{
+    ///     chunk::ChunkNaming,
+    ///     merge::{Merge, MergeResult},
+    ///     source::SourcePool,
+    ///     store::DirStore,
+    /// };
+    ///
+    /// let pool: SourcePool<DirStore> = SourcePool::new(vec![
+    ///     DirStore::new(PathBuf::from("mirror").join("a"), ChunkNaming::new()),
+    ///     DirStore::new(PathBuf::from("mirror").join("b"), ChunkNaming::new()),
+    /// ]);
+    ///
+    /// let result: MergeResult = Merge::new()
+    ///     .out_file(PathBuf::from("path").join("to").join("file"))
+    ///     .run_from_store(&pool, 8)
+    ///     .unwrap();
+    /// ```
+    pub fn run_from_store<S: ChunkStore>(
+        &self,
+        store: &S,
+        total_chunks: usize,
+    ) -> Result<MergeResult, MergeError> {
+        self.merge_from(total_chunks, |index| {
+            let data: Vec<u8> =
+                store.read(index).map_err(|_| MergeError::InFileNotRead)?;
+
+            self.decode(data).map_err(|_| MergeError::InFileNotRead)
+        })
+    }
+
+    /// Run the merge process with the chunks read from a store
+    /// and a sink as the output, such as a
+    /// [`MemoryStore`](crate::store::MemoryStore) and a buffer in memory,
+    /// without a file system.
+    ///
+    /// The chunks from `0` to `total_chunks` are merged whole, in order,
+    /// and the chunks of the result have no path. The input directory,
+    /// the output file, the chunk and byte ranges and the resume option
+    /// are ignored. Once failed or cancelled, the sink is not finished.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use filerune_fusion::{
+    ///     merge::{Merge, MergeResult},
+    ///     store::MemoryStore,
+    /// };
+    ///
+    /// let store: MemoryStore = MemoryStore::new(); // chunks from split...
+    ///
+    /// let mut output: Vec<u8> = Vec::new();
+    ///
+    /// let result: MergeResult = Merge::new()
+    ///     .run_from_store_to_sink(&store, 8, &mut output)
+    ///     .unwrap();
+    /// ```
+    pub fn run_from_store_to_sink<S: ChunkStore, K: MergeSink>(
+        &self,
+        store: &S,
+        total_chunks: usize,
+        sink: &mut K,
+    ) -> Result<MergeResult, MergeError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let started: Instant = Instant::now();
+
+        let mut chunks: Vec<ChunkInfo> = Vec::with_capacity(total_chunks);
+
+        let mut bytes_written: u64 = 0;
+
+        let mut verifier: OutputVerifier = self.output_verifier(None, &[])?;
+
+        for index in 0..total_chunks {
+            if self.is_cancelled() {
+                return Err(MergeError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(MergeError::DeadlineExceeded);
+            }
+
+            let data: Vec<u8> = store
+                .read(index)
+                .and_then(|data| {
+                    self.decode(data).map_err(|_| StoreError::ChunkNotRead)
+                })
+                .map_err(|_| MergeError::InFileNotRead)?;
+
+            verifier.update(&data)?;
+
+            sink.write(&data).map_err(|_| MergeError::OutFileNotWritten)?;
+
+            progress.advance(index, data.len());
+
+            bytes_written += data.len() as u64;
+
+            chunks.push(ChunkInfo {
+                index,
+                path: PathBuf::new(),
+                size: data.len(),
+            });
+        }
+
+        // the sink is not finished with a mismatched output
+        verifier.finish()?;
+
+        sink.finish().map_err(|_| MergeError::OutFileNotWritten)?;
+
+        progress.finish();
+
+        Ok(MergeResult {
+            bytes_written,
+            chunks_merged: chunks.len(),
+            out_file: None,
+            duration: started.elapsed(),
+            chunks,
+            bytes_resumed: 0,
+        })
+    }
+
+    /// Run the merge process with RaptorQ packets as the input,
+    /// such as the ones encoded by
+    /// [`Split::fountain`](crate::split::Split::fountain).
+    ///
+    /// The packets are received until the file can be reconstructed,
+    /// in any order and with any of them lost, with the transmission
+    /// information of the encoder. Each source block is written as soon
+    /// as it is decoded, in order, so the file is not held in memory.
+    /// The input directory and the compression of the process are ignored.
+    /// If the packets run out before, the process fails with
+    /// [`MergeError::SymbolsInsufficient`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::{fs, path::PathBuf};
+    ///
+    /// use filerune_fusion::{
+    ///     fountain::{
+    ///         FountainEncoder, ObjectTransmissionInformation,
+    ///         packet_from_bytes,
+    ///     },
+    ///     merge::{Merge, MergeResult},
+    ///     split::Split,
+    /// };
+    ///
+    /// let encoder: FountainEncoder<fs::File> = Split::new()
+    ///     .fountain(fs::File::open("file").unwrap())
+    ///     .unwrap();
+    ///
+    /// let config: ObjectTransmissionInformation = encoder.config();
+    ///
+    /// // send the packets over a lossy link...
+    /// let packets: Vec<Vec<u8>> = encoder
+    ///     .take(4096)
+    ///     .map(|packet| packet.unwrap().serialize())
+    ///     .collect();
+    ///
+    /// let result: MergeResult = Merge::new()
+    ///     .out_file(PathBuf::from("path").join("to").join("file"))
+    ///     .run_from_symbols(
+    ///         config,
+    ///         packets.iter().filter_map(|p| packet_from_bytes(p)),
+    ///     )
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "fountain")]
+    pub fn run_from_symbols<I: IntoIterator<Item = EncodingPacket>>(
+        &self,
+        config: ObjectTransmissionInformation,
+        packets: I,
+    ) -> Result<MergeResult, MergeError> {
+        let mut decoder: FountainDecoder = FountainDecoder::new(config);
+
+        let mut packets: I::IntoIter = packets.into_iter();
+
+        self.merge_from(decoder.blocks(), |index| {
+            loop {
+                if let Some(block) = decoder.take_block(index) {
+                    return Ok(block);
+                }
+
+                if self.is_cancelled() {
+                    return Err(MergeError::Cancelled);
+                }
+
+                if self.is_expired() {
+                    return Err(MergeError::DeadlineExceeded);
+                }
+
+                match packets.next() {
+                    | Some(packet) => decoder.push(packet),
+                    | None => return Err(MergeError::SymbolsInsufficient),
+                };
+            }
+        })
+    }
+
+    /// Run the merge process with the chunks downloaded from the URLs.
+    #[cfg(feature = "reqwest")]
+    fn run_from_urls(
+        &self,
+        urls: &[ChunkUrl],
+    ) -> Result<MergeResult, MergeError> {
+        let client: reqwest::blocking::Client =
+            reqwest::blocking::Client::new();
+
+        // the chunks downloaded ahead, in order
+        let mut downloaded: VecDeque<Result<Vec<u8>, MergeError>> =
+            VecDeque::new();
+
+        self.merge_from(urls.len(), |index| {
+            if downloaded.is_empty() {
+                let batch: &[ChunkUrl] = &urls
+                    [index..(index + self.concurrency.max(1)).min(urls.len())];
+
+                downloaded = thread::scope(|scope| {
+                    batch
+                        .iter()
+                        .map(|chunk| {
+                            scope.spawn(|| self.download_chunk(&client, chunk))
+                        })
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .map(|handle| {
+                            handle
+                                .join()
+                                .unwrap_or(Err(MergeError::InUrlNotRead))
+                        })
+                        .collect()
+                });
+            }
+
+            let data: Vec<u8> = downloaded
+                .pop_front()
+                .unwrap_or(Err(MergeError::InUrlNotRead))?;
+
+            self.decode(data).map_err(|_| MergeError::InFileNotRead)
+        })
+    }
+
+    /// Download a chunk, retried if failed or not matching.
+    #[cfg(feature = "reqwest")]
+    fn download_chunk(
+        &self,
+        client: &reqwest::blocking::Client,
+        chunk: &ChunkUrl,
+    ) -> Result<Vec<u8>, MergeError> {
+        let mut url: String = chunk.url.clone();
+
+        let mut retries: usize = 0;
+
+        loop {
+            let error: MergeError = match self.download_url(client, &mut url) {
+                | Ok(data) if chunk.matches(&data) => return Ok(data),
+                | Ok(_) => MergeError::ChunkMismatch,
+                | Err(error) => error,
+            };
+
+            if error == MergeError::InUrlExpired
+                || retries >= self.retries
+                || self.is_cancelled()
+            {
+                return Err(error);
+            }
+
+            retries += 1;
+        }
+    }
+
+    /// Download the content at the URL,
+    /// requested again at the refreshed URL if rejected as expired.
+    #[cfg(feature = "reqwest")]
+    fn download_url(
+        &self,
+        client: &reqwest::blocking::Client,
+        url: &mut String,
+    ) -> Result<Vec<u8>, MergeError> {
+        use reqwest::StatusCode;
+
+        let mut response: reqwest::blocking::Response = client
+            .get(url.as_str())
+            .send()
+            .map_err(|_| MergeError::InUrlNotRead)?;
+
+        if let (
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN,
+            Some(handler),
+        ) = (response.status(), self.on_url_expired.as_ref())
+        {
+            *url = handler.refresh(url).ok_or(MergeError::InUrlExpired)?;
+
+            response = client
+                .get(url.as_str())
+                .send()
+                .map_err(|_| MergeError::InUrlNotRead)?;
+        }
+
+        response
+            .error_for_status()
+            .and_then(|r| r.bytes())
+            .map(|bytes| bytes.to_vec())
+            .map_err(|_| MergeError::InUrlNotRead)
+    }
+
+    /// Merge the chunks read by their index to the output file.
+    fn merge_from<F: FnMut(usize) -> Result<Vec<u8>, MergeError>>(
+        &self,
+        total_chunks: usize,
+        mut read: F,
+    ) -> Result<MergeResult, MergeError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let out_file: &Path = self.prepare_out_file()?;
+
+        // write to a temporary file to rename, if atomic
+        let write_path: PathBuf = match self.is_atomic() {
+            | true => temp_path(out_file),
+            | false => out_file.to_path_buf(),
+        };
+
+        let output: fs::File = fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&write_path)
+            .map_err(|_| MergeError::OutFileNotOpened)?;
+
+        let started: Instant = Instant::now();
 
-        // merge
-        for entry in entries {
-            let input: fs::File = fs::OpenOptions::new()
-                .read(true)
-                .open(&entry)
-                .map_err(|_| MergeError::InFileNotOpened)?;
+        let mut writer: io::BufWriter<&fs::File> =
+            io::BufWriter::with_capacity(self.buffer_capacity, &output);
 
-            let mut reader: io::BufReader<fs::File> =
-                io::BufReader::with_capacity(buffer_capacity, input);
+        let mut chunks: Vec<ChunkInfo> = Vec::with_capacity(total_chunks);
 
-            let mut buffer: Vec<u8> = vec![0; buffer_capacity];
+        let mut bytes_written: u64 = 0;
 
-            loop {
-                let read: usize = reader
-                    .read(&mut buffer)
-                    .map_err(|_| MergeError::InFileNotRead)?;
+        let mut verifier: OutputVerifier = self.output_verifier(None, &[])?;
+
+        let mut result: Result<(), MergeError> = Ok(());
+
+        for index in 0..total_chunks {
+            if self.is_cancelled() {
+                result = Err(MergeError::Cancelled);
+                break;
+            }
+
+            if self.is_expired() {
+                result = Err(MergeError::DeadlineExceeded);
+                break;
+            }
+
+            let data: Vec<u8> = match read(index) {
+                | Ok(data) => data,
+                | Err(error) => {
+                    result = Err(error);
+                    break;
+                },
+            };
+
+            if writer.write_all(&data).is_err() {
+                result = Err(MergeError::OutFileNotWritten);
+                break;
+            }
+
+            if let Err(error) = verifier.update(&data) {
+                result = Err(error);
+                break;
+            }
+
+            progress.advance(index, data.len());
 
-                if read == 0 {
+            bytes_written += data.len() as u64;
+
+            // expose the chunk to the readers of the output
+            if self.progressive {
+                if writer.flush().is_err()
+                    || self.sync.sync_file(writer.get_ref()).is_err()
+                {
+                    result = Err(MergeError::OutFileNotWritten);
                     break;
                 }
 
-                writer
-                    .write_all(&buffer[..read])
+                self.watermark(bytes_written);
+            }
+
+            chunks.push(ChunkInfo {
+                index,
+                path: PathBuf::new(),
+                size: data.len(),
+            });
+        }
+
+        let result: Result<(), MergeError> = result.and_then(|_| {
+            verifier.finish()?;
+
+            writer.flush().map_err(|_| MergeError::OutFileNotWritten)?;
+
+            self.sync
+                .sync_file(&output)
+                .map_err(|_| MergeError::OutFileNotWritten)
+        });
+
+        drop(writer);
+        drop(output);
+
+        if let Err(error) = result {
+            let _ = fs::remove_file(&write_path);
+
+            return Err(error);
+        }
+
+        if self.is_atomic() {
+            fs::rename(&write_path, out_file)
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+        }
+
+        if let Some(parent) = out_dir(out_file) {
+            self.sync
+                .sync_dir(parent)
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+        }
+
+        progress.finish();
+
+        Ok(MergeResult {
+            bytes_written,
+            chunks_merged: chunks.len(),
+            out_file: Some(out_file.to_path_buf()),
+            duration: started.elapsed(),
+            chunks,
+            bytes_resumed: 0,
+        })
+    }
+
+    /// Scan the chunks of the input directory to merge, in order.
+    pub(crate) fn scan_chunks(
+        &self,
+        in_dir: &Path,
+        repair_dir: Option<&RepairDir>,
+    ) -> Result<Vec<ChunkSlice>, MergeError> {
+        // get inputs
+        let mut files: Vec<PathBuf> = Vec::new();
+
+        let read_dir: fs::ReadDir =
+            fs::read_dir(in_dir).map_err(|_| MergeError::InDirNotRead)?;
+
+        for entry in read_dir.filter_map(Result::ok) {
+            let path: PathBuf = entry.path();
+
+            if path.is_file() {
+                files.push(path);
+            } else {
+                self.warn(Warning::IgnoredEntry(path))?;
+            }
+        }
+
+        let found: bool = !files.is_empty();
+
+        let mut entries: Vec<(usize, PathBuf)> = self.index_chunks(files)?;
+
+        if let Some(repair_dir) = repair_dir {
+            repair_dir.patch(&mut entries);
+        }
+
+        if entries.is_empty() {
+            return Err(no_chunk(found));
+        }
+
+        let mut sized: Vec<(usize, PathBuf, usize)> =
+            Vec::with_capacity(entries.len());
+
+        for (index, entry) in entries {
+            let len: usize = self.chunk_len(&entry)?;
+
+            sized.push((index, entry, len));
+        }
+
+        self.validate_chunks(&sized)?;
+
+        Ok(self.select_chunks(sized))
+    }
+
+    /// Open a chunk slice to read its content from the offset.
+    fn open_slice(
+        &self,
+        slice: &ChunkSlice,
+    ) -> Result<Box<dyn io::Read>, MergeError> {
+        // the payload is verified before its content is read
+        if self.format == ChunkFormat::Header {
+            let data: Vec<u8> = fs::read(&slice.path)
+                .and_then(|data| self.decode(data))
+                .map_err(|_| MergeError::InFileNotRead)?;
+
+            let mut reader: io::Cursor<Vec<u8>> = io::Cursor::new(data);
+
+            reader.set_position(slice.offset as u64);
+
+            return Ok(Box::new(reader));
+        }
+
+        let mut input: fs::File = read_options()
+            .open(&slice.path)
+            .map_err(|_| MergeError::InFileNotOpened)?;
+
+        #[cfg(feature = "compression")]
+        if let Some(codec) = self.compression {
+            let mut reader: Box<dyn io::Read> = codec
+                .decoder(io::BufReader::with_capacity(
+                    self.buffer_capacity,
+                    input,
+                ))
+                .map_err(|_| MergeError::InFileNotRead)?;
+
+            // the decoded content can not be seeked
+            io::copy(
+                &mut (&mut reader).take(slice.offset as u64),
+                &mut io::sink(),
+            )
+            .map_err(|_| MergeError::InFileNotRead)?;
+
+            return Ok(reader);
+        }
+
+        if slice.offset > 0 {
+            input
+                .seek(io::SeekFrom::Start(slice.offset as u64))
+                .map_err(|_| MergeError::InFileNotRead)?;
+        }
+
+        Ok(Box::new(io::BufReader::with_capacity(self.buffer_capacity, input)))
+    }
+
+    /// Copy the content of a chunk slice to the output.
+    ///
+    /// Returns the number of bytes copied.
+    fn copy_slice(
+        &self,
+        slice: &ChunkSlice,
+        mut write: impl FnMut(&[u8]) -> Result<(), MergeError>,
+    ) -> Result<usize, MergeError> {
+        let mut reader: Box<dyn io::Read> = self.open_slice(slice)?;
+
+        let mut buffer: Vec<u8> = vec![0; self.buffer_capacity];
+
+        let mut size: usize = 0;
+
+        while size < slice.len {
+            let limit: usize = (slice.len - size).min(buffer.len());
+
+            let read: usize = reader
+                .read(&mut buffer[..limit])
+                .map_err(|_| MergeError::InFileNotRead)?;
+
+            if read == 0 {
+                break;
+            }
+
+            write(&buffer[..read])?;
+
+            size += read;
+        }
+
+        Ok(size)
+    }
+
+    /// Validate the output file by the policy, create its directory.
+    pub(crate) fn prepare_out_file(&self) -> Result<&Path, MergeError> {
+        match self.out_file {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if out_file exists and not resumed
+                if p.exists() && !(self.resume && p.is_file()) {
+                    match self.overwrite {
+                        | OutFilePolicy::Fail => {
+                            return Err(MergeError::OutFileExists);
+                        },
+                        | OutFilePolicy::Overwrite => {
+                            if p.is_dir() {
+                                return Err(MergeError::OutFileNotFile);
+                            }
+
+                            fs::remove_file(p)
+                                .map_err(|_| MergeError::OutFileNotRemoved)?;
+                        },
+                        | OutFilePolicy::RenameExisting => {
+                            fs::rename(p, backup_path(p))
+                                .map_err(|_| MergeError::OutFileNotRenamed)?;
+                        },
+                    }
+                }
+
+                // create outpath
+                if let Some(parent) = p.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|_| MergeError::OutDirNotCreated)?;
+                }
+
+                Ok(p)
+            },
+            | None => Err(MergeError::OutFileNotSet),
+        }
+    }
+
+    /// Clone a slice of a chunk at the position of the output with a reflink.
+    ///
+    /// Returns the number of bytes cloned,
+    /// or `None` if the clone is not supported.
+    fn clone_slice(
+        &self,
+        slice: &ChunkSlice,
+        writer: &mut io::BufWriter<&fs::File>,
+    ) -> Result<Option<usize>, MergeError> {
+        let position: u64 = writer
+            .stream_position()
+            .map_err(|_| MergeError::OutFileNotWritten)?;
+
+        let input: fs::File = fs::OpenOptions::new()
+            .read(true)
+            .open(&slice.path)
+            .map_err(|_| MergeError::InFileNotOpened)?;
+
+        let cloned: bool = clone_range(
+            &input,
+            slice.offset as u64,
+            writer.get_ref(),
+            position,
+            slice.len,
+        )
+        .map_err(|_| MergeError::OutFileNotWritten)?;
+
+        if !cloned {
+            return Ok(None);
+        }
+
+        writer
+            .seek(io::SeekFrom::Start(position + slice.len as u64))
+            .map_err(|_| MergeError::OutFileNotWritten)?;
+
+        Ok(Some(slice.len))
+    }
+
+    /// Merge the chunks from the input directory to the output.
+    ///
+    /// The output is resumed from `resume_from` bytes already written.
+    fn merge_chunks(
+        &self,
+        in_dir: &Path,
+        mut output: &fs::File,
+        resume_from: u64,
+    ) -> Result<MergeResult, MergeError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let started: Instant = Instant::now();
+
+        let buffer_capacity: usize = self.buffer_capacity;
+
+        let repair_dir: Option<RepairDir> = self.repair_chunks(in_dir)?;
+
+        let mut slices: Vec<ChunkSlice> =
+            self.scan_chunks(in_dir, repair_dir.as_ref())?;
+
+        let mut bytes_resumed: u64 = 0;
+
+        let mut verifier: OutputVerifier =
+            self.output_verifier(Some(in_dir), &slices)?;
+
+        if resume_from > 0 {
+            let (skipped, position) = resume_point(&slices, resume_from);
+
+            // drop the incomplete chunk
+            output
+                .set_len(position)
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+
+            output
+                .seek(io::SeekFrom::Start(position))
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+
+            // the part resumed is hashed from the chunks it was merged from
+            self.verify_slices(&slices[..skipped], &mut verifier)?;
+
+            slices.drain(..skipped);
+
+            bytes_resumed = position;
+        }
+
+        let len: u64 = slices.iter().map(|s| s.len as u64).sum();
+
+        self.ensure_space(len)?;
+
+        // allocate the disk space of the output up front
+        if self.preallocate {
+            allocate(output, bytes_resumed + len)
+                .map_err(|_| MergeError::OutFileNotAllocated)?;
+        }
+
+        // writer
+        let mut writer: io::BufWriter<&fs::File> =
+            io::BufWriter::with_capacity(buffer_capacity, output);
+
+        let mut chunks: Vec<ChunkInfo> = Vec::with_capacity(slices.len());
+
+        let mut bytes_written: u64 = 0;
+
+        // until not supported by the file system
+        let mut reflink: bool =
+            self.reflink != ReflinkPolicy::Never && !self.is_encoded();
+
+        let mut holes: SparseHoles = SparseHoles::default();
+
+        // merge
+        for slice in slices {
+            if self.is_cancelled() {
+                return Err(MergeError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(MergeError::DeadlineExceeded);
+            }
+
+            let cloned: Option<usize> = match reflink {
+                | true => self.clone_slice(&slice, &mut writer)?,
+                | false => None,
+            };
+
+            if reflink && cloned.is_none() {
+                if self.reflink == ReflinkPolicy::Always {
+                    return Err(MergeError::OutFileNotCloned);
+                }
+
+                self.warn(Warning::ReflinkFallback(slice.path.clone()))?;
+
+                reflink = false;
+            }
+
+            let size: usize = match cloned {
+                | Some(size) => {
+                    self.verify_slices(
+                        std::slice::from_ref(&slice),
+                        &mut verifier,
+                    )?;
+
+                    size
+                },
+                | None => self.copy_slice(&slice, |data| {
+                    verifier.update(data)?;
+
+                    match self.sparse {
+                        | true => holes.write(&mut writer, data),
+                        | false => writer.write_all(data),
+                    }
+                    .map_err(|_| MergeError::OutFileNotWritten)
+                })?,
+            };
+
+            progress.advance(slice.index, size);
+
+            bytes_written += size as u64;
+
+            // expose the chunk to the readers of the output
+            if self.progressive {
+                holes
+                    .finish(&mut writer)
                     .map_err(|_| MergeError::OutFileNotWritten)?;
+
+                writer.flush().map_err(|_| MergeError::OutFileNotWritten)?;
+
+                self.sync
+                    .sync_file(writer.get_ref())
+                    .map_err(|_| MergeError::OutFileNotWritten)?;
+
+                self.watermark(bytes_resumed + bytes_written);
             }
+
+            chunks.push(ChunkInfo {
+                index: slice.index,
+                path: slice.path,
+                size,
+            });
         }
 
+        holes.finish(&mut writer).map_err(|_| MergeError::OutFileNotWritten)?;
+
         writer.flush().map_err(|_| MergeError::OutFileNotWritten)?;
 
-        Ok(())
+        verifier.finish()?;
+
+        self.sync
+            .sync_file(writer.get_ref())
+            .map_err(|_| MergeError::OutFileNotWritten)?;
+
+        progress.finish();
+
+        Ok(MergeResult {
+            bytes_written,
+            chunks_merged: chunks.len(),
+            out_file: None,
+            duration: started.elapsed(),
+            chunks,
+            bytes_resumed,
+        })
     }
 }
 
@@ -306,3 +2968,258 @@ impl Default for Merge {
         Self::new()
     }
 }
+
+impl From<&Split> for Merge {
+    /// Create a merge process of the chunks written by a split process,
+    /// with its output directory, naming scheme, format and codec.
+    fn from(process: &Split) -> Self {
+        Self {
+            in_dir: process.out_dir.clone(),
+            naming: process.naming.clone(),
+            format: process.format,
+            #[cfg(feature = "compression")]
+            compression: process.compression,
+            ..Self::new()
+        }
+    }
+}
+
+impl From<&Check> for Merge {
+    /// Create a merge process of the chunks checked by a check process,
+    /// with its input directory, naming scheme, format and codec,
+    /// and the size of the file and the total number of chunks if set.
+    fn from(process: &Check) -> Self {
+        Self {
+            in_dir: process.in_dir.clone(),
+            naming: process.naming.clone(),
+            format: process.format,
+            #[cfg(feature = "compression")]
+            compression: process.compression,
+            expected_chunks: process.total_chunks,
+            expected_size: process.file_size,
+            ..Self::new()
+        }
+    }
+}
+
+/// Writer as the output of a merge process, set by [`Merge::out_writer`].
+///
+/// The writer is shared by the clones of the process,
+/// and held by one run at a time.
+#[derive(Clone)]
+pub struct OutWriter {
+    writer: Arc<Mutex<Box<dyn io::Write + Send>>>,
+}
+
+impl OutWriter {
+    /// Create a new output from a writer.
+    pub fn new<W: io::Write + Send + 'static>(writer: W) -> Self {
+        Self { writer: Arc::new(Mutex::new(Box::new(writer))) }
+    }
+
+    /// Hold the writer for a run.
+    fn lock(
+        &self
+    ) -> Result<MutexGuard<'_, Box<dyn io::Write + Send>>, MergeError> {
+        self.writer.lock().map_err(|_| MergeError::OutFileNotOpened)
+    }
+}
+
+impl fmt::Debug for OutWriter {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("OutWriter").finish_non_exhaustive()
+    }
+}
+
+/// Sink appending the merged output to a writer.
+struct WriterSink<W>(W);
+
+impl<W: io::Write> MergeSink for WriterSink<W> {
+    fn write(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        self.0.write_all(data).map_err(|_| StoreError::ChunkNotWritten)
+    }
+
+    fn finish(&mut self) -> Result<(), StoreError> {
+        self.0.flush().map_err(|_| StoreError::ChunkNotWritten)
+    }
+}
+
+/// Runs of zero blocks of the output, left as holes when sparse.
+///
+/// The hole before the next data written is kept pending, so the cursor
+/// of the output is moved once per run.
+#[derive(Debug, Default)]
+pub(crate) struct SparseHoles {
+    pending: u64,
+}
+
+impl SparseHoles {
+    /// Split the data into the runs to write, each with the length
+    /// of the hole to leave before it.
+    ///
+    /// The zeros at the end are kept pending.
+    pub(crate) fn runs<'a>(
+        &mut self,
+        data: &'a [u8],
+    ) -> Vec<(u64, &'a [u8])> {
+        let mut runs: Vec<(u64, &'a [u8])> = Vec::new();
+
+        let mut start: Option<usize> = None;
+
+        for (i, block) in data.chunks(SPARSE_BLOCK_SIZE).enumerate() {
+            let at: usize = i * SPARSE_BLOCK_SIZE;
+
+            if block.iter().any(|b| *b != 0) {
+                start.get_or_insert(at);
+
+                continue;
+            }
+
+            if let Some(start) = start.take() {
+                runs.push((self.take(), &data[start..at]));
+            }
+
+            self.pending += block.len() as u64;
+        }
+
+        if let Some(start) = start {
+            runs.push((self.take(), &data[start..]));
+        }
+
+        runs
+    }
+
+    /// Take the length of the hole pending.
+    pub(crate) fn take(&mut self) -> u64 {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Write the data to the output, seeking over the runs of zeros.
+    fn write(
+        &mut self,
+        writer: &mut io::BufWriter<&fs::File>,
+        data: &[u8],
+    ) -> io::Result<()> {
+        for (hole, run) in self.runs(data) {
+            if hole > 0 {
+                writer.seek(io::SeekFrom::Current(hole as i64))?;
+            }
+
+            writer.write_all(run)?;
+        }
+
+        Ok(())
+    }
+
+    /// Leave the hole pending at the end of the output,
+    /// extending the output over it.
+    fn finish(
+        &mut self,
+        writer: &mut io::BufWriter<&fs::File>,
+    ) -> io::Result<()> {
+        let hole: u64 = self.take();
+
+        if hole == 0 {
+            return Ok(());
+        }
+
+        let position: u64 = writer.seek(io::SeekFrom::Current(hole as i64))?;
+
+        writer.get_ref().set_len(position)
+    }
+}
+
+/// Verifier of the output as it is written, by the expected hash
+/// and the outboard tree.
+pub(crate) struct OutputVerifier {
+    hasher: Option<Hasher>,
+    expected_hash: Option<Digest>,
+    bao: Option<BaoVerifier<io::BufReader<fs::File>>>,
+}
+
+impl OutputVerifier {
+    /// Feed the next part of the output.
+    pub(crate) fn update(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), MergeError> {
+        if let Some(ref mut hasher) = self.hasher {
+            hasher.update(data);
+        }
+
+        if let Some(ref mut bao) = self.bao {
+            bao.update(data).map_err(|e| match e {
+                | BaoError::OutboardNotRead => MergeError::InFileNotRead,
+                | _ => MergeError::HashMismatch,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Finish the verification of the output.
+    pub(crate) fn finish(self) -> Result<(), MergeError> {
+        if let Some(bao) = self.bao {
+            bao.finish().map_err(|_| MergeError::HashMismatch)?;
+        }
+
+        match (self.expected_hash, self.hasher) {
+            | (Some(expected), Some(hasher)) => {
+                match hasher.finalize() == expected {
+                    | true => Ok(()),
+                    | false => Err(MergeError::HashMismatch),
+                }
+            },
+            | _ => Ok(()),
+        }
+    }
+}
+
+/// Temporary directory holding the chunks repaired from a read-only source,
+/// removed once dropped.
+pub(crate) struct RepairDir {
+    path: PathBuf,
+    chunks: Vec<(usize, PathBuf)>,
+}
+
+impl RepairDir {
+    /// Create a new temporary directory, unique to the process.
+    fn create() -> Result<Self, MergeError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let path: PathBuf = std::env::temp_dir().join(format!(
+            "filerune-repair-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        fs::create_dir_all(&path).map_err(|_| MergeError::ChunksNotRepaired)?;
+
+        Ok(Self { path, chunks: Vec::new() })
+    }
+
+    /// Replace the chunks indexed by the repaired ones, sorted by index.
+    pub(crate) fn patch(
+        &self,
+        entries: &mut Vec<(usize, PathBuf)>,
+    ) {
+        for (index, path) in self.chunks.iter() {
+            match entries.binary_search_by_key(index, |(i, _)| *i) {
+                | Ok(i) => entries[i].1 = path.clone(),
+                | Err(i) => entries.insert(i, (*index, path.clone())),
+            }
+        }
+    }
+}
+
+impl Drop for RepairDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,249 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The extension of the metadata file, appended to the name of the file.
+pub const METADATA_EXTENSION: &str = "meta";
+
+/// The first word of the metadata file, followed by its version.
+const METADATA_MAGIC: &str = "filerune-metadata";
+
+/// The version of the metadata file format.
+const METADATA_VERSION: u8 = 1;
+
+/// Permissions, timestamps and ownership of a file,
+/// recorded by [`Split::preserve_metadata`] and reapplied by
+/// [`Merge::preserve_metadata`].
+///
+/// The metadata is stored as lines of text, `<key> <value>`, in a file
+/// named after the file with a `.meta` suffix, such as `file.meta`.
+/// The mode and the ownership are only recorded on Unix.
+///
+/// [`Split::preserve_metadata`]: crate::split::Split::preserve_metadata
+/// [`Merge::preserve_metadata`]: crate::merge::Merge::preserve_metadata
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::metadata::FileMetadata;
+///
+/// let metadata: FileMetadata =
+///     FileMetadata::from_path(PathBuf::from("path").join("to").join("file"))
+///         .unwrap();
+///
+/// metadata
+///     .apply(PathBuf::from("path").join("to").join("copy"))
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// Whether the file is read-only.
+    pub readonly: bool,
+    /// The permission bits of the file, on Unix.
+    pub mode: Option<u32>,
+    /// The last modification time of the file.
+    pub modified: Option<SystemTime>,
+    /// The last access time of the file.
+    pub accessed: Option<SystemTime>,
+    /// The user owning the file, on Unix.
+    pub uid: Option<u32>,
+    /// The group owning the file, on Unix.
+    pub gid: Option<u32>,
+}
+
+impl FileMetadata {
+    /// Get the metadata of the file at a path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let metadata: fs::Metadata = fs::metadata(path)?;
+
+        #[cfg(unix)]
+        let (mode, uid, gid) = {
+            use std::os::unix::fs::MetadataExt as _;
+
+            (
+                Some(metadata.mode() & 0o7777),
+                Some(metadata.uid()),
+                Some(metadata.gid()),
+            )
+        };
+
+        #[cfg(not(unix))]
+        let (mode, uid, gid) = (None, None, None);
+
+        Ok(Self {
+            readonly: metadata.permissions().readonly(),
+            mode,
+            modified: metadata.modified().ok(),
+            accessed: metadata.accessed().ok(),
+            uid,
+            gid,
+        })
+    }
+
+    /// Read the metadata file at a path,
+    /// `None` if it is not a metadata file.
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Option<Self>> {
+        fs::read_to_string(path).map(|content| Self::decode(&content))
+    }
+
+    /// Encode the metadata as the content of a metadata file.
+    pub fn encode(&self) -> String {
+        let mut content: String =
+            format!("{METADATA_MAGIC} {METADATA_VERSION}\n");
+
+        content.push_str(&format!("readonly {}\n", self.readonly));
+
+        if let Some(mode) = self.mode {
+            content.push_str(&format!("mode {mode:o}\n"));
+        }
+
+        for (key, time) in
+            [("modified", self.modified), ("accessed", self.accessed)]
+        {
+            if let Some(since) =
+                time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            {
+                content.push_str(&format!(
+                    "{key} {}.{:09}\n",
+                    since.as_secs(),
+                    since.subsec_nanos()
+                ));
+            }
+        }
+
+        for (key, id) in [("uid", self.uid), ("gid", self.gid)] {
+            if let Some(id) = id {
+                content.push_str(&format!("{key} {id}\n"));
+            }
+        }
+
+        content
+    }
+
+    /// Decode the content of a metadata file,
+    /// `None` if it is not a metadata file.
+    ///
+    /// The keys unknown are skipped.
+    pub fn decode(content: &str) -> Option<Self> {
+        let mut lines = content.lines();
+
+        let header: Vec<&str> = lines.next()?.split_whitespace().collect();
+
+        match header.as_slice() {
+            | [magic, version]
+                if *magic == METADATA_MAGIC
+                    && version.parse() == Ok(METADATA_VERSION) => {},
+            | _ => return None,
+        }
+
+        let mut metadata: Self = Self::default();
+
+        for line in lines {
+            let (key, value) = match line.split_once(' ') {
+                | Some(pair) => pair,
+                | None => continue,
+            };
+
+            match key {
+                | "readonly" => metadata.readonly = value.parse().ok()?,
+                | "mode" => {
+                    metadata.mode = Some(u32::from_str_radix(value, 8).ok()?)
+                },
+                | "modified" => metadata.modified = Some(decode_time(value)?),
+                | "accessed" => metadata.accessed = Some(decode_time(value)?),
+                | "uid" => metadata.uid = Some(value.parse().ok()?),
+                | "gid" => metadata.gid = Some(value.parse().ok()?),
+                | _ => {},
+            }
+        }
+
+        Some(metadata)
+    }
+
+    /// Apply the metadata to the file at a path.
+    ///
+    /// The ownership is applied first, and only where permitted, such as
+    /// by a privileged user, then the timestamps and the permissions.
+    pub fn apply<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> io::Result<()> {
+        let path: &Path = path.as_ref();
+
+        #[cfg(unix)]
+        if self.uid.is_some() || self.gid.is_some() {
+            match std::os::unix::fs::chown(path, self.uid, self.gid) {
+                | Err(e) if e.kind() != io::ErrorKind::PermissionDenied => {
+                    return Err(e);
+                },
+                | _ => {},
+            }
+        }
+
+        let mut permissions: fs::Permissions =
+            fs::metadata(path)?.permissions();
+
+        permissions.set_readonly(self.readonly);
+
+        #[cfg(unix)]
+        if let Some(mode) = self.mode {
+            use std::os::unix::fs::PermissionsExt as _;
+
+            permissions.set_mode(mode);
+        }
+
+        let mut times: fs::FileTimes = fs::FileTimes::new();
+
+        if let Some(modified) = self.modified {
+            times = times.set_modified(modified);
+        }
+
+        if let Some(accessed) = self.accessed {
+            times = times.set_accessed(accessed);
+        }
+
+        // the times are set before a read-only file can not be opened
+        fs::OpenOptions::new().write(true).open(path)?.set_times(times)?;
+
+        fs::set_permissions(path, permissions)
+    }
+}
+
+/// Decode a time as seconds and nanoseconds since the Unix epoch.
+fn decode_time(value: &str) -> Option<SystemTime> {
+    let (secs, nanos) = value.split_once('.').unwrap_or((value, "0"));
+
+    UNIX_EPOCH
+        .checked_add(Duration::new(secs.parse().ok()?, nanos.parse().ok()?))
+}
+
+/// Get the path of the metadata file of a file, in a directory.
+pub(crate) fn metadata_path(
+    dir: &Path,
+    file_name: &str,
+) -> PathBuf {
+    dir.join(format!("{file_name}.{METADATA_EXTENSION}"))
+}
+
+/// Check if a path is a metadata file.
+pub(crate) fn is_metadata(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == METADATA_EXTENSION)
+}
+
+/// Find the metadata file of a directory, the first one by name.
+pub(crate) fn find_metadata(dir: &Path) -> Option<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| is_metadata(path) && path.is_file())
+        .collect();
+
+    paths.sort();
+
+    paths.into_iter().next()
+}
@@ -0,0 +1,276 @@
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+};
+
+#[cfg(feature = "compression")]
+use crate::codec::Codec;
+use crate::{
+    CHUNK_SIZE_DEFAULT,
+    chunk::ChunkNaming,
+    hash::{Digest, HashAlgorithm},
+    split::SplitError,
+};
+
+const DISPOSITION: &str = "Content-Disposition: form-data; name=";
+
+/// `multipart/form-data` body of a chunk.
+///
+/// The body has four fields, and a fifth with a codec:
+///
+/// - `index`: the index of the chunk.
+/// - `hash`: the hex digest of the chunk as stored in the `chunk` field.
+/// - `plain_hash`: the hex digest of the chunk before compression.
+/// - `codec`: the name of the codec the chunk is compressed with, if any.
+/// - `chunk`: the content of the chunk as stored, named by the naming scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartBody {
+    /// The index of the chunk.
+    pub index: usize,
+    /// The size of the chunk in bytes, before compression.
+    pub size: usize,
+    /// The size of the chunk as stored in the body in bytes.
+    pub stored_size: usize,
+    /// The digest of the chunk as stored in the body.
+    pub hash: Digest,
+    /// The digest of the chunk before compression.
+    pub plain_hash: Digest,
+    /// The boundary between the fields.
+    pub boundary: String,
+    /// The encoded body.
+    pub body: Vec<u8>,
+}
+
+impl MultipartBody {
+    /// Get the value of the `Content-Type` header for the body.
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+}
+
+/// Adapter to split a reader into `multipart/form-data` bodies,
+/// one per chunk.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{fs, path::PathBuf};
+///
+/// use filerune_fusion::{multipart::MultipartBody, split::Split};
+///
+/// let file: fs::File =
+///     fs::File::open(PathBuf::from("path").join("to").join("file")).unwrap();
+///
+/// for body in Split::new().chunk_size(1024 * 1024).multipart(file) {
+///     let body: MultipartBody = body.unwrap();
+///
+///     // send `body.body` with `body.content_type()`...
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MultipartSplit<R> {
+    reader: R,
+    chunk_size: usize,
+    naming: ChunkNaming,
+    hash_algorithm: HashAlgorithm,
+    #[cfg(feature = "compression")]
+    codec: Option<Codec>,
+    index: usize,
+    finished: bool,
+}
+
+impl<R: Read> MultipartSplit<R> {
+    /// Create a new adapter from a reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            chunk_size: CHUNK_SIZE_DEFAULT,
+            naming: ChunkNaming::new(),
+            hash_algorithm: HashAlgorithm::default(),
+            #[cfg(feature = "compression")]
+            codec: None,
+            index: 0,
+            finished: false,
+        }
+    }
+
+    /// Set the maximum size of each chunk.
+    ///
+    /// By default, the chunk size follows the [`CHUNK_SIZE_DEFAULT`].
+    pub fn chunk_size(
+        mut self,
+        size: usize,
+    ) -> Self {
+        self.chunk_size = size;
+        self
+    }
+
+    /// Set the naming scheme of the chunk file names in the bodies.
+    pub fn naming(
+        mut self,
+        naming: ChunkNaming,
+    ) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Set the algorithm to hash the chunks.
+    ///
+    /// By default, it is [`HashAlgorithm::Sha256`].
+    pub fn hash_algorithm(
+        mut self,
+        algorithm: HashAlgorithm,
+    ) -> Self {
+        self.hash_algorithm = algorithm;
+        self
+    }
+
+    /// Set the codec to compress each chunk with before it is encoded,
+    /// as [`Split::compression`](crate::split::Split::compression) does
+    /// for the chunk files.
+    ///
+    /// By default, the chunks are not compressed.
+    #[cfg(feature = "compression")]
+    pub fn codec(
+        mut self,
+        codec: Codec,
+    ) -> Self {
+        self.codec = Some(codec);
+        self
+    }
+
+    /// Compress the data of a chunk, if enabled.
+    fn compress<'a>(
+        &self,
+        data: &'a [u8],
+    ) -> Result<Cow<'a, [u8]>, SplitError> {
+        #[cfg(feature = "compression")]
+        if let Some(codec) = self.codec {
+            return codec
+                .compress(data)
+                .map(Cow::Owned)
+                .map_err(|_| SplitError::OutFileNotWritten);
+        }
+
+        Ok(Cow::Borrowed(data))
+    }
+
+    /// Get the name of the codec the chunks are compressed with, if any.
+    fn codec_name(&self) -> Option<&str> {
+        #[cfg(feature = "compression")]
+        if let Some(ref codec) = self.codec {
+            return Some(codec.as_str());
+        }
+
+        None
+    }
+
+    /// Encode a chunk as a `multipart/form-data` body.
+    fn encode(
+        &self,
+        data: &[u8],
+    ) -> Result<MultipartBody, SplitError> {
+        let plain_hash: Digest = self.hash_algorithm.digest(data);
+
+        let stored: Cow<[u8]> = self.compress(data)?;
+
+        let hash: Digest = match stored {
+            | Cow::Borrowed(_) => plain_hash.clone(),
+            | Cow::Owned(ref stored) => self.hash_algorithm.digest(stored),
+        };
+
+        let hex: String = hash.to_hex();
+
+        // derived from the digest, so it will not appear in the chunk
+        let boundary: String =
+            format!("filerune-{}", &hex[..32.min(hex.len())]);
+
+        let mut body: Vec<u8> = Vec::with_capacity(stored.len() + 512);
+
+        let mut fields: Vec<(&str, String)> = vec![
+            ("index", self.index.to_string()),
+            ("hash", hex.clone()),
+            ("plain_hash", plain_hash.to_hex()),
+        ];
+
+        if let Some(codec) = self.codec_name() {
+            fields.push(("codec", codec.to_string()));
+        }
+
+        for (name, value) in fields {
+            body.extend_from_slice(
+                format!("--{boundary}\r\n{DISPOSITION}\"{name}\"\r\n\r\n")
+                    .as_bytes(),
+            );
+            body.extend_from_slice(format!("{value}\r\n").as_bytes());
+        }
+
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\n{DISPOSITION}\"chunk\"; filename=\"{}\"\r\n",
+                self.naming.file_name(self.index)
+            )
+            .as_bytes(),
+        );
+
+        body.extend_from_slice(
+            b"Content-Type: application/octet-stream\r\n\r\n",
+        );
+
+        body.extend_from_slice(&stored);
+
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        Ok(MultipartBody {
+            index: self.index,
+            size: data.len(),
+            stored_size: stored.len(),
+            hash,
+            plain_hash,
+            boundary,
+            body,
+        })
+    }
+}
+
+impl<R: Read> Iterator for MultipartSplit<R> {
+    type Item = Result<MultipartBody, SplitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let mut buffer: Vec<u8> = vec![0; self.chunk_size];
+
+        let mut offset: usize = 0;
+
+        while offset < self.chunk_size {
+            match self.reader.read(&mut buffer[offset..]) {
+                | Ok(0) => break,
+                | Ok(n) => offset += n,
+                | Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+                | Err(_) => {
+                    self.finished = true;
+                    return Some(Err(SplitError::InFileNotRead));
+                },
+            };
+        }
+
+        if offset == 0 {
+            self.finished = true;
+            return None;
+        }
+
+        let body: Result<MultipartBody, SplitError> =
+            self.encode(&buffer[..offset]);
+
+        if body.is_err() {
+            self.finished = true;
+        }
+
+        self.index += 1;
+
+        Some(body)
+    }
+}
@@ -0,0 +1,445 @@
+use std::path::PathBuf;
+#[cfg(feature = "reqwest")]
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+use crate::{chunk::ChunkNaming, split::SplitResult};
+
+/// The minimum size of each chunk except the last one, in bytes.
+pub const NEXTCLOUD_CHUNK_SIZE_MIN: usize = 5 * 1024 * 1024;
+
+/// The maximum size of each chunk, in bytes.
+pub const NEXTCLOUD_CHUNK_SIZE_MAX: u64 = 5 * 1024 * 1024 * 1024;
+
+/// The maximum number of chunks of an upload.
+pub const NEXTCLOUD_CHUNKS_MAX: usize = 10000;
+
+/// WebDAV request of an upload.
+///
+/// The requests are sent by [`NextcloudUpload::upload_chunks`] with the
+/// `reqwest` feature, or by the HTTP client of the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebDavRequest {
+    /// The HTTP method, such as `MKCOL`, `PUT` or `MOVE`.
+    pub method: String,
+    /// The URL of the request.
+    pub url: String,
+    /// The headers of the request.
+    pub headers: Vec<(String, String)>,
+    /// The chunk file to send as the body, `None` if no body.
+    pub body: Option<PathBuf>,
+}
+
+/// Nextcloud error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextcloudError {
+    ChunkTooSmall,
+    ChunkTooLarge,
+    TooManyChunks,
+    UploadNotCreated,
+    ChunkNotRead,
+    ChunkNotUploaded,
+    UploadNotAssembled,
+    UploadNotAborted,
+}
+
+impl NextcloudError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::ChunkTooSmall => "chunk_too_small",
+            | Self::ChunkTooLarge => "chunk_too_large",
+            | Self::TooManyChunks => "too_many_chunks",
+            | Self::UploadNotCreated => "upload_not_created",
+            | Self::ChunkNotRead => "chunk_not_read",
+            | Self::ChunkNotUploaded => "chunk_not_uploaded",
+            | Self::UploadNotAssembled => "upload_not_assembled",
+            | Self::UploadNotAborted => "upload_not_aborted",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::ChunkTooSmall => {
+                "A chunk other than the last one is smaller than 5 MiB."
+            },
+            | Self::ChunkTooLarge => "A chunk is larger than 5 GiB.",
+            | Self::TooManyChunks => "The upload has more than 10000 chunks.",
+            | Self::UploadNotCreated => {
+                "The collection of the upload could not be created."
+            },
+            | Self::ChunkNotRead => "The chunk could not be read.",
+            | Self::ChunkNotUploaded => "The chunk could not be uploaded.",
+            | Self::UploadNotAssembled => {
+                "The chunks could not be assembled to the target file."
+            },
+            | Self::UploadNotAborted => {
+                "The collection of the upload could not be deleted."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Result of an upload to a Nextcloud server.
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone)]
+pub struct NextcloudResult {
+    /// The URL of the target file on the server.
+    pub url: String,
+    /// Size of the uploaded file in bytes.
+    pub file_size: u64,
+    /// The number of chunks uploaded.
+    pub total_chunks: usize,
+    /// The time taken by the upload.
+    pub duration: Duration,
+}
+
+/// Upload following the Nextcloud and ownCloud chunked upload v2 workflow.
+///
+/// The chunks are uploaded to a temporary collection with `MKCOL` and `PUT`,
+/// then assembled to the target file with `MOVE`.
+///
+/// With the `reqwest` feature, [`NextcloudUpload::upload_chunks`] sends the
+/// requests, deleting the temporary collection if one of them fails.
+/// Otherwise, [`NextcloudUpload::requests`] describes them for the HTTP
+/// client of the caller.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     nextcloud::{NextcloudUpload, WebDavRequest},
+///     split::{Split, SplitResult},
+/// };
+///
+/// let upload: NextcloudUpload = NextcloudUpload::new(
+///     "https://cloud.example.com",
+///     "alice",
+///     "upload-1",
+///     "Documents/file.bin",
+/// );
+///
+/// let result: SplitResult = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .chunk_size(10 * 1024 * 1024)
+///     .naming(NextcloudUpload::naming())
+///     .run()
+///     .unwrap();
+///
+/// for request in upload.requests(&result).unwrap() {
+///     // send the request...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NextcloudUpload {
+    pub server: String,
+    pub user: String,
+    pub upload_id: String,
+    pub target_path: String,
+    pub headers: Vec<(String, String)>,
+    #[cfg(feature = "reqwest")]
+    client: reqwest::blocking::Client,
+}
+
+impl NextcloudUpload {
+    /// Create a new upload.
+    pub fn new<
+        Server: Into<String>,
+        User: Into<String>,
+        UploadId: Into<String>,
+        TargetPath: Into<String>,
+    >(
+        server: Server,
+        user: User,
+        upload_id: UploadId,
+        target_path: TargetPath,
+    ) -> Self {
+        Self {
+            server: server.into().trim_end_matches('/').to_string(),
+            user: user.into(),
+            upload_id: upload_id.into(),
+            target_path: target_path.into().trim_start_matches('/').to_string(),
+            headers: Vec::new(),
+            #[cfg(feature = "reqwest")]
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Add a header to all requests, such as for authorization.
+    pub fn header<Name: Into<String>, Value: Into<String>>(
+        mut self,
+        name: Name,
+        value: Value,
+    ) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Get the naming scheme of the chunks expected by the server,
+    /// numbered from `1`.
+    pub fn naming() -> ChunkNaming {
+        ChunkNaming::new().pad_width(5).start_index(1)
+    }
+
+    /// Get the URL of the temporary collection of the chunks.
+    pub fn upload_url(&self) -> String {
+        format!(
+            "{}/remote.php/dav/uploads/{}/{}",
+            self.server,
+            encode_path(&self.user),
+            encode_path(&self.upload_id)
+        )
+    }
+
+    /// Get the URL of the target file.
+    pub fn destination_url(&self) -> String {
+        format!(
+            "{}/remote.php/dav/files/{}/{}",
+            self.server,
+            encode_path(&self.user),
+            encode_path(&self.target_path)
+        )
+    }
+
+    /// Get the request to create the temporary collection.
+    pub fn mkcol(&self) -> WebDavRequest {
+        WebDavRequest {
+            method: "MKCOL".to_string(),
+            url: self.upload_url(),
+            headers: vec![("Destination".to_string(), self.destination_url())],
+            body: None,
+        }
+    }
+
+    /// Get the request to upload a chunk.
+    pub fn put(
+        &self,
+        index: usize,
+        path: PathBuf,
+        total_length: u64,
+    ) -> WebDavRequest {
+        WebDavRequest {
+            method: "PUT".to_string(),
+            url: format!(
+                "{}/{}",
+                self.upload_url(),
+                Self::naming().file_name(index)
+            ),
+            headers: vec![
+                ("Destination".to_string(), self.destination_url()),
+                ("OC-Total-Length".to_string(), total_length.to_string()),
+            ],
+            body: Some(path),
+        }
+    }
+
+    /// Get the request to assemble the chunks to the target file.
+    pub fn assemble(
+        &self,
+        total_length: u64,
+    ) -> WebDavRequest {
+        WebDavRequest {
+            method: "MOVE".to_string(),
+            url: format!("{}/.file", self.upload_url()),
+            headers: vec![
+                ("Destination".to_string(), self.destination_url()),
+                ("OC-Total-Length".to_string(), total_length.to_string()),
+            ],
+            body: None,
+        }
+    }
+
+    /// Get all requests to upload the chunks of a split result, in order.
+    ///
+    /// The chunks are validated against the limits of the server.
+    pub fn requests(
+        &self,
+        result: &SplitResult,
+    ) -> Result<Vec<WebDavRequest>, NextcloudError> {
+        if result.chunks.len() > NEXTCLOUD_CHUNKS_MAX {
+            return Err(NextcloudError::TooManyChunks);
+        }
+
+        let mut requests: Vec<WebDavRequest> =
+            Vec::with_capacity(result.chunks.len() + 2);
+
+        requests.push(self.mkcol());
+
+        for (i, chunk) in result.chunks.iter().enumerate() {
+            if chunk.size as u64 > NEXTCLOUD_CHUNK_SIZE_MAX {
+                return Err(NextcloudError::ChunkTooLarge);
+            }
+
+            // only the last chunk can be smaller
+            if i + 1 < result.chunks.len()
+                && chunk.size < NEXTCLOUD_CHUNK_SIZE_MIN
+            {
+                return Err(NextcloudError::ChunkTooSmall);
+            }
+
+            requests.push(self.put(
+                chunk.index,
+                chunk.path.clone(),
+                result.file_size,
+            ));
+        }
+
+        requests.push(self.assemble(result.file_size));
+
+        Ok(requests)
+    }
+
+    /// Upload the chunks of a split result, then assemble them
+    /// to the target file.
+    ///
+    /// If a request fails, the temporary collection is deleted
+    /// with the chunks already uploaded.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{
+    ///     nextcloud::{NextcloudResult, NextcloudUpload},
+    ///     split::{Split, SplitResult},
+    /// };
+    ///
+    /// let upload: NextcloudUpload = NextcloudUpload::new(
+    ///     "https://cloud.example.com",
+    ///     "alice",
+    ///     "upload-1",
+    ///     "Documents/file.bin",
+    /// )
+    /// .header("Authorization", "Basic YWxpY2U6c2VjcmV0");
+    ///
+    /// let result: SplitResult = Split::new()
+    ///     .in_file(PathBuf::from("path").join("to").join("file"))
+    ///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .chunk_size(10 * 1024 * 1024)
+    ///     .naming(NextcloudUpload::naming())
+    ///     .run()
+    ///     .unwrap();
+    ///
+    /// let result: NextcloudResult = upload.upload_chunks(&result).unwrap();
+    /// ```
+    #[cfg(feature = "reqwest")]
+    pub fn upload_chunks(
+        &self,
+        result: &SplitResult,
+    ) -> Result<NextcloudResult, NextcloudError> {
+        let started: Instant = Instant::now();
+
+        let requests: Vec<WebDavRequest> = self.requests(result)?;
+
+        for (i, request) in requests.iter().enumerate() {
+            if let Err(error) = self.send(request) {
+                // the collection is not created by a failed `MKCOL`
+                if i > 0 {
+                    let _ = self.abort();
+                }
+
+                return Err(error);
+            }
+        }
+
+        Ok(NextcloudResult {
+            url: self.destination_url(),
+            file_size: result.file_size,
+            total_chunks: result.chunks.len(),
+            duration: started.elapsed(),
+        })
+    }
+
+    /// Delete the temporary collection with the chunks uploaded.
+    #[cfg(feature = "reqwest")]
+    pub fn abort(&self) -> Result<(), NextcloudError> {
+        let request: WebDavRequest = WebDavRequest {
+            method: "DELETE".to_string(),
+            url: self.upload_url(),
+            headers: Vec::new(),
+            body: None,
+        };
+
+        self.send(&request)
+    }
+
+    /// Send a request, with the headers of the upload.
+    #[cfg(feature = "reqwest")]
+    fn send(
+        &self,
+        request: &WebDavRequest,
+    ) -> Result<(), NextcloudError> {
+        let error: NextcloudError = match request.method.as_str() {
+            | "MKCOL" => NextcloudError::UploadNotCreated,
+            | "PUT" => NextcloudError::ChunkNotUploaded,
+            | "MOVE" => NextcloudError::UploadNotAssembled,
+            | _ => NextcloudError::UploadNotAborted,
+        };
+
+        let method: reqwest::Method =
+            reqwest::Method::from_bytes(request.method.as_bytes())
+                .map_err(|_| error)?;
+
+        let mut builder: reqwest::blocking::RequestBuilder =
+            self.client.request(method, &request.url);
+
+        for (name, value) in self.headers.iter().chain(&request.headers) {
+            builder = builder.header(name, value);
+        }
+
+        // the chunk is streamed from the file
+        if let Some(ref path) = request.body {
+            let file: fs::File = fs::File::open(path)
+                .map_err(|_| NextcloudError::ChunkNotRead)?;
+
+            builder = builder.body(file);
+        }
+
+        let response: reqwest::blocking::Response =
+            builder.send().map_err(|_| error)?;
+
+        if !response.status().is_success() {
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+/// Percent-encode a path, keeping the `/` separators.
+fn encode_path(path: &str) -> String {
+    let mut encoded: String = String::with_capacity(path.len());
+
+    for b in path.bytes() {
+        match b {
+            | b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'/' => encoded.push(b as char),
+            | _ => encoded.push_str(&format!("%{b:02X}")),
+        }
+    }
+
+    encoded
+}
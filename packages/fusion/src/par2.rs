@@ -0,0 +1,815 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read as _,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use md5::{Digest as _, Md5};
+
+use crate::{
+    BUFFER_CAPACITY_DEFAULT,
+    chunk::{ChunkInfo, ChunkNaming},
+    parity::{ParityError, read_chunk, write_file},
+    sync::SyncPolicy,
+};
+
+/// The maximum number of input slices of a PAR2 recovery set.
+pub const PAR2_SLICES_MAX: usize = 32768;
+
+/// The magic bytes at the start of a PAR2 packet.
+const MAGIC: &[u8; 8] = b"PAR2\0PKT";
+
+/// The length of the header of a PAR2 packet.
+const HEADER_LEN: usize = 64;
+
+const TYPE_MAIN: &[u8; 16] = b"PAR 2.0\0Main\0\0\0\0";
+const TYPE_FILE_DESC: &[u8; 16] = b"PAR 2.0\0FileDesc";
+const TYPE_IFSC: &[u8; 16] = b"PAR 2.0\0IFSC\0\0\0\0";
+const TYPE_RECOVERY: &[u8; 16] = b"PAR 2.0\0RecvSlic";
+const TYPE_CREATOR: &[u8; 16] = b"PAR 2.0\0Creator\0";
+
+/// The client identifier written to the creator packet.
+const CREATOR: &[u8] = b"filerune_fusion";
+
+/// The length of the head of a file hashed into its id.
+const HASH_16K: usize = 16 * 1024;
+
+/// Get the log and the antilog tables of GF(2^16),
+/// with the polynomial `x^16 + x^12 + x^3 + x + 1`.
+fn tables() -> &'static (Vec<u16>, Vec<u16>) {
+    static TABLES: OnceLock<(Vec<u16>, Vec<u16>)> = OnceLock::new();
+
+    TABLES.get_or_init(|| {
+        let mut log: Vec<u16> = vec![0; 65536];
+        let mut exp: Vec<u16> = vec![0; 65536];
+
+        let mut x: u32 = 1;
+
+        for (i, e) in exp.iter_mut().take(65535).enumerate() {
+            *e = x as u16;
+            log[x as usize] = i as u16;
+
+            x <<= 1;
+
+            if x & 0x10000 != 0 {
+                x ^= 0x1100b;
+            }
+        }
+
+        (log, exp)
+    })
+}
+
+/// Multiply two elements of GF(2^16).
+fn mul(
+    a: u16,
+    b: u16,
+) -> u16 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+
+    let (log, exp) = tables();
+
+    exp[(log[a as usize] as usize + log[b as usize] as usize) % 65535]
+}
+
+/// Get the inverse of a non-zero element of GF(2^16).
+fn inv(a: u16) -> u16 {
+    let (log, exp) = tables();
+
+    exp[(65535 - log[a as usize] as usize) % 65535]
+}
+
+/// Raise the generator to a power.
+fn pow2(exponent: usize) -> u16 {
+    tables().1[exponent % 65535]
+}
+
+/// Add the data multiplied by the factor to the output,
+/// as 16-bit little-endian words.
+fn mul_add(
+    factor: u16,
+    data: &[u8],
+    output: &mut [u8],
+) {
+    if factor == 0 {
+        return;
+    }
+
+    // the product is linear, so it is split by the bytes of the words
+    let mut low: [u16; 256] = [0; 256];
+    let mut high: [u16; 256] = [0; 256];
+
+    for b in 0..256 {
+        low[b] = mul(b as u16, factor);
+        high[b] = mul((b as u16) << 8, factor);
+    }
+
+    for (o, d) in output.chunks_exact_mut(2).zip(data.chunks_exact(2)) {
+        let product: u16 = low[d[0] as usize] ^ high[d[1] as usize];
+
+        o[0] ^= product as u8;
+        o[1] ^= (product >> 8) as u8;
+    }
+}
+
+/// Get the logs of the constants of the input slices.
+///
+/// The logs are relatively prime to `65535`, in increasing order.
+fn constant_logs(count: usize) -> Vec<usize> {
+    (1..65535usize)
+        .filter(|n| n % 3 != 0 && n % 5 != 0 && n % 17 != 0 && n % 257 != 0)
+        .take(count)
+        .collect()
+}
+
+/// Compute the MD5 digest of the data.
+fn md5(data: &[u8]) -> [u8; 16] {
+    Md5::digest(data).into()
+}
+
+/// The remainders of the bytes of CRC-32.
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table: [u32; 256] = [0; 256];
+    let mut i: usize = 0;
+
+    while i < 256 {
+        let mut crc: u32 = i as u32;
+        let mut bit: usize = 0;
+
+        while bit < 8 {
+            crc = match crc & 1 {
+                | 1 => (crc >> 1) ^ 0xedb8_8320,
+                | _ => crc >> 1,
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+/// Update a CRC-32 checksum with the data.
+///
+/// The checksum starts from `!0` and is inverted once all the data is added.
+fn crc32_update(
+    mut crc: u32,
+    data: &[u8],
+) -> u32 {
+    for byte in data {
+        crc = CRC32_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+
+    crc
+}
+
+/// Pad the data with zeros to a multiple of 4 bytes.
+fn pad4(data: &[u8]) -> Vec<u8> {
+    let mut padded: Vec<u8> = data.to_vec();
+
+    padded.resize(data.len().div_ceil(4) * 4, 0);
+
+    padded
+}
+
+/// Encode a packet of the recovery set.
+fn packet(
+    set_id: &[u8; 16],
+    kind: &[u8; 16],
+    body: &[u8],
+) -> Vec<u8> {
+    let mut packet: Vec<u8> = Vec::with_capacity(HEADER_LEN + body.len());
+
+    packet.extend_from_slice(MAGIC);
+    packet.extend_from_slice(&((HEADER_LEN + body.len()) as u64).to_le_bytes());
+    packet.extend_from_slice(&[0; 16]);
+    packet.extend_from_slice(set_id);
+    packet.extend_from_slice(kind);
+    packet.extend_from_slice(body);
+
+    let hash: [u8; 16] = md5(&packet[32..]);
+
+    packet[16..32].copy_from_slice(&hash);
+
+    packet
+}
+
+/// Order of the file ids in a recovery set, from the last byte.
+fn cmp_id(
+    a: &[u8; 16],
+    b: &[u8; 16],
+) -> std::cmp::Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+/// Check if the path is of a PAR2 file.
+pub(crate) fn is_par2(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("par2"))
+}
+
+/// Write the PAR2 index and recovery volume of the chunks
+/// written to the directory, each chunk as one input slice.
+///
+/// The chunks are read one at a time, by pieces of the buffer capacity,
+/// each piece hashed and added to the recovery slices, so only the
+/// recovery slices are held in memory.
+///
+/// The index is named `<base>.par2`, and the volume holding
+/// the recovery slices `<base>.vol00+<count>.par2`.
+pub(crate) fn write_par2(
+    out_dir: &Path,
+    base: &str,
+    chunks: &[ChunkInfo],
+    recovery_slices: usize,
+    sync: SyncPolicy,
+) -> Result<Vec<PathBuf>, ParityError> {
+    if recovery_slices == 0 || chunks.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    struct Source<'a> {
+        id: [u8; 16],
+        path: &'a Path,
+        name: Vec<u8>,
+        len: u64,
+        hash_16k: [u8; 16],
+    }
+
+    // the ids order the slices, so the heads are hashed first
+    let mut sources: Vec<Source> = Vec::with_capacity(chunks.len());
+
+    for chunk in chunks {
+        let file: fs::File = fs::File::open(&chunk.path)
+            .map_err(|_| ParityError::ChunkNotRead)?;
+
+        let len: u64 =
+            file.metadata().map_err(|_| ParityError::ChunkNotRead)?.len();
+
+        let mut head: Vec<u8> = Vec::with_capacity(HASH_16K);
+
+        file.take(HASH_16K as u64)
+            .read_to_end(&mut head)
+            .map_err(|_| ParityError::ChunkNotRead)?;
+
+        let name: Vec<u8> = chunk
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned().into_bytes())
+            .unwrap_or_default();
+
+        let hash_16k: [u8; 16] = md5(&head);
+
+        let mut id_input: Vec<u8> = hash_16k.to_vec();
+
+        id_input.extend_from_slice(&len.to_le_bytes());
+        id_input.extend_from_slice(&name);
+
+        sources.push(Source {
+            id: md5(&id_input),
+            path: &chunk.path,
+            name,
+            len,
+            hash_16k,
+        });
+    }
+
+    sources.sort_by(|a, b| cmp_id(&a.id, &b.id));
+
+    // each chunk is one slice
+    let slice_len: u64 =
+        sources.iter().map(|s| s.len).max().unwrap_or(0).div_ceil(4).max(1) * 4;
+
+    let slice_size: usize =
+        usize::try_from(slice_len).map_err(|_| ParityError::ChunkNotRead)?;
+
+    // the constants follow the slices, the empty files have none
+    let logs: Vec<usize> = constant_logs(sources.len());
+
+    let mut recovery: Vec<Vec<u8>> = vec![vec![0; slice_size]; recovery_slices];
+
+    let mut buffer: Vec<u8> = vec![0; BUFFER_CAPACITY_DEFAULT.min(slice_size)];
+
+    // the digest of each chunk, and the checksums of its slice
+    let mut hashes: Vec<([u8; 16], [u8; 16], u32)> =
+        Vec::with_capacity(sources.len());
+
+    let mut slice: usize = 0;
+
+    for source in sources.iter() {
+        let mut file: fs::File = fs::File::open(source.path)
+            .map_err(|_| ParityError::ChunkNotRead)?;
+
+        let mut hasher: Md5 = Md5::new();
+
+        let mut crc: u32 = !0;
+
+        let mut offset: usize = 0;
+
+        while (offset as u64) < source.len {
+            let len: usize =
+                buffer.len().min((source.len - offset as u64) as usize);
+
+            file.read_exact(&mut buffer[..len])
+                .map_err(|_| ParityError::ChunkNotRead)?;
+
+            hasher.update(&buffer[..len]);
+
+            crc = crc32_update(crc, &buffer[..len]);
+
+            // the last byte of an odd chunk is paired with the padding
+            let words: usize = len.div_ceil(2) * 2;
+
+            if words > len {
+                buffer[len] = 0;
+            }
+
+            for (exponent, output) in recovery.iter_mut().enumerate() {
+                mul_add(
+                    pow2(logs[slice] * exponent),
+                    &buffer[..words],
+                    &mut output[offset..offset + words],
+                );
+            }
+
+            offset += len;
+        }
+
+        let hash: [u8; 16] = hasher.clone().finalize().into();
+
+        // a full slice has the digest of the chunk
+        let slice_hash: [u8; 16] = match offset == slice_size {
+            | true => hash,
+            | false => {
+                buffer.fill(0);
+
+                let mut padding: usize = slice_size - offset;
+
+                while padding > 0 {
+                    let len: usize = padding.min(buffer.len());
+
+                    hasher.update(&buffer[..len]);
+
+                    crc = crc32_update(crc, &buffer[..len]);
+
+                    padding -= len;
+                }
+
+                hasher.finalize().into()
+            },
+        };
+
+        hashes.push((hash, slice_hash, !crc));
+
+        if source.len > 0 {
+            slice += 1;
+        }
+    }
+
+    let mut main: Vec<u8> = Vec::new();
+
+    main.extend_from_slice(&slice_len.to_le_bytes());
+    main.extend_from_slice(&(sources.len() as u32).to_le_bytes());
+
+    for source in sources.iter() {
+        main.extend_from_slice(&source.id);
+    }
+
+    let set_id: [u8; 16] = md5(&main);
+
+    let mut critical: Vec<u8> = packet(&set_id, TYPE_MAIN, &main);
+
+    for (source, (hash, ..)) in sources.iter().zip(hashes.iter()) {
+        let mut body: Vec<u8> = source.id.to_vec();
+
+        body.extend_from_slice(hash);
+        body.extend_from_slice(&source.hash_16k);
+        body.extend_from_slice(&source.len.to_le_bytes());
+        body.extend_from_slice(&pad4(&source.name));
+
+        critical.extend(packet(&set_id, TYPE_FILE_DESC, &body));
+    }
+
+    for (source, (_, slice_hash, crc)) in sources.iter().zip(hashes.iter()) {
+        let mut body: Vec<u8> = source.id.to_vec();
+
+        // an empty file has no slice
+        if source.len > 0 {
+            body.extend_from_slice(slice_hash);
+            body.extend_from_slice(&crc.to_le_bytes());
+        }
+
+        critical.extend(packet(&set_id, TYPE_IFSC, &body));
+    }
+
+    critical.extend(packet(&set_id, TYPE_CREATOR, &pad4(CREATOR)));
+
+    let mut volume: Vec<u8> = Vec::new();
+
+    for (exponent, output) in recovery.iter().enumerate() {
+        let mut body: Vec<u8> = (exponent as u32).to_le_bytes().to_vec();
+
+        body.extend_from_slice(output);
+
+        volume.extend(packet(&set_id, TYPE_RECOVERY, &body));
+    }
+
+    volume.extend_from_slice(&critical);
+
+    let index_path: PathBuf = out_dir.join(format!("{base}.par2"));
+
+    let volume_path: PathBuf =
+        out_dir.join(format!("{base}.vol00+{recovery_slices:02}.par2"));
+
+    write_file(&index_path, &[&critical], sync)
+        .map_err(|_| ParityError::ChunkNotWritten)?;
+
+    write_file(&volume_path, &[&volume], sync)
+        .map_err(|_| ParityError::ChunkNotWritten)?;
+
+    Ok(vec![index_path, volume_path])
+}
+
+/// Description of a file of a recovery set.
+#[derive(Debug, Clone)]
+struct FileDesc {
+    hash: [u8; 16],
+    len: usize,
+    name: String,
+}
+
+/// Packets of a recovery set read from PAR2 files.
+#[derive(Debug, Default)]
+struct RecoverySet {
+    slice_size: usize,
+    ids: Vec<[u8; 16]>,
+    files: BTreeMap<[u8; 16], FileDesc>,
+    checksums: BTreeMap<[u8; 16], Vec<[u8; 16]>>,
+    recovery: BTreeMap<usize, Vec<u8>>,
+}
+
+impl RecoverySet {
+    /// Read the valid packets of the PAR2 files of the directory.
+    fn read(in_dir: &Path) -> Result<Option<Self>, ParityError> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(in_dir)
+            .map_err(|_| ParityError::ChunkNotRead)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| is_par2(path) && path.is_file())
+            .collect();
+
+        paths.sort();
+
+        let mut set_id: Option<[u8; 16]> = None;
+
+        let mut set: RecoverySet = RecoverySet::default();
+
+        let mut has_main: bool = false;
+
+        for path in paths {
+            let data: Vec<u8> = match fs::read(&path) {
+                | Ok(data) => data,
+                | Err(_) => continue,
+            };
+
+            let mut offset: usize = 0;
+
+            while offset + HEADER_LEN <= data.len() {
+                // resync on the magic bytes after a damaged packet
+                if &data[offset..offset + 8] != MAGIC {
+                    offset += 4;
+                    continue;
+                }
+
+                let len: usize = u64::from_le_bytes(
+                    data[offset + 8..offset + 16]
+                        .try_into()
+                        .unwrap_or_default(),
+                ) as usize;
+
+                if len < HEADER_LEN
+                    || len % 4 != 0
+                    || offset
+                        .checked_add(len)
+                        .is_none_or(|end| end > data.len())
+                    || md5(&data[offset + 32..offset + len])
+                        != data[offset + 16..offset + 32]
+                {
+                    offset += 4;
+                    continue;
+                }
+
+                let id: [u8; 16] = data[offset + 32..offset + 48]
+                    .try_into()
+                    .unwrap_or_default();
+
+                let kind: &[u8] = &data[offset + 48..offset + 64];
+
+                let body: &[u8] = &data[offset + HEADER_LEN..offset + len];
+
+                offset += len;
+
+                if *set_id.get_or_insert(id) != id {
+                    continue;
+                }
+
+                set.add(kind, body, &mut has_main);
+            }
+        }
+
+        Ok(has_main.then_some(set))
+    }
+
+    /// Add a packet to the set.
+    fn add(
+        &mut self,
+        kind: &[u8],
+        body: &[u8],
+        has_main: &mut bool,
+    ) {
+        let id = |body: &[u8]| -> Option<[u8; 16]> {
+            body.get(..16)?.try_into().ok()
+        };
+
+        if kind == TYPE_MAIN && !*has_main && body.len() >= 12 {
+            let count: usize =
+                u32::from_le_bytes(body[8..12].try_into().unwrap_or_default())
+                    as usize;
+
+            let ids: Option<Vec<[u8; 16]>> =
+                (0..count).map(|i| id(body.get(12 + i * 16..)?)).collect();
+
+            if let Some(ids) = ids {
+                self.slice_size = u64::from_le_bytes(
+                    body[..8].try_into().unwrap_or_default(),
+                ) as usize;
+                self.ids = ids;
+
+                *has_main = true;
+            }
+        } else if kind == TYPE_FILE_DESC && body.len() >= 56 {
+            let name: String = String::from_utf8_lossy(&body[56..])
+                .trim_end_matches('\0')
+                .to_string();
+
+            if let (Some(file_id), Ok(hash)) =
+                (id(body), body[16..32].try_into())
+            {
+                self.files.insert(
+                    file_id,
+                    FileDesc {
+                        hash,
+                        len: u64::from_le_bytes(
+                            body[48..56].try_into().unwrap_or_default(),
+                        ) as usize,
+                        name,
+                    },
+                );
+            }
+        } else if kind == TYPE_IFSC && body.len() >= 16 {
+            if let Some(file_id) = id(body) {
+                let checksums: Vec<[u8; 16]> = body[16..]
+                    .chunks_exact(20)
+                    .filter_map(|c| c[..16].try_into().ok())
+                    .collect();
+
+                self.checksums.insert(file_id, checksums);
+            }
+        } else if kind == TYPE_RECOVERY && body.len() >= 4 {
+            let exponent: usize =
+                u32::from_le_bytes(body[..4].try_into().unwrap_or_default())
+                    as usize;
+
+            self.recovery.insert(exponent, body[4..].to_vec());
+        }
+    }
+}
+
+/// Invert a square matrix of GF(2^16), `None` if it is singular.
+fn invert(mut matrix: Vec<Vec<u16>>) -> Option<Vec<Vec<u16>>> {
+    let n: usize = matrix.len();
+
+    let mut inverse: Vec<Vec<u16>> =
+        (0..n).map(|i| (0..n).map(|j| (i == j) as u16).collect()).collect();
+
+    for col in 0..n {
+        let pivot: usize = (col..n).find(|&r| matrix[r][col] != 0)?;
+
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let factor: u16 = inv(matrix[col][col]);
+
+        for j in 0..n {
+            matrix[col][j] = mul(matrix[col][j], factor);
+            inverse[col][j] = mul(inverse[col][j], factor);
+        }
+
+        for r in 0..n {
+            let factor: u16 = matrix[r][col];
+
+            if r == col || factor == 0 {
+                continue;
+            }
+
+            for j in 0..n {
+                matrix[r][j] ^= mul(factor, matrix[col][j]);
+                inverse[r][j] ^= mul(factor, inverse[col][j]);
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+/// Reconstruct the chunks missing or corrupted in the directory
+/// from the PAR2 files, if any.
+///
+/// The chunks reconstructed are written to the output directory,
+/// which is the input directory unless it is read-only.
+/// Returns the indices of the chunks reconstructed.
+pub(crate) fn repair(
+    in_dir: &Path,
+    out_dir: &Path,
+    naming: &ChunkNaming,
+    sync: SyncPolicy,
+) -> Result<Vec<usize>, ParityError> {
+    let set: RecoverySet = match RecoverySet::read(in_dir)? {
+        | Some(set) => set,
+        | None => return Ok(Vec::new()),
+    };
+
+    let slice_size: usize = set.slice_size;
+
+    if slice_size == 0 || slice_size % 4 != 0 {
+        return Err(ParityError::Unrecoverable);
+    }
+
+    // the input slices, by file, in the order of the main packet
+    let mut slices: Vec<(usize, usize)> = Vec::new();
+
+    let mut files: Vec<&FileDesc> = Vec::with_capacity(set.ids.len());
+
+    for id in set.ids.iter() {
+        let file: &FileDesc =
+            set.files.get(id).ok_or(ParityError::Unrecoverable)?;
+
+        for slice in 0..file.len.div_ceil(slice_size) {
+            slices.push((files.len(), slice));
+        }
+
+        files.push(file);
+    }
+
+    if slices.len() > PAR2_SLICES_MAX {
+        return Err(ParityError::Unrecoverable);
+    }
+
+    // the content of each file, `None` if lost
+    let mut contents: Vec<Option<Vec<u8>>> = Vec::with_capacity(files.len());
+
+    let mut damaged: Vec<usize> = Vec::new();
+
+    for (i, file) in files.iter().enumerate() {
+        let data: Option<Vec<u8>> = read_chunk(in_dir, out_dir, &file.name)
+            .ok()
+            .filter(|data| data.len() == file.len);
+
+        if data.as_ref().is_none_or(|data| md5(data) != file.hash) {
+            damaged.push(i);
+        }
+
+        contents.push(data);
+    }
+
+    if damaged.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let read_slice =
+        |file: &Option<Vec<u8>>, slice: usize| -> Option<Vec<u8>> {
+            let data: &Vec<u8> = file.as_ref()?;
+
+            let start: usize = slice * slice_size;
+
+            let mut buffer: Vec<u8> =
+                data[start..data.len().min(start + slice_size)].to_vec();
+
+            buffer.resize(slice_size, 0);
+
+            Some(buffer)
+        };
+
+    // the slices of the damaged files not matching their checksums
+    let mut lost: Vec<usize> = Vec::new();
+
+    for (s, &(file, slice)) in slices.iter().enumerate() {
+        if !damaged.contains(&file) {
+            continue;
+        }
+
+        let checksum: Option<[u8; 16]> = set
+            .checksums
+            .get(&set.ids[file])
+            .and_then(|c| c.get(slice).copied());
+
+        let valid: bool = match (read_slice(&contents[file], slice), checksum) {
+            | (Some(buffer), Some(checksum)) => md5(&buffer) == checksum,
+            | _ => false,
+        };
+
+        if !valid {
+            lost.push(s);
+        }
+    }
+
+    let exponents: Vec<usize> = set
+        .recovery
+        .iter()
+        .filter(|(_, data)| data.len() == slice_size)
+        .map(|(exponent, _)| *exponent)
+        .take(lost.len())
+        .collect();
+
+    if exponents.len() < lost.len() {
+        return Err(ParityError::Unrecoverable);
+    }
+
+    let logs: Vec<usize> = constant_logs(slices.len());
+
+    // the recovery slices without the slices left
+    let mut residuals: Vec<Vec<u8>> =
+        exponents.iter().map(|e| set.recovery[e].clone()).collect();
+
+    for (s, &(file, slice)) in slices.iter().enumerate() {
+        if lost.contains(&s) {
+            continue;
+        }
+
+        let buffer: Vec<u8> = read_slice(&contents[file], slice)
+            .ok_or(ParityError::ChunkNotRead)?;
+
+        for (residual, exponent) in residuals.iter_mut().zip(exponents.iter()) {
+            mul_add(pow2(logs[s] * exponent), &buffer, residual);
+        }
+    }
+
+    let decoding: Vec<Vec<u16>> = invert(
+        exponents
+            .iter()
+            .map(|e| lost.iter().map(|&s| pow2(logs[s] * e)).collect())
+            .collect(),
+    )
+    .ok_or(ParityError::Unrecoverable)?;
+
+    for (row, &s) in decoding.iter().zip(lost.iter()) {
+        let mut recovered: Vec<u8> = vec![0; slice_size];
+
+        for (factor, residual) in row.iter().zip(residuals.iter()) {
+            mul_add(*factor, residual, &mut recovered);
+        }
+
+        let (file, slice) = slices[s];
+
+        let content: &mut Vec<u8> =
+            contents[file].get_or_insert_with(|| vec![0; files[file].len]);
+
+        content.resize(files[file].len, 0);
+
+        let start: usize = slice * slice_size;
+
+        let end: usize = content.len().min(start + slice_size);
+
+        content[start..end].copy_from_slice(&recovered[..end - start]);
+    }
+
+    let mut repaired: Vec<usize> = Vec::with_capacity(damaged.len());
+
+    for file in damaged {
+        let content: Vec<u8> = contents[file].take().unwrap_or_default();
+
+        if md5(&content) != files[file].hash {
+            return Err(ParityError::Unrecoverable);
+        }
+
+        let path: PathBuf = out_dir.join(&files[file].name);
+
+        write_file(&path, &[&content], sync)
+            .map_err(|_| ParityError::ChunkNotWritten)?;
+
+        if let Some(index) = naming.index_of(&path) {
+            repaired.push(index);
+        }
+    }
+
+    repaired.sort();
+
+    Ok(repaired)
+}
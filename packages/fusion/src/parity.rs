@@ -0,0 +1,498 @@
+use std::{
+    fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    chunk::{ChunkInfo, ChunkNaming, temp_path},
+    hash::HashAlgorithm,
+    sync::SyncPolicy,
+};
+
+/// The maximum number of data and parity chunks in a chunk set.
+pub const PARITY_SHARDS_MAX: usize = 256;
+
+/// The magic bytes at the start of a parity chunk.
+const MAGIC: &[u8; 8] = b"FRPARITY";
+
+/// The version of the parity chunk format.
+const VERSION: u8 = 1;
+
+/// The length of the digests in the header of a parity chunk.
+const DIGEST_LEN: usize = 32;
+
+/// The logarithms and the exponentials of GF(2^8),
+/// with the polynomial `x^8 + x^4 + x^3 + x^2 + 1`.
+const TABLES: ([u8; 256], [u8; 512]) = tables();
+
+const fn tables() -> ([u8; 256], [u8; 512]) {
+    let mut log: [u8; 256] = [0; 256];
+    let mut exp: [u8; 512] = [0; 512];
+
+    let mut x: usize = 1;
+    let mut i: usize = 0;
+
+    while i < 255 {
+        exp[i] = x as u8;
+        exp[i + 255] = x as u8;
+        log[x] = i as u8;
+
+        x <<= 1;
+
+        if x & 0x100 != 0 {
+            x ^= 0x11d;
+        }
+
+        i += 1;
+    }
+
+    (log, exp)
+}
+
+/// Multiply two elements of GF(2^8).
+pub(crate) fn mul(
+    a: u8,
+    b: u8,
+) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+
+    TABLES.1[TABLES.0[a as usize] as usize + TABLES.0[b as usize] as usize]
+}
+
+/// Get the inverse of a non-zero element of GF(2^8).
+pub(crate) fn inv(a: u8) -> u8 {
+    TABLES.1[255 - TABLES.0[a as usize] as usize]
+}
+
+/// Add the data multiplied by the coefficient to the output.
+pub(crate) fn mul_add(
+    coefficient: u8,
+    data: &[u8],
+    output: &mut [u8],
+) {
+    if coefficient == 0 {
+        return;
+    }
+
+    let mut table: [u8; 256] = [0; 256];
+
+    for (b, t) in table.iter_mut().enumerate() {
+        *t = mul(coefficient, b as u8);
+    }
+
+    for (o, d) in output.iter_mut().zip(data) {
+        *o ^= table[*d as usize];
+    }
+}
+
+/// Get the row of the encoding matrix of a shard.
+///
+/// The data shards are kept as is, and the parity shards follow
+/// a Cauchy matrix, so any `data` rows of the matrix are invertible.
+fn row(
+    shard: usize,
+    data: usize,
+) -> Vec<u8> {
+    (0..data)
+        .map(|j| match shard < data {
+            | true => (shard == j) as u8,
+            | false => inv(shard as u8 ^ j as u8),
+        })
+        .collect()
+}
+
+/// Invert a square matrix, `None` if it is singular.
+fn invert(mut matrix: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let n: usize = matrix.len();
+
+    let mut inverse: Vec<Vec<u8>> = (0..n).map(|i| row(i, n)).collect();
+
+    for col in 0..n {
+        let pivot: usize = (col..n).find(|&r| matrix[r][col] != 0)?;
+
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let factor: u8 = inv(matrix[col][col]);
+
+        for j in 0..n {
+            matrix[col][j] = mul(matrix[col][j], factor);
+            inverse[col][j] = mul(inverse[col][j], factor);
+        }
+
+        for r in 0..n {
+            let factor: u8 = matrix[r][col];
+
+            if r == col || factor == 0 {
+                continue;
+            }
+
+            for j in 0..n {
+                matrix[r][j] ^= mul(factor, matrix[col][j]);
+                inverse[r][j] ^= mul(factor, inverse[col][j]);
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+/// Header of a parity chunk, describing the whole chunk set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ParityHeader {
+    /// The number of data chunks.
+    data_chunks: usize,
+    /// The number of parity chunks.
+    parity_chunks: usize,
+    /// The length of each shard, the size of the largest data chunk.
+    shard_len: usize,
+    /// The size of each data chunk.
+    sizes: Vec<usize>,
+    /// The digest of each data chunk.
+    digests: Vec<Vec<u8>>,
+}
+
+impl ParityHeader {
+    /// Encode the header of a parity chunk with the digest of its shard.
+    fn encode(
+        &self,
+        index: usize,
+        shard_digest: &[u8],
+    ) -> Vec<u8> {
+        let mut header: Vec<u8> =
+            Vec::with_capacity(73 + self.data_chunks * (8 + DIGEST_LEN));
+
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+
+        for value in
+            [self.data_chunks, self.parity_chunks, index, self.shard_len]
+        {
+            header.extend_from_slice(&(value as u64).to_le_bytes());
+        }
+
+        header.extend_from_slice(shard_digest);
+
+        for (size, digest) in self.sizes.iter().zip(self.digests.iter()) {
+            header.extend_from_slice(&(*size as u64).to_le_bytes());
+            header.extend_from_slice(digest);
+        }
+
+        header
+    }
+
+    /// Decode a parity chunk, verified by the digest of its shard.
+    ///
+    /// Returns the header, the index of the parity chunk and its shard.
+    fn decode(data: &[u8]) -> Option<(Self, usize, &[u8])> {
+        let mut cursor: &[u8] = data.strip_prefix(MAGIC)?;
+
+        let (version, rest) = cursor.split_first()?;
+
+        if *version != VERSION {
+            return None;
+        }
+
+        cursor = rest;
+
+        let data_chunks: usize = read_usize(&mut cursor)?;
+        let parity_chunks: usize = read_usize(&mut cursor)?;
+        let index: usize = read_usize(&mut cursor)?;
+        let shard_len: usize = read_usize(&mut cursor)?;
+
+        if data_chunks + parity_chunks > PARITY_SHARDS_MAX
+            || index >= parity_chunks
+        {
+            return None;
+        }
+
+        let shard_digest: Vec<u8> = take(&mut cursor, DIGEST_LEN)?.to_vec();
+
+        let mut sizes: Vec<usize> = Vec::with_capacity(data_chunks);
+        let mut digests: Vec<Vec<u8>> = Vec::with_capacity(data_chunks);
+
+        for _ in 0..data_chunks {
+            sizes.push(read_usize(&mut cursor)?);
+            digests.push(take(&mut cursor, DIGEST_LEN)?.to_vec());
+        }
+
+        let shard: &[u8] = take(&mut cursor, shard_len)?;
+
+        if HashAlgorithm::Sha256.digest(shard).bytes != shard_digest {
+            return None;
+        }
+
+        let header: Self =
+            Self { data_chunks, parity_chunks, shard_len, sizes, digests };
+
+        Some((header, index, shard))
+    }
+}
+
+/// Take the next bytes of the cursor.
+fn take<'a>(
+    cursor: &mut &'a [u8],
+    len: usize,
+) -> Option<&'a [u8]> {
+    let (value, rest) = cursor.split_at_checked(len)?;
+
+    *cursor = rest;
+
+    Some(value)
+}
+
+/// Take the next `u64` of the cursor as `usize`.
+fn read_usize(cursor: &mut &[u8]) -> Option<usize> {
+    usize::try_from(u64::from_le_bytes(take(cursor, 8)?.try_into().ok()?)).ok()
+}
+
+/// Parity process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ParityError {
+    /// A chunk could not be read.
+    ChunkNotRead,
+    /// More chunks are lost than parity chunks are left.
+    Unrecoverable,
+    /// A chunk could not be written.
+    ChunkNotWritten,
+}
+
+/// Get the file name of a parity chunk by its index.
+pub(crate) fn file_name(
+    naming: &ChunkNaming,
+    index: usize,
+) -> String {
+    format!("{}.parity", naming.file_name(index))
+}
+
+/// Parse the index of a parity chunk from its path.
+pub(crate) fn index_of(
+    naming: &ChunkNaming,
+    path: &Path,
+) -> Option<usize> {
+    naming.parse(path.file_name()?.to_str()?.strip_suffix(".parity")?)
+}
+
+/// Read a chunk of the input directory, or its repaired copy
+/// in the output directory if any.
+pub(crate) fn read_chunk(
+    in_dir: &Path,
+    out_dir: &Path,
+    name: &str,
+) -> io::Result<Vec<u8>> {
+    match fs::read(out_dir.join(name)) {
+        | Ok(data) => Ok(data),
+        | Err(_) if in_dir != out_dir => fs::read(in_dir.join(name)),
+        | Err(e) => Err(e),
+    }
+}
+
+/// Write a file through a temporary file, synced by the policy.
+pub(crate) fn write_file(
+    path: &Path,
+    parts: &[&[u8]],
+    sync: SyncPolicy,
+) -> io::Result<()> {
+    let write_path: PathBuf = temp_path(path);
+
+    let mut file: fs::File = fs::File::create(&write_path)?;
+
+    for part in parts {
+        file.write_all(part)?;
+    }
+
+    sync.sync_file(&file)?;
+
+    drop(file);
+
+    fs::rename(&write_path, path)
+}
+
+/// Write the parity chunks of the data chunks written to the directory.
+///
+/// Each chunk is read once, while the parity shards are kept in memory.
+pub(crate) fn write_parity(
+    out_dir: &Path,
+    naming: &ChunkNaming,
+    chunks: &[ChunkInfo],
+    parity_chunks: usize,
+    sync: SyncPolicy,
+) -> Result<Vec<PathBuf>, ParityError> {
+    let data_chunks: usize = chunks.len();
+
+    if parity_chunks == 0 || data_chunks == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut header: ParityHeader = ParityHeader {
+        data_chunks,
+        parity_chunks,
+        shard_len: 0,
+        sizes: Vec::with_capacity(data_chunks),
+        digests: Vec::with_capacity(data_chunks),
+    };
+
+    // the stored size may differ from the size of the content
+    for chunk in chunks {
+        let len: usize = fs::metadata(&chunk.path)
+            .ok()
+            .and_then(|m| usize::try_from(m.len()).ok())
+            .ok_or(ParityError::ChunkNotRead)?;
+
+        header.shard_len = header.shard_len.max(len);
+    }
+
+    let mut shards: Vec<Vec<u8>> =
+        vec![vec![0; header.shard_len]; parity_chunks];
+
+    for (j, chunk) in chunks.iter().enumerate() {
+        let data: Vec<u8> =
+            fs::read(&chunk.path).map_err(|_| ParityError::ChunkNotRead)?;
+
+        for (i, shard) in shards.iter_mut().enumerate() {
+            mul_add(row(data_chunks + i, data_chunks)[j], &data, shard);
+        }
+
+        header.sizes.push(data.len());
+        header.digests.push(HashAlgorithm::Sha256.digest(&data).bytes);
+    }
+
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(parity_chunks);
+
+    for (i, shard) in shards.iter().enumerate() {
+        let path: PathBuf = out_dir.join(file_name(naming, i));
+
+        let shard_digest: Vec<u8> = HashAlgorithm::Sha256.digest(shard).bytes;
+
+        write_file(&path, &[&header.encode(i, &shard_digest), shard], sync)
+            .map_err(|_| ParityError::ChunkNotWritten)?;
+
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Reconstruct the data chunks missing or corrupted in the directory
+/// from the parity chunks, if any.
+///
+/// The chunks reconstructed are written to the output directory,
+/// which is the input directory unless it is read-only.
+/// Returns the indices of the chunks reconstructed.
+pub(crate) fn repair(
+    in_dir: &Path,
+    out_dir: &Path,
+    naming: &ChunkNaming,
+    sync: SyncPolicy,
+) -> Result<Vec<usize>, ParityError> {
+    let mut parity_paths: Vec<(usize, PathBuf)> = fs::read_dir(in_dir)
+        .map_err(|_| ParityError::ChunkNotRead)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter_map(|path| Some((index_of(naming, &path)?, path)))
+        .collect();
+
+    parity_paths.sort();
+
+    // the header of the first valid parity chunk
+    let mut header: Option<ParityHeader> = None;
+
+    let mut parity: Vec<usize> = Vec::with_capacity(parity_paths.len());
+
+    for (i, path) in parity_paths.iter() {
+        let data: Vec<u8> = match fs::read(path) {
+            | Ok(data) => data,
+            | Err(_) => continue,
+        };
+
+        let (h, index, _) = match ParityHeader::decode(&data) {
+            | Some(decoded) => decoded,
+            | None => continue,
+        };
+
+        if index != *i || header.as_ref().is_some_and(|header| *header != h) {
+            continue;
+        }
+
+        header = Some(h);
+
+        parity.push(index);
+    }
+
+    let header: ParityHeader = match header {
+        | Some(header) => header,
+        | None => return Ok(Vec::new()),
+    };
+
+    let data_chunks: usize = header.data_chunks;
+
+    let mut lost: Vec<usize> = Vec::new();
+
+    for j in 0..data_chunks {
+        let valid: bool = read_chunk(in_dir, out_dir, &naming.file_name(j))
+            .is_ok_and(|data| {
+                data.len() == header.sizes[j]
+                    && HashAlgorithm::Sha256.digest(&data).bytes
+                        == header.digests[j]
+            });
+
+        if !valid {
+            lost.push(j);
+        }
+    }
+
+    if lost.is_empty() {
+        return Ok(lost);
+    }
+
+    if lost.len() > parity.len() {
+        return Err(ParityError::Unrecoverable);
+    }
+
+    // the shards to decode from, the data chunks left first
+    let shards: Vec<usize> = (0..data_chunks)
+        .filter(|j| !lost.contains(j))
+        .chain(parity.iter().take(lost.len()).map(|i| data_chunks + i))
+        .collect();
+
+    let decoding: Vec<Vec<u8>> =
+        invert(shards.iter().map(|&s| row(s, data_chunks)).collect())
+            .ok_or(ParityError::Unrecoverable)?;
+
+    let mut outputs: Vec<Vec<u8>> = vec![vec![0; header.shard_len]; lost.len()];
+
+    for (k, &shard) in shards.iter().enumerate() {
+        let data: Vec<u8> = match shard < data_chunks {
+            | true => read_chunk(in_dir, out_dir, &naming.file_name(shard))
+                .map_err(|_| ParityError::ChunkNotRead)?,
+            | false => {
+                let path: PathBuf =
+                    in_dir.join(file_name(naming, shard - data_chunks));
+
+                let data: Vec<u8> =
+                    fs::read(path).map_err(|_| ParityError::ChunkNotRead)?;
+
+                match ParityHeader::decode(&data) {
+                    | Some((_, _, shard)) => shard.to_vec(),
+                    | None => return Err(ParityError::ChunkNotRead),
+                }
+            },
+        };
+
+        for (output, &j) in outputs.iter_mut().zip(lost.iter()) {
+            mul_add(decoding[j][k], &data, output);
+        }
+    }
+
+    for (mut output, &j) in outputs.into_iter().zip(lost.iter()) {
+        output.truncate(header.sizes[j]);
+
+        write_file(&out_dir.join(naming.file_name(j)), &[&output], sync)
+            .map_err(|_| ParityError::ChunkNotWritten)?;
+    }
+
+    Ok(lost)
+}
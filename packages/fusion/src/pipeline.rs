@@ -0,0 +1,799 @@
+use std::{
+    ffi::OsString,
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cancel::CancellationToken,
+    check::{Check, CheckError, CheckReport},
+    chunk::ChunkNaming,
+    compare::{Compare, CompareError, CompareResult},
+    merge::{Merge, MergeError, MergeResult},
+    progress::{Progress, ProgressGuard},
+    split::{Split, SplitError, SplitResult},
+    store::{ChunkStore, StoreError},
+    warning::{Warning, WarningHandler},
+};
+
+/// Chunk store shared by the stages of a pipeline.
+pub type SharedStore = Arc<Mutex<dyn ChunkStore + Send>>;
+
+/// Stage of a pipeline.
+#[derive(Clone)]
+pub enum Stage {
+    /// Split the input file into the chunk directory.
+    Split(Split),
+    /// Check the chunks of the chunk directory.
+    Check(Check),
+    /// Write the chunks of the chunk directory to a store.
+    Upload(SharedStore),
+    /// Read the chunks from a store into the chunk directory.
+    Download(SharedStore),
+    /// Merge the chunks of the chunk directory.
+    Merge(Merge),
+    /// Compare a file with the chunks of the chunk directory.
+    Compare(Compare),
+    /// Remove the chunk directory.
+    Cleanup,
+}
+
+impl Stage {
+    /// Get the name of the stage as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Split(_) => "split",
+            | Self::Check(_) => "check",
+            | Self::Upload(_) => "upload",
+            | Self::Download(_) => "download",
+            | Self::Merge(_) => "merge",
+            | Self::Compare(_) => "compare",
+            | Self::Cleanup => "cleanup",
+        }
+    }
+}
+
+impl fmt::Debug for Stage {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            | Self::Split(process) => {
+                f.debug_tuple("Split").field(process).finish()
+            },
+            | Self::Check(process) => {
+                f.debug_tuple("Check").field(process).finish()
+            },
+            | Self::Upload(_) => {
+                f.debug_tuple("Upload").finish_non_exhaustive()
+            },
+            | Self::Download(_) => {
+                f.debug_tuple("Download").finish_non_exhaustive()
+            },
+            | Self::Merge(process) => {
+                f.debug_tuple("Merge").field(process).finish()
+            },
+            | Self::Compare(process) => {
+                f.debug_tuple("Compare").field(process).finish()
+            },
+            | Self::Cleanup => f.write_str("Cleanup"),
+        }
+    }
+}
+
+/// Function receiving a stage with its index.
+type StageFn = dyn Fn(usize, &Stage) + Send + Sync;
+
+/// Handler to receive the stages of a pipeline as they start.
+#[derive(Clone)]
+pub struct StageHandler {
+    handler: Arc<StageFn>,
+}
+
+impl StageHandler {
+    /// Create a new stage handler.
+    pub fn new<F: Fn(usize, &Stage) + Send + Sync + 'static>(
+        handler: F
+    ) -> Self {
+        Self { handler: Arc::new(handler) }
+    }
+
+    /// Emit a stage with its index to the handler.
+    pub fn emit(
+        &self,
+        index: usize,
+        stage: &Stage,
+    ) {
+        (self.handler)(index, stage)
+    }
+}
+
+impl fmt::Debug for StageHandler {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("StageHandler").finish_non_exhaustive()
+    }
+}
+
+/// Result of the pipeline, with the result of each kind of stage run.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineResult {
+    /// The result of the last split stage.
+    pub split: Option<SplitResult>,
+    /// The report of the last check stage.
+    pub check: Option<CheckReport>,
+    /// The result of the last merge stage.
+    pub merge: Option<MergeResult>,
+    /// The result of the last compare stage.
+    pub compare: Option<CompareResult>,
+    /// The number of chunks written to the stores.
+    pub chunks_uploaded: usize,
+    /// The number of chunks read from the stores.
+    pub chunks_downloaded: usize,
+    /// The number of stages run.
+    pub stages: usize,
+    /// The time taken by the pipeline.
+    pub duration: Duration,
+}
+
+/// Pipeline process error enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineError {
+    Split(SplitError),
+    Check(CheckError),
+    Merge(MergeError),
+    Compare(CompareError),
+    Upload(StoreError),
+    Download(StoreError),
+    ChunkDirNotSet,
+    ChunkDirNotCreated,
+    ChunkDirNotRemoved,
+    TotalChunksUnknown,
+    FileNotMatched,
+    Cancelled,
+    DeadlineExceeded,
+}
+
+impl PipelineError {
+    /// Get the code of the error as `&str`.
+    ///
+    /// The errors of the stages keep the code of the stage.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::Split(error) => error.as_code(),
+            | Self::Check(error) => error.as_code(),
+            | Self::Merge(error) => error.as_code(),
+            | Self::Compare(error) => error.as_code(),
+            | Self::Upload(error) => error.as_code(),
+            | Self::Download(error) => error.as_code(),
+            | Self::ChunkDirNotSet => "chunk_dir_not_set",
+            | Self::ChunkDirNotCreated => "chunk_dir_not_created",
+            | Self::ChunkDirNotRemoved => "chunk_dir_not_removed",
+            | Self::TotalChunksUnknown => "total_chunks_unknown",
+            | Self::FileNotMatched => "file_not_matched",
+            | Self::Cancelled => "cancelled",
+            | Self::DeadlineExceeded => "deadline_exceeded",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    ///
+    /// The errors of the stages keep the message of the stage.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::Split(error) => error.as_message(),
+            | Self::Check(error) => error.as_message(),
+            | Self::Merge(error) => error.as_message(),
+            | Self::Compare(error) => error.as_message(),
+            | Self::Upload(error) => error.as_message(),
+            | Self::Download(error) => error.as_message(),
+            | Self::ChunkDirNotSet => "The chunk directory is not set.",
+            | Self::ChunkDirNotCreated => {
+                "The chunk directory could not be created."
+            },
+            | Self::ChunkDirNotRemoved => {
+                "The chunk directory could not be removed."
+            },
+            | Self::TotalChunksUnknown => {
+                "The total number of chunks is unknown before the stage."
+            },
+            | Self::FileNotMatched => {
+                "The file compared does not match the chunks."
+            },
+            | Self::Cancelled => "The pipeline was cancelled.",
+            | Self::DeadlineExceeded => {
+                "The deadline of the pipeline was exceeded."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Process to run operations one after another on a chunk directory,
+/// such as split, check, upload and cleanup,
+/// or download, check, merge and compare.
+///
+/// The chunk directory, the naming scheme and the handles set on the
+/// pipeline are shared by all the stages, and the results of a stage
+/// fill the options of the next ones not set, such as the file size
+/// and the total number of chunks to check and to merge after a split.
+/// The stages run in the order they are added.
+///
+/// To copy a file through chunks in one call, use [`Pipeline::copy`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     check::Check,
+///     merge::Merge,
+///     pipeline::{Pipeline, PipelineResult},
+///     split::Split,
+/// };
+///
+/// let result: PipelineResult = Pipeline::new()
+///     .chunk_dir(PathBuf::from("path").join("to").join("dir"))
+///     .split(Split::new().in_file(PathBuf::from("path").join("to").join("file")))
+///     .check(Check::new())
+///     .merge(Merge::new().out_file(PathBuf::from("path").join("to").join("copy")))
+///     .cleanup()
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    pub chunk_dir: Option<Arc<Path>>,
+    pub naming: Option<ChunkNaming>,
+    pub file_size: Option<u64>,
+    pub total_chunks: Option<usize>,
+    pub stages: Vec<Stage>,
+    pub cancellation_token: Option<CancellationToken>,
+    pub deadline: Option<Instant>,
+    pub progress: Option<Progress>,
+    pub on_warning: Option<WarningHandler>,
+    pub on_stage: Option<StageHandler>,
+    pub cleanup_on_error: bool,
+}
+
+/// State passed from a stage to the next ones.
+struct PipelineState {
+    chunk_dir: Option<Arc<Path>>,
+    naming: ChunkNaming,
+    file_size: Option<u64>,
+    total_chunks: Option<usize>,
+    chunk_size: Option<usize>,
+    file: Option<PathBuf>,
+}
+
+impl Pipeline {
+    /// Create a new pipeline.
+    pub fn new() -> Self {
+        Self {
+            chunk_dir: None,
+            naming: None,
+            file_size: None,
+            total_chunks: None,
+            stages: Vec::new(),
+            cancellation_token: None,
+            deadline: None,
+            progress: None,
+            on_warning: None,
+            on_stage: None,
+            cleanup_on_error: false,
+        }
+    }
+
+    /// Create a new pipeline copying a file through chunks,
+    /// with the split, check, merge, compare and cleanup stages.
+    ///
+    /// The chunks are written to a directory named after the output file
+    /// with a `.chunks` suffix, such as `file.chunks`, unless set by
+    /// [`Pipeline::chunk_dir`]. The merge expects the chunks of the split,
+    /// the output file is written atomically and compared with the chunks,
+    /// and the chunk directory is removed once done or when a stage fails.
+    /// An existing output file fails the merge with
+    /// [`MergeError::OutFileExists`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::pipeline::{Pipeline, PipelineResult};
+    ///
+    /// let result: PipelineResult = Pipeline::copy(
+    ///     PathBuf::from("path").join("to").join("file"),
+    ///     PathBuf::from("path").join("to").join("copy"),
+    /// )
+    /// .run()
+    /// .unwrap();
+    /// ```
+    pub fn copy<InFile: AsRef<Path>, OutFile: AsRef<Path>>(
+        in_file: InFile,
+        out_file: OutFile,
+    ) -> Self {
+        let mut chunk_dir: OsString = out_file.as_ref().as_os_str().to_owned();
+
+        chunk_dir.push(".chunks");
+
+        Self::new()
+            .chunk_dir(PathBuf::from(chunk_dir))
+            .split(Split::new().in_file(in_file))
+            .check(Check::new())
+            .merge(Merge::new().out_file(out_file).atomic(true))
+            .compare(Compare::new())
+            .cleanup()
+            .cleanup_on_error(true)
+    }
+
+    /// Create a new pipeline from an existing one.
+    pub fn from<P: Into<Pipeline>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the chunk directory shared by the stages.
+    ///
+    /// By default, it is the output directory of the first split stage.
+    pub fn chunk_dir<ChunkDir: AsRef<Path>>(
+        mut self,
+        path: ChunkDir,
+    ) -> Self {
+        self.chunk_dir = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the naming scheme of the chunk files of all the stages.
+    ///
+    /// By default, the naming scheme of the first split stage is used.
+    pub fn naming(
+        mut self,
+        naming: ChunkNaming,
+    ) -> Self {
+        self.naming = Some(naming);
+        self
+    }
+
+    /// Set the size of the file, for the stages without a split before.
+    pub fn file_size(
+        mut self,
+        size: u64,
+    ) -> Self {
+        self.file_size = Some(size);
+        self
+    }
+
+    /// Set the total number of chunks, for the stages without a split before,
+    /// such as a download.
+    pub fn total_chunks(
+        mut self,
+        total_chunks: usize,
+    ) -> Self {
+        self.total_chunks = Some(total_chunks);
+        self
+    }
+
+    /// Add a stage to the pipeline.
+    pub fn stage(
+        mut self,
+        stage: Stage,
+    ) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Add a split stage, writing to the chunk directory if not set.
+    pub fn split(
+        self,
+        process: Split,
+    ) -> Self {
+        self.stage(Stage::Split(process))
+    }
+
+    /// Add a check stage, reading the chunk directory if not set.
+    pub fn check(
+        self,
+        process: Check,
+    ) -> Self {
+        self.stage(Stage::Check(process))
+    }
+
+    /// Add a stage writing the chunks of the chunk directory to a store.
+    pub fn upload<S: ChunkStore + Send + 'static>(
+        self,
+        store: S,
+    ) -> Self {
+        self.stage(Stage::Upload(Arc::new(Mutex::new(store))))
+    }
+
+    /// Add a stage reading the chunks from a store into the chunk directory.
+    pub fn download<S: ChunkStore + Send + 'static>(
+        self,
+        store: S,
+    ) -> Self {
+        self.stage(Stage::Download(Arc::new(Mutex::new(store))))
+    }
+
+    /// Add a merge stage, reading the chunk directory if not set.
+    pub fn merge(
+        self,
+        process: Merge,
+    ) -> Self {
+        self.stage(Stage::Merge(process))
+    }
+
+    /// Add a compare stage, failing the pipeline if the file does not match.
+    ///
+    /// The file compared defaults to the output of the last merge stage,
+    /// or the input of the last split stage.
+    pub fn compare(
+        self,
+        process: Compare,
+    ) -> Self {
+        self.stage(Stage::Compare(process))
+    }
+
+    /// Add a stage removing the chunk directory.
+    pub fn cleanup(self) -> Self {
+        self.stage(Stage::Cleanup)
+    }
+
+    /// Set whether to remove the chunk directory when a stage fails,
+    /// so the chunks of a failed pipeline are not left behind.
+    ///
+    /// The whole chunk directory is removed, including the chunks
+    /// not written by the pipeline, such as before a check stage.
+    ///
+    /// By default, it is `false`.
+    pub fn cleanup_on_error(
+        mut self,
+        enabled: bool,
+    ) -> Self {
+        self.cleanup_on_error = enabled;
+        self
+    }
+
+    /// Set the token to cancel all the stages.
+    pub fn cancellation_token(
+        mut self,
+        token: CancellationToken,
+    ) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Set the deadline of all the stages.
+    pub fn deadline(
+        mut self,
+        deadline: Instant,
+    ) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set the handle to poll the progress of the running stage.
+    ///
+    /// The progress restarts with each stage,
+    /// use [`Pipeline::on_stage`] to follow the stages.
+    pub fn progress(
+        mut self,
+        progress: Progress,
+    ) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Set the handler to receive the warnings of all the stages.
+    pub fn on_warning<F: Fn(&Warning) + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_warning = Some(WarningHandler::new(handler));
+        self
+    }
+
+    /// Set the handler to receive each stage with its index as it starts.
+    pub fn on_stage<F: Fn(usize, &Stage) + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_stage = Some(StageHandler::new(handler));
+        self
+    }
+
+    /// Run the stages of the pipeline in order, until one fails.
+    pub fn run(&self) -> Result<PipelineResult, PipelineError> {
+        let started: Instant = Instant::now();
+
+        let mut state: PipelineState = PipelineState {
+            chunk_dir: self.chunk_dir.clone(),
+            naming: self.naming.clone().unwrap_or_default(),
+            file_size: self.file_size,
+            total_chunks: self.total_chunks,
+            chunk_size: None,
+            file: None,
+        };
+
+        // the first split stage sets the defaults
+        if let Some(Stage::Split(process)) =
+            self.stages.iter().find(|s| matches!(s, Stage::Split(_)))
+        {
+            if state.chunk_dir.is_none() {
+                state.chunk_dir = process.out_dir.clone();
+            }
+
+            if self.naming.is_none() {
+                state.naming = process.naming.clone();
+            }
+        }
+
+        let mut result: PipelineResult = PipelineResult::default();
+
+        if let Err(error) = self.run_stages(&mut state, &mut result) {
+            if self.cleanup_on_error {
+                if let Some(chunk_dir) = state.chunk_dir.as_deref() {
+                    let _ = fs::remove_dir_all(chunk_dir);
+                }
+            }
+
+            return Err(error);
+        }
+
+        result.duration = started.elapsed();
+
+        Ok(result)
+    }
+
+    /// Run the stages in order, until one fails.
+    fn run_stages(
+        &self,
+        state: &mut PipelineState,
+        result: &mut PipelineResult,
+    ) -> Result<(), PipelineError> {
+        for (index, stage) in self.stages.iter().enumerate() {
+            self.check_stop()?;
+
+            if let Some(ref handler) = self.on_stage {
+                handler.emit(index, stage);
+            }
+
+            self.run_stage(stage, state, result)?;
+
+            result.stages += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Run a stage with the shared options and the state of the pipeline.
+    fn run_stage(
+        &self,
+        stage: &Stage,
+        state: &mut PipelineState,
+        result: &mut PipelineResult,
+    ) -> Result<(), PipelineError> {
+        match stage {
+            | Stage::Split(process) => {
+                let mut process: Split = process.clone();
+
+                process.out_dir = process.out_dir.or(state.chunk_dir.clone());
+                process.naming = state.naming.clone();
+                process.cancellation_token = self
+                    .cancellation_token
+                    .clone()
+                    .or(process.cancellation_token);
+                process.deadline = self.deadline.or(process.deadline);
+                process.progress = self.progress.clone().or(process.progress);
+                process.on_warning =
+                    self.on_warning.clone().or(process.on_warning);
+
+                let split: SplitResult =
+                    process.run().map_err(PipelineError::Split)?;
+
+                state.chunk_dir = process.out_dir.clone();
+                state.file_size = Some(split.file_size);
+                state.total_chunks = Some(split.total_chunks);
+                state.chunk_size = Some(split.chunk_size);
+                state.file = process.in_file.as_deref().map(Path::to_path_buf);
+
+                result.split = Some(split);
+            },
+            | Stage::Check(process) => {
+                let mut process: Check = process.clone();
+
+                process.in_dir = process.in_dir.or(state.chunk_dir.clone());
+                process.naming = state.naming.clone();
+                process.file_size = process.file_size.or(state.file_size);
+                process.total_chunks =
+                    process.total_chunks.or(state.total_chunks);
+                process.cancellation_token = self
+                    .cancellation_token
+                    .clone()
+                    .or(process.cancellation_token);
+                process.deadline = self.deadline.or(process.deadline);
+                process.progress = self.progress.clone().or(process.progress);
+                process.on_warning =
+                    self.on_warning.clone().or(process.on_warning);
+
+                let report: CheckReport =
+                    process.run().map_err(PipelineError::Check)?;
+
+                state.file_size = Some(report.file_size);
+                state.total_chunks = Some(report.chunks.len());
+
+                result.check = Some(report);
+            },
+            | Stage::Upload(store) => {
+                let chunk_dir: &Path = state.chunk_dir()?;
+
+                let total_chunks: usize = state
+                    .total_chunks
+                    .ok_or(PipelineError::TotalChunksUnknown)?;
+
+                let progress: ProgressGuard =
+                    ProgressGuard::start(self.progress.as_ref());
+
+                let mut store: MutexGuard<dyn ChunkStore + Send> =
+                    store.lock().unwrap_or_else(|e| e.into_inner());
+
+                for index in 0..total_chunks {
+                    self.check_stop()?;
+
+                    let data: Vec<u8> =
+                        fs::read(chunk_dir.join(state.naming.file_name(index)))
+                            .map_err(|_| {
+                                PipelineError::Upload(StoreError::ChunkNotRead)
+                            })?;
+
+                    store.write(index, &data).map_err(PipelineError::Upload)?;
+
+                    progress.advance(index, data.len());
+
+                    result.chunks_uploaded += 1;
+                }
+
+                progress.finish();
+            },
+            | Stage::Download(store) => {
+                let chunk_dir: &Path = state.chunk_dir()?;
+
+                let total_chunks: usize = state
+                    .total_chunks
+                    .ok_or(PipelineError::TotalChunksUnknown)?;
+
+                fs::create_dir_all(chunk_dir)
+                    .map_err(|_| PipelineError::ChunkDirNotCreated)?;
+
+                let progress: ProgressGuard =
+                    ProgressGuard::start(self.progress.as_ref());
+
+                let store: MutexGuard<dyn ChunkStore + Send> =
+                    store.lock().unwrap_or_else(|e| e.into_inner());
+
+                for index in 0..total_chunks {
+                    self.check_stop()?;
+
+                    let data: Vec<u8> =
+                        store.read(index).map_err(PipelineError::Download)?;
+
+                    fs::write(
+                        chunk_dir.join(state.naming.file_name(index)),
+                        &data,
+                    )
+                    .map_err(|_| {
+                        PipelineError::Download(StoreError::ChunkNotWritten)
+                    })?;
+
+                    progress.advance(index, data.len());
+
+                    result.chunks_downloaded += 1;
+                }
+
+                progress.finish();
+            },
+            | Stage::Merge(process) => {
+                let mut process: Merge = process.clone();
+
+                process.in_dir = process.in_dir.or(state.chunk_dir.clone());
+                process.naming = state.naming.clone();
+                process.expected_chunks =
+                    process.expected_chunks.or(state.total_chunks);
+                process.expected_size =
+                    process.expected_size.or(state.file_size);
+                process.cancellation_token = self
+                    .cancellation_token
+                    .clone()
+                    .or(process.cancellation_token);
+                process.deadline = self.deadline.or(process.deadline);
+                process.progress = self.progress.clone().or(process.progress);
+                process.on_warning =
+                    self.on_warning.clone().or(process.on_warning);
+
+                let merge: MergeResult =
+                    process.run().map_err(PipelineError::Merge)?;
+
+                state.file = merge.out_file.clone();
+
+                result.merge = Some(merge);
+            },
+            | Stage::Compare(process) => {
+                let mut process: Compare = process.clone();
+
+                process.in_dir = process.in_dir.or(state.chunk_dir.clone());
+                process.in_file =
+                    process.in_file.or(state.file.as_deref().map(Arc::from));
+                process.naming = state.naming.clone();
+                process.chunk_size =
+                    state.chunk_size.unwrap_or(process.chunk_size);
+                process.cancellation_token = self
+                    .cancellation_token
+                    .clone()
+                    .or(process.cancellation_token);
+                process.deadline = self.deadline.or(process.deadline);
+                process.progress = self.progress.clone().or(process.progress);
+
+                let compare: CompareResult =
+                    process.run().map_err(PipelineError::Compare)?;
+
+                let matched: bool = compare.is_match();
+
+                result.compare = Some(compare);
+
+                if !matched {
+                    return Err(PipelineError::FileNotMatched);
+                }
+            },
+            | Stage::Cleanup => {
+                let chunk_dir: &Path = state.chunk_dir()?;
+
+                if chunk_dir.exists() {
+                    fs::remove_dir_all(chunk_dir)
+                        .map_err(|_| PipelineError::ChunkDirNotRemoved)?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Check if the pipeline is cancelled or its deadline exceeded.
+    fn check_stop(&self) -> Result<(), PipelineError> {
+        if self.cancellation_token.as_ref().is_some_and(|t| t.is_cancelled()) {
+            return Err(PipelineError::Cancelled);
+        }
+
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            return Err(PipelineError::DeadlineExceeded);
+        }
+
+        Ok(())
+    }
+}
+
+impl PipelineState {
+    /// Get the chunk directory of the pipeline.
+    fn chunk_dir(&self) -> Result<&Path, PipelineError> {
+        self.chunk_dir.as_deref().ok_or(PipelineError::ChunkDirNotSet)
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
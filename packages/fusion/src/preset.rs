@@ -0,0 +1,86 @@
+use crate::chunk::ChunkNaming;
+
+/// Preset to share large files over size-limited services.
+///
+/// The chunks are named after the original file, numbered from `001`,
+/// such as `video.mp4.001`, `video.mp4.002`,
+/// which can be detected by [`Merge::detect_naming`](crate::merge::Merge::detect_naming).
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{preset::Preset, split::Split};
+///
+/// Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("video.mp4"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .preset(Preset::Discord10M)
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Telegram, 2 GB per file.
+    Telegram2G,
+    /// Telegram Premium, 4 GB per file.
+    Telegram4G,
+    /// Discord, 10 MB per file.
+    Discord10M,
+    /// Discord, 25 MB per file.
+    Discord25M,
+    /// Email attachments, 20 MB per file.
+    Email20M,
+    /// FAT32 file systems, 4 GiB minus 1 byte per file.
+    Fat32,
+    /// Custom size limit per file.
+    Custom {
+        /// The maximum size of each chunk in bytes.
+        chunk_size: usize,
+    },
+}
+
+impl Preset {
+    /// Get the name of the preset as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Telegram2G => "telegram_2g",
+            | Self::Telegram4G => "telegram_4g",
+            | Self::Discord10M => "discord_10m",
+            | Self::Discord25M => "discord_25m",
+            | Self::Email20M => "email_20m",
+            | Self::Fat32 => "fat32",
+            | Self::Custom { .. } => "custom",
+        }
+    }
+
+    /// Get the maximum size of each chunk in bytes.
+    ///
+    /// The sizes are in decimal units to stay below the limits.
+    /// On 32-bit targets, the sizes are capped at `usize::MAX`.
+    pub fn chunk_size(&self) -> usize {
+        let size: u64 = match self {
+            | Self::Telegram2G => 2_000_000_000,
+            | Self::Telegram4G => 4_000_000_000,
+            | Self::Discord10M => 10_000_000,
+            | Self::Discord25M => 25_000_000,
+            | Self::Email20M => 20_000_000,
+            | Self::Fat32 => 4 * 1024 * 1024 * 1024 - 1,
+            | Self::Custom { chunk_size } => return *chunk_size,
+        };
+
+        size.min(usize::MAX as u64) as usize
+    }
+
+    /// Get the naming scheme of the chunks of a file.
+    pub fn naming<FileName: AsRef<str>>(
+        &self,
+        file_name: FileName,
+    ) -> ChunkNaming {
+        ChunkNaming::new()
+            .prefix(format!("{}.", file_name.as_ref()))
+            .pad_width(3)
+            .start_index(1)
+    }
+}
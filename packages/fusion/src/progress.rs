@@ -0,0 +1,233 @@
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicU8, AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+/// Phase of a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Phase {
+    /// The process has not started yet.
+    Pending,
+    /// The process is validating its options.
+    Preparing,
+    /// The process is handling the chunks.
+    Running,
+    /// The process has finished successfully.
+    Finished,
+    /// The process has failed or been cancelled.
+    Failed,
+}
+
+impl Phase {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            | 1 => Self::Preparing,
+            | 2 => Self::Running,
+            | 3 => Self::Finished,
+            | 4 => Self::Failed,
+            | _ => Self::Pending,
+        }
+    }
+}
+
+/// Snapshot of the progress of a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressSnapshot {
+    /// The phase of the process.
+    pub phase: Phase,
+    /// The number of bytes processed.
+    pub bytes_processed: u64,
+    /// The number of chunks processed.
+    pub chunks_processed: usize,
+    /// The highest index of the chunks processed.
+    pub chunk_index: Option<usize>,
+}
+
+/// Handle to poll the progress of a running process.
+///
+/// The progress is updated with atomic operations only,
+/// so it can be polled from another thread without blocking the process.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{path::PathBuf, thread};
+///
+/// use filerune_fusion::{
+///     progress::{Progress, ProgressSnapshot},
+///     split::Split,
+/// };
+///
+/// let progress: Progress = Progress::new();
+///
+/// let process: Split = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .progress(progress.clone());
+///
+/// let handle = thread::spawn(move || process.run());
+///
+/// let snapshot: ProgressSnapshot = progress.snapshot();
+///
+/// handle.join().unwrap().unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    inner: Arc<ProgressInner>,
+}
+
+#[derive(Debug)]
+struct ProgressInner {
+    phase: AtomicU8,
+    bytes_processed: AtomicU64,
+    chunks_processed: AtomicUsize,
+    chunk_index: AtomicUsize,
+}
+
+impl Default for ProgressInner {
+    fn default() -> Self {
+        Self {
+            phase: AtomicU8::new(Phase::Pending as u8),
+            bytes_processed: AtomicU64::new(0),
+            chunks_processed: AtomicUsize::new(0),
+            chunk_index: AtomicUsize::new(usize::MAX),
+        }
+    }
+}
+
+impl Progress {
+    /// Create a new progress handle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a snapshot of the current progress.
+    ///
+    /// Each field is read independently,
+    /// so the snapshot may mix values from consecutive updates.
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        let chunk_index: usize = self.inner.chunk_index.load(Ordering::Relaxed);
+
+        ProgressSnapshot {
+            phase: self.phase(),
+            bytes_processed: self.inner.bytes_processed.load(Ordering::Relaxed),
+            chunks_processed: self
+                .inner
+                .chunks_processed
+                .load(Ordering::Relaxed),
+            chunk_index: (chunk_index != usize::MAX).then_some(chunk_index),
+        }
+    }
+
+    /// Get the current phase.
+    pub fn phase(&self) -> Phase {
+        Phase::from_u8(self.inner.phase.load(Ordering::Relaxed))
+    }
+
+    fn set_phase(
+        &self,
+        phase: Phase,
+    ) {
+        self.inner.phase.store(phase as u8, Ordering::Relaxed);
+    }
+}
+
+/// Handler to receive the watermarks of a progressive merge.
+///
+/// A watermark is the length of the output, in bytes, which is written
+/// in order and flushed, so it is safe to read up to it.
+#[derive(Clone)]
+pub struct WatermarkHandler {
+    handler: Arc<dyn Fn(u64) + Send + Sync>,
+}
+
+impl WatermarkHandler {
+    /// Create a new watermark handler.
+    pub fn new<F: Fn(u64) + Send + Sync + 'static>(handler: F) -> Self {
+        Self { handler: Arc::new(handler) }
+    }
+
+    /// Emit a watermark to the handler.
+    pub fn emit(
+        &self,
+        watermark: u64,
+    ) {
+        (self.handler)(watermark)
+    }
+}
+
+impl fmt::Debug for WatermarkHandler {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("WatermarkHandler").finish_non_exhaustive()
+    }
+}
+
+/// Guard to update the progress of a process.
+///
+/// The progress is marked as failed when the guard is dropped
+/// before the process finishes.
+#[derive(Debug)]
+pub(crate) struct ProgressGuard {
+    progress: Option<Progress>,
+}
+
+impl ProgressGuard {
+    /// Start tracking the progress.
+    pub(crate) fn start(progress: Option<&Progress>) -> Self {
+        if let Some(p) = progress {
+            p.inner.bytes_processed.store(0, Ordering::Relaxed);
+            p.inner.chunks_processed.store(0, Ordering::Relaxed);
+            p.inner.chunk_index.store(usize::MAX, Ordering::Relaxed);
+            p.set_phase(Phase::Preparing);
+        }
+
+        Self { progress: progress.cloned() }
+    }
+
+    /// Record a processed chunk.
+    pub(crate) fn advance(
+        &self,
+        index: usize,
+        bytes: usize,
+    ) {
+        if let Some(ref p) = self.progress {
+            p.inner.bytes_processed.fetch_add(bytes as u64, Ordering::Relaxed);
+            p.inner.chunks_processed.fetch_add(1, Ordering::Relaxed);
+
+            // `usize::MAX` is used as the unset value
+            let _ = p.inner.chunk_index.fetch_update(
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+                |current| {
+                    (current == usize::MAX || current < index).then_some(index)
+                },
+            );
+
+            p.set_phase(Phase::Running);
+        }
+    }
+
+    /// Mark the progress as finished.
+    pub(crate) fn finish(&self) {
+        if let Some(ref p) = self.progress {
+            p.set_phase(Phase::Finished);
+        }
+    }
+}
+
+impl Drop for ProgressGuard {
+    fn drop(&mut self) {
+        if let Some(ref p) = self.progress {
+            if p.phase() != Phase::Finished {
+                p.set_phase(Phase::Failed);
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    fmt, fs,
+    io::{self, Read as _, Seek as _},
+    path::Path,
+};
+
+use crate::{
+    merge::{ChunkSlice, Merge, MergeError},
+    sys::read_options,
+};
+
+/// Reader presenting the chunks of a directory as the original file,
+/// without writing anything to disk.
+///
+/// The chunks are found and validated as merged by [`Merge`], with its
+/// naming scheme, format, codec and expected chunks, and opened one at a
+/// time as read. With a chunk or a byte range set, the reader presents
+/// the range only. The chunks with a header or compressed are decoded in
+/// memory, once each time the reader enters them.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{
+///     io::{self, Read as _, Seek as _, SeekFrom},
+///     path::PathBuf,
+/// };
+///
+/// use filerune_fusion::reader::ChunksReader;
+///
+/// let mut reader: ChunksReader =
+///     ChunksReader::open(PathBuf::from("path").join("to").join("dir"))
+///         .unwrap();
+///
+/// // serve the range `bytes=1024-2047` of the file
+/// reader.seek(SeekFrom::Start(1024)).unwrap();
+///
+/// io::copy(&mut reader.take(1024), &mut io::stdout()).unwrap();
+/// ```
+pub struct ChunksReader {
+    process: Merge,
+    slices: ReaderSlices,
+    position: u64,
+    current: Option<OpenSlice>,
+}
+
+/// Slice of a chunk opened at the position of the reader.
+struct OpenSlice {
+    reader: io::Take<Box<dyn io::Read + Send>>,
+    // the position of the end of the slice in the reader
+    end: u64,
+}
+
+/// Run asynchronously with `tokio` feature.
+///
+/// To use it, add the following code to the `Cargo.toml` file:
+///
+/// ```toml
+/// [dependencies]
+/// filerune_fusion = { version = "*", features = ["tokio"] }
+/// ```
+#[cfg(feature = "tokio")]
+pub mod tokio {
+    pub use crate::tokio::reader::AsyncChunksReader;
+}
+
+/// Slices of the chunks presented by a reader, one after another.
+#[derive(Debug)]
+pub(crate) struct ReaderSlices {
+    slices: Vec<ChunkSlice>,
+    // the position of each slice in the reader
+    starts: Vec<u64>,
+    len: u64,
+}
+
+impl ReaderSlices {
+    /// Lay out the slices of the chunks one after another.
+    pub(crate) fn new(slices: Vec<ChunkSlice>) -> Self {
+        let mut starts: Vec<u64> = Vec::with_capacity(slices.len());
+
+        let mut len: u64 = 0;
+
+        for slice in &slices {
+            starts.push(len);
+
+            len += slice.len as u64;
+        }
+
+        Self { slices, starts, len }
+    }
+
+    /// Get the size presented in bytes.
+    pub(crate) fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Get the number of slices.
+    pub(crate) fn count(&self) -> usize {
+        self.slices.len()
+    }
+
+    /// Get the slice holding a position, with the offset of the position
+    /// in the chunk, the length left to read in the slice and the position
+    /// of the end of the slice. Returns `None` at the end.
+    pub(crate) fn at(
+        &self,
+        position: u64,
+    ) -> Option<(&ChunkSlice, u64, u64, u64)> {
+        if position >= self.len {
+            return None;
+        }
+
+        // the last slice starting before the position, skipping the empty
+        let index: usize =
+            self.starts.partition_point(|start| *start <= position) - 1;
+
+        let slice: &ChunkSlice = &self.slices[index];
+
+        let skip: u64 = position - self.starts[index];
+
+        let end: u64 = self.starts[index] + slice.len as u64;
+
+        Some((slice, slice.offset as u64 + skip, end - position, end))
+    }
+
+    /// Get the position to seek to.
+    pub(crate) fn seek(
+        &self,
+        position: u64,
+        pos: io::SeekFrom,
+    ) -> io::Result<u64> {
+        let position: Option<u64> = match pos {
+            | io::SeekFrom::Start(offset) => Some(offset),
+            | io::SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            | io::SeekFrom::Current(offset) => {
+                position.checked_add_signed(offset)
+            },
+        };
+
+        position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })
+    }
+}
+
+impl ChunksReader {
+    /// Open a reader over the chunks of a directory,
+    /// named by their index only.
+    pub fn open<InDir: AsRef<Path>>(in_dir: InDir) -> Result<Self, MergeError> {
+        Self::from_merge(&Merge::new().in_dir(in_dir))
+    }
+
+    /// Open a reader over the chunks of the input directory of a merge
+    /// process, as they would be merged.
+    ///
+    /// The output file and the options to write it are not used.
+    pub fn from_merge(process: &Merge) -> Result<Self, MergeError> {
+        let in_dir: &Path = match process.in_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_dir not exists
+                if !p.exists() {
+                    return Err(MergeError::InDirNotFound);
+                }
+
+                // if in_dir not a directory
+                if !p.is_dir() {
+                    return Err(MergeError::InDirNotDir);
+                }
+
+                p
+            },
+            | None => return Err(MergeError::InDirNotSet),
+        };
+
+        Ok(Self {
+            process: process.clone(),
+            slices: ReaderSlices::new(process.scan_chunks(in_dir, None)?),
+            position: 0,
+            current: None,
+        })
+    }
+
+    /// Get the size of the file presented in bytes.
+    pub fn len(&self) -> u64 {
+        self.slices.len()
+    }
+
+    /// Check if the file presented is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of chunks read from.
+    pub fn total_chunks(&self) -> usize {
+        self.slices.count()
+    }
+
+    /// Open the slice holding the position of the reader,
+    /// `None` at the end.
+    fn open_current(&self) -> io::Result<Option<OpenSlice>> {
+        let (slice, offset, left, end) = match self.slices.at(self.position) {
+            | Some(at) => at,
+            | None => return Ok(None),
+        };
+
+        let reader: Box<dyn io::Read + Send> = match self.process.is_encoded() {
+            | true => {
+                let data: Vec<u8> =
+                    self.process.decode(fs::read(&slice.path)?)?;
+
+                let mut reader: io::Cursor<Vec<u8>> = io::Cursor::new(data);
+
+                reader.set_position(offset);
+
+                Box::new(reader)
+            },
+            | false => {
+                let mut input: fs::File = read_options().open(&slice.path)?;
+
+                input.seek(io::SeekFrom::Start(offset))?;
+
+                Box::new(io::BufReader::with_capacity(
+                    self.process.buffer_capacity,
+                    input,
+                ))
+            },
+        };
+
+        Ok(Some(OpenSlice { reader: reader.take(left), end }))
+    }
+}
+
+impl io::Read for ChunksReader {
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            if self.current.is_none() {
+                self.current = self.open_current()?;
+            }
+
+            let current: &mut OpenSlice = match self.current {
+                | Some(ref mut current) => current,
+                | None => return Ok(0),
+            };
+
+            let read: usize = current.reader.read(buf)?;
+
+            if read > 0 {
+                self.position += read as u64;
+
+                return Ok(read);
+            }
+
+            // the chunk is shorter than when the reader was opened
+            if self.position < current.end {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+
+            self.current = None;
+        }
+    }
+}
+
+impl io::Seek for ChunksReader {
+    fn seek(
+        &mut self,
+        pos: io::SeekFrom,
+    ) -> io::Result<u64> {
+        let position: u64 = self.slices.seek(self.position, pos)?;
+
+        if position != self.position {
+            self.position = position;
+            self.current = None;
+        }
+
+        Ok(position)
+    }
+}
+
+impl fmt::Debug for ChunksReader {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("ChunksReader")
+            .field("in_dir", &self.process.in_dir)
+            .field("total_chunks", &self.slices.count())
+            .field("len", &self.slices.len())
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+/// The age from which a temporary file is considered left by a crashed run.
+pub const STALE_AFTER_DEFAULT: Duration = Duration::from_secs(60 * 60);
+
+/// Report of the recover process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoverReport {
+    /// The temporary files left by crashed runs, removed unless dry run.
+    pub stale_files: Vec<PathBuf>,
+    /// The temporary files recently modified, kept as they may be in use.
+    pub active_files: Vec<PathBuf>,
+    /// The number of bytes of the stale files.
+    pub bytes_reclaimed: u64,
+}
+
+impl RecoverReport {
+    /// Check if no leftover of a crashed run was found.
+    pub fn is_clean(&self) -> bool {
+        self.stale_files.is_empty()
+    }
+}
+
+/// Recover process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoverError {
+    InDirNotFound,
+    InDirNotDir,
+    InDirNotSet,
+    InDirNotRead,
+    FileNotRemoved,
+}
+
+impl RecoverError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::InDirNotFound => "in_dir_not_found",
+            | Self::InDirNotDir => "in_dir_not_dir",
+            | Self::InDirNotSet => "in_dir_not_set",
+            | Self::InDirNotRead => "in_dir_not_read",
+            | Self::FileNotRemoved => "file_not_removed",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::InDirNotFound => "The input directory not found.",
+            | Self::InDirNotDir => "The input directory is not a directory.",
+            | Self::InDirNotSet => "The input directory is not set.",
+            | Self::InDirNotRead => "The input directory could not be read.",
+            | Self::FileNotRemoved => "A stale file could not be removed.",
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Process to clean the leftovers of crashed runs in a directory,
+/// such as a chunk directory or the directory of a merged file.
+///
+/// The temporary files written by atomic runs, named with a `.tmp` suffix,
+/// are removed once they are not modified for the stale age,
+/// while the chunks and other files are kept.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::recover::{Recover, RecoverReport};
+///
+/// let report: RecoverReport = Recover::new()
+///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+///     .dry_run(true)
+///     .run()
+///     .unwrap();
+///
+/// for path in report.stale_files {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Recover {
+    pub in_dir: Option<Arc<Path>>,
+    pub stale_after: Duration,
+    pub dry_run: bool,
+}
+
+impl Recover {
+    /// Create a new recover process.
+    pub fn new() -> Self {
+        Self { in_dir: None, stale_after: STALE_AFTER_DEFAULT, dry_run: false }
+    }
+
+    /// Create a new recover process from an existing one.
+    pub fn from<P: Into<Recover>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the directory to recover.
+    pub fn in_dir<InDir: AsRef<Path>>(
+        mut self,
+        path: InDir,
+    ) -> Self {
+        self.in_dir = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the directory to recover with a shared path.
+    ///
+    /// Unlike [`Recover::in_dir`], an [`Arc<Path>`] is stored without copying.
+    pub fn in_dir_shared<InDir: Into<Arc<Path>>>(
+        mut self,
+        path: InDir,
+    ) -> Self {
+        self.in_dir = Some(path.into());
+        self
+    }
+
+    /// Set the age from which a temporary file is considered stale.
+    ///
+    /// Temporary files modified more recently may belong to a running
+    /// process, so they are reported but kept.
+    ///
+    /// By default, it follows the [`STALE_AFTER_DEFAULT`].
+    pub fn stale_after(
+        mut self,
+        age: Duration,
+    ) -> Self {
+        self.stale_after = age;
+        self
+    }
+
+    /// Set whether to only report the leftovers, without removing them.
+    ///
+    /// By default, it is `false`.
+    pub fn dry_run(
+        mut self,
+        dry_run: bool,
+    ) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Run the recover process.
+    pub fn run(&self) -> Result<RecoverReport, RecoverError> {
+        let in_dir: &Path = match self.in_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_dir not exists
+                if !p.exists() {
+                    return Err(RecoverError::InDirNotFound);
+                }
+
+                // if in_dir not a directory
+                if !p.is_dir() {
+                    return Err(RecoverError::InDirNotDir);
+                }
+
+                p
+            },
+            | None => return Err(RecoverError::InDirNotSet),
+        };
+
+        let read_dir: fs::ReadDir =
+            fs::read_dir(in_dir).map_err(|_| RecoverError::InDirNotRead)?;
+
+        let now: SystemTime = SystemTime::now();
+
+        let mut report: RecoverReport = RecoverReport::default();
+
+        for entry in read_dir.filter_map(Result::ok) {
+            let path: PathBuf = entry.path();
+
+            if !is_temp_file(&path) {
+                continue;
+            }
+
+            let metadata: fs::Metadata = match fs::metadata(&path) {
+                | Ok(metadata) if metadata.is_file() => metadata,
+                | _ => continue,
+            };
+
+            // a file without modification time is considered stale
+            let age: Duration = metadata
+                .modified()
+                .ok()
+                .and_then(|m| now.duration_since(m).ok())
+                .unwrap_or(Duration::MAX);
+
+            if age < self.stale_after {
+                report.active_files.push(path);
+                continue;
+            }
+
+            if !self.dry_run {
+                fs::remove_file(&path)
+                    .map_err(|_| RecoverError::FileNotRemoved)?;
+            }
+
+            report.bytes_reclaimed += metadata.len();
+            report.stale_files.push(path);
+        }
+
+        report.stale_files.sort();
+        report.active_files.sort();
+
+        Ok(report)
+    }
+}
+
+impl Default for Recover {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clean the leftovers of crashed runs in a directory,
+/// with the default options of [`Recover`].
+pub fn recover<Dir: AsRef<Path>>(
+    dir: Dir
+) -> Result<RecoverReport, RecoverError> {
+    Recover::new().in_dir(dir).run()
+}
+
+/// Check if the path is of a temporary file written by an atomic run.
+fn is_temp_file(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "tmp")
+}
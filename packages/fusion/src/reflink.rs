@@ -0,0 +1,47 @@
+/// Policy to clone the chunks with reflinks, sharing the extents on disk.
+///
+/// On file systems with the copy-on-write support, such as Btrfs and XFS,
+/// the chunks share the extents with the input file when split,
+/// and the output file shares the extents with the chunks when merged.
+/// The data is not copied, so the process takes almost no time and space.
+///
+/// Reflinks are only supported on Linux, with `FICLONERANGE`. On macOS,
+/// APFS only clones whole files with `clonefile`, not the ranges of a
+/// chunk, so the chunks are never cloned there, nor on other platforms:
+/// [`ReflinkPolicy::Auto`] falls back to a copy with a
+/// [`Warning::ReflinkFallback`](crate::warning::Warning::ReflinkFallback),
+/// and [`ReflinkPolicy::Always`] fails.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{reflink::ReflinkPolicy, split::Split};
+///
+/// let process: Split = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .reflink(ReflinkPolicy::Auto);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReflinkPolicy {
+    /// Clone with reflinks if supported, copy the data otherwise.
+    Auto,
+    /// Always clone with reflinks, fail if not supported.
+    Always,
+    /// Never clone with reflinks, always copy the data.
+    #[default]
+    Never,
+}
+
+impl ReflinkPolicy {
+    /// Get the name of the policy as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Auto => "auto",
+            | Self::Always => "always",
+            | Self::Never => "never",
+        }
+    }
+}
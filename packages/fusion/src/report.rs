@@ -0,0 +1,594 @@
+use std::{collections::BTreeMap, fmt::Write as _, path::Path, time::Duration};
+
+use crate::{
+    check::{Check, CheckError, CheckReport},
+    compare::{Compare, CompareError, CompareResult},
+    merge::{DuplicatePolicy, Merge, MergeError, MergeResult, OutFilePolicy},
+    split::{
+        OverwritePolicy, SourceChangePolicy, Split, SplitError, SplitResult,
+    },
+};
+
+/// The version of the report schema, increased on breaking changes.
+pub const REPORT_SCHEMA_VERSION: u64 = 1;
+
+/// Operation of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Split,
+    Check,
+    Merge,
+    Compare,
+}
+
+impl Operation {
+    /// Get the name of the operation as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Split => "split",
+            | Self::Check => "check",
+            | Self::Merge => "merge",
+            | Self::Compare => "compare",
+        }
+    }
+}
+
+/// Value of an option or a result in a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportValue {
+    Null,
+    Bool(bool),
+    Number(u64),
+    Text(String),
+    List(Vec<ReportValue>),
+}
+
+impl From<bool> for ReportValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<usize> for ReportValue {
+    fn from(value: usize) -> Self {
+        Self::Number(value as u64)
+    }
+}
+
+impl From<u64> for ReportValue {
+    fn from(value: u64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<&str> for ReportValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<&Path> for ReportValue {
+    fn from(value: &Path) -> Self {
+        Self::Text(value.to_string_lossy().into_owned())
+    }
+}
+
+impl<T: Into<ReportValue>> From<Option<T>> for ReportValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl<T: Into<ReportValue>> From<Vec<T>> for ReportValue {
+    fn from(value: Vec<T>) -> Self {
+        Self::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Error of a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportError {
+    /// The code of the error, such as `in_file_not_found`.
+    pub code: String,
+    /// The message of the error.
+    pub message: String,
+}
+
+/// Statistics of a report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReportStats {
+    /// The time taken by the run, in milliseconds.
+    pub duration_ms: u64,
+    /// The number of bytes processed.
+    pub bytes: u64,
+    /// The number of chunks processed.
+    pub chunks: u64,
+}
+
+/// Machine-readable report of a run, with a stable schema for all processes.
+///
+/// The JSON form from [`Report::to_json`] has the following fields:
+///
+/// - `schema_version`: the [`REPORT_SCHEMA_VERSION`].
+/// - `operation`: `split`, `check`, `merge` or `compare`.
+/// - `success`: whether the run succeeded.
+/// - `options`: the options of the process.
+/// - `result`: the result of the run, `null` if failed.
+/// - `error`: the `code` and the `message` of the error, `null` if succeeded.
+/// - `stats`: the `duration_ms`, the `bytes` and the `chunks` processed.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::split::Split;
+///
+/// let json: String = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .report_json();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// The operation of the run.
+    pub operation: Operation,
+    /// The options of the process.
+    pub options: BTreeMap<String, ReportValue>,
+    /// The result of the run, `None` if failed.
+    pub result: Option<BTreeMap<String, ReportValue>>,
+    /// The error of the run, `None` if succeeded.
+    pub error: Option<ReportError>,
+    /// The statistics of the run.
+    pub stats: ReportStats,
+}
+
+impl Report {
+    /// Create a new report of a run.
+    fn new<const N: usize>(
+        operation: Operation,
+        options: [(&str, ReportValue); N],
+        duration: Duration,
+    ) -> Self {
+        Self {
+            operation,
+            options: options
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+            result: None,
+            error: None,
+            stats: ReportStats {
+                duration_ms: duration.as_millis() as u64,
+                ..ReportStats::default()
+            },
+        }
+    }
+
+    /// Set the result of the run.
+    fn succeed<const N: usize>(
+        mut self,
+        result: [(&str, ReportValue); N],
+        bytes: u64,
+        chunks: usize,
+    ) -> Self {
+        self.result = Some(
+            result
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        );
+        self.stats.bytes = bytes;
+        self.stats.chunks = chunks as u64;
+        self
+    }
+
+    /// Set the error of the run.
+    fn fail(
+        mut self,
+        code: &str,
+        message: &str,
+    ) -> Self {
+        self.error = Some(ReportError {
+            code: code.to_string(),
+            message: message.to_string(),
+        });
+        self
+    }
+
+    /// Create a report of a split run.
+    pub fn split(
+        process: &Split,
+        result: &Result<SplitResult, SplitError>,
+        duration: Duration,
+    ) -> Self {
+        let report: Self = Self::new(
+            Operation::Split,
+            [
+                ("in_file", process.in_file.as_deref().into()),
+                #[cfg(feature = "reqwest")]
+                ("in_url", process.in_url.as_deref().into()),
+                ("out_dir", process.out_dir.as_deref().into()),
+                ("chunk_size", process.chunk_size.into()),
+                ("chunk_schedule", process.chunk_schedule.clone().into()),
+                ("strategy", process.strategy.as_str().into()),
+                ("format", process.format.as_str().into()),
+                ("total_chunks", process.total_chunks.into()),
+                ("align", process.align.into()),
+                ("buffer_capacity", process.buffer_capacity.into()),
+                ("threads", process.threads.into()),
+                ("resume", process.resume.into()),
+                (
+                    "overwrite",
+                    match process.overwrite {
+                        | OverwritePolicy::Fail => "fail",
+                        | OverwritePolicy::Clean => "clean",
+                        | OverwritePolicy::Reuse => "reuse",
+                    }
+                    .into(),
+                ),
+                (
+                    "source_change",
+                    match process.source_change {
+                        | SourceChangePolicy::Fail => "fail",
+                        | SourceChangePolicy::Retry(_) => "retry",
+                        | SourceChangePolicy::Warn => "warn",
+                    }
+                    .into(),
+                ),
+                ("remove_source", process.remove_source.into()),
+                ("atomic", process.atomic.into()),
+                ("sync", process.sync.as_str().into()),
+                ("reflink", process.reflink.as_str().into()),
+                #[cfg(feature = "compression")]
+                (
+                    "compression",
+                    process.compression.as_ref().map(|c| c.as_str()).into(),
+                ),
+                ("parity_chunks", process.parity_chunks.into()),
+                ("par2_recovery", process.par2_recovery.into()),
+                ("bao", process.bao.into()),
+                ("check_space", process.check_space.into()),
+                ("space_reserve", process.space_reserve.into()),
+                ("lock", process.lock.into()),
+                ("verify_writes", process.verify_writes.into()),
+                ("sparse", process.sparse.into()),
+                ("preserve_metadata", process.preserve_metadata.into()),
+                ("strict", process.strict.into()),
+            ],
+            duration,
+        );
+
+        match result {
+            | Ok(result) => report.succeed(
+                [
+                    ("file_size", result.file_size.into()),
+                    ("total_chunks", result.total_chunks.into()),
+                    ("chunk_size", result.chunk_size.into()),
+                    ("chunks_resumed", result.chunks_resumed.into()),
+                    (
+                        "bao_hash",
+                        result
+                            .bao_hash
+                            .as_ref()
+                            .map(|d| ReportValue::Text(d.to_hex()))
+                            .into(),
+                    ),
+                ],
+                result.file_size,
+                result.total_chunks,
+            ),
+            | Err(error) => report.fail(error.as_code(), error.as_message()),
+        }
+    }
+
+    /// Create a report of a check run.
+    pub fn check(
+        process: &Check,
+        result: &Result<CheckReport, CheckError>,
+        duration: Duration,
+    ) -> Self {
+        let report: Self = Self::new(
+            Operation::Check,
+            [
+                ("in_dir", process.in_dir.as_deref().into()),
+                ("file_size", process.file_size.into()),
+                ("total_chunks", process.total_chunks.into()),
+                ("concurrency", process.concurrency.into()),
+                ("format", process.format.as_str().into()),
+                (
+                    "hash_algorithm",
+                    process.hash_algorithm.as_ref().map(|a| a.as_str()).into(),
+                ),
+                ("chunk_sizes", process.chunk_sizes.clone().into()),
+                #[cfg(feature = "compression")]
+                (
+                    "compression",
+                    process.compression.as_ref().map(|c| c.as_str()).into(),
+                ),
+                ("repair", process.repair.into()),
+                ("strict", process.strict.into()),
+            ],
+            duration,
+        );
+
+        match result {
+            | Ok(result) => {
+                let found: usize =
+                    result.chunks.iter().filter(|c| c.found).count();
+
+                report.succeed(
+                    [
+                        ("file_size", result.file_size.into()),
+                        ("actual_size", result.actual_size.into()),
+                        ("total_chunks", result.chunks.len().into()),
+                        ("missing", result.missing().into()),
+                        ("mismatched", result.mismatched().into()),
+                        (
+                            "aggregate_hash",
+                            result
+                                .aggregate_hash
+                                .as_ref()
+                                .map(|d| d.to_hex())
+                                .as_deref()
+                                .into(),
+                        ),
+                    ],
+                    result.actual_size,
+                    found,
+                )
+            },
+            | Err(error) => report.fail(error.as_code(), error.as_message()),
+        }
+    }
+
+    /// Create a report of a merge run.
+    pub fn merge(
+        process: &Merge,
+        result: &Result<MergeResult, MergeError>,
+        duration: Duration,
+    ) -> Self {
+        fn range<T: Copy + Into<ReportValue>>(
+            r: &Option<std::ops::Range<T>>
+        ) -> ReportValue {
+            r.as_ref().map(|r| vec![r.start, r.end]).into()
+        }
+
+        let report: Self = Self::new(
+            Operation::Merge,
+            [
+                ("in_dir", process.in_dir.as_deref().into()),
+                ("out_file", process.out_file.as_deref().into()),
+                ("buffer_capacity", process.buffer_capacity.into()),
+                ("detect_naming", process.detect_naming.into()),
+                ("format", process.format.as_str().into()),
+                (
+                    "duplicate_policy",
+                    match process.duplicate_policy {
+                        | DuplicatePolicy::Fail => "fail",
+                        | DuplicatePolicy::PreferLarger => "prefer_larger",
+                        | DuplicatePolicy::PreferNewer => "prefer_newer",
+                    }
+                    .into(),
+                ),
+                ("chunks", range(&process.chunk_range)),
+                ("byte_range", range(&process.byte_range)),
+                ("resume", process.resume.into()),
+                (
+                    "overwrite",
+                    match process.overwrite {
+                        | OutFilePolicy::Fail => "fail",
+                        | OutFilePolicy::Overwrite => "overwrite",
+                        | OutFilePolicy::RenameExisting => "rename_existing",
+                    }
+                    .into(),
+                ),
+                ("atomic", process.atomic.into()),
+                ("sync", process.sync.as_str().into()),
+                ("reflink", process.reflink.as_str().into()),
+                ("preallocate", process.preallocate.into()),
+                ("check_space", process.check_space.into()),
+                ("space_reserve", process.space_reserve.into()),
+                ("lock", process.lock.into()),
+                ("sparse", process.sparse.into()),
+                ("preserve_metadata", process.preserve_metadata.into()),
+                ("progressive", process.progressive.into()),
+                #[cfg(feature = "compression")]
+                (
+                    "compression",
+                    process.compression.as_ref().map(|c| c.as_str()).into(),
+                ),
+                ("repair", process.repair.into()),
+                ("read_only_source", process.read_only_source.into()),
+                (
+                    "expected_hash",
+                    process
+                        .expected_hash
+                        .as_ref()
+                        .map(|d| ReportValue::Text(d.to_hex()))
+                        .into(),
+                ),
+                (
+                    "bao",
+                    process
+                        .bao
+                        .as_ref()
+                        .map(|d| ReportValue::Text(d.to_hex()))
+                        .into(),
+                ),
+                ("expected_chunks", process.expected_chunks.into()),
+                ("expected_size", process.expected_size.into()),
+                ("strict", process.strict.into()),
+            ],
+            duration,
+        );
+
+        match result {
+            | Ok(result) => report.succeed(
+                [
+                    ("bytes_written", result.bytes_written.into()),
+                    ("chunks_merged", result.chunks_merged.into()),
+                    ("bytes_resumed", result.bytes_resumed.into()),
+                    ("out_file", result.out_file.as_deref().into()),
+                ],
+                result.bytes_written,
+                result.chunks_merged,
+            ),
+            | Err(error) => report.fail(error.as_code(), error.as_message()),
+        }
+    }
+
+    /// Create a report of a compare run.
+    pub fn compare(
+        process: &Compare,
+        result: &Result<CompareResult, CompareError>,
+        duration: Duration,
+    ) -> Self {
+        let report: Self = Self::new(
+            Operation::Compare,
+            [
+                ("in_file", process.in_file.as_deref().into()),
+                ("in_dir", process.in_dir.as_deref().into()),
+                ("chunk_size", process.chunk_size.into()),
+            ],
+            duration,
+        );
+
+        match result {
+            | Ok(result) => report.succeed(
+                [
+                    ("file_size", result.file_size.into()),
+                    ("total_chunks", result.total_chunks.into()),
+                    ("is_match", result.is_match().into()),
+                    ("mismatched", result.mismatched.clone().into()),
+                    ("missing", result.missing.clone().into()),
+                    ("extra", result.extra.clone().into()),
+                ],
+                result.file_size,
+                result.total_chunks,
+            ),
+            | Err(error) => report.fail(error.as_code(), error.as_message()),
+        }
+    }
+
+    /// Check if the run succeeded.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Get the report as a JSON object.
+    pub fn to_json(&self) -> String {
+        let mut json: String = String::new();
+
+        let _ = write!(
+            json,
+            "{{\"schema_version\":{},\"operation\":\"{}\",\"success\":{},",
+            REPORT_SCHEMA_VERSION,
+            self.operation.as_str(),
+            self.is_success()
+        );
+
+        json.push_str("\"options\":");
+        write_map(&mut json, &self.options);
+
+        json.push_str(",\"result\":");
+        match self.result {
+            | Some(ref result) => write_map(&mut json, result),
+            | None => json.push_str("null"),
+        }
+
+        json.push_str(",\"error\":");
+        match self.error {
+            | Some(ref error) => {
+                json.push_str("{\"code\":");
+                write_str(&mut json, &error.code);
+                json.push_str(",\"message\":");
+                write_str(&mut json, &error.message);
+                json.push('}');
+            },
+            | None => json.push_str("null"),
+        }
+
+        let _ = write!(
+            json,
+            ",\"stats\":{{\"duration_ms\":{},\"bytes\":{},\"chunks\":{}}}}}",
+            self.stats.duration_ms, self.stats.bytes, self.stats.chunks
+        );
+
+        json
+    }
+}
+
+/// Write a map as a JSON object.
+fn write_map(
+    json: &mut String,
+    map: &BTreeMap<String, ReportValue>,
+) {
+    json.push('{');
+
+    for (i, (key, value)) in map.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+
+        write_str(json, key);
+        json.push(':');
+        write_value(json, value);
+    }
+
+    json.push('}');
+}
+
+/// Write a value as JSON.
+fn write_value(
+    json: &mut String,
+    value: &ReportValue,
+) {
+    match value {
+        | ReportValue::Null => json.push_str("null"),
+        | ReportValue::Bool(b) => {
+            json.push_str(if *b { "true" } else { "false" })
+        },
+        | ReportValue::Number(n) => {
+            let _ = write!(json, "{n}");
+        },
+        | ReportValue::Text(s) => write_str(json, s),
+        | ReportValue::List(values) => {
+            json.push('[');
+
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+
+                write_value(json, value);
+            }
+
+            json.push(']');
+        },
+    }
+}
+
+/// Write a string as a JSON string with escapes.
+fn write_str(
+    json: &mut String,
+    s: &str,
+) {
+    json.push('"');
+
+    for c in s.chars() {
+        match c {
+            | '"' => json.push_str("\\\""),
+            | '\\' => json.push_str("\\\\"),
+            | '\n' => json.push_str("\\n"),
+            | '\r' => json.push_str("\\r"),
+            | '\t' => json.push_str("\\t"),
+            | c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            },
+            | c => json.push(c),
+        }
+    }
+
+    json.push('"');
+}
@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+
+use futures::stream::{self, StreamExt as _, TryStreamExt as _};
+
+use crate::{
+    BUFFER_CAPACITY_DEFAULT,
+    check::{Check, CheckError, CheckReport, ChunkStatus},
+    hash::{Digest, Hasher},
+    header::ChunkHeader,
+    progress::ProgressGuard,
+    runtime::{Reader as _, Runtime, read_to_end},
+    warning::Warning,
+};
+
+/// Run the check process.
+pub(crate) async fn run<Rt: Runtime>(
+    process: &Check
+) -> Result<CheckReport, CheckError> {
+    let progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let report: CheckReport = check_chunks::<Rt>(process, &progress).await?;
+
+    report.validate()?;
+
+    progress.finish();
+
+    Ok(report)
+}
+
+/// Run the check process without failing on missing chunks
+/// or mismatched size, to get the status of each chunk.
+pub(crate) async fn report<Rt: Runtime>(
+    process: &Check
+) -> Result<CheckReport, CheckError> {
+    let progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let report: CheckReport = check_chunks::<Rt>(process, &progress).await?;
+
+    progress.finish();
+
+    Ok(report)
+}
+
+/// Collect the status of each chunk.
+async fn check_chunks<Rt: Runtime>(
+    process: &Check,
+    progress: &ProgressGuard,
+) -> Result<CheckReport, CheckError> {
+    let in_dir: &Path = match process.in_dir {
+        | Some(ref p) => {
+            let p: &Path = p.as_ref();
+
+            // if in_dir not exists
+            if !p.exists() {
+                return Err(CheckError::InDirNotFound);
+            }
+
+            // if in_dir not a directory
+            if !p.is_dir() {
+                return Err(CheckError::InDirNotDir);
+            }
+
+            p
+        },
+        | None => return Err(CheckError::InDirNotSet),
+    };
+
+    // the headers are read off the executor
+    let located: Vec<(ChunkHeader, PathBuf)> = {
+        let process: Check = process.clone();
+        let in_dir: PathBuf = in_dir.to_path_buf();
+
+        Rt::spawn_blocking(move || process.locate_chunks(&in_dir)).await?
+    };
+
+    let set: Option<&ChunkHeader> = located.first().map(|(h, _)| h);
+
+    let file_size: u64 = process
+        .file_size
+        .or(set.and_then(|h| h.file_size))
+        .ok_or(CheckError::FileSizeNotSet)?;
+
+    let total_chunks: usize = process
+        .total_chunks
+        .or(set.and_then(|h| h.total_chunks))
+        .ok_or(CheckError::TotalChunksNotSet)?;
+
+    // the chunks are repaired off the executor
+    {
+        let process: Check = process.clone();
+        let in_dir: PathBuf = in_dir.to_path_buf();
+
+        Rt::spawn_blocking(move || process.repair_chunks(&in_dir)).await?;
+    }
+
+    let located: &[(ChunkHeader, PathBuf)] = &located;
+
+    let mut chunks: Vec<ChunkStatus> = stream::iter(0..total_chunks)
+        .map(|i| async move {
+            if process.is_cancelled() {
+                return Err(CheckError::Cancelled);
+            }
+
+            if process.is_expired() {
+                return Err(CheckError::DeadlineExceeded);
+            }
+
+            let target_file: PathBuf = process.chunk_path(in_dir, located, i);
+
+            let stat: Option<(usize, Option<Digest>)> =
+                stat_chunk::<Rt>(process, &target_file).await?;
+
+            if let Some((size, _)) = stat {
+                progress.advance(i, size);
+            }
+
+            Ok(match stat {
+                | Some((size, hash)) => ChunkStatus {
+                    index: i,
+                    path: target_file,
+                    size,
+                    found: true,
+                    hash,
+                    expected_size: process.expected_size(i),
+                },
+                | None => ChunkStatus {
+                    index: i,
+                    path: target_file,
+                    size: 0,
+                    found: false,
+                    hash: None,
+                    expected_size: process.expected_size(i),
+                },
+            })
+        })
+        .buffer_unordered(process.concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    chunks.sort_unstable_by_key(|c| c.index);
+
+    let actual_size: u64 = chunks.iter().map(|c| c.size as u64).sum();
+
+    let aggregate_hash: Option<Digest> = process
+        .hash_algorithm
+        .map(|algorithm| CheckReport::aggregate(algorithm, &chunks));
+
+    Ok(CheckReport { file_size, actual_size, chunks, aggregate_hash })
+}
+
+/// Get the size and the digest of a chunk, `None` if the chunk is missing.
+async fn stat_chunk<Rt: Runtime>(
+    process: &Check,
+    target_file: &Path,
+) -> Result<Option<(usize, Option<Digest>)>, CheckError> {
+    let mut file: Rt::File = match Rt::open(target_file).await {
+        | Ok(f) => f,
+        | Err(_) => return Ok(None),
+    };
+
+    let metadata: std::fs::Metadata = Rt::file_metadata(&file)
+        .await
+        .map_err(|_| CheckError::InFileNotRead)?;
+
+    if !metadata.is_file() {
+        process.warn(Warning::IgnoredEntry(target_file.to_path_buf()))?;
+
+        return Ok(None);
+    }
+
+    // the digest is of the decoded content
+    if process.is_encoded() {
+        let data: Vec<u8> = read_to_end(&mut file)
+            .await
+            .map_err(|_| CheckError::InFileNotRead)?;
+
+        let data: Vec<u8> = match process.strip_header(target_file, data)? {
+            | Some(data) => {
+                process.decode(data).map_err(|_| CheckError::InFileNotRead)?
+            },
+            | None => return Ok(None),
+        };
+
+        let hash: Option<Digest> =
+            process.hash_algorithm.map(|a| a.digest(&data));
+
+        return Ok(Some((data.len(), hash)));
+    }
+
+    let hash: Option<Digest> = match process.hash_algorithm {
+        | Some(algorithm) => {
+            let mut hasher: Hasher = algorithm.hasher();
+
+            let mut buffer: Vec<u8> = vec![0; BUFFER_CAPACITY_DEFAULT];
+
+            loop {
+                let read: usize = file
+                    .read(&mut buffer)
+                    .await
+                    .map_err(|_| CheckError::InFileNotRead)?;
+
+                if read == 0 {
+                    break;
+                }
+
+                hasher.update(&buffer[..read]);
+            }
+
+            Some(hasher.finalize())
+        },
+        | None => None,
+    };
+
+    // a chunk larger than the address space could not be merged
+    let len: usize = usize::try_from(metadata.len())
+        .map_err(|_| CheckError::InFileNotRead)?;
+
+    Ok(Some((len, hash)))
+}
@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    compare::{Compare, CompareError, CompareResult},
+    progress::ProgressGuard,
+    runtime::{Runtime, read_full},
+};
+
+/// Run the compare process.
+pub(crate) async fn run<Rt: Runtime>(
+    process: &Compare
+) -> Result<CompareResult, CompareError> {
+    let progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let in_file: &Path = match process.in_file {
+        | Some(ref p) => {
+            let p: &Path = p.as_ref();
+
+            // if in_file not exists
+            if !p.exists() {
+                return Err(CompareError::InFileNotFound);
+            }
+
+            // if in_file not a file
+            if !p.is_file() {
+                return Err(CompareError::InFileNotFile);
+            }
+
+            p
+        },
+        | None => return Err(CompareError::InFileNotSet),
+    };
+
+    let in_dir: &Path = match process.in_dir {
+        | Some(ref p) => {
+            let p: &Path = p.as_ref();
+
+            // if in_dir not exists
+            if !p.exists() {
+                return Err(CompareError::InDirNotFound);
+            }
+
+            // if in_dir not a directory
+            if !p.is_dir() {
+                return Err(CompareError::InDirNotDir);
+            }
+
+            p
+        },
+        | None => return Err(CompareError::InDirNotSet),
+    };
+
+    let chunk_size: usize = process.chunk_size;
+
+    let mut input: Rt::File =
+        Rt::open(in_file).await.map_err(|_| CompareError::InFileNotOpened)?;
+
+    let mut buffer: Vec<u8> = vec![0; chunk_size];
+
+    // one more byte to detect a larger chunk
+    let mut chunk_buffer: Vec<u8> = vec![0; chunk_size + 1];
+
+    let mut result: CompareResult = CompareResult::default();
+
+    loop {
+        if process.is_cancelled() {
+            return Err(CompareError::Cancelled);
+        }
+
+        if process.is_expired() {
+            return Err(CompareError::DeadlineExceeded);
+        }
+
+        let size: usize = read_full(&mut input, &mut buffer)
+            .await
+            .map_err(|_| CompareError::InFileNotRead)?;
+
+        if size == 0 {
+            break;
+        }
+
+        let index: usize = result.total_chunks;
+
+        let chunk_path: PathBuf = in_dir.join(process.naming.file_name(index));
+
+        if chunk_path.is_file() {
+            let mut chunk: Rt::File = Rt::open(&chunk_path)
+                .await
+                .map_err(|_| CompareError::ChunkNotRead)?;
+
+            let chunk_len: usize = read_full(&mut chunk, &mut chunk_buffer)
+                .await
+                .map_err(|_| CompareError::ChunkNotRead)?;
+
+            if buffer[..size] != chunk_buffer[..chunk_len] {
+                result.mismatched.push(index);
+            }
+        } else {
+            result.missing.push(index);
+        }
+
+        progress.advance(index, size);
+
+        result.file_size += size as u64;
+
+        result.total_chunks += 1;
+    }
+
+    // chunks beyond the end of the input file
+    let entries: Vec<PathBuf> =
+        Rt::read_dir(in_dir).await.map_err(|_| CompareError::InDirNotRead)?;
+
+    for path in entries {
+        if let Some(index) = process.naming.index_of(&path) {
+            if index >= result.total_chunks && path.is_file() {
+                result.extra.push(index);
+            }
+        }
+    }
+
+    result.extra.sort_unstable();
+
+    progress.finish();
+
+    Ok(result)
+}
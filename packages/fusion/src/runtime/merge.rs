@@ -0,0 +1,481 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{
+    chunk::{ChunkInfo, temp_path},
+    lock::FileLock,
+    merge::{
+        ChunkSlice, Merge, MergeError, MergeResult, OutFilePolicy,
+        OutputVerifier, RepairDir, SparseHoles, backup_path, no_chunk, out_dir,
+        resume_point,
+    },
+    progress::ProgressGuard,
+    runtime::{Reader as _, Runtime, Writer as _, read_to_end},
+    warning::Warning,
+};
+
+/// Run the merge process.
+pub(crate) async fn run<Rt: Runtime>(
+    process: &Merge
+) -> Result<MergeResult, MergeError> {
+    let _progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let in_dir: &Path = in_dir(process)?;
+
+    let _locks: Vec<FileLock> =
+        process.lock_paths(in_dir, process.out_file.as_deref())?;
+
+    let out_file: &Path = match process.out_file {
+        | Some(ref p) => {
+            let p: &Path = p.as_ref();
+
+            // if out_file exists and not resumed
+            if p.exists() && !(process.resume && p.is_file()) {
+                match process.overwrite {
+                    | OutFilePolicy::Fail => {
+                        return Err(MergeError::OutFileExists);
+                    },
+                    | OutFilePolicy::Overwrite => {
+                        if p.is_dir() {
+                            return Err(MergeError::OutFileNotFile);
+                        }
+
+                        Rt::remove_file(p)
+                            .await
+                            .map_err(|_| MergeError::OutFileNotRemoved)?;
+                    },
+                    | OutFilePolicy::RenameExisting => {
+                        Rt::rename(p, &backup_path(p))
+                            .await
+                            .map_err(|_| MergeError::OutFileNotRenamed)?;
+                    },
+                }
+            }
+
+            // create outpath
+            if let Some(parent) = p.parent() {
+                Rt::create_dir_all(parent)
+                    .await
+                    .map_err(|_| MergeError::OutDirNotCreated)?;
+            }
+
+            p
+        },
+        | None => return Err(MergeError::OutFileNotSet),
+    };
+
+    // write to a temporary file to rename, if atomic
+    let write_path: PathBuf = match process.is_atomic() {
+        | true => temp_path(out_file),
+        | false => out_file.to_path_buf(),
+    };
+
+    let mut output: Rt::File = Rt::create(&write_path, !process.resume)
+        .await
+        .map_err(|_| MergeError::OutFileNotOpened)?;
+
+    let resume_from: u64 = match process.resume {
+        | true => Rt::file_metadata(&output)
+            .await
+            .map_err(|_| MergeError::OutFileNotOpened)?
+            .len(),
+        | false => 0,
+    };
+
+    let result: Result<MergeResult, MergeError> =
+        merge_chunks::<Rt>(process, in_dir, &mut output, resume_from).await;
+
+    drop(output);
+
+    match result {
+        | Ok(result) => {
+            if process.is_atomic() {
+                Rt::rename(&write_path, out_file)
+                    .await
+                    .map_err(|_| MergeError::OutFileNotWritten)?;
+            }
+
+            process.apply_metadata(in_dir, out_file)?;
+
+            if let Some(parent) = out_dir(out_file) {
+                Rt::sync_dir(process.sync, parent)
+                    .await
+                    .map_err(|_| MergeError::OutFileNotWritten)?;
+            }
+
+            Ok(MergeResult { out_file: Some(out_file.to_path_buf()), ..result })
+        },
+        | Err(error) => {
+            // keep the output to be resumed, but not a corrupt one
+            if !process.resume
+                && error != MergeError::DeadlineExceeded
+                && (process.is_atomic()
+                    || error == MergeError::Cancelled
+                    || error == MergeError::HashMismatch)
+            {
+                let _ = Rt::remove_file(&write_path).await;
+            }
+
+            Err(error)
+        },
+    }
+}
+
+/// Run the merge process with an opened file as the output.
+pub(crate) async fn run_to_file<Rt: Runtime>(
+    process: &Merge,
+    mut file: Rt::File,
+) -> Result<MergeResult, MergeError> {
+    let _progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let in_dir: &Path = in_dir(process)?;
+
+    let _locks: Vec<FileLock> = process.lock_paths(in_dir, None)?;
+
+    match merge_chunks::<Rt>(process, in_dir, &mut file, 0).await {
+        | Err(MergeError::Cancelled) => {
+            let _ = Rt::set_len(&file, 0).await;
+
+            Err(MergeError::Cancelled)
+        },
+        | result => result,
+    }
+}
+
+/// Get the input directory of the process.
+pub(crate) fn in_dir(process: &Merge) -> Result<&Path, MergeError> {
+    match process.in_dir {
+        | Some(ref p) => {
+            let p: &Path = p.as_ref();
+
+            // if in_dir not exists
+            if !p.exists() {
+                return Err(MergeError::InDirNotFound);
+            }
+
+            // if in_dir not a directory
+            if !p.is_dir() {
+                return Err(MergeError::InDirNotDir);
+            }
+
+            Ok(p)
+        },
+        | None => Err(MergeError::InDirNotSet),
+    }
+}
+
+/// Merge the chunks from the input directory to the output.
+///
+/// The output is resumed from `resume_from` bytes already written.
+async fn merge_chunks<Rt: Runtime>(
+    process: &Merge,
+    in_dir: &Path,
+    output: &mut Rt::File,
+    resume_from: u64,
+) -> Result<MergeResult, MergeError> {
+    let progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let started: Instant = Instant::now();
+
+    let repair_dir: Option<RepairDir> = process.repair_chunks(in_dir)?;
+
+    let mut slices: Vec<ChunkSlice> =
+        scan_chunks::<Rt>(process, in_dir, repair_dir.as_ref()).await?;
+
+    let mut bytes_resumed: u64 = 0;
+
+    let mut verifier: OutputVerifier =
+        process.output_verifier(Some(in_dir), &slices)?;
+
+    if resume_from > 0 {
+        let (skipped, position) = resume_point(&slices, resume_from);
+
+        // drop the incomplete chunk
+        Rt::set_len(output, position)
+            .await
+            .map_err(|_| MergeError::OutFileNotWritten)?;
+
+        Rt::seek(output, position)
+            .await
+            .map_err(|_| MergeError::OutFileNotWritten)?;
+
+        // the part resumed is hashed from the chunks it was merged from
+        process.verify_slices(&slices[..skipped], &mut verifier)?;
+
+        slices.drain(..skipped);
+
+        bytes_resumed = position;
+    }
+
+    let len: u64 = slices.iter().map(|s| s.len as u64).sum();
+
+    process.ensure_space(len)?;
+
+    // allocate the disk space of the output up front
+    if process.preallocate {
+        Rt::allocate(output, bytes_resumed + len)
+            .map_err(|_| MergeError::OutFileNotAllocated)?;
+    }
+
+    let mut buffer: Vec<u8> = vec![0; process.buffer_capacity];
+
+    let mut chunks: Vec<ChunkInfo> = Vec::with_capacity(slices.len());
+
+    let mut bytes_written: u64 = 0;
+
+    let mut holes: SparseHoles = SparseHoles::default();
+
+    // merge
+    for slice in slices {
+        if process.is_cancelled() {
+            return Err(MergeError::Cancelled);
+        }
+
+        if process.is_expired() {
+            // keep the chunks written to be resumed
+            let _ = output.flush().await;
+
+            return Err(MergeError::DeadlineExceeded);
+        }
+
+        let size: usize = copy_slice::<Rt>(
+            process,
+            &slice,
+            output,
+            &mut buffer,
+            &mut verifier,
+            &mut holes,
+        )
+        .await?;
+
+        progress.advance(slice.index, size);
+
+        bytes_written += size as u64;
+
+        // expose the chunk to the readers of the output
+        if process.progressive {
+            finish_holes::<Rt>(output, &mut holes)
+                .await
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+
+            output.flush().await.map_err(|_| MergeError::OutFileNotWritten)?;
+
+            Rt::sync_file(process.sync, output)
+                .await
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+
+            process.watermark(bytes_resumed + bytes_written);
+        }
+
+        chunks.push(ChunkInfo { index: slice.index, path: slice.path, size });
+    }
+
+    finish_holes::<Rt>(output, &mut holes)
+        .await
+        .map_err(|_| MergeError::OutFileNotWritten)?;
+
+    output.flush().await.map_err(|_| MergeError::OutFileNotWritten)?;
+
+    verifier.finish()?;
+
+    Rt::sync_file(process.sync, output)
+        .await
+        .map_err(|_| MergeError::OutFileNotWritten)?;
+
+    progress.finish();
+
+    Ok(MergeResult {
+        bytes_written,
+        chunks_merged: chunks.len(),
+        out_file: None,
+        duration: started.elapsed(),
+        chunks,
+        bytes_resumed,
+    })
+}
+
+/// Copy the content of a chunk slice to the output.
+///
+/// Returns the number of bytes copied.
+async fn copy_slice<Rt: Runtime>(
+    process: &Merge,
+    slice: &ChunkSlice,
+    output: &mut Rt::File,
+    buffer: &mut [u8],
+    verifier: &mut OutputVerifier,
+    holes: &mut SparseHoles,
+) -> Result<usize, MergeError> {
+    let (offset, len) = (slice.offset, slice.len);
+
+    let mut input: Rt::File =
+        Rt::open(&slice.path).await.map_err(|_| MergeError::InFileNotOpened)?;
+
+    // the decoded content can not be seeked
+    if process.is_encoded() {
+        let data: Vec<u8> = read_to_end(&mut input)
+            .await
+            .and_then(|data| process.decode(data))
+            .map_err(|_| MergeError::InFileNotRead)?;
+
+        let data: &[u8] = data.get(offset..).unwrap_or_default();
+
+        let data: &[u8] = &data[..len.min(data.len())];
+
+        verifier.update(data)?;
+
+        write_output::<Rt>(process, output, holes, data)
+            .await
+            .map_err(|_| MergeError::OutFileNotWritten)?;
+
+        return Ok(data.len());
+    }
+
+    if offset > 0 {
+        Rt::seek(&mut input, offset as u64)
+            .await
+            .map_err(|_| MergeError::InFileNotRead)?;
+    }
+
+    let mut size: usize = 0;
+
+    while size < len {
+        let limit: usize = (len - size).min(buffer.len());
+
+        let read: usize = input
+            .read(&mut buffer[..limit])
+            .await
+            .map_err(|_| MergeError::InFileNotRead)?;
+
+        if read == 0 {
+            break;
+        }
+
+        verifier.update(&buffer[..read])?;
+
+        write_output::<Rt>(process, output, holes, &buffer[..read])
+            .await
+            .map_err(|_| MergeError::OutFileNotWritten)?;
+
+        size += read;
+    }
+
+    Ok(size)
+}
+
+/// Write the data to the output, seeking over the runs of zeros if sparse.
+async fn write_output<Rt: Runtime>(
+    process: &Merge,
+    output: &mut Rt::File,
+    holes: &mut SparseHoles,
+    data: &[u8],
+) -> io::Result<()> {
+    if !process.sparse {
+        return output.write_all(data).await;
+    }
+
+    for (hole, run) in holes.runs(data) {
+        if hole > 0 {
+            // the writes are completed before the cursor moves
+            output.flush().await?;
+
+            Rt::skip(output, hole).await?;
+        }
+
+        output.write_all(run).await?;
+    }
+
+    Ok(())
+}
+
+/// Leave the hole pending at the end of the output,
+/// extending the output over it.
+async fn finish_holes<Rt: Runtime>(
+    output: &mut Rt::File,
+    holes: &mut SparseHoles,
+) -> io::Result<()> {
+    let hole: u64 = holes.take();
+
+    if hole == 0 {
+        return Ok(());
+    }
+
+    output.flush().await?;
+
+    let position: u64 = Rt::skip(output, hole).await?;
+
+    Rt::set_len(output, position).await
+}
+
+/// Scan the chunks of the input directory to merge, in order.
+pub(crate) async fn scan_chunks<Rt: Runtime>(
+    process: &Merge,
+    in_dir: &Path,
+    repair_dir: Option<&RepairDir>,
+) -> Result<Vec<ChunkSlice>, MergeError> {
+    // get inputs
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    for path in
+        Rt::read_dir(in_dir).await.map_err(|_| MergeError::InDirNotRead)?
+    {
+        if path.is_file() {
+            files.push(path);
+        } else {
+            process.warn(Warning::IgnoredEntry(path))?;
+        }
+    }
+
+    let found: bool = !files.is_empty();
+
+    let mut entries: Vec<(usize, PathBuf)> = process.index_chunks(files)?;
+
+    if let Some(repair_dir) = repair_dir {
+        repair_dir.patch(&mut entries);
+    }
+
+    if entries.is_empty() {
+        return Err(no_chunk(found));
+    }
+
+    let mut sized: Vec<(usize, PathBuf, usize)> =
+        Vec::with_capacity(entries.len());
+
+    for (index, entry) in entries {
+        let len: usize = chunk_len::<Rt>(process, &entry).await?;
+
+        sized.push((index, entry, len));
+    }
+
+    process.validate_chunks(&sized)?;
+
+    Ok(process.select_chunks(sized))
+}
+
+/// Get the size of the content of a chunk file, once decoded.
+async fn chunk_len<Rt: Runtime>(
+    process: &Merge,
+    path: &Path,
+) -> Result<usize, MergeError> {
+    if process.is_encoded() {
+        let mut input: Rt::File =
+            Rt::open(path).await.map_err(|_| MergeError::InFileNotOpened)?;
+
+        return read_to_end(&mut input)
+            .await
+            .and_then(|data| process.decode(data))
+            .map(|data| data.len())
+            .map_err(|_| MergeError::InFileNotRead);
+    }
+
+    Rt::metadata(path)
+        .await
+        .ok()
+        .and_then(|m| usize::try_from(m.len()).ok())
+        .ok_or(MergeError::InFileNotRead)
+}
@@ -0,0 +1,193 @@
+use std::{
+    fs::Metadata,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::sync::SyncPolicy;
+
+pub mod split;
+
+pub mod check;
+
+pub mod merge;
+
+pub mod compare;
+
+/// Reader of an asynchronous runtime.
+pub(crate) trait Reader: Send {
+    /// Read into the buffer, `0` once the end is reached.
+    fn read(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> impl Future<Output = io::Result<usize>> + Send;
+}
+
+/// Writer of an asynchronous runtime.
+pub(crate) trait Writer: Send {
+    /// Write the entire data.
+    fn write_all(
+        &mut self,
+        data: &[u8],
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Flush the data written.
+    fn flush(&mut self) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// File system of an asynchronous runtime.
+///
+/// The processes are implemented once over this trait,
+/// and each runtime only adapts its own file system to it.
+pub(crate) trait Runtime {
+    /// The file of the runtime.
+    type File: Reader + Writer + Sync;
+
+    /// Open a file to read.
+    fn open(path: &Path)
+    -> impl Future<Output = io::Result<Self::File>> + Send;
+
+    /// Open a file to write, created if not exists.
+    fn create(
+        path: &Path,
+        truncate: bool,
+    ) -> impl Future<Output = io::Result<Self::File>> + Send;
+
+    /// Get the metadata of an opened file.
+    fn file_metadata(
+        file: &Self::File
+    ) -> impl Future<Output = io::Result<Metadata>> + Send;
+
+    /// Truncate or extend an opened file.
+    fn set_len(
+        file: &Self::File,
+        len: u64,
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Move the cursor of an opened file.
+    fn seek(
+        file: &mut Self::File,
+        position: u64,
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Move the cursor of an opened file forward,
+    /// returning its new position.
+    fn skip(
+        file: &mut Self::File,
+        len: u64,
+    ) -> impl Future<Output = io::Result<u64>> + Send;
+
+    /// Allocate the disk space of an opened file.
+    fn allocate(
+        file: &Self::File,
+        len: u64,
+    ) -> io::Result<()>;
+
+    /// Sync a written file by the policy.
+    fn sync_file(
+        policy: SyncPolicy,
+        file: &Self::File,
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Sync a directory by the policy, so the entries written persist.
+    fn sync_dir(
+        policy: SyncPolicy,
+        dir: &Path,
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Get the metadata of a path.
+    fn metadata(
+        path: &Path
+    ) -> impl Future<Output = io::Result<Metadata>> + Send;
+
+    /// Get the paths of the entries of a directory.
+    fn read_dir(
+        path: &Path
+    ) -> impl Future<Output = io::Result<Vec<PathBuf>>> + Send;
+
+    /// Create a directory and its parents.
+    fn create_dir_all(
+        path: &Path
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Write the data to a file, replacing its content.
+    fn write(
+        path: &Path,
+        data: &[u8],
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Rename a file or a directory.
+    fn rename(
+        from: &Path,
+        to: &Path,
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Remove a file.
+    fn remove_file(path: &Path) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Remove a directory and its content.
+    fn remove_dir_all(
+        path: &Path
+    ) -> impl Future<Output = io::Result<()>> + Send;
+
+    /// Run a blocking function on the thread pool of the runtime,
+    /// so the executor is not blocked by the file I/O and the hashing.
+    fn spawn_blocking<T: Send + 'static>(
+        f: impl FnOnce() -> T + Send + 'static
+    ) -> impl Future<Output = T> + Send;
+}
+
+/// Reader adapting the readers of the `futures` traits,
+/// as used by `async_std` and `smol`.
+#[cfg(any(feature = "async_std", feature = "smol"))]
+pub(crate) struct FuturesReader<R>(pub(crate) R);
+
+#[cfg(any(feature = "async_std", feature = "smol"))]
+impl<R: futures::io::AsyncRead + Unpin + Send> Reader for FuturesReader<R> {
+    async fn read(
+        &mut self,
+        buffer: &mut [u8],
+    ) -> io::Result<usize> {
+        futures::io::AsyncReadExt::read(&mut self.0, buffer).await
+    }
+}
+
+/// Read from the reader until the buffer is full or the end is reached.
+pub(crate) async fn read_full<R: Reader>(
+    reader: &mut R,
+    buffer: &mut [u8],
+) -> io::Result<usize> {
+    let mut offset: usize = 0;
+
+    while offset < buffer.len() {
+        match reader.read(&mut buffer[offset..]).await {
+            | Ok(0) => break,
+            | Ok(n) => offset += n,
+            | Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+            | Err(e) => return Err(e),
+        }
+    }
+
+    Ok(offset)
+}
+
+/// Read from the reader until the end is reached.
+pub(crate) async fn read_to_end<R: Reader>(
+    reader: &mut R
+) -> io::Result<Vec<u8>> {
+    let mut data: Vec<u8> = Vec::new();
+
+    let mut buffer: Vec<u8> = vec![0; crate::BUFFER_CAPACITY_DEFAULT];
+
+    loop {
+        match reader.read(&mut buffer).await {
+            | Ok(0) => break,
+            | Ok(n) => data.extend_from_slice(&buffer[..n]),
+            | Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+            | Err(e) => return Err(e),
+        }
+    }
+
+    Ok(data)
+}
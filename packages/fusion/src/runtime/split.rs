@@ -0,0 +1,469 @@
+use std::{
+    borrow::Cow,
+    fs::Metadata,
+    io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{
+    chunk::{ChunkInfo, temp_path},
+    hash::Digest,
+    lock::FileLock,
+    progress::ProgressGuard,
+    runtime::{Reader, Runtime, Writer as _, read_full, read_to_end},
+    split::{
+        ChunkLayout, OverwritePolicy, Split, SplitError, SplitResult,
+        VERIFY_ALGORITHM,
+    },
+    warning::Warning,
+};
+
+/// Run the split process.
+pub(crate) async fn run<Rt: Runtime>(
+    process: &Split
+) -> Result<SplitResult, SplitError> {
+    let _progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let in_file: &Path = match process.in_file {
+        | Some(ref p) => {
+            let p: &Path = p.as_ref();
+
+            // if in_file not exists
+            if !p.exists() {
+                return Err(SplitError::InFileNotFound);
+            }
+
+            // if in_file not a file
+            if !p.is_file() {
+                return Err(SplitError::InFileNotFile);
+            }
+
+            p
+        },
+        | None => return Err(SplitError::InFileNotSet),
+    };
+
+    let mut retries: usize = 0;
+
+    let result: SplitResult = loop {
+        match split_path::<Rt>(process, in_file).await {
+            | Err(error) if process.is_retried(&error, retries) => {
+                retries += 1;
+            },
+            | result => break result?,
+        }
+    };
+
+    if process.remove_source {
+        remove_source_file::<Rt>(process, in_file, &result).await?;
+    }
+
+    Ok(result)
+}
+
+/// Split the input file at a path to the output directory,
+/// checked for changes once read.
+async fn split_path<Rt: Runtime>(
+    process: &Split,
+    in_file: &Path,
+) -> Result<SplitResult, SplitError> {
+    let input_file: Rt::File =
+        Rt::open(in_file).await.map_err(|_| SplitError::InFileNotOpened)?;
+
+    let metadata: Metadata = Rt::file_metadata(&input_file)
+        .await
+        .map_err(|_| SplitError::InFileNotRead)?;
+
+    let result: SplitResult =
+        split_reader::<Rt, _>(process, input_file, Some(metadata.len()))
+            .await?;
+
+    let after: io::Result<Metadata> = Rt::metadata(in_file).await;
+
+    // if in_file changed in the meantime
+    let read: bool = result.file_size >= metadata.len();
+
+    if let Err(error) = process.check_source(in_file, &metadata, &after, read) {
+        for chunk in result.chunks.iter() {
+            let _ = Rt::remove_file(&chunk.path).await;
+        }
+
+        return Err(error);
+    }
+
+    Ok(result)
+}
+
+/// Split the reader to the output directory.
+///
+/// The input size is only required with the total number of chunks.
+pub(crate) async fn split_reader<Rt: Runtime, R: Reader>(
+    process: &Split,
+    mut reader: R,
+    input_size: Option<u64>,
+) -> Result<SplitResult, SplitError> {
+    let progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let _lock: Option<FileLock> = process.lock_out_dir(None)?;
+
+    let out_dir: &Path = prepare_out_dir::<Rt>(process).await?;
+
+    let layout: ChunkLayout = process.layout(input_size)?;
+
+    if let Some(input_size) = input_size {
+        process.ensure_space(out_dir, &layout, input_size)?;
+    }
+
+    let mut buffer: Vec<u8> = vec![0; layout.max_len()];
+
+    let started: Instant = Instant::now();
+
+    let mut file_size: u64 = 0;
+
+    let mut total_chunks: usize = 0;
+
+    let mut chunks: Vec<ChunkInfo> = Vec::new();
+
+    let mut chunks_resumed: usize = 0;
+
+    // the bytes read and the size of the last chunk cut from them
+    let mut filled: usize = 0;
+
+    let mut offset: usize = 0;
+
+    loop {
+        if process.is_cancelled() {
+            remove_chunks::<Rt>(process, out_dir, total_chunks).await;
+
+            return Err(SplitError::Cancelled);
+        }
+
+        if process.is_expired() {
+            return Err(SplitError::DeadlineExceeded);
+        }
+
+        // keep the bytes read after the last chunk
+        buffer.copy_within(offset..filled, 0);
+
+        filled -= offset;
+
+        let size: usize = layout.len(total_chunks);
+
+        filled += read_full(&mut reader, &mut buffer[filled..size])
+            .await
+            .map_err(|_| SplitError::InFileNotRead)?;
+
+        if filled == 0 {
+            break;
+        }
+
+        offset = layout.cut(&buffer[..filled]);
+
+        let output_path: PathBuf =
+            out_dir.join(process.naming.file_name(total_chunks));
+
+        // skip the chunk already written
+        if process.resume
+            && is_chunk_written::<Rt>(process, &output_path, offset).await
+        {
+            let chunk: ChunkInfo = ChunkInfo {
+                index: total_chunks,
+                path: output_path,
+                size: offset,
+            };
+
+            process.first_chunk(&chunk);
+
+            progress.advance(total_chunks, offset);
+
+            chunks.push(chunk);
+
+            file_size += offset as u64;
+
+            total_chunks += 1;
+
+            chunks_resumed += 1;
+
+            continue;
+        }
+
+        let chunk: ChunkInfo = write_chunk::<Rt>(
+            process,
+            out_dir,
+            &layout,
+            input_size,
+            total_chunks,
+            &buffer[..offset],
+        )
+        .await?;
+
+        progress.advance(total_chunks, offset);
+
+        chunks.push(chunk);
+
+        file_size += offset as u64;
+
+        total_chunks += 1;
+    }
+
+    let bao_hash: Option<Digest> =
+        write_outputs::<Rt>(process, out_dir, &chunks).await?;
+
+    progress.finish();
+
+    Ok(SplitResult {
+        file_size,
+        total_chunks,
+        chunk_size: layout.chunk_size,
+        chunks,
+        duration: started.elapsed(),
+        chunks_resumed,
+        bao_hash,
+    })
+}
+
+/// Validate the output directory, create it if not exists.
+pub(crate) async fn prepare_out_dir<Rt: Runtime>(
+    process: &Split
+) -> Result<&Path, SplitError> {
+    match process.out_dir {
+        | Some(ref p) => {
+            let p: &Path = p.as_ref();
+
+            if !p.exists() {
+                // if out_dir not exists
+                Rt::create_dir_all(p)
+                    .await
+                    .map_err(|_| SplitError::OutDirNotCreated)?
+            } else if p.is_file() {
+                // if out_dir not a directory
+                return Err(SplitError::OutDirNotDir);
+            } else if let Ok(entries) = Rt::read_dir(p).await {
+                // if out_dir not empty
+                if !entries.is_empty() {
+                    match process.overwrite {
+                        | OverwritePolicy::Fail => {
+                            return Err(SplitError::OutDirNotEmpty);
+                        },
+                        | OverwritePolicy::Clean => clean_dir::<Rt>(p).await?,
+                        | OverwritePolicy::Reuse => {
+                            process.warn(Warning::OutDirNotEmpty(
+                                p.to_path_buf(),
+                            ))?;
+                        },
+                    }
+                }
+            }
+
+            Ok(p)
+        },
+        | None => Err(SplitError::OutDirNotSet),
+    }
+}
+
+/// Write a chunk to the output directory.
+pub(crate) async fn write_chunk<Rt: Runtime>(
+    process: &Split,
+    out_dir: &Path,
+    layout: &ChunkLayout,
+    input_size: Option<u64>,
+    index: usize,
+    data: &[u8],
+) -> Result<ChunkInfo, SplitError> {
+    let output_path: PathBuf = out_dir.join(process.naming.file_name(index));
+
+    // write to a temporary file to rename, if atomic
+    let write_path: PathBuf = match process.atomic {
+        | true => temp_path(&output_path),
+        | false => output_path.clone(),
+    };
+
+    let encoded: Cow<[u8]> = process.encode(layout, input_size, index, data)?;
+
+    let mut output: Rt::File = Rt::create(&write_path, true)
+        .await
+        .map_err(|_| SplitError::OutFileNotOpened)?;
+
+    // the chunk of zeros is left as a hole, if sparse
+    match process.is_sparse_chunk(&encoded) {
+        | true => Rt::set_len(&output, encoded.len() as u64).await,
+        | false => output.write_all(&encoded).await,
+    }
+    .map_err(|_| SplitError::OutFileNotWritten)?;
+
+    output.flush().await.map_err(|_| SplitError::OutFileNotWritten)?;
+
+    Rt::sync_file(process.sync, &output)
+        .await
+        .map_err(|_| SplitError::OutFileNotWritten)?;
+
+    drop(output);
+
+    if process.verify_writes {
+        verify_chunk::<Rt>(&write_path, &encoded).await?;
+    }
+
+    if process.atomic {
+        Rt::rename(&write_path, &output_path)
+            .await
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+    }
+
+    let chunk: ChunkInfo =
+        ChunkInfo { index, path: output_path, size: data.len() };
+
+    process.first_chunk(&chunk);
+
+    Ok(chunk)
+}
+
+/// Write the files next to the chunks written, such as the parity chunks,
+/// and sync the output directory.
+///
+/// Returns the root of the outboard tree, if written.
+pub(crate) async fn write_outputs<Rt: Runtime>(
+    process: &Split,
+    out_dir: &Path,
+    chunks: &[ChunkInfo],
+) -> Result<Option<Digest>, SplitError> {
+    process.write_parity(out_dir, chunks)?;
+
+    let bao_hash: Option<Digest> = process.write_bao(out_dir, chunks)?;
+
+    process.write_metadata(out_dir)?;
+
+    for (name, script) in process.merge_scripts(chunks) {
+        Rt::write(&out_dir.join(name), script.as_bytes())
+            .await
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+    }
+
+    Rt::sync_dir(process.sync, out_dir)
+        .await
+        .map_err(|_| SplitError::OutFileNotWritten)?;
+
+    Ok(bao_hash)
+}
+
+/// Remove the chunks written to the output directory.
+///
+/// The chunks are kept to be resumed later, if resumed.
+pub(crate) async fn remove_chunks<Rt: Runtime>(
+    process: &Split,
+    out_dir: &Path,
+    total_chunks: usize,
+) {
+    if process.resume {
+        return;
+    }
+
+    for i in 0..total_chunks {
+        let _ =
+            Rt::remove_file(&out_dir.join(process.naming.file_name(i))).await;
+    }
+}
+
+/// Remove the entries of the output directory.
+async fn clean_dir<Rt: Runtime>(out_dir: &Path) -> Result<(), SplitError> {
+    let entries: Vec<PathBuf> = Rt::read_dir(out_dir)
+        .await
+        .map_err(|_| SplitError::OutDirNotCleaned)?;
+
+    for path in entries {
+        if path.is_dir() {
+            Rt::remove_dir_all(&path).await
+        } else {
+            Rt::remove_file(&path).await
+        }
+        .map_err(|_| SplitError::OutDirNotCleaned)?;
+    }
+
+    Ok(())
+}
+
+/// Check if a chunk file holds the data of the size, once decoded.
+async fn is_chunk_written<Rt: Runtime>(
+    process: &Split,
+    path: &Path,
+    size: usize,
+) -> bool {
+    let len: u64 = match Rt::metadata(path).await {
+        | Ok(metadata) if metadata.is_file() => metadata.len(),
+        | _ => return false,
+    };
+
+    if process.is_encoded() {
+        let mut file: Rt::File = match Rt::open(path).await {
+            | Ok(file) => file,
+            | Err(_) => return false,
+        };
+
+        return read_to_end(&mut file)
+            .await
+            .and_then(|data| process.decode(data))
+            .is_ok_and(|data| data.len() == size);
+    }
+
+    len as usize == size
+}
+
+/// Read back a chunk file written, and compare its hash to the content
+/// written.
+///
+/// The chunk file is removed if it differs.
+async fn verify_chunk<Rt: Runtime>(
+    path: &Path,
+    written: &[u8],
+) -> Result<(), SplitError> {
+    let mut file: Rt::File =
+        Rt::open(path).await.map_err(|_| SplitError::ChunksNotVerified)?;
+
+    let data: Vec<u8> = read_to_end(&mut file)
+        .await
+        .map_err(|_| SplitError::ChunksNotVerified)?;
+
+    drop(file);
+
+    if VERIFY_ALGORITHM.digest(&data) != VERIFY_ALGORITHM.digest(written) {
+        let _ = Rt::remove_file(path).await;
+
+        return Err(SplitError::ChunksNotVerified);
+    }
+
+    Ok(())
+}
+
+/// Remove the input file once the chunks written are verified.
+async fn remove_source_file<Rt: Runtime>(
+    process: &Split,
+    in_file: &Path,
+    result: &SplitResult,
+) -> Result<(), SplitError> {
+    let file_size: u64 = Rt::metadata(in_file)
+        .await
+        .map_err(|_| SplitError::InFileNotRead)?
+        .len();
+
+    let mut verified: bool = file_size == result.file_size
+        && result.chunks.iter().map(|c| c.size as u64).sum::<u64>()
+            == result.file_size;
+
+    for chunk in result.chunks.iter() {
+        if !verified {
+            break;
+        }
+
+        verified =
+            is_chunk_written::<Rt>(process, &chunk.path, chunk.size).await;
+    }
+
+    // if chunks not match in_file
+    if !verified {
+        return Err(SplitError::ChunksNotVerified);
+    }
+
+    Rt::remove_file(in_file).await.map_err(|_| SplitError::InFileNotRemoved)
+}
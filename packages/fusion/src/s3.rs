@@ -0,0 +1,389 @@
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use crate::{
+    layout::chunk_count,
+    store::{ChunkStore, StoreError},
+};
+
+/// The minimum size of each part except the last one, in bytes.
+pub const S3_PART_SIZE_MIN: usize = 5 * 1024 * 1024;
+
+/// The maximum size of each part, in bytes.
+pub const S3_PART_SIZE_MAX: u64 = 5 * 1024 * 1024 * 1024;
+
+/// The maximum number of parts of an upload.
+pub const S3_PARTS_MAX: usize = 10000;
+
+/// S3 error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum S3Error {
+    PartTooSmall,
+    PartTooLarge,
+    TooManyParts,
+    PartsMissing,
+    UploadEmpty,
+    UploadClosed,
+}
+
+impl S3Error {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::PartTooSmall => "part_too_small",
+            | Self::PartTooLarge => "part_too_large",
+            | Self::TooManyParts => "too_many_parts",
+            | Self::PartsMissing => "parts_missing",
+            | Self::UploadEmpty => "upload_empty",
+            | Self::UploadClosed => "upload_closed",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::PartTooSmall => {
+                "A part other than the last one is smaller than 5 MiB."
+            },
+            | Self::PartTooLarge => "A part is larger than 5 GiB.",
+            | Self::TooManyParts => "The upload has more than 10000 parts.",
+            | Self::PartsMissing => {
+                "The parts are not numbered contiguously from 1."
+            },
+            | Self::UploadEmpty => "The upload has no part.",
+            | Self::UploadClosed => {
+                "The upload is already completed or aborted."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Part to send with an `UploadPart` request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartRequest<'a> {
+    /// The ID of the multipart upload.
+    pub upload_id: &'a str,
+    /// The number of the part, from `1`.
+    pub part_number: usize,
+    /// The content of the part.
+    pub data: &'a [u8],
+}
+
+/// Part uploaded, to list in the `CompleteMultipartUpload` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletedPart {
+    /// The number of the part, from `1`.
+    pub part_number: usize,
+    /// The ETag returned by the `UploadPart` request.
+    pub etag: String,
+    /// The size of the part in bytes.
+    pub size: usize,
+}
+
+/// Function uploading a part, returning its ETag.
+type UploadPartFn = dyn Fn(&PartRequest) -> Option<String> + Send + Sync;
+
+/// Handler to send the `UploadPart` requests of an upload.
+///
+/// The handler receives each part and returns the ETag of the response,
+/// or `None` to fail.
+#[derive(Clone)]
+pub struct UploadPartHandler {
+    handler: Arc<UploadPartFn>,
+}
+
+impl UploadPartHandler {
+    /// Create a new upload part handler.
+    pub fn new<
+        F: Fn(&PartRequest) -> Option<String> + Send + Sync + 'static,
+    >(
+        handler: F
+    ) -> Self {
+        Self { handler: Arc::new(handler) }
+    }
+
+    /// Upload a part with the handler.
+    pub fn upload(
+        &self,
+        part: &PartRequest,
+    ) -> Option<String> {
+        (self.handler)(part)
+    }
+}
+
+impl fmt::Debug for UploadPartHandler {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("UploadPartHandler").finish_non_exhaustive()
+    }
+}
+
+/// State of a multipart upload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UploadState {
+    /// The parts are being uploaded.
+    #[default]
+    Open,
+    /// The upload is completed.
+    Completed,
+    /// The upload is aborted.
+    Aborted,
+}
+
+/// Multipart upload to Amazon S3 or a compatible service, fed by the split
+/// process as a chunk store.
+///
+/// The upload is created by the caller with `CreateMultipartUpload`, then
+/// each chunk written to the store is sent as a part, numbered from `1`,
+/// by the handler with `UploadPart`, and its ETag kept. Once all chunks are
+/// written, [`S3MultipartUpload::complete`] validates the parts and returns
+/// them for the `CompleteMultipartUpload` request, or
+/// [`S3MultipartUpload::abort`] closes the upload to be aborted with
+/// `AbortMultipartUpload`. The requests are sent by the client of the
+/// caller, so no chunk file is written.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     s3::{CompletedPart, S3MultipartUpload},
+///     split::{Split, SplitResult},
+/// };
+///
+/// let file_size: u64 = 64 * 1024 * 1024;
+///
+/// let chunk_size: usize = S3MultipartUpload::part_size(file_size);
+///
+/// S3MultipartUpload::validate(chunk_size, file_size).unwrap();
+///
+/// let mut upload: S3MultipartUpload =
+///     S3MultipartUpload::new("upload-id", |part| {
+///         // send the part with `UploadPart`...
+///         Some(format!("\"etag-{}\"", part.part_number))
+///     });
+///
+/// let result: SplitResult = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .chunk_size(chunk_size)
+///     .run_file_to_store(&mut upload)
+///     .unwrap();
+///
+/// match upload.complete() {
+///     | Ok(parts) => {
+///         // send the parts with `CompleteMultipartUpload`...
+///     },
+///     | Err(_) => {
+///         let upload_id: String = upload.abort();
+///         // send `AbortMultipartUpload`...
+///     },
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct S3MultipartUpload {
+    pub upload_id: String,
+    pub on_part: UploadPartHandler,
+    parts: BTreeMap<usize, CompletedPart>,
+    state: UploadState,
+}
+
+impl S3MultipartUpload {
+    /// Create a new upload with the ID returned by `CreateMultipartUpload`
+    /// and the handler to send the parts.
+    pub fn new<
+        UploadId: Into<String>,
+        F: Fn(&PartRequest) -> Option<String> + Send + Sync + 'static,
+    >(
+        upload_id: UploadId,
+        on_part: F,
+    ) -> Self {
+        Self {
+            upload_id: upload_id.into(),
+            on_part: UploadPartHandler::new(on_part),
+            parts: BTreeMap::new(),
+            state: UploadState::Open,
+        }
+    }
+
+    /// Validate a chunk size against the part limits,
+    /// for a file of the size.
+    pub fn validate(
+        chunk_size: usize,
+        file_size: u64,
+    ) -> Result<(), S3Error> {
+        if chunk_size as u64 > S3_PART_SIZE_MAX {
+            return Err(S3Error::PartTooLarge);
+        }
+
+        // a file in a single part can be smaller
+        if chunk_size < S3_PART_SIZE_MIN && file_size > chunk_size as u64 {
+            return Err(S3Error::PartTooSmall);
+        }
+
+        if chunk_count(file_size, chunk_size.max(1)) > S3_PARTS_MAX {
+            return Err(S3Error::TooManyParts);
+        }
+
+        Ok(())
+    }
+
+    /// Get the smallest chunk size within the part limits
+    /// for a file of the size, rounded up to a MiB.
+    ///
+    /// On 32-bit targets, the size is capped at `usize::MAX`.
+    pub fn part_size(file_size: u64) -> usize {
+        let mib: u64 = 1024 * 1024;
+
+        (file_size.div_ceil(S3_PARTS_MAX as u64).div_ceil(mib) * mib)
+            .clamp(S3_PART_SIZE_MIN as u64, S3_PART_SIZE_MAX)
+            .min(usize::MAX as u64) as usize
+    }
+
+    /// Get the state of the upload.
+    pub fn state(&self) -> UploadState {
+        self.state
+    }
+
+    /// Get the parts uploaded so far, in order.
+    pub fn parts(&self) -> Vec<CompletedPart> {
+        self.parts.values().cloned().collect()
+    }
+
+    /// Complete the upload, getting the parts for the
+    /// `CompleteMultipartUpload` request, in order.
+    ///
+    /// The parts must be numbered contiguously from `1`, and all of them
+    /// except the last one must be at least [`S3_PART_SIZE_MIN`] bytes.
+    /// If the parts are invalid, the upload stays open to be aborted.
+    pub fn complete(&mut self) -> Result<Vec<CompletedPart>, S3Error> {
+        if self.state != UploadState::Open {
+            return Err(S3Error::UploadClosed);
+        }
+
+        if self.parts.is_empty() {
+            return Err(S3Error::UploadEmpty);
+        }
+
+        let parts: Vec<CompletedPart> = self.parts();
+
+        if parts.iter().enumerate().any(|(i, p)| p.part_number != i + 1) {
+            return Err(S3Error::PartsMissing);
+        }
+
+        // only the last part can be smaller
+        if parts[..parts.len() - 1].iter().any(|p| p.size < S3_PART_SIZE_MIN) {
+            return Err(S3Error::PartTooSmall);
+        }
+
+        self.state = UploadState::Completed;
+
+        Ok(parts)
+    }
+
+    /// Abort the upload, getting its ID for the
+    /// `AbortMultipartUpload` request.
+    ///
+    /// No part is written to the upload afterwards.
+    pub fn abort(&mut self) -> String {
+        self.state = UploadState::Aborted;
+
+        self.upload_id.clone()
+    }
+
+    /// Get the XML body of the `CompleteMultipartUpload` request
+    /// listing the parts.
+    pub fn complete_body(parts: &[CompletedPart]) -> String {
+        let mut body: String = String::from("<CompleteMultipartUpload>");
+
+        for part in parts {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part.part_number,
+                escape_xml(&part.etag)
+            ));
+        }
+
+        body.push_str("</CompleteMultipartUpload>");
+
+        body
+    }
+}
+
+/// The chunks are sent as parts, numbered by their index from `1`.
+///
+/// The parts are not read back, reading a chunk fails with
+/// [`StoreError::ChunkNotRead`].
+impl ChunkStore for S3MultipartUpload {
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError> {
+        Ok(self.parts.contains_key(&(index + 1)))
+    }
+
+    fn read(
+        &self,
+        _index: usize,
+    ) -> Result<Vec<u8>, StoreError> {
+        Err(StoreError::ChunkNotRead)
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        let part_number: usize = index + 1;
+
+        if self.state != UploadState::Open
+            || part_number > S3_PARTS_MAX
+            || data.len() as u64 > S3_PART_SIZE_MAX
+        {
+            return Err(StoreError::ChunkNotWritten);
+        }
+
+        let etag: String = self
+            .on_part
+            .upload(&PartRequest {
+                upload_id: &self.upload_id,
+                part_number,
+                data,
+            })
+            .ok_or(StoreError::ChunkNotWritten)?;
+
+        self.parts.insert(
+            part_number,
+            CompletedPart { part_number, etag, size: data.len() },
+        );
+
+        Ok(())
+    }
+}
+
+/// Escape the text of an XML element.
+fn escape_xml(text: &str) -> String {
+    let mut escaped: String = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            | '&' => escaped.push_str("&amp;"),
+            | '<' => escaped.push_str("&lt;"),
+            | '>' => escaped.push_str("&gt;"),
+            | c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
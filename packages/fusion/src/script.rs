@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use crate::chunk::ChunkInfo;
+
+/// The file name of the generated shell script.
+pub const SHELL_SCRIPT_NAME: &str = "merge.sh";
+
+/// The file name of the generated batch script.
+pub const BATCH_SCRIPT_NAME: &str = "merge.bat";
+
+/// Generate a POSIX shell script to merge the chunks without this crate.
+///
+/// The script is expected to be placed next to the chunks.
+///
+/// ## Example
+///
+/// ```
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{chunk::ChunkInfo, script};
+///
+/// let chunks: Vec<ChunkInfo> = vec![
+///     ChunkInfo { index: 0, path: PathBuf::from("0"), size: 1 },
+///     ChunkInfo { index: 1, path: PathBuf::from("1"), size: 1 },
+/// ];
+///
+/// let script: String = script::shell("file.bin", &chunks);
+///
+/// assert!(script.contains("cat '1' >> 'file.bin'"));
+/// ```
+pub fn shell(
+    output_name: &str,
+    chunks: &[ChunkInfo],
+) -> String {
+    let output: String = shell_quote(output_name);
+
+    let mut script: String = String::from("#!/bin/sh\n");
+
+    script.push_str("# Merge the chunks into the original file.\n");
+    script.push_str("set -e\n");
+    script.push_str("cd \"$(dirname \"$0\")\"\n");
+    script.push_str(&format!(": > {output}\n"));
+
+    for chunk in chunks {
+        script.push_str(&format!(
+            "cat {} >> {output}\n",
+            shell_quote(&file_name(&chunk.path))
+        ));
+    }
+
+    script
+}
+
+/// Generate a Windows batch script to merge the chunks without this crate.
+///
+/// The script is expected to be placed next to the chunks.
+pub fn batch(
+    output_name: &str,
+    chunks: &[ChunkInfo],
+) -> String {
+    let output: String = batch_quote(output_name);
+
+    let mut script: String = String::from("@echo off\r\n");
+
+    script.push_str("rem Merge the chunks into the original file.\r\n");
+    script.push_str("cd /d \"%~dp0\"\r\n");
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let chunk: String = batch_quote(&file_name(&chunk.path));
+
+        if i == 0 {
+            script.push_str(&format!("copy /b {chunk} {output} >nul\r\n"));
+        } else {
+            script.push_str(&format!(
+                "copy /b {output}+{chunk} {output} >nul\r\n"
+            ));
+        }
+
+        script.push_str("if errorlevel 1 exit /b 1\r\n");
+    }
+
+    script
+}
+
+/// Get the file name of a chunk.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Quote a word for the shell.
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Quote a word for the batch script.
+fn batch_quote(word: &str) -> String {
+    format!("\"{}\"", word.replace('%', "%%"))
+}
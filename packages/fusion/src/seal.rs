@@ -0,0 +1,1058 @@
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    io::{self, Read as _, Write as _},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    KeyInit as _, XChaCha20Poly1305, XNonce,
+    aead::{Aead as _, AeadCore as _, OsRng, Payload, rand_core::RngCore as _},
+};
+
+use crate::{
+    CHUNK_SIZE_DEFAULT,
+    chunk::{ChunkInfo, ChunkNaming, temp_path},
+    sys::read_options,
+};
+
+/// The name of the encrypted manifest in a sealed archive.
+pub const SEAL_MANIFEST_NAME: &str = "seal.manifest";
+
+/// The length of a key to seal an archive with, in bytes.
+pub const SEAL_KEY_LEN: usize = 32;
+
+/// The length of the salt of a passphrase, in bytes.
+pub const SEAL_SALT_LEN: usize = 16;
+
+/// The length of the nonce of each encrypted file, in bytes.
+pub const SEAL_NONCE_LEN: usize = 24;
+
+/// The length of the random identifier of an archive, in bytes.
+pub const SEAL_ARCHIVE_ID_LEN: usize = 16;
+
+/// The magic bytes at the start of the manifest of a sealed archive.
+const MAGIC: &[u8; 8] = b"FRSEAL\0\0";
+
+/// The version of the sealed archive format.
+const VERSION: u8 = 1;
+
+/// The length of the header of the manifest, before its nonce.
+const HEADER_LEN: usize = MAGIC.len() + 2 + SEAL_SALT_LEN + 3 * 4;
+
+/// The memory cost of the key derived from a passphrase, in KiB.
+const KDF_MEMORY_COST: u32 = 19 * 1024;
+
+/// The number of passes of the key derived from a passphrase.
+const KDF_TIME_COST: u32 = 2;
+
+/// Secret to seal and unseal an archive.
+///
+/// A passphrase is stretched with Argon2id and a random salt kept in the
+/// manifest, a key is used as is.
+#[derive(Clone)]
+pub enum SealKey {
+    /// A passphrase to derive the key from.
+    Passphrase(Arc<str>),
+    /// A key of [`SEAL_KEY_LEN`] bytes.
+    Key([u8; SEAL_KEY_LEN]),
+}
+
+impl SealKey {
+    /// Get the mode of the key.
+    fn mode(&self) -> SealKeyMode {
+        match self {
+            | Self::Key(_) => SealKeyMode::Key,
+            | Self::Passphrase(_) => SealKeyMode::Passphrase,
+        }
+    }
+
+    /// Derive the key of the archive with the salt and the costs.
+    fn derive(
+        &self,
+        salt: &[u8],
+        costs: [u32; 3],
+    ) -> Option<XChaCha20Poly1305> {
+        let key: [u8; SEAL_KEY_LEN] = match self {
+            | Self::Key(key) => *key,
+            | Self::Passphrase(passphrase) => {
+                let [m_cost, t_cost, p_cost] = costs;
+
+                let params: Params =
+                    Params::new(m_cost, t_cost, p_cost, Some(SEAL_KEY_LEN))
+                        .ok()?;
+
+                let mut key: [u8; SEAL_KEY_LEN] = [0; SEAL_KEY_LEN];
+
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .ok()?;
+
+                key
+            },
+        };
+
+        Some(XChaCha20Poly1305::new(&key.into()))
+    }
+}
+
+impl fmt::Debug for SealKey {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        // the secret is never printed
+        match self {
+            | Self::Passphrase(_) => f.write_str("Passphrase(..)"),
+            | Self::Key(_) => f.write_str("Key(..)"),
+        }
+    }
+}
+
+/// Mode of the key an archive is sealed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SealKeyMode {
+    /// A key used as is.
+    Key,
+    /// A key derived from a passphrase with Argon2id.
+    Passphrase,
+}
+
+impl SealKeyMode {
+    /// Get the mode as stored in the manifest.
+    fn to_byte(self) -> u8 {
+        match self {
+            | Self::Key => 0,
+            | Self::Passphrase => 1,
+        }
+    }
+
+    /// Get the mode stored in the manifest.
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            | 0 => Some(Self::Key),
+            | 1 => Some(Self::Passphrase),
+            | _ => None,
+        }
+    }
+}
+
+/// Result of the seal process.
+#[derive(Debug, Clone)]
+pub struct SealResult {
+    /// Size of the original file in bytes.
+    pub file_size: u64,
+    /// The total number of chunks sealed from the original file.
+    pub total_chunks: usize,
+    /// The size of each chunk before encryption, the last chunk may differ.
+    pub chunk_size: usize,
+    /// The encrypted chunks written to the output directory, in order,
+    /// with their size before encryption.
+    pub chunks: Vec<ChunkInfo>,
+    /// The time taken by the seal process.
+    pub duration: Duration,
+}
+
+/// Seal process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SealError {
+    InFileNotFound,
+    InFileNotFile,
+    InFileNotSet,
+    InFileNotOpened,
+    InFileNotRead,
+    OutDirNotCreated,
+    OutDirNotDir,
+    OutDirNotSet,
+    OutDirNotEmpty,
+    OutFileNotWritten,
+    KeyNotSet,
+    KeyNotDerived,
+}
+
+impl SealError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::InFileNotFound => "in_file_not_found",
+            | Self::InFileNotFile => "in_file_not_file",
+            | Self::InFileNotSet => "in_file_not_set",
+            | Self::InFileNotOpened => "in_file_not_opened",
+            | Self::InFileNotRead => "in_file_not_read",
+            | Self::OutDirNotCreated => "out_dir_not_created",
+            | Self::OutDirNotDir => "out_dir_not_dir",
+            | Self::OutDirNotSet => "out_dir_not_set",
+            | Self::OutDirNotEmpty => "out_dir_not_empty",
+            | Self::OutFileNotWritten => "out_file_not_written",
+            | Self::KeyNotSet => "key_not_set",
+            | Self::KeyNotDerived => "key_not_derived",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::InFileNotFound => "The input file not found.",
+            | Self::InFileNotFile => "The input file is not a file.",
+            | Self::InFileNotSet => "The input file is not set.",
+            | Self::InFileNotOpened => "The input file could not be opened.",
+            | Self::InFileNotRead => "The input file could not be read.",
+            | Self::OutDirNotCreated => {
+                "The output directory could not be created."
+            },
+            | Self::OutDirNotDir => "The output directory is not a directory.",
+            | Self::OutDirNotSet => "The output directory is not set.",
+            | Self::OutDirNotEmpty => "The output directory is not empty.",
+            | Self::OutFileNotWritten => {
+                "The output file could not be written."
+            },
+            | Self::KeyNotSet => "The key or the passphrase is not set.",
+            | Self::KeyNotDerived => {
+                "The key could not be derived from the passphrase."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Process to seal a file into an encrypted archive of chunks.
+///
+/// The file is split into chunks, each encrypted and authenticated with
+/// XChaCha20-Poly1305 under a random nonce, bound to its index and to the
+/// archive, so the chunks cannot be altered, reordered, nor mixed with the
+/// chunks of another archive. The size of the file, the number of chunks and
+/// the name of the file are kept in the manifest, [`SEAL_MANIFEST_NAME`],
+/// encrypted the same way and written last, so an archive with a manifest
+/// is complete. The archive is opened with [`Unseal`] and the same key or
+/// passphrase.
+///
+/// The output directory must be empty, otherwise the process fails with
+/// [`SealError::OutDirNotEmpty`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::seal::{Seal, SealResult};
+///
+/// let result: SealResult = Seal::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .passphrase("correct horse battery staple")
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Seal {
+    pub in_file: Option<Arc<Path>>,
+    pub out_dir: Option<Arc<Path>>,
+    pub chunk_size: usize,
+    pub key: Option<SealKey>,
+}
+
+impl Seal {
+    /// Create a new seal process.
+    pub fn new() -> Self {
+        Self {
+            in_file: None,
+            out_dir: None,
+            chunk_size: CHUNK_SIZE_DEFAULT,
+            key: None,
+        }
+    }
+
+    /// Create a new seal process from an existing one.
+    pub fn from<P: Into<Seal>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the input file.
+    pub fn in_file<InFile: AsRef<Path>>(
+        mut self,
+        path: InFile,
+    ) -> Self {
+        self.in_file = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the output directory.
+    pub fn out_dir<OutDir: AsRef<Path>>(
+        mut self,
+        path: OutDir,
+    ) -> Self {
+        self.out_dir = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the maximum size of each chunk, before encryption.
+    ///
+    /// By default, the chunk size follows the [`CHUNK_SIZE_DEFAULT`].
+    pub fn chunk_size(
+        mut self,
+        size: usize,
+    ) -> Self {
+        self.chunk_size = size;
+        self
+    }
+
+    /// Set the passphrase to derive the key of the archive from.
+    pub fn passphrase<Passphrase: AsRef<str>>(
+        mut self,
+        passphrase: Passphrase,
+    ) -> Self {
+        self.key = Some(SealKey::Passphrase(Arc::from(passphrase.as_ref())));
+        self
+    }
+
+    /// Set the key of the archive, instead of a passphrase.
+    pub fn key(
+        mut self,
+        key: [u8; SEAL_KEY_LEN],
+    ) -> Self {
+        self.key = Some(SealKey::Key(key));
+        self
+    }
+
+    /// Run the seal process.
+    pub fn run(&self) -> Result<SealResult, SealError> {
+        let in_file: &Path = match self.in_file {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_file not exists
+                if !p.exists() {
+                    return Err(SealError::InFileNotFound);
+                }
+
+                // if in_file not a file
+                if !p.is_file() {
+                    return Err(SealError::InFileNotFile);
+                }
+
+                p
+            },
+            | None => return Err(SealError::InFileNotSet),
+        };
+
+        let key: &SealKey = self.key.as_ref().ok_or(SealError::KeyNotSet)?;
+
+        let out_dir: &Path = self.prepare_out_dir()?;
+
+        let started: Instant = Instant::now();
+
+        let mut salt: [u8; SEAL_SALT_LEN] = [0; SEAL_SALT_LEN];
+
+        let mut archive_id: [u8; SEAL_ARCHIVE_ID_LEN] =
+            [0; SEAL_ARCHIVE_ID_LEN];
+
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut archive_id);
+
+        let costs: [u32; 3] = [KDF_MEMORY_COST, KDF_TIME_COST, 1];
+
+        let cipher: XChaCha20Poly1305 =
+            key.derive(&salt, costs).ok_or(SealError::KeyNotDerived)?;
+
+        let chunk_size: usize = self.chunk_size.max(1);
+
+        let mut input: fs::File = read_options()
+            .open(in_file)
+            .map_err(|_| SealError::InFileNotOpened)?;
+
+        let mut buffer: Vec<u8> = vec![0; chunk_size];
+
+        let mut chunks: Vec<ChunkInfo> = Vec::new();
+
+        let naming: ChunkNaming = ChunkNaming::new();
+
+        loop {
+            let mut filled: usize = 0;
+
+            while filled < chunk_size {
+                match input.read(&mut buffer[filled..]) {
+                    | Ok(0) => break,
+                    | Ok(n) => filled += n,
+                    | Err(_) => return Err(SealError::InFileNotRead),
+                };
+            }
+
+            if filled == 0 {
+                break;
+            }
+
+            let index: usize = chunks.len();
+
+            let path: PathBuf = out_dir.join(naming.file_name(index));
+
+            let sealed: Vec<u8> = encrypt(
+                &cipher,
+                &chunk_aad(&archive_id, index),
+                &buffer[..filled],
+            )
+            .ok_or(SealError::OutFileNotWritten)?;
+
+            fs::write(&path, sealed)
+                .map_err(|_| SealError::OutFileNotWritten)?;
+
+            chunks.push(ChunkInfo { index, path, size: filled });
+        }
+
+        let file_size: u64 = chunks.iter().map(|c| c.size as u64).sum();
+
+        let manifest: SealManifest = SealManifest {
+            archive_id,
+            file_size,
+            total_chunks: chunks.len(),
+            file_name: in_file
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+
+        let mut header: Vec<u8> = Vec::with_capacity(HEADER_LEN);
+
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.push(key.mode().to_byte());
+        header.extend_from_slice(&salt);
+
+        for cost in costs {
+            header.extend_from_slice(&cost.to_le_bytes());
+        }
+
+        let sealed: Vec<u8> = encrypt(&cipher, &header, &manifest.encode())
+            .ok_or(SealError::OutFileNotWritten)?;
+
+        header.extend_from_slice(&sealed);
+
+        // the manifest is written last, complete or not at all
+        let manifest_path: PathBuf = out_dir.join(SEAL_MANIFEST_NAME);
+
+        let write_path: PathBuf = temp_path(&manifest_path);
+
+        fs::write(&write_path, header)
+            .and_then(|_| fs::rename(&write_path, &manifest_path))
+            .map_err(|_| SealError::OutFileNotWritten)?;
+
+        Ok(SealResult {
+            file_size,
+            total_chunks: chunks.len(),
+            chunk_size,
+            chunks,
+            duration: started.elapsed(),
+        })
+    }
+
+    /// Validate the output directory, create it if not exists.
+    fn prepare_out_dir(&self) -> Result<&Path, SealError> {
+        let p: &Path = match self.out_dir {
+            | Some(ref p) => p.as_ref(),
+            | None => return Err(SealError::OutDirNotSet),
+        };
+
+        if !p.exists() {
+            // if out_dir not exists
+            fs::create_dir_all(p).map_err(|_| SealError::OutDirNotCreated)?;
+        } else if !p.is_dir() {
+            // if out_dir not a directory
+            return Err(SealError::OutDirNotDir);
+        } else if fs::read_dir(p)
+            .is_ok_and(|mut entries| entries.next().is_some())
+        {
+            // if out_dir not empty
+            return Err(SealError::OutDirNotEmpty);
+        }
+
+        Ok(p)
+    }
+}
+
+impl Default for Seal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Nonce of an encrypted chunk of a sealed archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkNonce {
+    /// The index of the chunk.
+    pub index: usize,
+    /// The nonce the chunk is encrypted under.
+    pub nonce: [u8; SEAL_NONCE_LEN],
+}
+
+/// Audit of the nonces and the key parameters of a sealed archive,
+/// without any secret, see [`Unseal::audit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SealAudit {
+    /// The random identifier of the archive, if audited with the key.
+    pub archive_id: Option<[u8; SEAL_ARCHIVE_ID_LEN]>,
+    /// The mode of the key the archive is sealed with.
+    pub key_mode: SealKeyMode,
+    /// The salt of the passphrase, random with a key as well.
+    pub salt: [u8; SEAL_SALT_LEN],
+    /// The memory cost in KiB, the number of passes and the parallelism
+    /// of the key derived from a passphrase.
+    pub kdf_costs: [u32; 3],
+    /// The nonce the manifest is encrypted under.
+    pub manifest_nonce: [u8; SEAL_NONCE_LEN],
+    /// The nonces of the chunks, in order.
+    pub chunks: Vec<ChunkNonce>,
+}
+
+impl SealAudit {
+    /// Get the indices of the chunks encrypted under the nonce of the
+    /// manifest or of a previous chunk, empty if no nonce is repeated.
+    pub fn repeated_nonces(&self) -> Vec<usize> {
+        let mut seen: HashSet<[u8; SEAL_NONCE_LEN]> =
+            HashSet::from([self.manifest_nonce]);
+
+        self.chunks
+            .iter()
+            .filter(|chunk| !seen.insert(chunk.nonce))
+            .map(|chunk| chunk.index)
+            .collect()
+    }
+}
+
+/// Result of the unseal process.
+#[derive(Debug, Clone)]
+pub struct UnsealResult {
+    /// Size of the original file in bytes.
+    pub file_size: u64,
+    /// The total number of chunks unsealed.
+    pub total_chunks: usize,
+    /// The name of the original file, as sealed.
+    pub file_name: String,
+    /// The time taken by the unseal process.
+    pub duration: Duration,
+}
+
+/// Unseal process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsealError {
+    InDirNotFound,
+    InDirNotDir,
+    InDirNotSet,
+    ManifestNotFound,
+    ManifestInvalid,
+    KeyNotSet,
+    KeyInvalid,
+    ChunkNotFound,
+    ChunkNotAuthenticated,
+    OutFileNotSet,
+    OutFileExists,
+    OutFileNotWritten,
+}
+
+impl UnsealError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::InDirNotFound => "in_dir_not_found",
+            | Self::InDirNotDir => "in_dir_not_dir",
+            | Self::InDirNotSet => "in_dir_not_set",
+            | Self::ManifestNotFound => "manifest_not_found",
+            | Self::ManifestInvalid => "manifest_invalid",
+            | Self::KeyNotSet => "key_not_set",
+            | Self::KeyInvalid => "key_invalid",
+            | Self::ChunkNotFound => "chunk_not_found",
+            | Self::ChunkNotAuthenticated => "chunk_not_authenticated",
+            | Self::OutFileNotSet => "out_file_not_set",
+            | Self::OutFileExists => "out_file_exists",
+            | Self::OutFileNotWritten => "out_file_not_written",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::InDirNotFound => "The input directory not found.",
+            | Self::InDirNotDir => "The input directory is not a directory.",
+            | Self::InDirNotSet => "The input directory is not set.",
+            | Self::ManifestNotFound => {
+                "The manifest of the archive not found."
+            },
+            | Self::ManifestInvalid => {
+                "The manifest of the archive is invalid."
+            },
+            | Self::KeyNotSet => "The key or the passphrase is not set.",
+            | Self::KeyInvalid => {
+                "The key or the passphrase does not open the archive."
+            },
+            | Self::ChunkNotFound => "A chunk of the archive not found.",
+            | Self::ChunkNotAuthenticated => {
+                "A chunk of the archive is altered or out of place."
+            },
+            | Self::OutFileNotSet => "The output file is not set.",
+            | Self::OutFileExists => "The output file already exists.",
+            | Self::OutFileNotWritten => {
+                "The output file could not be written."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Process to unseal an archive written by [`Seal`] into a file.
+///
+/// The manifest is opened first, then each chunk is authenticated before
+/// its content is written. The output is written to a `.tmp` sibling and
+/// renamed once all chunks are authenticated, so no partial or altered
+/// content is left at the output file.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::seal::{Unseal, UnsealResult};
+///
+/// let result: UnsealResult = Unseal::new()
+///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+///     .out_file(PathBuf::from("path").join("to").join("file"))
+///     .passphrase("correct horse battery staple")
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Unseal {
+    pub in_dir: Option<Arc<Path>>,
+    pub out_file: Option<Arc<Path>>,
+    pub key: Option<SealKey>,
+}
+
+impl Unseal {
+    /// Create a new unseal process.
+    pub fn new() -> Self {
+        Self { in_dir: None, out_file: None, key: None }
+    }
+
+    /// Create a new unseal process from an existing one.
+    pub fn from<P: Into<Unseal>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the input directory of the archive.
+    pub fn in_dir<InDir: AsRef<Path>>(
+        mut self,
+        path: InDir,
+    ) -> Self {
+        self.in_dir = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the output file.
+    pub fn out_file<OutFile: AsRef<Path>>(
+        mut self,
+        path: OutFile,
+    ) -> Self {
+        self.out_file = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the passphrase the archive was sealed with.
+    pub fn passphrase<Passphrase: AsRef<str>>(
+        mut self,
+        passphrase: Passphrase,
+    ) -> Self {
+        self.key = Some(SealKey::Passphrase(Arc::from(passphrase.as_ref())));
+        self
+    }
+
+    /// Set the key the archive was sealed with, instead of a passphrase.
+    pub fn key(
+        mut self,
+        key: [u8; SEAL_KEY_LEN],
+    ) -> Self {
+        self.key = Some(SealKey::Key(key));
+        self
+    }
+
+    /// Run the unseal process.
+    pub fn run(&self) -> Result<UnsealResult, UnsealError> {
+        let in_dir: &Path = self.validate_in_dir()?;
+
+        let out_file: &Path = match self.out_file {
+            | Some(ref p) if p.exists() => {
+                return Err(UnsealError::OutFileExists);
+            },
+            | Some(ref p) => p.as_ref(),
+            | None => return Err(UnsealError::OutFileNotSet),
+        };
+
+        let key: &SealKey = self.key.as_ref().ok_or(UnsealError::KeyNotSet)?;
+
+        let started: Instant = Instant::now();
+
+        let (cipher, manifest) = open_manifest(in_dir, key)?;
+
+        if let Some(parent) =
+            out_file.parent().filter(|p| !p.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .map_err(|_| UnsealError::OutFileNotWritten)?;
+        }
+
+        let write_path: PathBuf = temp_path(out_file);
+
+        let result: Result<(), UnsealError> =
+            unseal_chunks(in_dir, &cipher, &manifest, &write_path);
+
+        if let Err(error) = result {
+            let _ = fs::remove_file(&write_path);
+
+            return Err(error);
+        }
+
+        fs::rename(&write_path, out_file)
+            .map_err(|_| UnsealError::OutFileNotWritten)?;
+
+        Ok(UnsealResult {
+            file_size: manifest.file_size,
+            total_chunks: manifest.total_chunks,
+            file_name: manifest.file_name,
+            duration: started.elapsed(),
+        })
+    }
+
+    /// Audit the nonces and the key parameters of the archive,
+    /// without decrypting any chunk.
+    ///
+    /// Only the nonce prefixed to each chunk is read, so the audit can be
+    /// exported to check that no nonce is repeated, with
+    /// [`SealAudit::repeated_nonces`], and that the key derivation follows
+    /// the policy. With the key or the passphrase set, the manifest is
+    /// opened for the identifier of the archive and its number of chunks,
+    /// otherwise the chunks are read up to the first one missing.
+    /// The output file is ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::seal::{SealAudit, Unseal};
+    ///
+    /// let audit: SealAudit = Unseal::new()
+    ///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .audit()
+    ///     .unwrap();
+    ///
+    /// assert!(audit.repeated_nonces().is_empty());
+    /// ```
+    pub fn audit(&self) -> Result<SealAudit, UnsealError> {
+        let in_dir: &Path = self.validate_in_dir()?;
+
+        let data: Vec<u8> = read_manifest(in_dir)?;
+
+        let header: ManifestHeader = ManifestHeader::parse(&data)?;
+
+        let manifest_nonce: [u8; SEAL_NONCE_LEN] = header
+            .sealed
+            .get(..SEAL_NONCE_LEN)
+            .and_then(|nonce| nonce.try_into().ok())
+            .ok_or(UnsealError::ManifestInvalid)?;
+
+        let manifest: Option<SealManifest> = match self.key {
+            | Some(ref key) => Some(open_manifest(in_dir, key)?.1),
+            | None => None,
+        };
+
+        let naming: ChunkNaming = ChunkNaming::new();
+
+        let mut chunks: Vec<ChunkNonce> = Vec::new();
+
+        loop {
+            let index: usize = chunks.len();
+
+            if manifest.as_ref().is_some_and(|m| index >= m.total_chunks) {
+                break;
+            }
+
+            let mut file: fs::File =
+                match fs::File::open(in_dir.join(naming.file_name(index))) {
+                    | Ok(file) => file,
+                    | Err(_) if manifest.is_none() => break,
+                    | Err(_) => return Err(UnsealError::ChunkNotFound),
+                };
+
+            let mut nonce: [u8; SEAL_NONCE_LEN] = [0; SEAL_NONCE_LEN];
+
+            file.read_exact(&mut nonce)
+                .map_err(|_| UnsealError::ChunkNotAuthenticated)?;
+
+            chunks.push(ChunkNonce { index, nonce });
+        }
+
+        Ok(SealAudit {
+            archive_id: manifest.map(|m| m.archive_id),
+            key_mode: header.key_mode,
+            salt: header.salt,
+            kdf_costs: header.costs,
+            manifest_nonce,
+            chunks,
+        })
+    }
+
+    /// Validate the input directory.
+    fn validate_in_dir(&self) -> Result<&Path, UnsealError> {
+        let p: &Path = match self.in_dir {
+            | Some(ref p) => p.as_ref(),
+            | None => return Err(UnsealError::InDirNotSet),
+        };
+
+        // if in_dir not exists
+        if !p.exists() {
+            return Err(UnsealError::InDirNotFound);
+        }
+
+        // if in_dir not a directory
+        if !p.is_dir() {
+            return Err(UnsealError::InDirNotDir);
+        }
+
+        Ok(p)
+    }
+}
+
+impl Default for Unseal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Manifest of a sealed archive, as encrypted.
+struct SealManifest {
+    /// The random identifier of the archive, bound to its chunks.
+    archive_id: [u8; SEAL_ARCHIVE_ID_LEN],
+    /// The size of the original file in bytes.
+    file_size: u64,
+    /// The total number of chunks.
+    total_chunks: usize,
+    /// The name of the original file.
+    file_name: String,
+}
+
+impl SealManifest {
+    /// Encode the manifest.
+    fn encode(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+
+        data.extend_from_slice(&self.archive_id);
+        data.extend_from_slice(&self.file_size.to_le_bytes());
+        data.extend_from_slice(&(self.total_chunks as u64).to_le_bytes());
+        data.extend_from_slice(self.file_name.as_bytes());
+
+        data
+    }
+
+    /// Decode the manifest.
+    fn decode(data: &[u8]) -> Option<Self> {
+        let (archive_id, rest) = data.split_at_checked(SEAL_ARCHIVE_ID_LEN)?;
+        let (file_size, rest) = rest.split_at_checked(8)?;
+        let (total_chunks, file_name) = rest.split_at_checked(8)?;
+
+        Some(Self {
+            archive_id: archive_id.try_into().ok()?,
+            file_size: u64::from_le_bytes(file_size.try_into().ok()?),
+            total_chunks: usize::try_from(u64::from_le_bytes(
+                total_chunks.try_into().ok()?,
+            ))
+            .ok()?,
+            file_name: String::from_utf8(file_name.to_vec()).ok()?,
+        })
+    }
+}
+
+/// Header of the manifest of a sealed archive, in plain text.
+struct ManifestHeader<'a> {
+    /// The header as stored, authenticated with the manifest.
+    header: &'a [u8],
+    /// The encrypted manifest, prefixed by its nonce.
+    sealed: &'a [u8],
+    /// The mode of the key.
+    key_mode: SealKeyMode,
+    /// The salt of the passphrase.
+    salt: [u8; SEAL_SALT_LEN],
+    /// The costs of the key derived from a passphrase.
+    costs: [u32; 3],
+}
+
+impl<'a> ManifestHeader<'a> {
+    /// Parse the header of the manifest data.
+    fn parse(data: &'a [u8]) -> Result<Self, UnsealError> {
+        let (header, sealed) = data
+            .split_at_checked(HEADER_LEN)
+            .filter(|(h, _)| h.starts_with(MAGIC) && h[MAGIC.len()] == VERSION)
+            .ok_or(UnsealError::ManifestInvalid)?;
+
+        let key_mode: SealKeyMode =
+            SealKeyMode::from_byte(header[MAGIC.len() + 1])
+                .ok_or(UnsealError::ManifestInvalid)?;
+
+        let start: usize = MAGIC.len() + 2;
+
+        let salt: [u8; SEAL_SALT_LEN] = header[start..start + SEAL_SALT_LEN]
+            .try_into()
+            .map_err(|_| UnsealError::ManifestInvalid)?;
+
+        let mut costs: [u32; 3] = [0; 3];
+
+        for (i, cost) in costs.iter_mut().enumerate() {
+            let start: usize = start + SEAL_SALT_LEN + i * 4;
+
+            *cost = u32::from_le_bytes(
+                header[start..start + 4]
+                    .try_into()
+                    .map_err(|_| UnsealError::ManifestInvalid)?,
+            );
+        }
+
+        Ok(Self { header, sealed, key_mode, salt, costs })
+    }
+}
+
+/// Read the manifest of an archive.
+fn read_manifest(in_dir: &Path) -> Result<Vec<u8>, UnsealError> {
+    fs::read(in_dir.join(SEAL_MANIFEST_NAME)).map_err(|e| match e.kind() {
+        | io::ErrorKind::NotFound => UnsealError::ManifestNotFound,
+        | _ => UnsealError::ManifestInvalid,
+    })
+}
+
+/// Open the manifest of an archive with the key.
+fn open_manifest(
+    in_dir: &Path,
+    key: &SealKey,
+) -> Result<(XChaCha20Poly1305, SealManifest), UnsealError> {
+    let data: Vec<u8> = read_manifest(in_dir)?;
+
+    let header: ManifestHeader = ManifestHeader::parse(&data)?;
+
+    // the key must be of the same mode as sealed with
+    if header.key_mode != key.mode() {
+        return Err(UnsealError::KeyInvalid);
+    }
+
+    let cipher: XChaCha20Poly1305 = key
+        .derive(&header.salt, header.costs)
+        .ok_or(UnsealError::ManifestInvalid)?;
+
+    let manifest: SealManifest = decrypt(&cipher, header.header, header.sealed)
+        .as_deref()
+        .and_then(SealManifest::decode)
+        .ok_or(UnsealError::KeyInvalid)?;
+
+    Ok((cipher, manifest))
+}
+
+/// Authenticate and write the chunks of an archive to the path.
+fn unseal_chunks(
+    in_dir: &Path,
+    cipher: &XChaCha20Poly1305,
+    manifest: &SealManifest,
+    write_path: &Path,
+) -> Result<(), UnsealError> {
+    let naming: ChunkNaming = ChunkNaming::new();
+
+    let output: fs::File = fs::File::create(write_path)
+        .map_err(|_| UnsealError::OutFileNotWritten)?;
+
+    let mut writer: io::BufWriter<fs::File> = io::BufWriter::new(output);
+
+    let mut file_size: u64 = 0;
+
+    for index in 0..manifest.total_chunks {
+        let sealed: Vec<u8> = fs::read(in_dir.join(naming.file_name(index)))
+            .map_err(|_| UnsealError::ChunkNotFound)?;
+
+        let data: Vec<u8> =
+            decrypt(cipher, &chunk_aad(&manifest.archive_id, index), &sealed)
+                .ok_or(UnsealError::ChunkNotAuthenticated)?;
+
+        writer.write_all(&data).map_err(|_| UnsealError::OutFileNotWritten)?;
+
+        file_size += data.len() as u64;
+    }
+
+    // the chunks are authenticated one by one, not their total
+    if file_size != manifest.file_size {
+        return Err(UnsealError::ChunkNotAuthenticated);
+    }
+
+    writer
+        .into_inner()
+        .map_err(|_| UnsealError::OutFileNotWritten)?
+        .sync_all()
+        .map_err(|_| UnsealError::OutFileNotWritten)
+}
+
+/// Get the associated data binding a chunk to its archive and index.
+fn chunk_aad(
+    archive_id: &[u8; SEAL_ARCHIVE_ID_LEN],
+    index: usize,
+) -> Vec<u8> {
+    let mut aad: Vec<u8> = Vec::with_capacity(SEAL_ARCHIVE_ID_LEN + 8);
+
+    aad.extend_from_slice(archive_id);
+    aad.extend_from_slice(&(index as u64).to_le_bytes());
+
+    aad
+}
+
+/// Encrypt the data under a random nonce, prepended to the ciphertext.
+fn encrypt(
+    cipher: &XChaCha20Poly1305,
+    aad: &[u8],
+    data: &[u8],
+) -> Option<Vec<u8>> {
+    let nonce: XNonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext: Vec<u8> =
+        cipher.encrypt(&nonce, Payload { msg: data, aad }).ok()?;
+
+    let mut sealed: Vec<u8> =
+        Vec::with_capacity(SEAL_NONCE_LEN + ciphertext.len());
+
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+
+    Some(sealed)
+}
+
+/// Decrypt the data sealed by [`encrypt`].
+fn decrypt(
+    cipher: &XChaCha20Poly1305,
+    aad: &[u8],
+    sealed: &[u8],
+) -> Option<Vec<u8>> {
+    let (nonce, ciphertext) = sealed.split_at_checked(SEAL_NONCE_LEN)?;
+
+    cipher
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .ok()
+}
@@ -0,0 +1,246 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{
+    chunk::ChunkInfo,
+    hash::Digest,
+    lock::FileLock,
+    progress::ProgressGuard,
+    split::{ChunkLayout, Split, SplitError, SplitResult},
+};
+
+/// Run asynchronously with `tokio` feature.
+///
+/// To use it, add the following code to the `Cargo.toml` file:
+///
+/// ```toml
+/// [dependencies]
+/// filerune_fusion = { version = "*", features = ["tokio"] }
+/// ```
+#[cfg(feature = "tokio")]
+pub mod tokio {
+    pub use crate::tokio::sink::AsyncSplitSink;
+}
+
+/// Writer splitting the bytes written to it into chunks,
+/// without the whole input in a file.
+///
+/// The bytes are buffered up to the size of the next chunk,
+/// and the chunk is written to the output directory once full,
+/// so the data of a socket or a compressor is split as it arrives.
+/// The last chunk and the files written next to the chunks, such as the
+/// parity chunks, are written by [`SplitSink::finish`].
+///
+/// The input file, the total number of chunks and the resume option
+/// of the process are ignored, as the size of the input is unknown.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{io, net::TcpStream, path::PathBuf};
+///
+/// use filerune_fusion::{
+///     sink::SplitSink,
+///     split::{Split, SplitResult},
+/// };
+///
+/// let mut stream: TcpStream = TcpStream::connect("127.0.0.1:8080").unwrap();
+///
+/// let mut sink: SplitSink = Split::new()
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .chunk_size(1024 * 1024)
+///     .sink()
+///     .unwrap();
+///
+/// io::copy(&mut stream, &mut sink).unwrap();
+///
+/// let result: SplitResult = sink.finish().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SplitSink {
+    process: Split,
+    out_dir: PathBuf,
+    layout: ChunkLayout,
+    buffer: Vec<u8>,
+    chunks: Vec<ChunkInfo>,
+    file_size: u64,
+    started: Instant,
+    progress: ProgressGuard,
+    // the error failing the writes, returned once finished
+    error: Option<SplitError>,
+    _lock: Option<FileLock>,
+}
+
+impl SplitSink {
+    /// Create a new sink writing the chunks to the output directory
+    /// of a split process.
+    ///
+    /// The output directory is prepared and locked, if enabled,
+    /// before the first byte is written.
+    pub fn new(process: &Split) -> Result<Self, SplitError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(process.progress.as_ref());
+
+        let lock: Option<FileLock> = process.lock_out_dir(None)?;
+
+        let out_dir: PathBuf = process.prepare_out_dir()?.to_path_buf();
+
+        // the input is the bytes written, of an unknown size
+        let mut process: Split = process.clone();
+
+        process.in_file = None;
+        process.total_chunks = None;
+        process.resume = false;
+
+        let layout: ChunkLayout = process.layout(None)?;
+
+        Ok(Self {
+            process,
+            out_dir,
+            buffer: Vec::with_capacity(layout.max_len()),
+            layout,
+            chunks: Vec::new(),
+            file_size: 0,
+            started: Instant::now(),
+            progress,
+            error: None,
+            _lock: lock,
+        })
+    }
+
+    /// Get the number of bytes written to the sink.
+    pub fn bytes_written(&self) -> u64 {
+        self.file_size + self.buffer.len() as u64
+    }
+
+    /// Get the chunks written to the output directory, in order.
+    ///
+    /// The bytes buffered for the next chunk are not included.
+    pub fn chunks(&self) -> &[ChunkInfo] {
+        &self.chunks
+    }
+
+    /// Write the chunks left and the files written next to the chunks,
+    /// and get the result of the split process.
+    ///
+    /// Fails with the error of a previous write, if any.
+    pub fn finish(mut self) -> Result<SplitResult, SplitError> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        while !self.buffer.is_empty() {
+            self.write_chunk(self.buffer.len())?;
+        }
+
+        let process: &Split = &self.process;
+
+        let out_dir: &Path = &self.out_dir;
+
+        process.write_parity(out_dir, &self.chunks)?;
+
+        let bao_hash: Option<Digest> =
+            process.write_bao(out_dir, &self.chunks)?;
+
+        process.write_metadata(out_dir)?;
+
+        for (name, script) in process.merge_scripts(&self.chunks) {
+            fs::write(out_dir.join(name), script)
+                .map_err(|_| SplitError::OutFileNotWritten)?;
+        }
+
+        process
+            .sync
+            .sync_dir(out_dir)
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+
+        self.progress.finish();
+
+        Ok(SplitResult {
+            file_size: self.file_size,
+            total_chunks: self.chunks.len(),
+            chunk_size: self.layout.chunk_size,
+            chunks: self.chunks,
+            duration: self.started.elapsed(),
+            chunks_resumed: 0,
+            bao_hash,
+        })
+    }
+
+    /// Write the next chunk cut from the bytes buffered, up to the size.
+    fn write_chunk(
+        &mut self,
+        size: usize,
+    ) -> Result<(), SplitError> {
+        if self.process.is_cancelled() {
+            self.process.remove_chunks(&self.out_dir, self.chunks.len());
+
+            return Err(SplitError::Cancelled);
+        }
+
+        if self.process.is_expired() {
+            return Err(SplitError::DeadlineExceeded);
+        }
+
+        let index: usize = self.chunks.len();
+
+        let len: usize = self.layout.cut(&self.buffer[..size]);
+
+        let chunk: ChunkInfo = self.process.write_chunk(
+            &self.out_dir,
+            &self.layout,
+            None,
+            index,
+            &self.buffer[..len],
+        )?;
+
+        self.progress.advance(index, len);
+
+        self.file_size += len as u64;
+
+        self.chunks.push(chunk);
+
+        self.buffer.drain(..len);
+
+        Ok(())
+    }
+}
+
+impl io::Write for SplitSink {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> io::Result<usize> {
+        if let Some(ref error) = self.error {
+            return Err(io::Error::other(error.to_message()));
+        }
+
+        let size: usize = self.layout.len(self.chunks.len());
+
+        // the bytes up to the end of the next chunk
+        let len: usize = (size - self.buffer.len()).min(buf.len());
+
+        self.buffer.extend_from_slice(&buf[..len]);
+
+        if self.buffer.len() == size {
+            if let Err(error) = self.write_chunk(size) {
+                let e: io::Error = io::Error::other(error.to_message());
+
+                self.error = Some(error);
+
+                return Err(e);
+            }
+        }
+
+        Ok(len)
+    }
+
+    /// The bytes are kept in the buffer until the next chunk is full,
+    /// so nothing is written by a flush.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
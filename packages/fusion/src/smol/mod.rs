@@ -3,3 +3,7 @@ pub mod split;
 pub mod check;
 
 pub mod merge;
+
+pub mod compare;
+
+pub mod runtime;
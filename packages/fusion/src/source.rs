@@ -0,0 +1,221 @@
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::store::{ChunkStore, StoreError};
+
+/// Statistics of a chunk source in a pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceStats {
+    /// The smoothed latency of the source, `None` if not measured yet.
+    pub latency: Option<Duration>,
+    /// Whether the source answered its last request.
+    pub healthy: bool,
+    /// The number of chunks read from the source.
+    pub reads: usize,
+    /// The number of bytes read from the source.
+    pub bytes: u64,
+    /// The number of requests the source failed to answer.
+    pub failures: usize,
+}
+
+impl SourceStats {
+    /// Record a request answered in the duration.
+    fn succeed(
+        &mut self,
+        duration: Duration,
+    ) {
+        self.latency = Some(match self.latency {
+            | Some(latency) => (latency * 3 + duration) / 4,
+            | None => duration,
+        });
+        self.healthy = true;
+    }
+
+    /// Record a request not answered.
+    fn fail(&mut self) {
+        self.healthy = false;
+        self.failures += 1;
+    }
+}
+
+/// Pool of chunk sources holding the same chunks, such as mirrors.
+///
+/// Each chunk is read from the fastest healthy source first, falling back to
+/// the other sources if it is missing or the source fails. The sources are
+/// ranked by the latency measured with [`SourcePool::probe`] and the reads,
+/// and a failed source is only tried last until it answers again.
+/// Chunks written to the pool are written to the first source.
+/// Sources of different kinds can be pooled as `Box<dyn ChunkStore>`.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     chunk::ChunkNaming,
+///     source::{SourcePool, SourceStats},
+///     store::{ChunkStore as _, DirStore},
+/// };
+///
+/// let pool: SourcePool<DirStore> = SourcePool::new(vec![
+///     DirStore::new(PathBuf::from("mirror").join("a"), ChunkNaming::new()),
+///     DirStore::new(PathBuf::from("mirror").join("b"), ChunkNaming::new()),
+/// ]);
+///
+/// pool.probe(0);
+///
+/// let chunk: Vec<u8> = pool.read(0).unwrap();
+///
+/// let stats: Vec<SourceStats> = pool.stats();
+/// ```
+#[derive(Debug)]
+pub struct SourcePool<S: ChunkStore> {
+    sources: Vec<S>,
+    stats: Mutex<Vec<SourceStats>>,
+}
+
+impl<S: ChunkStore> SourcePool<S> {
+    /// Create a new pool of the sources.
+    pub fn new(sources: Vec<S>) -> Self {
+        let stats: Vec<SourceStats> =
+            vec![SourceStats::default(); sources.len()];
+
+        Self { sources, stats: Mutex::new(stats) }
+    }
+
+    /// Get the sources of the pool.
+    pub fn sources(&self) -> &[S] {
+        &self.sources
+    }
+
+    /// Get the statistics of each source, in the order of the sources.
+    pub fn stats(&self) -> Vec<SourceStats> {
+        self.lock().clone()
+    }
+
+    /// Probe the availability and the latency of each source
+    /// by looking up the chunk.
+    ///
+    /// Returns whether each source holds the chunk,
+    /// `None` if the source failed to answer.
+    pub fn probe(
+        &self,
+        index: usize,
+    ) -> Vec<Option<bool>> {
+        self.sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| {
+                let started: Instant = Instant::now();
+
+                let result: Result<bool, StoreError> = source.contains(index);
+
+                let mut stats: MutexGuard<Vec<SourceStats>> = self.lock();
+
+                match result {
+                    | Ok(found) => {
+                        stats[i].succeed(started.elapsed());
+
+                        Some(found)
+                    },
+                    | Err(_) => {
+                        stats[i].fail();
+
+                        None
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Get the indices of the sources, from the fastest healthy one.
+    pub fn ranking(&self) -> Vec<usize> {
+        let stats: MutexGuard<Vec<SourceStats>> = self.lock();
+
+        let mut ranking: Vec<usize> = (0..self.sources.len()).collect();
+
+        // the sources not measured yet come after the measured ones
+        ranking.sort_by_key(|&i| {
+            (!stats[i].healthy, stats[i].latency.unwrap_or(Duration::MAX))
+        });
+
+        ranking
+    }
+
+    /// Lock the statistics of the sources.
+    fn lock(&self) -> MutexGuard<'_, Vec<SourceStats>> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<S: ChunkStore> ChunkStore for SourcePool<S> {
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError> {
+        let mut result: Result<bool, StoreError> = Ok(false);
+
+        for i in self.ranking() {
+            match self.sources[i].contains(index) {
+                | Ok(true) => return Ok(true),
+                | Ok(false) => {},
+                | Err(error) => {
+                    self.lock()[i].fail();
+
+                    result = Err(error);
+                },
+            }
+        }
+
+        result
+    }
+
+    fn read(
+        &self,
+        index: usize,
+    ) -> Result<Vec<u8>, StoreError> {
+        let mut error: StoreError = StoreError::ChunkNotFound;
+
+        for i in self.ranking() {
+            let started: Instant = Instant::now();
+
+            let result: Result<Vec<u8>, StoreError> =
+                self.sources[i].read(index);
+
+            let mut stats: MutexGuard<Vec<SourceStats>> = self.lock();
+
+            match result {
+                | Ok(data) => {
+                    stats[i].succeed(started.elapsed());
+                    stats[i].reads += 1;
+                    stats[i].bytes += data.len() as u64;
+
+                    return Ok(data);
+                },
+                // the source is healthy without the chunk
+                | Err(StoreError::ChunkNotFound) => {},
+                | Err(e) => {
+                    stats[i].fail();
+
+                    error = e;
+                },
+            }
+        }
+
+        Err(error)
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        match self.sources.first_mut() {
+            | Some(source) => source.write(index, data),
+            | None => Err(StoreError::ChunkNotWritten),
+        }
+    }
+}
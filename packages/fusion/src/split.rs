@@ -1,10 +1,55 @@
 use std::{
-    fs,
-    io::{self, Read as _, Write as _},
+    borrow::Cow,
+    fmt, fs,
+    io::{self, Read as _, Seek as _, Write as _},
+    panic,
     path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use crate::{BUFFER_CAPACITY_DEFAULT, CHUNK_SIZE_DEFAULT};
+#[cfg(feature = "compression")]
+use crate::codec::Codec;
+
+#[cfg(feature = "fountain")]
+use crate::fountain::FountainEncoder;
+
+#[cfg(feature = "reqwest")]
+use crate::store::RefreshHandler;
+
+use crate::{
+    BUFFER_CAPACITY_DEFAULT, CHUNK_SIZE_DEFAULT,
+    bao::{self, ChunkContents, OUTBOARD_EXTENSION},
+    cancel::CancellationToken,
+    check::{Check, CheckError, CheckReport},
+    chunk::{ChunkHandler, ChunkInfo, ChunkNaming, temp_path},
+    hash::{Digest, HashAlgorithm, Hasher},
+    header::{self, ChunkFormat},
+    layout::chunk_count,
+    lock::{FileLock, LockError, LockMode},
+    metadata::{FileMetadata, metadata_path},
+    multipart::MultipartSplit,
+    par2::{self, PAR2_SLICES_MAX},
+    parity::{self, PARITY_SHARDS_MAX},
+    preset::Preset,
+    progress::{Progress, ProgressGuard},
+    reflink::ReflinkPolicy,
+    report::Report,
+    script::{self, BATCH_SCRIPT_NAME, SHELL_SCRIPT_NAME},
+    sink::SplitSink,
+    store::ChunkStore,
+    strategy::ChunkStrategy,
+    sync::SyncPolicy,
+    sys::{
+        DiskSpace, clone_range, copy_range, disk_space, is_hole, long_path,
+        long_path_shared, read_options,
+    },
+    warning::{Warning, WarningHandler},
+};
 
 /// Run asynchronously with `async_std` feature.
 ///
@@ -45,13 +90,87 @@ pub mod tokio {
     pub use crate::tokio::split::SplitAsyncExt;
 }
 
+/// Run asynchronously with `tokio_uring` feature, on Linux.
+///
+/// To use it, add the following code to the `Cargo.toml` file:
+///
+/// ```toml
+/// [dependencies]
+/// filerune_fusion = { version = "*", features = ["tokio_uring"] }
+/// ```
+#[cfg(all(feature = "tokio_uring", target_os = "linux"))]
+pub mod tokio_uring {
+    pub use crate::tokio_uring::split::SplitAsyncExt;
+}
+
+/// The algorithm to hash the chunks read back with [`Split::verify_writes`].
+pub(crate) const VERIFY_ALGORITHM: HashAlgorithm = HashAlgorithm::Blake3;
+
 /// Result of the split process.
 #[derive(Debug, Clone)]
 pub struct SplitResult {
     /// Size of the original file in bytes.
-    pub file_size: usize,
+    pub file_size: u64,
+    /// The total number of chunks splitted from the original file.
+    pub total_chunks: usize,
+    /// The size of each chunk used in bytes, the last chunk may differ.
+    /// The maximum size of the chunks with content-defined chunks.
+    pub chunk_size: usize,
+    /// The chunks written to the output directory, in order.
+    pub chunks: Vec<ChunkInfo>,
+    /// The time taken by the split process.
+    pub duration: Duration,
+    /// The number of chunks kept from a previous run when resumed.
+    pub chunks_resumed: usize,
+    /// The BLAKE3 hash of the file, the root of the outboard tree
+    /// written with [`Split::bao`].
+    pub bao_hash: Option<Digest>,
+}
+
+/// Result of the repair of the chunks by [`Split::repair_into`].
+#[derive(Debug, Clone)]
+pub struct RepairResult {
+    /// Size of the original file in bytes.
+    pub file_size: u64,
     /// The total number of chunks splitted from the original file.
     pub total_chunks: usize,
+    /// The chunks re-written to the directory, in order.
+    pub repaired: Vec<ChunkInfo>,
+    /// The time taken by the repair.
+    pub duration: Duration,
+}
+
+/// Policy for a non-empty output directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Fail with [`SplitError::OutDirNotEmpty`].
+    Fail,
+    /// Remove the entries of the output directory before splitting.
+    Clean,
+    /// Write the chunks alongside the existing entries,
+    /// reported as [`Warning::OutDirNotEmpty`].
+    #[default]
+    Reuse,
+}
+
+/// Policy for an input file changed during the split.
+///
+/// The input file is checked by its size and its modification time
+/// once the chunks are split, or when it is read short.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SourceChangePolicy {
+    /// Fail with [`SplitError::SourceChangedDuringSplit`].
+    #[default]
+    Fail,
+    /// Split the file again from the start, up to the number of retries,
+    /// then fail with [`SplitError::SourceChangedDuringSplit`].
+    Retry(usize),
+    /// Keep the chunks split from the content read,
+    /// reported as [`Warning::SourceChanged`].
+    ///
+    /// A file shrunk is not read to the end, so it still fails with
+    /// [`SplitError::SourceChangedDuringSplit`].
+    Warn,
 }
 
 /// Split process error enum.
@@ -62,11 +181,31 @@ pub enum SplitError {
     InFileNotSet,
     InFileNotOpened,
     InFileNotRead,
+    SourceChangedDuringSplit,
+    InUrlNotRead,
+    InUrlExpired,
+    InReaderConsumed,
     OutDirNotCreated,
     OutDirNotDir,
     OutDirNotSet,
+    OutDirNotEmpty,
+    OutDirNotCleaned,
     OutFileNotOpened,
     OutFileNotWritten,
+    OutFileNotCloned,
+    ChunksNotVerified,
+    InFileNotRemoved,
+    TotalChunksInvalid,
+    ChunkStrategyInvalid,
+    InFileSizeUnknown,
+    ParityLimitExceeded,
+    FountainLimitExceeded,
+    InsufficientSpace,
+    Locked,
+    LockNotAcquired,
+    WarningRaised,
+    Cancelled,
+    DeadlineExceeded,
 }
 
 impl SplitError {
@@ -78,11 +217,31 @@ impl SplitError {
             | Self::InFileNotSet => "in_file_not_set",
             | Self::InFileNotOpened => "in_file_not_opened",
             | Self::InFileNotRead => "in_file_not_read",
+            | Self::SourceChangedDuringSplit => "source_changed_during_split",
+            | Self::InUrlNotRead => "in_url_not_read",
+            | Self::InUrlExpired => "in_url_expired",
+            | Self::InReaderConsumed => "in_reader_consumed",
             | Self::OutDirNotCreated => "out_dir_not_created",
             | Self::OutDirNotDir => "out_dir_not_dir",
             | Self::OutDirNotSet => "out_dir_not_set",
+            | Self::OutDirNotEmpty => "out_dir_not_empty",
+            | Self::OutDirNotCleaned => "out_dir_not_cleaned",
             | Self::OutFileNotOpened => "out_file_not_opened",
             | Self::OutFileNotWritten => "out_file_not_written",
+            | Self::OutFileNotCloned => "out_file_not_cloned",
+            | Self::ChunksNotVerified => "chunks_not_verified",
+            | Self::InFileNotRemoved => "in_file_not_removed",
+            | Self::TotalChunksInvalid => "total_chunks_invalid",
+            | Self::ChunkStrategyInvalid => "chunk_strategy_invalid",
+            | Self::InFileSizeUnknown => "in_file_size_unknown",
+            | Self::ParityLimitExceeded => "parity_limit_exceeded",
+            | Self::FountainLimitExceeded => "fountain_limit_exceeded",
+            | Self::InsufficientSpace => "insufficient_space",
+            | Self::Locked => "locked",
+            | Self::LockNotAcquired => "lock_not_acquired",
+            | Self::WarningRaised => "warning_raised",
+            | Self::Cancelled => "cancelled",
+            | Self::DeadlineExceeded => "deadline_exceeded",
         }
     }
 
@@ -99,17 +258,65 @@ impl SplitError {
             | Self::InFileNotSet => "The input file is not set.",
             | Self::InFileNotOpened => "The input file could not be opened.",
             | Self::InFileNotRead => "The input file could not be read.",
+            | Self::SourceChangedDuringSplit => {
+                "The input file changed during the split process."
+            },
+            | Self::InUrlNotRead => "The input URL could not be read.",
+            | Self::InUrlExpired => "The input URL is expired.",
+            | Self::InReaderConsumed => {
+                "The input reader is already consumed by a previous run."
+            },
             | Self::OutDirNotCreated => {
                 "The output directory could not be created."
             },
             | Self::OutDirNotDir => "The output directory is not a directory.",
             | Self::OutDirNotSet => "The output directory is not set.",
+            | Self::OutDirNotEmpty => "The output directory is not empty.",
+            | Self::OutDirNotCleaned => {
+                "The output directory could not be cleaned."
+            },
             | Self::OutFileNotOpened => {
                 "The output file could not be created or opened."
             },
             | Self::OutFileNotWritten => {
                 "The output file could not be written."
             },
+            | Self::OutFileNotCloned => {
+                "The chunk could not be cloned from the input file."
+            },
+            | Self::ChunksNotVerified => {
+                "The chunks written do not match the input file."
+            },
+            | Self::InFileNotRemoved => "The input file could not be removed.",
+            | Self::TotalChunksInvalid => {
+                "The total number of chunks is zero or larger than the file size."
+            },
+            | Self::ChunkStrategyInvalid => {
+                "The sizes of the content-defined chunks are zero or out of order."
+            },
+            | Self::InFileSizeUnknown => {
+                "The input size is unknown to split by the total number of chunks."
+            },
+            | Self::ParityLimitExceeded => {
+                "The total number of data and parity chunks exceeds the limit."
+            },
+            | Self::FountainLimitExceeded => {
+                "The input is too large to be encoded with the symbol size."
+            },
+            | Self::InsufficientSpace => {
+                "The output directory has not enough free space for the chunks."
+            },
+            | Self::Locked => {
+                "The output directory is locked by another process."
+            },
+            | Self::LockNotAcquired => {
+                "The lock of the output directory could not be acquired."
+            },
+            | Self::WarningRaised => "A warning was raised in strict mode.",
+            | Self::Cancelled => "The split process was cancelled.",
+            | Self::DeadlineExceeded => {
+                "The deadline of the split process was exceeded."
+            },
         }
     }
 
@@ -121,6 +328,10 @@ impl SplitError {
 
 /// Process to split file from a path to a directory.
 ///
+/// On Windows, the paths are made absolute with the `\\?\` prefix,
+/// so the chunks can be written past the 260 characters of `MAX_PATH`
+/// and to UNC shares.
+///
 /// ## Example
 ///
 /// ```no_run
@@ -136,10 +347,47 @@ impl SplitError {
 /// ```
 #[derive(Debug, Clone)]
 pub struct Split {
-    pub in_file: Option<PathBuf>,
-    pub out_dir: Option<PathBuf>,
+    pub in_file: Option<Arc<Path>>,
+    #[cfg(feature = "reqwest")]
+    pub in_url: Option<Arc<str>>,
+    #[cfg(feature = "reqwest")]
+    pub on_url_expired: Option<RefreshHandler>,
+    pub in_reader: Option<InReader>,
+    pub out_dir: Option<Arc<Path>>,
     pub chunk_size: usize,
+    pub chunk_schedule: Vec<usize>,
+    pub strategy: ChunkStrategy,
+    pub total_chunks: Option<usize>,
+    pub align: usize,
     pub buffer_capacity: usize,
+    pub naming: ChunkNaming,
+    pub format: ChunkFormat,
+    pub cancellation_token: Option<CancellationToken>,
+    pub deadline: Option<Instant>,
+    pub progress: Option<Progress>,
+    pub on_warning: Option<WarningHandler>,
+    pub on_first_chunk: Option<ChunkHandler>,
+    pub threads: usize,
+    pub merge_script: bool,
+    pub resume: bool,
+    pub overwrite: OverwritePolicy,
+    pub source_change: SourceChangePolicy,
+    pub remove_source: bool,
+    pub atomic: bool,
+    pub sync: SyncPolicy,
+    pub reflink: ReflinkPolicy,
+    #[cfg(feature = "compression")]
+    pub compression: Option<Codec>,
+    pub parity_chunks: usize,
+    pub par2_recovery: usize,
+    pub bao: bool,
+    pub check_space: bool,
+    pub space_reserve: u64,
+    pub lock: bool,
+    pub verify_writes: bool,
+    pub sparse: bool,
+    pub preserve_metadata: bool,
+    pub strict: bool,
 }
 
 impl Split {
@@ -147,9 +395,46 @@ impl Split {
     pub fn new() -> Self {
         Self {
             in_file: None,
+            #[cfg(feature = "reqwest")]
+            in_url: None,
+            #[cfg(feature = "reqwest")]
+            on_url_expired: None,
+            in_reader: None,
             out_dir: None,
             chunk_size: CHUNK_SIZE_DEFAULT,
+            chunk_schedule: Vec::new(),
+            strategy: ChunkStrategy::Fixed,
+            total_chunks: None,
+            align: 1,
             buffer_capacity: BUFFER_CAPACITY_DEFAULT,
+            naming: ChunkNaming::new(),
+            format: ChunkFormat::Raw,
+            cancellation_token: None,
+            deadline: None,
+            progress: None,
+            on_warning: None,
+            on_first_chunk: None,
+            threads: 1,
+            merge_script: false,
+            resume: false,
+            overwrite: OverwritePolicy::Reuse,
+            source_change: SourceChangePolicy::Fail,
+            remove_source: false,
+            atomic: false,
+            sync: SyncPolicy::None,
+            reflink: ReflinkPolicy::Never,
+            #[cfg(feature = "compression")]
+            compression: None,
+            parity_chunks: 0,
+            par2_recovery: 0,
+            bao: false,
+            check_space: false,
+            space_reserve: 0,
+            lock: false,
+            verify_writes: false,
+            sparse: false,
+            preserve_metadata: false,
+            strict: false,
         }
     }
 
@@ -163,7 +448,66 @@ impl Split {
         mut self,
         path: InFile,
     ) -> Self {
-        self.in_file = Some(path.as_ref().to_path_buf());
+        self.in_file = Some(long_path(path.as_ref()));
+        self
+    }
+
+    /// Set the input file with a shared path.
+    ///
+    /// Unlike [`Split::in_file`], an [`Arc<Path>`] is stored without copying,
+    /// unless extended on Windows.
+    pub fn in_file_shared<InFile: Into<Arc<Path>>>(
+        mut self,
+        path: InFile,
+    ) -> Self {
+        self.in_file = Some(long_path_shared(path.into()));
+        self
+    }
+
+    /// Set the URL of a remote input, instead of the input file.
+    ///
+    /// The resource is downloaded and split in one pass. When resumed,
+    /// the download starts after the chunks already written with a `Range`
+    /// request. The size of the resource is requested with `HEAD` to resume
+    /// or to split by the total number of chunks.
+    /// It only applies to [`Split::run`].
+    #[cfg(feature = "reqwest")]
+    pub fn in_url<InUrl: AsRef<str>>(
+        mut self,
+        url: InUrl,
+    ) -> Self {
+        self.in_url = Some(Arc::from(url.as_ref()));
+        self
+    }
+
+    /// Set a reader as the input, instead of the input file,
+    /// such as the standard input.
+    ///
+    /// The reader is split in one pass, and the file size is counted from
+    /// the bytes read. The size of the input is required to resume or to
+    /// split by the total number of chunks, `None` if unknown. The reader
+    /// is consumed by the first run, a clone of the process shares it.
+    /// It only applies to [`Split::run`].
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::{io, path::PathBuf};
+    ///
+    /// use filerune_fusion::split::{Split, SplitResult};
+    ///
+    /// let result: SplitResult = Split::new()
+    ///     .in_reader(io::stdin(), None)
+    ///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn in_reader<R: io::Read + Send + 'static>(
+        mut self,
+        reader: R,
+        size: Option<u64>,
+    ) -> Self {
+        self.in_reader = Some(InReader::new(reader, size));
         self
     }
 
@@ -172,7 +516,19 @@ impl Split {
         mut self,
         path: OutDir,
     ) -> Self {
-        self.out_dir = Some(path.as_ref().to_path_buf());
+        self.out_dir = Some(long_path(path.as_ref()));
+        self
+    }
+
+    /// Set the output directory with a shared path.
+    ///
+    /// Unlike [`Split::out_dir`], an [`Arc<Path>`] is stored without copying,
+    /// unless extended on Windows.
+    pub fn out_dir_shared<OutDir: Into<Arc<Path>>>(
+        mut self,
+        path: OutDir,
+    ) -> Self {
+        self.out_dir = Some(long_path_shared(path.into()));
         self
     }
 
@@ -187,118 +543,2745 @@ impl Split {
         self
     }
 
-    /// Set the size of the buffer capacity.
+    /// Set the sizes of the leading chunks,
+    /// followed by the chunks of [`Split::chunk_size`].
     ///
-    /// By default, it is [`BUFFER_CAPACITY_DEFAULT`].
-    pub fn buffer_capacity(
+    /// Such as a small first chunk to preview the head of the file quickly,
+    /// then larger chunks for the rest. The sizes are listed in the chunks of
+    /// the result, to be checked with [`Check::chunk_sizes`].
+    /// It does not apply with [`Split::total_chunks`].
+    ///
+    /// [`Check::chunk_sizes`]: crate::check::Check::chunk_sizes
+    pub fn chunk_schedule<I: IntoIterator<Item = usize>>(
         mut self,
-        capacity: usize,
+        sizes: I,
     ) -> Self {
-        self.buffer_capacity = capacity;
+        self.chunk_schedule = sizes.into_iter().collect();
         self
     }
 
-    /// Run the split process.
-    pub fn run(&self) -> Result<SplitResult, SplitError> {
-        let in_file: &Path = match self.in_file {
-            | Some(ref p) => {
-                let p: &Path = p.as_path();
+    /// Set the strategy to cut the boundaries of the chunks.
+    ///
+    /// With [`ChunkStrategy::ContentDefined`], the chunks are cut by the
+    /// content instead of [`Split::chunk_size`] and [`Split::chunk_schedule`],
+    /// so their sizes vary. The sizes are listed in the chunks of the result,
+    /// to be checked with [`Check::chunk_sizes`], and the chunks are merged
+    /// as is. Invalid sizes fail with [`SplitError::ChunkStrategyInvalid`].
+    /// It does not apply with [`Split::total_chunks`].
+    ///
+    /// By default, it is [`ChunkStrategy::Fixed`].
+    ///
+    /// [`Check::chunk_sizes`]: crate::check::Check::chunk_sizes
+    pub fn strategy(
+        mut self,
+        strategy: ChunkStrategy,
+    ) -> Self {
+        self.strategy = strategy;
+        self
+    }
 
-                // if in_file not exists
-                if !p.exists() {
-                    return Err(SplitError::InFileNotFound);
-                }
+    /// Set the chunk size and the naming scheme from a preset.
+    ///
+    /// The chunks are named after the input file,
+    /// so it should be set after [`Split::in_file`].
+    pub fn preset(
+        mut self,
+        preset: Preset,
+    ) -> Self {
+        let file_name: String = self
+            .in_file
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
 
-                // if in_file not a file
-                if !p.is_file() {
-                    return Err(SplitError::InFileNotFile);
-                }
+        self.chunk_size = preset.chunk_size();
+        self.naming = preset.naming(file_name);
+        self
+    }
 
-                p
-            },
-            | None => return Err(SplitError::InFileNotSet),
-        };
+    /// Set the total number of chunks to split into,
+    /// instead of the maximum size of each chunk.
+    ///
+    /// The chunks are in even size, with the remainder on the last chunk.
+    /// The input size must be known, so it does not apply to the readers.
+    pub fn total_chunks(
+        mut self,
+        chunks: usize,
+    ) -> Self {
+        self.total_chunks = Some(chunks);
+        self
+    }
 
-        let out_dir: &Path = match self.out_dir {
-            | Some(ref p) => {
-                let p: &Path = p.as_path();
+    /// Set the alignment of the chunk boundaries, in bytes.
+    ///
+    /// The chunk size is rounded down to a multiple of the alignment,
+    /// at least the alignment itself, so every chunk but the last one starts
+    /// and ends on a multiple of it. With [`Split::total_chunks`], the
+    /// remaining bytes are added to the last chunk.
+    ///
+    /// By default, it is `1`, the chunks are not aligned.
+    pub fn align(
+        mut self,
+        bytes: usize,
+    ) -> Self {
+        self.align = bytes;
+        self
+    }
 
-                if !p.exists() {
-                    // if out_dir not exists
-                    fs::create_dir_all(p)
-                        .map_err(|_| SplitError::OutDirNotCreated)?
-                } else if p.is_file() {
-                    // if out_dir not a directory
-                    return Err(SplitError::OutDirNotDir);
-                }
+    /// Set the size of the buffer capacity.
+    ///
+    /// By default, it is [`BUFFER_CAPACITY_DEFAULT`].
+    pub fn buffer_capacity(
+        mut self,
+        capacity: usize,
+    ) -> Self {
+        self.buffer_capacity = capacity;
+        self
+    }
 
-                p
-            },
-            | None => return Err(SplitError::OutDirNotSet),
-        };
+    /// Set the naming scheme of the chunk files.
+    ///
+    /// By default, chunks are named by their index only.
+    pub fn naming(
+        mut self,
+        naming: ChunkNaming,
+    ) -> Self {
+        self.naming = naming;
+        self
+    }
 
-        let chunk_size: usize = self.chunk_size;
+    /// Set the prefix of the chunk file names.
+    pub fn chunk_prefix<Prefix: Into<String>>(
+        mut self,
+        prefix: Prefix,
+    ) -> Self {
+        self.naming = self.naming.prefix(prefix);
+        self
+    }
 
-        let buffer_capacity: usize = self.buffer_capacity;
+    /// Set the extension of the chunk file names.
+    pub fn chunk_extension<Extension: AsRef<str>>(
+        mut self,
+        extension: Extension,
+    ) -> Self {
+        self.naming = self.naming.extension(extension);
+        self
+    }
 
-        let input_file: fs::File = fs::OpenOptions::new()
-            .read(true)
-            .open(in_file)
-            .map_err(|_| SplitError::InFileNotOpened)?;
+    /// Set the minimum width of the chunk index, padded with zeros.
+    pub fn pad_width(
+        mut self,
+        width: usize,
+    ) -> Self {
+        self.naming = self.naming.pad_width(width);
+        self
+    }
 
-        let file_size: usize =
-            input_file.metadata().map_err(|_| SplitError::InFileNotRead)?.len()
-                as usize;
+    /// Set the index of the first chunk in the file names.
+    pub fn start_index(
+        mut self,
+        index: usize,
+    ) -> Self {
+        self.naming = self.naming.start_index(index);
+        self
+    }
 
-        let mut reader: io::BufReader<fs::File> =
-            io::BufReader::with_capacity(buffer_capacity, input_file);
+    /// Set the format of the chunk files.
+    ///
+    /// With [`ChunkFormat::Header`], each chunk begins with a
+    /// [`ChunkHeader`](crate::header::ChunkHeader) holding its index,
+    /// the total number of chunks, the size of the file and the digest of
+    /// its payload. The chunks are merged and checked with the same format
+    /// set by [`Merge::format`](crate::merge::Merge::format) and
+    /// [`Check::format`](crate::check::Check::format).
+    /// As with compression, the chunks are written from the content read,
+    /// not cloned nor copied in the kernel, and no merge script is written.
+    ///
+    /// By default, it is [`ChunkFormat::Raw`].
+    pub fn format(
+        mut self,
+        format: ChunkFormat,
+    ) -> Self {
+        self.format = format;
+        self
+    }
 
-        let mut buffer: Vec<u8> = vec![0; chunk_size];
+    /// Set the handle to poll the progress of the process.
+    pub fn progress(
+        mut self,
+        progress: Progress,
+    ) -> Self {
+        self.progress = Some(progress);
+        self
+    }
 
-        let mut total_chunks: usize = 0;
+    /// Set the token to cancel the process.
+    ///
+    /// Once cancelled, the chunks written by the process are removed.
+    pub fn cancellation_token(
+        mut self,
+        token: CancellationToken,
+    ) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
 
-        loop {
-            let mut offset: usize = 0;
+    /// Set the deadline of the process, checked between chunks.
+    ///
+    /// Once passed, the process fails with [`SplitError::DeadlineExceeded`]
+    /// and the chunks written are kept to be resumed.
+    pub fn deadline(
+        mut self,
+        deadline: Instant,
+    ) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
 
-            while offset < chunk_size {
-                match reader.read(&mut buffer[offset..]) {
-                    | Ok(0) => break,
-                    | Ok(n) => offset += n,
-                    | Err(_) => return Err(SplitError::InFileNotRead),
-                };
-            }
+    /// Set whether to write the scripts to merge the chunks without this crate.
+    ///
+    /// The [`SHELL_SCRIPT_NAME`] and [`BATCH_SCRIPT_NAME`] scripts
+    /// are written to the output directory,
+    /// listing the chunks by the naming scheme of the process.
+    ///
+    /// By default, it is `false`.
+    pub fn merge_script(
+        mut self,
+        enabled: bool,
+    ) -> Self {
+        self.merge_script = enabled;
+        self
+    }
 
-            if offset == 0 {
-                break;
-            }
+    /// Set the policy for a non-empty output directory.
+    ///
+    /// By default, it is [`OverwritePolicy::Reuse`].
+    pub fn overwrite(
+        mut self,
+        policy: OverwritePolicy,
+    ) -> Self {
+        self.overwrite = policy;
+        self
+    }
 
-            let output_path: PathBuf = out_dir.join(total_chunks.to_string());
+    /// Set the policy for an input file changed during the split.
+    ///
+    /// Once the chunks are split, the size and the modification time of the
+    /// input file are checked against the ones read before, so a file
+    /// growing or shrinking in the meantime does not produce a chunk set
+    /// inconsistent with [`SplitResult::file_size`]. Unless kept with
+    /// [`SourceChangePolicy::Warn`], the chunks split are removed, even when
+    /// resumed, as they no longer match the input file. A changed file is
+    /// never removed by [`Split::remove_source`].
+    /// It does not apply to the opened files and the readers.
+    ///
+    /// By default, it is [`SourceChangePolicy::Fail`].
+    pub fn source_change(
+        mut self,
+        policy: SourceChangePolicy,
+    ) -> Self {
+        self.source_change = policy;
+        self
+    }
 
-            let output: fs::File = fs::OpenOptions::new()
-                .create(true)
-                .truncate(true)
-                .write(true)
-                .open(output_path)
-                .map_err(|_| SplitError::OutFileNotOpened)?;
+    /// Set whether to resume an interrupted split.
+    ///
+    /// The chunks already in the output directory with the expected size
+    /// are kept and skipped in the input, only the missing or short chunks
+    /// are written. Once cancelled, the chunks written are kept to be
+    /// resumed later.
+    ///
+    /// With the readers, the skipped chunks are still read from the input.
+    ///
+    /// By default, it is `false`.
+    pub fn resume(
+        mut self,
+        resume: bool,
+    ) -> Self {
+        self.resume = resume;
+        self
+    }
 
-            let mut writer: io::BufWriter<fs::File> =
-                io::BufWriter::with_capacity(buffer_capacity, output);
+    /// Set whether to remove the input file after splitting,
+    /// moving the file into its chunks.
+    ///
+    /// The input file is only removed once all chunks are written and
+    /// verified: the chunk files must be of the sizes written and add up
+    /// to the size of the input file, or the process fails with
+    /// [`SplitError::ChunksNotVerified`] and the input file is kept.
+    /// It does not apply to the opened files and the readers.
+    ///
+    /// By default, it is `false`.
+    pub fn remove_source(
+        mut self,
+        remove: bool,
+    ) -> Self {
+        self.remove_source = remove;
+        self
+    }
 
-            writer
-                .write_all(&buffer[..offset])
-                .map_err(|_| SplitError::OutFileNotWritten)?;
+    /// Set whether to write the chunks atomically.
+    ///
+    /// Each chunk is written to a `.tmp` sibling first, such as `0.tmp`,
+    /// and renamed once complete, so other processes polling the output directory
+    /// never observe a partially written chunk.
+    ///
+    /// By default, it is `false`.
+    pub fn atomic(
+        mut self,
+        atomic: bool,
+    ) -> Self {
+        self.atomic = atomic;
+        self
+    }
 
-            writer.flush().map_err(|_| SplitError::OutFileNotWritten)?;
+    /// Set the policy to sync the written chunks to the disk.
+    ///
+    /// By default, it is [`SyncPolicy::None`], the chunks are only flushed.
+    pub fn sync(
+        mut self,
+        policy: SyncPolicy,
+    ) -> Self {
+        self.sync = policy;
+        self
+    }
 
-            total_chunks += 1;
-        }
+    /// Set the policy to clone the chunks from the input file with reflinks.
+    ///
+    /// With [`ReflinkPolicy::Always`], the process fails with
+    /// [`SplitError::OutFileNotCloned`] if the file system does not support it,
+    /// and on platforms other than Linux, see [`ReflinkPolicy`].
+    /// With [`ReflinkPolicy::Auto`], the fallback to a copy is reported once
+    /// as [`Warning::ReflinkFallback`] with the input file.
+    /// It only applies to [`Split::run`].
+    ///
+    /// By default, it is [`ReflinkPolicy::Never`], the data is copied.
+    pub fn reflink(
+        mut self,
+        policy: ReflinkPolicy,
+    ) -> Self {
+        self.reflink = policy;
+        self
+    }
 
-        Ok(SplitResult { file_size, total_chunks })
+    /// Set the codec to compress each chunk as it is written.
+    ///
+    /// The chunks are merged and checked with the same codec set by
+    /// [`Merge::compression`](crate::merge::Merge::compression) and
+    /// [`Check::compression`](crate::check::Check::compression).
+    /// The sizes of the chunks in the result are the sizes before compression.
+    /// Compressed chunks are not cloned with reflinks,
+    /// and no merge scripts are written for them.
+    ///
+    /// By default, the chunks are not compressed.
+    #[cfg(feature = "compression")]
+    pub fn compression(
+        mut self,
+        codec: Codec,
+    ) -> Self {
+        self.compression = Some(codec);
+        self
     }
-}
 
-impl Default for Split {
-    fn default() -> Self {
-        Self::new()
+    /// Set the number of parity chunks to write alongside the chunks.
+    ///
+    /// The parity chunks are Reed-Solomon shards of the chunks, named after
+    /// the chunk naming with a `.parity` suffix, such as `0.parity`.
+    /// Up to the same number of missing or corrupted chunks can be
+    /// reconstructed with [`Merge::repair`](crate::merge::Merge::repair)
+    /// and [`Check::repair`](crate::check::Check::repair).
+    /// The chunks and the parity chunks are limited to
+    /// [`PARITY_SHARDS_MAX`] in total, otherwise the process fails with
+    /// [`SplitError::ParityLimitExceeded`].
+    ///
+    /// By default, it is `0`, no parity chunk is written.
+    pub fn parity_chunks(
+        mut self,
+        count: usize,
+    ) -> Self {
+        self.parity_chunks = count;
+        self
     }
+
+    /// Set the number of PAR2 recovery slices to write alongside the chunks.
+    ///
+    /// The chunks are described by a PAR2 recovery set, each chunk as one
+    /// input slice, written to `<file>.par2` and `<file>.vol00+<count>.par2`
+    /// after the name of the input file, so the chunk set can be verified
+    /// and repaired by the existing PAR2 clients, such as `par2cmdline`.
+    /// Up to the same number of missing or corrupted chunks can be
+    /// reconstructed with [`Merge::repair`](crate::merge::Merge::repair)
+    /// and [`Check::repair`](crate::check::Check::repair).
+    /// The chunks and the recovery slices are limited to
+    /// [`PAR2_SLICES_MAX`] in total, otherwise the process fails with
+    /// [`SplitError::ParityLimitExceeded`].
+    ///
+    /// By default, it is `0`, no PAR2 file is written.
+    pub fn par2_recovery(
+        mut self,
+        count: usize,
+    ) -> Self {
+        self.par2_recovery = count;
+        self
+    }
+
+    /// Set whether to write the `bao` outboard tree of the file
+    /// alongside the chunks.
+    ///
+    /// The tree is written to `<file>.obao` after the name of the input
+    /// file, and its root, the BLAKE3 hash of the file, is returned as
+    /// [`SplitResult::bao_hash`]. With them, any chunk or any byte range
+    /// can be verified as it is streamed, with
+    /// [`BaoVerifier`](crate::bao::BaoVerifier) or
+    /// [`Merge::bao`](crate::merge::Merge::bao), before the whole file is
+    /// received. The tree is built from the content of the chunks,
+    /// before compression.
+    ///
+    /// By default, it is `false`.
+    pub fn bao(
+        mut self,
+        bao: bool,
+    ) -> Self {
+        self.bao = bao;
+        self
+    }
+
+    /// Set whether to verify the free space of the output directory
+    /// before writing.
+    ///
+    /// The space needed is the size of the file plus the overhead of the
+    /// chunks, their headers, the parity chunks and the rounding of each
+    /// file to the blocks of the file system. The chunks already written
+    /// are not counted when resumed. If the space available is smaller,
+    /// the process fails early with [`SplitError::InsufficientSpace`]
+    /// instead of failing halfway with [`SplitError::OutFileNotWritten`].
+    /// The input size must be known, and the space is verified with
+    /// `statvfs` on Unix only, the check always passes on other platforms,
+    /// such as Windows.
+    ///
+    /// By default, it is `false`.
+    pub fn check_space(
+        mut self,
+        check_space: bool,
+    ) -> Self {
+        self.check_space = check_space;
+        self
+    }
+
+    /// Set the bytes to keep free on the file system of the output
+    /// directory, once the chunks are written.
+    ///
+    /// It only applies with [`Split::check_space`].
+    ///
+    /// By default, it is `0`.
+    pub fn space_reserve(
+        mut self,
+        bytes: u64,
+    ) -> Self {
+        self.space_reserve = bytes;
+        self
+    }
+
+    /// Set whether to take an exclusive lock on the output directory
+    /// while writing.
+    ///
+    /// The lock is taken on `<dir>.lock` next to the output directory, see
+    /// [`FileLock`]. If another process holds a lock on it, such as a split
+    /// or a merge of the same chunks, the process fails early with
+    /// [`SplitError::Locked`] instead of interleaving the writes.
+    ///
+    /// By default, it is `false`.
+    pub fn lock(
+        mut self,
+        lock: bool,
+    ) -> Self {
+        self.lock = lock;
+        self
+    }
+
+    /// Set whether to read back each chunk once written, and compare it
+    /// to the content written.
+    ///
+    /// Each chunk file is read again after it is written and synced by the
+    /// [`Split::sync`] policy, and its BLAKE3 hash compared to the hash of
+    /// the content written, or of the range of the input file for the
+    /// chunks copied in the kernel or cloned. A chunk differing is removed,
+    /// and the process fails with [`SplitError::ChunksNotVerified`], so the
+    /// silent corruption of the storage is caught before the input file is
+    /// removed with [`Split::remove_source`]. It reads every chunk twice.
+    ///
+    /// By default, it is `false`.
+    pub fn verify_writes(
+        mut self,
+        verify_writes: bool,
+    ) -> Self {
+        self.verify_writes = verify_writes;
+        self
+    }
+
+    /// Set whether to leave the chunks of zeros as holes.
+    ///
+    /// A chunk made only of zeros is created at its size without writing
+    /// its content, so it takes no space on file systems supporting sparse
+    /// files. With [`Split::run`], the holes of the input file are found
+    /// with `SEEK_DATA` on Linux, macOS, FreeBSD and illumos, and the chunks
+    /// within them are not read. On other platforms, such as Windows, the
+    /// chunks are read, and the chunks of zeros are still left as holes.
+    /// The chunks are merged as is, and the holes are recreated in the
+    /// output with [`Merge::sparse`](crate::merge::Merge::sparse), so the
+    /// gigabytes of zeros of a sparse disk image are never written.
+    /// Encoded chunks, compressed or with a header, are always written.
+    ///
+    /// By default, it is `false`.
+    pub fn sparse(
+        mut self,
+        sparse: bool,
+    ) -> Self {
+        self.sparse = sparse;
+        self
+    }
+
+    /// Set whether to record the metadata of the input file alongside
+    /// the chunks.
+    ///
+    /// The permissions, the timestamps and, on Unix, the ownership of the
+    /// input file are written to `<file>.meta` after the name of the input
+    /// file, see [`FileMetadata`], to be reapplied to the merged file with
+    /// [`Merge::preserve_metadata`](crate::merge::Merge::preserve_metadata).
+    /// It only applies with [`Split::in_file`].
+    ///
+    /// By default, it is `false`.
+    pub fn preserve_metadata(
+        mut self,
+        preserve_metadata: bool,
+    ) -> Self {
+        self.preserve_metadata = preserve_metadata;
+        self
+    }
+
+    /// Set whether to fail on the first warning of the process.
+    ///
+    /// The warning is emitted to the handler set by
+    /// [`Split::on_warning`] before the process fails with
+    /// [`SplitError::WarningRaised`], so conditions otherwise tolerated,
+    /// such as stray entries or a reflink falling back to a copy,
+    /// stop the process.
+    ///
+    /// By default, it is `false`.
+    pub fn strict(
+        mut self,
+        strict: bool,
+    ) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set the number of threads to split the file with.
+    ///
+    /// When more than one thread is set, each thread reads the input file
+    /// at independent offsets and writes its own chunks.
+    /// The chunks, the files written alongside and the result are the same
+    /// whatever the number of threads, only the order of the progress
+    /// events differs. It only applies to [`Split::run`].
+    ///
+    /// By default, it is `1`.
+    pub fn threads(
+        mut self,
+        threads: usize,
+    ) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Set the handler to receive the warnings of the process.
+    pub fn on_warning<F: Fn(&Warning) + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_warning = Some(WarningHandler::new(handler));
+        self
+    }
+
+    /// Set the handler to refresh the input URL once expired.
+    ///
+    /// When a request to the input URL is rejected with
+    /// `401 Unauthorized` or `403 Forbidden`, the URL returned by the handler
+    /// is requested instead, and used for the rest of the process.
+    /// A download interrupted midway is requested again from the position
+    /// read, so a long download is not restarted once its URL expires.
+    /// The process fails with [`SplitError::InUrlExpired`] if the handler
+    /// returns `None`. It only applies to [`Split::in_url`].
+    #[cfg(feature = "reqwest")]
+    pub fn on_url_expired<
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    >(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_url_expired = Some(RefreshHandler::new(handler));
+        self
+    }
+
+    /// Set the handler to receive the first chunk once it is written.
+    ///
+    /// The first chunk is emitted as soon as it is written, synced by
+    /// [`Split::sync`] and renamed by [`Split::atomic`], while the rest of
+    /// the file is still being split, so the head of the file can be consumed
+    /// early. A first chunk already written is emitted once resumed.
+    pub fn on_first_chunk<F: Fn(&ChunkInfo) + Send + Sync + 'static>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_first_chunk = Some(ChunkHandler::new(handler));
+        self
+    }
+
+    /// Emit a warning to the handler of the process.
+    ///
+    /// In strict mode, the process fails once the warning is emitted.
+    pub(crate) fn warn(
+        &self,
+        warning: Warning,
+    ) -> Result<(), SplitError> {
+        if let Some(ref handler) = self.on_warning {
+            handler.emit(&warning);
+        }
+
+        match self.strict {
+            | true => Err(SplitError::WarningRaised),
+            | false => Ok(()),
+        }
+    }
+
+    /// Emit a chunk to the handler of the first chunk, if it is the first.
+    pub(crate) fn first_chunk(
+        &self,
+        chunk: &ChunkInfo,
+    ) {
+        if chunk.index != 0 {
+            return;
+        }
+
+        if let Some(ref handler) = self.on_first_chunk {
+            handler.emit(chunk);
+        }
+    }
+
+    /// Check if the content of a chunk is left as a hole, if sparse.
+    pub(crate) fn is_sparse_chunk(
+        &self,
+        data: &[u8],
+    ) -> bool {
+        self.sparse && !self.is_encoded() && data.iter().all(|b| *b == 0)
+    }
+
+    /// Check if the chunks are encoded, compressed or with a header.
+    pub(crate) fn is_encoded(&self) -> bool {
+        #[cfg(feature = "compression")]
+        if self.compression.is_some() {
+            return true;
+        }
+
+        self.format != ChunkFormat::Raw
+    }
+
+    /// Encode the data of a chunk to be stored,
+    /// compressed and with a header if enabled.
+    ///
+    /// The header describes the chunk set of the layout,
+    /// split from an input of the size, if known.
+    pub(crate) fn encode<'a>(
+        &self,
+        layout: &ChunkLayout,
+        input_size: Option<u64>,
+        index: usize,
+        data: &'a [u8],
+    ) -> Result<Cow<'a, [u8]>, SplitError> {
+        let payload: Cow<[u8]> = self.compress(data)?;
+
+        match self.format {
+            | ChunkFormat::Raw => Ok(payload),
+            | ChunkFormat::Header => Ok(Cow::Owned(header::encode(
+                index,
+                layout.total(input_size),
+                input_size,
+                &payload,
+            ))),
+        }
+    }
+
+    /// Compress the data of a chunk, if enabled.
+    fn compress<'a>(
+        &self,
+        data: &'a [u8],
+    ) -> Result<Cow<'a, [u8]>, SplitError> {
+        #[cfg(feature = "compression")]
+        if let Some(codec) = self.compression {
+            return codec
+                .compress(data)
+                .map(Cow::Owned)
+                .map_err(|_| SplitError::OutFileNotWritten);
+        }
+
+        Ok(Cow::Borrowed(data))
+    }
+
+    /// Decode the content of a chunk as stored,
+    /// verified by its header and decompressed if enabled.
+    pub(crate) fn decode(
+        &self,
+        data: Vec<u8>,
+    ) -> io::Result<Vec<u8>> {
+        let data: Vec<u8> = match self.format {
+            | ChunkFormat::Raw => data,
+            | ChunkFormat::Header => header::decode(data)?,
+        };
+
+        #[cfg(feature = "compression")]
+        if let Some(codec) = self.compression {
+            return codec.decompress(&data);
+        }
+
+        Ok(data)
+    }
+
+    /// Check if a chunk file holds the data of the size, once decoded.
+    pub(crate) fn is_chunk_written(
+        &self,
+        path: &Path,
+        size: usize,
+    ) -> bool {
+        let metadata: fs::Metadata = match fs::metadata(path) {
+            | Ok(metadata) if metadata.is_file() => metadata,
+            | _ => return false,
+        };
+
+        if self.is_encoded() {
+            return fs::read(path)
+                .and_then(|data| self.decode(data))
+                .is_ok_and(|data| data.len() == size);
+        }
+
+        metadata.len() == size as u64
+    }
+
+    /// Check if the process is cancelled.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation_token.as_ref().is_some_and(|t| t.is_cancelled())
+    }
+
+    /// Check if the deadline of the process is exceeded.
+    pub(crate) fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Check if the process is split again after the error,
+    /// by the policy for a changed input file.
+    pub(crate) fn is_retried(
+        &self,
+        error: &SplitError,
+        retries: usize,
+    ) -> bool {
+        *error == SplitError::SourceChangedDuringSplit
+            && matches!(
+                self.source_change,
+                SourceChangePolicy::Retry(n) if retries < n
+            )
+    }
+
+    /// Check if the input file changed during the split,
+    /// by its metadata read before and after, handled by the policy.
+    ///
+    /// Returns `Ok` if not changed, or if the chunks split from a file
+    /// read without error and not shrunk are kept.
+    pub(crate) fn check_source(
+        &self,
+        in_file: &Path,
+        before: &fs::Metadata,
+        after: &io::Result<fs::Metadata>,
+        read: bool,
+    ) -> Result<(), SplitError> {
+        let after: &fs::Metadata = match after {
+            | Ok(after)
+                if after.len() == before.len()
+                    && after.modified().ok() == before.modified().ok() =>
+            {
+                return Ok(());
+            },
+            | Ok(after) => after,
+            | Err(_) => return Err(SplitError::SourceChangedDuringSplit),
+        };
+
+        let read_to_end: bool = read && after.len() >= before.len();
+
+        match self.source_change {
+            | SourceChangePolicy::Warn if read_to_end && !self.remove_source => {
+                self.warn(Warning::SourceChanged(in_file.to_path_buf()))
+            },
+            | _ => Err(SplitError::SourceChangedDuringSplit),
+        }
+    }
+
+    /// Run the split process.
+    ///
+    /// On Linux, the chunks of an input file are copied in the kernel with
+    /// `copy_file_range`. On other platforms, and on file systems without
+    /// the support, they are read and written through the buffer instead.
+    pub fn run(&self) -> Result<SplitResult, SplitError> {
+        let _progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        #[cfg(feature = "reqwest")]
+        if let Some(ref url) = self.in_url {
+            return self.run_from_url(url);
+        }
+
+        if let Some(ref reader) = self.in_reader {
+            return self.run_from_reader(reader);
+        }
+
+        let in_file: &Path = self.source_file()?;
+
+        let mut retries: usize = 0;
+
+        let result: SplitResult = loop {
+            match self.run_from_path(in_file) {
+                | Err(error) if self.is_retried(&error, retries) => {
+                    retries += 1;
+                },
+                | result => break result?,
+            }
+        };
+
+        if self.remove_source {
+            self.remove_source_file(in_file, &result)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Repair the chunks split from the input file into a directory,
+    /// re-writing only the chunks missing or corrupted.
+    ///
+    /// The chunks are checked by [`Check`] with the layout, the naming
+    /// scheme and the format of the process, and their digests compared
+    /// to the content of the input file at their offsets, so a chunk of the
+    /// right size but altered is caught as well. Each chunk to repair is
+    /// read from its offset and written as by [`Split::run`], the other
+    /// chunks are left untouched. The parity chunks, the PAR2 files and the
+    /// outboard tree are not re-written.
+    ///
+    /// The output directory of the process is ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::split::{RepairResult, Split};
+    ///
+    /// let result: RepairResult = Split::new()
+    ///     .in_file(PathBuf::from("path").join("to").join("file"))
+    ///     .repair_into(PathBuf::from("path").join("to").join("dir"))
+    ///     .unwrap();
+    ///
+    /// for chunk in result.repaired {
+    ///     println!("{}", chunk.path.display());
+    /// }
+    /// ```
+    pub fn repair_into<OutDir: AsRef<Path>>(
+        &self,
+        out_dir: OutDir,
+    ) -> Result<RepairResult, SplitError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let in_file: &Path = self.source_file()?;
+
+        let out_dir: &Path = out_dir.as_ref();
+
+        let _lock: Option<FileLock> = self.lock_out_dir(Some(out_dir))?;
+
+        if !out_dir.exists() {
+            // if out_dir not exists
+            fs::create_dir_all(out_dir)
+                .map_err(|_| SplitError::OutDirNotCreated)?;
+        } else if !out_dir.is_dir() {
+            // if out_dir not a directory
+            return Err(SplitError::OutDirNotDir);
+        }
+
+        let started: Instant = Instant::now();
+
+        let metadata: fs::Metadata =
+            fs::metadata(in_file).map_err(|_| SplitError::InFileNotRead)?;
+
+        let file_size: u64 = metadata.len();
+
+        let layout: ChunkLayout = self.file_layout(in_file, file_size)?;
+
+        let total_chunks: usize = layout.count(file_size);
+
+        let sizes: Vec<usize> =
+            (0..total_chunks).map(|i| layout.len_in(i, file_size)).collect();
+
+        let report: CheckReport =
+            self.check_chunks(out_dir, file_size, &sizes)?;
+
+        let mut input: fs::File = read_options()
+            .open(in_file)
+            .map_err(|_| SplitError::InFileNotOpened)?;
+
+        let mut buffer: Vec<u8> = vec![0; layout.max_len()];
+
+        let mut repaired: Vec<ChunkInfo> = Vec::new();
+
+        for chunk in report.chunks {
+            if self.is_cancelled() {
+                return Err(SplitError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(SplitError::DeadlineExceeded);
+            }
+
+            let index: usize = chunk.index;
+
+            let size: usize = sizes[index];
+
+            input
+                .seek(io::SeekFrom::Start(layout.offset(index)))
+                .map_err(|_| SplitError::InFileNotRead)?;
+
+            input
+                .read_exact(&mut buffer[..size])
+                .map_err(|_| SplitError::InFileNotRead)?;
+
+            progress.advance(index, size);
+
+            // if the chunk holds the content of the input file
+            if chunk.found
+                && chunk.size == size
+                && chunk.hash
+                    == Some(HashAlgorithm::Blake3.digest(&buffer[..size]))
+            {
+                continue;
+            }
+
+            repaired.push(self.write_chunk(
+                out_dir,
+                &layout,
+                Some(file_size),
+                index,
+                &buffer[..size],
+            )?);
+        }
+
+        // if in_file changed in the meantime
+        let after: io::Result<fs::Metadata> = fs::metadata(in_file);
+
+        self.check_source(in_file, &metadata, &after, true)?;
+
+        self.sync
+            .sync_dir(out_dir)
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+
+        progress.finish();
+
+        Ok(RepairResult {
+            file_size,
+            total_chunks,
+            repaired,
+            duration: started.elapsed(),
+        })
+    }
+
+    /// Run the split process and get the report as a JSON object.
+    ///
+    /// See [`Report`] for the schema of the report.
+    pub fn report_json(&self) -> String {
+        let started: Instant = Instant::now();
+
+        let result: Result<SplitResult, SplitError> = self.run();
+
+        Report::split(self, &result, started.elapsed()).to_json()
+    }
+
+    /// Create a sink splitting the bytes written to it into chunks
+    /// in the output directory, see [`SplitSink`].
+    pub fn sink(&self) -> Result<SplitSink, SplitError> {
+        SplitSink::new(self)
+    }
+
+    /// Split a reader into `multipart/form-data` bodies, one per chunk,
+    /// instead of writing the chunks to the output directory.
+    ///
+    /// The chunk size, the naming scheme and the compression codec
+    /// of the process are used.
+    pub fn multipart<R: io::Read>(
+        &self,
+        reader: R,
+    ) -> MultipartSplit<R> {
+        let multipart: MultipartSplit<R> = MultipartSplit::new(reader)
+            .chunk_size(self.chunk_size)
+            .naming(self.naming.clone());
+
+        #[cfg(feature = "compression")]
+        if let Some(codec) = self.compression {
+            return multipart.codec(codec);
+        }
+
+        multipart
+    }
+
+    /// Split a buffer in memory into a store, such as a
+    /// [`MemoryStore`](crate::store::MemoryStore), without a file system.
+    ///
+    /// The chunks are cut by the layout of the process and written to the
+    /// store by index, encoded if enabled, and the chunks of the result have
+    /// no path. The input file, the output directory, the resume option and
+    /// the files written next to the chunks, such as the parity chunks,
+    /// are ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use filerune_fusion::{
+    ///     split::{Split, SplitResult},
+    ///     store::MemoryStore,
+    /// };
+    ///
+    /// let mut store: MemoryStore = MemoryStore::new();
+    ///
+    /// let result: SplitResult =
+    ///     Split::new().chunk_size(4).run_to_store(b"hello world", &mut store).unwrap();
+    ///
+    /// assert_eq!(result.total_chunks, 3);
+    /// ```
+    pub fn run_to_store<S: ChunkStore>(
+        &self,
+        data: &[u8],
+        store: &mut S,
+    ) -> Result<SplitResult, SplitError> {
+        self.split_to_store(&mut &data[..], data.len() as u64, store)
+    }
+
+    /// Split the input file into a store, such as a bucket,
+    /// chunk by chunk, without writing the chunks to a directory.
+    ///
+    /// The chunks are written to the store by index as by
+    /// [`Split::run_to_store`], read one at a time from the input file,
+    /// so the file is not staged in memory nor on the local disk.
+    /// The output directory, the resume option and the files written
+    /// next to the chunks are ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    ///
+    /// use filerune_fusion::{
+    ///     split::{Split, SplitResult},
+    ///     store::MemoryStore,
+    /// };
+    ///
+    /// let mut store: MemoryStore = MemoryStore::new();
+    ///
+    /// let result: SplitResult = Split::new()
+    ///     .in_file(PathBuf::from("path").join("to").join("file"))
+    ///     .run_file_to_store(&mut store)
+    ///     .unwrap();
+    /// ```
+    pub fn run_file_to_store<S: ChunkStore>(
+        &self,
+        store: &mut S,
+    ) -> Result<SplitResult, SplitError> {
+        let in_file: &Path = self.source_file()?;
+
+        let metadata: fs::Metadata =
+            fs::metadata(in_file).map_err(|_| SplitError::InFileNotRead)?;
+
+        let input: fs::File = read_options()
+            .open(in_file)
+            .map_err(|_| SplitError::InFileNotOpened)?;
+
+        let mut reader: io::BufReader<fs::File> =
+            io::BufReader::with_capacity(self.buffer_capacity, input);
+
+        let result: Result<SplitResult, SplitError> =
+            self.split_to_store(&mut reader, metadata.len(), store);
+
+        // if in_file changed in the meantime, read short or not
+        if matches!(result, Ok(_) | Err(SplitError::InFileNotRead)) {
+            let after: io::Result<fs::Metadata> = fs::metadata(in_file);
+
+            self.check_source(in_file, &metadata, &after, result.is_ok())?;
+        }
+
+        result
+    }
+
+    /// Split a reader of the input size into a store.
+    fn split_to_store<R: io::Read, S: ChunkStore>(
+        &self,
+        reader: &mut R,
+        input_size: u64,
+        store: &mut S,
+    ) -> Result<SplitResult, SplitError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let started: Instant = Instant::now();
+
+        let layout: ChunkLayout = self.layout(Some(input_size))?;
+
+        let mut buffer: Vec<u8> = vec![0; layout.max_len()];
+
+        let mut chunks: Vec<ChunkInfo> = Vec::new();
+
+        let mut file_size: u64 = 0;
+
+        // the bytes read after the last chunk cut by the content
+        let mut carry: usize = 0;
+
+        loop {
+            if self.is_cancelled() {
+                return Err(SplitError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(SplitError::DeadlineExceeded);
+            }
+
+            let index: usize = chunks.len();
+
+            let size: usize = layout.len(index);
+
+            let mut offset: usize = carry;
+
+            while offset < size {
+                match reader.read(&mut buffer[offset..size]) {
+                    | Ok(0) => break,
+                    | Ok(n) => offset += n,
+                    | Err(_) => return Err(SplitError::InFileNotRead),
+                };
+            }
+
+            if offset == 0 {
+                break;
+            }
+
+            let len: usize = layout.cut(&buffer[..offset]);
+
+            let encoded: Cow<[u8]> =
+                self.encode(&layout, Some(input_size), index, &buffer[..len])?;
+
+            store
+                .write(index, &encoded)
+                .map_err(|_| SplitError::OutFileNotWritten)?;
+
+            let chunk: ChunkInfo =
+                ChunkInfo { index, path: PathBuf::new(), size: len };
+
+            self.first_chunk(&chunk);
+
+            progress.advance(index, len);
+
+            chunks.push(chunk);
+
+            file_size += len as u64;
+
+            buffer.copy_within(len..offset, 0);
+
+            carry = offset - len;
+        }
+
+        progress.finish();
+
+        Ok(SplitResult {
+            file_size,
+            total_chunks: chunks.len(),
+            chunk_size: layout.chunk_size,
+            chunks,
+            duration: started.elapsed(),
+            chunks_resumed: 0,
+            bao_hash: None,
+        })
+    }
+
+    /// Encode a reader into an open-ended stream of RaptorQ packets,
+    /// instead of writing the chunks to the output directory.
+    ///
+    /// The chunk size of the process is used as the size of the source
+    /// blocks, read one at a time, with the symbols of the
+    /// [`SYMBOL_SIZE_DEFAULT`](crate::fountain::SYMBOL_SIZE_DEFAULT),
+    /// see [`FountainEncoder`] for the other options. The file is
+    /// reconstructed from any sufficient subset of the packets by
+    /// [`Merge::run_from_symbols`](crate::merge::Merge::run_from_symbols).
+    #[cfg(feature = "fountain")]
+    pub fn fountain<R: io::Read + io::Seek>(
+        &self,
+        reader: R,
+    ) -> Result<FountainEncoder<R>, SplitError> {
+        FountainEncoder::new(reader)?.block_size(self.chunk_size)
+    }
+
+    /// Run the split process with an opened file as the input.
+    ///
+    /// The input file of the process is ignored,
+    /// the file is read from its current position
+    /// and the file size is counted from the bytes read.
+    pub fn run_from_file(
+        &self,
+        file: fs::File,
+    ) -> Result<SplitResult, SplitError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let _lock: Option<FileLock> = self.lock_out_dir(None)?;
+
+        let out_dir: &Path = self.prepare_out_dir()?;
+
+        // the size from the current position
+        let mut file: fs::File = file;
+
+        let input_size: Option<u64> =
+            match self.total_chunks.is_some() || self.resume {
+                | true => {
+                    let len: u64 = file
+                        .metadata()
+                        .map_err(|_| SplitError::InFileNotRead)?
+                        .len();
+
+                    let position: u64 = file
+                        .stream_position()
+                        .map_err(|_| SplitError::InFileNotRead)?;
+
+                    Some(len.saturating_sub(position))
+                },
+                | false => None,
+            };
+
+        let mut reader: io::BufReader<fs::File> =
+            io::BufReader::with_capacity(self.buffer_capacity, file);
+
+        self.split_stream(
+            &mut reader,
+            |r, n| r.seek_relative(n as i64),
+            input_size,
+            out_dir,
+            &progress,
+            Vec::new(),
+        )
+    }
+
+    /// Run the split process with a remote input.
+    #[cfg(feature = "reqwest")]
+    fn run_from_url(
+        &self,
+        url: &str,
+    ) -> Result<SplitResult, SplitError> {
+        use reqwest::{blocking::Client, header::CONTENT_LENGTH};
+
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let _lock: Option<FileLock> = self.lock_out_dir(None)?;
+
+        let out_dir: &Path = self.prepare_out_dir()?;
+
+        let client: Client = Client::new();
+
+        let mut url: String = url.to_string();
+
+        // the size is only requested when required
+        let input_size: Option<u64> =
+            match self.total_chunks.is_some() || self.resume {
+                | true => send_refreshed(
+                    &mut url,
+                    self.on_url_expired.as_ref(),
+                    |url| client.head(url),
+                )?
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok()?.parse::<u64>().ok()),
+                | false => None,
+            };
+
+        let layout: ChunkLayout = self.layout(input_size)?;
+
+        // the chunks already written from the start are not downloaded
+        let mut resumed: Vec<ChunkInfo> = Vec::new();
+
+        let mut position: u64 = 0;
+
+        if let Some(input_size) = input_size {
+            while let Some(chunk) = self.written_chunk(
+                out_dir,
+                resumed.len(),
+                layout.len_in(resumed.len(), input_size),
+            ) {
+                position += chunk.size as u64;
+
+                resumed.push(chunk);
+            }
+        }
+
+        let mut response: UrlReader = UrlReader::open(
+            client,
+            url,
+            self.on_url_expired.as_ref(),
+            position,
+        )?;
+
+        self.split_stream(
+            &mut response,
+            |r, n| io::copy(&mut r.take(n as u64), &mut io::sink()).map(|_| ()),
+            input_size,
+            out_dir,
+            &progress,
+            resumed,
+        )
+    }
+
+    /// Run the split process with a reader as the input.
+    fn run_from_reader(
+        &self,
+        reader: &InReader,
+    ) -> Result<SplitResult, SplitError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let _lock: Option<FileLock> = self.lock_out_dir(None)?;
+
+        let out_dir: &Path = self.prepare_out_dir()?;
+
+        let mut reader: io::BufReader<Box<dyn io::Read + Send>> =
+            io::BufReader::with_capacity(
+                self.buffer_capacity,
+                reader.take().ok_or(SplitError::InReaderConsumed)?,
+            );
+
+        self.split_stream(
+            &mut reader,
+            |r, n| io::copy(&mut r.take(n as u64), &mut io::sink()).map(|_| ()),
+            self.in_reader.as_ref().and_then(InReader::size),
+            out_dir,
+            &progress,
+            Vec::new(),
+        )
+    }
+
+    /// Split a stream to the output directory, after the chunks resumed.
+    ///
+    /// The chunks already written are skipped in the stream,
+    /// if the input size is known.
+    fn split_stream<R: io::Read>(
+        &self,
+        reader: &mut R,
+        skip: impl Fn(&mut R, usize) -> io::Result<()>,
+        input_size: Option<u64>,
+        out_dir: &Path,
+        progress: &ProgressGuard,
+        resumed: Vec<ChunkInfo>,
+    ) -> Result<SplitResult, SplitError> {
+        let layout: ChunkLayout = self.layout(input_size)?;
+
+        if let Some(input_size) = input_size {
+            self.ensure_space(out_dir, &layout, input_size)?;
+        }
+
+        let mut buffer: Vec<u8> = vec![0; layout.max_len()];
+
+        let started: Instant = Instant::now();
+
+        for chunk in resumed.iter() {
+            self.first_chunk(chunk);
+
+            progress.advance(chunk.index, chunk.size);
+        }
+
+        let mut file_size: u64 = resumed.iter().map(|c| c.size as u64).sum();
+
+        let mut total_chunks: usize = resumed.len();
+
+        let mut chunks_resumed: usize = resumed.len();
+
+        let mut chunks: Vec<ChunkInfo> = resumed;
+
+        // the bytes read after the last chunk cut by the content
+        let mut carry: usize = 0;
+
+        loop {
+            if self.is_cancelled() {
+                self.remove_chunks(out_dir, total_chunks);
+
+                return Err(SplitError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(SplitError::DeadlineExceeded);
+            }
+
+            let mut offset: usize = carry;
+
+            let size: usize = layout.len(total_chunks);
+
+            // skip the chunk already written, at the boundary of the stream
+            if let Some(chunk) =
+                input_size.filter(|_| carry == 0).and_then(|input_size| {
+                    self.written_chunk(
+                        out_dir,
+                        total_chunks,
+                        (size as u64).min(input_size.saturating_sub(file_size))
+                            as usize,
+                    )
+                })
+            {
+                skip(reader, chunk.size)
+                    .map_err(|_| SplitError::InFileNotRead)?;
+
+                self.first_chunk(&chunk);
+
+                progress.advance(total_chunks, chunk.size);
+
+                file_size += chunk.size as u64;
+
+                total_chunks += 1;
+
+                chunks_resumed += 1;
+
+                chunks.push(chunk);
+
+                continue;
+            }
+
+            while offset < size {
+                match reader.read(&mut buffer[offset..size]) {
+                    | Ok(0) => break,
+                    | Ok(n) => offset += n,
+                    | Err(_) => return Err(SplitError::InFileNotRead),
+                };
+            }
+
+            if offset == 0 {
+                break;
+            }
+
+            let len: usize = layout.cut(&buffer[..offset]);
+
+            chunks.push(self.write_chunk(
+                out_dir,
+                &layout,
+                input_size,
+                total_chunks,
+                &buffer[..len],
+            )?);
+
+            progress.advance(total_chunks, len);
+
+            file_size += len as u64;
+
+            total_chunks += 1;
+
+            buffer.copy_within(len..offset, 0);
+
+            carry = offset - len;
+        }
+
+        self.write_parity(out_dir, &chunks)?;
+
+        let bao_hash: Option<Digest> = self.write_bao(out_dir, &chunks)?;
+
+        self.write_metadata(out_dir)?;
+
+        for (name, script) in self.merge_scripts(&chunks) {
+            fs::write(out_dir.join(name), script)
+                .map_err(|_| SplitError::OutFileNotWritten)?;
+        }
+
+        self.sync
+            .sync_dir(out_dir)
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+
+        progress.finish();
+
+        Ok(SplitResult {
+            file_size,
+            total_chunks,
+            chunk_size: layout.chunk_size,
+            chunks,
+            duration: started.elapsed(),
+            chunks_resumed,
+            bao_hash,
+        })
+    }
+
+    /// Run the split process with the input file at a path,
+    /// with one or more threads.
+    ///
+    /// On Linux, the chunks are copied in the kernel with `copy_file_range`,
+    /// falling back to the buffer if not supported by the file system.
+    fn run_from_path(
+        &self,
+        in_file: &Path,
+    ) -> Result<SplitResult, SplitError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let _lock: Option<FileLock> = self.lock_out_dir(None)?;
+
+        let out_dir: &Path = self.prepare_out_dir()?;
+
+        let started: Instant = Instant::now();
+
+        let metadata: fs::Metadata =
+            fs::metadata(in_file).map_err(|_| SplitError::InFileNotRead)?;
+
+        let file_size: u64 = metadata.len();
+
+        let layout: ChunkLayout = self.file_layout(in_file, file_size)?;
+
+        self.ensure_space(out_dir, &layout, file_size)?;
+
+        let chunk_size: usize = layout.chunk_size;
+
+        let total_chunks: usize = layout.count(file_size);
+
+        let next: AtomicUsize = AtomicUsize::new(0);
+
+        let stop: AtomicBool = AtomicBool::new(false);
+
+        let resumed: AtomicUsize = AtomicUsize::new(0);
+
+        // the fallback is shared, so it is reported once by any thread
+        let reflink_fallback: AtomicBool = AtomicBool::new(false);
+
+        let worker = || -> Result<(), SplitError> {
+            let mut input: fs::File = read_options()
+                .open(in_file)
+                .map_err(|_| SplitError::InFileNotOpened)?;
+
+            let mut buffer: Vec<u8> = vec![0; layout.max_len()];
+
+            // until not supported by the file system
+            let mut reflink: bool =
+                self.reflink != ReflinkPolicy::Never && !self.is_encoded();
+
+            let mut zero_copy: bool = !self.is_encoded();
+
+            loop {
+                if stop.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+
+                if self.is_cancelled() {
+                    return Err(SplitError::Cancelled);
+                }
+
+                if self.is_expired() {
+                    return Err(SplitError::DeadlineExceeded);
+                }
+
+                let index: usize = next.fetch_add(1, Ordering::Relaxed);
+
+                if index >= total_chunks {
+                    return Ok(());
+                }
+
+                let offset: u64 = layout.offset(index);
+
+                let size: usize = layout.len_in(index, file_size);
+
+                // skip the chunk already written
+                if let Some(chunk) = self.written_chunk(out_dir, index, size) {
+                    self.first_chunk(&chunk);
+
+                    resumed.fetch_add(1, Ordering::Relaxed);
+
+                    progress.advance(index, size);
+
+                    continue;
+                }
+
+                // the chunk within a hole of the input file is not read
+                if self.sparse
+                    && !self.is_encoded()
+                    && is_hole(&input, offset, size as u64)
+                        .map_err(|_| SplitError::InFileNotRead)?
+                {
+                    self.hole_chunk(out_dir, index, size)?;
+
+                    progress.advance(index, size);
+
+                    continue;
+                }
+
+                if reflink && reflink_fallback.load(Ordering::Relaxed) {
+                    reflink = false;
+                }
+
+                if reflink {
+                    let cloned: Option<ChunkInfo> =
+                        self.clone_chunk(&input, offset, out_dir, index, size)?;
+
+                    if cloned.is_some() {
+                        progress.advance(index, size);
+
+                        continue;
+                    }
+
+                    if self.reflink == ReflinkPolicy::Always {
+                        return Err(SplitError::OutFileNotCloned);
+                    }
+
+                    if !reflink_fallback.swap(true, Ordering::Relaxed) {
+                        self.warn(Warning::ReflinkFallback(
+                            in_file.to_path_buf(),
+                        ))?;
+                    }
+
+                    reflink = false;
+                }
+
+                if zero_copy {
+                    let copied: Option<ChunkInfo> =
+                        self.copy_chunk(&input, offset, out_dir, index, size)?;
+
+                    if copied.is_some() {
+                        progress.advance(index, size);
+
+                        continue;
+                    }
+
+                    zero_copy = false;
+                }
+
+                input
+                    .seek(io::SeekFrom::Start(offset))
+                    .map_err(|_| SplitError::InFileNotRead)?;
+
+                input
+                    .read_exact(&mut buffer[..size])
+                    .map_err(|_| SplitError::InFileNotRead)?;
+
+                self.write_chunk(
+                    out_dir,
+                    &layout,
+                    Some(file_size),
+                    index,
+                    &buffer[..size],
+                )?;
+
+                progress.advance(index, size);
+            }
+        };
+
+        let result: Result<(), SplitError> = thread::scope(|scope| {
+            let handles: Vec<thread::ScopedJoinHandle<_>> =
+                (0..self.threads.min(total_chunks))
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let result: Result<(), SplitError> = worker();
+
+                            if result.is_err() {
+                                stop.store(true, Ordering::Relaxed);
+                            }
+
+                            result
+                        })
+                    })
+                    .collect();
+
+            handles.into_iter().try_for_each(|handle| {
+                handle.join().unwrap_or_else(|e| panic::resume_unwind(e))
+            })
+        });
+
+        // if in_file changed in the meantime, read short or not
+        if matches!(result, Ok(()) | Err(SplitError::InFileNotRead)) {
+            let after: io::Result<fs::Metadata> = fs::metadata(in_file);
+
+            if let Err(error) =
+                self.check_source(in_file, &metadata, &after, result.is_ok())
+            {
+                self.discard_chunks(out_dir, total_chunks);
+
+                return Err(error);
+            }
+        }
+
+        if let Err(error) = result {
+            if error == SplitError::Cancelled {
+                self.remove_chunks(out_dir, total_chunks);
+            }
+
+            return Err(error);
+        }
+
+        let chunks: Vec<ChunkInfo> = (0..total_chunks)
+            .map(|index| ChunkInfo {
+                index,
+                path: out_dir.join(self.naming.file_name(index)),
+                size: layout.len_in(index, file_size),
+            })
+            .collect();
+
+        self.write_parity(out_dir, &chunks)?;
+
+        let bao_hash: Option<Digest> = self.write_bao(out_dir, &chunks)?;
+
+        self.write_metadata(out_dir)?;
+
+        for (name, script) in self.merge_scripts(&chunks) {
+            fs::write(out_dir.join(name), script)
+                .map_err(|_| SplitError::OutFileNotWritten)?;
+        }
+
+        self.sync
+            .sync_dir(out_dir)
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+
+        progress.finish();
+
+        Ok(SplitResult {
+            file_size,
+            total_chunks,
+            chunk_size,
+            chunks,
+            duration: started.elapsed(),
+            chunks_resumed: resumed.into_inner(),
+            bao_hash,
+        })
+    }
+
+    /// Validate the input file.
+    fn source_file(&self) -> Result<&Path, SplitError> {
+        match self.in_file {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_file not exists
+                if !p.exists() {
+                    return Err(SplitError::InFileNotFound);
+                }
+
+                // if in_file not a file
+                if !p.is_file() {
+                    return Err(SplitError::InFileNotFile);
+                }
+
+                Ok(p)
+            },
+            | None => Err(SplitError::InFileNotSet),
+        }
+    }
+
+    /// Check the chunks written to a directory, hashed by BLAKE3,
+    /// with the sizes expected by the layout.
+    fn check_chunks(
+        &self,
+        out_dir: &Path,
+        file_size: u64,
+        sizes: &[usize],
+    ) -> Result<CheckReport, SplitError> {
+        let mut check: Check = Check::new()
+            .in_dir(out_dir)
+            .file_size(file_size)
+            .total_chunks(sizes.len())
+            .chunk_sizes(sizes.iter().copied())
+            .hash_algorithm(HashAlgorithm::Blake3)
+            .naming(self.naming.clone())
+            .format(self.format)
+            .strict(self.strict);
+
+        check.cancellation_token = self.cancellation_token.clone();
+        check.deadline = self.deadline;
+        check.on_warning = self.on_warning.clone();
+
+        #[cfg(feature = "compression")]
+        {
+            check.compression = self.compression;
+        }
+
+        check.report().map_err(|error| match error {
+            | CheckError::InDirNotDir => SplitError::OutDirNotDir,
+            | CheckError::WarningRaised => SplitError::WarningRaised,
+            | CheckError::Cancelled => SplitError::Cancelled,
+            | CheckError::DeadlineExceeded => SplitError::DeadlineExceeded,
+            | _ => SplitError::ChunksNotVerified,
+        })
+    }
+
+    /// Validate the output directory, create it if not exists.
+    pub(crate) fn prepare_out_dir(&self) -> Result<&Path, SplitError> {
+        match self.out_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                if !p.exists() {
+                    // if out_dir not exists
+                    fs::create_dir_all(p)
+                        .map_err(|_| SplitError::OutDirNotCreated)?
+                } else if p.is_file() {
+                    // if out_dir not a directory
+                    return Err(SplitError::OutDirNotDir);
+                } else if fs::read_dir(p)
+                    .is_ok_and(|mut entries| entries.next().is_some())
+                {
+                    // if out_dir not empty
+                    match self.overwrite {
+                        | OverwritePolicy::Fail => {
+                            return Err(SplitError::OutDirNotEmpty);
+                        },
+                        | OverwritePolicy::Clean => {
+                            let read_dir: fs::ReadDir = fs::read_dir(p)
+                                .map_err(|_| SplitError::OutDirNotCleaned)?;
+
+                            for entry in read_dir {
+                                let path: PathBuf = entry
+                                    .map_err(|_| SplitError::OutDirNotCleaned)?
+                                    .path();
+
+                                if path.is_dir() {
+                                    fs::remove_dir_all(&path)
+                                } else {
+                                    fs::remove_file(&path)
+                                }
+                                .map_err(|_| SplitError::OutDirNotCleaned)?;
+                            }
+                        },
+                        | OverwritePolicy::Reuse => {
+                            self.warn(Warning::OutDirNotEmpty(
+                                p.to_path_buf(),
+                            ))?;
+                        },
+                    }
+                }
+
+                Ok(p)
+            },
+            | None => Err(SplitError::OutDirNotSet),
+        }
+    }
+
+    /// Get the sizes of the chunks to split into.
+    ///
+    /// The input size is only required with the total number of chunks.
+    pub(crate) fn layout(
+        &self,
+        file_size: Option<u64>,
+    ) -> Result<ChunkLayout, SplitError> {
+        let align: usize = self.align.max(1);
+
+        let total_chunks: usize = match self.total_chunks {
+            | Some(n) => n,
+            | None if self.strategy != ChunkStrategy::Fixed => {
+                if !self.strategy.is_valid() {
+                    return Err(SplitError::ChunkStrategyInvalid);
+                }
+
+                // each chunk is cut by the content, up to the maximum size
+                return Ok(ChunkLayout {
+                    schedule: Vec::new(),
+                    chunk_size: match self.strategy {
+                        | ChunkStrategy::ContentDefined { max, .. } => max,
+                        | ChunkStrategy::Fixed => self.chunk_size,
+                    },
+                    last: None,
+                    strategy: self.strategy,
+                });
+            },
+            | None => {
+                return Ok(ChunkLayout {
+                    schedule: self
+                        .chunk_schedule
+                        .iter()
+                        .map(|size| (size / align * align).max(align))
+                        .collect(),
+                    chunk_size: (self.chunk_size / align * align).max(align),
+                    last: None,
+                    strategy: ChunkStrategy::Fixed,
+                });
+            },
+        };
+
+        let file_size: u64 = file_size.ok_or(SplitError::InFileSizeUnknown)?;
+
+        // an empty file has no chunk
+        if file_size == 0 {
+            return Ok(ChunkLayout {
+                schedule: Vec::new(),
+                chunk_size: 0,
+                last: None,
+                strategy: ChunkStrategy::Fixed,
+            });
+        }
+
+        if total_chunks == 0 || total_chunks as u64 > file_size {
+            return Err(SplitError::TotalChunksInvalid);
+        }
+
+        if self.parity_chunks > 0
+            && total_chunks + self.parity_chunks > PARITY_SHARDS_MAX
+        {
+            return Err(SplitError::ParityLimitExceeded);
+        }
+
+        if self.par2_recovery > 0
+            && total_chunks + self.par2_recovery > PAR2_SLICES_MAX
+        {
+            return Err(SplitError::ParityLimitExceeded);
+        }
+
+        let chunk_size: u64 =
+            file_size / total_chunks as u64 / align as u64 * align as u64;
+
+        let last: u64 = file_size - chunk_size * (total_chunks as u64 - 1);
+
+        // if the chunks are too small to be aligned,
+        // or too large to be held in memory
+        match (usize::try_from(chunk_size), usize::try_from(last)) {
+            | (Ok(chunk_size), Ok(last)) if chunk_size > 0 => Ok(ChunkLayout {
+                schedule: Vec::new(),
+                chunk_size,
+                last: Some((total_chunks - 1, last)),
+                strategy: ChunkStrategy::Fixed,
+            }),
+            | _ => Err(SplitError::TotalChunksInvalid),
+        }
+    }
+
+    /// Get the sizes of the chunks to split the input file at a path into.
+    ///
+    /// The content-defined chunks are cut by a first pass over the file,
+    /// so they are written by offsets as the fixed ones.
+    pub(crate) fn file_layout(
+        &self,
+        in_file: &Path,
+        file_size: u64,
+    ) -> Result<ChunkLayout, SplitError> {
+        let layout: ChunkLayout = self.layout(Some(file_size))?;
+
+        if layout.strategy == ChunkStrategy::Fixed {
+            return Ok(layout);
+        }
+
+        let mut input: fs::File = read_options()
+            .open(in_file)
+            .map_err(|_| SplitError::InFileNotOpened)?;
+
+        let mut buffer: Vec<u8> = vec![0; layout.max_len()];
+
+        let mut schedule: Vec<usize> = Vec::new();
+
+        let mut carry: usize = 0;
+
+        loop {
+            let mut filled: usize = carry;
+
+            while filled < buffer.len() {
+                match input.read(&mut buffer[filled..]) {
+                    | Ok(0) => break,
+                    | Ok(n) => filled += n,
+                    | Err(_) => return Err(SplitError::InFileNotRead),
+                };
+            }
+
+            if filled == 0 {
+                break;
+            }
+
+            let len: usize = layout.cut(&buffer[..filled]);
+
+            schedule.push(len);
+
+            buffer.copy_within(len..filled, 0);
+
+            carry = filled - len;
+        }
+
+        // if in_file changed in the meantime
+        if schedule.iter().map(|len| *len as u64).sum::<u64>() != file_size {
+            return Err(SplitError::SourceChangedDuringSplit);
+        }
+
+        Ok(ChunkLayout { schedule, ..layout })
+    }
+
+    /// Get the name of the merged file, after the input file.
+    fn output_name(&self) -> String {
+        self.in_file
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "output".to_string())
+    }
+
+    /// Get the scripts to merge the chunks, if enabled.
+    pub(crate) fn merge_scripts(
+        &self,
+        chunks: &[ChunkInfo],
+    ) -> Vec<(&'static str, String)> {
+        // the scripts only concatenate the chunks
+        if !self.merge_script || self.is_encoded() {
+            return Vec::new();
+        }
+
+        let output_name: String = self.output_name();
+
+        vec![
+            (SHELL_SCRIPT_NAME, script::shell(&output_name, chunks)),
+            (BATCH_SCRIPT_NAME, script::batch(&output_name, chunks)),
+        ]
+    }
+
+    /// Write the parity chunks and the PAR2 files of the chunks written,
+    /// if enabled.
+    pub(crate) fn write_parity(
+        &self,
+        out_dir: &Path,
+        chunks: &[ChunkInfo],
+    ) -> Result<(), SplitError> {
+        if self.parity_chunks > 0 {
+            if chunks.len() + self.parity_chunks > PARITY_SHARDS_MAX {
+                return Err(SplitError::ParityLimitExceeded);
+            }
+
+            parity::write_parity(
+                out_dir,
+                &self.naming,
+                chunks,
+                self.parity_chunks,
+                self.sync,
+            )
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+        }
+
+        if self.par2_recovery > 0 {
+            if chunks.len() + self.par2_recovery > PAR2_SLICES_MAX {
+                return Err(SplitError::ParityLimitExceeded);
+            }
+
+            par2::write_par2(
+                out_dir,
+                &self.output_name(),
+                chunks,
+                self.par2_recovery,
+                self.sync,
+            )
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the outboard tree of the chunks written to the directory,
+    /// if enabled.
+    ///
+    /// Returns the root of the tree.
+    pub(crate) fn write_bao(
+        &self,
+        out_dir: &Path,
+        chunks: &[ChunkInfo],
+    ) -> Result<Option<Digest>, SplitError> {
+        if !self.bao {
+            return Ok(None);
+        }
+
+        let path: PathBuf = out_dir.join(format!(
+            "{}.{}",
+            self.output_name(),
+            OUTBOARD_EXTENSION
+        ));
+
+        let reader: ChunkContents<_> =
+            ChunkContents::new(chunks.iter().map(|chunk| {
+                fs::read(&chunk.path).and_then(|data| self.decode(data))
+            }));
+
+        let len: u64 = chunks.iter().map(|chunk| chunk.size as u64).sum();
+
+        bao::write_outboard(&path, reader, len, self.sync)
+            .map(Some)
+            .map_err(|_| SplitError::OutFileNotWritten)
+    }
+
+    /// Write the metadata of the input file to the directory, if enabled.
+    pub(crate) fn write_metadata(
+        &self,
+        out_dir: &Path,
+    ) -> Result<(), SplitError> {
+        let in_file: &Path = match self.in_file {
+            | Some(ref p) if self.preserve_metadata => p,
+            | _ => return Ok(()),
+        };
+
+        let metadata: FileMetadata = FileMetadata::from_path(in_file)
+            .map_err(|_| SplitError::InFileNotRead)?;
+
+        fs::write(
+            metadata_path(out_dir, &self.output_name()),
+            metadata.encode(),
+        )
+        .map_err(|_| SplitError::OutFileNotWritten)
+    }
+
+    /// Take an exclusive lock on the output directory, if enabled.
+    ///
+    /// The output directory of the process is locked if none is given.
+    pub(crate) fn lock_out_dir(
+        &self,
+        out_dir: Option<&Path>,
+    ) -> Result<Option<FileLock>, SplitError> {
+        if !self.lock {
+            return Ok(None);
+        }
+
+        let out_dir: &Path = match out_dir.or(self.out_dir.as_deref()) {
+            | Some(p) => p,
+            | None => return Err(SplitError::OutDirNotSet),
+        };
+
+        FileLock::acquire(out_dir, LockMode::Exclusive).map(Some).map_err(|e| {
+            match e {
+                | LockError::Locked => SplitError::Locked,
+                | LockError::LockNotAcquired => SplitError::LockNotAcquired,
+            }
+        })
+    }
+
+    /// Verify the free space of the output directory for the chunks
+    /// of a file, if enabled.
+    pub(crate) fn ensure_space(
+        &self,
+        out_dir: &Path,
+        layout: &ChunkLayout,
+        file_size: u64,
+    ) -> Result<(), SplitError> {
+        if !self.check_space {
+            return Ok(());
+        }
+
+        let space: DiskSpace = match disk_space(out_dir) {
+            | Ok(Some(space)) => space,
+            // the space is unknown
+            | _ => return Ok(()),
+        };
+
+        let header_len: usize = match self.format {
+            | ChunkFormat::Raw => 0,
+            | ChunkFormat::Header => header::HEADER_LEN,
+        };
+
+        let total_chunks: usize = layout.count(file_size);
+
+        let mut required: u64 = self.space_reserve;
+
+        for index in 0..total_chunks {
+            let size: usize = layout.len_in(index, file_size);
+
+            // the chunk already written takes no more space
+            if self.resume
+                && fs::metadata(out_dir.join(self.naming.file_name(index)))
+                    .is_ok()
+            {
+                continue;
+            }
+
+            required = required
+                .saturating_add(space.allocated((size + header_len) as u64));
+        }
+
+        if self.parity_chunks > 0 {
+            let parity_len: u64 =
+                space.allocated((layout.max_len() + header_len) as u64);
+
+            required =
+                required.saturating_add(parity_len * self.parity_chunks as u64);
+        }
+
+        match required > space.available {
+            | true => Err(SplitError::InsufficientSpace),
+            | false => Ok(()),
+        }
+    }
+
+    /// Read back a chunk file written, and verify its hash.
+    ///
+    /// The chunk file is removed if it differs.
+    pub(crate) fn verify_chunk(
+        &self,
+        path: &Path,
+        expected: &Digest,
+    ) -> Result<(), SplitError> {
+        let mut file: fs::File = read_options()
+            .open(path)
+            .map_err(|_| SplitError::ChunksNotVerified)?;
+
+        let digest: Digest = hash_reader(&mut file, self.buffer_capacity)
+            .map_err(|_| SplitError::ChunksNotVerified)?;
+
+        drop(file);
+
+        if digest != *expected {
+            let _ = fs::remove_file(path);
+
+            return Err(SplitError::ChunksNotVerified);
+        }
+
+        Ok(())
+    }
+
+    /// Get the chunk already written with the expected size, if resumed.
+    pub(crate) fn written_chunk(
+        &self,
+        out_dir: &Path,
+        index: usize,
+        size: usize,
+    ) -> Option<ChunkInfo> {
+        if !self.resume || size == 0 {
+            return None;
+        }
+
+        let path: PathBuf = out_dir.join(self.naming.file_name(index));
+
+        match self.is_chunk_written(&path, size) {
+            | true => Some(ChunkInfo { index, path, size }),
+            | false => None,
+        }
+    }
+
+    /// Remove the input file once the chunks written are verified.
+    pub(crate) fn remove_source_file(
+        &self,
+        in_file: &Path,
+        result: &SplitResult,
+    ) -> Result<(), SplitError> {
+        let file_size: u64 =
+            fs::metadata(in_file).map_err(|_| SplitError::InFileNotRead)?.len();
+
+        let verified: bool = result
+            .chunks
+            .iter()
+            .all(|c| self.is_chunk_written(&c.path, c.size));
+
+        // if chunks not match in_file
+        if !verified
+            || file_size != result.file_size
+            || result.chunks.iter().map(|c| c.size as u64).sum::<u64>()
+                != result.file_size
+        {
+            return Err(SplitError::ChunksNotVerified);
+        }
+
+        fs::remove_file(in_file).map_err(|_| SplitError::InFileNotRemoved)
+    }
+
+    /// Write a chunk to the output directory.
+    pub(crate) fn write_chunk(
+        &self,
+        out_dir: &Path,
+        layout: &ChunkLayout,
+        input_size: Option<u64>,
+        index: usize,
+        data: &[u8],
+    ) -> Result<ChunkInfo, SplitError> {
+        if self.is_sparse_chunk(data) {
+            return self.hole_chunk(out_dir, index, data.len());
+        }
+
+        let encoded: Cow<[u8]> =
+            self.encode(layout, input_size, index, data)?;
+
+        self.create_chunk(
+            out_dir,
+            index,
+            data.len(),
+            |output| {
+                let mut writer: io::BufWriter<&fs::File> =
+                    io::BufWriter::with_capacity(self.buffer_capacity, output);
+
+                writer.write_all(&encoded)?;
+
+                writer.flush()?;
+
+                Ok(true)
+            },
+            || Ok(VERIFY_ALGORITHM.digest(&encoded)),
+        )
+        .and_then(|chunk| chunk.ok_or(SplitError::OutFileNotWritten))
+    }
+
+    /// Create a chunk of zeros as a hole, at its size without any content.
+    fn hole_chunk(
+        &self,
+        out_dir: &Path,
+        index: usize,
+        size: usize,
+    ) -> Result<ChunkInfo, SplitError> {
+        self.create_chunk(
+            out_dir,
+            index,
+            size,
+            |output| output.set_len(size as u64).map(|_| true),
+            || {
+                hash_reader(
+                    &mut io::repeat(0).take(size as u64),
+                    self.buffer_capacity,
+                )
+            },
+        )
+        .and_then(|chunk| chunk.ok_or(SplitError::OutFileNotWritten))
+    }
+
+    /// Copy a chunk from the input file at the offset in the kernel.
+    ///
+    /// Returns `None` if the copy is not supported,
+    /// the chunk file is then not created.
+    fn copy_chunk(
+        &self,
+        input: &fs::File,
+        offset: u64,
+        out_dir: &Path,
+        index: usize,
+        size: usize,
+    ) -> Result<Option<ChunkInfo>, SplitError> {
+        self.create_chunk(
+            out_dir,
+            index,
+            size,
+            |output| copy_range(input, offset, output, size),
+            || self.hash_range(input, offset, size),
+        )
+    }
+
+    /// Clone a chunk from the input file at the offset with a reflink.
+    ///
+    /// Returns `None` if the clone is not supported,
+    /// the chunk file is then not created.
+    fn clone_chunk(
+        &self,
+        input: &fs::File,
+        offset: u64,
+        out_dir: &Path,
+        index: usize,
+        size: usize,
+    ) -> Result<Option<ChunkInfo>, SplitError> {
+        self.create_chunk(
+            out_dir,
+            index,
+            size,
+            |output| clone_range(input, offset, output, 0, size),
+            || self.hash_range(input, offset, size),
+        )
+    }
+
+    /// Hash the range of the input file a chunk is copied from.
+    ///
+    /// The position of the input file is moved.
+    fn hash_range(
+        &self,
+        mut input: &fs::File,
+        offset: u64,
+        size: usize,
+    ) -> io::Result<Digest> {
+        input.seek(io::SeekFrom::Start(offset))?;
+
+        hash_reader(&mut input.take(size as u64), self.buffer_capacity)
+    }
+
+    /// Create a chunk file of the size with the content written by `write`,
+    /// verified against the hash from `expected` if the writes are verified.
+    ///
+    /// Returns `None` if nothing is written, the chunk file is then removed.
+    fn create_chunk(
+        &self,
+        out_dir: &Path,
+        index: usize,
+        size: usize,
+        write: impl FnOnce(&fs::File) -> io::Result<bool>,
+        expected: impl FnOnce() -> io::Result<Digest>,
+    ) -> Result<Option<ChunkInfo>, SplitError> {
+        let output_path: PathBuf = out_dir.join(self.naming.file_name(index));
+
+        // write to a temporary file to rename, if atomic
+        let write_path: PathBuf = match self.atomic {
+            | true => temp_path(&output_path),
+            | false => output_path.clone(),
+        };
+
+        let output: fs::File = fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(&write_path)
+            .map_err(|_| SplitError::OutFileNotOpened)?;
+
+        if !write(&output).map_err(|_| SplitError::OutFileNotWritten)? {
+            drop(output);
+
+            let _ = fs::remove_file(&write_path);
+
+            return Ok(None);
+        }
+
+        self.sync
+            .sync_file(&output)
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+
+        drop(output);
+
+        if self.verify_writes {
+            let expected: Digest =
+                expected().map_err(|_| SplitError::InFileNotRead)?;
+
+            self.verify_chunk(&write_path, &expected)?;
+        }
+
+        if self.atomic {
+            fs::rename(&write_path, &output_path)
+                .map_err(|_| SplitError::OutFileNotWritten)?;
+        }
+
+        let chunk: ChunkInfo = ChunkInfo { index, path: output_path, size };
+
+        self.first_chunk(&chunk);
+
+        Ok(Some(chunk))
+    }
+
+    /// Remove the chunks written to the output directory.
+    ///
+    /// The chunks are kept to be resumed later, if resumed.
+    pub(crate) fn remove_chunks(
+        &self,
+        out_dir: &Path,
+        total_chunks: usize,
+    ) {
+        if self.resume {
+            return;
+        }
+
+        self.discard_chunks(out_dir, total_chunks);
+    }
+
+    /// Remove the chunks written, even when resumed.
+    pub(crate) fn discard_chunks(
+        &self,
+        out_dir: &Path,
+        total_chunks: usize,
+    ) {
+        for i in 0..total_chunks {
+            let _ = fs::remove_file(out_dir.join(self.naming.file_name(i)));
+        }
+    }
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sizes of the chunks to split into.
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkLayout {
+    /// The sizes of the leading chunks, before the chunks of `chunk_size`.
+    pub(crate) schedule: Vec<usize>,
+    /// The size of each chunk except the scheduled and the last ones.
+    pub(crate) chunk_size: usize,
+    /// The index and the size of the last chunk, if it is fixed.
+    pub(crate) last: Option<(usize, usize)>,
+    /// The strategy to cut the chunks read up to their size.
+    pub(crate) strategy: ChunkStrategy,
+}
+
+impl ChunkLayout {
+    /// Get the size of the next chunk at the start of the data read.
+    pub(crate) fn cut(
+        &self,
+        data: &[u8],
+    ) -> usize {
+        self.strategy.cut(data)
+    }
+
+    /// Get the size of the chunk at an index.
+    pub(crate) fn len(
+        &self,
+        index: usize,
+    ) -> usize {
+        match self.last {
+            | Some((last, size)) if index >= last => size,
+            | _ => match self.schedule.get(index) {
+                | Some(size) => *size,
+                | None => self.chunk_size,
+            },
+        }
+    }
+
+    /// Get the offset of the chunk at an index.
+    pub(crate) fn offset(
+        &self,
+        index: usize,
+    ) -> u64 {
+        let scheduled: usize = index.min(self.schedule.len());
+
+        self.schedule[..scheduled].iter().map(|size| *size as u64).sum::<u64>()
+            + (index - scheduled) as u64 * self.chunk_size as u64
+    }
+
+    /// Get the size of the chunk at an index, in a file of the size.
+    pub(crate) fn len_in(
+        &self,
+        index: usize,
+        file_size: u64,
+    ) -> usize {
+        let remaining: u64 = file_size.saturating_sub(self.offset(index));
+
+        (self.len(index) as u64).min(remaining) as usize
+    }
+
+    /// Get the number of chunks of a file of the size, if known before
+    /// it is split.
+    ///
+    /// The number of chunks cut by the content is only known once split.
+    pub(crate) fn total(
+        &self,
+        file_size: Option<u64>,
+    ) -> Option<usize> {
+        match self.strategy {
+            | ChunkStrategy::Fixed => file_size.map(|size| self.count(size)),
+            | ChunkStrategy::ContentDefined { .. } => None,
+        }
+    }
+
+    /// Get the maximum size of the chunks.
+    pub(crate) fn max_len(&self) -> usize {
+        let max: usize =
+            self.schedule.iter().copied().fold(self.chunk_size, usize::max);
+
+        self.last.map_or(max, |(_, size)| size.max(max))
+    }
+
+    /// Get the number of chunks of a file.
+    pub(crate) fn count(
+        &self,
+        file_size: u64,
+    ) -> usize {
+        if let Some((last, _)) = self.last {
+            return last + 1;
+        }
+
+        let mut remaining: u64 = file_size;
+
+        let mut count: usize = 0;
+
+        for size in self.schedule.iter() {
+            if remaining == 0 {
+                return count;
+            }
+
+            remaining = remaining.saturating_sub(*size as u64);
+
+            count += 1;
+        }
+
+        count + chunk_count(remaining, self.chunk_size)
+    }
+}
+
+/// Reader as the input of a split process, set by [`Split::in_reader`].
+///
+/// The reader is shared by the clones of the process,
+/// and taken by the first run.
+#[derive(Clone)]
+pub struct InReader {
+    reader: Arc<Mutex<Option<Box<dyn io::Read + Send>>>>,
+    size: Option<u64>,
+}
+
+impl InReader {
+    /// Create a new input from a reader, with its size if known.
+    pub fn new<R: io::Read + Send + 'static>(
+        reader: R,
+        size: Option<u64>,
+    ) -> Self {
+        Self { reader: Arc::new(Mutex::new(Some(Box::new(reader)))), size }
+    }
+
+    /// Get the size of the input, if known.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// Check if the reader is already taken by a run.
+    pub fn is_consumed(&self) -> bool {
+        self.reader.lock().map_or(true, |reader| reader.is_none())
+    }
+
+    /// Take the reader, `None` if already taken.
+    fn take(&self) -> Option<Box<dyn io::Read + Send>> {
+        self.reader.lock().ok()?.take()
+    }
+}
+
+impl fmt::Debug for InReader {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("InReader")
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Remote input of a split process.
+///
+/// With a refresh handler, a download interrupted midway is requested
+/// again from the position read.
+#[cfg(feature = "reqwest")]
+struct UrlReader<'a> {
+    client: reqwest::blocking::Client,
+    url: String,
+    on_url_expired: Option<&'a RefreshHandler>,
+    response: reqwest::blocking::Response,
+    position: u64,
+    /// The position of the last interrupted read, not requested again.
+    interrupted: Option<u64>,
+}
+
+#[cfg(feature = "reqwest")]
+impl<'a> UrlReader<'a> {
+    /// Request the remote input from a position.
+    fn open(
+        client: reqwest::blocking::Client,
+        mut url: String,
+        on_url_expired: Option<&'a RefreshHandler>,
+        position: u64,
+    ) -> Result<Self, SplitError> {
+        let response: reqwest::blocking::Response =
+            request_from(&client, &mut url, on_url_expired, position)?;
+
+        Ok(Self {
+            client,
+            url,
+            on_url_expired,
+            response,
+            position,
+            interrupted: None,
+        })
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl io::Read for UrlReader<'_> {
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        loop {
+            let error: io::Error = match self.response.read(buf) {
+                | Ok(len) => {
+                    self.position += len as u64;
+
+                    return Ok(len);
+                },
+                | Err(e) => e,
+            };
+
+            // without handler, or if no byte is read since requested again
+            if self.on_url_expired.is_none()
+                || self.interrupted == Some(self.position)
+            {
+                return Err(error);
+            }
+
+            self.interrupted = Some(self.position);
+
+            self.response = request_from(
+                &self.client,
+                &mut self.url,
+                self.on_url_expired,
+                self.position,
+            )
+            .map_err(|_| error)?;
+        }
+    }
+}
+
+/// Hash the content of a reader to the end
+/// with the algorithm of the verified writes.
+fn hash_reader<R: io::Read>(
+    reader: &mut R,
+    buffer_capacity: usize,
+) -> io::Result<Digest> {
+    let mut hasher: Hasher = VERIFY_ALGORITHM.hasher();
+
+    let mut buffer: Vec<u8> = vec![0; buffer_capacity.max(1)];
+
+    loop {
+        match reader.read(&mut buffer)? {
+            | 0 => break,
+            | n => hasher.update(&buffer[..n]),
+        }
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Request a remote input from a position,
+/// skipping the bytes before if the range is not supported by the server.
+#[cfg(feature = "reqwest")]
+fn request_from(
+    client: &reqwest::blocking::Client,
+    url: &mut String,
+    on_url_expired: Option<&RefreshHandler>,
+    position: u64,
+) -> Result<reqwest::blocking::Response, SplitError> {
+    use reqwest::{StatusCode, header::RANGE};
+
+    let mut response: reqwest::blocking::Response =
+        send_refreshed(url, on_url_expired, |url| match position {
+            | 0 => client.get(url),
+            | _ => client.get(url).header(RANGE, format!("bytes={position}-")),
+        })?;
+
+    if position > 0 && response.status() != StatusCode::PARTIAL_CONTENT {
+        io::copy(&mut (&mut response).take(position), &mut io::sink())
+            .map_err(|_| SplitError::InUrlNotRead)?;
+    }
+
+    Ok(response)
+}
+
+/// Send a request to a remote input,
+/// sent again to the refreshed URL if rejected as expired.
+#[cfg(feature = "reqwest")]
+fn send_refreshed(
+    url: &mut String,
+    on_url_expired: Option<&RefreshHandler>,
+    request: impl Fn(&str) -> reqwest::blocking::RequestBuilder,
+) -> Result<reqwest::blocking::Response, SplitError> {
+    use reqwest::StatusCode;
+
+    let response: reqwest::blocking::Response =
+        request(url).send().map_err(|_| SplitError::InUrlNotRead)?;
+
+    let response: reqwest::blocking::Response =
+        match (response.status(), on_url_expired) {
+            | (
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN,
+                Some(handler),
+            ) => {
+                *url = handler.refresh(url).ok_or(SplitError::InUrlExpired)?;
+
+                request(url).send().map_err(|_| SplitError::InUrlNotRead)?
+            },
+            | _ => response,
+        };
+
+    response.error_for_status().map_err(|_| SplitError::InUrlNotRead)
 }
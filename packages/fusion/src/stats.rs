@@ -0,0 +1,348 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[cfg(feature = "compression")]
+use std::io;
+
+#[cfg(feature = "compression")]
+use crate::codec::Codec;
+
+use crate::{
+    bagit::{self, PAYLOAD_DIR_NAME},
+    chunk::ChunkNaming,
+    hash::HashAlgorithm,
+};
+
+/// Statistics of a stored chunk set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkStats {
+    /// The number of chunks.
+    pub chunk_count: usize,
+    /// The number of bytes of the chunks as stored.
+    pub total_bytes: u64,
+    /// The size of the smallest chunk, `0` without chunk.
+    pub min_chunk_size: usize,
+    /// The average size of the chunks, rounded down, `0` without chunk.
+    pub avg_chunk_size: usize,
+    /// The size of the largest chunk, `0` without chunk.
+    pub max_chunk_size: usize,
+    /// The codec of the chunks, if all compressed with the same codec.
+    #[cfg(feature = "compression")]
+    pub compression: Option<Codec>,
+    /// The ratio of the content size to the stored size,
+    /// if the chunks are compressed.
+    #[cfg(feature = "compression")]
+    pub compression_ratio: Option<f64>,
+    /// The algorithm of the manifest of the chunks, if any.
+    pub hash_algorithm: Option<HashAlgorithm>,
+}
+
+/// Stats process error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsError {
+    InPathNotFound,
+    InPathNotSet,
+    InPathNotRead,
+    ManifestInvalid,
+}
+
+impl StatsError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::InPathNotFound => "in_path_not_found",
+            | Self::InPathNotSet => "in_path_not_set",
+            | Self::InPathNotRead => "in_path_not_read",
+            | Self::ManifestInvalid => "manifest_invalid",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::InPathNotFound => "The input path not found.",
+            | Self::InPathNotSet => "The input path is not set.",
+            | Self::InPathNotRead => "The input path could not be read.",
+            | Self::ManifestInvalid => "The manifest is invalid.",
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Process to summarize a stored chunk set.
+///
+/// The input path is either a chunk directory, a bag written by
+/// [`Bag`](crate::bagit::Bag), or the payload manifest of a bag such as
+/// `manifest-sha256.txt`. Only the files following the naming scheme are
+/// counted, not the parity chunks, the PAR2 files nor the scripts.
+/// The compressed chunks are detected by their frames and decompressed
+/// to measure the compression ratio.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::stats::{ChunkStats, stats};
+///
+/// let stats: ChunkStats =
+///     stats(PathBuf::from("path").join("to").join("dir")).unwrap();
+///
+/// println!("{} chunks, {} bytes", stats.chunk_count, stats.total_bytes);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub in_path: Option<Arc<Path>>,
+    pub naming: ChunkNaming,
+}
+
+impl Stats {
+    /// Create a new stats process.
+    pub fn new() -> Self {
+        Self { in_path: None, naming: ChunkNaming::new() }
+    }
+
+    /// Create a new stats process from an existing one.
+    pub fn from<P: Into<Stats>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the chunk directory, the bag or the manifest to summarize.
+    pub fn in_path<InPath: AsRef<Path>>(
+        mut self,
+        path: InPath,
+    ) -> Self {
+        self.in_path = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the naming scheme of the chunks.
+    ///
+    /// By default, the chunks are named by their index.
+    pub fn naming(
+        mut self,
+        naming: ChunkNaming,
+    ) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Run the stats process.
+    pub fn run(&self) -> Result<ChunkStats, StatsError> {
+        let in_path: &Path = match self.in_path {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_path not exists
+                if !p.exists() {
+                    return Err(StatsError::InPathNotFound);
+                }
+
+                p
+            },
+            | None => return Err(StatsError::InPathNotSet),
+        };
+
+        let (chunks, hash_algorithm) = match in_path.is_dir() {
+            | true if in_path.join(bagit::DECLARATION_NAME).is_file() => {
+                let (algorithm, path) = find_manifest(in_path)?;
+
+                (self.manifest_chunks(&path, algorithm)?, Some(algorithm))
+            },
+            | true => (self.dir_chunks(in_path)?, None),
+            | false => {
+                let algorithm: HashAlgorithm = manifest_algorithm(in_path)
+                    .ok_or(StatsError::ManifestInvalid)?;
+
+                (self.manifest_chunks(in_path, algorithm)?, Some(algorithm))
+            },
+        };
+
+        let mut sizes: Vec<usize> = Vec::with_capacity(chunks.len());
+
+        for path in chunks.iter() {
+            sizes.push(
+                fs::metadata(path).map_err(|_| StatsError::InPathNotRead)?.len()
+                    as usize,
+            );
+        }
+
+        let total_bytes: u64 = sizes.iter().map(|s| *s as u64).sum();
+
+        #[cfg(feature = "compression")]
+        let (compression, compression_ratio) =
+            compression_of(&chunks, total_bytes)?;
+
+        Ok(ChunkStats {
+            chunk_count: sizes.len(),
+            total_bytes,
+            min_chunk_size: sizes.iter().copied().min().unwrap_or(0),
+            avg_chunk_size: total_bytes
+                .checked_div(sizes.len() as u64)
+                .unwrap_or(0) as usize,
+            max_chunk_size: sizes.iter().copied().max().unwrap_or(0),
+            #[cfg(feature = "compression")]
+            compression,
+            #[cfg(feature = "compression")]
+            compression_ratio,
+            hash_algorithm,
+        })
+    }
+
+    /// List the chunks of a directory.
+    fn dir_chunks(
+        &self,
+        in_dir: &Path,
+    ) -> Result<Vec<PathBuf>, StatsError> {
+        let read_dir: fs::ReadDir =
+            fs::read_dir(in_dir).map_err(|_| StatsError::InPathNotRead)?;
+
+        let mut chunks: Vec<(usize, PathBuf)> = read_dir
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .filter_map(|path| Some((self.naming.index_of(&path)?, path)))
+            .collect();
+
+        chunks.sort();
+
+        Ok(chunks.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// List the chunks of the payload listed by a manifest.
+    fn manifest_chunks(
+        &self,
+        manifest: &Path,
+        algorithm: HashAlgorithm,
+    ) -> Result<Vec<PathBuf>, StatsError> {
+        let content: String = fs::read_to_string(manifest)
+            .map_err(|_| StatsError::InPathNotRead)?;
+
+        let bag_dir: &Path = manifest.parent().unwrap_or(Path::new(""));
+
+        let mut chunks: Vec<(usize, PathBuf)> =
+            bagit::decode_manifest(algorithm, &content)
+                .map_err(|_| StatsError::ManifestInvalid)?
+                .into_iter()
+                .filter(|entry| entry.path.starts_with(PAYLOAD_DIR_NAME))
+                .filter_map(|entry| {
+                    Some((
+                        self.naming.index_of(&entry.path)?,
+                        bag_dir.join(entry.path),
+                    ))
+                })
+                .collect();
+
+        chunks.sort();
+
+        Ok(chunks.into_iter().map(|(_, path)| path).collect())
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Summarize a stored chunk set, with the default options of [`Stats`].
+pub fn stats<InPath: AsRef<Path>>(
+    path: InPath
+) -> Result<ChunkStats, StatsError> {
+    Stats::new().in_path(path).run()
+}
+
+/// Get the algorithm of a payload manifest by its name.
+fn manifest_algorithm(path: &Path) -> Option<HashAlgorithm> {
+    let name: &str = path.file_name()?.to_str()?;
+
+    [HashAlgorithm::Sha256, HashAlgorithm::Blake3]
+        .into_iter()
+        .find(|algorithm| bagit::manifest_name(*algorithm, false) == name)
+}
+
+/// Find the payload manifest of a bag.
+fn find_manifest(
+    bag_dir: &Path
+) -> Result<(HashAlgorithm, PathBuf), StatsError> {
+    let read_dir: fs::ReadDir =
+        fs::read_dir(bag_dir).map_err(|_| StatsError::InPathNotRead)?;
+
+    let mut manifests: Vec<(HashAlgorithm, PathBuf)> = read_dir
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter_map(|path| Some((manifest_algorithm(&path)?, path)))
+        .collect();
+
+    manifests.sort_by(|a, b| a.1.cmp(&b.1));
+
+    manifests.into_iter().next().ok_or(StatsError::ManifestInvalid)
+}
+
+/// Detect the codec of a chunk by the magic bytes of its frame.
+#[cfg(feature = "compression")]
+fn detect_codec(head: &[u8]) -> Option<Codec> {
+    match head {
+        | [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Codec::Zstd { level: 0 }),
+        | [0x04, 0x22, 0x4d, 0x18, ..] => Some(Codec::Lz4),
+        | [0x1f, 0x8b, ..] => Some(Codec::Gzip { level: 6 }),
+        | _ => None,
+    }
+}
+
+/// Get the codec of the chunks and their compression ratio,
+/// if all compressed with the same codec.
+#[cfg(feature = "compression")]
+fn compression_of(
+    chunks: &[PathBuf],
+    total_bytes: u64,
+) -> Result<(Option<Codec>, Option<f64>), StatsError> {
+    let mut codec: Option<Codec> = None;
+
+    let mut content_bytes: u64 = 0;
+
+    for path in chunks {
+        let data: Vec<u8> =
+            fs::read(path).map_err(|_| StatsError::InPathNotRead)?;
+
+        let detected: Codec = match detect_codec(&data) {
+            | Some(detected)
+                if codec.is_none_or(|c| c.as_str() == detected.as_str()) =>
+            {
+                detected
+            },
+            | _ => return Ok((None, None)),
+        };
+
+        // not a frame of the codec, despite the magic bytes
+        content_bytes += match detected.decoder(data.as_slice()) {
+            | Ok(mut decoder) => {
+                match io::copy(&mut decoder, &mut io::sink()) {
+                    | Ok(len) => len,
+                    | Err(_) => return Ok((None, None)),
+                }
+            },
+            | Err(_) => return Ok((None, None)),
+        };
+
+        codec = Some(detected);
+    }
+
+    let ratio: Option<f64> = codec
+        .filter(|_| total_bytes > 0)
+        .map(|_| content_bytes as f64 / total_bytes as f64);
+
+    Ok((codec, ratio))
+}
@@ -0,0 +1,984 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[cfg(any(feature = "object_store", feature = "reqwest"))]
+use std::fmt;
+
+#[cfg(feature = "reqwest")]
+use std::{
+    collections::{HashSet, VecDeque},
+    io,
+    sync::mpsc::{Receiver, SyncSender, sync_channel},
+    thread,
+    time::Duration,
+};
+
+#[cfg(feature = "reqwest")]
+use crate::hash::{Digest, HashAlgorithm};
+
+#[cfg(feature = "object_store")]
+use object_store::{ObjectStore, PutPayload, path::Path as ObjectPath};
+
+use crate::{bitmap::ChunkBitmap, chunk::ChunkNaming};
+
+/// Store error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreError {
+    ChunkNotFound,
+    ChunkNotRead,
+    ChunkNotWritten,
+}
+
+impl StoreError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::ChunkNotFound => "chunk_not_found",
+            | Self::ChunkNotRead => "chunk_not_read",
+            | Self::ChunkNotWritten => "chunk_not_written",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::ChunkNotFound => "The chunk not found.",
+            | Self::ChunkNotRead => "The chunk could not be read.",
+            | Self::ChunkNotWritten => "The chunk could not be written.",
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Storage of chunks addressed by index.
+pub trait ChunkStore {
+    /// Check if the chunk is in the store.
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError>;
+
+    /// Read the content of the chunk.
+    fn read(
+        &self,
+        index: usize,
+    ) -> Result<Vec<u8>, StoreError>;
+
+    /// Write the content of the chunk, replacing the existing one.
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError>;
+
+    /// Get the bitmap of the chunks in the store, out of `len` chunks.
+    fn bitmap(
+        &self,
+        len: usize,
+    ) -> Result<ChunkBitmap, StoreError> {
+        let mut bitmap: ChunkBitmap = ChunkBitmap::new(len);
+
+        for index in 0..len {
+            if self.contains(index)? {
+                bitmap.insert(index);
+            }
+        }
+
+        Ok(bitmap)
+    }
+}
+
+impl<S: ChunkStore + ?Sized> ChunkStore for Box<S> {
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError> {
+        (**self).contains(index)
+    }
+
+    fn read(
+        &self,
+        index: usize,
+    ) -> Result<Vec<u8>, StoreError> {
+        (**self).read(index)
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        (**self).write(index, data)
+    }
+}
+
+/// Store of chunk files in a directory, as written by the split process.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     chunk::ChunkNaming,
+///     store::{ChunkStore as _, DirStore},
+/// };
+///
+/// let store: DirStore = DirStore::new(
+///     PathBuf::from("path").join("to").join("dir"),
+///     ChunkNaming::new(),
+/// );
+///
+/// let chunk: Vec<u8> = store.read(0).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DirStore {
+    pub dir: Arc<Path>,
+    pub naming: ChunkNaming,
+}
+
+impl DirStore {
+    /// Create a new store of the chunk files in the directory.
+    pub fn new<Dir: AsRef<Path>>(
+        dir: Dir,
+        naming: ChunkNaming,
+    ) -> Self {
+        Self { dir: Arc::from(dir.as_ref()), naming }
+    }
+
+    /// Get the path of the chunk file.
+    pub fn path(
+        &self,
+        index: usize,
+    ) -> PathBuf {
+        self.dir.join(self.naming.file_name(index))
+    }
+}
+
+impl ChunkStore for DirStore {
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError> {
+        Ok(self.path(index).is_file())
+    }
+
+    fn read(
+        &self,
+        index: usize,
+    ) -> Result<Vec<u8>, StoreError> {
+        let path: PathBuf = self.path(index);
+
+        // if chunk not exists
+        if !path.is_file() {
+            return Err(StoreError::ChunkNotFound);
+        }
+
+        fs::read(path).map_err(|_| StoreError::ChunkNotRead)
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|_| StoreError::ChunkNotWritten)?;
+
+        fs::write(self.path(index), data)
+            .map_err(|_| StoreError::ChunkNotWritten)
+    }
+}
+
+/// Store of chunks as objects under a prefix of an [`ObjectStore`],
+/// such as a bucket of Amazon S3, Google Cloud Storage or Azure Blob Storage.
+///
+/// The objects are named by the naming scheme under the prefix.
+/// The requests are run on the handle of a `tokio` runtime and block
+/// until completed, so the store must not be used from an asynchronous
+/// task of the runtime, but from a blocking one, such as with
+/// `tokio::task::spawn_blocking`.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{path::PathBuf, sync::Arc};
+///
+/// use filerune_fusion::{
+///     chunk::ChunkNaming,
+///     split::{Split, SplitResult},
+///     store::BucketStore,
+/// };
+/// use object_store::memory::InMemory;
+///
+/// let runtime: tokio::runtime::Runtime =
+///     tokio::runtime::Builder::new_current_thread().build().unwrap();
+///
+/// let mut store: BucketStore = BucketStore::new(
+///     Arc::new(InMemory::new()),
+///     "uploads/file",
+///     ChunkNaming::new(),
+///     runtime.handle().clone(),
+/// );
+///
+/// let result: SplitResult = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .run_file_to_store(&mut store)
+///     .unwrap();
+/// ```
+#[cfg(feature = "object_store")]
+#[derive(Clone)]
+pub struct BucketStore {
+    pub store: Arc<dyn ObjectStore>,
+    pub prefix: ObjectPath,
+    pub naming: ChunkNaming,
+    pub handle: tokio::runtime::Handle,
+}
+
+#[cfg(feature = "object_store")]
+impl BucketStore {
+    /// Create a new store of the chunks under the prefix.
+    pub fn new<Prefix: AsRef<str>>(
+        store: Arc<dyn ObjectStore>,
+        prefix: Prefix,
+        naming: ChunkNaming,
+        handle: tokio::runtime::Handle,
+    ) -> Self {
+        Self {
+            store,
+            prefix: ObjectPath::from(prefix.as_ref()),
+            naming,
+            handle,
+        }
+    }
+
+    /// Get the location of the chunk object.
+    pub fn location(
+        &self,
+        index: usize,
+    ) -> ObjectPath {
+        self.prefix.child(self.naming.file_name(index))
+    }
+}
+
+#[cfg(feature = "object_store")]
+impl fmt::Debug for BucketStore {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("BucketStore")
+            .field("store", &self.store.to_string())
+            .field("prefix", &self.prefix)
+            .field("naming", &self.naming)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "object_store")]
+impl ChunkStore for BucketStore {
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError> {
+        match self.handle.block_on(self.store.head(&self.location(index))) {
+            | Ok(_) => Ok(true),
+            | Err(object_store::Error::NotFound { .. }) => Ok(false),
+            | Err(_) => Err(StoreError::ChunkNotRead),
+        }
+    }
+
+    fn read(
+        &self,
+        index: usize,
+    ) -> Result<Vec<u8>, StoreError> {
+        let location: ObjectPath = self.location(index);
+
+        self.handle.block_on(async {
+            match self.store.get(&location).await {
+                | Ok(result) => result
+                    .bytes()
+                    .await
+                    .map(|bytes| bytes.to_vec())
+                    .map_err(|_| StoreError::ChunkNotRead),
+                | Err(object_store::Error::NotFound { .. }) => {
+                    Err(StoreError::ChunkNotFound)
+                },
+                | Err(_) => Err(StoreError::ChunkNotRead),
+            }
+        })
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        let payload: PutPayload = PutPayload::from(data.to_vec());
+
+        self.handle
+            .block_on(self.store.put(&self.location(index), payload))
+            .map(|_| ())
+            .map_err(|_| StoreError::ChunkNotWritten)
+    }
+}
+
+/// Store of chunks in memory, without a file system.
+///
+/// The chunks are kept as written, encoded if the process encodes them,
+/// so it stands for a directory or a remote store in tests and in
+/// environments without a writable file system.
+///
+/// ## Example
+///
+/// ```no_run
+/// use filerune_fusion::{
+///     merge::Merge,
+///     split::{Split, SplitResult},
+///     store::MemoryStore,
+/// };
+///
+/// let mut store: MemoryStore = MemoryStore::new();
+///
+/// let result: SplitResult =
+///     Split::new().chunk_size(4).run_to_store(b"hello world", &mut store).unwrap();
+///
+/// let mut output: Vec<u8> = Vec::new();
+///
+/// Merge::new()
+///     .run_from_store_to_sink(&store, result.total_chunks, &mut output)
+///     .unwrap();
+///
+/// assert_eq!(output, b"hello world");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStore {
+    pub chunks: HashMap<usize, Vec<u8>>,
+}
+
+impl MemoryStore {
+    /// Create a new empty store.
+    pub fn new() -> Self {
+        Self { chunks: HashMap::new() }
+    }
+
+    /// Get the number of chunks in the store.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Check if the store has no chunk.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Remove a chunk from the store, returning its content if any.
+    pub fn remove(
+        &mut self,
+        index: usize,
+    ) -> Option<Vec<u8>> {
+        self.chunks.remove(&index)
+    }
+}
+
+impl ChunkStore for MemoryStore {
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError> {
+        Ok(self.chunks.contains_key(&index))
+    }
+
+    fn read(
+        &self,
+        index: usize,
+    ) -> Result<Vec<u8>, StoreError> {
+        self.chunks.get(&index).cloned().ok_or(StoreError::ChunkNotFound)
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        self.chunks.insert(index, data.to_vec());
+
+        Ok(())
+    }
+}
+
+/// Destination of a merged output, such as a remote object.
+///
+/// The content is written in order, then the output is completed with
+/// [`MergeSink::finish`]. An output dropped before it is finished
+/// should be discarded by the destination.
+pub trait MergeSink {
+    /// Append the content to the output.
+    fn write(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), StoreError>;
+
+    /// Complete the output once all content is written.
+    fn finish(&mut self) -> Result<(), StoreError>;
+}
+
+/// The merged output is appended to the buffer in memory.
+impl MergeSink for Vec<u8> {
+    fn write(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        self.extend_from_slice(data);
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), StoreError> {
+        Ok(())
+    }
+}
+
+/// Function returning the URL to request instead of an expired one.
+#[cfg(feature = "reqwest")]
+type RefreshFn = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+/// Handler to refresh the URL of a remote resource once expired,
+/// such as a presigned URL or a URL carrying a short-lived token.
+///
+/// The handler receives the expired URL and returns the URL to request
+/// instead, or `None` to fail.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::split::Split;
+///
+/// Split::new()
+///     .in_url("https://example.com/file?token=expired")
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .on_url_expired(|_| Some("https://example.com/file?token=new".into()))
+///     .run()
+///     .unwrap();
+/// ```
+#[cfg(feature = "reqwest")]
+#[derive(Clone)]
+pub struct RefreshHandler {
+    handler: Arc<RefreshFn>,
+}
+
+#[cfg(feature = "reqwest")]
+impl RefreshHandler {
+    /// Create a new refresh handler.
+    pub fn new<F: Fn(&str) -> Option<String> + Send + Sync + 'static>(
+        handler: F
+    ) -> Self {
+        Self { handler: Arc::new(handler) }
+    }
+
+    /// Get a refreshed URL from the handler.
+    pub fn refresh(
+        &self,
+        url: &str,
+    ) -> Option<String> {
+        (self.handler)(url)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl fmt::Debug for RefreshHandler {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("RefreshHandler").finish_non_exhaustive()
+    }
+}
+
+/// Remote chunk of a hosted chunk set, with its expected size and hash.
+///
+/// A list of chunk URLs is read from a manifest with
+/// [`ChunkUrl::parse_manifest`], and merged with
+/// [`Merge::in_urls`](crate::merge::Merge::in_urls).
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkUrl {
+    /// The URL of the chunk.
+    pub url: String,
+    /// The expected size of the chunk in bytes, as downloaded.
+    pub size: Option<usize>,
+    /// The expected hash of the chunk, as downloaded.
+    pub hash: Option<Digest>,
+}
+
+#[cfg(feature = "reqwest")]
+impl ChunkUrl {
+    /// Create a new chunk URL, without expected size and hash.
+    pub fn new<Url: Into<String>>(url: Url) -> Self {
+        Self { url: url.into(), size: None, hash: None }
+    }
+
+    /// Set the expected size of the chunk.
+    pub fn size(
+        mut self,
+        size: usize,
+    ) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Set the expected hash of the chunk.
+    pub fn hash(
+        mut self,
+        hash: Digest,
+    ) -> Self {
+        self.hash = Some(hash);
+        self
+    }
+
+    /// Parse a manifest of chunk URLs, in order.
+    ///
+    /// Each line holds the URL of a chunk, optionally followed by its size
+    /// and its hash as `<algorithm>:<hex>`, separated by spaces, such as
+    /// `https://example.com/file.0 1048576 sha256:9f86...`. Empty lines and
+    /// lines starting with `#` are skipped.
+    ///
+    /// Returns `None` if a line is invalid.
+    pub fn parse_manifest(manifest: &str) -> Option<Vec<Self>> {
+        let mut urls: Vec<Self> = Vec::new();
+
+        for line in manifest.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+
+            let mut url: Self = Self::new(fields.next()?);
+
+            if let Some(size) = fields.next() {
+                url.size = Some(size.parse().ok()?);
+            }
+
+            if let Some(hash) = fields.next() {
+                let (algorithm, hex) = hash.split_once(':')?;
+
+                url.hash = Some(Digest::from_hex(
+                    HashAlgorithm::parse(algorithm)?,
+                    hex,
+                )?);
+            }
+
+            if fields.next().is_some() {
+                return None;
+            }
+
+            urls.push(url);
+        }
+
+        Some(urls)
+    }
+
+    /// Check if the downloaded content matches the expected size and hash.
+    pub fn matches(
+        &self,
+        data: &[u8],
+    ) -> bool {
+        self.size.is_none_or(|size| size == data.len())
+            && self
+                .hash
+                .as_ref()
+                .is_none_or(|hash| hash.algorithm.digest(data) == *hash)
+    }
+}
+
+/// Sink uploading the merged output with an HTTP request,
+/// streamed with the chunked transfer encoding.
+///
+/// The request is sent from a background thread as the content is written.
+/// If the sink is dropped before it is finished, the request is aborted.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     merge::{Merge, MergeResult},
+///     store::HttpSink,
+/// };
+///
+/// let mut sink: HttpSink = HttpSink::put("https://example.com/file");
+///
+/// let result: MergeResult = Merge::new()
+///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+///     .run_to_sink(&mut sink)
+///     .unwrap();
+/// ```
+#[cfg(feature = "reqwest")]
+#[derive(Debug)]
+pub struct HttpSink {
+    sender: Option<SyncSender<Option<Vec<u8>>>>,
+    request: Option<thread::JoinHandle<Result<(), StoreError>>>,
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpSink {
+    /// Create a new sink sending the content with a `PUT` request to the URL.
+    pub fn put<Url: AsRef<str>>(url: Url) -> Self {
+        Self::new(reqwest::blocking::Client::new().put(url.as_ref()))
+    }
+
+    /// Create a new sink sending the content as the body of a request,
+    /// such as with the method or the headers required by the server.
+    pub fn new(request: reqwest::blocking::RequestBuilder) -> Self {
+        let (sender, receiver) = sync_channel::<Option<Vec<u8>>>(4);
+
+        let body: reqwest::blocking::Body =
+            reqwest::blocking::Body::new(ChannelReader {
+                receiver,
+                data: Vec::new(),
+                position: 0,
+                finished: false,
+            });
+
+        let request: thread::JoinHandle<Result<(), StoreError>> =
+            thread::spawn(move || {
+                request
+                    .body(body)
+                    .send()
+                    .and_then(|r| r.error_for_status())
+                    .map(|_| ())
+                    .map_err(|_| StoreError::ChunkNotWritten)
+            });
+
+        Self { sender: Some(sender), request: Some(request) }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl MergeSink for HttpSink {
+    fn write(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        match self.sender {
+            | Some(ref sender) => sender
+                .send(Some(data.to_vec()))
+                .map_err(|_| StoreError::ChunkNotWritten),
+            | None => Err(StoreError::ChunkNotWritten),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), StoreError> {
+        if let Some(sender) = self.sender.take() {
+            // the request fails later if the body is not received
+            let _ = sender.send(None);
+        }
+
+        match self.request.take() {
+            | Some(request) => {
+                request.join().unwrap_or(Err(StoreError::ChunkNotWritten))
+            },
+            | None => Ok(()),
+        }
+    }
+}
+
+/// Store uploading each chunk with an HTTP request to a URL template,
+/// such as `https://example.com/upload/{index}`.
+///
+/// The `{index}` placeholder of the template is replaced by the index of
+/// the chunk. The chunks are uploaded from background threads, up to the
+/// concurrency at a time, so the split process goes on while they are sent.
+/// A failed request is retried with an exponential backoff, unless
+/// rejected by the server with a client error other than
+/// `408 Request Timeout` or `429 Too Many Requests`. Once all chunks are
+/// written, [`HttpStore::finish`] waits for the uploads to complete.
+///
+/// The chunks are not read back, reading a chunk fails with
+/// [`StoreError::ChunkNotRead`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     split::{Split, SplitResult},
+///     store::HttpStore,
+/// };
+///
+/// let mut store: HttpStore = HttpStore::put("https://example.com/upload/{index}")
+///     .header("Authorization", "Bearer token")
+///     .concurrency(4)
+///     .retries(3);
+///
+/// let result: SplitResult = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .run_file_to_store(&mut store)
+///     .unwrap();
+///
+/// store.finish().unwrap();
+/// ```
+#[cfg(feature = "reqwest")]
+#[derive(Debug)]
+pub struct HttpStore {
+    pub method: reqwest::Method,
+    pub url_template: Arc<str>,
+    pub headers: Vec<(String, String)>,
+    pub concurrency: usize,
+    pub retries: usize,
+    pub retry_delay: Duration,
+    client: reqwest::blocking::Client,
+    written: HashSet<usize>,
+    uploads: VecDeque<thread::JoinHandle<Result<(), StoreError>>>,
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpStore {
+    /// Create a new store sending the chunks with a `PUT` request.
+    pub fn put<UrlTemplate: AsRef<str>>(url_template: UrlTemplate) -> Self {
+        Self::new(reqwest::Method::PUT, url_template)
+    }
+
+    /// Create a new store sending the chunks with a `POST` request.
+    pub fn post<UrlTemplate: AsRef<str>>(url_template: UrlTemplate) -> Self {
+        Self::new(reqwest::Method::POST, url_template)
+    }
+
+    /// Create a new store sending the chunks with the method.
+    pub fn new<UrlTemplate: AsRef<str>>(
+        method: reqwest::Method,
+        url_template: UrlTemplate,
+    ) -> Self {
+        Self {
+            method,
+            url_template: Arc::from(url_template.as_ref()),
+            headers: Vec::new(),
+            concurrency: 1,
+            retries: 0,
+            retry_delay: Duration::from_secs(1),
+            client: reqwest::blocking::Client::new(),
+            written: HashSet::new(),
+            uploads: VecDeque::new(),
+        }
+    }
+
+    /// Add a header to all requests, such as for authorization.
+    pub fn header<Name: Into<String>, Value: Into<String>>(
+        mut self,
+        name: Name,
+        value: Value,
+    ) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the maximum number of chunks to upload concurrently.
+    ///
+    /// By default, it is `1`.
+    pub fn concurrency(
+        mut self,
+        concurrency: usize,
+    ) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Set the number of times a failed request is retried.
+    ///
+    /// By default, it is `0`.
+    pub fn retries(
+        mut self,
+        retries: usize,
+    ) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set the delay before the first retry of a failed request,
+    /// doubled for each of the next ones.
+    ///
+    /// By default, it is 1 second.
+    pub fn retry_delay(
+        mut self,
+        delay: Duration,
+    ) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Get the URL of the chunk.
+    pub fn url(
+        &self,
+        index: usize,
+    ) -> String {
+        self.url_template.replace("{index}", &index.to_string())
+    }
+
+    /// Wait for the uploads in progress to complete.
+    pub fn finish(&mut self) -> Result<(), StoreError> {
+        let mut result: Result<(), StoreError> = Ok(());
+
+        // wait for all uploads, even after one failed
+        while let Some(upload) = self.uploads.pop_front() {
+            let upload: Result<(), StoreError> =
+                upload.join().unwrap_or(Err(StoreError::ChunkNotWritten));
+
+            result = result.and(upload);
+        }
+
+        result
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl ChunkStore for HttpStore {
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError> {
+        Ok(self.written.contains(&index))
+    }
+
+    fn read(
+        &self,
+        _index: usize,
+    ) -> Result<Vec<u8>, StoreError> {
+        Err(StoreError::ChunkNotRead)
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        // wait for the oldest upload to keep the concurrency
+        while self.uploads.len() >= self.concurrency.max(1) {
+            if let Some(upload) = self.uploads.pop_front() {
+                upload.join().unwrap_or(Err(StoreError::ChunkNotWritten))?;
+            }
+        }
+
+        let mut request: reqwest::blocking::RequestBuilder =
+            self.client.request(self.method.clone(), self.url(index));
+
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+
+        let data: Vec<u8> = data.to_vec();
+
+        let retries: usize = self.retries;
+
+        let retry_delay: Duration = self.retry_delay;
+
+        self.uploads.push_back(thread::spawn(move || {
+            send_retried(request, data, retries, retry_delay)
+        }));
+
+        self.written.insert(index);
+
+        Ok(())
+    }
+}
+
+/// Send a request with the body, retried with an exponential backoff.
+#[cfg(feature = "reqwest")]
+fn send_retried(
+    request: reqwest::blocking::RequestBuilder,
+    body: Vec<u8>,
+    retries: usize,
+    retry_delay: Duration,
+) -> Result<(), StoreError> {
+    use reqwest::StatusCode;
+
+    let mut delay: Duration = retry_delay;
+
+    for retry in 0..=retries {
+        let request: reqwest::blocking::RequestBuilder =
+            request.try_clone().ok_or(StoreError::ChunkNotWritten)?;
+
+        let retried: bool = match request.body(body.clone()).send() {
+            | Ok(response) if response.status().is_success() => {
+                return Ok(());
+            },
+            | Ok(response) => {
+                !response.status().is_client_error()
+                    || response.status() == StatusCode::REQUEST_TIMEOUT
+                    || response.status() == StatusCode::TOO_MANY_REQUESTS
+            },
+            | Err(_) => true,
+        };
+
+        if !retried || retry == retries {
+            break;
+        }
+
+        thread::sleep(delay);
+
+        delay = delay.saturating_mul(2);
+    }
+
+    Err(StoreError::ChunkNotWritten)
+}
+
+/// Body of a request, received from the writes of a sink.
+#[cfg(feature = "reqwest")]
+struct ChannelReader {
+    receiver: Receiver<Option<Vec<u8>>>,
+    data: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+#[cfg(feature = "reqwest")]
+impl io::Read for ChannelReader {
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        while self.position == self.data.len() {
+            if self.finished {
+                return Ok(0);
+            }
+
+            match self.receiver.recv() {
+                | Ok(Some(data)) => {
+                    self.data = data;
+                    self.position = 0;
+                },
+                | Ok(None) => self.finished = true,
+                // the sink is dropped before it is finished
+                | Err(_) => {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                },
+            }
+        }
+
+        let len: usize = buf.len().min(self.data.len() - self.position);
+
+        buf[..len]
+            .copy_from_slice(&self.data[self.position..self.position + len]);
+
+        self.position += len;
+
+        Ok(len)
+    }
+}
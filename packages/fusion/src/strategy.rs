@@ -0,0 +1,153 @@
+/// Strategy to cut the boundaries of the chunks.
+///
+/// Content-defined chunks are cut where a rolling hash of the content
+/// matches, with FastCDC, so an insertion or a deletion in the file only
+/// changes the chunks around it. The chunks of similar files are shared,
+/// which suits deduplicated storage and incremental transfers.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{split::Split, strategy::ChunkStrategy};
+///
+/// let process: Split = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .strategy(ChunkStrategy::ContentDefined {
+///         min: 256 * 1024,
+///         avg: 1024 * 1024,
+///         max: 4 * 1024 * 1024,
+///     });
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChunkStrategy {
+    /// Cut the chunks by the chunk size, the schedule
+    /// or the total number of chunks.
+    #[default]
+    Fixed,
+    /// Cut the chunks by the content, between the minimum
+    /// and the maximum sizes, around the average size.
+    ContentDefined {
+        /// The minimum size of each chunk except the last one.
+        min: usize,
+        /// The average size of the chunks.
+        avg: usize,
+        /// The maximum size of each chunk.
+        max: usize,
+    },
+}
+
+impl ChunkStrategy {
+    /// Get the name of the strategy as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::Fixed => "fixed",
+            | Self::ContentDefined { .. } => "content_defined",
+        }
+    }
+
+    /// Check if the sizes of the strategy are valid,
+    /// non-zero and in increasing order.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            | Self::Fixed => true,
+            | Self::ContentDefined { min, avg, max } => {
+                min > 0 && min <= avg && avg <= max
+            },
+        }
+    }
+
+    /// Get the size of the next chunk at the start of the data,
+    /// holding up to the maximum size of the chunks.
+    ///
+    /// The data is cut as a whole with the fixed strategy.
+    pub(crate) fn cut(
+        &self,
+        data: &[u8],
+    ) -> usize {
+        match *self {
+            | Self::Fixed => data.len(),
+            | Self::ContentDefined { min, avg, max } => {
+                fast_cdc(data, min, avg, max)
+            },
+        }
+    }
+}
+
+/// The random values of the bytes rolled into the gear hash.
+const GEAR: [u64; 256] = gear();
+
+const fn gear() -> [u64; 256] {
+    let mut table: [u64; 256] = [0; 256];
+
+    // splitmix64, with a fixed seed so the boundaries are stable
+    let mut state: u64 = 0;
+    let mut i: usize = 0;
+
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z: u64 = state;
+
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+
+    table
+}
+
+/// Get a mask of the most significant bits of the gear hash,
+/// which depend on the last 64 bytes rolled.
+fn mask(bits: u32) -> u64 {
+    match bits {
+        | 0 => 0,
+        | bits => !0 << (64 - bits.min(64)),
+    }
+}
+
+/// Find the boundary of the chunk at the start of the data with FastCDC,
+/// with the normalized chunking of one level.
+///
+/// The boundary is less likely before the average size, with a stricter
+/// mask, and more likely after it, so the sizes stay close to the average.
+fn fast_cdc(
+    data: &[u8],
+    min: usize,
+    avg: usize,
+    max: usize,
+) -> usize {
+    let len: usize = data.len().min(max);
+
+    if len <= min {
+        return len;
+    }
+
+    let bits: u32 = avg.max(1).ilog2();
+
+    let mask_small: u64 = mask(bits + 1);
+    let mask_large: u64 = mask(bits.saturating_sub(1));
+
+    let normal: usize = avg.clamp(min, len);
+
+    let mut hash: u64 = 0;
+
+    for (i, byte) in data.iter().enumerate().take(len).skip(min) {
+        hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+
+        let mask: u64 = match i < normal {
+            | true => mask_small,
+            | false => mask_large,
+        };
+
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+
+    len
+}
@@ -0,0 +1,70 @@
+use std::{fs, io, path::Path};
+
+/// Policy to sync the written files to the disk.
+///
+/// Flushing only hands the data to the operating system, which may lose it
+/// on a power loss. Syncing waits for the data to reach the disk, at the cost
+/// of the throughput.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{split::Split, sync::SyncPolicy};
+///
+/// let process: Split = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .sync(SyncPolicy::Data);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Only flush the written files.
+    #[default]
+    None,
+    /// Sync the content of the written files with `sync_data`,
+    /// and the directories containing them.
+    Data,
+    /// Sync the content and the metadata of the written files with
+    /// `sync_all`, and the directories containing them.
+    All,
+}
+
+impl SyncPolicy {
+    /// Get the name of the policy as `&str`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            | Self::None => "none",
+            | Self::Data => "data",
+            | Self::All => "all",
+        }
+    }
+
+    /// Sync a written file by the policy.
+    pub(crate) fn sync_file(
+        &self,
+        file: &fs::File,
+    ) -> io::Result<()> {
+        match self {
+            | Self::None => Ok(()),
+            | Self::Data => file.sync_data(),
+            | Self::All => file.sync_all(),
+        }
+    }
+
+    /// Sync a directory by the policy, so the entries written persist.
+    ///
+    /// Directories are only synced on Unix,
+    /// other platforms do not open them as files.
+    pub(crate) fn sync_dir(
+        &self,
+        dir: &Path,
+    ) -> io::Result<()> {
+        if *self == Self::None || cfg!(not(unix)) {
+            return Ok(());
+        }
+
+        fs::File::open(dir)?.sync_all()
+    }
+}
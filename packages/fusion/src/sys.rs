@@ -0,0 +1,572 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[cfg(target_os = "linux")]
+use std::ptr;
+#[cfg(unix)]
+use std::{ffi::CString, mem, os::fd::AsRawFd, os::unix::ffi::OsStrExt};
+
+#[cfg(windows)]
+use std::os::windows::fs::OpenOptionsExt as _;
+
+/// Flag to open a file to be read sequentially on Windows.
+#[cfg(windows)]
+const FILE_FLAG_SEQUENTIAL_SCAN: u32 = 0x0800_0000;
+
+/// Get the options to open a file to read sequentially, from start to end.
+///
+/// On Windows, the file is opened with `FILE_FLAG_SEQUENTIAL_SCAN`,
+/// so the system cache reads ahead of large sequential reads.
+/// The hint is not needed on other platforms.
+pub(crate) fn read_options() -> fs::OpenOptions {
+    let mut options: fs::OpenOptions = fs::OpenOptions::new();
+
+    options.read(true);
+
+    #[cfg(windows)]
+    options.custom_flags(FILE_FLAG_SEQUENTIAL_SCAN);
+
+    options
+}
+
+/// Get a path as a shared path, extended past `MAX_PATH` on Windows.
+///
+/// See [`extend_path`].
+pub(crate) fn long_path(path: &Path) -> Arc<Path> {
+    match extend_path(path) {
+        | Some(extended) => Arc::from(extended),
+        | None => Arc::from(path),
+    }
+}
+
+/// Get a shared path extended past `MAX_PATH` on Windows.
+///
+/// See [`extend_path`].
+pub(crate) fn long_path_shared(path: Arc<Path>) -> Arc<Path> {
+    match extend_path(&path) {
+        | Some(extended) => Arc::from(extended),
+        | None => path,
+    }
+}
+
+/// Extend a path past the 260 characters of `MAX_PATH` on Windows,
+/// made absolute with the `\\?\` prefix, or `\\?\UNC\` for the UNC shares.
+///
+/// The separators and the `..` components are resolved first, as the
+/// verbatim paths are not normalized. Returns `None` for the paths already
+/// verbatim or not resolved, and on other platforms.
+#[cfg(windows)]
+fn extend_path(path: &Path) -> Option<PathBuf> {
+    use std::{
+        ffi::OsString,
+        path::{Component, Components, Prefix},
+    };
+
+    let absolute: PathBuf = std::path::absolute(path).ok()?;
+
+    let mut components: Components = absolute.components();
+
+    let mut extended: OsString = OsString::from(r"\\?\");
+
+    match components.next()? {
+        | Component::Prefix(prefix) => match prefix.kind() {
+            | Prefix::Disk(_) => extended.push(absolute.as_os_str()),
+            | Prefix::UNC(server, share) => {
+                extended.push(r"UNC\");
+                extended.push(server);
+                extended.push(r"\");
+                extended.push(share);
+                extended.push(components.as_path());
+            },
+            | _ => return None,
+        },
+        | _ => return None,
+    }
+
+    Some(PathBuf::from(extended))
+}
+
+/// Extend a path past the 260 characters of `MAX_PATH` on Windows,
+/// made absolute with the `\\?\` prefix, or `\\?\UNC\` for the UNC shares.
+///
+/// The separators and the `..` components are resolved first, as the
+/// verbatim paths are not normalized. Returns `None` for the paths already
+/// verbatim or not resolved, and on other platforms.
+#[cfg(not(windows))]
+fn extend_path(_path: &Path) -> Option<PathBuf> {
+    None
+}
+
+/// Allocate the disk space of a file up to `len` bytes,
+/// without changing the size of the file, with `fallocate`.
+///
+/// It is ignored on file systems without the support.
+#[cfg(target_os = "linux")]
+pub(crate) fn allocate<F: AsRawFd>(
+    file: &F,
+    len: u64,
+) -> io::Result<()> {
+    // SAFETY: the descriptor is held open by the borrowed file.
+    let result: libc::c_int = unsafe {
+        libc::fallocate(
+            file.as_raw_fd(),
+            libc::FALLOC_FL_KEEP_SIZE,
+            0,
+            len as libc::off_t,
+        )
+    };
+
+    if result == 0 {
+        return Ok(());
+    }
+
+    let error: io::Error = io::Error::last_os_error();
+
+    match error.raw_os_error() {
+        | Some(libc::EOPNOTSUPP) | Some(libc::ENOSYS) => Ok(()),
+        | _ => Err(error),
+    }
+}
+
+/// Allocate the disk space of a file up to `len` bytes,
+/// without changing the size of the file, with `F_PREALLOCATE`.
+///
+/// The space is allocated contiguous if possible. It is ignored on file
+/// systems without the support.
+#[cfg(target_vendor = "apple")]
+pub(crate) fn allocate<F: AsRawFd>(
+    file: &F,
+    len: u64,
+) -> io::Result<()> {
+    // SAFETY: the struct is plain data, written by the call.
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+
+    // SAFETY: the descriptor is held open by the borrowed file,
+    // and the struct outlives the call.
+    if unsafe { libc::fstat(file.as_raw_fd(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let size: u64 = stat.st_size as u64;
+
+    if len <= size {
+        return Ok(());
+    }
+
+    // allocated from the end of the file
+    let mut store: libc::fstore_t = libc::fstore_t {
+        fst_flags: libc::F_ALLOCATECONTIG | libc::F_ALLOCATEALL,
+        fst_posmode: libc::F_PEOFPOSMODE,
+        fst_offset: 0,
+        fst_length: (len - size) as libc::off_t,
+        fst_bytesalloc: 0,
+    };
+
+    // SAFETY: the descriptor is held open by the borrowed file,
+    // and the struct outlives the call.
+    let mut result: libc::c_int = unsafe {
+        libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &raw mut store)
+    };
+
+    // not enough contiguous space, allocate it in pieces
+    if result == -1 {
+        store.fst_flags = libc::F_ALLOCATEALL;
+
+        // SAFETY: as above.
+        result = unsafe {
+            libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &raw mut store)
+        };
+    }
+
+    if result != -1 {
+        return Ok(());
+    }
+
+    let error: io::Error = io::Error::last_os_error();
+
+    match error.raw_os_error() {
+        | Some(libc::ENOTSUP) | Some(libc::EINVAL) => Ok(()),
+        | _ => Err(error),
+    }
+}
+
+/// Allocate the disk space of a file up to `len` bytes,
+/// without changing the size of the file.
+///
+/// The space is only allocated on Linux and Apple platforms, it is ignored
+/// on other platforms.
+#[cfg(not(any(target_os = "linux", target_vendor = "apple")))]
+pub(crate) fn allocate<F>(
+    _file: &F,
+    _len: u64,
+) -> io::Result<()> {
+    Ok(())
+}
+
+/// Space of the file system holding a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DiskSpace {
+    /// The bytes available to an unprivileged user.
+    pub(crate) available: u64,
+    /// The size of the blocks the files are allocated by, in bytes.
+    pub(crate) block_size: u64,
+}
+
+impl DiskSpace {
+    /// Get the bytes a file of `len` bytes takes on disk.
+    pub(crate) fn allocated(
+        &self,
+        len: u64,
+    ) -> u64 {
+        len.div_ceil(self.block_size.max(1)) * self.block_size.max(1)
+    }
+}
+
+/// Get the space of the file system holding a path, with `statvfs`.
+#[cfg(unix)]
+pub(crate) fn disk_space(path: &Path) -> io::Result<Option<DiskSpace>> {
+    let path: CString = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+    // SAFETY: the struct is plain data, written by the call.
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+
+    // SAFETY: the path is a valid C string, and the struct outlives the call.
+    let result: libc::c_int =
+        unsafe { libc::statvfs(path.as_ptr(), &mut stat) };
+
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(Some(DiskSpace {
+        available: stat.f_bavail as u64 * stat.f_frsize as u64,
+        block_size: stat.f_frsize as u64,
+    }))
+}
+
+/// Get the space of the file system holding a path.
+///
+/// Returns `None` on platforms other than Unix, the space is unknown.
+#[cfg(not(unix))]
+pub(crate) fn disk_space(_path: &Path) -> io::Result<Option<DiskSpace>> {
+    Ok(None)
+}
+
+/// Try to take an advisory lock on a file with `flock`, without blocking,
+/// shared or exclusive.
+///
+/// Returns `false` if a conflicting lock is held by another open file.
+#[cfg(all(unix, not(target_os = "solaris")))]
+pub(crate) fn try_lock(
+    file: &fs::File,
+    exclusive: bool,
+) -> io::Result<bool> {
+    let operation: libc::c_int = match exclusive {
+        | true => libc::LOCK_EX,
+        | false => libc::LOCK_SH,
+    };
+
+    // SAFETY: the descriptor is held open by the borrowed file.
+    let result: libc::c_int =
+        unsafe { libc::flock(file.as_raw_fd(), operation | libc::LOCK_NB) };
+
+    if result == 0 {
+        return Ok(true);
+    }
+
+    let error: io::Error = io::Error::last_os_error();
+
+    match error.raw_os_error() {
+        | Some(libc::EWOULDBLOCK) => Ok(false),
+        | _ => Err(error),
+    }
+}
+
+/// Try to take a lock on the whole file with `LockFileEx`, without
+/// blocking, shared or exclusive.
+///
+/// Returns `false` if a conflicting lock is held by another open file.
+/// A shared lock is not upgraded to an exclusive one by the same handle.
+#[cfg(windows)]
+pub(crate) fn try_lock(
+    file: &fs::File,
+    exclusive: bool,
+) -> io::Result<bool> {
+    use std::{mem, os::windows::io::AsRawHandle as _};
+
+    use windows_sys::Win32::{
+        Foundation::ERROR_LOCK_VIOLATION,
+        Storage::FileSystem::{
+            LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY, LockFileEx,
+        },
+        System::IO::OVERLAPPED,
+    };
+
+    let flags: u32 = match exclusive {
+        | true => LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY,
+        | false => LOCKFILE_FAIL_IMMEDIATELY,
+    };
+
+    // SAFETY: the struct is plain data, locking from the offset `0`.
+    let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
+
+    // SAFETY: the handle is held open by the borrowed file,
+    // and the struct outlives the call.
+    let result: i32 = unsafe {
+        LockFileEx(
+            file.as_raw_handle(),
+            flags,
+            0,
+            u32::MAX,
+            u32::MAX,
+            &mut overlapped,
+        )
+    };
+
+    if result != 0 {
+        return Ok(true);
+    }
+
+    let error: io::Error = io::Error::last_os_error();
+
+    match error.raw_os_error() {
+        | Some(code) if code == ERROR_LOCK_VIOLATION as i32 => Ok(false),
+        | _ => Err(error),
+    }
+}
+
+/// Try to take an advisory lock on a file, without blocking,
+/// shared or exclusive.
+///
+/// Fails with [`io::ErrorKind::Unsupported`], the files cannot be locked
+/// on this platform.
+#[cfg(not(any(all(unix, not(target_os = "solaris")), windows)))]
+pub(crate) fn try_lock(
+    _file: &fs::File,
+    _exclusive: bool,
+) -> io::Result<bool> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// Check if an opened file is still the file at a path,
+/// not removed or replaced since it was opened.
+#[cfg(unix)]
+pub(crate) fn is_same_file(
+    file: &fs::File,
+    path: &Path,
+) -> bool {
+    use std::os::unix::fs::MetadataExt as _;
+
+    match (file.metadata(), fs::metadata(path)) {
+        | (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        | _ => false,
+    }
+}
+
+/// Check if an opened file is still the file at a path,
+/// not removed or replaced since it was opened.
+///
+/// Only checks that a file is still at the path on other platforms
+/// than Unix.
+#[cfg(not(unix))]
+pub(crate) fn is_same_file(
+    _file: &fs::File,
+    path: &Path,
+) -> bool {
+    path.is_file()
+}
+
+/// Check if `len` bytes of a file from the offset are a hole,
+/// with no data allocated, with `lseek` and `SEEK_DATA`.
+///
+/// Returns `false` on file systems without the support,
+/// the whole file is then data.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_os = "solaris",
+))]
+pub(crate) fn is_hole(
+    file: &fs::File,
+    offset: u64,
+    len: u64,
+) -> io::Result<bool> {
+    if len == 0 {
+        return Ok(false);
+    }
+
+    // SAFETY: the descriptor is held open by the borrowed file.
+    let result: libc::off_t = unsafe {
+        libc::lseek(file.as_raw_fd(), offset as libc::off_t, libc::SEEK_DATA)
+    };
+
+    if result >= 0 {
+        return Ok(result as u64 >= offset + len);
+    }
+
+    let error: io::Error = io::Error::last_os_error();
+
+    match error.raw_os_error() {
+        // no data after the offset
+        | Some(libc::ENXIO) => Ok(true),
+        | Some(libc::EINVAL) | Some(libc::EOPNOTSUPP) => Ok(false),
+        | _ => Err(error),
+    }
+}
+
+/// Check if `len` bytes of a file from the offset are a hole,
+/// with no data allocated.
+///
+/// Always returns `false` on the platforms without `SEEK_DATA`, such as
+/// Windows, NetBSD and OpenBSD, the whole file is then data.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_os = "solaris",
+)))]
+pub(crate) fn is_hole(
+    _file: &fs::File,
+    _offset: u64,
+    _len: u64,
+) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Copy `len` bytes of a file from the offset to the current position
+/// of another file, in the kernel with `copy_file_range`.
+///
+/// Returns `false` if the copy is not supported between the files,
+/// before any byte is copied.
+#[cfg(target_os = "linux")]
+pub(crate) fn copy_range(
+    input: &fs::File,
+    offset: u64,
+    output: &fs::File,
+    len: usize,
+) -> io::Result<bool> {
+    let mut offset: libc::loff_t = offset as libc::loff_t;
+
+    let mut copied: usize = 0;
+
+    while copied < len {
+        // SAFETY: the descriptors are held open by the borrowed files,
+        // and the offset outlives the call.
+        let result: isize = unsafe {
+            libc::copy_file_range(
+                input.as_raw_fd(),
+                &mut offset,
+                output.as_raw_fd(),
+                ptr::null_mut(),
+                len - copied,
+                0,
+            )
+        };
+
+        match result {
+            | 0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            | n if n > 0 => copied += n as usize,
+            | _ => {
+                let error: io::Error = io::Error::last_os_error();
+
+                return match error.raw_os_error() {
+                    | Some(
+                        libc::ENOSYS
+                        | libc::EXDEV
+                        | libc::EOPNOTSUPP
+                        | libc::EINVAL
+                        | libc::EPERM,
+                    ) if copied == 0 => Ok(false),
+                    | _ => Err(error),
+                };
+            },
+        }
+    }
+
+    Ok(true)
+}
+
+/// Copy `len` bytes of a file from the offset to the current position
+/// of another file, in the kernel with `copy_file_range`.
+///
+/// Always returns `false` on platforms other than Linux,
+/// the chunk is then copied through the buffer.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn copy_range(
+    _input: &fs::File,
+    _offset: u64,
+    _output: &fs::File,
+    _len: usize,
+) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Clone `len` bytes of a file from the offset to the destination offset
+/// of another file with a reflink, sharing the extents on disk.
+///
+/// Returns `false` if the clone is not supported between the files,
+/// such as across file systems or with unaligned offsets.
+#[cfg(target_os = "linux")]
+pub(crate) fn clone_range(
+    input: &fs::File,
+    offset: u64,
+    output: &fs::File,
+    dest_offset: u64,
+    len: usize,
+) -> io::Result<bool> {
+    let range: libc::file_clone_range = libc::file_clone_range {
+        src_fd: input.as_raw_fd() as i64,
+        src_offset: offset,
+        src_length: len as u64,
+        dest_offset,
+    };
+
+    // SAFETY: the descriptors are held open by the borrowed files,
+    // and the range outlives the call.
+    let result: libc::c_int =
+        unsafe { libc::ioctl(output.as_raw_fd(), libc::FICLONERANGE, &range) };
+
+    if result == 0 {
+        return Ok(true);
+    }
+
+    let error: io::Error = io::Error::last_os_error();
+
+    match error.raw_os_error() {
+        | Some(
+            libc::ENOSYS
+            | libc::ENOTTY
+            | libc::EXDEV
+            | libc::EOPNOTSUPP
+            | libc::EINVAL,
+        ) => Ok(false),
+        | _ => Err(error),
+    }
+}
+
+/// Clone `len` bytes of a file from the offset to the destination offset
+/// of another file with a reflink, sharing the extents on disk.
+///
+/// Returns `false` if the clone is not supported between the files,
+/// such as across file systems or with unaligned offsets.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn clone_range(
+    _input: &fs::File,
+    _offset: u64,
+    _output: &fs::File,
+    _dest_offset: u64,
+    _len: usize,
+) -> io::Result<bool> {
+    Ok(false)
+}
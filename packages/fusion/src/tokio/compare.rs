@@ -0,0 +1,19 @@
+use crate::{
+    compare::{Compare, CompareError, CompareResult},
+    runtime,
+    tokio::runtime::Tokio,
+};
+
+/// Trait for running the compare process.
+pub trait CompareAsyncExt {
+    /// Run the compare process asynchronously.
+    fn run_async(
+        &self
+    ) -> impl std::future::Future<Output = Result<CompareResult, CompareError>> + Send;
+}
+
+impl CompareAsyncExt for Compare {
+    async fn run_async(&self) -> Result<CompareResult, CompareError> {
+        runtime::compare::run::<Tokio>(self).await
+    }
+}
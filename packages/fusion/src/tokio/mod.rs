@@ -3,3 +3,11 @@ pub mod split;
 pub mod check;
 
 pub mod merge;
+
+pub mod compare;
+
+pub mod reader;
+
+pub mod sink;
+
+pub mod runtime;
@@ -0,0 +1,267 @@
+use std::{
+    fmt,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+
+use tokio::{
+    fs,
+    io::{
+        AsyncRead, AsyncReadExt as _, AsyncSeek, AsyncSeekExt as _, BufReader,
+        ReadBuf, Take,
+    },
+};
+
+use crate::{
+    merge::{Merge, MergeError},
+    reader::ReaderSlices,
+    runtime,
+    sys::read_options,
+    tokio::runtime::Tokio,
+};
+
+/// Reader of the content of a chunk.
+type SliceReader = Take<Pin<Box<dyn AsyncRead + Send>>>;
+
+/// Future opening a chunk at the position of the reader.
+type OpenFuture = Pin<Box<dyn Future<Output = io::Result<SliceReader>> + Send>>;
+
+/// Asynchronous reader presenting the chunks of a directory as the original
+/// file, without writing anything to disk.
+///
+/// It is the counterpart of [`ChunksReader`](crate::reader::ChunksReader)
+/// implementing [`AsyncRead`] and [`AsyncSeek`], so the chunks are opened
+/// and read without blocking the runtime.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{io::SeekFrom, path::PathBuf};
+///
+/// use tokio::io::{AsyncReadExt as _, AsyncSeekExt as _};
+///
+/// use filerune_fusion::reader::tokio::AsyncChunksReader;
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     let mut reader: AsyncChunksReader = AsyncChunksReader::open(
+///         PathBuf::from("path").join("to").join("dir"),
+///     )
+///     .await
+///     .unwrap();
+///
+///     // serve the range `bytes=1024-2047` of the file
+///     reader.seek(SeekFrom::Start(1024)).await.unwrap();
+///
+///     let mut range: Vec<u8> = vec![0; 1024];
+///
+///     reader.read_exact(&mut range).await.unwrap();
+/// }
+/// ```
+pub struct AsyncChunksReader {
+    process: Arc<Merge>,
+    slices: ReaderSlices,
+    position: u64,
+    state: State,
+}
+
+/// State of the chunk read from.
+enum State {
+    /// No chunk is opened at the position.
+    Idle,
+    /// The chunk at the position is being opened.
+    Opening(OpenFuture, u64),
+    /// The chunk at the position is opened, until the end of its slice.
+    Reading(SliceReader, u64),
+}
+
+impl AsyncChunksReader {
+    /// Open a reader over the chunks of a directory,
+    /// named by their index only.
+    pub async fn open<InDir: AsRef<Path>>(
+        in_dir: InDir
+    ) -> Result<Self, MergeError> {
+        Self::from_merge(&Merge::new().in_dir(in_dir)).await
+    }
+
+    /// Open a reader over the chunks of the input directory of a merge
+    /// process, as they would be merged.
+    ///
+    /// The output file and the options to write it are not used.
+    pub async fn from_merge(process: &Merge) -> Result<Self, MergeError> {
+        let in_dir: &Path = runtime::merge::in_dir(process)?;
+
+        let slices: ReaderSlices = ReaderSlices::new(
+            runtime::merge::scan_chunks::<Tokio>(process, in_dir, None).await?,
+        );
+
+        Ok(Self {
+            process: Arc::new(process.clone()),
+            slices,
+            position: 0,
+            state: State::Idle,
+        })
+    }
+
+    /// Get the size of the file presented in bytes.
+    pub fn len(&self) -> u64 {
+        self.slices.len()
+    }
+
+    /// Check if the file presented is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of chunks read from.
+    pub fn total_chunks(&self) -> usize {
+        self.slices.count()
+    }
+
+    /// Start opening the slice holding the position of the reader,
+    /// `None` at the end.
+    fn open_current(&self) -> Option<State> {
+        let (slice, offset, left, end) = self.slices.at(self.position)?;
+
+        let process: Arc<Merge> = self.process.clone();
+
+        let path: PathBuf = slice.path.clone();
+
+        let future: OpenFuture = Box::pin(async move {
+            let reader: Pin<Box<dyn AsyncRead + Send>> = match process
+                .is_encoded()
+            {
+                | true => {
+                    let data: Vec<u8> =
+                        process.decode(fs::read(&path).await?)?;
+
+                    let mut reader: io::Cursor<Vec<u8>> = io::Cursor::new(data);
+
+                    reader.set_position(offset);
+
+                    Box::pin(reader)
+                },
+                | false => {
+                    let mut input: fs::File =
+                        fs::OpenOptions::from(read_options())
+                            .open(&path)
+                            .await?;
+
+                    input.seek(io::SeekFrom::Start(offset)).await?;
+
+                    Box::pin(BufReader::with_capacity(
+                        process.buffer_capacity,
+                        input,
+                    ))
+                },
+            };
+
+            Ok(reader.take(left))
+        });
+
+        Some(State::Opening(future, end))
+    }
+}
+
+impl AsyncRead for AsyncChunksReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this: &mut Self = self.get_mut();
+
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        loop {
+            match this.state {
+                | State::Idle => match this.open_current() {
+                    | Some(state) => this.state = state,
+                    | None => return Poll::Ready(Ok(())),
+                },
+                | State::Opening(ref mut future, end) => {
+                    let result: io::Result<SliceReader> =
+                        ready!(future.as_mut().poll(cx));
+
+                    match result {
+                        | Ok(reader) => {
+                            this.state = State::Reading(reader, end)
+                        },
+                        | Err(e) => {
+                            this.state = State::Idle;
+
+                            return Poll::Ready(Err(e));
+                        },
+                    }
+                },
+                | State::Reading(ref mut reader, end) => {
+                    let filled: usize = buf.filled().len();
+
+                    ready!(Pin::new(reader).poll_read(cx, buf))?;
+
+                    let read: usize = buf.filled().len() - filled;
+
+                    if read > 0 {
+                        this.position += read as u64;
+
+                        return Poll::Ready(Ok(()));
+                    }
+
+                    // the chunk is shorter than when the reader was opened
+                    if this.position < end {
+                        return Poll::Ready(Err(io::Error::from(
+                            io::ErrorKind::UnexpectedEof,
+                        )));
+                    }
+
+                    this.state = State::Idle;
+                },
+            }
+        }
+    }
+}
+
+impl AsyncSeek for AsyncChunksReader {
+    fn start_seek(
+        self: Pin<&mut Self>,
+        pos: io::SeekFrom,
+    ) -> io::Result<()> {
+        let this: &mut Self = self.get_mut();
+
+        let position: u64 = this.slices.seek(this.position, pos)?;
+
+        if position != this.position {
+            this.position = position;
+            this.state = State::Idle;
+        }
+
+        Ok(())
+    }
+
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+impl fmt::Debug for AsyncChunksReader {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("AsyncChunksReader")
+            .field("in_dir", &self.process.in_dir)
+            .field("total_chunks", &self.slices.count())
+            .field("len", &self.slices.len())
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    fmt,
+    future::{Future, poll_fn},
+    io,
+    path::Path,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+    time::Instant,
+};
+
+use tokio::io::AsyncWrite;
+
+use crate::{
+    chunk::ChunkInfo,
+    hash::Digest,
+    lock::FileLock,
+    progress::ProgressGuard,
+    runtime,
+    split::{ChunkLayout, Split, SplitError, SplitResult},
+    tokio::runtime::Tokio,
+};
+
+/// Future writing a chunk to the output directory.
+type WriteFuture =
+    Pin<Box<dyn Future<Output = Result<ChunkInfo, SplitError>> + Send>>;
+
+/// Asynchronous writer splitting the bytes written to it into chunks,
+/// without the whole input in a file.
+///
+/// It is the counterpart of [`SplitSink`](crate::sink::SplitSink)
+/// implementing [`AsyncWrite`], so the body of a request is split as it
+/// arrives. A full chunk is written to the output directory by the next
+/// write, flush or shutdown of the sink, and the last chunk and the files
+/// written next to the chunks by [`AsyncSplitSink::finish`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use tokio::{io, net::TcpStream};
+///
+/// use filerune_fusion::{
+///     sink::tokio::AsyncSplitSink,
+///     split::{Split, SplitResult, tokio::SplitAsyncExt as _},
+/// };
+///
+/// # async fn example() {
+/// let mut stream: TcpStream =
+///     TcpStream::connect("127.0.0.1:8080").await.unwrap();
+///
+/// let mut sink: AsyncSplitSink = Split::new()
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .chunk_size(1024 * 1024)
+///     .sink_async()
+///     .await
+///     .unwrap();
+///
+/// io::copy(&mut stream, &mut sink).await.unwrap();
+///
+/// let result: SplitResult = sink.finish().await.unwrap();
+/// # }
+/// ```
+pub struct AsyncSplitSink {
+    process: Arc<Split>,
+    out_dir: Arc<Path>,
+    layout: Arc<ChunkLayout>,
+    buffer: Vec<u8>,
+    chunks: Vec<ChunkInfo>,
+    file_size: u64,
+    started: Instant,
+    progress: ProgressGuard,
+    // the chunk being written and its size, kept in the buffer until written
+    writing: Option<(WriteFuture, usize)>,
+    // the error failing the writes, returned once finished
+    error: Option<SplitError>,
+    _lock: Option<FileLock>,
+}
+
+impl AsyncSplitSink {
+    /// Create a new sink writing the chunks to the output directory
+    /// of a split process.
+    ///
+    /// The output directory is prepared and locked, if enabled,
+    /// before the first byte is written.
+    pub async fn new(process: &Split) -> Result<Self, SplitError> {
+        let progress: ProgressGuard =
+            ProgressGuard::start(process.progress.as_ref());
+
+        let lock: Option<FileLock> = process.lock_out_dir(None)?;
+
+        let out_dir: Arc<Path> =
+            Arc::from(runtime::split::prepare_out_dir::<Tokio>(process).await?);
+
+        // the input is the bytes written, of an unknown size
+        let mut process: Split = process.clone();
+
+        process.in_file = None;
+        process.total_chunks = None;
+        process.resume = false;
+
+        let layout: ChunkLayout = process.layout(None)?;
+
+        Ok(Self {
+            process: Arc::new(process),
+            out_dir,
+            buffer: Vec::with_capacity(layout.max_len()),
+            layout: Arc::new(layout),
+            chunks: Vec::new(),
+            file_size: 0,
+            started: Instant::now(),
+            progress,
+            writing: None,
+            error: None,
+            _lock: lock,
+        })
+    }
+
+    /// Get the number of bytes written to the sink.
+    pub fn bytes_written(&self) -> u64 {
+        self.file_size + self.buffer.len() as u64
+    }
+
+    /// Get the chunks written to the output directory, in order.
+    ///
+    /// The bytes buffered for the next chunk are not included.
+    pub fn chunks(&self) -> &[ChunkInfo] {
+        &self.chunks
+    }
+
+    /// Write the chunks left and the files written next to the chunks,
+    /// and get the result of the split process.
+    ///
+    /// Fails with the error of a previous write, if any.
+    pub async fn finish(mut self) -> Result<SplitResult, SplitError> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        poll_fn(|cx| self.poll_written(cx)).await?;
+
+        while !self.buffer.is_empty() {
+            self.start_write(self.buffer.len());
+
+            poll_fn(|cx| self.poll_written(cx)).await?;
+        }
+
+        let bao_hash: Option<Digest> = runtime::split::write_outputs::<Tokio>(
+            &self.process,
+            &self.out_dir,
+            &self.chunks,
+        )
+        .await?;
+
+        self.progress.finish();
+
+        Ok(SplitResult {
+            file_size: self.file_size,
+            total_chunks: self.chunks.len(),
+            chunk_size: self.layout.chunk_size,
+            chunks: self.chunks,
+            duration: self.started.elapsed(),
+            chunks_resumed: 0,
+            bao_hash,
+        })
+    }
+
+    /// Start writing the next chunk cut from the bytes buffered,
+    /// up to the size.
+    fn start_write(
+        &mut self,
+        size: usize,
+    ) {
+        let index: usize = self.chunks.len();
+
+        let len: usize = self.layout.cut(&self.buffer[..size]);
+
+        let process: Arc<Split> = self.process.clone();
+
+        let out_dir: Arc<Path> = self.out_dir.clone();
+
+        let layout: Arc<ChunkLayout> = self.layout.clone();
+
+        let data: Vec<u8> = self.buffer[..len].to_vec();
+
+        let future: WriteFuture = Box::pin(async move {
+            if process.is_cancelled() {
+                runtime::split::remove_chunks::<Tokio>(
+                    &process, &out_dir, index,
+                )
+                .await;
+
+                return Err(SplitError::Cancelled);
+            }
+
+            if process.is_expired() {
+                return Err(SplitError::DeadlineExceeded);
+            }
+
+            runtime::split::write_chunk::<Tokio>(
+                &process, &out_dir, &layout, None, index, &data,
+            )
+            .await
+        });
+
+        self.writing = Some((future, len));
+    }
+
+    /// Wait for the chunk being written, if any.
+    fn poll_written(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), SplitError>> {
+        let (future, len) = match self.writing {
+            | Some((ref mut future, len)) => (future, len),
+            | None => return Poll::Ready(Ok(())),
+        };
+
+        let result: Result<ChunkInfo, SplitError> =
+            ready!(future.as_mut().poll(cx));
+
+        self.writing = None;
+
+        match result {
+            | Ok(chunk) => {
+                self.progress.advance(chunk.index, len);
+
+                self.file_size += len as u64;
+
+                self.chunks.push(chunk);
+
+                self.buffer.drain(..len);
+
+                Poll::Ready(Ok(()))
+            },
+            | Err(error) => {
+                self.error = Some(error);
+
+                Poll::Ready(Err(error))
+            },
+        }
+    }
+
+    /// Wait for the chunk being written, failing with the error of a
+    /// previous write as an I/O error.
+    fn poll_io(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        if let Some(error) = self.error {
+            return Poll::Ready(Err(io::Error::other(error.to_message())));
+        }
+
+        ready!(self.poll_written(cx))
+            .map_err(|error| io::Error::other(error.to_message()))?;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for AsyncSplitSink {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this: &mut Self = self.get_mut();
+
+        ready!(this.poll_io(cx))?;
+
+        let size: usize = this.layout.len(this.chunks.len());
+
+        // the bytes up to the end of the next chunk
+        let len: usize = (size - this.buffer.len()).min(buf.len());
+
+        this.buffer.extend_from_slice(&buf[..len]);
+
+        if this.buffer.len() == size {
+            this.start_write(size);
+        }
+
+        Poll::Ready(Ok(len))
+    }
+
+    /// The full chunk is written by a flush, the bytes of the next chunk
+    /// are kept in the buffer until it is full.
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.get_mut().poll_io(cx)
+    }
+
+    /// The last chunk is only written by [`AsyncSplitSink::finish`].
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.get_mut().poll_io(cx)
+    }
+}
+
+impl fmt::Debug for AsyncSplitSink {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("AsyncSplitSink")
+            .field("out_dir", &self.out_dir)
+            .field("total_chunks", &self.chunks.len())
+            .field("bytes_written", &self.bytes_written())
+            .field("writing", &self.writing.is_some())
+            .finish_non_exhaustive()
+    }
+}
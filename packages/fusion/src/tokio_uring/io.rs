@@ -0,0 +1,85 @@
+use std::{io, mem};
+
+use tokio_uring::{buf::IoBuf as _, fs};
+
+/// Read up to `len` bytes of a file at the position into the buffer,
+/// replacing its content.
+///
+/// Returns the number of bytes read, less than `len` at the end of the file.
+pub(crate) async fn read_full_at(
+    file: &fs::File,
+    buffer: &mut Vec<u8>,
+    position: u64,
+    len: usize,
+) -> io::Result<usize> {
+    let mut data: Vec<u8> = mem::take(buffer);
+
+    data.clear();
+    data.reserve(len);
+
+    let mut result: io::Result<()> = Ok(());
+
+    while data.len() < len {
+        let filled: usize = data.len();
+
+        let (read, slice) = file
+            .read_at(data.slice(filled..len), position + filled as u64)
+            .await;
+
+        data = slice.into_inner();
+
+        match read {
+            | Ok(0) => break,
+            | Ok(_) => {},
+            | Err(e) => {
+                result = Err(e);
+                break;
+            },
+        }
+    }
+
+    let filled: usize = data.len();
+
+    *buffer = data;
+
+    result.map(|_| filled)
+}
+
+/// Write the whole content of the buffer to a file at the position.
+pub(crate) async fn write_all_at(
+    file: &fs::File,
+    buffer: &mut Vec<u8>,
+    position: u64,
+) -> io::Result<()> {
+    let mut data: Vec<u8> = mem::take(buffer);
+
+    let len: usize = data.len();
+
+    let mut written: usize = 0;
+
+    let mut result: io::Result<()> = Ok(());
+
+    while written < len {
+        let (write, slice) = file
+            .write_at(data.slice(written..len), position + written as u64)
+            .await;
+
+        data = slice.into_inner();
+
+        match write {
+            | Ok(0) => {
+                result = Err(io::Error::from(io::ErrorKind::WriteZero));
+                break;
+            },
+            | Ok(n) => written += n,
+            | Err(e) => {
+                result = Err(e);
+                break;
+            },
+        }
+    }
+
+    *buffer = data;
+
+    result
+}
@@ -0,0 +1,349 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use tokio_uring::fs;
+
+use crate::{
+    chunk::{ChunkInfo, temp_path},
+    lock::FileLock,
+    merge::{
+        ChunkSlice, Merge, MergeError, MergeResult, OutputVerifier, RepairDir,
+        out_dir, resume_point,
+    },
+    progress::ProgressGuard,
+    sys::allocate,
+    tokio_uring::{
+        io::{read_full_at, write_all_at},
+        sync::{sync_dir, sync_file},
+    },
+};
+
+/// Trait for running the merge process.
+pub trait MergeAsyncExt {
+    /// Run the merge process asynchronously.
+    ///
+    /// It must be run within the `tokio_uring` runtime,
+    /// such as with `tokio_uring::start`.
+    fn run_async(
+        &self
+    ) -> impl std::future::Future<Output = Result<MergeResult, MergeError>>;
+}
+
+impl MergeAsyncExt for Merge {
+    async fn run_async(&self) -> Result<MergeResult, MergeError> {
+        let in_dir: &Path = match self.in_dir {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_dir not exists
+                if !p.exists() {
+                    return Err(MergeError::InDirNotFound);
+                }
+
+                // if in_dir not a directory
+                if !p.is_dir() {
+                    return Err(MergeError::InDirNotDir);
+                }
+
+                p
+            },
+            | None => return Err(MergeError::InDirNotSet),
+        };
+
+        let _locks: Vec<FileLock> =
+            self.lock_paths(in_dir, self.out_file.as_deref())?;
+
+        let out_file: &Path = self.prepare_out_file()?;
+
+        // write to a temporary file to rename, if atomic
+        let write_path: PathBuf = match self.is_atomic() {
+            | true => temp_path(out_file),
+            | false => out_file.to_path_buf(),
+        };
+
+        let output: std::fs::File = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(!self.resume)
+            .write(true)
+            .open(&write_path)
+            .map_err(|_| MergeError::OutFileNotOpened)?;
+
+        let resume_from: u64 = match self.resume {
+            | true => output
+                .metadata()
+                .map_err(|_| MergeError::OutFileNotOpened)?
+                .len(),
+            | false => 0,
+        };
+
+        match merge_chunks(self, in_dir, output, resume_from).await {
+            | Ok(result) => {
+                if self.is_atomic() {
+                    fs::rename(&write_path, out_file)
+                        .await
+                        .map_err(|_| MergeError::OutFileNotWritten)?;
+                }
+
+                self.apply_metadata(in_dir, out_file)?;
+
+                if let Some(parent) = out_dir(out_file) {
+                    sync_dir(self.sync, parent)
+                        .await
+                        .map_err(|_| MergeError::OutFileNotWritten)?;
+                }
+
+                Ok(MergeResult {
+                    out_file: Some(out_file.to_path_buf()),
+                    ..result
+                })
+            },
+            | Err(error) => {
+                // keep the output to be resumed, but not a corrupt one
+                if !self.resume
+                    && error != MergeError::DeadlineExceeded
+                    && (self.is_atomic()
+                        || error == MergeError::Cancelled
+                        || error == MergeError::HashMismatch)
+                {
+                    let _ = fs::remove_file(&write_path).await;
+                }
+
+                Err(error)
+            },
+        }
+    }
+}
+
+/// Merge the chunks from the input directory to the output.
+///
+/// The output is resumed from `resume_from` bytes already written.
+async fn merge_chunks(
+    process: &Merge,
+    in_dir: &Path,
+    output: std::fs::File,
+    resume_from: u64,
+) -> Result<MergeResult, MergeError> {
+    let progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let started: Instant = Instant::now();
+
+    let repair_dir: Option<RepairDir> = process.repair_chunks(in_dir)?;
+
+    let mut slices: Vec<ChunkSlice> =
+        process.scan_chunks(in_dir, repair_dir.as_ref())?;
+
+    let mut bytes_resumed: u64 = 0;
+
+    let mut verifier: OutputVerifier =
+        process.output_verifier(Some(in_dir), &slices)?;
+
+    if resume_from > 0 {
+        let (skipped, position) = resume_point(&slices, resume_from);
+
+        // drop the incomplete chunk
+        output.set_len(position).map_err(|_| MergeError::OutFileNotWritten)?;
+
+        // the part resumed is hashed from the chunks it was merged from
+        process.verify_slices(&slices[..skipped], &mut verifier)?;
+
+        slices.drain(..skipped);
+
+        bytes_resumed = position;
+    }
+
+    let len: u64 = slices.iter().map(|s| s.len as u64).sum();
+
+    process.ensure_space(len)?;
+
+    // allocate the disk space of the output up front
+    if process.preallocate {
+        allocate(&output, bytes_resumed + len)
+            .map_err(|_| MergeError::OutFileNotAllocated)?;
+    }
+
+    // the output is extended over the holes left at the end, if sparse
+    let extended: Option<std::fs::File> = match process.sparse {
+        | true => {
+            Some(output.try_clone().map_err(|_| MergeError::OutFileNotOpened)?)
+        },
+        | false => None,
+    };
+
+    let output: fs::File = fs::File::from_std(output);
+
+    let mut buffer: Vec<u8> = Vec::with_capacity(process.buffer_capacity);
+
+    let mut chunks: Vec<ChunkInfo> = Vec::with_capacity(slices.len());
+
+    let mut bytes_written: u64 = 0;
+
+    // merge
+    for slice in slices {
+        if process.is_cancelled() {
+            return Err(MergeError::Cancelled);
+        }
+
+        if process.is_expired() {
+            return Err(MergeError::DeadlineExceeded);
+        }
+
+        let size: usize = copy_slice(
+            process,
+            &slice,
+            &output,
+            &mut buffer,
+            bytes_resumed + bytes_written,
+            &mut verifier,
+        )
+        .await?;
+
+        progress.advance(slice.index, size);
+
+        bytes_written += size as u64;
+
+        // expose the chunk to the readers of the output
+        if process.progressive {
+            extend_output(extended.as_ref(), bytes_resumed + bytes_written)?;
+
+            sync_file(process.sync, &output)
+                .await
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+
+            process.watermark(bytes_resumed + bytes_written);
+        }
+
+        chunks.push(ChunkInfo { index: slice.index, path: slice.path, size });
+    }
+
+    extend_output(extended.as_ref(), bytes_resumed + bytes_written)?;
+
+    verifier.finish()?;
+
+    sync_file(process.sync, &output)
+        .await
+        .map_err(|_| MergeError::OutFileNotWritten)?;
+
+    output.close().await.map_err(|_| MergeError::OutFileNotWritten)?;
+
+    progress.finish();
+
+    Ok(MergeResult {
+        bytes_written,
+        chunks_merged: chunks.len(),
+        out_file: None,
+        duration: started.elapsed(),
+        chunks,
+        bytes_resumed,
+    })
+}
+
+/// Copy the content of a chunk slice to the output at the position.
+///
+/// Returns the number of bytes copied.
+async fn copy_slice(
+    process: &Merge,
+    slice: &ChunkSlice,
+    output: &fs::File,
+    buffer: &mut Vec<u8>,
+    position: u64,
+    verifier: &mut OutputVerifier,
+) -> Result<usize, MergeError> {
+    let input: fs::File = fs::File::open(&slice.path)
+        .await
+        .map_err(|_| MergeError::InFileNotOpened)?;
+
+    // the decoded content can not be read at an offset
+    if process.is_encoded() {
+        let len: usize = std::fs::metadata(&slice.path)
+            .ok()
+            .and_then(|m| usize::try_from(m.len()).ok())
+            .ok_or(MergeError::InFileNotRead)?;
+
+        read_full_at(&input, buffer, 0, len)
+            .await
+            .map_err(|_| MergeError::InFileNotRead)?;
+
+        let _ = input.close().await;
+
+        let mut data: Vec<u8> = process
+            .decode(std::mem::take(buffer))
+            .map_err(|_| MergeError::InFileNotRead)?;
+
+        data.truncate(slice.offset + slice.len);
+        data.drain(..slice.offset.min(data.len()));
+
+        let size: usize = data.len();
+
+        verifier.update(&data)?;
+
+        if !is_hole(process, &data) {
+            write_all_at(output, &mut data, position)
+                .await
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+        }
+
+        return Ok(size);
+    }
+
+    let mut size: usize = 0;
+
+    while size < slice.len {
+        let limit: usize = (slice.len - size).min(process.buffer_capacity);
+
+        let read: usize =
+            read_full_at(&input, buffer, (slice.offset + size) as u64, limit)
+                .await
+                .map_err(|_| MergeError::InFileNotRead)?;
+
+        if read == 0 {
+            break;
+        }
+
+        verifier.update(&buffer[..read])?;
+
+        if !is_hole(process, &buffer[..read]) {
+            write_all_at(output, buffer, position + size as u64)
+                .await
+                .map_err(|_| MergeError::OutFileNotWritten)?;
+        }
+
+        size += read;
+    }
+
+    let _ = input.close().await;
+
+    Ok(size)
+}
+
+/// Check if the data is left as a hole in the output, if sparse.
+///
+/// The data is written at positions, so the holes are whole buffers of zeros.
+fn is_hole(
+    process: &Merge,
+    data: &[u8],
+) -> bool {
+    process.sparse && data.iter().all(|b| *b == 0)
+}
+
+/// Extend the output over the holes left at the end, if sparse.
+fn extend_output(
+    output: Option<&std::fs::File>,
+    len: u64,
+) -> Result<(), MergeError> {
+    let output: &std::fs::File = match output {
+        | Some(output) => output,
+        | None => return Ok(()),
+    };
+
+    let current: u64 =
+        output.metadata().map_err(|_| MergeError::OutFileNotWritten)?.len();
+
+    if current < len {
+        output.set_len(len).map_err(|_| MergeError::OutFileNotWritten)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod split;
+
+pub mod merge;
+
+pub mod io;
+
+pub mod sync;
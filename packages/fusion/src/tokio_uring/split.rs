@@ -0,0 +1,292 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use tokio_uring::fs;
+
+use crate::{
+    chunk::{ChunkInfo, temp_path},
+    hash::Digest,
+    lock::FileLock,
+    progress::ProgressGuard,
+    split::{ChunkLayout, Split, SplitError, SplitResult, VERIFY_ALGORITHM},
+    tokio_uring::{
+        io::{read_full_at, write_all_at},
+        sync::{sync_dir, sync_file},
+    },
+};
+
+/// Trait for running the split process.
+pub trait SplitAsyncExt {
+    /// Run the split process asynchronously.
+    ///
+    /// It must be run within the `tokio_uring` runtime,
+    /// such as with `tokio_uring::start`.
+    fn run_async(
+        &self
+    ) -> impl std::future::Future<Output = Result<SplitResult, SplitError>>;
+}
+
+impl SplitAsyncExt for Split {
+    async fn run_async(&self) -> Result<SplitResult, SplitError> {
+        let _progress: ProgressGuard =
+            ProgressGuard::start(self.progress.as_ref());
+
+        let in_file: &Path = match self.in_file {
+            | Some(ref p) => {
+                let p: &Path = p.as_ref();
+
+                // if in_file not exists
+                if !p.exists() {
+                    return Err(SplitError::InFileNotFound);
+                }
+
+                // if in_file not a file
+                if !p.is_file() {
+                    return Err(SplitError::InFileNotFile);
+                }
+
+                p
+            },
+            | None => return Err(SplitError::InFileNotSet),
+        };
+
+        let mut retries: usize = 0;
+
+        let result: SplitResult = loop {
+            match split_path(self, in_file).await {
+                | Err(error) if self.is_retried(&error, retries) => {
+                    retries += 1;
+                },
+                | result => break result?,
+            }
+        };
+
+        if self.remove_source {
+            self.remove_source_file(in_file, &result)?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Split the input file at a path to the output directory,
+/// checked for changes once read.
+async fn split_path(
+    process: &Split,
+    in_file: &Path,
+) -> Result<SplitResult, SplitError> {
+    let progress: ProgressGuard =
+        ProgressGuard::start(process.progress.as_ref());
+
+    let _lock: Option<FileLock> = process.lock_out_dir(None)?;
+
+    let out_dir: &Path = process.prepare_out_dir()?;
+
+    let metadata: std::fs::Metadata =
+        std::fs::metadata(in_file).map_err(|_| SplitError::InFileNotRead)?;
+
+    let file_size: u64 = metadata.len();
+
+    let layout: ChunkLayout = process.file_layout(in_file, file_size)?;
+
+    process.ensure_space(out_dir, &layout, file_size)?;
+
+    let total_chunks: usize = layout.count(file_size);
+
+    let input: fs::File = fs::File::open(in_file)
+        .await
+        .map_err(|_| SplitError::InFileNotOpened)?;
+
+    let mut buffer: Vec<u8> = Vec::with_capacity(layout.max_len());
+
+    let started: Instant = Instant::now();
+
+    let mut chunks: Vec<ChunkInfo> = Vec::with_capacity(total_chunks);
+
+    let mut chunks_resumed: usize = 0;
+
+    for index in 0..total_chunks {
+        if process.is_cancelled() {
+            process.remove_chunks(out_dir, index);
+
+            return Err(SplitError::Cancelled);
+        }
+
+        if process.is_expired() {
+            return Err(SplitError::DeadlineExceeded);
+        }
+
+        let offset: u64 = layout.offset(index);
+
+        let size: usize = layout.len_in(index, file_size);
+
+        let chunk: ChunkInfo = match process.written_chunk(out_dir, index, size)
+        {
+            // skip the chunk already written
+            | Some(chunk) => {
+                chunks_resumed += 1;
+
+                chunk
+            },
+            | None => {
+                let read: usize =
+                    read_full_at(&input, &mut buffer, offset, size)
+                        .await
+                        .map_err(|_| SplitError::InFileNotRead)?;
+
+                // if in_file changed in the meantime
+                if read != size {
+                    let error: SplitError = match process.check_source(
+                        in_file,
+                        &metadata,
+                        &std::fs::metadata(in_file),
+                        false,
+                    ) {
+                        | Ok(()) => SplitError::InFileNotRead,
+                        | Err(error) => {
+                            process.discard_chunks(out_dir, index);
+
+                            error
+                        },
+                    };
+
+                    return Err(error);
+                }
+
+                write_chunk(
+                    process,
+                    out_dir,
+                    &layout,
+                    file_size,
+                    index,
+                    &mut buffer,
+                )
+                .await?
+            },
+        };
+
+        process.first_chunk(&chunk);
+
+        progress.advance(index, size);
+
+        chunks.push(chunk);
+    }
+
+    let _ = input.close().await;
+
+    // if in_file changed in the meantime
+    if let Err(error) = process.check_source(
+        in_file,
+        &metadata,
+        &std::fs::metadata(in_file),
+        true,
+    ) {
+        process.discard_chunks(out_dir, total_chunks);
+
+        return Err(error);
+    }
+
+    process.write_parity(out_dir, &chunks)?;
+
+    let bao_hash: Option<Digest> = process.write_bao(out_dir, &chunks)?;
+
+    process.write_metadata(out_dir)?;
+
+    for (name, script) in process.merge_scripts(&chunks) {
+        std::fs::write(out_dir.join(name), script)
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+    }
+
+    sync_dir(process.sync, out_dir)
+        .await
+        .map_err(|_| SplitError::OutFileNotWritten)?;
+
+    progress.finish();
+
+    Ok(SplitResult {
+        file_size,
+        total_chunks,
+        chunk_size: layout.chunk_size,
+        chunks,
+        duration: started.elapsed(),
+        chunks_resumed,
+        bao_hash,
+    })
+}
+
+/// Write a chunk file with the content of the buffer.
+async fn write_chunk(
+    process: &Split,
+    out_dir: &Path,
+    layout: &ChunkLayout,
+    file_size: u64,
+    index: usize,
+    buffer: &mut Vec<u8>,
+) -> Result<ChunkInfo, SplitError> {
+    let output_path: PathBuf = out_dir.join(process.naming.file_name(index));
+
+    // write to a temporary file to rename, if atomic
+    let write_path: PathBuf = match process.atomic {
+        | true => temp_path(&output_path),
+        | false => output_path.clone(),
+    };
+
+    let output: std::fs::File = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(&write_path)
+        .map_err(|_| SplitError::OutFileNotOpened)?;
+
+    let size: usize = buffer.len();
+
+    // the chunk of zeros is left as a hole, if sparse
+    let hole: bool = process.is_sparse_chunk(buffer);
+
+    if hole {
+        output
+            .set_len(size as u64)
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+    }
+
+    let output: fs::File = fs::File::from_std(output);
+
+    // the content is moved to be written, unless encoded
+    let mut encoded: Option<Vec<u8>> =
+        match process.encode(layout, Some(file_size), index, buffer)? {
+            | Cow::Owned(data) => Some(data),
+            | Cow::Borrowed(_) => None,
+        };
+
+    let stored: &mut Vec<u8> = match encoded {
+        | Some(ref mut data) => data,
+        | None => buffer,
+    };
+
+    if !hole {
+        write_all_at(&output, stored, 0)
+            .await
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+    }
+
+    sync_file(process.sync, &output)
+        .await
+        .map_err(|_| SplitError::OutFileNotWritten)?;
+
+    output.close().await.map_err(|_| SplitError::OutFileNotWritten)?;
+
+    if process.verify_writes {
+        process.verify_chunk(&write_path, &VERIFY_ALGORITHM.digest(stored))?;
+    }
+
+    if process.atomic {
+        fs::rename(&write_path, &output_path)
+            .await
+            .map_err(|_| SplitError::OutFileNotWritten)?;
+    }
+
+    Ok(ChunkInfo { index, path: output_path, size })
+}
@@ -0,0 +1,33 @@
+use std::{io, path::Path};
+
+use tokio_uring::fs;
+
+use crate::sync::SyncPolicy;
+
+/// Sync a written file by the policy.
+pub(crate) async fn sync_file(
+    policy: SyncPolicy,
+    file: &fs::File,
+) -> io::Result<()> {
+    match policy {
+        | SyncPolicy::None => Ok(()),
+        | SyncPolicy::Data => file.sync_data().await,
+        | SyncPolicy::All => file.sync_all().await,
+    }
+}
+
+/// Sync a directory by the policy, so the entries written persist.
+pub(crate) async fn sync_dir(
+    policy: SyncPolicy,
+    dir: &Path,
+) -> io::Result<()> {
+    if policy == SyncPolicy::None {
+        return Ok(());
+    }
+
+    let file: fs::File = fs::File::open(dir).await?;
+
+    file.sync_all().await?;
+
+    file.close().await
+}
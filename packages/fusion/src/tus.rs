@@ -0,0 +1,608 @@
+use std::{
+    fs, thread,
+    time::{Duration, Instant},
+};
+
+use reqwest::{
+    StatusCode, Url,
+    blocking::{Client, RequestBuilder, Response},
+    header::{CONTENT_TYPE, LOCATION},
+};
+
+use crate::{
+    split::SplitResult,
+    store::{ChunkStore, StoreError},
+};
+
+/// The version of the tus protocol spoken by the client.
+pub const TUS_VERSION: &str = "1.0.0";
+
+/// The content type of the body of a `PATCH` request.
+const OFFSET_OCTET_STREAM: &str = "application/offset+octet-stream";
+
+/// tus error enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TusError {
+    UploadNotCreated,
+    UploadNotFound,
+    OffsetNotRead,
+    OffsetMismatch,
+    ChunkNotRead,
+    ChunkNotUploaded,
+    UploadIncomplete,
+}
+
+impl TusError {
+    /// Get the code of the error as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::UploadNotCreated => "upload_not_created",
+            | Self::UploadNotFound => "upload_not_found",
+            | Self::OffsetNotRead => "offset_not_read",
+            | Self::OffsetMismatch => "offset_mismatch",
+            | Self::ChunkNotRead => "chunk_not_read",
+            | Self::ChunkNotUploaded => "chunk_not_uploaded",
+            | Self::UploadIncomplete => "upload_incomplete",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::UploadNotCreated => "The upload could not be created.",
+            | Self::UploadNotFound => "The upload not found on the server.",
+            | Self::OffsetNotRead => {
+                "The offset of the upload could not be read."
+            },
+            | Self::OffsetMismatch => {
+                "The offset of the upload is behind the chunks sent."
+            },
+            | Self::ChunkNotRead => "The chunk could not be read.",
+            | Self::ChunkNotUploaded => "The chunk could not be uploaded.",
+            | Self::UploadIncomplete => {
+                "The upload is not complete on the server."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Result of an upload to a tus server.
+#[derive(Debug, Clone)]
+pub struct TusResult {
+    /// The URL of the upload on the server.
+    pub upload_url: String,
+    /// Size of the uploaded file in bytes.
+    pub file_size: u64,
+    /// The number of bytes already on the server when resumed.
+    pub bytes_resumed: u64,
+    /// The time taken by the upload.
+    pub duration: Duration,
+}
+
+/// Resumable upload to a server speaking the tus 1.0 protocol.
+///
+/// The upload is created on the endpoint with a `POST` request, then the
+/// chunks are appended in order with `PATCH` requests from the offset
+/// reported by the server with `HEAD`. A failed request is retried after
+/// the offset is negotiated again, so only the missing bytes are sent.
+///
+/// To resume after a restart, keep the URL of the upload and pass it to
+/// [`TusUpload::upload_url`], then send the chunks again from the start:
+/// the bytes already on the server are skipped.
+///
+/// The chunks are sent as written, so they must hold the content only,
+/// not compressed nor encrypted, and without a header.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     split::{Split, SplitResult},
+///     tus::{TusResult, TusUpload},
+/// };
+///
+/// let result: SplitResult = Split::new()
+///     .in_file(PathBuf::from("path").join("to").join("file"))
+///     .out_dir(PathBuf::from("path").join("to").join("dir"))
+///     .run()
+///     .unwrap();
+///
+/// let mut upload: TusUpload = TusUpload::new("https://example.com/files/")
+///     .metadata("filename", "file")
+///     .retries(3);
+///
+/// let result: TusResult = upload.upload_chunks(&result).unwrap();
+///
+/// // keep `result.upload_url` to resume later
+/// ```
+#[derive(Debug, Clone)]
+pub struct TusUpload {
+    pub endpoint: String,
+    pub upload_url: Option<String>,
+    pub file_size: Option<u64>,
+    pub headers: Vec<(String, String)>,
+    pub metadata: Vec<(String, String)>,
+    pub retries: usize,
+    pub retry_delay: Duration,
+    client: Client,
+    // the offset reported by the server, `None` to request it
+    offset: Option<u64>,
+    // the offset of the next chunk in the file
+    position: u64,
+    // the size of the file is sent once all chunks are uploaded
+    length_deferred: bool,
+    bytes_resumed: Option<u64>,
+    next_index: usize,
+    started: Option<Instant>,
+}
+
+impl TusUpload {
+    /// Create a new upload to the creation endpoint of the server.
+    pub fn new<Endpoint: Into<String>>(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            upload_url: None,
+            file_size: None,
+            headers: Vec::new(),
+            metadata: Vec::new(),
+            retries: 0,
+            retry_delay: Duration::from_secs(1),
+            client: Client::new(),
+            offset: None,
+            position: 0,
+            length_deferred: false,
+            bytes_resumed: None,
+            next_index: 0,
+            started: None,
+        }
+    }
+
+    /// Set the URL of an upload created before, to resume it
+    /// instead of creating a new one.
+    pub fn upload_url<UploadUrl: Into<String>>(
+        mut self,
+        url: UploadUrl,
+    ) -> Self {
+        self.upload_url = Some(url.into());
+        self
+    }
+
+    /// Set the size of the file, sent when the upload is created.
+    ///
+    /// By default, it is the size of the split result with
+    /// [`TusUpload::upload_chunks`]. Otherwise, the size is sent once all
+    /// chunks are uploaded, if the server supports the
+    /// `creation-defer-length` extension.
+    pub fn file_size(
+        mut self,
+        size: u64,
+    ) -> Self {
+        self.file_size = Some(size);
+        self
+    }
+
+    /// Add a header to all requests, such as for authorization.
+    pub fn header<Name: Into<String>, Value: Into<String>>(
+        mut self,
+        name: Name,
+        value: Value,
+    ) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Add a pair to the `Upload-Metadata` of the upload.
+    pub fn metadata<Key: Into<String>, Value: Into<String>>(
+        mut self,
+        key: Key,
+        value: Value,
+    ) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set the number of times a failed request is retried.
+    ///
+    /// By default, it is `0`.
+    pub fn retries(
+        mut self,
+        retries: usize,
+    ) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set the delay before a failed request is retried.
+    ///
+    /// By default, it is 1 second.
+    pub fn retry_delay(
+        mut self,
+        delay: Duration,
+    ) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Create the upload on the server if not created yet,
+    /// getting its URL.
+    pub fn create(&mut self) -> Result<String, TusError> {
+        if let Some(ref url) = self.upload_url {
+            return Ok(url.clone());
+        }
+
+        let url: String = self.retry(|upload| upload.send_create())?;
+
+        self.upload_url = Some(url.clone());
+        self.offset = Some(0);
+
+        Ok(url)
+    }
+
+    /// Get the offset of the upload from the server.
+    pub fn offset(&mut self) -> Result<u64, TusError> {
+        let offset: u64 = self.retry(|upload| upload.send_head())?;
+
+        self.offset = Some(offset);
+
+        Ok(offset)
+    }
+
+    /// Upload the chunks of a split result, in order,
+    /// then complete the upload.
+    pub fn upload_chunks(
+        &mut self,
+        result: &SplitResult,
+    ) -> Result<TusResult, TusError> {
+        self.file_size.get_or_insert(result.file_size);
+
+        for chunk in &result.chunks {
+            let data: Vec<u8> =
+                fs::read(&chunk.path).map_err(|_| TusError::ChunkNotRead)?;
+
+            self.upload_chunk(&data)?;
+        }
+
+        self.finish()
+    }
+
+    /// Append the next chunk to the upload.
+    ///
+    /// The bytes of the chunk already on the server are skipped.
+    pub fn upload_chunk(
+        &mut self,
+        data: &[u8],
+    ) -> Result<(), TusError> {
+        self.create()?;
+
+        let start: u64 = self.position;
+
+        self.retry(|upload| upload.send_chunk(start, data))?;
+
+        self.position += data.len() as u64;
+        self.next_index += 1;
+
+        Ok(())
+    }
+
+    /// Complete the upload once all chunks are uploaded,
+    /// sending the size of the file if deferred.
+    pub fn finish(&mut self) -> Result<TusResult, TusError> {
+        let upload_url: String = self.create()?;
+
+        if self.length_deferred {
+            self.retry(|upload| upload.send_length())?;
+
+            self.length_deferred = false;
+        }
+
+        if self.offset()? != self.position {
+            return Err(TusError::UploadIncomplete);
+        }
+
+        Ok(TusResult {
+            upload_url,
+            file_size: self.position,
+            bytes_resumed: self.bytes_resumed.unwrap_or(self.position),
+            duration: self.started.map(|s| s.elapsed()).unwrap_or_default(),
+        })
+    }
+
+    /// Run the request up to the number of retries.
+    fn retry<T>(
+        &mut self,
+        mut request: impl FnMut(&mut Self) -> Result<T, TusError>,
+    ) -> Result<T, TusError> {
+        self.started.get_or_insert_with(Instant::now);
+
+        let mut retries: usize = 0;
+
+        loop {
+            match request(self) {
+                | Ok(value) => return Ok(value),
+                | Err(error) if is_retried(error) && retries < self.retries => {
+                    retries += 1;
+
+                    // negotiate the offset again
+                    self.offset = None;
+
+                    thread::sleep(self.retry_delay);
+                },
+                | Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Build a request with the headers of the protocol.
+    fn request(
+        &self,
+        builder: RequestBuilder,
+    ) -> RequestBuilder {
+        self.headers
+            .iter()
+            .fold(builder, |builder, (name, value)| builder.header(name, value))
+            .header("Tus-Resumable", TUS_VERSION)
+    }
+
+    /// Send the `POST` request creating the upload.
+    fn send_create(&mut self) -> Result<String, TusError> {
+        let mut builder: RequestBuilder =
+            self.request(self.client.post(&self.endpoint));
+
+        builder = match self.file_size {
+            | Some(size) => builder.header("Upload-Length", size),
+            | None => builder.header("Upload-Defer-Length", 1),
+        };
+
+        if !self.metadata.is_empty() {
+            builder = builder.header("Upload-Metadata", self.encode_metadata());
+        }
+
+        let response: Response =
+            builder.send().map_err(|_| TusError::UploadNotCreated)?;
+
+        if response.status() != StatusCode::CREATED {
+            return Err(TusError::UploadNotCreated);
+        }
+
+        let location: &str = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|l| l.to_str().ok())
+            .ok_or(TusError::UploadNotCreated)?;
+
+        // the location can be relative to the endpoint
+        let url: Url = response
+            .url()
+            .join(location)
+            .map_err(|_| TusError::UploadNotCreated)?;
+
+        self.length_deferred = self.file_size.is_none();
+        self.bytes_resumed = Some(0);
+
+        Ok(url.to_string())
+    }
+
+    /// Send the `HEAD` request getting the offset of the upload.
+    fn send_head(&mut self) -> Result<u64, TusError> {
+        let url: &str =
+            self.upload_url.as_deref().ok_or(TusError::UploadNotFound)?;
+
+        let response: Response = self
+            .request(self.client.head(url))
+            .send()
+            .map_err(|_| TusError::OffsetNotRead)?;
+
+        match response.status() {
+            | StatusCode::NOT_FOUND | StatusCode::GONE => {
+                return Err(TusError::UploadNotFound);
+            },
+            | status if !status.is_success() => {
+                return Err(TusError::OffsetNotRead);
+            },
+            | _ => {},
+        }
+
+        let offset: u64 =
+            upload_offset(&response).ok_or(TusError::OffsetNotRead)?;
+
+        self.length_deferred =
+            response.headers().contains_key("Upload-Defer-Length");
+        self.bytes_resumed.get_or_insert(offset);
+
+        Ok(offset)
+    }
+
+    /// Send the `PATCH` requests appending the chunk starting at the
+    /// offset in the file, from the offset of the upload.
+    fn send_chunk(
+        &mut self,
+        start: u64,
+        data: &[u8],
+    ) -> Result<(), TusError> {
+        let end: u64 = start + data.len() as u64;
+
+        loop {
+            let offset: u64 = match self.offset {
+                | Some(offset) => offset,
+                | None => {
+                    let offset: u64 = self.send_head()?;
+
+                    self.offset = Some(offset);
+
+                    offset
+                },
+            };
+
+            // the chunk is already on the server
+            if offset >= end {
+                return Ok(());
+            }
+
+            // the bytes before the chunk are missing on the server
+            if offset < start {
+                return Err(TusError::OffsetMismatch);
+            }
+
+            let url: &str =
+                self.upload_url.as_deref().ok_or(TusError::UploadNotFound)?;
+
+            let response: Response = self
+                .request(self.client.patch(url))
+                .header(CONTENT_TYPE, OFFSET_OCTET_STREAM)
+                .header("Upload-Offset", offset)
+                .body(data[(offset - start) as usize..].to_vec())
+                .send()
+                .map_err(|_| TusError::ChunkNotUploaded)?;
+
+            match response.status() {
+                | StatusCode::NOT_FOUND | StatusCode::GONE => {
+                    return Err(TusError::UploadNotFound);
+                },
+                | status if !status.is_success() => {
+                    return Err(TusError::ChunkNotUploaded);
+                },
+                | _ => {},
+            }
+
+            let next: u64 =
+                upload_offset(&response).ok_or(TusError::ChunkNotUploaded)?;
+
+            // the server must accept some bytes to go on
+            if next <= offset || next > end {
+                return Err(TusError::ChunkNotUploaded);
+            }
+
+            self.offset = Some(next);
+
+            if next == end {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Send the `PATCH` request declaring the deferred size of the file.
+    fn send_length(&mut self) -> Result<(), TusError> {
+        let url: &str =
+            self.upload_url.as_deref().ok_or(TusError::UploadNotFound)?;
+
+        let response: Response = self
+            .request(self.client.patch(url))
+            .header(CONTENT_TYPE, OFFSET_OCTET_STREAM)
+            .header("Upload-Offset", self.position)
+            .header("Upload-Length", self.position)
+            .body(Vec::new())
+            .send()
+            .map_err(|_| TusError::ChunkNotUploaded)?;
+
+        match response.status() {
+            | StatusCode::NOT_FOUND | StatusCode::GONE => {
+                Err(TusError::UploadNotFound)
+            },
+            | status if !status.is_success() => Err(TusError::ChunkNotUploaded),
+            | _ => Ok(()),
+        }
+    }
+
+    /// Encode the `Upload-Metadata` header,
+    /// with the values in base64.
+    fn encode_metadata(&self) -> String {
+        self.metadata
+            .iter()
+            .map(|(key, value)| match value.is_empty() {
+                | true => key.clone(),
+                | false => format!("{key} {}", encode_base64(value.as_bytes())),
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+/// The chunks are appended to the upload in order, as they are split.
+///
+/// The chunks are not read back, reading a chunk fails with
+/// [`StoreError::ChunkNotRead`]. Once all chunks are written,
+/// [`TusUpload::finish`] completes the upload.
+impl ChunkStore for TusUpload {
+    fn contains(
+        &self,
+        index: usize,
+    ) -> Result<bool, StoreError> {
+        Ok(index < self.next_index)
+    }
+
+    fn read(
+        &self,
+        _index: usize,
+    ) -> Result<Vec<u8>, StoreError> {
+        Err(StoreError::ChunkNotRead)
+    }
+
+    fn write(
+        &mut self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), StoreError> {
+        // the chunks can only be appended
+        if index != self.next_index {
+            return Err(StoreError::ChunkNotWritten);
+        }
+
+        self.upload_chunk(data).map_err(|_| StoreError::ChunkNotWritten)
+    }
+}
+
+/// Check if a request failed with the error can be retried.
+fn is_retried(error: TusError) -> bool {
+    matches!(
+        error,
+        TusError::UploadNotCreated
+            | TusError::OffsetNotRead
+            | TusError::ChunkNotUploaded
+    )
+}
+
+/// Get the `Upload-Offset` header of a response.
+fn upload_offset(response: &Response) -> Option<u64> {
+    response.headers().get("Upload-Offset")?.to_str().ok()?.parse().ok()
+}
+
+/// Encode the bytes in standard base64 with padding.
+fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded: String = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for group in data.chunks(3) {
+        let bytes: [u8; 3] = [
+            group[0],
+            *group.get(1).unwrap_or(&0),
+            *group.get(2).unwrap_or(&0),
+        ];
+
+        let value: u32 = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            match i <= group.len() {
+                | true => encoded.push(
+                    ALPHABET[(value >> (18 - 6 * i) & 0x3f) as usize] as char,
+                ),
+                | false => encoded.push('='),
+            }
+        }
+    }
+
+    encoded
+}
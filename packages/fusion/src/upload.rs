@@ -0,0 +1,464 @@
+use std::{
+    ffi::OsString,
+    fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use crate::{
+    bitmap::ChunkBitmap,
+    check::{Check, CheckError, CheckReport},
+    chunk::ChunkNaming,
+    layout::{chunk_count, chunk_range},
+    merge::{Merge, MergeError, MergeResult},
+};
+
+/// The name of the journal file in the session directory.
+pub const JOURNAL_FILE_NAME: &str = "session.journal";
+
+/// The first word of the journal, followed by its version.
+const JOURNAL_MAGIC: &str = "filerune-upload";
+
+/// The version of the journal format.
+const JOURNAL_VERSION: u8 = 1;
+
+/// File and chunk sizes of a session, with the chunks put, from a journal.
+type Journal = ((u64, usize), Vec<usize>);
+
+/// The number of chunk files written, to name their temporary files apart.
+static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+/// Upload session error enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadError {
+    Check(CheckError),
+    Merge(MergeError),
+    SessionDirNotCreated,
+    SessionDirNotRemoved,
+    SessionNotFound,
+    SessionMismatch,
+    JournalNotRead,
+    JournalNotWritten,
+    ChunkSizeInvalid,
+    ChunkOutOfRange,
+    ChunkSizeMismatch,
+    ChunkNotWritten,
+}
+
+impl UploadError {
+    /// Get the code of the error as `&str`.
+    ///
+    /// The errors of the check and merge processes keep their code.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::Check(error) => error.as_code(),
+            | Self::Merge(error) => error.as_code(),
+            | Self::SessionDirNotCreated => "session_dir_not_created",
+            | Self::SessionDirNotRemoved => "session_dir_not_removed",
+            | Self::SessionNotFound => "session_not_found",
+            | Self::SessionMismatch => "session_mismatch",
+            | Self::JournalNotRead => "journal_not_read",
+            | Self::JournalNotWritten => "journal_not_written",
+            | Self::ChunkSizeInvalid => "chunk_size_invalid",
+            | Self::ChunkOutOfRange => "chunk_out_of_range",
+            | Self::ChunkSizeMismatch => "chunk_size_mismatch",
+            | Self::ChunkNotWritten => "chunk_not_written",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    ///
+    /// The errors of the check and merge processes keep their message.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::Check(error) => error.as_message(),
+            | Self::Merge(error) => error.as_message(),
+            | Self::SessionDirNotCreated => {
+                "The session directory could not be created."
+            },
+            | Self::SessionDirNotRemoved => {
+                "The session directory could not be removed."
+            },
+            | Self::SessionNotFound => {
+                "The session directory has no journal to resume."
+            },
+            | Self::SessionMismatch => {
+                "The journal is of a session with another file or chunk size."
+            },
+            | Self::JournalNotRead => "The journal could not be read.",
+            | Self::JournalNotWritten => "The journal could not be written.",
+            | Self::ChunkSizeInvalid => "The chunk size must be positive.",
+            | Self::ChunkOutOfRange => {
+                "The index of the chunk is not within the file."
+            },
+            | Self::ChunkSizeMismatch => {
+                "The size of the chunk does not match its place in the file."
+            },
+            | Self::ChunkNotWritten => "The chunk could not be written.",
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Session of a chunked upload on the receiving side,
+/// such as a server accepting the chunks of a file from clients.
+///
+/// The session is created with the size of the file and the size of
+/// its chunks, then the chunks are put by index, in any order, from any
+/// number of threads. Each chunk is written to a temporary file renamed
+/// to the chunk file, so a chunk put again replaces the previous one whole
+/// and a reader never sees a partial chunk. The completeness is reported
+/// by the check process, and the file is assembled by the merge process
+/// once finalized.
+///
+/// The state of the session is kept in the session directory, with a
+/// journal of the chunks put, [`JOURNAL_FILE_NAME`], synced as each chunk
+/// is written. After a restart, the session is resumed by creating it
+/// again on the same directory, or by opening it with
+/// [`UploadSession::open`], and the chunks received before are recognized
+/// from the journal, so only the missing ones are requested again.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::{
+///     merge::{Merge, MergeResult},
+///     upload::UploadSession,
+/// };
+///
+/// let session: UploadSession = UploadSession::new(
+///     PathBuf::from("path").join("to").join("session"),
+///     10 * 1024 * 1024,
+///     1024 * 1024,
+/// )
+/// .unwrap();
+///
+/// // from the requests of the clients...
+/// session.put_chunk(0, &[0; 1024 * 1024]).unwrap();
+///
+/// if session.missing().unwrap().is_empty() {
+///     let result: MergeResult = session
+///         .finalize(
+///             Merge::new().out_file(PathBuf::from("path").join("to").join("file")),
+///         )
+///         .unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UploadSession {
+    pub dir: Arc<Path>,
+    pub file_size: u64,
+    pub chunk_size: usize,
+    pub naming: ChunkNaming,
+    journal: Arc<Mutex<()>>,
+}
+
+impl UploadSession {
+    /// Create a new session in the directory, or resume the one in it.
+    pub fn new<Dir: AsRef<Path>>(
+        dir: Dir,
+        file_size: u64,
+        chunk_size: usize,
+    ) -> Result<Self, UploadError> {
+        Self::with_naming(dir, file_size, chunk_size, ChunkNaming::new())
+    }
+
+    /// Create a new session in the directory with the naming scheme
+    /// of the chunk files, or resume the one in it.
+    pub fn with_naming<Dir: AsRef<Path>>(
+        dir: Dir,
+        file_size: u64,
+        chunk_size: usize,
+        naming: ChunkNaming,
+    ) -> Result<Self, UploadError> {
+        if chunk_size == 0 {
+            return Err(UploadError::ChunkSizeInvalid);
+        }
+
+        fs::create_dir_all(dir.as_ref())
+            .map_err(|_| UploadError::SessionDirNotCreated)?;
+
+        let session: Self = Self {
+            dir: Arc::from(dir.as_ref()),
+            file_size,
+            chunk_size,
+            naming,
+            journal: Arc::new(Mutex::new(())),
+        };
+
+        match session.read_journal()? {
+            | Some((sizes, _)) if sizes != (file_size, chunk_size) => {
+                return Err(UploadError::SessionMismatch);
+            },
+            | Some(_) => {},
+            | None => {
+                fs::write(
+                    session.journal_path(),
+                    format!(
+                        "{JOURNAL_MAGIC} {JOURNAL_VERSION} {file_size} {chunk_size}\n"
+                    ),
+                )
+                .map_err(|_| UploadError::JournalNotWritten)?;
+            },
+        }
+
+        Ok(session)
+    }
+
+    /// Open the session in the directory to resume it,
+    /// with the file and chunk sizes of its journal.
+    pub fn open<Dir: AsRef<Path>>(dir: Dir) -> Result<Self, UploadError> {
+        Self::open_with_naming(dir, ChunkNaming::new())
+    }
+
+    /// Open the session in the directory to resume it, with the naming
+    /// scheme of the chunk files and the sizes of its journal.
+    pub fn open_with_naming<Dir: AsRef<Path>>(
+        dir: Dir,
+        naming: ChunkNaming,
+    ) -> Result<Self, UploadError> {
+        let session: Self = Self {
+            dir: Arc::from(dir.as_ref()),
+            file_size: 0,
+            chunk_size: 0,
+            naming,
+            journal: Arc::new(Mutex::new(())),
+        };
+
+        let ((file_size, chunk_size), _) =
+            session.read_journal()?.ok_or(UploadError::SessionNotFound)?;
+
+        if chunk_size == 0 {
+            return Err(UploadError::ChunkSizeInvalid);
+        }
+
+        Ok(Self { file_size, chunk_size, ..session })
+    }
+
+    /// Get the path of the journal file.
+    pub fn journal_path(&self) -> PathBuf {
+        self.dir.join(JOURNAL_FILE_NAME)
+    }
+
+    /// Get the total number of chunks of the file.
+    pub fn total_chunks(&self) -> usize {
+        chunk_count(self.file_size, self.chunk_size)
+    }
+
+    /// Get the path of the chunk file.
+    pub fn path(
+        &self,
+        index: usize,
+    ) -> PathBuf {
+        self.dir.join(self.naming.file_name(index))
+    }
+
+    /// Put the content of a chunk, replacing the one put before.
+    ///
+    /// The size of the content must match the place of the chunk in the
+    /// file: the chunk size, or the remaining bytes for the last chunk.
+    pub fn put_chunk(
+        &self,
+        index: usize,
+        data: &[u8],
+    ) -> Result<(), UploadError> {
+        let (_, size) = chunk_range(index, self.file_size, self.chunk_size)
+            .ok_or(UploadError::ChunkOutOfRange)?;
+
+        if data.len() != size {
+            return Err(UploadError::ChunkSizeMismatch);
+        }
+
+        let path: PathBuf = self.path(index);
+
+        // the temporary file is unique to the write
+        let mut file_name: OsString =
+            path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+
+        file_name.push(format!(
+            ".{}.{}.tmp",
+            process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let temp_path: PathBuf = path.with_file_name(file_name);
+
+        let result: Result<(), UploadError> = fs::write(&temp_path, data)
+            .and_then(|_| fs::rename(&temp_path, &path))
+            .map_err(|_| UploadError::ChunkNotWritten);
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+
+            return result;
+        }
+
+        self.append_journal(index)
+    }
+
+    /// Get the bitmap of the chunks received, from the journal.
+    ///
+    /// A chunk in the journal is only received if its file is still
+    /// in the session directory with the expected size.
+    pub fn received(&self) -> Result<ChunkBitmap, UploadError> {
+        let (_, indices) =
+            self.read_journal()?.ok_or(UploadError::SessionNotFound)?;
+
+        let mut bitmap: ChunkBitmap = ChunkBitmap::new(self.total_chunks());
+
+        for index in indices {
+            let size: Option<usize> =
+                chunk_range(index, self.file_size, self.chunk_size)
+                    .map(|(_, size)| size);
+
+            let found: bool = fs::metadata(self.path(index)).is_ok_and(|m| {
+                m.is_file() && Some(m.len()) == size.map(|s| s as u64)
+            });
+
+            if found {
+                bitmap.insert(index);
+            }
+        }
+
+        Ok(bitmap)
+    }
+
+    /// Check if the chunk is put.
+    pub fn has_chunk(
+        &self,
+        index: usize,
+    ) -> bool {
+        self.path(index).is_file()
+    }
+
+    /// Get the status of each chunk of the session
+    /// from the check process.
+    pub fn status(&self) -> Result<CheckReport, UploadError> {
+        self.check().report().map_err(UploadError::Check)
+    }
+
+    /// Get the indices of the chunks not received yet, from the journal.
+    pub fn missing(&self) -> Result<Vec<usize>, UploadError> {
+        Ok(self.received()?.missing().collect())
+    }
+
+    /// Check if all chunks are put and match the file size.
+    pub fn is_complete(&self) -> Result<bool, UploadError> {
+        Ok(self.status()?.is_complete())
+    }
+
+    /// Finalize the session, merging the chunks with the process to its
+    /// output file once all of them are put.
+    ///
+    /// The input directory, the naming scheme and the expected chunks of
+    /// the process are set by the session. If a chunk is missing,
+    /// the process fails with the error of the check process.
+    pub fn finalize(
+        &self,
+        merge: Merge,
+    ) -> Result<MergeResult, UploadError> {
+        self.check().run().map_err(UploadError::Check)?;
+
+        merge
+            .in_dir_shared(Arc::clone(&self.dir))
+            .naming(self.naming.clone())
+            .expected_chunks(self.total_chunks(), self.file_size)
+            .run()
+            .map_err(UploadError::Merge)
+    }
+
+    /// Remove the session directory with its chunks,
+    /// such as once finalized or abandoned.
+    pub fn remove(self) -> Result<(), UploadError> {
+        fs::remove_dir_all(&self.dir)
+            .map_err(|_| UploadError::SessionDirNotRemoved)
+    }
+
+    /// Append a chunk written to the journal, synced to the disk.
+    fn append_journal(
+        &self,
+        index: usize,
+    ) -> Result<(), UploadError> {
+        // the lines of concurrent writes are not interleaved
+        let _guard = self.journal.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut file: fs::File = fs::OpenOptions::new()
+            .append(true)
+            .open(self.journal_path())
+            .map_err(|_| UploadError::JournalNotWritten)?;
+
+        file.write_all(format!("{index}\n").as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|_| UploadError::JournalNotWritten)
+    }
+
+    /// Read the sizes and the chunks of the journal,
+    /// `None` if the session directory has no journal.
+    ///
+    /// A line not complete, as written before a crash, is skipped.
+    fn read_journal(&self) -> Result<Option<Journal>, UploadError> {
+        let journal: String = match fs::read_to_string(self.journal_path()) {
+            | Ok(journal) => journal,
+            | Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(None);
+            },
+            | Err(_) => return Err(UploadError::JournalNotRead),
+        };
+
+        let mut lines = journal.split_inclusive('\n');
+
+        let header: Vec<&str> = lines
+            .next()
+            .ok_or(UploadError::JournalNotRead)?
+            .split_whitespace()
+            .collect();
+
+        let sizes: (u64, usize) = match header.as_slice() {
+            | [magic, version, file_size, chunk_size]
+                if *magic == JOURNAL_MAGIC
+                    && version.parse() == Ok(JOURNAL_VERSION) =>
+            {
+                (
+                    file_size
+                        .parse()
+                        .map_err(|_| UploadError::JournalNotRead)?,
+                    chunk_size
+                        .parse()
+                        .map_err(|_| UploadError::JournalNotRead)?,
+                )
+            },
+            | _ => return Err(UploadError::JournalNotRead),
+        };
+
+        let indices: Vec<usize> = lines
+            .filter_map(|line| line.strip_suffix('\n')?.parse().ok())
+            .collect();
+
+        Ok(Some((sizes, indices)))
+    }
+
+    /// Get the check process of the chunks of the session.
+    fn check(&self) -> Check {
+        Check::new()
+            .in_dir_shared(Arc::clone(&self.dir))
+            .naming(self.naming.clone())
+            .file_size(self.file_size)
+            .total_chunks(self.total_chunks())
+    }
+}
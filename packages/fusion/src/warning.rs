@@ -0,0 +1,126 @@
+use std::{fmt, path::PathBuf, sync::Arc};
+
+/// Non-fatal issue reported by a process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// An entry in the input directory was ignored.
+    IgnoredEntry(PathBuf),
+    /// The output directory was not empty before the process.
+    OutDirNotEmpty(PathBuf),
+    /// A chunk with the same index as another was discarded.
+    DuplicateChunk(PathBuf),
+    /// A missing or corrupted chunk was reconstructed from the parity chunks.
+    ChunkRepaired(PathBuf),
+    /// A chunk could not be cloned with reflinks, the data was copied.
+    ///
+    /// It holds the input file when splitting, the chunk when merging.
+    ReflinkFallback(PathBuf),
+    /// A chunk not matching the digest of its header was ignored.
+    CorruptedChunk(PathBuf),
+    /// The input file changed during the split, the chunks were kept.
+    SourceChanged(PathBuf),
+    /// The metadata of the input file was not recorded or not applied
+    /// to the output file.
+    MetadataNotApplied(PathBuf),
+}
+
+impl Warning {
+    /// Get the code of the warning as `&str`.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::IgnoredEntry(_) => "ignored_entry",
+            | Self::OutDirNotEmpty(_) => "out_dir_not_empty",
+            | Self::DuplicateChunk(_) => "duplicate_chunk",
+            | Self::ChunkRepaired(_) => "chunk_repaired",
+            | Self::ReflinkFallback(_) => "reflink_fallback",
+            | Self::CorruptedChunk(_) => "corrupted_chunk",
+            | Self::SourceChanged(_) => "source_changed",
+            | Self::MetadataNotApplied(_) => "metadata_not_applied",
+        }
+    }
+
+    /// Get the code of the warning as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the warning as `&str`.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::IgnoredEntry(_) => {
+                "An entry in the input directory was ignored."
+            },
+            | Self::OutDirNotEmpty(_) => {
+                "The output directory was not empty before the process."
+            },
+            | Self::DuplicateChunk(_) => {
+                "A chunk with the same index as another was discarded."
+            },
+            | Self::ChunkRepaired(_) => {
+                "A missing or corrupted chunk was reconstructed from the parity chunks."
+            },
+            | Self::ReflinkFallback(_) => {
+                "A chunk could not be cloned with reflinks, the data was copied."
+            },
+            | Self::CorruptedChunk(_) => {
+                "A chunk not matching the digest of its header was ignored."
+            },
+            | Self::SourceChanged(_) => {
+                "The input file changed during the split, the chunks were kept."
+            },
+            | Self::MetadataNotApplied(_) => {
+                "The metadata of the input file could not be applied to the output."
+            },
+        }
+    }
+
+    /// Get the message of the warning as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Handler to receive the warnings of a process.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+///
+/// use filerune_fusion::merge::Merge;
+///
+/// Merge::new()
+///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+///     .out_file(PathBuf::from("path").join("to").join("file"))
+///     .on_warning(|warning| eprintln!("{}", warning.as_message()))
+///     .run()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct WarningHandler {
+    handler: Arc<dyn Fn(&Warning) + Send + Sync>,
+}
+
+impl WarningHandler {
+    /// Create a new warning handler.
+    pub fn new<F: Fn(&Warning) + Send + Sync + 'static>(handler: F) -> Self {
+        Self { handler: Arc::new(handler) }
+    }
+
+    /// Emit a warning to the handler.
+    pub fn emit(
+        &self,
+        warning: &Warning,
+    ) {
+        (self.handler)(warning)
+    }
+}
+
+impl fmt::Debug for WarningHandler {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("WarningHandler").finish_non_exhaustive()
+    }
+}
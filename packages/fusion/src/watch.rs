@@ -0,0 +1,410 @@
+use std::{
+    fmt, fs,
+    path::Path,
+    sync::{
+        Arc,
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as _};
+
+use crate::{
+    cancel::CancellationToken,
+    check::{Check, CheckError},
+    merge::{Merge, MergeError, MergeResult},
+};
+
+/// The default interval between two rescans of the directory,
+/// the fallback for the events missed.
+pub const WATCH_INTERVAL_DEFAULT: Duration = Duration::from_secs(5);
+
+/// The longest wait for an event, before the cancellation
+/// and the deadline are checked again.
+const EVENT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Watch process error enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchError {
+    Check(CheckError),
+    Merge(MergeError),
+    InDirNotSet,
+    Cancelled,
+    DeadlineExceeded,
+}
+
+impl WatchError {
+    /// Get the code of the error as `&str`.
+    ///
+    /// The errors of the check and merge processes keep their code.
+    pub fn as_code(&self) -> &str {
+        match self {
+            | Self::Check(error) => error.as_code(),
+            | Self::Merge(error) => error.as_code(),
+            | Self::InDirNotSet => "in_dir_not_set",
+            | Self::Cancelled => "cancelled",
+            | Self::DeadlineExceeded => "deadline_exceeded",
+        }
+    }
+
+    /// Get the code of the error as `String`.
+    pub fn to_code(&self) -> String {
+        self.as_code().to_string()
+    }
+
+    /// Get the message of the error as `&str`.
+    ///
+    /// The errors of the check and merge processes keep their message.
+    pub fn as_message(&self) -> &str {
+        match self {
+            | Self::Check(error) => error.as_message(),
+            | Self::Merge(error) => error.as_message(),
+            | Self::InDirNotSet => "The input directory is not set.",
+            | Self::Cancelled => "The watch process was cancelled.",
+            | Self::DeadlineExceeded => {
+                "The deadline of the watch process was exceeded."
+            },
+        }
+    }
+
+    /// Get the message of the error as `String`.
+    pub fn to_message(&self) -> String {
+        self.as_message().to_string()
+    }
+}
+
+/// Function receiving the result of a watch process.
+type MergedFn = dyn Fn(&Result<MergeResult, WatchError>) + Send + Sync;
+
+/// Handler to receive the result of a watch process once the chunks
+/// are merged, or once it fails.
+#[derive(Clone)]
+pub struct MergedHandler {
+    handler: Arc<MergedFn>,
+}
+
+impl MergedHandler {
+    /// Create a new merged handler.
+    pub fn new<
+        F: Fn(&Result<MergeResult, WatchError>) + Send + Sync + 'static,
+    >(
+        handler: F
+    ) -> Self {
+        Self { handler: Arc::new(handler) }
+    }
+
+    /// Emit a result to the handler.
+    pub fn emit(
+        &self,
+        result: &Result<MergeResult, WatchError>,
+    ) {
+        (self.handler)(result)
+    }
+}
+
+impl fmt::Debug for MergedHandler {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("MergedHandler").finish_non_exhaustive()
+    }
+}
+
+/// Process to watch an incoming chunk directory, such as the target of
+/// an upload endpoint, and merge it once all expected chunks arrived.
+///
+/// The directory is watched with the notification API of the operating
+/// system, through `notify`, and checked by the check process whenever
+/// its entries changed. Once the check process passes, the chunks are
+/// merged by the merge process, and the result is emitted to the handler.
+/// The directory may not exist when the process starts.
+/// The input directory of the check and merge processes is set by the
+/// watch process, and the expected chunks of the merge process by the
+/// file size and the total number of chunks of the check process, if set.
+///
+/// The directory is also rescanned at each interval, as a fallback for
+/// the events missed, such as on network file systems, or if the
+/// notification API is not available.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::{path::PathBuf, time::Duration};
+///
+/// use filerune_fusion::{check::Check, merge::Merge, watch::Watch};
+///
+/// let watcher = Watch::new()
+///     .in_dir(PathBuf::from("path").join("to").join("dir"))
+///     .check(Check::new().file_size(8 * 1024 * 1024).total_chunks(8))
+///     .merge(Merge::new().out_file(PathBuf::from("path").join("to").join("file")))
+///     .interval(Duration::from_secs(1))
+///     .on_merged(|result| {
+///         // notify the application...
+///     })
+///     .spawn();
+///
+/// // later...
+/// let result = watcher.join().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Watch {
+    pub in_dir: Option<Arc<Path>>,
+    pub check: Check,
+    pub merge: Merge,
+    pub interval: Duration,
+    pub on_merged: Option<MergedHandler>,
+    pub cancellation_token: Option<CancellationToken>,
+    pub deadline: Option<Instant>,
+}
+
+impl Watch {
+    /// Create a new watch process.
+    pub fn new() -> Self {
+        Self {
+            in_dir: None,
+            check: Check::new(),
+            merge: Merge::new(),
+            interval: WATCH_INTERVAL_DEFAULT,
+            on_merged: None,
+            cancellation_token: None,
+            deadline: None,
+        }
+    }
+
+    /// Create a new watch process from an existing one.
+    pub fn from<P: Into<Watch>>(process: P) -> Self {
+        process.into()
+    }
+
+    /// Set the directory to watch.
+    pub fn in_dir<InDir: AsRef<Path>>(
+        mut self,
+        path: InDir,
+    ) -> Self {
+        self.in_dir = Some(Arc::from(path.as_ref()));
+        self
+    }
+
+    /// Set the check process run on the directory once changed.
+    pub fn check(
+        mut self,
+        process: Check,
+    ) -> Self {
+        self.check = process;
+        self
+    }
+
+    /// Set the merge process run on the directory once checked.
+    pub fn merge(
+        mut self,
+        process: Merge,
+    ) -> Self {
+        self.merge = process;
+        self
+    }
+
+    /// Set the interval between two rescans of the directory,
+    /// the fallback for the events missed.
+    ///
+    /// By default, it is [`WATCH_INTERVAL_DEFAULT`].
+    pub fn interval(
+        mut self,
+        interval: Duration,
+    ) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the handler to receive the result once merged or failed.
+    pub fn on_merged<
+        F: Fn(&Result<MergeResult, WatchError>) + Send + Sync + 'static,
+    >(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.on_merged = Some(MergedHandler::new(handler));
+        self
+    }
+
+    /// Set the cancellation token to stop watching.
+    pub fn cancellation_token(
+        mut self,
+        token: CancellationToken,
+    ) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Set the deadline to stop watching.
+    pub fn deadline(
+        mut self,
+        deadline: Instant,
+    ) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Run the watch process, blocking until the chunks are merged.
+    pub fn run(&self) -> Result<MergeResult, WatchError> {
+        let result: Result<MergeResult, WatchError> = self.watch();
+
+        if let Some(ref handler) = self.on_merged {
+            handler.emit(&result);
+        }
+
+        result
+    }
+
+    /// Run the watch process on a new thread.
+    pub fn spawn(self) -> thread::JoinHandle<Result<MergeResult, WatchError>> {
+        thread::spawn(move || self.run())
+    }
+
+    /// Wait for the chunks to be complete, then merge them.
+    fn watch(&self) -> Result<MergeResult, WatchError> {
+        let in_dir: &Arc<Path> =
+            self.in_dir.as_ref().ok_or(WatchError::InDirNotSet)?;
+
+        let check: Check = self.check.clone().in_dir_shared(Arc::clone(in_dir));
+
+        let mut merge: Merge =
+            self.merge.clone().in_dir_shared(Arc::clone(in_dir));
+
+        if let (Some(total_chunks), Some(file_size)) =
+            (check.total_chunks, check.file_size)
+        {
+            merge = merge.expected_chunks(total_chunks, file_size);
+        }
+
+        let (sender, receiver) = mpsc::channel::<notify::Result<Event>>();
+
+        // the events of the directory, unless the API is not available
+        let mut watcher: Option<RecommendedWatcher> =
+            notify::recommended_watcher(sender).ok();
+
+        let mut watching: bool = false;
+
+        let mut last: Option<DirState> = None;
+
+        let mut scanned: Option<Instant> = None;
+
+        let mut changed: bool = false;
+
+        loop {
+            if self.is_cancelled() {
+                return Err(WatchError::Cancelled);
+            }
+
+            if self.is_expired() {
+                return Err(WatchError::DeadlineExceeded);
+            }
+
+            // the directory may be created after the process started
+            if let (false, Some(watcher)) = (watching, watcher.as_mut()) {
+                watching =
+                    watcher.watch(in_dir, RecursiveMode::NonRecursive).is_ok();
+
+                // the chunks arrived before watching
+                changed |= watching;
+            }
+
+            while let Ok(event) = receiver.try_recv() {
+                changed |= is_change(event);
+            }
+
+            if scanned.is_none_or(|s| s.elapsed() >= self.interval) {
+                let state: Option<DirState> = DirState::scan(in_dir);
+
+                changed |= state.is_some() && state != last;
+
+                last = state;
+
+                scanned = Some(Instant::now());
+            }
+
+            // only check the directory once changed
+            if std::mem::take(&mut changed) {
+                match check.run() {
+                    | Ok(_) => return merge.run().map_err(WatchError::Merge),
+                    // the chunks still arriving
+                    | Err(
+                        CheckError::MissingChunks(_)
+                        | CheckError::ChunkSizeMismatch(_)
+                        | CheckError::SizeMismatch(_),
+                    ) => {},
+                    | Err(error) => return Err(WatchError::Check(error)),
+                }
+            }
+
+            let timeout: Duration = match scanned {
+                | Some(s) => {
+                    self.interval.saturating_sub(s.elapsed()).min(EVENT_TIMEOUT)
+                },
+                | None => EVENT_TIMEOUT,
+            };
+
+            match receiver.recv_timeout(timeout) {
+                | Ok(event) => changed |= is_change(event),
+                | Err(RecvTimeoutError::Timeout) => {},
+                // no watcher, so only the rescans are left
+                | Err(RecvTimeoutError::Disconnected) => thread::sleep(timeout),
+            }
+        }
+    }
+
+    /// Check if the process is cancelled.
+    fn is_cancelled(&self) -> bool {
+        self.cancellation_token.as_ref().is_some_and(|t| t.is_cancelled())
+    }
+
+    /// Check if the deadline of the process is exceeded.
+    fn is_expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+}
+
+impl Default for Watch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check if an event changed the entries of the directory.
+///
+/// The files read, such as the chunks read by the check process,
+/// are not changed.
+fn is_change(event: notify::Result<Event>) -> bool {
+    event.is_ok_and(|event| !event.kind.is_access())
+}
+
+/// State of the entries of a directory, to detect changes between scans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DirState {
+    entries: usize,
+    total_size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl DirState {
+    /// Scan the directory, `None` if it could not be read.
+    fn scan(dir: &Path) -> Option<Self> {
+        let mut state: Self =
+            Self { entries: 0, total_size: 0, modified: None };
+
+        for entry in fs::read_dir(dir).ok()?.filter_map(Result::ok) {
+            let metadata: fs::Metadata = match entry.metadata() {
+                | Ok(metadata) => metadata,
+                | Err(_) => continue,
+            };
+
+            state.entries += 1;
+            state.total_size += metadata.len();
+            state.modified = state.modified.max(metadata.modified().ok());
+        }
+
+        Some(state)
+    }
+}
@@ -1,11 +1,17 @@
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use std::{
+        env,
+        sync::{Arc, Mutex},
+    };
 
     use async_std::{fs, path::PathBuf, stream::StreamExt as _};
 
     use filerune_fusion::{
-        check::{Check, CheckError, async_std::CheckAsyncExt as _},
+        check::{
+            Check, CheckError, CheckReport, async_std::CheckAsyncExt as _,
+        },
+        compare::{Compare, CompareResult, async_std::CompareAsyncExt as _},
         merge::{Merge, async_std::MergeAsyncExt as _},
         split::{Split, SplitResult, async_std::SplitAsyncExt as _},
     };
@@ -33,16 +39,16 @@ mod tests {
             .await
             .unwrap();
 
-        (
-            root.clone(),
-            cache_dir,
-            root.join(".media")
-                .join("output")
-                .join("async_std")
-                .join(cache_name)
-                .join(file_name),
-            split_result,
-        )
+        let output_dir: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("async_std")
+            .join(cache_name);
+
+        // remove the outputs of a previous run
+        let _ = fs::remove_dir_all(&output_dir).await;
+
+        (root.clone(), cache_dir, output_dir.join(file_name), split_result)
     }
 
     #[async_std::test]
@@ -59,6 +65,31 @@ mod tests {
         assert!(chunk_count > 0, "No chunks were created.");
     }
 
+    #[async_std::test]
+    async fn test_split_from_reader() {
+        let root: PathBuf = env::current_dir().unwrap().into();
+        let chunk_size: usize = 1024 * 1024;
+
+        let data: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap();
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("async_std")
+            .join("split_from_reader");
+
+        let split_result: SplitResult = Split::new()
+            .out_dir(&cache_dir)
+            .chunk_size(chunk_size)
+            .run_async_from_reader(data.as_slice())
+            .await
+            .unwrap();
+
+        assert_eq!(split_result.file_size, data.len() as u64);
+        assert_eq!(split_result.total_chunks, data.len().div_ceil(chunk_size));
+    }
+
     #[async_std::test]
     async fn test_check_with_missing_chunks() {
         let (_, cache_dir, _, split_result) =
@@ -82,6 +113,50 @@ mod tests {
         panic!("Check should fail due to missing chunks.");
     }
 
+    #[async_std::test]
+    async fn test_check_with_concurrency() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_with_concurrency").await;
+
+        let result: Result<CheckReport, CheckError> = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks + 3)
+            .concurrency(8)
+            .run_async()
+            .await;
+
+        match result {
+            | Err(CheckError::MissingChunks(err)) => {
+                let total_chunks: usize = split_result.total_chunks;
+
+                assert_eq!(
+                    err.missing,
+                    vec![total_chunks, total_chunks + 1, total_chunks + 2]
+                );
+            },
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[async_std::test]
+    async fn test_compare_matches_chunks() {
+        let (root, cache_dir, _, split_result) =
+            setup("compare_matches_chunks").await;
+
+        let result: CompareResult = Compare::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .in_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert!(result.is_match());
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+    }
+
     #[async_std::test]
     async fn test_check_with_size_error() {
         let (_, cache_dir, _, split_result) =
@@ -136,6 +211,62 @@ mod tests {
         );
     }
 
+    #[async_std::test]
+    async fn test_merge_to_opened_file() {
+        let (root, cache_dir, output_path, _) =
+            setup("merge_to_opened_file").await;
+
+        fs::create_dir_all(output_path.parent().unwrap()).await.unwrap();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .run_async_to_file(fs::File::create(&output_path).await.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
+    #[async_std::test]
+    async fn test_merge_reports_watermarks() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_reports_watermarks").await;
+
+        let watermarks: Arc<Mutex<Vec<u64>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let handler_watermarks: Arc<Mutex<Vec<u64>>> = watermarks.clone();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .progressive(true)
+            .on_watermark(move |watermark| {
+                handler_watermarks.lock().unwrap().push(watermark);
+            })
+            .run_async()
+            .await
+            .unwrap();
+
+        let expected: Vec<u64> = split_result
+            .chunks
+            .iter()
+            .scan(0, |offset, chunk| {
+                *offset += chunk.size as u64;
+
+                Some(*offset)
+            })
+            .collect();
+
+        assert_eq!(*watermarks.lock().unwrap(), expected);
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
     #[async_std::test]
     async fn test_merge_on_empty_cache_dir() {
         let root: PathBuf = env::current_dir().unwrap().into();
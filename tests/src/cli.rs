@@ -0,0 +1,287 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use filerune::{
+        args::parse_size,
+        error::{CliError, EXIT_INPUT, EXIT_MISMATCH, EXIT_OUTPUT, EXIT_USAGE},
+    };
+    use filerune_fusion::hash::HashAlgorithm;
+
+    fn setup(cache_name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf =
+            root.join(".media").join("cache").join("cli").join(cache_name);
+
+        // remove the outputs of a previous run
+        let _ = fs::remove_dir_all(&cache_dir);
+
+        fs::create_dir_all(&cache_dir).unwrap();
+
+        (asset_path, cache_dir.join("chunks"), cache_dir)
+    }
+
+    fn run(args: &[&str]) -> (Result<(), CliError>, String) {
+        let mut out: Vec<u8> = Vec::new();
+
+        let result: Result<(), CliError> = filerune::run(args, &mut out);
+
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_cli_split_check_merge() {
+        let (asset_path, chunk_dir, cache_dir) = setup("split_check_merge");
+
+        let data: Vec<u8> = fs::read(&asset_path).unwrap();
+        let file_size: String = data.len().to_string();
+        let manifest: PathBuf = cache_dir.join("chunks.sha256");
+        let out_file: PathBuf = cache_dir.join("test.jpg");
+
+        let (result, output) = run(&[
+            "split",
+            "--chunk-size",
+            "1M",
+            "--manifest",
+            manifest.to_str().unwrap(),
+            asset_path.to_str().unwrap(),
+            chunk_dir.to_str().unwrap(),
+        ]);
+
+        result.unwrap();
+
+        let total_chunks: usize = data.len().div_ceil(1024 * 1024);
+
+        assert_eq!(
+            output,
+            format!("split {} bytes into {total_chunks} chunks\n", data.len())
+        );
+
+        // the manifest holds a line per chunk, as written by `sha256sum`
+        let lines: Vec<String> = fs::read_to_string(&manifest)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(lines.len(), total_chunks);
+
+        for (index, line) in lines.iter().enumerate() {
+            let chunk: Vec<u8> =
+                fs::read(chunk_dir.join(index.to_string())).unwrap();
+
+            assert_eq!(
+                line,
+                &format!(
+                    "{}  {index}",
+                    HashAlgorithm::Sha256.digest(&chunk).to_hex()
+                )
+            );
+        }
+
+        let (result, output) = run(&[
+            "check",
+            "--file-size",
+            &file_size,
+            "--total-chunks",
+            &total_chunks.to_string(),
+            chunk_dir.to_str().unwrap(),
+        ]);
+
+        result.unwrap();
+
+        assert_eq!(
+            output,
+            format!("found {total_chunks} chunks of {} bytes\n", data.len())
+        );
+
+        let expected_hash: String =
+            HashAlgorithm::Sha256.digest(&data).to_hex();
+
+        let (result, output) = run(&[
+            "merge",
+            &format!("--expected-hash={expected_hash}"),
+            chunk_dir.to_str().unwrap(),
+            out_file.to_str().unwrap(),
+        ]);
+
+        result.unwrap();
+
+        assert_eq!(
+            output,
+            format!("merged {total_chunks} chunks into {} bytes\n", data.len())
+        );
+        assert_eq!(fs::read(&out_file).unwrap(), data);
+
+        // the output file is kept without `--overwrite`
+        let (result, _) = run(&[
+            "merge",
+            chunk_dir.to_str().unwrap(),
+            out_file.to_str().unwrap(),
+        ]);
+
+        assert_eq!(result.unwrap_err().exit_code(), EXIT_OUTPUT);
+    }
+
+    #[test]
+    fn test_cli_check_missing_chunks() {
+        let (asset_path, chunk_dir, _) = setup("check_missing_chunks");
+
+        let file_size: u64 = fs::metadata(&asset_path).unwrap().len();
+
+        run(&[
+            "split",
+            "-s",
+            "1M",
+            asset_path.to_str().unwrap(),
+            chunk_dir.to_str().unwrap(),
+        ])
+        .0
+        .unwrap();
+
+        fs::remove_file(chunk_dir.join("1")).unwrap();
+
+        let (result, output) = run(&[
+            "check",
+            "--file-size",
+            &file_size.to_string(),
+            "-n",
+            &file_size.div_ceil(1024 * 1024).to_string(),
+            "--json",
+            chunk_dir.to_str().unwrap(),
+        ]);
+
+        let error: CliError = result.unwrap_err();
+
+        assert_eq!(error.as_code(), "missing_chunks");
+        assert_eq!(error.exit_code(), EXIT_MISMATCH);
+
+        // the report is printed for the error too
+        let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(report["success"], false);
+        assert_eq!(report["error"]["code"], "missing_chunks");
+    }
+
+    #[test]
+    fn test_cli_merge_hash_mismatch() {
+        let (asset_path, chunk_dir, cache_dir) = setup("merge_hash_mismatch");
+
+        run(&[
+            "split",
+            asset_path.to_str().unwrap(),
+            chunk_dir.to_str().unwrap(),
+        ])
+        .0
+        .unwrap();
+
+        let (result, _) = run(&[
+            "merge",
+            "--hash",
+            "blake3",
+            "--expected-hash",
+            &"00".repeat(32),
+            chunk_dir.to_str().unwrap(),
+            cache_dir.join("test.jpg").to_str().unwrap(),
+        ]);
+
+        let error: CliError = result.unwrap_err();
+
+        assert_eq!(error.as_code(), "hash_mismatch");
+        assert_eq!(error.exit_code(), EXIT_MISMATCH);
+    }
+
+    #[test]
+    fn test_cli_merge_to_stdout() {
+        let (asset_path, chunk_dir, _) = setup("merge_to_stdout");
+
+        run(&[
+            "split",
+            "-s",
+            "1M",
+            asset_path.to_str().unwrap(),
+            chunk_dir.to_str().unwrap(),
+        ])
+        .0
+        .unwrap();
+
+        // the merged output is written alone
+        let mut out: Vec<u8> = Vec::new();
+
+        filerune::run(["merge", chunk_dir.to_str().unwrap(), "-"], &mut out)
+            .unwrap();
+
+        assert_eq!(out, fs::read(&asset_path).unwrap());
+    }
+
+    #[test]
+    fn test_cli_usage() {
+        let (result, output) = run(&["--help"]);
+
+        result.unwrap();
+
+        assert!(output.starts_with("A file handling solution."));
+
+        let (result, output) = run(&["split", "-h"]);
+
+        result.unwrap();
+
+        assert!(output.contains("Usage: filerune split"));
+
+        for (args, code, argument) in [
+            (&[][..], "command_not_set", None),
+            (&["join"][..], "command_unknown", Some("join")),
+            (&["split", "--bogus"][..], "option_unknown", Some("--bogus")),
+            (
+                &["split", "--chunk-size"][..],
+                "option_value_not_set",
+                Some("--chunk-size"),
+            ),
+            (
+                &["split", "--chunk-size", "2X", "in", "out"][..],
+                "option_value_invalid",
+                Some("--chunk-size"),
+            ),
+            (
+                &["merge", "--overwrite=yes", "in", "out"][..],
+                "option_value_unexpected",
+                Some("--overwrite"),
+            ),
+            (&["split", "in"][..], "argument_not_set", Some("<OUT_DIR>")),
+            (&["check", "in", "out"][..], "argument_unexpected", Some("out")),
+        ] {
+            let error: CliError = run(args).0.unwrap_err();
+
+            assert_eq!(error.as_code(), code);
+            assert_eq!(error.argument(), argument);
+            assert_eq!(error.exit_code(), EXIT_USAGE);
+        }
+
+        // the arguments after `--` are positional
+        let error: CliError =
+            run(&["split", "--", "--in", ".media/cache/cli/out"])
+                .0
+                .unwrap_err();
+
+        assert_eq!(error.as_code(), "in_file_not_found");
+        assert_eq!(error.exit_code(), EXIT_INPUT);
+    }
+
+    #[test]
+    fn test_cli_parse_size() {
+        assert_eq!(parse_size("0"), Some(0));
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("16b"), Some(16));
+        assert_eq!(parse_size("4k"), Some(4 * 1024));
+        assert_eq!(parse_size("4KB"), Some(4 * 1024));
+        assert_eq!(parse_size("2MiB"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_size("1T"), Some(1 << 40));
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("M"), None);
+        assert_eq!(parse_size("1.5M"), None);
+        assert_eq!(parse_size("-1"), None);
+        assert_eq!(parse_size("99999999999T"), None);
+    }
+}
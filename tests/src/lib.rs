@@ -1,18 +1,90 @@
 pub mod async_std;
 
+pub mod cli;
+
 pub mod smol;
 
 pub mod tokio;
 
+#[cfg(target_os = "linux")]
+pub mod tokio_uring;
+
 #[cfg(test)]
 mod tests {
-    use std::{env, fs, path::PathBuf};
+    use std::{
+        env, fs,
+        io::{
+            self, BufRead as _, BufReader, Read as _, Seek as _, SeekFrom,
+            Write as _,
+        },
+        net::{SocketAddr, TcpListener, TcpStream},
+        path::{Path, PathBuf},
+        sync::{
+            Arc, Mutex, MutexGuard,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::{Duration, Instant, SystemTime},
+    };
 
+    use camino::{Utf8Path, Utf8PathBuf};
     use filerune_fusion::{
-        check::{Check, CheckError},
-        merge::Merge,
-        split::{Split, SplitResult},
+        bagit::{Bag, BagEntry, BagError, BagManifest},
+        bao::{self, BaoError, BaoVerifier},
+        bitmap::ChunkBitmap,
+        cancel::CancellationToken,
+        check::{Check, CheckError, CheckReport, ChunkStatus},
+        chunk::{ChunkInfo, ChunkNaming, ForeignNaming},
+        codec::Codec,
+        compare::{Compare, CompareResult},
+        db::{ChunkRecord, ManifestRecord, RecordError, SplitRecord},
+        exchange::{Exchange, ExchangeError, Message},
+        fountain::{
+            FountainEncoder, ObjectTransmissionInformation, packet_from_bytes,
+        },
+        gc::{Gc, GcError, GcReason, GcReport},
+        hash::{Digest, HashAlgorithm},
+        header::{ChunkFormat, ChunkHeader, HEADER_LEN},
+        layout::{chunk_count, chunk_range, index_for_offset},
+        lock::{FileLock, LockError, LockMode},
+        merge::{
+            DuplicatePolicy, Merge, MergeError, MergeResult, OutFilePolicy,
+        },
+        multipart::MultipartBody,
+        nextcloud::{
+            NEXTCLOUD_CHUNK_SIZE_MIN, NextcloudError, NextcloudResult,
+            NextcloudUpload, WebDavRequest,
+        },
+        pipeline::{Pipeline, PipelineError, PipelineResult},
+        preset::Preset,
+        progress::{Phase, Progress, ProgressSnapshot},
+        reader::ChunksReader,
+        recover::{Recover, RecoverReport, recover},
+        reflink::ReflinkPolicy,
+        s3::{CompletedPart, S3Error, S3MultipartUpload, UploadState},
+        seal::{
+            SEAL_NONCE_LEN, Seal, SealAudit, SealError, SealKeyMode,
+            SealResult, Unseal, UnsealError, UnsealResult,
+        },
+        sink::SplitSink,
+        source::{SourcePool, SourceStats},
+        split::{
+            OverwritePolicy, RepairResult, SourceChangePolicy, Split,
+            SplitError, SplitResult,
+        },
+        stats::{ChunkStats, StatsError, stats},
+        store::{
+            BucketStore, ChunkStore, ChunkUrl, DirStore, HttpSink, HttpStore,
+            MemoryStore, StoreError,
+        },
+        strategy::ChunkStrategy,
+        sync::SyncPolicy,
+        tus::{TusError, TusResult, TusUpload},
+        upload::{UploadError, UploadSession},
+        warning::Warning,
+        watch::{Watch, WatchError},
     };
+    use object_store::memory::InMemory;
 
     fn setup(cache_name: &str) -> (PathBuf, PathBuf, PathBuf, SplitResult) {
         let root: PathBuf = env::current_dir().unwrap();
@@ -31,16 +103,13 @@ mod tests {
             .run()
             .unwrap();
 
-        (
-            root.clone(),
-            cache_dir,
-            root.join(".media")
-                .join("output")
-                .join("std")
-                .join(cache_name)
-                .join(file_name),
-            split_result,
-        )
+        let output_dir: PathBuf =
+            root.join(".media").join("output").join("std").join(cache_name);
+
+        // remove the outputs of a previous run
+        let _ = fs::remove_dir_all(&output_dir);
+
+        (root.clone(), cache_dir, output_dir.join(file_name), split_result)
     }
 
     #[tokio::test]
@@ -54,69 +123,6329 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_check_with_missing_chunks() {
-        let (_, cache_dir, _, split_result) =
-            setup("check_with_missing_chunks");
+    async fn test_split_cancelled() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_cancelled");
 
-        if let Err(error) = Check::new()
+        let token: CancellationToken = CancellationToken::new();
+
+        token.cancel();
+
+        let result: Result<SplitResult, SplitError> = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&cache_dir)
+            .cancellation_token(token)
+            .run();
+
+        assert_eq!(result.unwrap_err(), SplitError::Cancelled);
+
+        assert_eq!(
+            fs::read_dir(&cache_dir).unwrap().count(),
+            0,
+            "Cancelled split should leave no chunks."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_with_threads() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_with_threads");
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("split_with_threads")
+            .join("test.jpg");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(64 * 1024)
+            .threads(4)
+            .run()
+            .unwrap();
+
+        Check::new()
             .in_dir(&cache_dir)
             .file_size(split_result.file_size)
-            .total_chunks(split_result.total_chunks + 1)
+            .total_chunks(split_result.total_chunks)
+            .run()
+            .unwrap();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
             .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_outputs_match_whatever_threads() {
+        let (root, _, output_path, _) =
+            setup("split_outputs_match_whatever_threads");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        // the files written and their content, by name
+        let read_dir = |dir: &Path| -> Vec<(PathBuf, Vec<u8>)> {
+            let mut files: Vec<(PathBuf, Vec<u8>)> = fs::read_dir(dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| path.is_file())
+                .map(|path| {
+                    (
+                        path.strip_prefix(dir).unwrap().to_path_buf(),
+                        fs::read(&path).unwrap(),
+                    )
+                })
+                .collect();
+
+            files.sort();
+
+            files
+        };
+
+        let mut outputs = Vec::new();
+
+        for threads in [1, 3, 8] {
+            let bag_dir: PathBuf =
+                output_path.with_file_name(format!("bag_{threads}"));
+
+            let split_result: SplitResult = Split::new()
+                .in_file(&asset_path)
+                .out_dir(bag_dir.join("data"))
+                .chunk_size(256 * 1024)
+                .compression(Codec::Zstd { level: 3 })
+                .parity_chunks(2)
+                .par2_recovery(2)
+                .merge_script(true)
+                .threads(threads)
+                .run()
+                .unwrap();
+
+            let check_report: CheckReport = Check::new()
+                .in_dir(bag_dir.join("data"))
+                .file_size(split_result.file_size)
+                .total_chunks(split_result.total_chunks)
+                .compression(Codec::Zstd { level: 3 })
+                .hash_algorithm(HashAlgorithm::Sha256)
+                .run()
+                .unwrap();
+
+            let manifest: BagManifest =
+                Bag::new().bag_dir(&bag_dir).write().unwrap();
+
+            outputs.push((
+                (
+                    split_result.file_size,
+                    split_result.total_chunks,
+                    split_result.chunk_size,
+                    split_result
+                        .chunks
+                        .iter()
+                        .map(|c| (c.index, c.size))
+                        .collect::<Vec<(usize, usize)>>(),
+                ),
+                check_report.aggregate_hash,
+                manifest,
+                read_dir(&bag_dir),
+                read_dir(&bag_dir.join("data")),
+            ));
+        }
+
+        for output in outputs[1..].iter() {
+            assert_eq!(*output, outputs[0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_result_lists_chunks() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_result_lists_chunks");
+
+        let split: Split = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024);
+
+        for split_result in
+            [split.run().unwrap(), split.clone().threads(4).run().unwrap()]
         {
-            match error {
-                | CheckError::MissingChunks(_) => {
-                    return;
-                },
-                | err => panic!("Unexpected error: {:?}", err),
+            assert_eq!(split_result.chunk_size, 1024 * 1024);
+            assert_eq!(split_result.chunks.len(), split_result.total_chunks);
+
+            for (i, chunk) in split_result.chunks.iter().enumerate() {
+                assert_eq!(chunk.index, i);
+                assert_eq!(chunk.path, cache_dir.join(i.to_string()));
+                assert_eq!(
+                    chunk.size as u64,
+                    fs::metadata(&chunk.path).unwrap().len()
+                );
             }
-        };
 
-        panic!("Check should fail due to missing chunks.");
+            assert_eq!(
+                split_result
+                    .chunks
+                    .iter()
+                    .map(|chunk| chunk.size as u64)
+                    .sum::<u64>(),
+                split_result.file_size
+            );
+        }
     }
 
     #[tokio::test]
-    async fn test_check_with_size_error() {
-        let (_, cache_dir, _, split_result) = setup("check_with_size_error");
+    async fn test_split_chunks_match_input() {
+        let (root, _, output_path, _) = setup("split_chunks_match_input");
 
-        if let Err(error) = Check::new()
-            .in_dir(&cache_dir)
-            .file_size(split_result.file_size + 1)
-            .total_chunks(split_result.total_chunks)
-            .run()
+        let input: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let split: Split = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .chunk_size(1000 * 1000);
+
+        for split_result in
+            [split.run().unwrap(), split.clone().threads(4).run().unwrap()]
         {
-            match error {
-                | CheckError::SizeMismatch(_) => {
-                    return;
-                },
-                | err => panic!("Unexpected error: {:?}", err),
+            let mut offset: usize = 0;
+
+            for chunk in split_result.chunks.iter() {
+                assert_eq!(
+                    fs::read(&chunk.path).unwrap(),
+                    input[offset..offset + chunk.size]
+                );
+
+                offset += chunk.size;
             }
-        };
 
-        panic!("Check should fail due to size mismatch.");
+            assert_eq!(offset, input.len());
+        }
     }
 
     #[tokio::test]
-    async fn test_successful_check() {
-        let (_, cache_dir, _, split_result) = setup("successful_check");
+    async fn test_split_by_total_chunks() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let file_size: usize =
+            fs::metadata(&asset_path).unwrap().len() as usize;
+
+        for threads in [1, 3] {
+            let cache_dir: PathBuf = root
+                .join(".media")
+                .join("cache")
+                .join("std")
+                .join(format!("split_by_total_chunks_{threads}"));
+            let output_path: PathBuf = root
+                .join(".media")
+                .join("output")
+                .join("std")
+                .join(format!("split_by_total_chunks_{threads}"))
+                .join("test.jpg");
+
+            let split_result: SplitResult = Split::new()
+                .in_file(&asset_path)
+                .out_dir(&cache_dir)
+                .total_chunks(7)
+                .threads(threads)
+                .run()
+                .unwrap();
+
+            assert_eq!(split_result.total_chunks, 7);
+            assert_eq!(split_result.chunk_size, file_size / 7);
+            assert_eq!(
+                split_result.chunks.last().unwrap().size,
+                file_size - file_size / 7 * 6
+            );
+
+            Merge::new()
+                .in_dir(&cache_dir)
+                .out_file(&output_path)
+                .overwrite(OutFilePolicy::Overwrite)
+                .run()
+                .unwrap();
+
+            assert_eq!(
+                fs::read(&output_path).unwrap(),
+                fs::read(&asset_path).unwrap()
+            );
+        }
+
+        assert_eq!(
+            Split::new()
+                .in_file(&asset_path)
+                .out_dir(
+                    root.join(".media")
+                        .join("cache")
+                        .join("std")
+                        .join("split_by_total_chunks_0")
+                )
+                .total_chunks(0)
+                .run()
+                .unwrap_err(),
+            SplitError::TotalChunksInvalid
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_with_aligned_chunks() {
+        let (root, _, output_path, split_result) =
+            setup("split_with_aligned_chunks");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let aligned_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunks_dir)
+            .chunk_size(1000 * 1000)
+            .align(4096)
+            .run()
+            .unwrap();
+
+        assert_eq!(aligned_result.chunk_size, 244 * 4096);
+        assert_eq!(aligned_result.file_size, split_result.file_size);
+
+        for chunk in &aligned_result.chunks[..aligned_result.total_chunks - 1] {
+            assert_eq!(chunk.size, 244 * 4096);
+        }
+
+        let total_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunks_dir)
+            .total_chunks(7)
+            .align(4096)
+            .overwrite(OverwritePolicy::Clean)
+            .run()
+            .unwrap();
+
+        assert_eq!(total_result.total_chunks, 7);
+        assert_eq!(total_result.chunk_size % 4096, 0);
+
+        Merge::new().in_dir(&chunks_dir).out_file(&output_path).run().unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_chunk_schedule() {
+        let (root, _, output_path, split_result) =
+            setup("split_check_and_merge_with_chunk_schedule");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        for threads in [1, 3] {
+            let chunks_dir: PathBuf =
+                output_path.with_file_name(format!("chunks_{threads}"));
+
+            let schedule_result: SplitResult = Split::new()
+                .in_file(&asset_path)
+                .out_dir(&chunks_dir)
+                .chunk_size(1024 * 1024)
+                .chunk_schedule([1000, 500 * 1000])
+                .threads(threads)
+                .run()
+                .unwrap();
+
+            let sizes: Vec<usize> =
+                schedule_result.chunks.iter().map(|c| c.size).collect();
+
+            assert_eq!(sizes[..3], [1000, 500 * 1000, 1024 * 1024]);
+            assert_eq!(sizes.len(), split_result.total_chunks + 2);
+            assert_eq!(
+                sizes.iter().sum::<usize>() as u64,
+                split_result.file_size
+            );
+
+            Check::new()
+                .in_dir(&chunks_dir)
+                .file_size(schedule_result.file_size)
+                .total_chunks(schedule_result.total_chunks)
+                .chunk_sizes(sizes)
+                .run()
+                .unwrap();
+
+            Merge::new()
+                .in_dir(&chunks_dir)
+                .out_file(&output_path)
+                .overwrite(OutFilePolicy::Overwrite)
+                .run()
+                .unwrap();
+
+            assert_eq!(
+                fs::read(&output_path).unwrap(),
+                fs::read(&asset_path).unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_content_defined_chunks() {
+        let (root, _, output_path, _) =
+            setup("split_check_and_merge_with_content_defined_chunks");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let strategy: ChunkStrategy = ChunkStrategy::ContentDefined {
+            min: 64 * 1024,
+            avg: 256 * 1024,
+            max: 1024 * 1024,
+        };
+
+        let split = |chunks_dir: &Path, threads: usize| -> SplitResult {
+            Split::new()
+                .in_file(&asset_path)
+                .out_dir(chunks_dir)
+                .strategy(strategy)
+                .overwrite(OverwritePolicy::Clean)
+                .threads(threads)
+                .run()
+                .unwrap()
+        };
+
+        let split_result: SplitResult =
+            split(&output_path.with_file_name("chunks_1"), 1);
+
+        let sizes: Vec<usize> =
+            split_result.chunks.iter().map(|c| c.size).collect();
+
+        assert!(sizes.len() > 1);
+        assert!(
+            sizes[..sizes.len() - 1]
+                .iter()
+                .all(|size| (64 * 1024..=1024 * 1024).contains(size))
+        );
+        assert_eq!(sizes.iter().sum::<usize>() as u64, split_result.file_size);
+
+        // the same boundaries from the path and from a stream
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks_3");
+
+        let threads_result: SplitResult = split(&chunks_dir, 3);
+
+        let stream_result: SplitResult = Split::new()
+            .out_dir(output_path.with_file_name("chunks_stream"))
+            .strategy(strategy)
+            .run_from_file(fs::File::open(&asset_path).unwrap())
+            .unwrap();
+
+        for result in [&threads_result, &stream_result] {
+            assert_eq!(
+                result.chunks.iter().map(|c| c.size).collect::<Vec<usize>>(),
+                sizes
+            );
+        }
 
         Check::new()
-            .in_dir(&cache_dir)
+            .in_dir(&chunks_dir)
             .file_size(split_result.file_size)
             .total_chunks(split_result.total_chunks)
+            .chunk_sizes(sizes.clone())
+            .run()
+            .unwrap();
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        // the boundaries after an insertion are kept
+        let shifted_path: PathBuf = output_path.with_file_name("shifted.jpg");
+
+        let mut shifted: Vec<u8> = b"inserted".to_vec();
+
+        shifted.extend(fs::read(&asset_path).unwrap());
+
+        fs::write(&shifted_path, &shifted).unwrap();
+
+        let shifted_result: SplitResult = Split::new()
+            .in_file(&shifted_path)
+            .out_dir(output_path.with_file_name("chunks_shifted"))
+            .strategy(strategy)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            shifted_result.chunks.last().map(|c| c.size),
+            split_result.chunks.last().map(|c| c.size)
+        );
+
+        match Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunks_dir)
+            .strategy(ChunkStrategy::ContentDefined { min: 2, avg: 1, max: 4 })
+            .run()
+        {
+            | Err(SplitError::ChunkStrategyInvalid) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_with_preset() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_and_merge_with_preset");
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("split_and_merge_with_preset")
+            .join("test.jpg");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .preset(Preset::Custom { chunk_size: 1024 * 1024 })
+            .run()
+            .unwrap();
+
+        assert_eq!(split_result.chunks[0].path, cache_dir.join("test.jpg.001"));
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .detect_naming(true)
             .run()
             .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
     }
 
+    #[cfg(unix)]
     #[tokio::test]
-    async fn test_merge_creates_output_file() {
-        let (_, cache_dir, output_path, _) = setup("merge_creates_output_file");
+    async fn test_split_with_merge_script() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_with_merge_script");
 
-        Merge::new().in_dir(&cache_dir).out_file(&output_path).run().unwrap();
+        let _ = fs::remove_file(cache_dir.join("test.jpg"));
 
-        assert!(
-            output_path.exists(),
-            "Output file should be created after merging."
+        Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .chunk_prefix("it's part ")
+            .merge_script(true)
+            .run()
+            .unwrap();
+
+        assert!(cache_dir.join("merge.bat").is_file());
+
+        let status: std::process::ExitStatus = std::process::Command::new("sh")
+            .arg(cache_dir.join("merge.sh"))
+            .status()
+            .unwrap();
+
+        assert!(status.success());
+
+        assert_eq!(
+            fs::read(cache_dir.join("test.jpg")).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_resumes_written_chunks() {
+        let (root, cache_dir, _, split_result) =
+            setup("split_resumes_written_chunks");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        for threads in [1, 4] {
+            // a missing chunk and a short chunk
+            fs::remove_file(cache_dir.join("3")).unwrap();
+
+            fs::OpenOptions::new()
+                .write(true)
+                .open(cache_dir.join("5"))
+                .unwrap()
+                .set_len(10)
+                .unwrap();
+
+            let result: SplitResult = Split::new()
+                .in_file(&asset_path)
+                .out_dir(&cache_dir)
+                .chunk_size(1024 * 1024)
+                .threads(threads)
+                .resume(true)
+                .run()
+                .unwrap();
+
+            assert_eq!(result.chunks_resumed, split_result.total_chunks - 2);
+            assert_eq!(result.file_size, split_result.file_size);
+            assert_eq!(result.chunks, split_result.chunks);
+
+            let compare_result: CompareResult = Compare::new()
+                .in_file(&asset_path)
+                .in_dir(&cache_dir)
+                .chunk_size(1024 * 1024)
+                .run()
+                .unwrap();
+
+            assert!(compare_result.is_match());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_emits_first_chunk() {
+        let (root, _, output_path, _) = setup("split_emits_first_chunk");
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let first_chunks: Arc<Mutex<Vec<(usize, u64)>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_chunks: Arc<Mutex<Vec<(usize, u64)>>> =
+            first_chunks.clone();
+
+        let split_result: SplitResult = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .on_first_chunk(move |chunk| {
+                // written once emitted
+                let len: u64 = fs::metadata(&chunk.path).unwrap().len();
+
+                handler_chunks.lock().unwrap().push((chunk.index, len));
+            })
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            *first_chunks.lock().unwrap(),
+            [(0, split_result.chunks[0].size as u64)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_source_changed_during_split() {
+        let (root, _, output_path, split_result) =
+            setup("split_source_changed_during_split");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        fs::create_dir_all(output_path.parent().unwrap()).unwrap();
+
+        // the input file grows once its first chunk is written
+        let split = |appends: usize| -> Split {
+            fs::copy(&asset_path, &output_path).unwrap();
+
+            let appended: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+
+            let in_file: PathBuf = output_path.clone();
+
+            Split::new()
+                .in_file(&output_path)
+                .out_dir(&chunks_dir)
+                .chunk_size(1024 * 1024)
+                .overwrite(OverwritePolicy::Clean)
+                .on_first_chunk(move |_| {
+                    if appended.fetch_add(1, Ordering::Relaxed) < appends {
+                        fs::OpenOptions::new()
+                            .append(true)
+                            .open(&in_file)
+                            .unwrap()
+                            .write_all(b"appended")
+                            .unwrap();
+                    }
+                })
+        };
+
+        match split(1).run() {
+            | Err(SplitError::SourceChangedDuringSplit) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // the chunks split from the changed file are removed
+        assert_eq!(fs::read_dir(&chunks_dir).unwrap().count(), 0);
+
+        // split again once changed
+        let result: SplitResult =
+            split(1).source_change(SourceChangePolicy::Retry(1)).run().unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size + 8);
+
+        match split(2).source_change(SourceChangePolicy::Retry(1)).run() {
+            | Err(SplitError::SourceChangedDuringSplit) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // the chunks of the content read are kept
+        let warnings: Arc<Mutex<Vec<String>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_warnings: Arc<Mutex<Vec<String>>> = warnings.clone();
+
+        let result: SplitResult = split(1)
+            .source_change(SourceChangePolicy::Warn)
+            .on_warning(move |w| {
+                handler_warnings.lock().unwrap().push(w.to_code())
+            })
+            .run()
+            .unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(*warnings.lock().unwrap(), ["source_changed"]);
+
+        // a changed file is never removed
+        match split(1)
+            .source_change(SourceChangePolicy::Warn)
+            .remove_source(true)
+            .run()
+        {
+            | Err(SplitError::SourceChangedDuringSplit) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert!(output_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_split_repair_into() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("split_repair_into");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        // a missing, a truncated and an altered chunk
+        fs::remove_file(cache_dir.join("2")).unwrap();
+
+        let data: Vec<u8> = fs::read(cache_dir.join("5")).unwrap();
+
+        fs::write(cache_dir.join("5"), &data[..1024]).unwrap();
+
+        let mut data: Vec<u8> = fs::read(cache_dir.join("6")).unwrap();
+
+        data[0] = !data[0];
+
+        fs::write(cache_dir.join("6"), &data).unwrap();
+
+        let modified: SystemTime =
+            fs::metadata(cache_dir.join("0")).unwrap().modified().unwrap();
+
+        let split: Split =
+            Split::new().in_file(&asset_path).chunk_size(1024 * 1024);
+
+        let result: RepairResult = split.repair_into(&cache_dir).unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+        assert_eq!(
+            result.repaired.iter().map(|c| c.index).collect::<Vec<usize>>(),
+            vec![2, 5, 6]
+        );
+        assert_eq!(
+            result.repaired,
+            vec![
+                split_result.chunks[2].clone(),
+                split_result.chunks[5].clone(),
+                split_result.chunks[6].clone(),
+            ]
+        );
+
+        // the intact chunks are left untouched
+        assert_eq!(
+            fs::metadata(cache_dir.join("0")).unwrap().modified().unwrap(),
+            modified
+        );
+
+        Merge::new().in_dir(&cache_dir).out_file(&output_path).run().unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        // nothing left to repair
+        assert!(split.repair_into(&cache_dir).unwrap().repaired.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_split_verify_writes() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("split_verify_writes");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let split = |format: ChunkFormat, reflink: ReflinkPolicy| {
+            Split::new()
+                .in_file(&asset_path)
+                .out_dir(&cache_dir)
+                .chunk_size(1024 * 1024)
+                .overwrite(OverwritePolicy::Clean)
+                .format(format)
+                .reflink(reflink)
+                .atomic(true)
+                .verify_writes(true)
+        };
+
+        // written from the buffer
+        let result: SplitResult =
+            split(ChunkFormat::Header, ReflinkPolicy::Never).run().unwrap();
+
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+
+        // copied in the kernel or cloned
+        let result: SplitResult =
+            split(ChunkFormat::Raw, ReflinkPolicy::Auto).run().unwrap();
+
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, split_result.file_size);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_in_memory() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        for format in [ChunkFormat::Raw, ChunkFormat::Header] {
+            let mut store: MemoryStore = MemoryStore::new();
+
+            let split_result: SplitResult = Split::new()
+                .chunk_size(1024 * 1024)
+                .format(format)
+                .run_to_store(&asset, &mut store)
+                .unwrap();
+
+            assert_eq!(split_result.file_size, asset.len() as u64);
+            assert_eq!(split_result.total_chunks, 8);
+            assert_eq!(store.len(), 8);
+            assert_eq!(split_result.chunks[7].path, PathBuf::new());
+
+            let merge: Merge = Merge::new().format(format);
+
+            let mut output: Vec<u8> = Vec::new();
+
+            let result: MergeResult =
+                merge.run_from_store_to_sink(&store, 8, &mut output).unwrap();
+
+            assert_eq!(result.bytes_written, asset.len() as u64);
+            assert_eq!(result.out_file, None);
+            assert_eq!(output, asset);
+
+            // a missing chunk
+            store.remove(3).unwrap();
+
+            match merge.run_from_store_to_sink(&store, 8, &mut Vec::new()) {
+                | Err(MergeError::InFileNotRead) => {},
+                | result => panic!("Unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_bucket() {
+        let (root, _, output_path, _) =
+            setup("split_check_and_merge_with_bucket");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let mut store: BucketStore = BucketStore::new(
+            Arc::new(InMemory::new()),
+            "uploads/test",
+            ChunkNaming::new(),
+            tokio::runtime::Handle::current(),
+        );
+
+        // the requests block, so the store is used from a blocking task
+        tokio::task::spawn_blocking(move || {
+            let split_result: SplitResult = Split::new()
+                .in_file(&asset_path)
+                .chunk_size(1024 * 1024)
+                .run_file_to_store(&mut store)
+                .unwrap();
+
+            assert_eq!(split_result.total_chunks, 8);
+            assert!(store.contains(7).unwrap());
+            assert_eq!(store.location(7).as_ref(), "uploads/test/7");
+
+            let check: Check = Check::new()
+                .file_size(split_result.file_size)
+                .total_chunks(split_result.total_chunks)
+                .hash_algorithm(HashAlgorithm::Blake3);
+
+            let report: CheckReport = check.run_from_store(&store).unwrap();
+
+            assert_eq!(
+                report.chunks[1].hash,
+                Some(HashAlgorithm::Blake3.digest(
+                    &fs::read(&asset_path).unwrap()
+                        [1024 * 1024..2 * 1024 * 1024]
+                ))
+            );
+
+            Merge::new()
+                .out_file(&output_path)
+                .run_from_store(&store, split_result.total_chunks)
+                .unwrap();
+
+            assert_eq!(
+                fs::read(&output_path).unwrap(),
+                fs::read(&asset_path).unwrap()
+            );
+
+            // a chunk missing in the bucket
+            assert_eq!(store.read(8).unwrap_err(), StoreError::ChunkNotFound);
+
+            match check.total_chunks(9).run_from_store(&store) {
+                | Err(CheckError::MissingChunks(err)) => {
+                    assert_eq!(err.missing, vec![8]);
+                },
+                | result => panic!("Unexpected result: {:?}", result),
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_split_overwrite_policy() {
+        let (root, cache_dir, _, split_result) =
+            setup("split_overwrite_policy");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let split: Split = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024);
+
+        match split.clone().overwrite(OverwritePolicy::Fail).run() {
+            | Err(SplitError::OutDirNotEmpty) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        let stray_path: PathBuf = cache_dir.join("stray");
+
+        fs::create_dir_all(stray_path.join("nested")).unwrap();
+
+        let result: SplitResult =
+            split.overwrite(OverwritePolicy::Clean).run().unwrap();
+
+        assert!(!stray_path.exists());
+        assert_eq!(result.chunks, split_result.chunks);
+        assert_eq!(
+            fs::read_dir(&cache_dir).unwrap().count(),
+            split_result.total_chunks
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_removes_source() {
+        let (root, _, output_path, _) = setup("split_removes_source");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        fs::create_dir_all(output_path.parent().unwrap()).unwrap();
+        fs::copy(&asset_path, &output_path).unwrap();
+
+        let split: Split = Split::new()
+            .in_file(&output_path)
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .remove_source(true);
+
+        split.clone().threads(4).run().unwrap();
+
+        assert!(!output_path.exists());
+
+        // resuming the written chunks removes the source as well
+        fs::copy(&asset_path, &output_path).unwrap();
+
+        let result: SplitResult = split.resume(true).run().unwrap();
+
+        assert_eq!(result.chunks_resumed, result.total_chunks);
+        assert!(!output_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_atomically() {
+        let (root, _, output_path, split_result) =
+            setup("split_and_merge_atomically");
+
+        let chunk_size: usize = 1024 * 1024;
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+        let temp_file: PathBuf = output_path.with_file_name("test.jpg.tmp");
+
+        let result: SplitResult = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .chunk_size(chunk_size)
+            .threads(4)
+            .atomic(true)
+            .run()
+            .unwrap();
+
+        for chunk in result.chunks.iter() {
+            assert_eq!(chunk.path, chunks_dir.join(chunk.index.to_string()));
+            assert_eq!(chunk.size, split_result.chunks[chunk.index].size);
+        }
+
+        // no temporary file is left
+        assert_eq!(
+            fs::read_dir(&chunks_dir).unwrap().count(),
+            split_result.total_chunks
+        );
+
+        // interrupted in the middle of the second chunk
+        fs::write(&temp_file, &asset[..chunk_size * 3 / 2]).unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .resume(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_resumed, chunk_size as u64);
+        assert!(!temp_file.exists());
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_with_sync() {
+        let (root, _, output_path, split_result) =
+            setup("split_and_merge_with_sync");
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let result: SplitResult = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .atomic(true)
+            .sync(SyncPolicy::All)
+            .run()
+            .unwrap();
+
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .sync(SyncPolicy::Data)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_with_reflink() {
+        let (root, _, output_path, _) = setup("split_and_merge_with_reflink");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunks_dir)
+            .reflink(ReflinkPolicy::Auto)
+            .run()
+            .unwrap();
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .reflink(ReflinkPolicy::Auto)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        // only supported by some file systems
+        match Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .reflink(ReflinkPolicy::Always)
+            .run()
+        {
+            | Ok(_) => assert_eq!(
+                fs::read(&output_path).unwrap(),
+                fs::read(&asset_path).unwrap()
+            ),
+            | Err(error) => assert_eq!(error, MergeError::OutFileNotCloned),
+        }
+    }
+
+    /// Serve the content over HTTP with `Range` support,
+    /// counting the bytes of the bodies sent.
+    fn serve_http(content: Vec<u8>) -> (String, Arc<AtomicUsize>) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let url: String =
+            format!("http://{}/test.jpg", listener.local_addr().unwrap());
+
+        let sent: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+
+        let counter: Arc<AtomicUsize> = Arc::clone(&sent);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream: TcpStream = stream.unwrap();
+
+                let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+
+                let mut request: String = String::new();
+                let mut start: usize = 0;
+
+                loop {
+                    let mut line: String = String::new();
+
+                    reader.read_line(&mut line).unwrap();
+
+                    if let Some(range) = line
+                        .strip_prefix("range: bytes=")
+                        .and_then(|r| r.trim().strip_suffix('-'))
+                    {
+                        start = range.parse().unwrap();
+                    }
+
+                    if line.trim().is_empty() {
+                        break;
+                    }
+
+                    request.push_str(&line);
+                }
+
+                let body: &[u8] = &content[start..];
+
+                let status: &str = match start {
+                    | 0 => "200 OK",
+                    | _ => "206 Partial Content",
+                };
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+
+                if request.starts_with("GET") {
+                    stream.write_all(body).unwrap();
+
+                    counter.fetch_add(body.len(), Ordering::SeqCst);
+                }
+            }
+        });
+
+        (url, sent)
+    }
+
+    #[test]
+    fn test_split_from_url() {
+        let (root, _, output_path, split_result) = setup("split_from_url");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let (url, sent) = serve_http(asset.clone());
+
+        let split: Split = Split::new()
+            .in_url(&url)
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024);
+
+        let result: SplitResult = split.run().unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(sent.load(Ordering::SeqCst), asset.len());
+
+        // only the missing chunks at the end are downloaded again
+        for i in 5..result.total_chunks {
+            fs::remove_file(chunks_dir.join(i.to_string())).unwrap();
+        }
+
+        let result: SplitResult = split.resume(true).run().unwrap();
+
+        assert_eq!(result.chunks_resumed, 5);
+        assert_eq!(
+            sent.load(Ordering::SeqCst),
+            asset.len() + asset.len() - 5 * 1024 * 1024
+        );
+
+        Merge::new().in_dir(&chunks_dir).out_file(&output_path).run().unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+    }
+
+    #[test]
+    fn test_split_from_url_refreshes_expired_url() {
+        let (root, _, output_path, _) = setup("split_from_url_refreshes");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let addr: SocketAddr = listener.local_addr().unwrap();
+
+        // the token accepted by the server
+        let valid: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(1));
+
+        let token: Arc<AtomicUsize> = Arc::clone(&valid);
+
+        let content: Vec<u8> = asset.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream: TcpStream = stream.unwrap();
+
+                let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+
+                let mut requested: usize = 0;
+                let mut start: usize = 0;
+
+                loop {
+                    let mut line: String = String::new();
+
+                    reader.read_line(&mut line).unwrap();
+
+                    if let Some(t) = line
+                        .strip_prefix("GET /test.jpg?token=")
+                        .and_then(|l| l.split(' ').next())
+                    {
+                        requested = t.parse().unwrap();
+                    }
+
+                    if let Some(range) = line
+                        .strip_prefix("range: bytes=")
+                        .and_then(|r| r.trim().strip_suffix('-'))
+                    {
+                        start = range.parse().unwrap();
+                    }
+
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+
+                if requested != token.load(Ordering::SeqCst) {
+                    stream
+                        .write_all(
+                            b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        )
+                        .unwrap();
+
+                    continue;
+                }
+
+                let body: &[u8] = &content[start..];
+
+                let status: &str = match start {
+                    | 0 => "200 OK",
+                    | _ => "206 Partial Content",
+                };
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+
+                // the first token expires midway
+                match requested {
+                    | 1 => {
+                        stream.write_all(&body[..3 * 1024 * 1024]).unwrap();
+
+                        token.store(2, Ordering::SeqCst);
+                    },
+                    | _ => stream.write_all(body).unwrap(),
+                }
+            }
+        });
+
+        let refreshed: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+
+        let counter: Arc<AtomicUsize> = Arc::clone(&refreshed);
+
+        let result: SplitResult = Split::new()
+            .in_url(format!("http://{addr}/test.jpg?token=1"))
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .on_url_expired(move |url| {
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                Some(url.replace("token=1", "token=2"))
+            })
+            .run()
+            .unwrap();
+
+        assert_eq!(refreshed.load(Ordering::SeqCst), 1);
+        assert_eq!(result.file_size, asset.len() as u64);
+
+        Merge::new().in_dir(&chunks_dir).out_file(&output_path).run().unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        // the expired URL is not refreshed
+        let split: Split = Split::new()
+            .in_url(format!("http://{addr}/test.jpg?token=1"))
+            .out_dir(&chunks_dir)
+            .overwrite(OverwritePolicy::Clean);
+
+        assert_eq!(split.clone().run().unwrap_err(), SplitError::InUrlNotRead);
+        assert_eq!(
+            split.on_url_expired(|_| None).run().unwrap_err(),
+            SplitError::InUrlExpired
+        );
+    }
+
+    #[test]
+    fn test_merge_to_http_sink() {
+        let (root, cache_dir, _, _) = setup("merge_to_http_sink");
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let url: String =
+            format!("http://{}/test.jpg", listener.local_addr().unwrap());
+
+        // receive the chunked body of the request
+        let server: thread::JoinHandle<(String, Vec<u8>)> = thread::spawn(
+            move || {
+                let (stream, _) = listener.accept().unwrap();
+
+                let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+
+                let mut head: String = String::new();
+
+                while !head.ends_with("\r\n\r\n") {
+                    reader.read_line(&mut head).unwrap();
+                }
+
+                let mut body: Vec<u8> = Vec::new();
+
+                loop {
+                    let mut line: String = String::new();
+
+                    reader.read_line(&mut line).unwrap();
+
+                    let size: usize =
+                        usize::from_str_radix(line.trim(), 16).unwrap();
+
+                    let mut chunk: Vec<u8> = vec![0; size + 2];
+
+                    reader.read_exact(&mut chunk).unwrap();
+
+                    if size == 0 {
+                        break;
+                    }
+
+                    body.extend_from_slice(&chunk[..size]);
+                }
+
+                (&stream)
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    )
+                    .unwrap();
+
+                (head, body)
+            },
+        );
+
+        let mut sink: HttpSink = HttpSink::put(&url);
+
+        let result: MergeResult =
+            Merge::new().in_dir(&cache_dir).run_to_sink(&mut sink).unwrap();
+
+        let (head, body) = server.join().unwrap();
+
+        assert!(head.starts_with("PUT /test.jpg"));
+        assert!(head.contains("transfer-encoding: chunked"));
+        assert_eq!(result.out_file, None);
+        assert_eq!(result.bytes_written, body.len() as u64);
+        assert_eq!(
+            body,
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_from_urls() {
+        let (root, _, output_path, split_result) = setup("merge_from_urls");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let chunks: Vec<Vec<u8>> = split_result
+            .chunks
+            .iter()
+            .map(|chunk| fs::read(&chunk.path).unwrap())
+            .collect();
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let addr: SocketAddr = listener.local_addr().unwrap();
+
+        let requests: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+
+        let counter: Arc<AtomicUsize> = Arc::clone(&requests);
+
+        let served: Vec<Vec<u8>> = chunks.clone();
+
+        thread::spawn(move || {
+            let mut failed: bool = false;
+
+            for stream in listener.incoming() {
+                let mut stream: TcpStream = stream.unwrap();
+
+                let mut line: String = String::new();
+
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let index: usize = line
+                    .split_whitespace()
+                    .nth(1)
+                    .and_then(|p| p.strip_prefix("/chunk/"))
+                    .and_then(|i| i.parse().ok())
+                    .unwrap();
+
+                // the first request of a chunk fails once
+                if index == 3 && !failed {
+                    failed = true;
+
+                    write!(
+                        stream,
+                        "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    )
+                    .unwrap();
+
+                    continue;
+                }
+
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    served[index].len()
+                )
+                .unwrap();
+
+                stream.write_all(&served[index]).unwrap();
+            }
+        });
+
+        let manifest: String = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                format!(
+                    "http://{addr}/chunk/{i} {} sha256:{}\n",
+                    chunk.len(),
+                    HashAlgorithm::Sha256.digest(chunk).to_hex()
+                )
+            })
+            .collect();
+
+        let urls: Vec<ChunkUrl> = ChunkUrl::parse_manifest(&format!(
+            "# chunks of test.jpg\n\n{manifest}"
+        ))
+        .unwrap();
+
+        assert_eq!(urls.len(), split_result.total_chunks);
+
+        let result: MergeResult = Merge::new()
+            .in_urls(urls.clone())
+            .out_file(&output_path)
+            .concurrency(3)
+            .retries(1)
+            .run()
+            .unwrap();
+
+        assert_eq!(result.bytes_written, asset.len() as u64);
+        assert_eq!(result.chunks_merged, split_result.total_chunks);
+        assert_eq!(
+            requests.load(Ordering::SeqCst),
+            split_result.total_chunks + 1
+        );
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        // a chunk not matching its expected size is rejected
+        let mut mismatched: Vec<ChunkUrl> = urls;
+
+        mismatched[5].size = Some(1);
+
+        assert_eq!(
+            Merge::new()
+                .in_urls(mismatched)
+                .out_file(&output_path)
+                .overwrite(OutFilePolicy::Overwrite)
+                .concurrency(3)
+                .run()
+                .unwrap_err(),
+            MergeError::ChunkMismatch
+        );
+
+        assert_eq!(ChunkUrl::parse_manifest("http://a/0 not-a-size"), None);
+    }
+
+    #[test]
+    fn test_split_to_http_store() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let asset: Vec<u8> = fs::read(&asset_path).unwrap();
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let addr: SocketAddr = listener.local_addr().unwrap();
+
+        let uploaded: Arc<Mutex<MemoryStore>> =
+            Arc::new(Mutex::new(MemoryStore::new()));
+
+        let received: Arc<Mutex<MemoryStore>> = Arc::clone(&uploaded);
+
+        thread::spawn(move || {
+            let mut failed: bool = false;
+
+            for stream in listener.incoming() {
+                let mut stream: TcpStream = stream.unwrap();
+
+                let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+
+                let mut request: String = String::new();
+                let mut length: usize = 0;
+
+                loop {
+                    let mut line: String = String::new();
+
+                    reader.read_line(&mut line).unwrap();
+
+                    if let Some(value) =
+                        line.to_lowercase().strip_prefix("content-length: ")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+
+                    if line.trim().is_empty() {
+                        break;
+                    }
+
+                    request.push_str(&line);
+                }
+
+                let mut body: Vec<u8> = vec![0; length];
+
+                reader.read_exact(&mut body).unwrap();
+
+                let index: usize = request
+                    .split_whitespace()
+                    .nth(1)
+                    .and_then(|p| p.strip_prefix("/upload/"))
+                    .and_then(|i| i.parse().ok())
+                    .unwrap();
+
+                let status: &str = if !request.starts_with("PUT")
+                    || !request.contains("authorization: Bearer token")
+                {
+                    "401 Unauthorized"
+                } else if index == 2 && !failed {
+                    // the first upload of a chunk fails once
+                    failed = true;
+
+                    "503 Service Unavailable"
+                } else {
+                    received.lock().unwrap().write(index, &body).unwrap();
+
+                    "201 Created"
+                };
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+        });
+
+        let mut store: HttpStore =
+            HttpStore::put(format!("http://{addr}/upload/{{index}}"))
+                .header("Authorization", "Bearer token")
+                .concurrency(3)
+                .retries(1)
+                .retry_delay(Duration::ZERO);
+
+        assert_eq!(store.url(7), format!("http://{addr}/upload/7"));
+
+        let result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .chunk_size(1024 * 1024)
+            .run_file_to_store(&mut store)
+            .unwrap();
+
+        store.finish().unwrap();
+
+        assert!(store.contains(result.total_chunks - 1).unwrap());
+
+        let uploaded: MemoryStore = uploaded.lock().unwrap().clone();
+
+        assert_eq!(uploaded.len(), result.total_chunks);
+
+        let mut output: Vec<u8> = Vec::new();
+
+        Merge::new()
+            .run_from_store_to_sink(&uploaded, result.total_chunks, &mut output)
+            .unwrap();
+
+        assert_eq!(output, asset);
+
+        // a rejected upload is not retried
+        let mut store: HttpStore =
+            HttpStore::post(format!("http://{addr}/upload/{{index}}"))
+                .retries(3)
+                .retry_delay(Duration::from_secs(60));
+
+        store.write(0, b"chunk").unwrap();
+
+        assert_eq!(store.finish(), Err(StoreError::ChunkNotWritten));
+    }
+
+    #[tokio::test]
+    async fn test_split_progress_snapshot() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_progress_snapshot");
+
+        let progress: Progress = Progress::new();
+
+        assert_eq!(progress.phase(), Phase::Pending);
+
+        let split_result: SplitResult = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .progress(progress.clone())
+            .run()
+            .unwrap();
+
+        let snapshot: ProgressSnapshot = progress.snapshot();
+
+        assert_eq!(snapshot.phase, Phase::Finished);
+        assert_eq!(snapshot.bytes_processed, split_result.file_size);
+        assert_eq!(snapshot.chunks_processed, split_result.total_chunks);
+        assert_eq!(snapshot.chunk_index, Some(split_result.total_chunks - 1));
+
+        assert!(
+            Split::new()
+                .in_file(root.join("assets").join("not_found.jpg"))
+                .out_dir(&cache_dir)
+                .progress(progress.clone())
+                .run()
+                .is_err()
+        );
+
+        assert_eq!(progress.phase(), Phase::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_naming() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_check_and_merge_with_naming");
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("split_check_and_merge_with_naming")
+            .join("test.jpg");
+
+        let naming: ChunkNaming = ChunkNaming::new()
+            .prefix("part-")
+            .extension("bin")
+            .pad_width(5)
+            .start_index(1);
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .naming(naming.clone())
+            .run()
+            .unwrap();
+
+        assert!(cache_dir.join("part-00001.bin").is_file());
+
+        Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .naming(naming.clone())
+            .run()
+            .unwrap();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .chunk_prefix("part-")
+            .chunk_extension("bin")
+            .pad_width(5)
+            .start_index(1)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_and_merge_from_split() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("check_and_merge_from_split");
+        let output_dir: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("check_and_merge_from_split");
+
+        let _ = fs::remove_dir_all(&output_dir);
+
+        let split: Split = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .chunk_prefix("part-")
+            .chunk_extension("bin");
+
+        let split_result: SplitResult = split.run().unwrap();
+
+        // the stages chain without copying the options
+        let check: Check = Check::from_split(&split, &split_result);
+
+        assert_eq!(check.in_dir, split.out_dir);
+        assert_eq!(check.file_size, Some(split_result.file_size));
+        assert_eq!(check.total_chunks, Some(split_result.total_chunks));
+        assert_eq!(check.naming.file_name(0), "part-0.bin");
+
+        let report: CheckReport = check.run().unwrap();
+
+        Merge::from_check(&check, &report)
+            .out_file(output_dir.join("from_check.jpg"))
+            .run()
+            .unwrap();
+
+        Merge::from_split(&split, &split_result)
+            .out_file(output_dir.join("from_split.jpg"))
+            .run()
+            .unwrap();
+
+        // the conversions keep the options set before the run
+        Merge::from(&Check::from(&split).file_size(split_result.file_size))
+            .out_file(output_dir.join("from.jpg"))
+            .run()
+            .unwrap();
+
+        for name in ["from_check.jpg", "from_split.jpg", "from.jpg"] {
+            assert_eq!(
+                fs::read(output_dir.join(name)).unwrap(),
+                fs::read(&asset_path).unwrap()
+            );
+        }
+
+        // the merge fails if the chunks differ from the split result
+        fs::remove_file(cache_dir.join("part-1.bin")).unwrap();
+
+        assert_eq!(
+            Merge::from_split(&split, &split_result)
+                .out_file(output_dir.join("incomplete.jpg"))
+                .run()
+                .unwrap_err(),
+            MergeError::ChunksIncomplete
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chunks_reader() {
+        let (root, cache_dir, _, split_result) = setup("chunks_reader");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let mut reader: ChunksReader = ChunksReader::open(&cache_dir).unwrap();
+
+        assert_eq!(reader.len(), asset.len() as u64);
+        assert_eq!(reader.total_chunks(), split_result.total_chunks);
+
+        let mut data: Vec<u8> = Vec::new();
+
+        reader.read_to_end(&mut data).unwrap();
+
+        assert_eq!(data, asset);
+
+        // the reads across the chunk boundaries
+        let chunk_size: u64 = split_result.chunk_size as u64;
+
+        for (from, len) in
+            [(0, 16), (chunk_size - 100, 200), (2 * chunk_size - 1, 2), (5, 0)]
+        {
+            reader.seek(SeekFrom::Start(from)).unwrap();
+
+            let mut range: Vec<u8> = Vec::new();
+
+            (&mut reader).take(len).read_to_end(&mut range).unwrap();
+
+            assert_eq!(
+                range,
+                asset[from as usize..(from + len) as usize],
+                "{from}..{}",
+                from + len
+            );
+        }
+
+        assert_eq!(
+            reader.seek(SeekFrom::End(-10)).unwrap(),
+            asset.len() as u64 - 10
+        );
+
+        data.clear();
+
+        reader.read_to_end(&mut data).unwrap();
+
+        assert_eq!(data, asset[asset.len() - 10..]);
+
+        // past the end, nothing is read
+        reader.seek(SeekFrom::Current(10)).unwrap();
+
+        assert_eq!(reader.read(&mut [0; 16]).unwrap(), 0);
+        assert!(
+            reader.seek(SeekFrom::Current(-(asset.len() as i64) - 11)).is_err()
+        );
+
+        // the range of a merge process is presented only
+        let mut reader: ChunksReader = ChunksReader::from_merge(
+            &Merge::new()
+                .in_dir(&cache_dir)
+                .byte_range(1000..chunk_size + 1000),
+        )
+        .unwrap();
+
+        data.clear();
+
+        reader.read_to_end(&mut data).unwrap();
+
+        assert_eq!(reader.len(), chunk_size);
+        assert_eq!(data, asset[1000..chunk_size as usize + 1000]);
+
+        assert_eq!(
+            ChunksReader::open(cache_dir.join("missing")).unwrap_err(),
+            MergeError::InDirNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chunks_reader_with_header() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("chunks_reader_with_header");
+
+        let _ = fs::remove_dir_all(&cache_dir);
+
+        Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .format(ChunkFormat::Header)
+            .run()
+            .unwrap();
+
+        let asset: Vec<u8> = fs::read(&asset_path).unwrap();
+
+        let mut reader: ChunksReader = ChunksReader::from_merge(
+            &Merge::new().in_dir(&cache_dir).format(ChunkFormat::Header),
+        )
+        .unwrap();
+
+        reader.seek(SeekFrom::Start(1024 * 1024 - 8)).unwrap();
+
+        let mut data: Vec<u8> = vec![0; 16];
+
+        reader.read_exact(&mut data).unwrap();
+
+        assert_eq!(data, asset[1024 * 1024 - 8..1024 * 1024 + 8]);
+
+        reader.rewind().unwrap();
+
+        data.clear();
+
+        reader.read_to_end(&mut data).unwrap();
+
+        assert_eq!(data, asset);
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_utf8_paths() {
+        let root: Utf8PathBuf =
+            Utf8PathBuf::try_from(env::current_dir().unwrap()).unwrap();
+        let asset_path: Utf8PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: Utf8PathBuf =
+            root.join(".media/cache/std/split_check_and_merge_with_utf8_paths");
+        let output_path: Utf8PathBuf = root.join(
+            ".media/output/std/split_check_and_merge_with_utf8_paths/test.jpg",
+        );
+
+        let naming: ChunkNaming = ChunkNaming::new().extension("bin");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .naming(naming.clone())
+            .run()
+            .unwrap();
+
+        for chunk in split_result.chunks.iter() {
+            let path: &Utf8Path = chunk.utf8_path().unwrap();
+
+            assert_eq!(path, naming.utf8_path(&cache_dir, chunk.index));
+            assert_eq!(naming.utf8_index_of(path), Some(chunk.index));
+        }
+
+        let report: CheckReport = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .naming(naming.clone())
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            report.chunks[0].utf8_path().unwrap(),
+            cache_dir.join("0.bin")
+        );
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .naming(naming)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.utf8_out_file(), Some(output_path.as_path()));
+    }
+
+    #[tokio::test]
+    async fn test_split_with_threads_zero_chunk_size() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let input_dir: PathBuf = root
+            .join(".media")
+            .join("input")
+            .join("std")
+            .join("split_with_threads_zero_chunk_size");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_with_threads_zero_chunk_size");
+
+        fs::create_dir_all(&input_dir).unwrap();
+
+        let input_path: PathBuf = input_dir.join("input.bin");
+
+        fs::write(&input_path, b"abc").unwrap();
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&input_path)
+            .out_dir(&cache_dir)
+            .chunk_size(0)
+            .threads(2)
+            .run()
+            .unwrap();
+
+        assert_eq!(split_result.file_size, 3);
+        assert_eq!(split_result.total_chunks, 3);
+    }
+
+    #[tokio::test]
+    async fn test_check_with_missing_chunks() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_with_missing_chunks");
+
+        if let Err(error) = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks + 1)
+            .run()
+        {
+            match error {
+                | CheckError::MissingChunks(_) => {
+                    return;
+                },
+                | err => panic!("Unexpected error: {:?}", err),
+            }
+        };
+
+        panic!("Check should fail due to missing chunks.");
+    }
+
+    #[tokio::test]
+    async fn test_check_with_size_error() {
+        let (_, cache_dir, _, split_result) = setup("check_with_size_error");
+
+        if let Err(error) = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size + 1)
+            .total_chunks(split_result.total_chunks)
+            .run()
+        {
+            match error {
+                | CheckError::SizeMismatch(_) => {
+                    return;
+                },
+                | err => panic!("Unexpected error: {:?}", err),
+            }
+        };
+
+        panic!("Check should fail due to size mismatch.");
+    }
+
+    #[tokio::test]
+    async fn test_check_report_lists_chunks() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_report_lists_chunks");
+
+        let total_chunks: usize = split_result.total_chunks + 1;
+
+        let report: CheckReport = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(total_chunks)
+            .report()
+            .unwrap();
+
+        assert!(!report.is_complete());
+        assert_eq!(report.missing(), vec![split_result.total_chunks]);
+        assert_eq!(report.actual_size, split_result.file_size);
+        assert_eq!(report.chunks.len(), total_chunks);
+        assert_eq!(
+            report.chunks.last(),
+            Some(&ChunkStatus {
+                index: split_result.total_chunks,
+                path: cache_dir.join(split_result.total_chunks.to_string()),
+                size: 0,
+                found: false,
+                hash: None,
+                expected_size: None,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_aggregate_hash() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_reports_aggregate_hash");
+
+        let complete: CheckReport = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .hash_algorithm(HashAlgorithm::Sha256)
+            .run()
+            .unwrap();
+
+        for chunk in &complete.chunks {
+            assert_eq!(
+                chunk.hash,
+                Some(
+                    HashAlgorithm::Sha256
+                        .digest(&fs::read(&chunk.path).unwrap())
+                )
+            );
+        }
+
+        // a missing chunk does not change the digest of the chunks found
+        let partial: CheckReport = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks + 1)
+            .hash_algorithm(HashAlgorithm::Sha256)
+            .report()
+            .unwrap();
+
+        assert!(partial.aggregate_hash.is_some());
+        assert_eq!(partial.aggregate_hash, complete.aggregate_hash);
+
+        let bitmap: ChunkBitmap = partial.bitmap();
+
+        assert_eq!(bitmap.len(), split_result.total_chunks + 1);
+        assert_eq!(bitmap.count(), split_result.total_chunks);
+        assert_eq!(
+            bitmap.missing().collect::<Vec<usize>>(),
+            vec![split_result.total_chunks]
+        );
+
+        let unhashed: CheckReport = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .run()
+            .unwrap();
+
+        assert_eq!(unhashed.aggregate_hash, None);
+    }
+
+    #[tokio::test]
+    async fn test_check_with_chunk_size_error() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_with_chunk_size_error");
+
+        // move the tail of the first chunk to the second one
+        let first: Vec<u8> = fs::read(cache_dir.join("0")).unwrap();
+        let mut second: Vec<u8> = fs::read(cache_dir.join("1")).unwrap();
+
+        second.extend_from_slice(&first[first.len() - 16..]);
+
+        fs::write(cache_dir.join("0"), &first[..first.len() - 16]).unwrap();
+        fs::write(cache_dir.join("1"), &second).unwrap();
+
+        let process: Check = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks);
+
+        // the total size still matches
+        process.run().unwrap();
+
+        match process
+            .chunk_sizes(split_result.chunks.iter().map(|c| c.size))
+            .run()
+        {
+            | Err(CheckError::ChunkSizeMismatch(err)) => {
+                assert_eq!(err.mismatched, vec![0, 1]);
+            },
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunk_bitmap_set_operations() {
+        let a: ChunkBitmap = ChunkBitmap::from_indices(10, [0, 3, 8, 9]);
+        let b: ChunkBitmap = ChunkBitmap::from_indices(12, [3, 4, 9, 11]);
+
+        assert_eq!(
+            a.union(&b).iter().collect::<Vec<usize>>(),
+            vec![0, 3, 4, 8, 9, 11]
+        );
+        assert_eq!(a.union(&b).len(), 12);
+        assert_eq!(
+            a.intersection(&b).iter().collect::<Vec<usize>>(),
+            vec![3, 9]
+        );
+        assert_eq!(a.intersection(&b).len(), 10);
+        assert_eq!(a.difference(&b).iter().collect::<Vec<usize>>(), vec![0, 8]);
+        assert_eq!(
+            b.difference(&a).iter().collect::<Vec<usize>>(),
+            vec![4, 11]
+        );
+
+        assert!(ChunkBitmap::full(10).is_full());
+        assert_eq!(ChunkBitmap::full(10).as_bytes(), &[0xff, 0x03]);
+        assert_eq!(ChunkBitmap::new(10).count(), 0);
+
+        // bits beyond the length are ignored
+        assert_eq!(
+            ChunkBitmap::from_bytes(10, vec![0xff, 0xff, 0xff]),
+            ChunkBitmap::full(10)
+        );
+
+        let json: String = serde_json::to_string(&a).unwrap();
+
+        assert_eq!(serde_json::from_str::<ChunkBitmap>(&json).unwrap(), a);
+    }
+
+    #[tokio::test]
+    async fn test_exchange_fetches_missing_chunks() {
+        let (root, cache_dir, _, split_result) =
+            setup("exchange_fetches_missing_chunks");
+
+        let peer_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("exchange_fetches_missing_chunks_peer");
+
+        // the peer holds the first two chunks only
+        if peer_dir.exists() {
+            fs::remove_dir_all(&peer_dir).unwrap();
+        }
+
+        fs::create_dir_all(&peer_dir).unwrap();
+
+        for i in 0..2 {
+            fs::copy(
+                cache_dir.join(i.to_string()),
+                peer_dir.join(i.to_string()),
+            )
+            .unwrap();
+        }
+
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let addr: SocketAddr = listener.local_addr().unwrap();
+
+        let total_chunks: usize = split_result.total_chunks;
+
+        let server: thread::JoinHandle<usize> = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+
+            Exchange::new(
+                DirStore::new(&cache_dir, ChunkNaming::new()),
+                total_chunks,
+            )
+            .serve(&mut &stream, &mut &stream)
+            .unwrap()
+        });
+
+        let stream: TcpStream = TcpStream::connect(addr).unwrap();
+
+        let mut exchange: Exchange<DirStore> = Exchange::new(
+            DirStore::new(&peer_dir, ChunkNaming::new()),
+            total_chunks,
+        );
+
+        let fetched: ChunkBitmap =
+            exchange.fetch(&mut &stream, &mut &stream).unwrap();
+
+        drop(stream);
+
+        assert_eq!(server.join().unwrap(), total_chunks - 2);
+        assert_eq!(
+            fetched.iter().collect::<Vec<usize>>(),
+            (2..total_chunks).collect::<Vec<usize>>()
+        );
+        assert!(exchange.bitmap().unwrap().is_full());
+
+        Check::new()
+            .in_dir(&peer_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(total_chunks)
+            .run()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exchange_rejects_oversized_message() {
+        let (_, cache_dir, _, split_result) =
+            setup("exchange_rejects_oversized_message");
+
+        // a chunk message claiming the largest length, streamed forever
+        let mut header: Vec<u8> = vec![3];
+
+        header.extend_from_slice(&u64::MAX.to_le_bytes());
+
+        let mut reader: io::Chain<io::Cursor<Vec<u8>>, io::Repeat> =
+            io::Cursor::new(header).chain(io::repeat(0));
+
+        assert_eq!(
+            Message::read_from(&mut reader, 1024).unwrap_err(),
+            ExchangeError::MessageInvalid
+        );
+
+        let exchange: Exchange<DirStore> = Exchange::new(
+            DirStore::new(&cache_dir, ChunkNaming::new()),
+            split_result.total_chunks,
+        )
+        .max_message_len(1024);
+
+        let request: Vec<u8> =
+            Message::Chunk { index: 0, data: vec![0; 2048] }.encode();
+
+        assert_eq!(
+            exchange
+                .serve(&mut request.as_slice(), &mut io::sink())
+                .unwrap_err(),
+            ExchangeError::MessageInvalid
+        );
+    }
+
+    /// Store failing every request, as an unreachable mirror.
+    struct UnreachableStore;
+
+    impl ChunkStore for UnreachableStore {
+        fn contains(
+            &self,
+            _index: usize,
+        ) -> Result<bool, StoreError> {
+            Err(StoreError::ChunkNotRead)
+        }
+
+        fn read(
+            &self,
+            _index: usize,
+        ) -> Result<Vec<u8>, StoreError> {
+            Err(StoreError::ChunkNotRead)
+        }
+
+        fn write(
+            &mut self,
+            _index: usize,
+            _data: &[u8],
+        ) -> Result<(), StoreError> {
+            Err(StoreError::ChunkNotWritten)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_from_source_pool() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_from_source_pool");
+
+        let total_chunks: usize = split_result.total_chunks;
+
+        // mirror holding the first chunk only
+        let mirror_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("merge_from_source_pool_mirror");
+
+        let _ = fs::remove_dir_all(&mirror_dir);
+
+        fs::create_dir_all(&mirror_dir).unwrap();
+
+        fs::copy(&split_result.chunks[0].path, mirror_dir.join("0")).unwrap();
+
+        let pool: SourcePool<Box<dyn ChunkStore>> = SourcePool::new(vec![
+            Box::new(UnreachableStore),
+            Box::new(DirStore::new(&mirror_dir, ChunkNaming::new())),
+            Box::new(DirStore::new(&cache_dir, ChunkNaming::new())),
+        ]);
+
+        assert_eq!(pool.probe(0), vec![None, Some(true), Some(true)]);
+
+        let ranking: Vec<usize> = pool.ranking();
+
+        assert_eq!(ranking[2], 0);
+
+        let result: MergeResult = Merge::new()
+            .out_file(&output_path)
+            .run_from_store(&pool, total_chunks)
+            .unwrap();
+
+        assert_eq!(result.chunks_merged, total_chunks);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+
+        let stats: Vec<SourceStats> = pool.stats();
+
+        // the chunks missing from the mirror are read from the cache
+        assert!(!stats[0].healthy);
+        assert_eq!(stats[0].reads, 0);
+        assert_eq!(stats[1].reads + stats[2].reads, total_chunks);
+        assert!(stats[2].reads >= total_chunks - 1);
+        assert_eq!(
+            stats.iter().map(|s| s.bytes).sum::<u64>(),
+            split_result.file_size
+        );
+    }
+
+    #[tokio::test]
+    async fn test_successful_check() {
+        let (_, cache_dir, _, split_result) = setup("successful_check");
+
+        Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .run()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_merge_creates_output_file() {
+        let (_, cache_dir, output_path, _) = setup("merge_creates_output_file");
+
+        Merge::new().in_dir(&cache_dir).out_file(&output_path).run().unwrap();
+
+        assert!(
+            output_path.exists(),
+            "Output file should be created after merging."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_preallocates_output_file() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_preallocates_output_file");
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .preallocate(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, split_result.file_size);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_sparse_output() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let input_path: PathBuf = root
+            .join(".media")
+            .join("input")
+            .join("std")
+            .join("merge_sparse_output.img");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("merge_sparse_output");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("merge_sparse_output.img");
+
+        let _ = fs::remove_file(&output_path);
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        // an image with zeros around the data, and at the end
+        let mut data: Vec<u8> = vec![0; 1024 * 1024 + 10];
+
+        data.extend_from_slice(&asset[..1024 * 1024]);
+        data.extend(vec![0; 2 * 1024 * 1024]);
+
+        fs::create_dir_all(input_path.parent().unwrap()).unwrap();
+        fs::write(&input_path, &data).unwrap();
+
+        Split::new()
+            .in_file(&input_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .run()
+            .unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .sparse(true)
+            .expected_hash(
+                HashAlgorithm::Sha256,
+                HashAlgorithm::Sha256.digest(&data).bytes,
+            )
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, data.len() as u64);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        // only the blocks of the data are allocated
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt as _;
+
+            let allocated: u64 =
+                fs::metadata(&output_path).unwrap().blocks() * 512;
+
+            assert!(allocated < data.len() as u64 / 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_sparse_chunks() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let input_path: PathBuf = root
+            .join(".media")
+            .join("input")
+            .join("std")
+            .join("split_sparse_chunks.img");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_sparse_chunks");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("split_sparse_chunks.img");
+
+        let _ = fs::remove_file(&output_path);
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        // a sparse image with holes around the data
+        fs::create_dir_all(input_path.parent().unwrap()).unwrap();
+
+        let mut input: fs::File = fs::File::create(&input_path).unwrap();
+
+        input.set_len(2 * 1024 * 1024).unwrap();
+        input.seek(SeekFrom::End(0)).unwrap();
+        input.write_all(&asset[..1024 * 1024]).unwrap();
+        input.set_len(5 * 1024 * 1024).unwrap();
+
+        drop(input);
+
+        let data: Vec<u8> = fs::read(&input_path).unwrap();
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&input_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .sparse(true)
+            .verify_writes(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(split_result.total_chunks, 5);
+
+        // the chunks of zeros are holes at their size
+        for chunk in &split_result.chunks {
+            assert_eq!(fs::metadata(&chunk.path).unwrap().len(), 1024 * 1024);
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt as _;
+
+                let blocks: u64 = fs::metadata(&chunk.path).unwrap().blocks();
+
+                assert_eq!(blocks == 0, chunk.index != 2);
+            }
+        }
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .sparse(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_preserve_metadata() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let input_path: PathBuf = root
+            .join(".media")
+            .join("input")
+            .join("std")
+            .join("preserve_metadata.jpg");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("preserve_metadata");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("preserve_metadata.jpg");
+
+        let _ = fs::remove_file(&output_path);
+
+        fs::create_dir_all(input_path.parent().unwrap()).unwrap();
+        fs::copy(root.join("assets").join("test.jpg"), &input_path).unwrap();
+
+        let modified: SystemTime =
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+
+        fs::File::options()
+            .write(true)
+            .open(&input_path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+
+            fs::set_permissions(&input_path, fs::Permissions::from_mode(0o640))
+                .unwrap();
+        }
+
+        Split::new()
+            .in_file(&input_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .preserve_metadata(true)
+            .run()
+            .unwrap();
+
+        assert!(cache_dir.join("preserve_metadata.jpg.meta").is_file());
+
+        // the metadata file is not taken for a chunk
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .preserve_metadata(true)
+            .strict(true)
+            .run()
+            .unwrap();
+
+        let metadata: fs::Metadata = fs::metadata(&output_path).unwrap();
+
+        assert_eq!(metadata.modified().unwrap(), modified);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+
+            assert_eq!(metadata.permissions().mode() & 0o7777, 0o640);
+        }
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&input_path).unwrap()
+        );
+
+        // without the metadata recorded, it is reported in strict mode
+        fs::remove_file(cache_dir.join("preserve_metadata.jpg.meta")).unwrap();
+
+        let result: Result<MergeResult, MergeError> = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .preserve_metadata(true)
+            .strict(true)
+            .run();
+
+        assert_eq!(result.unwrap_err(), MergeError::WarningRaised);
+    }
+
+    #[tokio::test]
+    async fn test_split_merge_long_path() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        // a chunk directory past the 260 characters of `MAX_PATH`
+        let long_dir: PathBuf = (0..8).fold(
+            root.join(".media").join("cache").join("std").join("long_path"),
+            |dir, i| dir.join(format!("{i}-{}", "d".repeat(40))),
+        );
+
+        let output_path: PathBuf = long_dir.join("output.jpg");
+
+        assert!(output_path.as_os_str().len() > 260);
+
+        let _ = fs::remove_file(&output_path);
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(long_dir.join("chunks"))
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .run()
+            .unwrap();
+
+        Merge::new()
+            .in_dir(long_dir.join("chunks"))
+            .out_file(&output_path)
+            .expected_chunks(split_result.total_chunks, split_result.file_size)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_space_before_writing() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("check_space_before_writing");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let space_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("check_space_before_writing_split");
+
+        let _ = fs::remove_dir_all(&space_dir);
+
+        // no room left for the reserve
+        assert_eq!(
+            Split::new()
+                .in_file(&asset_path)
+                .out_dir(&space_dir)
+                .chunk_size(1024 * 1024)
+                .check_space(true)
+                .space_reserve(u64::MAX / 2)
+                .run()
+                .unwrap_err(),
+            SplitError::InsufficientSpace,
+        );
+        assert_eq!(fs::read_dir(&space_dir).unwrap().count(), 0);
+
+        let result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&space_dir)
+            .chunk_size(1024 * 1024)
+            .check_space(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+
+        assert_eq!(
+            Merge::new()
+                .in_dir(&cache_dir)
+                .out_file(&output_path)
+                .check_space(true)
+                .space_reserve(u64::MAX / 2)
+                .run()
+                .unwrap_err(),
+            MergeError::InsufficientSpace,
+        );
+        assert_eq!(fs::metadata(&output_path).map_or(0, |m| m.len()), 0);
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .check_space(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, split_result.file_size);
+    }
+
+    #[tokio::test]
+    async fn test_lock_conflicting_operations() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("lock_conflicting_operations");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let lock_path: PathBuf = FileLock::lock_path(&cache_dir);
+
+        assert_eq!(
+            lock_path.file_name().unwrap(),
+            "lock_conflicting_operations.lock"
+        );
+
+        // a split holding the chunk directory
+        let lock: FileLock =
+            FileLock::acquire(&cache_dir, LockMode::Exclusive).unwrap();
+
+        assert!(lock_path.exists());
+        assert_eq!(
+            FileLock::acquire(&cache_dir, LockMode::Shared).unwrap_err(),
+            LockError::Locked,
+        );
+        assert_eq!(
+            Split::new()
+                .in_file(&asset_path)
+                .out_dir(&cache_dir)
+                .chunk_size(1024 * 1024)
+                .lock(true)
+                .run()
+                .unwrap_err(),
+            SplitError::Locked,
+        );
+        assert_eq!(
+            Merge::new()
+                .in_dir(&cache_dir)
+                .out_file(&output_path)
+                .lock(true)
+                .run()
+                .unwrap_err(),
+            MergeError::Locked,
+        );
+        assert!(!output_path.exists());
+
+        drop(lock);
+
+        assert!(!lock_path.exists());
+
+        // merges reading the same chunks
+        let reader: FileLock =
+            FileLock::acquire(&cache_dir, LockMode::Shared).unwrap();
+
+        // another merge writing the output
+        let writer: FileLock =
+            FileLock::acquire(&output_path, LockMode::Exclusive).unwrap();
+
+        assert_eq!(
+            Merge::new()
+                .in_dir(&cache_dir)
+                .out_file(&output_path)
+                .lock(true)
+                .run()
+                .unwrap_err(),
+            MergeError::Locked,
+        );
+
+        drop(writer);
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .lock(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, split_result.file_size);
+        assert!(!FileLock::lock_path(&output_path).exists());
+
+        // still held by the other reader
+        assert!(lock_path.exists());
+        assert_eq!(
+            Split::new()
+                .in_file(&asset_path)
+                .out_dir(&cache_dir)
+                .lock(true)
+                .run()
+                .unwrap_err(),
+            SplitError::Locked,
+        );
+
+        drop(reader);
+
+        assert!(!lock_path.exists());
+
+        // the read-only source is not locked
+        let lock: FileLock =
+            FileLock::acquire(&cache_dir, LockMode::Exclusive).unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .read_only_source(true)
+            .lock(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, split_result.file_size);
+
+        drop(lock);
+    }
+
+    #[tokio::test]
+    async fn test_merge_reports_watermarks() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_reports_watermarks");
+
+        let watermarks: Arc<Mutex<Vec<(u64, u64)>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_watermarks: Arc<Mutex<Vec<(u64, u64)>>> =
+            watermarks.clone();
+
+        let handler_path: PathBuf = output_path.clone();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .progressive(true)
+            .on_watermark(move |watermark| {
+                // readable in place up to the watermark
+                let len: u64 = fs::metadata(&handler_path).unwrap().len();
+
+                handler_watermarks.lock().unwrap().push((watermark, len));
+            })
+            .run()
+            .unwrap();
+
+        let expected: Vec<(u64, u64)> = split_result
+            .chunks
+            .iter()
+            .scan(0, |offset, chunk| {
+                *offset += chunk.size as u64;
+
+                Some((*offset, *offset))
+            })
+            .collect();
+
+        assert_eq!(*watermarks.lock().unwrap(), expected);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compression_round_trip() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("compression_round_trip");
+
+        let output_dir: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("compression_round_trip");
+
+        fs::create_dir_all(&output_dir).unwrap();
+
+        // compressible input
+        let content: Vec<u8> = b"filerune compression round trip\n"
+            .iter()
+            .copied()
+            .cycle()
+            .take(300 * 1024)
+            .collect();
+
+        let in_file: PathBuf = output_dir.join("input.txt");
+
+        fs::write(&in_file, &content).unwrap();
+
+        for codec in
+            [Codec::Zstd { level: 3 }, Codec::Lz4, Codec::Gzip { level: 6 }]
+        {
+            let chunk_dir: PathBuf = cache_dir.join(codec.as_str());
+
+            let split_result: SplitResult = Split::new()
+                .in_file(&in_file)
+                .out_dir(&chunk_dir)
+                .chunk_size(100 * 1024)
+                .overwrite(OverwritePolicy::Clean)
+                .compression(codec)
+                .run()
+                .unwrap();
+
+            // the sizes are of the content, the chunks are smaller
+            assert_eq!(split_result.total_chunks, 3);
+            assert_eq!(split_result.file_size, content.len() as u64);
+
+            for chunk in split_result.chunks.iter() {
+                assert_eq!(chunk.size, 100 * 1024);
+                assert!(fs::metadata(&chunk.path).unwrap().len() < 10 * 1024);
+            }
+
+            let check: Check = Check::new()
+                .in_dir(&chunk_dir)
+                .file_size(split_result.file_size)
+                .total_chunks(split_result.total_chunks)
+                .chunk_sizes(split_result.chunks.iter().map(|c| c.size))
+                .hash_algorithm(HashAlgorithm::Sha256)
+                .compression(codec);
+
+            let report: CheckReport = check.run().unwrap();
+
+            assert_eq!(report.actual_size, content.len() as u64);
+            assert_eq!(
+                report.chunks[0].hash,
+                Some(HashAlgorithm::Sha256.digest(&content[..100 * 1024]))
+            );
+
+            let output_path: PathBuf =
+                output_dir.join(format!("{}.txt", codec.as_str()));
+
+            Merge::new()
+                .in_dir(&chunk_dir)
+                .out_file(&output_path)
+                .overwrite(OutFilePolicy::Overwrite)
+                .compression(codec)
+                .run()
+                .unwrap();
+
+            assert_eq!(fs::read(&output_path).unwrap(), content);
+
+            // ranges apply to the content
+            Merge::new()
+                .in_dir(&chunk_dir)
+                .out_file(&output_path)
+                .overwrite(OutFilePolicy::Overwrite)
+                .byte_range(150 * 1024..250 * 1024)
+                .compression(codec)
+                .run()
+                .unwrap();
+
+            assert_eq!(
+                fs::read(&output_path).unwrap(),
+                &content[150 * 1024..250 * 1024]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_out_file_policy() {
+        let (_, cache_dir, output_path, _) = setup("merge_out_file_policy");
+
+        let merge: Merge =
+            Merge::new().in_dir(&cache_dir).out_file(&output_path);
+
+        merge.run().unwrap();
+
+        match merge.run() {
+            | Err(MergeError::OutFileExists) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        merge.clone().overwrite(OutFilePolicy::RenameExisting).run().unwrap();
+
+        let backup_path: PathBuf = output_path.with_file_name("test.jpg.1");
+
+        assert_eq!(
+            fs::read(&backup_path).unwrap(),
+            fs::read(&output_path).unwrap()
+        );
+
+        // a directory is never removed
+        fs::remove_file(&output_path).unwrap();
+        fs::create_dir_all(output_path.join("nested")).unwrap();
+
+        match merge.clone().overwrite(OutFilePolicy::Overwrite).run() {
+            | Err(MergeError::OutFileNotFile) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert!(output_path.join("nested").is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_recover_removes_stale_temp_files() {
+        let (_, cache_dir, _, split_result) =
+            setup("recover_removes_stale_temp_files");
+
+        // left by a crashed atomic run
+        let temp_path: PathBuf = cache_dir.join("0.tmp");
+
+        fs::write(&temp_path, [0; 16]).unwrap();
+
+        // recently modified, as in use
+        let report: RecoverReport = recover(&cache_dir).unwrap();
+
+        assert!(report.is_clean());
+        assert_eq!(report.active_files, vec![temp_path.clone()]);
+
+        let recover: Recover =
+            Recover::new().in_dir(&cache_dir).stale_after(Duration::ZERO);
+
+        let report: RecoverReport =
+            recover.clone().dry_run(true).run().unwrap();
+
+        assert_eq!(report.stale_files, vec![temp_path.clone()]);
+        assert_eq!(report.bytes_reclaimed, 16);
+        assert!(temp_path.exists());
+
+        let report: RecoverReport = recover.run().unwrap();
+
+        assert_eq!(report.stale_files, vec![temp_path.clone()]);
+        assert!(!temp_path.exists());
+
+        // the chunks are kept
+        for chunk in split_result.chunks {
+            assert!(chunk.path.exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_json() {
+        let (_, cache_dir, output_path, split_result) = setup("report_json");
+
+        let json: String = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .report_json();
+
+        let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(report["schema_version"], 1);
+        assert_eq!(report["operation"], "merge");
+        assert_eq!(report["success"], true);
+        assert_eq!(report["options"]["in_dir"], cache_dir.to_str().unwrap());
+        assert_eq!(report["options"]["chunks"], serde_json::Value::Null);
+        assert_eq!(report["options"]["overwrite"], "fail");
+        assert_eq!(report["result"]["bytes_written"], split_result.file_size);
+        assert_eq!(report["error"], serde_json::Value::Null);
+        assert_eq!(report["stats"]["bytes"], split_result.file_size);
+        assert_eq!(report["stats"]["chunks"], split_result.total_chunks);
+
+        // the output already exists
+        let json: String = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .report_json();
+
+        let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(report["success"], false);
+        assert_eq!(report["result"], serde_json::Value::Null);
+        assert_eq!(report["error"]["code"], "out_file_exists");
+        assert_eq!(report["stats"]["bytes"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_merge_result_lists_chunks() {
+        let (_, cache_dir, output_path, split_result) =
+            setup("merge_result_lists_chunks");
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.chunks.len(), split_result.total_chunks);
+
+        for (i, chunk) in merge_result.chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i);
+            assert_eq!(chunk.path, cache_dir.join(i.to_string()));
+        }
+
+        assert_eq!(
+            merge_result
+                .chunks
+                .iter()
+                .map(|chunk| chunk.size as u64)
+                .sum::<u64>(),
+            split_result.file_size
+        );
+
+        assert_eq!(merge_result.bytes_written, split_result.file_size);
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks);
+        assert_eq!(merge_result.out_file, Some(output_path));
+    }
+
+    #[tokio::test]
+    async fn test_merge_chunk_and_byte_range() {
+        let (root, cache_dir, output_path, _) =
+            setup("merge_chunk_and_byte_range");
+
+        let chunk_size: usize = 1024 * 1024;
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .chunks(2..4)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.chunks_merged, 2);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            &asset[2 * chunk_size..4 * chunk_size]
+        );
+
+        // a byte range across chunks
+        let range: std::ops::Range<usize> = 1000..chunk_size * 3 + 10;
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .byte_range(range.start as u64..range.end as u64)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.chunks_merged, 4);
+        assert_eq!(merge_result.bytes_written, range.len() as u64);
+        assert_eq!(fs::read(&output_path).unwrap(), &asset[range]);
+
+        // a byte range beyond the end of the file
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .byte_range(asset.len() as u64 - 10..asset.len() as u64 + 10)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, 10);
+        assert_eq!(fs::read(&output_path).unwrap(), &asset[asset.len() - 10..]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_resumes_partial_output() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_resumes_partial_output");
+
+        let chunk_size: usize = 1024 * 1024;
+
+        Merge::new().in_dir(&cache_dir).out_file(&output_path).run().unwrap();
+
+        // interrupted in the middle of the third chunk
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&output_path)
+            .unwrap()
+            .set_len((chunk_size * 5 / 2) as u64)
+            .unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .resume(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_resumed, chunk_size as u64 * 2);
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks - 2);
+        assert_eq!(
+            merge_result.bytes_written,
+            split_result.file_size - chunk_size as u64 * 2
+        );
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_with_expected_hash() {
+        let (root, cache_dir, output_path, _) =
+            setup("merge_with_expected_hash");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let digest: Vec<u8> = HashAlgorithm::Sha256.digest(&asset).bytes;
+
+        let chunk_size: usize = 1024 * 1024;
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .expected_hash(HashAlgorithm::Sha256, digest.clone())
+            .run()
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        // the part resumed is hashed as well
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&output_path)
+            .unwrap()
+            .set_len((chunk_size * 5 / 2) as u64)
+            .unwrap();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .resume(true)
+            .expected_hash(HashAlgorithm::Sha256, digest.clone())
+            .run()
+            .unwrap();
+
+        let mut corrupted: Vec<u8> = fs::read(cache_dir.join("3")).unwrap();
+
+        corrupted[0] ^= 0xff;
+
+        fs::write(cache_dir.join("3"), &corrupted).unwrap();
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .atomic(true)
+            .expected_hash(HashAlgorithm::Sha256, digest)
+            .run()
+        {
+            | Err(MergeError::HashMismatch) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // the atomic output is not renamed to its path
+        assert!(!output_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_merge_with_wrong_expected_hash() {
+        let (_, cache_dir, output_path, _) =
+            setup("merge_with_wrong_expected_hash");
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .expected_hash(HashAlgorithm::Sha256, vec![0; 32])
+            .run()
+        {
+            | Err(MergeError::HashMismatch) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // the corrupt output is removed
+        assert!(!output_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_merge_with_expected_chunks() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_with_expected_chunks");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let merge: Merge = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite);
+
+        merge
+            .clone()
+            .expected_chunks(split_result.total_chunks, split_result.file_size)
+            .run()
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        match merge
+            .clone()
+            .expected_chunks(
+                split_result.total_chunks,
+                split_result.file_size + 1,
+            )
+            .run()
+        {
+            | Err(MergeError::FileSizeMismatch) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        match merge
+            .clone()
+            .expected_chunks(
+                split_result.total_chunks + 1,
+                split_result.file_size,
+            )
+            .run()
+        {
+            | Err(MergeError::ChunksIncomplete) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // a chunk missing in the middle
+        fs::remove_file(cache_dir.join("3")).unwrap();
+
+        match merge
+            .clone()
+            .expected_chunks(split_result.total_chunks, split_result.file_size)
+            .chunks(0..2)
+            .run()
+        {
+            | Err(MergeError::ChunksIncomplete) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // no byte written
+        assert_eq!(fs::metadata(&output_path).unwrap().len(), 0);
+
+        // concatenated as found without expectation
+        let result: MergeResult = merge.run().unwrap();
+
+        assert_eq!(result.chunks.len(), split_result.total_chunks - 1);
+    }
+
+    #[test]
+    fn test_blake3_test_vectors() {
+        // the inputs and hashes of the official BLAKE3 test vectors
+        let vectors: [(usize, &str); 22] = [
+            (
+                0,
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            ),
+            (
+                1,
+                "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+            ),
+            (
+                1023,
+                "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11",
+            ),
+            (
+                1024,
+                "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+            ),
+            (
+                1025,
+                "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+            ),
+            (
+                2048,
+                "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a",
+            ),
+            (
+                2049,
+                "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030",
+            ),
+            (
+                3072,
+                "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2",
+            ),
+            (
+                3073,
+                "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3",
+            ),
+            (
+                4096,
+                "015094013f57a5277b59d8475c0501042c0b642e531b0a1c8f58d2163229e969",
+            ),
+            (
+                4097,
+                "9b4052b38f1c5fc8b1f9ff7ac7b27cd242487b3d890d15c96a1c25b8aa0fb995",
+            ),
+            (
+                5120,
+                "9cadc15fed8b5d854562b26a9536d9707cadeda9b143978f319ab34230535833",
+            ),
+            (
+                5121,
+                "628bd2cb2004694adaab7bbd778a25df25c47b9d4155a55f8fbd79f2fe154cff",
+            ),
+            (
+                6144,
+                "3e2e5b74e048f3add6d21faab3f83aa44d3b2278afb83b80b3c35164ebeca205",
+            ),
+            (
+                6145,
+                "f1323a8631446cc50536a9f705ee5cb619424d46887f3c376c695b70e0f0507f",
+            ),
+            (
+                7168,
+                "61da957ec2499a95d6b8023e2b0e604ec7f6b50e80a9678b89d2628e99ada77a",
+            ),
+            (
+                7169,
+                "a003fc7a51754a9b3c7fae0367ab3d782dccf28855a03d435f8cfe74605e7817",
+            ),
+            (
+                8192,
+                "aae792484c8efe4f19e2ca7d371d8c467ffb10748d8a5a1ae579948f718a2a63",
+            ),
+            (
+                8193,
+                "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3b",
+            ),
+            (
+                16384,
+                "f875d6646de28985646f34ee13be9a576fd515f76b5b0a26bb324735041ddde4",
+            ),
+            (
+                31744,
+                "62b6960e1a44bcc1eb1a611a8d6235b6b4b78f32e7abc4fb4c6cdcce94895c47",
+            ),
+            (
+                102400,
+                "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085",
+            ),
+        ];
+
+        for (len, hash) in vectors {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+
+            let digest: Digest = HashAlgorithm::Blake3.digest(&data);
+
+            assert_eq!(digest.to_hex(), hash, "input of {len} bytes");
+
+            let (root, outboard): (Digest, Vec<u8>) =
+                bao::outboard(data.as_slice(), len as u64).unwrap();
+
+            assert_eq!(root, digest);
+
+            // the size, then a parent node for each BLAKE3 chunk but one
+            assert_eq!(
+                outboard.len(),
+                8 + 64 * (len.div_ceil(1024).max(1) - 1),
+                "input of {len} bytes"
+            );
+            assert_eq!(outboard[..8], (len as u64).to_le_bytes());
+
+            let mut verifier: BaoVerifier<&[u8]> =
+                BaoVerifier::new(outboard.as_slice(), &root, 0).unwrap();
+
+            verifier.update(&data).unwrap();
+            verifier.finish().unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_with_bao() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let asset: Vec<u8> = fs::read(&asset_path).unwrap();
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_and_merge_with_bao");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("split_and_merge_with_bao")
+            .join("test.jpg");
+
+        let chunk_size: usize = 1024 * 1024;
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(chunk_size)
+            .overwrite(OverwritePolicy::Clean)
+            .bao(true)
+            .run()
+            .unwrap();
+
+        let bao_hash: Digest = split_result.bao_hash.unwrap();
+
+        assert_eq!(bao_hash, HashAlgorithm::Blake3.digest(&asset));
+
+        let outboard: Vec<u8> =
+            fs::read(cache_dir.join("test.jpg.obao")).unwrap();
+
+        // a chunk verified alone
+        let mut verifier: BaoVerifier<&[u8]> = BaoVerifier::new(
+            outboard.as_slice(),
+            &bao_hash,
+            chunk_size as u64 * 3,
+        )
+        .unwrap();
+
+        verifier.update(&fs::read(cache_dir.join("3")).unwrap()).unwrap();
+
+        // the outboard tree is not merged
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .bao(bao_hash.bytes.clone())
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks);
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        let range: std::ops::Range<usize> = 4096..chunk_size * 2 + 100;
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .byte_range(range.start as u64..range.end as u64)
+            .bao(bao_hash.bytes.clone())
+            .run()
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), &asset[range]);
+
+        let mut corrupted: Vec<u8> = fs::read(cache_dir.join("5")).unwrap();
+
+        corrupted[2048] ^= 0xff;
+
+        fs::write(cache_dir.join("5"), &corrupted).unwrap();
+
+        let mut verifier: BaoVerifier<&[u8]> = BaoVerifier::new(
+            outboard.as_slice(),
+            &bao_hash,
+            chunk_size as u64 * 5,
+        )
+        .unwrap();
+
+        assert_eq!(verifier.update(&corrupted), Err(BaoError::HashMismatch));
+
+        let progress: Progress = Progress::new();
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .bao(bao_hash.bytes.clone())
+            .progress(progress.clone())
+            .run()
+        {
+            | Err(MergeError::HashMismatch) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // detected in the corrupted chunk, before the rest is merged
+        assert_eq!(progress.snapshot().chunks_processed, 5);
+
+        fs::remove_file(cache_dir.join("test.jpg.obao")).unwrap();
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .bao(bao_hash.bytes)
+            .run()
+        {
+            | Err(MergeError::OutboardNotFound) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_chunk_headers() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let asset: Vec<u8> = fs::read(&asset_path).unwrap();
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_check_and_merge_with_chunk_headers");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("split_check_and_merge_with_chunk_headers")
+            .join("test.jpg");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .format(ChunkFormat::Header)
+            .run()
+            .unwrap();
+
+        let header: ChunkHeader =
+            ChunkHeader::read(cache_dir.join("2")).unwrap().unwrap();
+
+        assert_eq!(header.index, 2);
+        assert_eq!(header.total_chunks, Some(split_result.total_chunks));
+        assert_eq!(header.file_size, Some(asset.len() as u64));
+        assert_eq!(
+            fs::metadata(cache_dir.join("2")).unwrap().len() as usize,
+            HEADER_LEN + 1024 * 1024
+        );
+
+        // the chunks are renamed in the reverse order
+        for i in 0..split_result.total_chunks {
+            fs::rename(
+                cache_dir.join(i.to_string()),
+                cache_dir
+                    .join(format!("part-{}", split_result.total_chunks - i)),
+            )
+            .unwrap();
+        }
+
+        let check: Check =
+            Check::new().in_dir(&cache_dir).format(ChunkFormat::Header);
+
+        let report: CheckReport = check.run().unwrap();
+
+        assert_eq!(report.file_size, asset.len() as u64);
+        assert_eq!(report.chunks.len(), split_result.total_chunks);
+        assert_eq!(report.chunks[0].path, cache_dir.join("part-8"));
+
+        let merge: Merge = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .format(ChunkFormat::Header);
+
+        merge.run().unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        // the raw format merges the chunks by their names
+        assert!(
+            Merge::new()
+                .in_dir(&cache_dir)
+                .out_file(&output_path)
+                .overwrite(OutFilePolicy::Overwrite)
+                .run()
+                .is_err()
+        );
+
+        let mut corrupted: Vec<u8> =
+            fs::read(cache_dir.join("part-4")).unwrap();
+
+        corrupted[HEADER_LEN + 100] ^= 0xff;
+
+        fs::write(cache_dir.join("part-4"), &corrupted).unwrap();
+
+        let warnings: Arc<Mutex<Vec<Warning>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let received: Arc<Mutex<Vec<Warning>>> = Arc::clone(&warnings);
+
+        let report: CheckReport = check
+            .clone()
+            .on_warning(move |w| received.lock().unwrap().push(w.clone()))
+            .report()
+            .unwrap();
+
+        assert_eq!(report.missing(), vec![4]);
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![Warning::CorruptedChunk(cache_dir.join("part-4"))]
+        );
+
+        match merge.run() {
+            | Err(MergeError::InFileNotRead) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        fs::remove_file(cache_dir.join("part-4")).unwrap();
+
+        match merge.run() {
+            | Err(MergeError::ChunkHeaderMismatch) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // a range is merged from the chunks found
+        merge.clone().chunks(0..3).run().unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), &asset[..3 * 1024 * 1024]);
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_past_deadline() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("split_check_and_merge_past_deadline");
+
+        let deadline: Instant = Instant::now();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let split_error: SplitError = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .deadline(deadline)
+            .run()
+            .unwrap_err();
+
+        assert_eq!(split_error, SplitError::DeadlineExceeded);
+
+        let check_error: CheckError = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .deadline(deadline)
+            .run()
+            .unwrap_err();
+
+        assert_eq!(check_error, CheckError::DeadlineExceeded);
+
+        let merge_error: MergeError = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .deadline(deadline)
+            .run()
+            .unwrap_err();
+
+        assert_eq!(merge_error, MergeError::DeadlineExceeded);
+        assert!(!output_path.exists());
+
+        // resume the output kept on the deadline
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .resume(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_warns_ignored_entry() {
+        let (_, cache_dir, output_path, _) = setup("merge_warns_ignored_entry");
+
+        let stray_dir: PathBuf = cache_dir.join("stray");
+
+        fs::create_dir_all(&stray_dir).unwrap();
+
+        let warnings: Arc<Mutex<Vec<Warning>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_warnings: Arc<Mutex<Vec<Warning>>> = warnings.clone();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .on_warning(move |warning| {
+                handler_warnings.lock().unwrap().push(warning.clone())
+            })
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![Warning::IgnoredEntry(stray_dir)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_skips_stray_files() {
+        let (root, cache_dir, output_path, _) =
+            setup("merge_skips_stray_files");
+
+        for name in [".DS_Store", "Thumbs.db", "._0", "manifest.json"] {
+            fs::write(cache_dir.join(name), b"stray").unwrap();
+        }
+
+        let warnings: Arc<Mutex<Vec<Warning>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_warnings: Arc<Mutex<Vec<Warning>>> = warnings.clone();
+
+        let merge: Merge = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .on_warning(move |warning| {
+                handler_warnings.lock().unwrap().push(warning.clone())
+            });
+
+        merge.run().unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+
+        // the files of the operating system are skipped silently
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![Warning::IgnoredEntry(cache_dir.join("manifest.json"))]
+        );
+
+        fs::remove_file(&output_path).unwrap();
+
+        match merge.clone().strict(true).run() {
+            | Err(MergeError::WarningRaised) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        let _ = fs::remove_file(&output_path);
+
+        // a directory without chunk
+        let stray_dir: PathBuf = cache_dir.with_file_name("merge_stray_files");
+
+        fs::create_dir_all(&stray_dir).unwrap();
+        fs::write(stray_dir.join(".DS_Store"), b"stray").unwrap();
+
+        match Merge::new().in_dir(&stray_dir).out_file(&output_path).run() {
+            | Err(MergeError::InDirNoChunk) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strict_fails_on_warning() {
+        let (_, cache_dir, output_path, split_result) =
+            setup("strict_fails_on_warning");
+
+        let stray_dir: PathBuf = cache_dir.join("stray");
+
+        fs::create_dir_all(&stray_dir).unwrap();
+
+        let warnings: Arc<Mutex<Vec<Warning>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_warnings: Arc<Mutex<Vec<Warning>>> = warnings.clone();
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .strict(true)
+            .on_warning(move |warning| {
+                handler_warnings.lock().unwrap().push(warning.clone())
+            })
+            .run()
+        {
+            | Err(MergeError::WarningRaised) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![Warning::IgnoredEntry(stray_dir)]
+        );
+
+        // the output directory is not empty
+        match Split::new()
+            .in_file(cache_dir.join("0"))
+            .out_dir(&cache_dir)
+            .strict(true)
+            .run()
+        {
+            | Err(SplitError::WarningRaised) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // the stray directory is not a chunk to check
+        assert!(
+            Check::new()
+                .in_dir(&cache_dir)
+                .file_size(split_result.file_size)
+                .total_chunks(split_result.total_chunks)
+                .strict(true)
+                .run()
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_resolves_duplicate_chunks() {
+        let (root, cache_dir, output_path, _) =
+            setup("merge_resolves_duplicate_chunks");
+
+        // a shorter duplicate of the chunk 3
+        fs::write(cache_dir.join("003"), b"stale").unwrap();
+
+        match Merge::new().in_dir(&cache_dir).out_file(&output_path).run() {
+            | Err(MergeError::DuplicateChunk) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        let warnings: Arc<Mutex<Vec<Warning>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_warnings: Arc<Mutex<Vec<Warning>>> = warnings.clone();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .duplicate_policy(DuplicatePolicy::PreferLarger)
+            .overwrite(OutFilePolicy::Overwrite)
+            .on_warning(move |warning| {
+                handler_warnings.lock().unwrap().push(warning.clone())
+            })
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.chunks[3].path, cache_dir.join("3"));
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![Warning::DuplicateChunk(cache_dir.join("003"))]
+        );
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+
+        fs::remove_file(cache_dir.join("003")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_repair_from_parity_chunks() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("repair_from_parity_chunks");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("repair_from_parity_chunks")
+            .join("test.jpg");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .parity_chunks(2)
+            .run()
+            .unwrap();
+
+        assert!(cache_dir.join("0.parity").is_file());
+        assert!(cache_dir.join("1.parity").is_file());
+
+        // a missing chunk and a corrupted chunk of the same size
+        fs::remove_file(cache_dir.join("0")).unwrap();
+
+        let mut corrupted: Vec<u8> = fs::read(cache_dir.join("1")).unwrap();
+
+        corrupted[0] ^= 0xff;
+
+        fs::write(cache_dir.join("1"), &corrupted).unwrap();
+
+        let warnings: Arc<Mutex<Vec<Warning>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_warnings: Arc<Mutex<Vec<Warning>>> = warnings.clone();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .repair(true)
+            .on_warning(move |warning| {
+                handler_warnings.lock().unwrap().push(warning.clone())
+            })
+            .run()
+            .unwrap();
+
+        // the parity chunks are not merged nor ignored
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks);
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![
+                Warning::ChunkRepaired(cache_dir.join("0")),
+                Warning::ChunkRepaired(cache_dir.join("1")),
+            ]
+        );
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        fs::remove_file(cache_dir.join("2")).unwrap();
+
+        let check: Check = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .repair(true);
+
+        assert!(check.run().is_ok());
+
+        // more chunks lost than parity chunks
+        for index in 0..3 {
+            fs::remove_file(cache_dir.join(index.to_string())).unwrap();
+        }
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .repair(true)
+            .run()
+        {
+            | Err(MergeError::ChunksNotRepaired) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        match Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .total_chunks(255)
+            .overwrite(OverwritePolicy::Clean)
+            .parity_chunks(2)
+            .run()
+        {
+            | Err(SplitError::ParityLimitExceeded) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repair_from_par2_files() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("repair_from_par2_files");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("repair_from_par2_files")
+            .join("test.jpg");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .par2_recovery(2)
+            .run()
+            .unwrap();
+
+        assert!(cache_dir.join("test.jpg.par2").is_file());
+        assert!(cache_dir.join("test.jpg.vol00+02.par2").is_file());
+
+        // a missing chunk and a corrupted last chunk, shorter than the slices
+        let last: String = (split_result.total_chunks - 1).to_string();
+
+        fs::remove_file(cache_dir.join("0")).unwrap();
+
+        let mut corrupted: Vec<u8> = fs::read(cache_dir.join(&last)).unwrap();
+
+        corrupted[0] ^= 0xff;
+
+        fs::write(cache_dir.join(&last), &corrupted).unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .repair(true)
+            .run()
+            .unwrap();
+
+        // the PAR2 files are not merged
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        // more chunks lost than recovery slices
+        for index in 0..3 {
+            fs::remove_file(cache_dir.join(index.to_string())).unwrap();
+        }
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .repair(true)
+            .run()
+        {
+            | Err(MergeError::ChunksNotRepaired) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repair_from_par2_files_of_odd_chunks() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("repair_from_par2_files_of_odd_chunks");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("repair_from_par2_files_of_odd_chunks")
+            .join("test.jpg");
+
+        // the chunks are of an odd size, read in several pieces
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(3_000_001)
+            .overwrite(OverwritePolicy::Clean)
+            .par2_recovery(1)
+            .run()
+            .unwrap();
+
+        assert!(cache_dir.join("test.jpg.vol00+01.par2").is_file());
+
+        fs::remove_file(cache_dir.join("0")).unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .repair(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_from_read_only_source() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("merge_from_read_only_source");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("merge_from_read_only_source")
+            .join("test.jpg");
+
+        Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .parity_chunks(2)
+            .run()
+            .unwrap();
+
+        // a missing chunk and a corrupted chunk
+        fs::remove_file(cache_dir.join("0")).unwrap();
+
+        let mut corrupted: Vec<u8> = fs::read(cache_dir.join("5")).unwrap();
+
+        corrupted[0] ^= 0xff;
+
+        fs::write(cache_dir.join("5"), &corrupted).unwrap();
+
+        let listing = || {
+            let mut entries: Vec<(PathBuf, Vec<u8>)> = fs::read_dir(&cache_dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .map(|path| (path.clone(), fs::read(&path).unwrap()))
+                .collect();
+
+            entries.sort();
+
+            entries
+        };
+
+        let before: Vec<(PathBuf, Vec<u8>)> = listing();
+
+        let warnings: Arc<Mutex<Vec<Warning>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_warnings: Arc<Mutex<Vec<Warning>>> = warnings.clone();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .repair(true)
+            .read_only_source(true)
+            .on_warning(move |warning| {
+                handler_warnings.lock().unwrap().push(warning.clone())
+            })
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec![
+                Warning::ChunkRepaired(cache_dir.join("0")),
+                Warning::ChunkRepaired(cache_dir.join("5")),
+            ]
+        );
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        // nothing written to the input directory
+        assert_eq!(listing(), before);
+    }
+
+    #[tokio::test]
+    async fn test_bagit_manifests() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let bag_dir: PathBuf =
+            root.join(".media").join("cache").join("std").join("bagit");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("bagit")
+            .join("test.jpg");
+
+        let _ = fs::remove_dir_all(&bag_dir);
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(bag_dir.join("data"))
+            .chunk_size(1024 * 1024)
+            .run()
+            .unwrap();
+
+        let bag: Bag = Bag::new()
+            .bag_dir(&bag_dir)
+            .info("External-Identifier", "test.jpg");
+
+        let manifest: BagManifest = bag.write().unwrap();
+
+        assert_eq!(manifest.entries.len(), split_result.total_chunks);
+        assert_eq!(manifest.payload_size, split_result.file_size);
+
+        let content: String =
+            fs::read_to_string(bag_dir.join("manifest-sha256.txt")).unwrap();
+
+        let chunk: Vec<u8> = fs::read(bag_dir.join("data").join("0")).unwrap();
+
+        assert!(content.contains(&format!(
+            "{}  data/0\n",
+            HashAlgorithm::Sha256.digest(&chunk).to_hex()
+        )));
+        assert!(
+            fs::read_to_string(bag_dir.join("bag-info.txt")).unwrap().contains(
+                &format!(
+                    "Payload-Oxum: {}.{}\n",
+                    split_result.file_size, split_result.total_chunks
+                )
+            )
+        );
+        assert!(bag_dir.join("bagit.txt").is_file());
+        assert!(bag_dir.join("tagmanifest-sha256.txt").is_file());
+
+        assert_eq!(bag.verify().unwrap(), manifest);
+
+        Merge::new()
+            .in_dir(bag_dir.join("data"))
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        // a corrupted chunk
+        let mut corrupted: Vec<u8> = chunk.clone();
+
+        corrupted[0] ^= 0xff;
+
+        fs::write(bag_dir.join("data").join("0"), &corrupted).unwrap();
+
+        assert_eq!(bag.verify(), Err(BagError::PayloadNotMatched));
+
+        // a chunk not listed
+        fs::write(bag_dir.join("data").join("0"), &chunk).unwrap();
+        fs::write(bag_dir.join("data").join("extra"), b"extra").unwrap();
+
+        assert_eq!(bag.verify(), Err(BagError::PayloadNotMatched));
+
+        // a modified manifest
+        fs::remove_file(bag_dir.join("data").join("extra")).unwrap();
+        fs::write(bag_dir.join("manifest-sha256.txt"), &content[65..]).unwrap();
+
+        assert_eq!(bag.verify(), Err(BagError::TagFileNotMatched));
+    }
+
+    #[tokio::test]
+    async fn test_db_records_round_trip() {
+        let (_, cache_dir, _, split_result) = setup("db_records_round_trip");
+
+        let split: SplitRecord = SplitRecord::try_from(&split_result).unwrap();
+
+        assert_eq!(split.file_size, split_result.file_size as i64);
+        assert_eq!(split.total_chunks, split_result.total_chunks as i64);
+        assert_eq!(split.bao_hash, None);
+
+        let mut chunks: Vec<ChunkRecord> =
+            ChunkRecord::from_result(&split_result).unwrap();
+
+        assert_eq!(chunks.len(), split_result.total_chunks);
+        assert_eq!(chunks[1].chunk_index, 1);
+        assert_eq!(chunks[1].size, 1024 * 1024);
+
+        // the rows read back in any order
+        chunks.reverse();
+
+        let result: SplitResult = split.clone().into_result(chunks).unwrap();
+
+        assert_eq!(result.chunks, split_result.chunks);
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(
+            result.duration.as_millis(),
+            split_result.duration.as_millis()
+        );
+
+        // a hash of another length
+        let invalid: SplitRecord =
+            SplitRecord { bao_hash: Some("00ff".to_string()), ..split };
+
+        assert_eq!(
+            invalid.into_result(Vec::new()).unwrap_err(),
+            RecordError::HashInvalid
+        );
+
+        // a negative size
+        let negative: ChunkRecord = ChunkRecord {
+            chunk_index: 0,
+            path: cache_dir.join("0").to_str().unwrap().to_string(),
+            size: -1,
+        };
+
+        assert_eq!(
+            ChunkInfo::try_from(negative).unwrap_err(),
+            RecordError::ValueOutOfRange
+        );
+
+        // a manifest entry
+        let data: Vec<u8> = fs::read(cache_dir.join("0")).unwrap();
+
+        let entry: BagEntry = BagEntry {
+            path: PathBuf::from("data").join("0"),
+            hash: HashAlgorithm::Blake3.digest(&data),
+        };
+
+        let record: ManifestRecord = ManifestRecord::try_from(&entry).unwrap();
+
+        assert_eq!(record.hash_algorithm, "blake3");
+        assert_eq!(record.hash, entry.hash.to_hex());
+        assert_eq!(BagEntry::try_from(record.clone()).unwrap(), entry);
+
+        let unknown: ManifestRecord =
+            ManifestRecord { hash_algorithm: "md5".to_string(), ..record };
+
+        assert_eq!(
+            BagEntry::try_from(unknown).unwrap_err(),
+            RecordError::HashInvalid
+        );
+    }
+
+    #[tokio::test]
+    async fn test_seal_and_unseal() {
+        let (root, _, output_path, split_result) = setup("seal_and_unseal");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let sealed_dir: PathBuf =
+            root.join(".media").join("cache").join("std").join("sealed");
+
+        let _ = fs::remove_dir_all(&sealed_dir);
+
+        let sealed: SealResult = Seal::new()
+            .in_file(&asset_path)
+            .out_dir(&sealed_dir)
+            .chunk_size(1024 * 1024)
+            .passphrase("correct horse battery staple")
+            .run()
+            .unwrap();
+
+        assert_eq!(sealed.file_size, split_result.file_size);
+        assert_eq!(sealed.total_chunks, split_result.total_chunks);
+
+        // the chunks are not stored in plain
+        assert_ne!(
+            fs::read(&sealed.chunks[0].path).unwrap(),
+            fs::read(&split_result.chunks[0].path).unwrap()
+        );
+
+        // the output directory must be empty
+        assert_eq!(
+            Seal::new()
+                .in_file(&asset_path)
+                .out_dir(&sealed_dir)
+                .passphrase("correct horse battery staple")
+                .run()
+                .unwrap_err(),
+            SealError::OutDirNotEmpty
+        );
+
+        // a wrong passphrase
+        assert_eq!(
+            Unseal::new()
+                .in_dir(&sealed_dir)
+                .out_file(&output_path)
+                .passphrase("wrong")
+                .run()
+                .unwrap_err(),
+            UnsealError::KeyInvalid
+        );
+
+        let result: UnsealResult = Unseal::new()
+            .in_dir(&sealed_dir)
+            .out_file(&output_path)
+            .passphrase("correct horse battery staple")
+            .run()
+            .unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(result.file_name, "test.jpg");
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        fs::remove_file(&output_path).unwrap();
+
+        // swapped chunks
+        let first: PathBuf = sealed.chunks[0].path.clone();
+        let second: PathBuf = sealed.chunks[1].path.clone();
+        let swap: PathBuf = sealed_dir.join("swap");
+
+        fs::rename(&first, &swap).unwrap();
+        fs::rename(&second, &first).unwrap();
+        fs::rename(&swap, &second).unwrap();
+
+        assert_eq!(
+            Unseal::new()
+                .in_dir(&sealed_dir)
+                .out_file(&output_path)
+                .passphrase("correct horse battery staple")
+                .run()
+                .unwrap_err(),
+            UnsealError::ChunkNotAuthenticated
+        );
+        assert!(!output_path.exists());
+
+        // sealed with a key
+        let _ = fs::remove_dir_all(&sealed_dir);
+
+        let key: [u8; 32] = [7; 32];
+
+        Seal::new()
+            .in_file(&asset_path)
+            .out_dir(&sealed_dir)
+            .chunk_size(1024 * 1024)
+            .key(key)
+            .run()
+            .unwrap();
+
+        fs::remove_file(sealed_dir.join("1")).unwrap();
+
+        assert_eq!(
+            Unseal::new()
+                .in_dir(&sealed_dir)
+                .out_file(&output_path)
+                .key(key)
+                .run()
+                .unwrap_err(),
+            UnsealError::ChunkNotFound
+        );
+        assert_eq!(
+            Unseal::new()
+                .in_dir(&sealed_dir)
+                .out_file(&output_path)
+                .passphrase("correct horse battery staple")
+                .run()
+                .unwrap_err(),
+            UnsealError::KeyInvalid
+        );
+    }
+
+    #[tokio::test]
+    async fn test_seal_audit() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let sealed_dir: PathBuf =
+            root.join(".media").join("cache").join("std").join("seal_audit");
+
+        let _ = fs::remove_dir_all(&sealed_dir);
+
+        let sealed: SealResult = Seal::new()
+            .in_file(&asset_path)
+            .out_dir(&sealed_dir)
+            .chunk_size(1024 * 1024)
+            .passphrase("correct horse battery staple")
+            .run()
+            .unwrap();
+
+        // without the passphrase, the chunks are read up to the last one
+        let audit: SealAudit =
+            Unseal::new().in_dir(&sealed_dir).audit().unwrap();
+
+        assert_eq!(audit.archive_id, None);
+        assert_eq!(audit.key_mode, SealKeyMode::Passphrase);
+        assert_eq!(audit.kdf_costs, [19 * 1024, 2, 1]);
+        assert_eq!(audit.chunks.len(), sealed.total_chunks);
+        assert!(audit.repeated_nonces().is_empty());
+
+        for (chunk, info) in audit.chunks.iter().zip(sealed.chunks.iter()) {
+            assert_eq!(chunk.index, info.index);
+            assert_eq!(
+                chunk.nonce[..],
+                fs::read(&info.path).unwrap()[..SEAL_NONCE_LEN]
+            );
+        }
+
+        let opened: SealAudit = Unseal::new()
+            .in_dir(&sealed_dir)
+            .passphrase("correct horse battery staple")
+            .audit()
+            .unwrap();
+
+        assert!(opened.archive_id.is_some());
+        assert_eq!(opened.chunks, audit.chunks);
+
+        // a chunk encrypted under the nonce of another one
+        let first: Vec<u8> = fs::read(&sealed.chunks[0].path).unwrap();
+        let mut second: Vec<u8> = fs::read(&sealed.chunks[1].path).unwrap();
+
+        second[..SEAL_NONCE_LEN].copy_from_slice(&first[..SEAL_NONCE_LEN]);
+
+        fs::write(&sealed.chunks[1].path, second).unwrap();
+
+        assert_eq!(
+            Unseal::new()
+                .in_dir(&sealed_dir)
+                .audit()
+                .unwrap()
+                .repeated_nonces(),
+            vec![1]
+        );
+
+        // the manifest is opened with the passphrase only
+        assert_eq!(
+            Unseal::new().in_dir(&sealed_dir).key([7; 32]).audit().unwrap_err(),
+            UnsealError::KeyInvalid
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_of_chunk_sets() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("stats_of_chunk_sets");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let last_size: usize = split_result.chunks.last().unwrap().size;
+
+        let chunk_stats: ChunkStats = stats(&cache_dir).unwrap();
+
+        assert_eq!(chunk_stats.chunk_count, split_result.total_chunks);
+        assert_eq!(chunk_stats.total_bytes, split_result.file_size);
+        assert_eq!(chunk_stats.min_chunk_size, last_size);
+        assert_eq!(chunk_stats.max_chunk_size, 1024 * 1024);
+        assert_eq!(
+            chunk_stats.avg_chunk_size,
+            (split_result.file_size / split_result.total_chunks as u64)
+                as usize
+        );
+        assert_eq!(chunk_stats.compression, None);
+        assert_eq!(chunk_stats.compression_ratio, None);
+        assert_eq!(chunk_stats.hash_algorithm, None);
+
+        // the chunks of a bag, without the parity chunks
+        let bag_dir: PathBuf = output_path.with_file_name("bag");
+
+        Split::new()
+            .in_file(&asset_path)
+            .out_dir(bag_dir.join("data"))
+            .chunk_size(1024 * 1024)
+            .compression(Codec::Zstd { level: 3 })
+            .parity_chunks(1)
+            .run()
+            .unwrap();
+
+        Bag::new().bag_dir(&bag_dir).write().unwrap();
+
+        for path in [bag_dir.clone(), bag_dir.join("manifest-sha256.txt")] {
+            let chunk_stats: ChunkStats = stats(&path).unwrap();
+
+            assert_eq!(chunk_stats.chunk_count, split_result.total_chunks);
+            assert_eq!(chunk_stats.compression, Some(Codec::Zstd { level: 0 }));
+            assert_eq!(
+                chunk_stats.compression_ratio,
+                Some(
+                    split_result.file_size as f64
+                        / chunk_stats.total_bytes as f64
+                )
+            );
+            assert_eq!(chunk_stats.hash_algorithm, Some(HashAlgorithm::Sha256));
+        }
+
+        assert_eq!(
+            stats(output_path.with_file_name("missing")),
+            Err(StatsError::InPathNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_from_fountain_symbols() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("merge_from_fountain_symbols")
+            .join("test.jpg");
+
+        // a part of the asset, to keep the decoding short
+        let asset: Vec<u8> = fs::read(&asset_path).unwrap()[..500_000].to_vec();
+
+        let encoder: FountainEncoder<io::Cursor<&[u8]>> = Split::new()
+            .chunk_size(64 * 1024)
+            .fountain(io::Cursor::new(asset.as_slice()))
+            .unwrap();
+
+        let config: ObjectTransmissionInformation = encoder.config();
+
+        let blocks: usize = encoder.blocks();
+
+        let symbols: usize =
+            config.transfer_length().div_ceil(u64::from(config.symbol_size()))
+                as usize;
+
+        // every third packet is lost, the others arrive in reverse
+        let mut packets: Vec<Vec<u8>> = encoder
+            .take(symbols * 2)
+            .enumerate()
+            .filter(|(i, _)| i % 3 != 0)
+            .map(|(_, packet)| packet.unwrap().serialize())
+            .collect();
+
+        packets.reverse();
+
+        let merge_result: MergeResult = Merge::new()
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .run_from_symbols(
+                config,
+                packets.iter().filter_map(|p| packet_from_bytes(p)),
+            )
+            .unwrap();
+
+        assert_eq!(merge_result.chunks_merged, blocks);
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        match Merge::new()
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .run_from_symbols(
+                config,
+                packets[..symbols / 2]
+                    .iter()
+                    .filter_map(|p| packet_from_bytes(p)),
+            ) {
+            | Err(MergeError::SymbolsInsufficient) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_round_trip() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("pipeline_round_trip");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("pipeline_round_trip")
+            .join("test.jpg");
+
+        let _ = fs::remove_dir_all(&cache_dir);
+
+        let remote_dir: PathBuf = cache_dir.join("remote");
+
+        let stages: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let handler_stages: Arc<Mutex<Vec<String>>> = stages.clone();
+
+        let uploaded: PipelineResult = Pipeline::new()
+            .split(
+                Split::new()
+                    .in_file(&asset_path)
+                    .out_dir(cache_dir.join("local"))
+                    .chunk_size(1024 * 1024),
+            )
+            .check(Check::new())
+            .upload(DirStore::new(&remote_dir, ChunkNaming::new()))
+            .cleanup()
+            .on_stage(move |_, stage| {
+                handler_stages.lock().unwrap().push(stage.as_str().to_string())
+            })
+            .run()
+            .unwrap();
+
+        let split_result: SplitResult = uploaded.split.unwrap();
+
+        assert_eq!(
+            *stages.lock().unwrap(),
+            ["split", "check", "upload", "cleanup"]
+        );
+        assert_eq!(uploaded.chunks_uploaded, split_result.total_chunks);
+        assert!(!cache_dir.join("local").exists());
+
+        let downloaded: PipelineResult = Pipeline::new()
+            .chunk_dir(cache_dir.join("download"))
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .download(DirStore::new(&remote_dir, ChunkNaming::new()))
+            .check(Check::new())
+            .merge(
+                Merge::new()
+                    .out_file(&output_path)
+                    .overwrite(OutFilePolicy::Overwrite),
+            )
+            .compare(Compare::new().chunk_size(split_result.chunk_size))
+            .cleanup()
+            .run()
+            .unwrap();
+
+        assert_eq!(downloaded.stages, 5);
+        assert!(downloaded.compare.unwrap().is_match());
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+
+        match Pipeline::new().check(Check::new()).run() {
+            | Err(PipelineError::Check(CheckError::InDirNotSet)) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_copy() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let output_dir: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("pipeline_copy");
+
+        let _ = fs::remove_dir_all(&output_dir);
+
+        fs::create_dir_all(&output_dir).unwrap();
+
+        let output_path: PathBuf = output_dir.join("test.jpg");
+        let chunk_dir: PathBuf = output_dir.join("test.jpg.chunks");
+
+        let result: PipelineResult =
+            Pipeline::copy(&asset_path, &output_path).run().unwrap();
+
+        assert_eq!(result.stages, 5);
+        assert!(result.compare.unwrap().is_match());
+        assert_eq!(
+            result.merge.unwrap().chunks_merged,
+            result.split.unwrap().total_chunks
+        );
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+        assert!(!chunk_dir.exists());
+
+        // the chunk directory is removed when a stage fails
+        match Pipeline::copy(&asset_path, &output_path).run() {
+            | Err(PipelineError::Merge(MergeError::OutFileExists)) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert!(!chunk_dir.exists());
+
+        // the chunk directory set is used instead
+        let result: PipelineResult =
+            Pipeline::copy(&asset_path, output_dir.join("copy.jpg"))
+                .chunk_dir(output_dir.join("chunks"))
+                .on_stage(|index, stage| {
+                    assert_eq!(
+                        stage.as_str(),
+                        ["split", "check", "merge", "compare", "cleanup"]
+                            [index]
+                    )
+                })
+                .run()
+                .unwrap();
+
+        assert_eq!(result.stages, 5);
+        assert!(output_dir.join("copy.jpg").is_file());
+        assert!(!output_dir.join("chunks").exists());
+        assert!(!output_dir.join("copy.jpg.chunks").exists());
+    }
+
+    #[tokio::test]
+    async fn test_upload_session() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let session_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("upload_session");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("upload_session")
+            .join("test.jpg");
+
+        let _ = fs::remove_dir_all(&session_dir);
+        let _ = fs::remove_file(&output_path);
+
+        let chunk_size: usize = 1024 * 1024;
+
+        let session: UploadSession =
+            UploadSession::new(&session_dir, asset.len() as u64, chunk_size)
+                .unwrap();
+
+        assert_eq!(session.total_chunks(), 8);
+
+        // the chunks are put concurrently, in any order, some of them twice
+        thread::scope(|scope| {
+            for worker in 0..4 {
+                let session: &UploadSession = &session;
+                let asset: &[u8] = &asset;
+
+                scope.spawn(move || {
+                    for index in (0..7).rev().filter(|i| i % 2 == worker % 2) {
+                        let start: usize = index * chunk_size;
+
+                        session
+                            .put_chunk(index, &asset[start..start + chunk_size])
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(session.missing().unwrap(), vec![7]);
+        assert!(!session.is_complete().unwrap());
+
+        assert_eq!(
+            session
+                .finalize(Merge::new().out_file(&output_path))
+                .unwrap_err()
+                .as_code(),
+            "missing_chunks"
+        );
+
+        assert_eq!(
+            session.put_chunk(7, &asset[7 * chunk_size..7 * chunk_size + 1]),
+            Err(UploadError::ChunkSizeMismatch)
+        );
+        assert_eq!(
+            session.put_chunk(8, &[]),
+            Err(UploadError::ChunkOutOfRange)
+        );
+
+        // a session is resumed from its journal after a restart
+        drop(session);
+
+        assert_eq!(
+            UploadSession::new(&session_dir, asset.len() as u64, 512 * 1024)
+                .unwrap_err(),
+            UploadError::SessionMismatch
+        );
+
+        let session: UploadSession = UploadSession::open(&session_dir).unwrap();
+
+        assert_eq!(session.file_size, asset.len() as u64);
+        assert_eq!(session.chunk_size, chunk_size);
+        assert_eq!(session.received().unwrap().count(), 7);
+
+        // a chunk lost since it was received is requested again
+        fs::remove_file(session.path(3)).unwrap();
+
+        assert_eq!(session.missing().unwrap(), vec![3, 7]);
+
+        for index in [3, 7] {
+            let start: usize = index * chunk_size;
+
+            session
+                .put_chunk(
+                    index,
+                    &asset[start..asset.len().min(start + chunk_size)],
+                )
+                .unwrap();
+        }
+
+        assert!(session.is_complete().unwrap());
+
+        let result: MergeResult =
+            session.finalize(Merge::new().out_file(&output_path)).unwrap();
+
+        assert_eq!(result.bytes_written, asset.len() as u64);
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        session.remove().unwrap();
+
+        assert!(!session_dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_gc_stale_chunk_dirs() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let cache_root: PathBuf =
+            root.join(".media").join("cache").join("std").join("gc");
+
+        let _ = fs::remove_dir_all(&cache_root);
+
+        fs::create_dir_all(&cache_root).unwrap();
+
+        let set_modified = |path: &Path, age: Duration| {
+            fs::File::open(path)
+                .unwrap()
+                .set_modified(SystemTime::now() - age)
+                .unwrap();
+        };
+
+        let hour: Duration = Duration::from_secs(60 * 60);
+
+        // recent chunk directory
+        let fresh_dir: PathBuf = cache_root.join("fresh");
+
+        fs::create_dir_all(&fresh_dir).unwrap();
+        fs::write(fresh_dir.join("0"), [0u8; 16]).unwrap();
+
+        // chunk directory untouched for two days
+        let stale_dir: PathBuf = cache_root.join("stale");
+
+        fs::create_dir_all(&stale_dir).unwrap();
+        fs::write(stale_dir.join("0"), [0u8; 32]).unwrap();
+        fs::write(stale_dir.join("1"), [0u8; 8]).unwrap();
+        set_modified(&stale_dir.join("0"), hour * 48);
+        set_modified(&stale_dir.join("1"), hour * 48);
+        set_modified(&stale_dir, hour * 48);
+
+        // upload session untouched for two hours
+        let session_dir: PathBuf = cache_root.join("session");
+
+        let session: UploadSession =
+            UploadSession::new(&session_dir, 16, 8).unwrap();
+
+        session.put_chunk(0, &[0u8; 8]).unwrap();
+        set_modified(&session.path(0), hour * 2);
+        set_modified(&session.journal_path(), hour * 2);
+        set_modified(&session_dir, hour * 2);
+
+        // loose files are kept
+        fs::write(cache_root.join("loose"), [0u8; 4]).unwrap();
+        set_modified(&cache_root.join("loose"), hour * 48);
+
+        let gc: Gc =
+            Gc::new().root(&cache_root).ttl(hour * 24).session_ttl(hour);
+
+        let report: GcReport = gc.clone().dry_run(true).run().unwrap();
+
+        assert!(report.dry_run);
+        assert_eq!(report.dirs_scanned, 3);
+        assert_eq!(
+            report
+                .collected
+                .iter()
+                .map(|e| (e.path.clone(), e.reason))
+                .collect::<Vec<_>>(),
+            vec![
+                (session_dir.clone(), GcReason::SessionAbandoned),
+                (stale_dir.clone(), GcReason::Expired),
+            ],
+        );
+        assert_eq!(report.collected[1].size, 40);
+        assert!(report.bytes_reclaimed > 40);
+        assert!(stale_dir.exists() && session_dir.exists());
+
+        let report: GcReport = gc.run().unwrap();
+
+        assert!(!report.dry_run);
+        assert_eq!(report.collected.len(), 2);
+        assert!(!stale_dir.exists());
+        assert!(!session_dir.exists());
+        assert!(fresh_dir.exists());
+        assert!(cache_root.join("loose").exists());
+
+        // nothing left to collect
+        assert!(gc.run().unwrap().collected.is_empty());
+
+        assert_eq!(
+            Gc::new().root(cache_root.join("missing")).run().unwrap_err(),
+            GcError::RootNotFound,
+        );
+        assert_eq!(Gc::new().run().unwrap_err(), GcError::RootNotSet);
+    }
+
+    #[tokio::test]
+    async fn test_watch_merges_incoming_chunks() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("watch_merges_incoming_chunks");
+
+        let incoming_dir: PathBuf = output_path.with_file_name("incoming");
+
+        let _ = fs::remove_dir_all(&incoming_dir);
+
+        let merged: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+
+        let counter: Arc<AtomicUsize> = Arc::clone(&merged);
+
+        let watcher: thread::JoinHandle<Result<MergeResult, WatchError>> =
+            Watch::new()
+                .in_dir(&incoming_dir)
+                .check(
+                    Check::new()
+                        .file_size(split_result.file_size)
+                        .total_chunks(split_result.total_chunks),
+                )
+                .merge(Merge::new().out_file(&output_path))
+                // merged by the events, long before a rescan
+                .interval(Duration::from_secs(60))
+                .on_merged(move |result| {
+                    assert!(result.is_ok());
+
+                    counter.fetch_add(1, Ordering::SeqCst);
+                })
+                .spawn();
+
+        // the chunks arrive one by one in a directory created later
+        thread::sleep(Duration::from_millis(50));
+
+        fs::create_dir_all(&incoming_dir).unwrap();
+
+        for index in 0..split_result.total_chunks {
+            fs::copy(
+                cache_dir.join(index.to_string()),
+                incoming_dir.join(index.to_string()),
+            )
+            .unwrap();
+
+            thread::sleep(Duration::from_millis(20));
+
+            if index + 1 < split_result.total_chunks {
+                assert!(!output_path.exists());
+            }
+        }
+
+        let result: MergeResult = watcher.join().unwrap().unwrap();
+
+        assert_eq!(result.bytes_written, split_result.file_size);
+        assert_eq!(merged.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).unwrap()
+        );
+
+        // stop watching a directory never complete
+        let token: CancellationToken = CancellationToken::new();
+
+        let watcher: thread::JoinHandle<Result<MergeResult, WatchError>> =
+            Watch::new()
+                .in_dir(incoming_dir.with_file_name("never"))
+                .interval(Duration::from_millis(10))
+                .cancellation_token(token.clone())
+                .spawn();
+
+        token.cancel();
+
+        assert_eq!(watcher.join().unwrap().unwrap_err(), WatchError::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_layout_matches_split_chunks() {
+        let (_, _, _, split_result) = setup("layout_matches_split_chunks");
+
+        let file_size: u64 = split_result.file_size;
+        let chunk_size: usize = split_result.chunk_size;
+
+        assert_eq!(
+            chunk_count(file_size, chunk_size),
+            split_result.total_chunks
+        );
+
+        let mut offset: u64 = 0;
+
+        for chunk in split_result.chunks.iter() {
+            assert_eq!(
+                chunk_range(chunk.index, file_size, chunk_size),
+                Some((offset, chunk.size))
+            );
+            assert_eq!(
+                index_for_offset(offset, file_size, chunk_size),
+                Some(chunk.index)
+            );
+            assert_eq!(
+                index_for_offset(
+                    offset + chunk.size as u64 - 1,
+                    file_size,
+                    chunk_size
+                ),
+                Some(chunk.index)
+            );
+
+            offset += chunk.size as u64;
+        }
+
+        assert_eq!(
+            chunk_range(split_result.total_chunks, file_size, chunk_size),
+            None
+        );
+        assert_eq!(index_for_offset(file_size, file_size, chunk_size), None);
+        assert_eq!(chunk_count(0, chunk_size), 0);
+    }
+
+    #[tokio::test]
+    async fn test_merge_detects_foreign_naming() {
+        let foreign_names: [fn(usize) -> String; 3] = [
+            |i| {
+                format!(
+                    "x{}{}",
+                    (b'a' + (i / 26) as u8) as char,
+                    (b'a' + (i % 26) as u8) as char
+                )
+            },
+            |i| format!("test.jpg.{:03}", i + 1),
+            |i| format!("test.jpg.part{}", i + 1),
+        ];
+
+        for (n, foreign_name) in foreign_names.iter().enumerate() {
+            let (root, cache_dir, output_path, split_result) =
+                setup(&format!("merge_detects_foreign_naming_{n}"));
+
+            for i in 0..split_result.total_chunks {
+                fs::rename(
+                    cache_dir.join(i.to_string()),
+                    cache_dir.join(foreign_name(i)),
+                )
+                .unwrap();
+            }
+
+            Merge::new()
+                .in_dir(&cache_dir)
+                .out_file(&output_path)
+                .detect_naming(true)
+                .run()
+                .unwrap();
+
+            assert_eq!(
+                fs::read(&output_path).unwrap(),
+                fs::read(root.join("assets").join("test.jpg")).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_foreign_naming_gnu_split_widened_suffix() {
+        let letters = || b'a'..=b'z';
+
+        // `xaa` to `xyz`, then `xzaaa` to `xzyzz`, as named by `split`
+        let mut names: Vec<String> = Vec::new();
+
+        for a in b'a'..=b'y' {
+            for b in letters() {
+                names.push(format!("x{}{}", a as char, b as char));
+            }
+        }
+
+        for a in b'a'..=b'y' {
+            for b in letters() {
+                for c in letters() {
+                    names.push(format!(
+                        "xz{}{}{}",
+                        a as char, b as char, c as char
+                    ));
+                }
+            }
+        }
+
+        let naming: ForeignNaming = ForeignNaming::detect(&names).unwrap();
+
+        assert_eq!(
+            naming,
+            ForeignNaming::GnuSplit { prefix: "x".into(), suffix_width: 2 }
+        );
+
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(naming.parse(name), Some(i), "{name}");
+        }
+
+        assert_eq!(naming.parse("xyz"), Some(649));
+        assert_eq!(naming.parse("xzaaa"), Some(650));
+        assert_eq!(naming.parse("xzzaaaa"), Some(17550));
+
+        // a fixed suffix width runs to `zz`
+        assert_eq!(naming.parse("xzz"), Some(675));
+        assert_eq!(naming.parse("xzaa"), None);
+        assert_eq!(naming.parse("xzzaaa"), None);
+    }
+
+    #[tokio::test]
+    async fn test_compare_reports_changed_chunks() {
+        let (root, cache_dir, _, split_result) =
+            setup("compare_reports_changed_chunks");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let extra_chunk: PathBuf =
+            cache_dir.join(split_result.total_chunks.to_string());
+
+        // left from the previous run
+        let _ = fs::remove_file(&extra_chunk);
+
+        let compare: Compare = Compare::new()
+            .in_file(&asset_path)
+            .in_dir(&cache_dir)
+            .chunk_size(1024 * 1024);
+
+        assert!(compare.run().unwrap().is_match());
+
+        // corrupt, remove and add chunks
+        fs::write(cache_dir.join("0"), b"corrupted").unwrap();
+        fs::remove_file(cache_dir.join("1")).unwrap();
+        fs::write(&extra_chunk, b"").unwrap();
+
+        assert_eq!(
+            compare.run().unwrap(),
+            CompareResult {
+                file_size: split_result.file_size,
+                total_chunks: split_result.total_chunks,
+                mismatched: vec![0],
+                missing: vec![1],
+                extra: vec![split_result.total_chunks],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_with_opened_files() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_and_merge_with_opened_files");
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("std")
+            .join("split_and_merge_with_opened_files")
+            .join("test.jpg");
+
+        let split_result: SplitResult = Split::new()
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .run_from_file(fs::File::open(&asset_path).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            split_result.file_size as u64,
+            fs::metadata(&asset_path).unwrap().len()
+        );
+
+        fs::create_dir_all(output_path.parent().unwrap()).unwrap();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .run_to_file(fs::File::create(&output_path).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_with_shared_paths() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: Arc<Path> =
+            Arc::from(root.join("assets").join("test.jpg"));
+        let cache_dir: Arc<Path> = Arc::from(
+            root.join(".media")
+                .join("cache")
+                .join("std")
+                .join("split_and_merge_with_shared_paths"),
+        );
+        let output_path: Arc<Path> = Arc::from(
+            root.join(".media")
+                .join("output")
+                .join("std")
+                .join("split_and_merge_with_shared_paths")
+                .join("test.jpg"),
+        );
+
+        let split: Split = Split::new()
+            .in_file_shared(asset_path.clone())
+            .out_dir_shared(cache_dir.clone())
+            .chunk_size(1024 * 1024);
+
+        // the cloned process shares the same paths
+        assert!(Arc::ptr_eq(
+            split.clone().in_file.as_ref().unwrap(),
+            &asset_path
+        ));
+
+        let split_result: SplitResult = split.run().unwrap();
+
+        Check::new()
+            .in_dir_shared(cache_dir.clone())
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .run()
+            .unwrap();
+
+        Merge::new()
+            .in_dir_shared(cache_dir)
+            .out_file_shared(output_path.clone())
+            .overwrite(OutFilePolicy::Overwrite)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).unwrap(),
+            fs::read(&asset_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_to_multipart_bodies() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let chunk_size: usize = 1024 * 1024;
+
+        let bodies: Vec<MultipartBody> = Split::new()
+            .chunk_size(chunk_size)
+            .chunk_extension("bin")
+            .multipart(fs::File::open(&asset_path).unwrap())
+            .collect::<Result<Vec<MultipartBody>, SplitError>>()
+            .unwrap();
+
+        let asset: Vec<u8> = fs::read(&asset_path).unwrap();
+
+        assert_eq!(bodies.len(), asset.len().div_ceil(chunk_size));
+
+        let mut merged: Vec<u8> = Vec::with_capacity(asset.len());
+
+        for (i, body) in bodies.iter().enumerate() {
+            let trailer: String = format!("\r\n--{}--\r\n", body.boundary);
+            let end: usize = body.body.len() - trailer.len();
+            let data: &[u8] = &body.body[end - body.size..end];
+
+            assert_eq!(body.index, i);
+            assert!(body.body.ends_with(trailer.as_bytes()));
+            assert_eq!(body.hash, HashAlgorithm::Sha256.digest(data));
+            assert!(
+                String::from_utf8_lossy(&body.body[..end - body.size])
+                    .contains(&format!("filename=\"{i}.bin\""))
+            );
+
+            merged.extend_from_slice(data);
+        }
+
+        assert_eq!(merged, asset);
+    }
+
+    #[tokio::test]
+    async fn test_split_to_compressed_multipart_bodies() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let codec: Codec = Codec::Zstd { level: 3 };
+
+        let bodies: Vec<MultipartBody> = Split::new()
+            .chunk_size(1024 * 1024)
+            .compression(codec)
+            .multipart(fs::File::open(&asset_path).unwrap())
+            .collect::<Result<Vec<MultipartBody>, SplitError>>()
+            .unwrap();
+
+        let mut merged: Vec<u8> = Vec::new();
+
+        for body in bodies.iter() {
+            let trailer: String = format!("\r\n--{}--\r\n", body.boundary);
+            let end: usize = body.body.len() - trailer.len();
+            let stored: &[u8] = &body.body[end - body.stored_size..end];
+            let data: Vec<u8> = codec.decompress(stored).unwrap();
+
+            assert_eq!(data.len(), body.size);
+            assert_eq!(body.hash, HashAlgorithm::Sha256.digest(stored));
+            assert_eq!(body.plain_hash, HashAlgorithm::Sha256.digest(&data));
+
+            let fields: String =
+                String::from_utf8_lossy(&body.body[..end - body.stored_size])
+                    .into_owned();
+
+            assert!(fields.contains(&body.plain_hash.to_hex()));
+            assert!(fields.contains("\r\n\r\nzstd\r\n"));
+
+            merged.extend_from_slice(&data);
+        }
+
+        assert_eq!(merged, fs::read(&asset_path).unwrap());
+    }
+
+    #[test]
+    fn test_split_to_nextcloud_requests() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_to_nextcloud_requests");
+
+        let upload: NextcloudUpload = NextcloudUpload::new(
+            "https://cloud.example.com/",
+            "alice",
+            "upload-1",
+            "/Photos/test image.jpg",
+        );
+
+        let split: Split = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .naming(NextcloudUpload::naming());
+
+        let split_result: SplitResult =
+            split.clone().chunk_size(NEXTCLOUD_CHUNK_SIZE_MIN).run().unwrap();
+
+        let requests: Vec<WebDavRequest> =
+            upload.requests(&split_result).unwrap();
+
+        let methods: Vec<&str> =
+            requests.iter().map(|r| r.method.as_str()).collect();
+
+        assert_eq!(methods, vec!["MKCOL", "PUT", "PUT", "MOVE"]);
+        assert_eq!(
+            requests[1].url,
+            "https://cloud.example.com/remote.php/dav/uploads/alice/upload-1/00001"
+        );
+        assert_eq!(requests[1].body, Some(cache_dir.join("00001")));
+        assert_eq!(
+            requests[3].headers,
+            vec![
+                (
+                    "Destination".to_string(),
+                    "https://cloud.example.com/remote.php/dav/files/alice/Photos/test%20image.jpg"
+                        .to_string()
+                ),
+                (
+                    "OC-Total-Length".to_string(),
+                    split_result.file_size.to_string()
+                ),
+            ]
+        );
+
+        let split_result: SplitResult =
+            split.chunk_size(1024 * 1024).run().unwrap();
+
+        assert_eq!(
+            upload.requests(&split_result),
+            Err(NextcloudError::ChunkTooSmall)
+        );
+    }
+
+    /// The method and the path of the requests received by the server.
+    type RequestLog = Arc<Mutex<Vec<String>>>;
+
+    /// Serve the WebDAV endpoints of a Nextcloud server in memory,
+    /// failing the `PUT` request of the chunk, logging the requests.
+    fn serve_nextcloud(
+        failed_chunk: Option<&'static str>
+    ) -> (String, RequestLog, Arc<Mutex<Vec<u8>>>) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let server: String =
+            format!("http://{}", listener.local_addr().unwrap());
+
+        let requests: RequestLog = Arc::new(Mutex::new(Vec::new()));
+
+        let content: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let log: RequestLog = Arc::clone(&requests);
+
+        let target: Arc<Mutex<Vec<u8>>> = Arc::clone(&content);
+
+        thread::spawn(move || {
+            let mut chunks: Vec<(String, Vec<u8>)> = Vec::new();
+
+            for stream in listener.incoming() {
+                let mut stream: TcpStream = stream.unwrap();
+
+                let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+
+                let mut request: String = String::new();
+                let mut length: usize = 0;
+                let mut authorized: bool = false;
+
+                loop {
+                    let mut line: String = String::new();
+
+                    reader.read_line(&mut line).unwrap();
+
+                    let lower: String = line.to_lowercase();
+
+                    if let Some(value) = lower.strip_prefix("content-length: ")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+
+                    if lower.starts_with("authorization: basic") {
+                        authorized = true;
+                    }
+
+                    if line.trim().is_empty() {
+                        break;
+                    }
+
+                    request.push_str(&line);
+                }
+
+                let mut body: Vec<u8> = vec![0; length];
+
+                reader.read_exact(&mut body).unwrap();
+
+                // the method and the path of the request line
+                let line: String =
+                    request.split(' ').take(2).collect::<Vec<&str>>().join(" ");
+
+                let name: String =
+                    line.rsplit('/').next().unwrap_or_default().to_string();
+
+                log.lock().unwrap().push(line.clone());
+
+                let response: &str = if !authorized {
+                    "401 Unauthorized"
+                } else if line.starts_with("MKCOL") {
+                    "201 Created"
+                } else if line.starts_with("PUT") {
+                    if failed_chunk == Some(name.as_str()) {
+                        "507 Insufficient Storage"
+                    } else {
+                        chunks.push((name, body));
+
+                        "201 Created"
+                    }
+                } else if line.starts_with("MOVE") {
+                    let mut content: MutexGuard<Vec<u8>> =
+                        target.lock().unwrap();
+
+                    chunks.sort();
+
+                    for (_, data) in chunks.drain(..) {
+                        content.extend_from_slice(&data);
+                    }
+
+                    "201 Created"
+                } else if line.starts_with("DELETE") {
+                    chunks.clear();
+
+                    "204 No Content"
+                } else {
+                    "405 Method Not Allowed"
+                };
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {response}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+        });
+
+        (server, requests, content)
+    }
+
+    #[test]
+    fn test_split_to_nextcloud_upload() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("std")
+            .join("split_to_nextcloud_upload");
+
+        let asset: Vec<u8> = fs::read(&asset_path).unwrap();
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(NEXTCLOUD_CHUNK_SIZE_MIN)
+            .naming(NextcloudUpload::naming())
+            .run()
+            .unwrap();
+
+        let (server, requests, content) = serve_nextcloud(None);
+
+        let result: NextcloudResult =
+            NextcloudUpload::new(&server, "alice", "upload-1", "test.jpg")
+                .header("Authorization", "Basic YWxpY2U6c2VjcmV0")
+                .upload_chunks(&split_result)
+                .unwrap();
+
+        assert_eq!(
+            result.url,
+            format!("{server}/remote.php/dav/files/alice/test.jpg")
+        );
+        assert_eq!(result.file_size, asset.len() as u64);
+        assert_eq!(result.total_chunks, 2);
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                "MKCOL /remote.php/dav/uploads/alice/upload-1",
+                "PUT /remote.php/dav/uploads/alice/upload-1/00001",
+                "PUT /remote.php/dav/uploads/alice/upload-1/00002",
+                "MOVE /remote.php/dav/uploads/alice/upload-1/.file",
+            ]
+        );
+        assert_eq!(*content.lock().unwrap(), asset);
+
+        // a chunk failed to upload, then the collection is deleted
+        let (server, requests, content) = serve_nextcloud(Some("00002"));
+
+        assert_eq!(
+            NextcloudUpload::new(&server, "alice", "upload-2", "test.jpg")
+                .header("Authorization", "Basic YWxpY2U6c2VjcmV0")
+                .upload_chunks(&split_result)
+                .unwrap_err(),
+            NextcloudError::ChunkNotUploaded
+        );
+        assert_eq!(
+            requests.lock().unwrap().last().unwrap(),
+            "DELETE /remote.php/dav/uploads/alice/upload-2"
+        );
+        assert!(content.lock().unwrap().is_empty());
+
+        // the collection is not created, nothing to delete
+        let (server, requests, _) = serve_nextcloud(None);
+
+        assert_eq!(
+            NextcloudUpload::new(&server, "alice", "upload-3", "test.jpg")
+                .upload_chunks(&split_result)
+                .unwrap_err(),
+            NextcloudError::UploadNotCreated
+        );
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_split_to_s3_multipart_upload() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let file_size: u64 = fs::metadata(&asset_path).unwrap().len();
+
+        // the part limits
+        assert_eq!(
+            S3MultipartUpload::validate(1024 * 1024, file_size).unwrap_err(),
+            S3Error::PartTooSmall
+        );
+        assert_eq!(
+            S3MultipartUpload::validate(
+                5 * 1024 * 1024,
+                10001 * 5 * 1024 * 1024
+            )
+            .unwrap_err(),
+            S3Error::TooManyParts
+        );
+        assert_eq!(S3MultipartUpload::part_size(file_size), 5 * 1024 * 1024);
+        assert_eq!(
+            S3MultipartUpload::part_size(100 * 1024 * 1024 * 1024),
+            11 * 1024 * 1024
+        );
+
+        let chunk_size: usize = S3MultipartUpload::part_size(file_size);
+
+        S3MultipartUpload::validate(chunk_size, file_size).unwrap();
+
+        let sent: Arc<Mutex<Vec<(usize, usize)>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let received: Arc<Mutex<Vec<(usize, usize)>>> = Arc::clone(&sent);
+
+        let mut upload: S3MultipartUpload =
+            S3MultipartUpload::new("upload-1", move |part| {
+                assert_eq!(part.upload_id, "upload-1");
+
+                received
+                    .lock()
+                    .unwrap()
+                    .push((part.part_number, part.data.len()));
+
+                Some(format!("\"etag-{}\"", part.part_number))
+            });
+
+        let result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .chunk_size(chunk_size)
+            .run_file_to_store(&mut upload)
+            .unwrap();
+
+        assert_eq!(result.total_chunks, 2);
+        assert_eq!(
+            *sent.lock().unwrap(),
+            vec![
+                (1, chunk_size),
+                (2, (file_size - chunk_size as u64) as usize)
+            ]
+        );
+
+        let parts: Vec<CompletedPart> = upload.complete().unwrap();
+
+        assert_eq!(upload.state(), UploadState::Completed);
+        assert_eq!(parts[1].etag, "\"etag-2\"");
+        assert_eq!(
+            S3MultipartUpload::complete_body(&parts),
+            "<CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>\"etag-1\"</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>\"etag-2\"</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+
+        // no part is sent once closed
+        assert_eq!(upload.complete().unwrap_err(), S3Error::UploadClosed);
+        assert_eq!(
+            upload.write(2, b"late").unwrap_err(),
+            StoreError::ChunkNotWritten
+        );
+
+        // parts too small to complete, then aborted
+        let mut upload: S3MultipartUpload =
+            S3MultipartUpload::new("upload-2", |_| Some("\"etag\"".into()));
+
+        Split::new()
+            .in_file(&asset_path)
+            .chunk_size(1024 * 1024)
+            .run_file_to_store(&mut upload)
+            .unwrap();
+
+        assert_eq!(upload.complete().unwrap_err(), S3Error::PartTooSmall);
+        assert_eq!(upload.abort(), "upload-2");
+        assert_eq!(upload.state(), UploadState::Aborted);
+
+        // a part failed to upload
+        let mut upload: S3MultipartUpload =
+            S3MultipartUpload::new("upload-3", |part| {
+                (part.part_number == 1).then(|| "\"etag\"".to_string())
+            });
+
+        match Split::new()
+            .in_file(&asset_path)
+            .chunk_size(chunk_size)
+            .run_file_to_store(&mut upload)
+        {
+            | Err(SplitError::OutFileNotWritten) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert_eq!(upload.parts().len(), 1);
+    }
+
+    /// Serve a tus 1.0 endpoint in memory, failing the second `PATCH`
+    /// request once, counting the bytes of the bodies appended.
+    fn serve_tus() -> (String, Arc<Mutex<Vec<u8>>>, Arc<AtomicUsize>) {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let endpoint: String =
+            format!("http://{}/files/", listener.local_addr().unwrap());
+
+        let content: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let received: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+
+        let upload: Arc<Mutex<Vec<u8>>> = Arc::clone(&content);
+
+        let counter: Arc<AtomicUsize> = Arc::clone(&received);
+
+        thread::spawn(move || {
+            let mut patches: usize = 0;
+
+            for stream in listener.incoming() {
+                let mut stream: TcpStream = stream.unwrap();
+
+                let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+
+                let mut request: String = String::new();
+                let mut length: usize = 0;
+                let mut offset: Option<usize> = None;
+
+                loop {
+                    let mut line: String = String::new();
+
+                    reader.read_line(&mut line).unwrap();
+
+                    let lower: String = line.to_lowercase();
+
+                    if let Some(value) = lower.strip_prefix("content-length: ")
+                    {
+                        length = value.trim().parse().unwrap();
+                    }
+
+                    if let Some(value) = lower.strip_prefix("upload-offset: ") {
+                        offset = Some(value.trim().parse().unwrap());
+                    }
+
+                    if line.trim().is_empty() {
+                        break;
+                    }
+
+                    request.push_str(&line);
+                }
+
+                let mut body: Vec<u8> = vec![0; length];
+
+                reader.read_exact(&mut body).unwrap();
+
+                let mut content: MutexGuard<Vec<u8>> = upload.lock().unwrap();
+
+                let response: String = if request.starts_with("POST") {
+                    "201 Created\r\nLocation: /files/1".to_string()
+                } else if !request.contains("/files/1 ") {
+                    "404 Not Found".to_string()
+                } else if request.starts_with("HEAD") {
+                    format!("200 OK\r\nUpload-Offset: {}", content.len())
+                } else if offset != Some(content.len()) {
+                    "409 Conflict".to_string()
+                } else {
+                    patches += 1;
+
+                    match patches {
+                        | 2 => "500 Internal Server Error".to_string(),
+                        | _ => {
+                            content.extend_from_slice(&body);
+
+                            counter.fetch_add(body.len(), Ordering::SeqCst);
+
+                            format!(
+                                "204 No Content\r\nUpload-Offset: {}",
+                                content.len()
+                            )
+                        },
+                    }
+                };
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {response}\r\nTus-Resumable: 1.0.0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )
+                .unwrap();
+            }
+        });
+
+        (endpoint, content, received)
+    }
+
+    #[test]
+    fn test_split_to_tus_upload() {
+        let (root, _, _, split_result) = setup("split_to_tus_upload");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let (endpoint, content, received) = serve_tus();
+
+        let mut upload: TusUpload = TusUpload::new(&endpoint)
+            .file_size(asset.len() as u64)
+            .metadata("filename", "test.jpg")
+            .retries(1)
+            .retry_delay(Duration::ZERO);
+
+        // the failed request is retried, then the upload stops
+        for chunk in &split_result.chunks[..3] {
+            upload.upload_chunk(&fs::read(&chunk.path).unwrap()).unwrap();
+        }
+
+        let upload_url: String = upload.upload_url.clone().unwrap();
+
+        assert_eq!(upload_url, format!("{endpoint}1"));
+        assert_eq!(content.lock().unwrap().len(), 3 * 1024 * 1024);
+
+        // resume after a restart, skipping the bytes on the server
+        let result: TusResult = TusUpload::new(&endpoint)
+            .upload_url(&upload_url)
+            .upload_chunks(&split_result)
+            .unwrap();
+
+        assert_eq!(result.upload_url, upload_url);
+        assert_eq!(result.file_size, asset.len() as u64);
+        assert_eq!(result.bytes_resumed, 3 * 1024 * 1024);
+        assert_eq!(received.load(Ordering::SeqCst), asset.len());
+        assert_eq!(*content.lock().unwrap(), asset);
+
+        assert_eq!(
+            TusUpload::new(&endpoint)
+                .upload_url(format!("{endpoint}2"))
+                .offset()
+                .unwrap_err(),
+            TusError::UploadNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_from_reader() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("split_from_reader");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let split: Split = Split::new()
+            .in_reader(io::Cursor::new(asset.clone()), None)
+            .out_dir(&chunks_dir)
+            .chunk_size(split_result.chunk_size);
+
+        let result: SplitResult = split.run().unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+
+        for chunk in result.chunks.iter() {
+            assert_eq!(
+                fs::read(&chunk.path).unwrap(),
+                fs::read(cache_dir.join(chunk.index.to_string())).unwrap()
+            );
+        }
+
+        // the reader is consumed by the first run
+        assert!(split.in_reader.as_ref().unwrap().is_consumed());
+        assert_eq!(split.run().unwrap_err(), SplitError::InReaderConsumed);
+
+        // the total number of chunks requires the size
+        let result: SplitResult = Split::new()
+            .in_reader(io::Cursor::new(asset.clone()), Some(asset.len() as u64))
+            .out_dir(&chunks_dir)
+            .total_chunks(4)
+            .overwrite(OverwritePolicy::Clean)
+            .run()
+            .unwrap();
+
+        assert_eq!(result.total_chunks, 4);
+        assert_eq!(result.file_size, asset.len() as u64);
+
+        assert_eq!(
+            Split::new()
+                .in_reader(io::Cursor::new(asset.clone()), None)
+                .out_dir(&chunks_dir)
+                .total_chunks(4)
+                .run()
+                .unwrap_err(),
+            SplitError::InFileSizeUnknown
+        );
+
+        // the chunks already written are skipped in the stream
+        fs::remove_file(chunks_dir.join("3")).unwrap();
+
+        let result: SplitResult = Split::new()
+            .in_reader(io::Cursor::new(asset.clone()), Some(asset.len() as u64))
+            .out_dir(&chunks_dir)
+            .total_chunks(4)
+            .resume(true)
+            .run()
+            .unwrap();
+
+        assert_eq!(result.chunks_resumed, 3);
+
+        Merge::new().in_dir(&chunks_dir).out_file(&output_path).run().unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+    }
+
+    #[tokio::test]
+    async fn test_merge_to_writer() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_to_writer");
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+
+        let result: MergeResult =
+            Merge::new().in_dir(&cache_dir).run_to_writer(&mut buffer).unwrap();
+
+        assert_eq!(buffer, asset);
+        assert_eq!(result.bytes_written, asset.len() as u64);
+        assert_eq!(result.chunks_merged, split_result.total_chunks);
+        assert_eq!(result.out_file, None);
+
+        // the range of the process is written only
+        buffer.clear();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .byte_range(1000..1024 * 1024 + 1000)
+            .run_to_writer(&mut buffer)
+            .unwrap();
+
+        assert_eq!(buffer, asset[1000..1024 * 1024 + 1000]);
+
+        // the writer set to the process is used instead of the output file
+        fs::create_dir_all(output_path.parent().unwrap()).unwrap();
+
+        let writer_path: PathBuf = output_path.with_file_name("writer.jpg");
+
+        let merge: Merge = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .out_writer(fs::File::create(&writer_path).unwrap());
+
+        merge.run().unwrap();
+
+        assert!(!output_path.exists());
+        assert_eq!(fs::read(&writer_path).unwrap(), asset);
+
+        // a missing chunk fails without finishing the output
+        fs::remove_file(cache_dir.join("1")).unwrap();
+
+        assert_eq!(
+            Merge::new()
+                .in_dir(&cache_dir)
+                .expected_chunks(
+                    split_result.total_chunks,
+                    split_result.file_size
+                )
+                .run_to_writer(io::sink())
+                .unwrap_err(),
+            MergeError::ChunksIncomplete
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_sink() {
+        let (root, cache_dir, output_path, split_result) = setup("split_sink");
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let asset: Vec<u8> = fs::read(&asset_path).unwrap();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let mut sink: SplitSink = Split::new()
+            .out_dir(&chunks_dir)
+            .chunk_size(split_result.chunk_size)
+            .overwrite(OverwritePolicy::Clean)
+            .sink()
+            .unwrap();
+
+        // written in parts not aligned to the chunks
+        for part in asset.chunks(100_000) {
+            sink.write_all(part).unwrap();
+        }
+
+        assert_eq!(sink.bytes_written(), asset.len() as u64);
+        assert_eq!(sink.chunks().len(), asset.len() / split_result.chunk_size);
+
+        let result: SplitResult = sink.finish().unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+
+        for chunk in result.chunks.iter() {
+            assert_eq!(
+                fs::read(&chunk.path).unwrap(),
+                fs::read(cache_dir.join(chunk.index.to_string())).unwrap()
+            );
+        }
+
+        // the same boundaries as the file with content-defined chunks
+        let strategy: ChunkStrategy = ChunkStrategy::ContentDefined {
+            min: 64 * 1024,
+            avg: 256 * 1024,
+            max: 1024 * 1024,
+        };
+
+        let file_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(output_path.with_file_name("chunks_file"))
+            .strategy(strategy)
+            .run()
+            .unwrap();
+
+        let mut sink: SplitSink = Split::new()
+            .out_dir(&chunks_dir)
+            .strategy(strategy)
+            .format(ChunkFormat::Header)
+            .overwrite(OverwritePolicy::Clean)
+            .sink()
+            .unwrap();
+
+        io::copy(&mut fs::File::open(&asset_path).unwrap(), &mut sink).unwrap();
+
+        let result: SplitResult = sink.finish().unwrap();
+
+        assert_eq!(
+            result.chunks.iter().map(|c| c.size).collect::<Vec<usize>>(),
+            file_result.chunks.iter().map(|c| c.size).collect::<Vec<usize>>()
+        );
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .format(ChunkFormat::Header)
+            .run()
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).unwrap(), asset);
+
+        // the writes fail once cancelled
+        let token: CancellationToken = CancellationToken::new();
+
+        let mut sink: SplitSink = Split::new()
+            .out_dir(&chunks_dir)
+            .chunk_size(1024)
+            .overwrite(OverwritePolicy::Clean)
+            .cancellation_token(token.clone())
+            .sink()
+            .unwrap();
+
+        sink.write_all(&asset[..4096]).unwrap();
+
+        token.cancel();
+
+        assert!(sink.write_all(&asset[4096..8192]).is_err());
+        assert_eq!(sink.finish().unwrap_err(), SplitError::Cancelled);
+        assert_eq!(fs::read_dir(&chunks_dir).unwrap().count(), 0);
+
+        assert_eq!(Split::new().sink().unwrap_err(), SplitError::OutDirNotSet);
+    }
+
+    #[tokio::test]
+    async fn test_merge_cancelled() {
+        let (_, cache_dir, output_path, _) = setup("merge_cancelled");
+
+        let token: CancellationToken = CancellationToken::new();
+
+        token.cancel();
+
+        let result: Result<MergeResult, MergeError> = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .cancellation_token(token)
+            .run();
+
+        assert_eq!(result.unwrap_err(), MergeError::Cancelled);
+
+        assert!(
+            !output_path.exists(),
+            "Cancelled merge should remove the output file."
         );
     }
 
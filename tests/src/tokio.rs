@@ -1,13 +1,40 @@
 #[cfg(test)]
 mod tests {
-    use std::{env, path::PathBuf};
+    use std::{
+        env,
+        io::{SeekFrom, Write as _},
+        path::PathBuf,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::Instant,
+    };
 
-    use tokio::fs::{self, ReadDir};
+    use tokio::{
+        fs::{self, ReadDir},
+        io::{self, AsyncReadExt as _, AsyncSeekExt as _, AsyncWriteExt as _},
+    };
 
     use filerune_fusion::{
-        check::{Check, CheckError, tokio::CheckAsyncExt as _},
-        merge::{Merge, tokio::MergeAsyncExt as _},
-        split::{Split, SplitResult, tokio::SplitAsyncExt as _},
+        cancel::CancellationToken,
+        check::{Check, CheckError, CheckReport, tokio::CheckAsyncExt as _},
+        codec::Codec,
+        compare::{Compare, CompareResult, tokio::CompareAsyncExt as _},
+        hash::HashAlgorithm,
+        header::ChunkFormat,
+        merge::{
+            Merge, MergeError, MergeResult, OutFilePolicy,
+            tokio::MergeAsyncExt as _,
+        },
+        reader::tokio::AsyncChunksReader,
+        sink::tokio::AsyncSplitSink,
+        split::{
+            OverwritePolicy, SourceChangePolicy, Split, SplitError,
+            SplitResult, tokio::SplitAsyncExt as _,
+        },
+        strategy::ChunkStrategy,
+        sync::SyncPolicy,
     };
 
     async fn setup(
@@ -30,16 +57,13 @@ mod tests {
             .await
             .unwrap();
 
-        (
-            root.clone(),
-            cache_dir,
-            root.join(".media")
-                .join("output")
-                .join("tokio")
-                .join(cache_name)
-                .join(file_name),
-            split_result,
-        )
+        let output_dir: PathBuf =
+            root.join(".media").join("output").join("tokio").join(cache_name);
+
+        // remove the outputs of a previous run
+        let _ = fs::remove_dir_all(&output_dir).await;
+
+        (root.clone(), cache_dir, output_dir.join(file_name), split_result)
     }
 
     #[tokio::test]
@@ -57,6 +81,394 @@ mod tests {
         assert!(chunk_count > 0, "No chunks were created.");
     }
 
+    #[tokio::test]
+    async fn test_split_from_reader() {
+        let root: PathBuf = env::current_dir().unwrap();
+        let chunk_size: usize = 1024 * 1024;
+
+        let data: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap();
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("tokio")
+            .join("split_from_reader");
+
+        let split_result: SplitResult = Split::new()
+            .out_dir(&cache_dir)
+            .chunk_size(chunk_size)
+            .run_async_from_reader(data.as_slice())
+            .await
+            .unwrap();
+
+        assert_eq!(split_result.file_size, data.len() as u64);
+        assert_eq!(split_result.total_chunks, data.len().div_ceil(chunk_size));
+        assert_eq!(split_result.chunks.len(), split_result.total_chunks);
+
+        for (i, chunk) in split_result.chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i);
+            assert_eq!(chunk.path, cache_dir.join(i.to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_from_reader_by_total_chunks() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let result: Result<SplitResult, SplitError> = Split::new()
+            .out_dir(
+                root.join(".media")
+                    .join("cache")
+                    .join("tokio")
+                    .join("split_from_reader_by_total_chunks"),
+            )
+            .total_chunks(4)
+            .run_async_from_reader(&b"data"[..])
+            .await;
+
+        assert_eq!(result.unwrap_err(), SplitError::InFileSizeUnknown);
+    }
+
+    #[tokio::test]
+    async fn test_split_resumes_written_chunks() {
+        let (root, cache_dir, _, split_result) =
+            setup("split_resumes_written_chunks").await;
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        // a missing chunk and a short chunk
+        fs::remove_file(cache_dir.join("1")).await.unwrap();
+
+        fs::write(cache_dir.join("7"), b"short").await.unwrap();
+
+        let result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .resume(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(result.chunks_resumed, split_result.total_chunks - 2);
+        assert_eq!(result.chunks, split_result.chunks);
+
+        let compare_result: CompareResult = Compare::new()
+            .in_file(&asset_path)
+            .in_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert!(compare_result.is_match());
+    }
+
+    #[tokio::test]
+    async fn test_split_emits_first_chunk() {
+        let (root, _, output_path, _) = setup("split_emits_first_chunk").await;
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let first_chunks: Arc<Mutex<Vec<(usize, u64)>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_chunks: Arc<Mutex<Vec<(usize, u64)>>> =
+            first_chunks.clone();
+
+        let split_result: SplitResult = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .on_first_chunk(move |chunk| {
+                // written once emitted
+                let len: u64 = std::fs::metadata(&chunk.path).unwrap().len();
+
+                handler_chunks.lock().unwrap().push((chunk.index, len));
+            })
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *first_chunks.lock().unwrap(),
+            [(0, split_result.chunks[0].size as u64)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_source_changed_during_split() {
+        let (root, _, output_path, split_result) =
+            setup("split_source_changed_during_split").await;
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        fs::create_dir_all(output_path.parent().unwrap()).await.unwrap();
+        fs::copy(&asset_path, &output_path).await.unwrap();
+
+        // the input file grows once its first chunk is written
+        let appended: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+
+        let handler_appended: Arc<AtomicBool> = appended.clone();
+
+        let in_file: PathBuf = output_path.clone();
+
+        let split: Split = Split::new()
+            .in_file(&output_path)
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .on_first_chunk(move |_| {
+                if !handler_appended.swap(true, Ordering::Relaxed) {
+                    std::fs::OpenOptions::new()
+                        .append(true)
+                        .open(&in_file)
+                        .unwrap()
+                        .write_all(b"appended")
+                        .unwrap();
+                }
+            });
+
+        match split.run_async().await {
+            | Err(SplitError::SourceChangedDuringSplit) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        // split again once changed
+        fs::copy(&asset_path, &output_path).await.unwrap();
+
+        appended.store(false, Ordering::Relaxed);
+
+        let result: SplitResult = split
+            .source_change(SourceChangePolicy::Retry(1))
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size + 8);
+    }
+
+    #[tokio::test]
+    async fn test_split_with_aligned_chunks() {
+        let (root, _, output_path, split_result) =
+            setup("split_with_aligned_chunks").await;
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let aligned_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunks_dir)
+            .chunk_size(1000 * 1000)
+            .align(4096)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(aligned_result.chunk_size, 244 * 4096);
+        assert_eq!(aligned_result.file_size, split_result.file_size);
+
+        for chunk in &aligned_result.chunks[..aligned_result.total_chunks - 1] {
+            assert_eq!(chunk.size, 244 * 4096);
+        }
+
+        let total_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunks_dir)
+            .total_chunks(7)
+            .align(4096)
+            .overwrite(OverwritePolicy::Clean)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(total_result.total_chunks, 7);
+        assert_eq!(total_result.chunk_size % 4096, 0);
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(&asset_path).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_chunk_schedule() {
+        let (root, _, output_path, split_result) =
+            setup("split_check_and_merge_with_chunk_schedule").await;
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let schedule_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .chunk_schedule([1000, 500 * 1000])
+            .run_async()
+            .await
+            .unwrap();
+
+        let sizes: Vec<usize> =
+            schedule_result.chunks.iter().map(|c| c.size).collect();
+
+        assert_eq!(sizes[..3], [1000, 500 * 1000, 1024 * 1024]);
+        assert_eq!(sizes.len(), split_result.total_chunks + 2);
+        assert_eq!(sizes.iter().sum::<usize>() as u64, split_result.file_size);
+
+        Check::new()
+            .in_dir(&chunks_dir)
+            .file_size(schedule_result.file_size)
+            .total_chunks(schedule_result.total_chunks)
+            .chunk_sizes(sizes)
+            .run_async()
+            .await
+            .unwrap();
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(&asset_path).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_content_defined_chunks() {
+        let (root, _, output_path, _) =
+            setup("split_check_and_merge_with_content_defined_chunks").await;
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let strategy: ChunkStrategy = ChunkStrategy::ContentDefined {
+            min: 64 * 1024,
+            avg: 256 * 1024,
+            max: 1024 * 1024,
+        };
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunks_dir)
+            .strategy(strategy)
+            .run_async()
+            .await
+            .unwrap();
+
+        // the same boundaries as the sync process
+        let sync_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(output_path.with_file_name("chunks_sync"))
+            .strategy(strategy)
+            .run()
+            .unwrap();
+
+        let sizes: Vec<usize> =
+            split_result.chunks.iter().map(|c| c.size).collect();
+
+        assert_eq!(
+            sync_result.chunks.iter().map(|c| c.size).collect::<Vec<usize>>(),
+            sizes
+        );
+
+        Check::new()
+            .in_dir(&chunks_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .chunk_sizes(sizes)
+            .run_async()
+            .await
+            .unwrap();
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(&asset_path).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_overwrite_policy() {
+        let (root, cache_dir, _, split_result) =
+            setup("split_overwrite_policy").await;
+
+        let split: Split = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024);
+
+        match split.clone().overwrite(OverwritePolicy::Fail).run_async().await {
+            | Err(SplitError::OutDirNotEmpty) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+
+        fs::write(cache_dir.join("stray"), b"stray").await.unwrap();
+
+        let result: SplitResult =
+            split.overwrite(OverwritePolicy::Clean).run_async().await.unwrap();
+
+        assert!(!cache_dir.join("stray").exists());
+        assert_eq!(result.chunks, split_result.chunks);
+    }
+
+    #[tokio::test]
+    async fn test_split_removes_source() {
+        let (root, _, output_path, split_result) =
+            setup("split_removes_source").await;
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        fs::create_dir_all(output_path.parent().unwrap()).await.unwrap();
+        fs::copy(&asset_path, &output_path).await.unwrap();
+
+        let result: SplitResult = Split::new()
+            .in_file(&output_path)
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .remove_source(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert!(!output_path.exists());
+        assert_eq!(result.file_size, split_result.file_size);
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(&asset_path).await.unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn test_check_with_missing_chunks() {
         let (_, cache_dir, _, split_result) =
@@ -81,59 +493,1039 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_check_with_size_error() {
+    async fn test_check_with_concurrency() {
         let (_, cache_dir, _, split_result) =
-            setup("check_with_size_error").await;
+            setup("check_with_concurrency").await;
 
-        if let Err(error) = Check::new()
+        let result: Result<CheckReport, CheckError> = Check::new()
             .in_dir(&cache_dir)
-            .file_size(split_result.file_size + 1)
-            .total_chunks(split_result.total_chunks)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks + 3)
+            .concurrency(8)
             .run_async()
-            .await
-        {
-            match error {
-                | CheckError::SizeMismatch(_) => {
-                    return;
-                },
-                | err => panic!("Unexpected error: {:?}", err),
-            }
-        };
+            .await;
 
-        panic!("Check should fail due to size mismatch.");
+        match result {
+            | Err(CheckError::MissingChunks(err)) => {
+                let total_chunks: usize = split_result.total_chunks;
+
+                assert_eq!(
+                    err.missing,
+                    vec![total_chunks, total_chunks + 1, total_chunks + 2]
+                );
+            },
+            | result => panic!("Unexpected result: {:?}", result),
+        }
     }
 
     #[tokio::test]
-    async fn test_successful_check() {
-        let (_, cache_dir, _, split_result) = setup("successful_check").await;
+    async fn test_check_report_matches_whatever_concurrency() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_report_matches_whatever_concurrency").await;
 
-        Check::new()
+        let check: Check = Check::new()
             .in_dir(&cache_dir)
             .file_size(split_result.file_size)
             .total_chunks(split_result.total_chunks)
-            .run_async()
+            .hash_algorithm(HashAlgorithm::Sha256);
+
+        let report: CheckReport = check.run_async().await.unwrap();
+
+        for concurrency in [2, 3, 16] {
+            assert_eq!(
+                check.clone().concurrency(concurrency).run_async().await,
+                Ok(report.clone())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_report_lists_chunks() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_report_lists_chunks").await;
+
+        let report: CheckReport = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks + 1)
+            .concurrency(4)
+            .report_async()
             .await
             .unwrap();
+
+        assert_eq!(report.missing(), vec![split_result.total_chunks]);
+
+        for (i, chunk) in report.chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i);
+        }
     }
 
     #[tokio::test]
-    async fn test_merge_creates_output_file() {
-        let (_, cache_dir, output_path, _) =
-            setup("merge_creates_output_file").await;
+    async fn test_check_reports_aggregate_hash() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_reports_aggregate_hash").await;
 
-        Merge::new()
+        let process: Check = Check::new()
             .in_dir(&cache_dir)
-            .out_file(&output_path)
-            .run_async()
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks + 1)
+            .hash_algorithm(HashAlgorithm::Sha256);
+
+        let report: CheckReport =
+            process.clone().concurrency(4).report_async().await.unwrap();
+
+        assert!(report.aggregate_hash.is_some());
+        assert_eq!(report, process.report().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_check_with_chunk_size_error() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_with_chunk_size_error").await;
+
+        // truncate the last chunk
+        let last: PathBuf =
+            cache_dir.join((split_result.total_chunks - 1).to_string());
+
+        let content: Vec<u8> = fs::read(&last).await.unwrap();
+
+        fs::write(&last, &content[..content.len() - 1]).await.unwrap();
+
+        let report: CheckReport = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .chunk_sizes(split_result.chunks.iter().map(|c| c.size))
+            .concurrency(4)
+            .report_async()
             .await
             .unwrap();
 
-        assert!(
-            output_path.exists(),
+        assert_eq!(report.mismatched(), vec![split_result.total_chunks - 1]);
+        assert!(!report.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_compare_matches_chunks() {
+        let (root, cache_dir, _, split_result) =
+            setup("compare_matches_chunks").await;
+
+        let result: CompareResult = Compare::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .in_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert!(result.is_match());
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+    }
+
+    #[tokio::test]
+    async fn test_check_with_size_error() {
+        let (_, cache_dir, _, split_result) =
+            setup("check_with_size_error").await;
+
+        if let Err(error) = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size + 1)
+            .total_chunks(split_result.total_chunks)
+            .run_async()
+            .await
+        {
+            match error {
+                | CheckError::SizeMismatch(_) => {
+                    return;
+                },
+                | err => panic!("Unexpected error: {:?}", err),
+            }
+        };
+
+        panic!("Check should fail due to size mismatch.");
+    }
+
+    #[tokio::test]
+    async fn test_successful_check() {
+        let (_, cache_dir, _, split_result) = setup("successful_check").await;
+
+        Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .run_async()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_merge_creates_output_file() {
+        let (_, cache_dir, output_path, _) =
+            setup("merge_creates_output_file").await;
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert!(
+            output_path.exists(),
             "Output file should be created after merging."
         );
     }
 
+    #[tokio::test]
+    async fn test_merge_preallocates_output_file() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_preallocates_output_file").await;
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .preallocate(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, split_result.file_size);
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compression_round_trip() {
+        let (root, cache_dir, output_path, _) =
+            setup("compression_round_trip").await;
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let chunk_dir: PathBuf = cache_dir.join("zstd");
+
+        let codec: Codec = Codec::Zstd { level: 3 };
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunk_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .compression(codec)
+            .run_async()
+            .await
+            .unwrap();
+
+        let report: CheckReport = Check::new()
+            .in_dir(&chunk_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .chunk_sizes(split_result.chunks.iter().map(|c| c.size))
+            .compression(codec)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(report.actual_size, split_result.file_size);
+
+        // resumed from the chunks compressed
+        let resumed: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunk_dir)
+            .chunk_size(1024 * 1024)
+            .resume(true)
+            .compression(codec)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(resumed.chunks_resumed, split_result.total_chunks);
+
+        Merge::new()
+            .in_dir(&chunk_dir)
+            .out_file(&output_path)
+            .byte_range(1000..split_result.file_size - 1000)
+            .compression(codec)
+            .run_async()
+            .await
+            .unwrap();
+
+        let content: Vec<u8> = fs::read(&asset_path).await.unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            &content[1000..content.len() - 1000]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repair_from_parity_chunks() {
+        let (root, cache_dir, output_path, _) =
+            setup("repair_from_parity_chunks").await;
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let chunk_dir: PathBuf = cache_dir.join("parity");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&chunk_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .parity_chunks(1)
+            .run_async()
+            .await
+            .unwrap();
+
+        fs::remove_file(chunk_dir.join("0")).await.unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&chunk_dir)
+            .out_file(&output_path)
+            .repair(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks);
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(&asset_path).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_reports_watermarks() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_reports_watermarks").await;
+
+        let watermarks: Arc<Mutex<Vec<(u64, u64)>>> =
+            Arc::new(Mutex::new(Vec::new()));
+
+        let handler_watermarks: Arc<Mutex<Vec<(u64, u64)>>> =
+            watermarks.clone();
+
+        let handler_path: PathBuf = output_path.clone();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .progressive(true)
+            .on_watermark(move |watermark| {
+                // readable in place up to the watermark
+                let len: u64 = std::fs::metadata(&handler_path).unwrap().len();
+
+                handler_watermarks.lock().unwrap().push((watermark, len));
+            })
+            .run_async()
+            .await
+            .unwrap();
+
+        let expected: Vec<(u64, u64)> = split_result
+            .chunks
+            .iter()
+            .scan(0, |offset, chunk| {
+                *offset += chunk.size as u64;
+
+                Some((*offset, *offset))
+            })
+            .collect();
+
+        assert_eq!(*watermarks.lock().unwrap(), expected);
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_result_lists_chunks() {
+        let (_, cache_dir, output_path, split_result) =
+            setup("merge_result_lists_chunks").await;
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(merge_result.chunks.len(), split_result.total_chunks);
+
+        for (i, chunk) in merge_result.chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i);
+            assert_eq!(chunk.path, cache_dir.join(i.to_string()));
+        }
+
+        assert_eq!(
+            merge_result
+                .chunks
+                .iter()
+                .map(|chunk| chunk.size as u64)
+                .sum::<u64>(),
+            split_result.file_size
+        );
+
+        assert_eq!(merge_result.bytes_written, split_result.file_size);
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks);
+        assert_eq!(merge_result.out_file, Some(output_path));
+    }
+
+    #[tokio::test]
+    async fn test_merge_byte_range() {
+        let (root, cache_dir, output_path, _) = setup("merge_byte_range").await;
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap();
+
+        let range: std::ops::Range<usize> = 1024 * 1024 - 10..2 * 1024 * 1024;
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .byte_range(range.start as u64..range.end as u64)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(merge_result.chunks_merged, 2);
+        assert_eq!(merge_result.bytes_written, range.len() as u64);
+        assert_eq!(fs::read(&output_path).await.unwrap(), &asset[range]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_resumes_partial_output() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("merge_resumes_partial_output").await;
+
+        let chunk_size: usize = 1024 * 1024;
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .run_async()
+            .await
+            .unwrap();
+
+        // interrupted in the middle of the second chunk
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&output_path)
+            .await
+            .unwrap()
+            .set_len((chunk_size * 3 / 2) as u64)
+            .await
+            .unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .resume(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_resumed, chunk_size as u64);
+        assert_eq!(merge_result.chunks_merged, split_result.total_chunks - 1);
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_with_expected_hash() {
+        let (root, cache_dir, output_path, _) =
+            setup("merge_with_expected_hash").await;
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap();
+
+        let chunk_size: usize = 1024 * 1024;
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .run_async()
+            .await
+            .unwrap();
+
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&output_path)
+            .await
+            .unwrap()
+            .set_len((chunk_size * 3 / 2) as u64)
+            .await
+            .unwrap();
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .resume(true)
+            .expected_hash(
+                HashAlgorithm::Sha256,
+                HashAlgorithm::Sha256.digest(&asset).bytes,
+            )
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).await.unwrap(), asset);
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .expected_hash(HashAlgorithm::Sha256, vec![0; 32])
+            .run_async()
+            .await
+        {
+            | Err(MergeError::HashMismatch) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_with_expected_chunks() {
+        let (_, cache_dir, output_path, split_result) =
+            setup("merge_with_expected_chunks").await;
+
+        fs::remove_file(cache_dir.join("3")).await.unwrap();
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .expected_chunks(split_result.total_chunks, split_result.file_size)
+            .run_async()
+            .await
+        {
+            | Err(MergeError::ChunksIncomplete) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_with_bao() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let asset: Vec<u8> = fs::read(&asset_path).await.unwrap();
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("tokio")
+            .join("split_and_merge_with_bao");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("tokio")
+            .join("split_and_merge_with_bao")
+            .join("test.jpg");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .bao(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        let bao_hash: Vec<u8> = split_result.bao_hash.unwrap().bytes;
+
+        assert_eq!(bao_hash, HashAlgorithm::Blake3.digest(&asset).bytes);
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .bao(bao_hash.clone())
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).await.unwrap(), asset);
+
+        let mut corrupted: Vec<u8> =
+            fs::read(cache_dir.join("2")).await.unwrap();
+
+        corrupted[0] ^= 0xff;
+
+        fs::write(cache_dir.join("2"), &corrupted).await.unwrap();
+
+        match Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .bao(bao_hash)
+            .run_async()
+            .await
+        {
+            | Err(MergeError::HashMismatch) => {},
+            | result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_with_chunk_headers() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let asset_path: PathBuf = root.join("assets").join("test.jpg");
+
+        let asset: Vec<u8> = fs::read(&asset_path).await.unwrap();
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("tokio")
+            .join("split_check_and_merge_with_chunk_headers");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("tokio")
+            .join("split_check_and_merge_with_chunk_headers")
+            .join("test.jpg");
+
+        let split_result: SplitResult = Split::new()
+            .in_file(&asset_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .format(ChunkFormat::Header)
+            .run_async()
+            .await
+            .unwrap();
+
+        for i in 0..split_result.total_chunks {
+            fs::rename(
+                cache_dir.join(i.to_string()),
+                cache_dir.join(format!("{i}.renamed")),
+            )
+            .await
+            .unwrap();
+        }
+
+        let report: CheckReport = Check::new()
+            .in_dir(&cache_dir)
+            .format(ChunkFormat::Header)
+            .concurrency(4)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(report.file_size, asset.len() as u64);
+        assert_eq!(report.chunks.len(), split_result.total_chunks);
+
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .overwrite(OutFilePolicy::Overwrite)
+            .format(ChunkFormat::Header)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).await.unwrap(), asset);
+    }
+
+    #[tokio::test]
+    async fn test_split_check_and_merge_past_deadline() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("split_check_and_merge_past_deadline").await;
+
+        let deadline: Instant = Instant::now();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let split_error: SplitError = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .deadline(deadline)
+            .run_async()
+            .await
+            .unwrap_err();
+
+        assert_eq!(split_error, SplitError::DeadlineExceeded);
+
+        let check_error: CheckError = Check::new()
+            .in_dir(&cache_dir)
+            .file_size(split_result.file_size)
+            .total_chunks(split_result.total_chunks)
+            .deadline(deadline)
+            .run_async()
+            .await
+            .unwrap_err();
+
+        assert_eq!(check_error, CheckError::DeadlineExceeded);
+
+        let merge_error: MergeError = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .deadline(deadline)
+            .run_async()
+            .await
+            .unwrap_err();
+
+        assert_eq!(merge_error, MergeError::DeadlineExceeded);
+        assert!(!output_path.exists());
+
+        // resume the output kept on the deadline
+        Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .resume(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_atomically() {
+        let (root, _, output_path, split_result) =
+            setup("split_and_merge_atomically").await;
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .atomic(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        let mut entries: ReadDir = fs::read_dir(&chunks_dir).await.unwrap();
+        let mut count: usize = 0;
+
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            assert_ne!(entry.path().extension(), Some("tmp".as_ref()));
+
+            count += 1;
+        }
+
+        assert_eq!(count, split_result.total_chunks);
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert!(!output_path.with_file_name("test.jpg.tmp").exists());
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_and_merge_with_sync() {
+        let (root, _, output_path, split_result) =
+            setup("split_and_merge_with_sync").await;
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let result: SplitResult = Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&chunks_dir)
+            .chunk_size(1024 * 1024)
+            .sync(SyncPolicy::All)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .atomic(true)
+            .sync(SyncPolicy::Data)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_to_opened_file() {
+        let (root, cache_dir, output_path, _) =
+            setup("merge_to_opened_file").await;
+
+        fs::create_dir_all(output_path.parent().unwrap()).await.unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .run_async_to_file(fs::File::create(&output_path).await.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(merge_result.out_file, None);
+
+        assert_eq!(
+            fs::read(&output_path).await.unwrap(),
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_sparse_output() {
+        let root: PathBuf = env::current_dir().unwrap();
+
+        let input_path: PathBuf = root
+            .join(".media")
+            .join("input")
+            .join("tokio")
+            .join("merge_sparse_output.img");
+
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("tokio")
+            .join("merge_sparse_output");
+
+        let output_path: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("tokio")
+            .join("merge_sparse_output.img");
+
+        let _ = fs::remove_file(&output_path).await;
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap();
+
+        // an image with zeros around the data, and at the end
+        let mut data: Vec<u8> = vec![0; 1024 * 1024 + 10];
+
+        data.extend_from_slice(&asset[..1024 * 1024]);
+        data.extend(vec![0; 2 * 1024 * 1024]);
+
+        fs::create_dir_all(input_path.parent().unwrap()).await.unwrap();
+        fs::write(&input_path, &data).await.unwrap();
+
+        Split::new()
+            .in_file(&input_path)
+            .out_dir(&cache_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .run_async()
+            .await
+            .unwrap();
+
+        let merge_result: MergeResult = Merge::new()
+            .in_dir(&cache_dir)
+            .out_file(&output_path)
+            .sparse(true)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(merge_result.bytes_written, data.len() as u64);
+        assert_eq!(fs::read(&output_path).await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_chunks_reader() {
+        let (root, cache_dir, _, split_result) = setup("chunks_reader").await;
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap();
+
+        let mut reader: AsyncChunksReader =
+            AsyncChunksReader::open(&cache_dir).await.unwrap();
+
+        assert_eq!(reader.len(), asset.len() as u64);
+        assert_eq!(reader.total_chunks(), split_result.total_chunks);
+
+        let mut data: Vec<u8> = Vec::new();
+
+        reader.read_to_end(&mut data).await.unwrap();
+
+        assert_eq!(data, asset);
+
+        // the reads across the chunk boundaries
+        let chunk_size: u64 = split_result.chunk_size as u64;
+
+        for (from, len) in
+            [(0, 16), (chunk_size - 100, 200), (2 * chunk_size - 1, 2)]
+        {
+            reader.seek(SeekFrom::Start(from)).await.unwrap();
+
+            let mut range: Vec<u8> = Vec::new();
+
+            (&mut reader).take(len).read_to_end(&mut range).await.unwrap();
+
+            assert_eq!(range, asset[from as usize..(from + len) as usize]);
+        }
+
+        assert_eq!(
+            reader.seek(SeekFrom::End(-10)).await.unwrap(),
+            asset.len() as u64 - 10
+        );
+
+        data.clear();
+
+        reader.read_to_end(&mut data).await.unwrap();
+
+        assert_eq!(data, asset[asset.len() - 10..]);
+
+        assert!(
+            reader
+                .seek(SeekFrom::Current(-(asset.len() as i64) - 1))
+                .await
+                .is_err()
+        );
+
+        // the chunks with a header are decoded
+        let header_dir: PathBuf =
+            cache_dir.with_file_name("chunks_reader_header");
+
+        Split::new()
+            .in_file(root.join("assets").join("test.jpg"))
+            .out_dir(&header_dir)
+            .chunk_size(1024 * 1024)
+            .overwrite(OverwritePolicy::Clean)
+            .format(ChunkFormat::Header)
+            .run_async()
+            .await
+            .unwrap();
+
+        let mut reader: AsyncChunksReader = AsyncChunksReader::from_merge(
+            &Merge::new().in_dir(&header_dir).format(ChunkFormat::Header),
+        )
+        .await
+        .unwrap();
+
+        reader.seek(SeekFrom::Start(1024 * 1024 - 8)).await.unwrap();
+
+        let mut data: Vec<u8> = vec![0; 16];
+
+        reader.read_exact(&mut data).await.unwrap();
+
+        assert_eq!(data, asset[1024 * 1024 - 8..1024 * 1024 + 8]);
+
+        assert_eq!(
+            AsyncChunksReader::open(cache_dir.join("missing"))
+                .await
+                .unwrap_err(),
+            MergeError::InDirNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_sink() {
+        let (root, cache_dir, output_path, split_result) =
+            setup("split_sink").await;
+
+        let asset: Vec<u8> =
+            fs::read(root.join("assets").join("test.jpg")).await.unwrap();
+
+        let chunks_dir: PathBuf = output_path.with_file_name("chunks");
+
+        let mut sink: AsyncSplitSink = Split::new()
+            .out_dir(&chunks_dir)
+            .chunk_size(split_result.chunk_size)
+            .overwrite(OverwritePolicy::Clean)
+            .sink_async()
+            .await
+            .unwrap();
+
+        io::copy(&mut &asset[..], &mut sink).await.unwrap();
+
+        assert_eq!(sink.bytes_written(), asset.len() as u64);
+        assert_eq!(sink.chunks().len(), asset.len() / split_result.chunk_size);
+
+        let result: SplitResult = sink.finish().await.unwrap();
+
+        assert_eq!(result.file_size, split_result.file_size);
+        assert_eq!(result.total_chunks, split_result.total_chunks);
+
+        for chunk in result.chunks.iter() {
+            assert_eq!(
+                fs::read(&chunk.path).await.unwrap(),
+                fs::read(cache_dir.join(chunk.index.to_string()))
+                    .await
+                    .unwrap()
+            );
+        }
+
+        // the chunks with a header are merged back
+        let mut sink: AsyncSplitSink = Split::new()
+            .out_dir(&chunks_dir)
+            .chunk_size(512 * 1024)
+            .format(ChunkFormat::Header)
+            .overwrite(OverwritePolicy::Clean)
+            .sink_async()
+            .await
+            .unwrap();
+
+        for part in asset.chunks(100_000) {
+            sink.write_all(part).await.unwrap();
+        }
+
+        sink.finish().await.unwrap();
+
+        Merge::new()
+            .in_dir(&chunks_dir)
+            .out_file(&output_path)
+            .format(ChunkFormat::Header)
+            .run_async()
+            .await
+            .unwrap();
+
+        assert_eq!(fs::read(&output_path).await.unwrap(), asset);
+
+        // the writes fail once cancelled
+        let token: CancellationToken = CancellationToken::new();
+
+        let mut sink: AsyncSplitSink = Split::new()
+            .out_dir(&chunks_dir)
+            .chunk_size(1024)
+            .overwrite(OverwritePolicy::Clean)
+            .cancellation_token(token.clone())
+            .sink_async()
+            .await
+            .unwrap();
+
+        sink.write_all(&asset[..4096]).await.unwrap();
+        sink.flush().await.unwrap();
+
+        token.cancel();
+
+        assert!(sink.write_all(&asset[4096..8192]).await.is_err());
+        assert_eq!(sink.finish().await.unwrap_err(), SplitError::Cancelled);
+        assert_eq!(std::fs::read_dir(&chunks_dir).unwrap().count(), 0);
+
+        assert_eq!(
+            Split::new().sink_async().await.unwrap_err(),
+            SplitError::OutDirNotSet
+        );
+    }
+
     #[tokio::test]
     async fn test_merge_on_empty_cache_dir() {
         let root: PathBuf = env::current_dir().unwrap();
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use filerune_fusion::{
+        merge::{Merge, MergeResult, tokio_uring::MergeAsyncExt as _},
+        split::{Split, SplitResult, tokio_uring::SplitAsyncExt as _},
+    };
+
+    fn paths(cache_name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let root: PathBuf = env::current_dir().unwrap();
+        let file_name: &str = "test.jpg";
+
+        let asset_path: PathBuf = root.join("assets").join(file_name);
+        let cache_dir: PathBuf = root
+            .join(".media")
+            .join("cache")
+            .join("tokio_uring")
+            .join(cache_name);
+        let output_dir: PathBuf = root
+            .join(".media")
+            .join("output")
+            .join("tokio_uring")
+            .join(cache_name);
+
+        // remove the outputs of a previous run
+        let _ = fs::remove_dir_all(&output_dir);
+
+        (asset_path, cache_dir, output_dir.join(file_name))
+    }
+
+    #[test]
+    fn test_split_and_merge_file() {
+        let (asset_path, cache_dir, output_path) =
+            paths("split_and_merge_file");
+
+        tokio_uring::start(async {
+            let chunk_size: usize = 1024 * 1024;
+
+            let split_result: SplitResult = Split::new()
+                .in_file(&asset_path)
+                .out_dir(&cache_dir)
+                .chunk_size(chunk_size)
+                .run_async()
+                .await
+                .unwrap();
+
+            let file_size: u64 = fs::metadata(&asset_path).unwrap().len();
+
+            assert_eq!(split_result.file_size, file_size);
+            assert_eq!(
+                split_result.total_chunks,
+                file_size.div_ceil(chunk_size as u64) as usize
+            );
+
+            let merge_result: MergeResult = Merge::new()
+                .in_dir(&cache_dir)
+                .out_file(&output_path)
+                .run_async()
+                .await
+                .unwrap();
+
+            assert_eq!(merge_result.bytes_written, file_size);
+            assert_eq!(merge_result.chunks_merged, split_result.total_chunks);
+
+            assert_eq!(
+                fs::read(&asset_path).unwrap(),
+                fs::read(&output_path).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn test_split_and_merge_with_small_buffer() {
+        let (asset_path, cache_dir, output_path) =
+            paths("split_and_merge_with_small_buffer");
+
+        tokio_uring::start(async {
+            Split::new()
+                .in_file(&asset_path)
+                .out_dir(&cache_dir)
+                .chunk_size(1000 * 1000)
+                .run_async()
+                .await
+                .unwrap();
+
+            Merge::new()
+                .in_dir(&cache_dir)
+                .out_file(&output_path)
+                .buffer_capacity(4096)
+                .run_async()
+                .await
+                .unwrap();
+
+            assert_eq!(
+                fs::read(&asset_path).unwrap(),
+                fs::read(&output_path).unwrap()
+            );
+        });
+    }
+}